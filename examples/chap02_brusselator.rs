@@ -0,0 +1,29 @@
+extern crate failure;
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::prelude::*;
+
+const SPACE_GRID_SIZE: usize = 128;
+const DT: f32 = 0.01;
+const VISUALIZATION_STEP: usize = 10;
+
+fn main() -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new(
+        "Brusselator",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let model = Brusselator::default();
+    let mut fields = model.initial_fields((SPACE_GRID_SIZE, SPACE_GRID_SIZE), &mut rand::thread_rng());
+    loop {
+        for _ in 0..VISUALIZATION_STEP {
+            Integrator::SemiImplicitEuler.step(&model, &mut fields, DT);
+        }
+        // uを赤、vを緑で表示する
+        if matrix.render_channels(&[&fields[0], &fields[1]], &model.ranges())? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
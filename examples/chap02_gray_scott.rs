@@ -10,10 +10,16 @@ const K: f32 = 0.06;
 
 fn main() -> Result<(), impl Debug> {
     let (u, v) = initial_matrix();
-    let matrix = MatrixVisualizer::new(
+    let mut matrix = MatrixVisualizer::new(
         "Gray Scott",
         "res/shaders/matrix_visualizer_vertex.glsl",
         "res/shaders/matrix_visualizer_fragment.glsl",
-    );
-    matrix?.draw_loop((u, v), F, K, laplacian)
+    )?;
+    matrix.on_frame(|window, info| {
+        window.set_title(&format!(
+            "Gray Scott frame: {} (f={}, k={})",
+            info.frame, info.f, info.k
+        ));
+    });
+    matrix.draw_loop((u, v), F, K, laplacian)
 }
@@ -0,0 +1,30 @@
+extern crate failure;
+extern crate my_alife;
+
+use my_alife::algorithm::gray_scott::{initial_matrix, laplacian};
+use my_alife::sonification::AudioOutput;
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+
+// model parameter
+const F: f32 = 0.04;
+const K: f32 = 0.06;
+
+fn main() -> Result<(), failure::Error> {
+    let mut uv = initial_matrix();
+    let mut matrix = MatrixVisualizer::new(
+        "Gray Scott (audio)",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let audio = AudioOutput::new()?;
+    loop {
+        let v = laplacian(&mut uv, F, K);
+        // 中央の行のvの濃度を音の大きさにする
+        audio.sonifier().set_row(v.row(v.rows() / 2).iter().cloned());
+        if matrix.render_frame(v)? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,33 @@
+extern crate failure;
+extern crate my_alife;
+
+use my_alife::algorithm::backend::{gray_scott_stepper, update_gray_scott};
+use my_alife::algorithm::gray_scott::{initial_matrix, GrayScott};
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use std::env;
+
+// model parameter
+const F: f32 = 0.04;
+const K: f32 = 0.06;
+
+// 使える中で一番速い計算方法で計算する。引数(naive, threads, simd, gpu)で計算方法を指定できる
+fn main() -> Result<(), failure::Error> {
+    let preferred = match env::args().nth(1) {
+        Some(name) => Some(name.parse()?),
+        None => None,
+    };
+    let mut matrix = MatrixVisualizer::new(
+        "Gray Scott",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let stepper = gray_scott_stepper(matrix.display(), &initial_matrix(), GrayScott::new(F, K), preferred)?;
+    let backend = stepper.backend();
+    matrix.on_frame(move |window, info| {
+        window.set_title(&format!(
+            "Gray Scott ({}) frame: {} (f={}, k={})",
+            backend, info.frame, info.f, info.k
+        ));
+    });
+    matrix.try_draw_loop(stepper, F, K, |stepper, f, k| update_gray_scott(&mut **stepper, f, k))
+}
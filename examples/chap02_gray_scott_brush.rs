@@ -0,0 +1,87 @@
+extern crate failure;
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::gray_scott::{initial_matrix, GrayScott};
+use my_alife::algorithm::integrator::Integrator;
+use my_alife::visualizer::brush::{Brush, BrushSettings, Fields, Tool};
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::undo::UndoStack;
+use my_alife::visualizer::{ControlFlow, VirtualKeyCode};
+
+const STEPS_PER_FRAME: usize = 8;
+const UNDO_BUDGET: usize = 1 << 20;
+const PARAMETER_STEP: f32 = 0.001;
+
+// 1: Uを加える, 2: Vを加える, 3: 消す, 4: ノイズ, [/]: 半径, ,/.: 強さ
+// 上/下: f, 右/左: k, Z: 取り消し, Y: やり直し
+fn main() -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new(
+        "Gray Scott brush",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let mut model = GrayScott::new(0.04, 0.06);
+    let (u, v) = initial_matrix();
+    let mut fields = [u, v];
+    let mut brush = BrushSettings::new(vec![
+        ("add U", Tool::Add(0)),
+        ("add V", Tool::Add(1)),
+        ("erase", Tool::Erase),
+        ("noise", Tool::Noise),
+    ]);
+    brush.select(1);
+    let mut history = UndoStack::new(UNDO_BUDGET);
+    let mut rng = rand::thread_rng();
+    loop {
+        let keys = matrix.pressed_keys().to_vec();
+        if brush.handle_keys(&keys) {
+            println!("{}", brush.describe());
+        }
+        for key in keys {
+            let restored = match key {
+                VirtualKeyCode::Z => history.undo(&mut fields),
+                VirtualKeyCode::Y => history.redo(&mut fields),
+                _ => None,
+            };
+            for (name, value) in restored.unwrap_or_default() {
+                match name.as_str() {
+                    "f" => model.f = value,
+                    _ => model.k = value,
+                }
+            }
+            let (f, k) = match key {
+                VirtualKeyCode::Up => (model.f + PARAMETER_STEP, model.k),
+                VirtualKeyCode::Down => (model.f - PARAMETER_STEP, model.k),
+                VirtualKeyCode::Right => (model.f, model.k + PARAMETER_STEP),
+                VirtualKeyCode::Left => (model.f, model.k - PARAMETER_STEP),
+                _ => continue,
+            };
+            history.record_parameter("f", model.f, f);
+            history.record_parameter("k", model.k, k);
+            model.f = f;
+            model.k = k;
+            println!("f = {:.3}, k = {:.3}", f, k);
+        }
+        // 押してから離すまでを1回の操作として取り消せるようにする
+        match (matrix.mouse_cell(), matrix.mouse().left) {
+            (Some(cell), true) => {
+                history.begin();
+                let before = fields.clone();
+                Fields::new(&mut fields, &[1.0, 0.0]).stroke(&brush, cell, &mut rng);
+                history.record(brush.tool_name(), &before, &fields);
+            }
+            (_, false) => {
+                history.end(brush.tool_name());
+            }
+            _ => {}
+        }
+        for _ in 0..STEPS_PER_FRAME {
+            Integrator::Euler.step(&model, &mut fields, 1.0);
+        }
+        if matrix.render_frame(&fields[1])? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,51 @@
+extern crate failure;
+extern crate my_alife;
+
+use my_alife::algorithm::comparison::{Comparison, ComparisonView};
+use my_alife::algorithm::gray_scott::initial_matrix;
+use my_alife::prelude::*;
+
+// 少しだけ違う2つのパラメータ
+const A: (f32, f32) = (0.04, 0.06);
+const B: (f32, f32) = (0.041, 0.06);
+const VISUALIZATION_STEP: usize = 8;
+
+// 2つのGray-Scottを同じだけ進めて並べる。Vキーで差の表示に切り替える
+fn main() -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::builder("Gray Scott A/B").size(1200, 600).build()?;
+    let uv = initial_matrix();
+    let a = CpuGrayScott::auto(&uv, GrayScott::new(A.0, A.1))?;
+    let b = CpuGrayScott::auto(&uv, GrayScott::new(B.0, B.1))?;
+    let mut comparison = Comparison::new(a, b);
+    let mut view = ComparisonView::SideBySide;
+    loop {
+        comparison.step(VISUALIZATION_STEP)?;
+        matrix.set_title(&format!(
+            "Gray Scott A{:?} / B{:?} step: {} distance: {:.5}",
+            A,
+            B,
+            comparison.steps(),
+            comparison.distance()?
+        ));
+        let composed = comparison.compose(view, 1.0)?;
+        if matrix.render_frame(&composed)? == ControlFlow::Stop {
+            break;
+        }
+        if matrix.pressed_keys().contains(&VirtualKeyCode::V) {
+            view = view.toggled();
+            match view {
+                ComparisonView::SideBySide => {
+                    matrix.set_value_range(0.0, 1.0);
+                    matrix.set_transform(Transform::Linear);
+                    matrix.set_colormap(Colormap::Grayscale);
+                }
+                ComparisonView::Difference => {
+                    matrix.set_value_range(-0.5, 0.5);
+                    matrix.set_transform(Transform::Signed);
+                    matrix.set_colormap(Colormap::Diverging);
+                }
+            }
+        }
+    }
+    Ok(())
+}
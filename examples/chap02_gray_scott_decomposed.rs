@@ -0,0 +1,33 @@
+extern crate my_alife;
+
+use my_alife::algorithm::domain::Decomposition;
+use my_alife::algorithm::gray_scott::{decomposed_laplacian, initial_matrix};
+use my_alife::visualizer::matrix_visualizer::{Matrix, MatrixVisualizer};
+use std::fmt::Debug;
+use std::thread;
+
+// model parameter
+const F: f32 = 0.04;
+const K: f32 = 0.06;
+
+// 盤面をCPUの数だけの帯に分けて計算する
+fn update(uv: &mut (Matrix<f32>, Matrix<f32>), f: f32, k: f32) -> &Matrix<f32> {
+    let workers = thread::available_parallelism().map_or(1, |n| n.get());
+    decomposed_laplacian(uv, f, k, &Decomposition::new(workers, 1))
+}
+
+fn main() -> Result<(), impl Debug> {
+    let (u, v) = initial_matrix();
+    let mut matrix = MatrixVisualizer::new(
+        "Gray Scott (decomposed)",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    matrix.on_frame(|window, info| {
+        window.set_title(&format!(
+            "Gray Scott (decomposed) frame: {} (f={}, k={})",
+            info.frame, info.f, info.k
+        ));
+    });
+    matrix.draw_loop((u, v), F, K, update)
+}
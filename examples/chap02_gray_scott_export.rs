@@ -0,0 +1,52 @@
+extern crate failure;
+extern crate my_alife;
+
+use my_alife::algorithm::gray_scott::{initial_matrix, GrayScott};
+use my_alife::algorithm::integrator::Integrator;
+use my_alife::visualizer::export::FrameExporter;
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::texture::ValueMapping;
+use my_alife::visualizer::{ControlFlow, VirtualKeyCode};
+use std::env;
+
+const STEPS_PER_FRAME: u64 = 8;
+const FPS: u32 = 30;
+
+// 引数のディレクトリ(省略するとframes)に毎フレームをPNGで書き出す。E: 書き出しを止める/再開する
+fn main() -> Result<(), failure::Error> {
+    let directory = env::args().nth(1).unwrap_or_else(|| "frames".to_string());
+    let mut matrix = MatrixVisualizer::new(
+        "Gray Scott export",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let model = GrayScott::new(0.04, 0.06);
+    let mut exporter = FrameExporter::new(&directory, ValueMapping::default())?;
+    exporter.set_parameter("f", model.f);
+    exporter.set_parameter("k", model.k);
+    let (u, v) = initial_matrix();
+    let mut fields = [u, v];
+    let mut recording = true;
+    let mut step = 0;
+    loop {
+        for _ in 0..STEPS_PER_FRAME {
+            Integrator::Euler.step(&model, &mut fields, 1.0);
+        }
+        step += STEPS_PER_FRAME;
+        if matrix.pressed_keys().contains(&VirtualKeyCode::E) {
+            recording = !recording;
+        }
+        if recording {
+            exporter.push(&fields[1], step)?;
+        }
+        if matrix.render_frame(&fields[1])? == ControlFlow::Stop {
+            break;
+        }
+    }
+    let command = exporter.ffmpeg_command(FPS, "gray_scott.mp4");
+    let frames = exporter.len();
+    let manifest = exporter.finish()?;
+    println!("wrote {} frames and {}", frames, manifest.display());
+    println!("{}", command);
+    Ok(())
+}
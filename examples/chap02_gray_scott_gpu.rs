@@ -0,0 +1,20 @@
+extern crate my_alife;
+
+use my_alife::algorithm::gpu::GpuStepper;
+use my_alife::algorithm::gray_scott::{initial_matrix, GrayScott};
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use std::fmt::Debug;
+
+// model parameter
+const F: f32 = 0.04;
+const K: f32 = 0.06;
+
+fn main() -> Result<(), impl Debug> {
+    let matrix = MatrixVisualizer::new(
+        "Gray Scott (GPU)",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let stepper = GpuStepper::gray_scott(matrix.display(), &initial_matrix(), GrayScott::new(F, K))?;
+    matrix.try_draw_loop(stepper, F, K, |stepper, f, k| stepper.update(f, k))
+}
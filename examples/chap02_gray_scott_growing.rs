@@ -0,0 +1,42 @@
+extern crate failure;
+extern crate my_alife;
+
+use my_alife::algorithm::gray_scott::{initial_matrix, GrayScott};
+use my_alife::algorithm::growing_domain::{GrowingDomain, GrowthMode, GrowthRate};
+use my_alife::algorithm::integrator::Integrator;
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+
+const F: f32 = 0.035;
+const K: f32 = 0.065;
+// 領域の横の長さが1ステップに伸びる割合。約3500ステップで2倍になる
+const GROWTH_RATE: f32 = 2e-4;
+// 1フレームに進めるステップ数
+const STEPS_PER_FRAME: usize = 8;
+
+// 横に伸びていく領域の上で、斑点が分裂して数を増やしていく様子を描く
+fn main() -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new(
+        "Gray Scott on a growing domain",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let (u, v) = initial_matrix();
+    let mut domain = GrowingDomain::new(
+        GrayScott::new(F, K),
+        vec![u, v],
+        GrowthMode::Uniform,
+        GrowthRate::Exponential(GROWTH_RATE),
+    );
+    loop {
+        for _ in 0..STEPS_PER_FRAME {
+            domain.step(Integrator::Euler, 1.0);
+        }
+        let (rows, cols) = domain.dim();
+        matrix.set_title(&format!("Gray Scott on a growing domain ({}x{})", rows, cols));
+        if matrix.render_frame(&domain.fields()[1])? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,43 @@
+extern crate failure;
+extern crate my_alife;
+
+use my_alife::algorithm::gray_scott::{initial_matrix, GrayScott};
+use my_alife::algorithm::integrator::Integrator;
+use my_alife::http::{MjpegServer, StreamCommand};
+use my_alife::visualizer::export::FrameExporter;
+use my_alife::visualizer::texture::ValueMapping;
+use std::env;
+use std::thread;
+use std::time::Duration;
+
+const STEPS_PER_FRAME: u64 = 8;
+const FRAME_INTERVAL: Duration = Duration::from_millis(33);
+
+// ウィンドウを開かずに動かし、http://<host>:8080/ で見られるようにする(引数で待ち受けるアドレスを変えられる)
+// snapshotを押すとsnapshots/に盤面を保存する
+fn main() -> Result<(), failure::Error> {
+    let address = env::args().nth(1).unwrap_or_else(|| "0.0.0.0:8080".to_string());
+    let server = MjpegServer::bind(&address, ValueMapping::default())?;
+    println!("listening on http://{}/", server.local_addr());
+    let model = GrayScott::new(0.04, 0.06);
+    let (u, v) = initial_matrix();
+    let mut fields = [u, v];
+    let mut snapshots = FrameExporter::new("snapshots", ValueMapping::default())?;
+    let mut step = 0;
+    loop {
+        for command in server.poll_commands() {
+            if command == StreamCommand::Snapshot {
+                let path = snapshots.push(&fields[1], step)?;
+                println!("saved {}", path.display());
+            }
+        }
+        if !server.is_paused() {
+            for _ in 0..STEPS_PER_FRAME {
+                Integrator::Euler.step(&model, &mut fields, 1.0);
+            }
+            step += STEPS_PER_FRAME;
+            server.publish(&fields[1])?;
+        }
+        thread::sleep(FRAME_INTERVAL);
+    }
+}
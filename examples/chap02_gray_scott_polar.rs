@@ -0,0 +1,30 @@
+extern crate failure;
+extern crate my_alife;
+
+use my_alife::algorithm::gray_scott::{initial_matrix, laplacian};
+use my_alife::visualizer::polar_visualizer::PolarVisualizer;
+use my_alife::visualizer::{ControlFlow, Draw};
+
+const F: f32 = 0.04;
+const K: f32 = 0.06;
+// 1フレームで回す角度(rad)
+const ROTATION: f32 = 0.005;
+
+// Gray-Scottモデルをリング状の生息地に見立てて、ゆっくり回しながら表示する
+fn main() -> Result<(), failure::Error> {
+    let mut polar = PolarVisualizer::new("Gray Scott on a ring")?;
+    polar.set_inner_radius(0.35);
+    let mut uv = initial_matrix();
+    let mut frame = 0;
+    loop {
+        frame += 1;
+        polar.set_rotation(frame as f32 * ROTATION);
+        if let Some((row, col)) = polar.mouse_cell() {
+            polar.set_title(&format!("Gray Scott on a ring (r={}, θ={})", row, col));
+        }
+        if polar.render_frame(laplacian(&mut uv, F, K))? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,45 @@
+extern crate failure;
+extern crate my_alife;
+
+use my_alife::algorithm::gray_scott::{initial_matrix, laplacian};
+use my_alife::osc::{ControlEvent, OscReceiver};
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+
+// 外部のコントローラーから/alife/param/f, /alife/param/k, /alife/inject x y を受け付ける
+const OSC_ADDRESS: &str = "0.0.0.0:9000";
+const INJECT_RADIUS: usize = 5;
+
+fn main() -> Result<(), failure::Error> {
+    let (mut f, mut k) = (0.04, 0.06);
+    let mut uv = initial_matrix();
+    let mut matrix = MatrixVisualizer::new(
+        "Gray Scott (remote)",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let receiver = OscReceiver::bind(OSC_ADDRESS)?;
+    loop {
+        for event in receiver.poll_events() {
+            match event {
+                ControlEvent::SetParameter(ref name, value) if name == "f" => f = value,
+                ControlEvent::SetParameter(ref name, value) if name == "k" => k = value,
+                ControlEvent::Inject { x, y, value } => {
+                    let (rows, cols) = uv.1.dim();
+                    for row in y.saturating_sub(INJECT_RADIUS)..(y + INJECT_RADIUS).min(rows) {
+                        for col in x.saturating_sub(INJECT_RADIUS)..(x + INJECT_RADIUS).min(cols) {
+                            uv.0[[row, col]] = 0.5;
+                            uv.1[[row, col]] = 0.25 * value;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        let v = laplacian(&mut uv, f, k);
+        if matrix.render_frame(v)? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,115 @@
+extern crate failure;
+extern crate my_alife;
+
+use my_alife::algorithm::gray_scott::{initial_matrix_using, GrayScott};
+use my_alife::algorithm::integrator::Integrator;
+use my_alife::visualizer::brush::{Brush, BrushSettings, Fields, Tool};
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::session::{Player, Session};
+use my_alife::visualizer::{ControlFlow, VirtualKeyCode};
+use std::env;
+
+const STEPS_PER_FRAME: usize = 8;
+const PARAMETER_STEP: f32 = 0.001;
+const SEED: u64 = 2018;
+
+// record <file>: 操作を記録して閉じたときに保存する, replay <file>: 記録した操作を再生する
+// 1-4: 道具, [/]: 半径, ,/.: 強さ, 上/下: f, 右/左: k
+fn main() -> Result<(), failure::Error> {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 3 || (args[1] != "record" && args[1] != "replay") {
+        return Err(failure::err_msg(
+            "usage: chap02_gray_scott_session (record|replay) <file>",
+        ));
+    }
+    let path = &args[2];
+    let (mut session, mut player) = if args[1] == "record" {
+        (Session::new(SEED), None)
+    } else {
+        let session = Session::load(path)?;
+        (session.clone(), Some(Player::new(session)))
+    };
+    let mut matrix = MatrixVisualizer::new(
+        "Gray Scott session",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let mut rng = session.rng();
+    let mut model = GrayScott::new(0.04, 0.06);
+    let (u, v) = initial_matrix_using(&mut rng);
+    let mut fields = [u, v];
+    let mut brush = BrushSettings::new(vec![
+        ("add U", Tool::Add(0)),
+        ("add V", Tool::Add(1)),
+        ("erase", Tool::Erase),
+        ("noise", Tool::Noise),
+    ]);
+    let mut step = 0;
+    loop {
+        let interactions = match player {
+            Some(ref mut player) => player.poll(step),
+            None => {
+                let recorded = session.interactions().len();
+                record_input(&matrix, &mut brush, &model, &mut session, step);
+                session.interactions()[recorded..].to_vec()
+            }
+        };
+        for interaction in interactions {
+            let args = &interaction.args;
+            match interaction.name.as_str() {
+                "f" => model.f = args[0],
+                "k" => model.k = args[0],
+                "stroke" => {
+                    brush.select(args[0] as usize);
+                    brush.set_radius(args[1] as usize);
+                    brush.set_intensity(args[2]);
+                    let cell = (args[3] as usize, args[4] as usize);
+                    Fields::new(&mut fields, &[1.0, 0.0]).stroke(&brush, cell, &mut rng);
+                }
+                _ => println!("unknown interaction {:?}", interaction),
+            }
+        }
+        for _ in 0..STEPS_PER_FRAME {
+            Integrator::Euler.step(&model, &mut fields, 1.0);
+        }
+        step += 1;
+        if matrix.render_frame(&fields[1])? == ControlFlow::Stop {
+            break;
+        }
+    }
+    if player.is_none() {
+        session.save(path)?;
+        println!("saved {} interactions to {}", session.interactions().len(), path);
+    }
+    Ok(())
+}
+
+// マウスとキーボードの操作を記録する。盤面への反映は再生のときと同じ経路で行う
+fn record_input(
+    matrix: &MatrixVisualizer,
+    brush: &mut BrushSettings,
+    model: &GrayScott,
+    session: &mut Session,
+    step: u64,
+) {
+    brush.handle_keys(matrix.pressed_keys());
+    for &key in matrix.pressed_keys() {
+        match key {
+            VirtualKeyCode::Up => session.record(step, "f", &[model.f + PARAMETER_STEP]),
+            VirtualKeyCode::Down => session.record(step, "f", &[model.f - PARAMETER_STEP]),
+            VirtualKeyCode::Right => session.record(step, "k", &[model.k + PARAMETER_STEP]),
+            VirtualKeyCode::Left => session.record(step, "k", &[model.k - PARAMETER_STEP]),
+            _ => {}
+        }
+    }
+    if let (Some((row, col)), true) = (matrix.mouse_cell(), matrix.mouse().left) {
+        let args = [
+            brush.selected() as f32,
+            brush.radius() as f32,
+            brush.intensity(),
+            row as f32,
+            col as f32,
+        ];
+        session.record(step, "stroke", &args);
+    }
+}
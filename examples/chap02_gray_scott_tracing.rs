@@ -0,0 +1,28 @@
+extern crate failure;
+extern crate my_alife;
+extern crate tracing_subscriber;
+
+use my_alife::algorithm::backend::{gray_scott_stepper, update_gray_scott};
+use my_alife::algorithm::gray_scott::{initial_matrix, GrayScott};
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use tracing_subscriber::fmt::format::FmtSpan;
+
+// model parameter
+const F: f32 = 0.04;
+const K: f32 = 0.06;
+
+// frame, update, step, upload, swapのspanが閉じるたびに、かかった時間を標準エラー出力に書き出す
+// `cargo run --release --features tracing --example chap02_gray_scott_tracing`で動かす
+fn main() -> Result<(), failure::Error> {
+    tracing_subscriber::fmt()
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .init();
+    let matrix = MatrixVisualizer::new(
+        "Gray Scott",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let stepper = gray_scott_stepper(matrix.display(), &initial_matrix(), GrayScott::new(F, K), None)?;
+    matrix.try_draw_loop(stepper, F, K, |stepper, f, k| update_gray_scott(&mut **stepper, f, k))
+}
@@ -0,0 +1,48 @@
+extern crate failure;
+extern crate my_alife;
+extern crate ndarray;
+
+use my_alife::algorithm::geometry::{CellKind, Geometry};
+use my_alife::algorithm::gray_scott::{initial_matrix, GrayScott};
+use my_alife::algorithm::integrator::Integrator;
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+use ndarray::Zip;
+
+const STEPS_PER_FRAME: usize = 8;
+const BRUSH_RADIUS: usize = 3;
+
+fn main() -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new(
+        "Gray Scott with walls (left: wall, right: erase)",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let model = GrayScott::new(0.04, 0.06);
+    let (u, v) = initial_matrix();
+    let mut geometry = Geometry::new(u.dim());
+    let mut fields = [u, v];
+    loop {
+        // マウスで壁を描く
+        if let Some((row, col)) = matrix.mouse_cell() {
+            if matrix.mouse().left {
+                geometry.paint(row, col, BRUSH_RADIUS, CellKind::Wall);
+            } else if matrix.mouse().right {
+                geometry.paint(row, col, BRUSH_RADIUS, CellKind::Open);
+            }
+        }
+        for _ in 0..STEPS_PER_FRAME {
+            Integrator::Euler.step(&geometry.with_geometry(&model), &mut fields, 1.0);
+            geometry.apply(&mut fields[1]);
+        }
+        // 壁を白く重ねて表示する
+        let mut view = fields[1].clone();
+        Zip::from(&mut view)
+            .and(&geometry.to_matrix())
+            .apply(|v, &wall| *v = v.max(wall));
+        if matrix.render_frame(&view)? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,69 @@
+extern crate failure;
+extern crate my_alife;
+
+use my_alife::algorithm::gray_scott::{initial_matrix, GrayScott};
+use my_alife::algorithm::integrator::Integrator;
+use my_alife::net::protocol::ClientCommand;
+use my_alife::net::server::StateServer;
+use my_alife::visualizer::Matrix;
+use std::env;
+use std::thread;
+use std::time::Duration;
+
+const STEPS_PER_FRAME: u64 = 8;
+const FRAME_INTERVAL: Duration = Duration::from_millis(33);
+const PARAMETER_STEP: f32 = 0.001;
+const INJECT_RADIUS: usize = 3;
+
+// ウィンドウを開かずに動かし、WebSocketで盤面とVの平均を配信する(引数で待ち受けるアドレスを変えられる)
+// res/web/dashboard.htmlをブラウザで開くか、chap02_remote_clientでつなぐと見られる
+// クライアントからは 上/下: f, 右/左: k, 左クリック: Vを注入 の操作ができる
+fn main() -> Result<(), failure::Error> {
+    let address = env::args().nth(1).unwrap_or_else(|| "0.0.0.0:9001".to_string());
+    let server = StateServer::bind(&address)?;
+    println!("streaming on ws://{}/", server.local_addr());
+    let mut model = GrayScott::new(0.04, 0.06);
+    let (u, v) = initial_matrix();
+    let mut fields = [u, v];
+    let mut step = 0;
+    loop {
+        for command in server.poll_commands() {
+            match command {
+                ClientCommand::Key(ref key) if key == "Up" => model.f += PARAMETER_STEP,
+                ClientCommand::Key(ref key) if key == "Down" => model.f -= PARAMETER_STEP,
+                ClientCommand::Key(ref key) if key == "Right" => model.k += PARAMETER_STEP,
+                ClientCommand::Key(ref key) if key == "Left" => model.k -= PARAMETER_STEP,
+                ClientCommand::SetParameter(ref name, value) if name == "f" => model.f = value,
+                ClientCommand::SetParameter(ref name, value) if name == "k" => model.k = value,
+                ClientCommand::Mouse {
+                    row, col, left: true, ..
+                } => inject(&mut fields[1], row, col),
+                _ => {}
+            }
+        }
+        for _ in 0..STEPS_PER_FRAME {
+            Integrator::Euler.step(&model, &mut fields, 1.0);
+        }
+        step += STEPS_PER_FRAME;
+        server.publish(step, &fields[1]);
+        let mean = fields[1].scalar_sum() / fields[1].len() as f32;
+        let observables = [
+            ("mean_v", mean),
+            ("f", model.f),
+            ("k", model.k),
+            ("clients", server.client_count() as f32),
+        ];
+        server.publish_observables(step, &observables);
+        thread::sleep(FRAME_INTERVAL);
+    }
+}
+
+// (row, col)のまわりのVを1にする
+fn inject(v: &mut Matrix<f32>, row: usize, col: usize) {
+    let (rows, cols) = v.dim();
+    for r in row.saturating_sub(INJECT_RADIUS)..(row + INJECT_RADIUS).min(rows) {
+        for c in col.saturating_sub(INJECT_RADIUS)..(col + INJECT_RADIUS).min(cols) {
+            v[[r, c]] = 1.0;
+        }
+    }
+}
@@ -0,0 +1,34 @@
+extern crate my_alife;
+extern crate ndarray;
+
+use my_alife::algorithm::gray_scott::{in_place_laplacian, initial_matrix, laplacian};
+use ndarray::Array2;
+use std::time::Instant;
+
+const FRAMES: usize = 200;
+
+// 一時的な配列を作るlaplacianと、作らないin_place_laplacianの1フレームあたりの時間を比べる
+// `cargo run --release --example chap02_kernel_benchmark`で測る
+fn main() {
+    let mut uv = initial_matrix();
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        laplacian(&mut uv, 0.04, 0.06);
+    }
+    let naive = start.elapsed() / FRAMES as u32;
+
+    let mut uv = initial_matrix();
+    let mut scratch = (Array2::zeros(uv.0.dim()), Array2::zeros(uv.0.dim()));
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        in_place_laplacian(&mut uv, 0.04, 0.06, &mut scratch);
+    }
+    let in_place = start.elapsed() / FRAMES as u32;
+
+    println!("laplacian:          {:?} / frame", naive);
+    println!("in_place_laplacian: {:?} / frame", in_place);
+    println!(
+        "speedup:            {:.2}x",
+        naive.as_secs_f64() / in_place.as_secs_f64()
+    );
+}
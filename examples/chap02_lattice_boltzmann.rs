@@ -0,0 +1,49 @@
+extern crate failure;
+extern crate my_alife;
+extern crate ndarray;
+
+use my_alife::algorithm::lbm::Lbm;
+use my_alife::visualizer::colormap::Colormap;
+use my_alife::visualizer::image::load_luminance;
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+use ndarray::Array2;
+use std::env;
+
+const ROWS: usize = 128;
+const COLS: usize = 384;
+const TAU: f32 = 0.52;
+const INFLOW: f32 = 0.1;
+const STEPS_PER_FRAME: usize = 10;
+
+fn main() -> Result<(), failure::Error> {
+    // 引数でPNGを渡すと、暗い画素を障害物にする。なければ円柱を置く
+    let obstacles = match env::args().nth(1) {
+        Some(path) => load_luminance(path)?.mapv(|e| e < 0.5),
+        None => Array2::from_shape_fn((ROWS, COLS), |(row, col)| {
+            // 対称性を崩すため、少しだけ中心からずらす
+            let (dr, dc) = (row as f32 - ROWS as f32 / 2.0 - 1.0, col as f32 - COLS as f32 / 5.0);
+            dr * dr + dc * dc < 100.0
+        }),
+    };
+    let mut fluid = Lbm::new(obstacles.dim(), TAU);
+    fluid.set_obstacles(obstacles);
+    fluid.set_inflow(Some((INFLOW, 0.0)));
+
+    let mut matrix = MatrixVisualizer::new(
+        "Lattice Boltzmann (vorticity)",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    matrix.set_value_range(-0.05, 0.05);
+    matrix.set_colormap(Colormap::Heat);
+    loop {
+        for _ in 0..STEPS_PER_FRAME {
+            fluid.step();
+        }
+        if matrix.render_frame(&fluid.vorticity())? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,58 @@
+extern crate failure;
+extern crate my_alife;
+extern crate ndarray;
+extern crate rand;
+
+use my_alife::algorithm::lenia::{Animal, Lenia, LeniaKernel};
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::{ControlFlow, VirtualKeyCode};
+use ndarray::Array2;
+use rand::Rng;
+use std::env;
+
+const SPACE_GRID_SIZE: usize = 128;
+const BLOB_SIZE: usize = 40;
+const SAVE_PATH: &str = "lenia_animal.json";
+
+// 引数でLeniaのコミュニティの形式のJSONを指定すると、その最初の生き物を動かす
+// 指定しなければランダムな塊から始める。S: 今の盤面を生き物として保存する
+fn main() -> Result<(), failure::Error> {
+    let animal = match env::args().nth(1) {
+        Some(path) => Animal::load_all(&path)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| failure::err_msg("no animals in the file"))?,
+        None => {
+            let mut rng = rand::thread_rng();
+            let cells = Array2::from_shape_fn((BLOB_SIZE, BLOB_SIZE), |_| rng.gen_range(0.0, 1.0));
+            let lenia = Lenia::new(13, 10.0, vec![LeniaKernel::new(0.15, 0.015)]);
+            Animal::new("R", "random", lenia, cells)
+        }
+    };
+    println!("{} {}", animal.code, animal.name);
+    // 生き物を盤面の中央に置く
+    let (rows, cols) = animal.cells.dim();
+    let size = SPACE_GRID_SIZE.max(2 * rows).max(2 * cols);
+    let mut cells = Array2::<f32>::zeros((size, size));
+    let (top, left) = ((size - rows) / 2, (size - cols) / 2);
+    for ((row, col), &value) in animal.cells.indexed_iter() {
+        cells[[top + row, left + col]] = value;
+    }
+    let mut matrix = MatrixVisualizer::new(
+        "Lenia",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    loop {
+        cells = animal.lenia.step(&cells);
+        if matrix.pressed_keys().contains(&VirtualKeyCode::S) {
+            let found = Animal::new(&animal.code, &animal.name, animal.lenia.clone(), cells.clone());
+            Animal::save_all(SAVE_PATH, &[found])?;
+            println!("saved to {}", SAVE_PATH);
+        }
+        if matrix.render_frame(&cells)? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
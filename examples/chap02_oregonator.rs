@@ -0,0 +1,33 @@
+extern crate failure;
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::integrator::Integrator;
+use my_alife::algorithm::oregonator::Oregonator;
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+
+const SPACE_GRID_SIZE: usize = 128;
+const DT: f32 = 5e-4;
+const VISUALIZATION_STEP: usize = 40;
+
+fn main() -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new(
+        "Oregonator",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let model = Oregonator::default();
+    let mut fields = model.initial_fields((SPACE_GRID_SIZE, SPACE_GRID_SIZE), &mut rand::thread_rng());
+    loop {
+        for _ in 0..VISUALIZATION_STEP {
+            Integrator::Euler.step(&model, &mut fields, DT);
+        }
+        // x, y, zをそれぞれ赤、緑、青で表示する
+        let channels = [&fields[0], &fields[1], &fields[2]];
+        if matrix.render_channels(&channels, &model.ranges())? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,35 @@
+extern crate failure;
+extern crate my_alife;
+
+use my_alife::net::client::RemoteView;
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+use std::env;
+
+// 別のプロセス(chap02_gray_scott_websocketなど)で動いているシミュレーションにつないで表示する
+// 引数でサーバーのアドレスを指定する(省略すると127.0.0.1:9001)
+fn main() -> Result<(), failure::Error> {
+    let address = env::args().nth(1).unwrap_or_else(|| "127.0.0.1:9001".to_string());
+    let mut remote = RemoteView::connect(&address)?;
+    let mut matrix = MatrixVisualizer::new(
+        &format!("remote {}", address),
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let mut shown = 0;
+    while remote.render_frame(&mut matrix)? == ControlFlow::Continue {
+        let (step, observables) = remote.observables();
+        if step != shown {
+            shown = step;
+            let values: Vec<String> = observables
+                .iter()
+                .map(|&(ref name, value)| format!("{}={:.4}", name, value))
+                .collect();
+            matrix.set_title(&format!("remote {} step {} {}", address, step, values.join(" ")));
+        }
+    }
+    if !remote.is_connected() {
+        println!("disconnected from {}", address);
+    }
+    Ok(())
+}
@@ -0,0 +1,44 @@
+extern crate my_alife;
+
+use my_alife::algorithm::bit_life::BitLife;
+use my_alife::algorithm::game_of_life::{random_cells, step};
+use my_alife::algorithm::life_like::LifeRule;
+use std::time::Instant;
+
+const SIZE: usize = 1024;
+const GENERATIONS: u32 = 20;
+
+// 1024×1024の盤面で、game_of_life::step, LifeRule::step_swar, BitLife::stepの1世代あたりの時間を比べる
+// `cargo run --release --example chap03_bit_life_benchmark`で測る
+fn main() {
+    let cells = random_cells((SIZE, SIZE), 0.3);
+
+    let mut naive_cells = cells.clone();
+    let start = Instant::now();
+    for _ in 0..GENERATIONS {
+        naive_cells = step(&naive_cells);
+    }
+    let naive = start.elapsed() / GENERATIONS;
+
+    let rule: LifeRule = "B3/S23".parse().unwrap();
+    let mut swar_cells = cells.clone();
+    let start = Instant::now();
+    for _ in 0..GENERATIONS {
+        swar_cells = rule.step_swar(&swar_cells);
+    }
+    let swar = start.elapsed() / GENERATIONS;
+
+    let mut life = BitLife::from_cells(&cells);
+    let start = Instant::now();
+    for _ in 0..GENERATIONS {
+        life.step();
+    }
+    let bits = start.elapsed() / GENERATIONS;
+    assert_eq!(life.to_cells(), naive_cells);
+    assert_eq!(swar_cells, naive_cells);
+
+    println!("game_of_life::step:  {:?} / generation", naive);
+    println!("LifeRule::step_swar: {:?} / generation", swar);
+    println!("BitLife::step:       {:?} / generation", bits);
+    println!("speedup:             {:.1}x", naive.as_secs_f64() / bits.as_secs_f64());
+}
@@ -0,0 +1,64 @@
+extern crate failure;
+extern crate my_alife;
+
+use my_alife::algorithm::turing::{tape_palette, TapeHistory, TransitionTable, TuringMachine, FAMOUS_MACHINES};
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+use std::env;
+
+// 表示するテープの幅と、残しておく時間(行)
+const TAPE_WIDTH: usize = 200;
+const HISTORY_LENGTH: usize = 200;
+const STEPS_PER_FRAME: usize = 2;
+
+// 忙しいビーバーのテープの時間発展。上から下へ時間が進む
+// 引数でFAMOUS_MACHINESの名前か遷移表を指定する(例: cargo run --example chap03_busy_beaver -- 1RB1LC_1RC1RB_1RD0LE_1LA1LD_1RZ0LA)
+fn main() -> Result<(), failure::Error> {
+    let arg = env::args().nth(1).unwrap_or_else(|| "BB(4)".to_string());
+    let table = match TransitionTable::famous(&arg) {
+        Some(table) => table,
+        None => arg.parse()?,
+    };
+    for &(name, machine, steps, nonzero) in FAMOUS_MACHINES.iter() {
+        if machine == table.to_string() {
+            println!("{}: halts after {} steps with {} nonzero symbols", name, steps, nonzero);
+        }
+    }
+    let mut matrix = MatrixVisualizer::new(
+        "Turing machine",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let palette = tape_palette(&table);
+    matrix.set_legend(Some(&palette));
+    let mut machine = TuringMachine::new(table);
+    let mut history = TapeHistory::new(-(TAPE_WIDTH as i64) / 2, TAPE_WIDTH, HISTORY_LENGTH);
+    let mut left = -(TAPE_WIDTH as i64) / 2;
+    history.record(&machine);
+    loop {
+        for _ in 0..STEPS_PER_FRAME {
+            if !machine.step() {
+                break;
+            }
+            // ヘッドが見えなくなったら中央に戻す
+            let head = machine.head();
+            if head < left || head >= left + TAPE_WIDTH as i64 {
+                left = head - TAPE_WIDTH as i64 / 2;
+                history.set_left(left);
+            }
+            history.record(&machine);
+        }
+        matrix.set_title(&format!(
+            "{} (steps={}, nonzero={}{})",
+            machine.table(),
+            machine.steps(),
+            machine.count_nonzero(),
+            if machine.is_halted() { ", halted" } else { "" }
+        ));
+        matrix.draw_palette(&history.matrix(), &palette)?;
+        if matrix.poll_events() == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,56 @@
+extern crate failure;
+extern crate my_alife;
+
+use my_alife::algorithm::ensemble::Ensemble;
+use my_alife::algorithm::game_of_life::random_cells;
+use my_alife::algorithm::neighborhood::Neighborhood;
+use my_alife::algorithm::stochastic_ca::StochasticCa;
+use my_alife::visualizer::colormap::Colormap;
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+
+const SPACE_GRID_SIZE: usize = 128;
+const REPLICAS: usize = 32;
+const SEED: u64 = 2018;
+// 感染と回復の確率。臨界点の近くでは、レプリカによって生き残るかどうかが分かれる
+const INFECTION: f32 = 0.3;
+const RECOVERY: f32 = 0.5;
+
+// 接触過程を乱数の種だけ変えて32個動かし、セルごとの平均を表示する
+fn main() -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new(
+        "Contact process ensemble",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    matrix.set_colormap(Colormap::Viridis);
+    let ca = StochasticCa::new(2, Neighborhood::VonNeumann, |state, counts| match state {
+        0 => {
+            let p = 1.0 - (1.0 - INFECTION).powi(i32::from(counts[1]));
+            vec![1.0 - p, p]
+        }
+        _ => vec![RECOVERY, 1.0 - RECOVERY],
+    });
+    let mut ensemble = Ensemble::new(REPLICAS, SEED, |_| {
+        random_cells((SPACE_GRID_SIZE, SPACE_GRID_SIZE), 0.5)
+    });
+    let cells = (SPACE_GRID_SIZE * SPACE_GRID_SIZE) as f32;
+    loop {
+        ensemble.step(1, |cells, rng| *cells = ca.step(cells, rng));
+        let density =
+            ensemble.observable(|cells| cells.iter().map(|&e| f32::from(e)).sum::<f32>() / cells.len() as f32);
+        matrix.set_title(&format!(
+            "Contact process t={} density: {:.3} ± {:.3} (survived {}/{})",
+            ensemble.steps(),
+            density.mean(),
+            density.std_dev(),
+            density.values.iter().filter(|&&d| d * cells >= 1.0).count(),
+            REPLICAS
+        ));
+        let statistics = ensemble.field_statistics(|cells| cells);
+        if matrix.render_frame(&statistics.mean)? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
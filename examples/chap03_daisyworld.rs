@@ -0,0 +1,42 @@
+extern crate failure;
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::daisyworld::{to_csv, LatticeDaisyworld};
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::palette::Palette;
+use my_alife::visualizer::ControlFlow;
+use std::fs;
+
+const SPACE_GRID_SIZE: usize = 128;
+// 太陽の明るさを少しずつ上げていく
+const LUMINOSITY: (f32, f32) = (0.6, 1.6);
+const STEPS: usize = 2000;
+
+fn main() -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new(
+        "Daisyworld",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    // BARE, WHITE, BLACKの順
+    let palette = Palette::new()
+        .with("Bare", [140, 110, 70])
+        .with("White", [240, 240, 240])
+        .with("Black", [30, 30, 30]);
+    matrix.set_legend(Some(&palette));
+    let mut rng = rand::thread_rng();
+    let mut world = LatticeDaisyworld::random((SPACE_GRID_SIZE, SPACE_GRID_SIZE), LUMINOSITY.0, &mut rng);
+    let mut series = Vec::new();
+    for i in 0..STEPS {
+        world.set_luminosity(LUMINOSITY.0 + (LUMINOSITY.1 - LUMINOSITY.0) * i as f32 / STEPS as f32);
+        series.push(world.step(&mut rng));
+        matrix.draw_palette(world.cells(), &palette)?;
+        if matrix.poll_events() == ControlFlow::Stop {
+            break;
+        }
+    }
+    // 明るさ・温度・被覆率の時系列
+    fs::write("daisyworld.csv", to_csv(&series))?;
+    Ok(())
+}
@@ -0,0 +1,36 @@
+extern crate failure;
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::daisyworld::LatticeDaisyworld;
+use my_alife::visualizer::colormap::Colormap;
+use my_alife::visualizer::sphere_visualizer::SphereVisualizer;
+use my_alife::visualizer::{ControlFlow, Draw};
+
+// 経度方向は緯度方向の2倍の長さ
+const SPACE_GRID_SIZE: (usize, usize) = (64, 128);
+// 太陽の明るさを少しずつ上げていく
+const LUMINOSITY: (f32, f32) = (0.6, 1.6);
+const STEPS: usize = 2000;
+// 温度(K)の表示範囲
+const TEMPERATURE_RANGE: (f32, f32) = (270.0, 320.0);
+
+// 格子版Daisyworldの温度を、ゆっくり自転する惑星に貼って表示する。ドラッグで向きを変えられる
+fn main() -> Result<(), failure::Error> {
+    let mut sphere = SphereVisualizer::new("Daisyworld")?;
+    sphere.set_colormap(Colormap::Heat);
+    sphere.set_value_range(TEMPERATURE_RANGE.0, TEMPERATURE_RANGE.1);
+    sphere.set_spin(0.01);
+    sphere.set_pitch(0.3);
+    let mut rng = rand::thread_rng();
+    let mut world = LatticeDaisyworld::random(SPACE_GRID_SIZE, LUMINOSITY.0, &mut rng);
+    for i in 0..STEPS {
+        world.set_luminosity(LUMINOSITY.0 + (LUMINOSITY.1 - LUMINOSITY.0) * i as f32 / STEPS as f32);
+        let stats = world.step(&mut rng);
+        sphere.set_title(&format!("Daisyworld ({:.1}K)", stats.temperature));
+        if sphere.render_frame(world.temperature())? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
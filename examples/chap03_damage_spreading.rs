@@ -0,0 +1,34 @@
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::damage::{DamageSpreading, Dynamics};
+use my_alife::algorithm::elementary_ca::step;
+use rand::Rng;
+
+const WIDTH: usize = 201;
+const STEPS: usize = 80;
+const TRIALS: usize = 8;
+
+fn main() {
+    let mut rng = rand::thread_rng();
+    let classes: Vec<(u8, Dynamics, f32)> = (0..=255u8)
+        .map(|rule| {
+            let runs: Vec<DamageSpreading> = (0..TRIALS)
+                .map(|_| {
+                    let initial: Vec<u8> = (0..WIDTH).map(|_| rng.gen_range(0, 2)).collect();
+                    DamageSpreading::run(&initial, WIDTH / 2, STEPS, |cells| step(cells, rule))
+                })
+                .collect();
+            let mean = DamageSpreading::mean(&runs).unwrap();
+            (rule, mean.classify(), mean.exponent())
+        })
+        .collect();
+    for &dynamics in &[Dynamics::Ordered, Dynamics::Critical, Dynamics::Chaotic] {
+        let rules: Vec<String> = classes
+            .iter()
+            .filter(|&&(_, class, _)| class == dynamics)
+            .map(|&(rule, _, exponent)| format!("{}({:.3})", rule, exponent))
+            .collect();
+        println!("{:?} ({} rules): {}", dynamics, rules.len(), rules.join(" "));
+    }
+}
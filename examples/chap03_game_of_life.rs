@@ -0,0 +1,34 @@
+extern crate failure;
+extern crate my_alife;
+
+use my_alife::algorithm::cycle::{state_hash, CycleDetector};
+use my_alife::algorithm::game_of_life::{random_cells, SparseLife};
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+
+const SPACE_GRID_SIZE: usize = 256;
+const TILE_SIZE: usize = 16;
+
+fn main() -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new(
+        "Game of Life",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let mut life = SparseLife::new(random_cells((SPACE_GRID_SIZE, SPACE_GRID_SIZE), 0.2), TILE_SIZE);
+    matrix.draw(life.cells())?;
+    let mut detector = CycleDetector::new();
+    loop {
+        life.step();
+        // 変化したタイルだけを転送し直す
+        matrix.draw_dirty(life.cells(), life.dirty())?;
+        if let Some(cycle) = detector.observe(state_hash(life.cells())) {
+            println!("period {} after {} generations", cycle.period, cycle.transient);
+            break;
+        }
+        if matrix.poll_events() == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,68 @@
+extern crate failure;
+extern crate my_alife;
+
+use my_alife::algorithm::game_of_life::{random_cells, step, ALIVE, DEAD};
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::selection::{outline, Clipboard, Selection};
+use my_alife::visualizer::undo::UndoStack;
+use my_alife::visualizer::{ControlFlow, VirtualKeyCode};
+
+const SPACE_GRID_SIZE: usize = 128;
+const OUTLINE: u8 = 2;
+const UNDO_BUDGET: usize = 1 << 18;
+
+// Shift+ドラッグ: 範囲選択, C: コピー, X: 切り取り, V: 貼り付け, R: 回転, M: 左右反転, Space: 一時停止
+// Z: 取り消し, Y: やり直し
+fn main() -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new(
+        "Game of Life edit",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    matrix.set_value_range(0.0, OUTLINE as f32);
+    let mut cells = random_cells((SPACE_GRID_SIZE, SPACE_GRID_SIZE), 0.2);
+    let mut selection = Selection::new();
+    let mut clipboard = Clipboard::new();
+    let mut history = UndoStack::new(UNDO_BUDGET);
+    let mut paused = false;
+    loop {
+        let cursor = matrix.mouse_cell();
+        let keys = matrix.pressed_keys().to_vec();
+        if keys.contains(&VirtualKeyCode::Space) {
+            paused = !paused;
+        }
+        let before = cells.clone();
+        if clipboard.handle_keys(&keys, &mut cells, selection.region(), cursor, DEAD) {
+            history.record("paste", &[before], &[cells.clone()]);
+        }
+        for key in &keys {
+            match *key {
+                VirtualKeyCode::Z => history.undo(std::slice::from_mut(&mut cells)),
+                VirtualKeyCode::Y => history.redo(std::slice::from_mut(&mut cells)),
+                _ => None,
+            };
+        }
+        // 選択中でなければ左クリックでセルを生き返らせる
+        if !selection.update(matrix.mouse(), cursor) && matrix.mouse().left {
+            if let Some(cell) = cursor {
+                history.begin();
+                let before = cells.clone();
+                cells[cell] = ALIVE;
+                history.record("draw", &[before], &[cells.clone()]);
+            }
+        } else {
+            history.end("draw");
+        }
+        if !paused {
+            cells = step(&cells);
+        }
+        let view = match selection.region() {
+            Some(region) => outline(&cells, region, OUTLINE),
+            None => cells.clone(),
+        };
+        if matrix.render_frame(&view)? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,34 @@
+extern crate failure;
+extern crate my_alife;
+
+use my_alife::algorithm::game_of_life::{births_and_deaths, random_cells, step, ALIVE};
+use my_alife::osc::{OscSender, SimulationEvent};
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+
+const SPACE_GRID_SIZE: usize = 128;
+// SuperColliderが標準で待ち受けているport
+const OSC_TARGET: &str = "127.0.0.1:57120";
+
+fn main() -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new(
+        "Game of Life (OSC)",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let sender = OscSender::new(OSC_TARGET)?;
+    let mut cells = random_cells((SPACE_GRID_SIZE, SPACE_GRID_SIZE), 0.2);
+    loop {
+        let next = step(&cells);
+        let (births, deaths) = births_and_deaths(&cells, &next);
+        let population = next.iter().filter(|&&e| e == ALIVE).count();
+        sender.send_event(&SimulationEvent::Births(births as u64))?;
+        sender.send_event(&SimulationEvent::Deaths(deaths as u64))?;
+        sender.send_event(&SimulationEvent::Population(population as u64))?;
+        cells = next;
+        if matrix.render_frame(&cells)? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,54 @@
+extern crate failure;
+extern crate my_alife;
+extern crate ndarray;
+extern crate rand;
+
+use my_alife::algorithm::golly::{GollyRule, Macrocell};
+use my_alife::algorithm::hashlife::HashLife;
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+use ndarray::Array2;
+use rand::Rng;
+use std::env;
+
+const SPACE_GRID_SIZE: usize = 128;
+const DEFAULT_FILE: &str = "res/rules/WireWorld.rule";
+
+// 引数でGollyのファイルを指定する
+// .rule: ランダムな盤面で規則を動かす, .mc: パターンをHashLifeで動かす(例: res/patterns/glider.mc)
+fn main() -> Result<(), failure::Error> {
+    let path = env::args().nth(1).unwrap_or_else(|| DEFAULT_FILE.to_string());
+    let mut matrix = MatrixVisualizer::new(
+        "Golly import",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    if path.ends_with(".mc") {
+        let pattern = Macrocell::load(&path)?;
+        println!("rule: {}", pattern.rule().unwrap_or("B3/S23"));
+        let mut life = HashLife::from_macrocell(&pattern)?;
+        let (x, y) = pattern.bounds().map_or((0, 0), |(x, y, _, _)| (x, y));
+        let offset = SPACE_GRID_SIZE as i64 / 4;
+        loop {
+            let view = life.viewport(x - offset, y - offset, SPACE_GRID_SIZE, SPACE_GRID_SIZE);
+            if matrix.render_frame(&view)? == ControlFlow::Stop {
+                break;
+            }
+            life.step(1)?;
+        }
+        return Ok(());
+    }
+    let rule = GollyRule::load(&path)?;
+    println!("{}: {} states", rule.name(), rule.num_states());
+    let num_states = rule.num_states();
+    let mut rng = rand::thread_rng();
+    let mut cells = Array2::from_shape_fn((SPACE_GRID_SIZE, SPACE_GRID_SIZE), |_| rng.gen_range(0, num_states));
+    matrix.set_value_range(0.0, (num_states - 1) as f32);
+    loop {
+        cells = rule.step(&cells);
+        if matrix.render_frame(&cells)? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,34 @@
+extern crate failure;
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::game_of_life::random_cells;
+use my_alife::algorithm::neighborhood::Neighborhood;
+use my_alife::algorithm::stochastic_ca::StochasticCa;
+use my_alife::visualizer::colormap::Colormap;
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+
+const SPACE_GRID_SIZE: usize = 256;
+const NUM_STATES: u8 = 8;
+
+fn main() -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new(
+        "Greenberg-Hastings",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    matrix.set_value_range(0.0, f32::from(NUM_STATES - 1));
+    matrix.set_colormap(Colormap::Viridis);
+    let ca = StochasticCa::greenberg_hastings(Neighborhood::Moore, NUM_STATES, 0.3);
+    let mut rng = rand::thread_rng();
+    // 1%のセルを興奮させておく
+    let mut cells = random_cells((SPACE_GRID_SIZE, SPACE_GRID_SIZE), 0.01);
+    loop {
+        cells = ca.step(&cells, &mut rng);
+        if matrix.render_frame(&cells)? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,44 @@
+extern crate failure;
+extern crate my_alife;
+
+use my_alife::algorithm::hashlife::HashLife;
+use my_alife::visualizer::camera::Camera;
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+
+// グライダーは4世代で右下に1セル進む
+const GLIDER: [(i64, i64); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+
+fn main() -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new(
+        "HashLife",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let mut camera = Camera::new(600.0, 600.0);
+    camera.set_zoom(8.0);
+    matrix.set_camera(Some(camera));
+
+    let mut life = HashLife::new();
+    for &(x, y) in &GLIDER {
+        life.set_cell(x, y, true);
+    }
+    loop {
+        life.step(1)?;
+        let position = (
+            life.generation() as f64 / 4.0 + 1.0,
+            life.generation() as f64 / 4.0 + 1.0,
+        );
+        let rect = {
+            let camera = matrix.camera_mut().unwrap();
+            // グライダーを追いかける
+            camera.follow(position, 0.1);
+            camera.visible_cells()
+        };
+        let view = life.viewport(rect.x, rect.y, rect.width, rect.height);
+        if matrix.render_frame(&view)? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,47 @@
+extern crate failure;
+extern crate my_alife;
+
+use my_alife::algorithm::patterns::parse_rle;
+use my_alife::algorithm::self_replication::{evoloop, langtons_loop, langtons_loops, loop_palette, LoopColony};
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+use std::env;
+use std::fs;
+
+const SPACE_GRID_SIZE: usize = 256;
+
+// Langtonの自己複製ループ。コロニーが外側に広がり、内側には複製をやめたループが残る
+// 引数でGollyの`Evoloop.rule`とRLEのパターンを指定するとEvoloopを動かす
+// (例: cargo run --example chap03_langtons_loops -- Evoloop.rule Evoloop-finite.rle)
+fn main() -> Result<(), failure::Error> {
+    let mut args = env::args().skip(1);
+    let (rule, pattern) = match (args.next(), args.next()) {
+        (Some(rule), Some(pattern)) => (evoloop(rule)?, parse_rle(&fs::read_to_string(pattern)?)?),
+        _ => (langtons_loops(), langtons_loop()),
+    };
+    let mut matrix = MatrixVisualizer::new(
+        rule.name(),
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let palette = loop_palette();
+    matrix.set_legend(Some(&palette));
+    let mut colony = LoopColony::new(rule, (SPACE_GRID_SIZE, SPACE_GRID_SIZE));
+    colony.place_center(&pattern);
+    loop {
+        colony.step();
+        if colony.time().is_multiple_of(50) {
+            matrix.set_title(&format!(
+                "{} (t={}, loops={})",
+                colony.rule().name(),
+                colony.time(),
+                colony.count_loops()
+            ));
+        }
+        matrix.draw_palette(colony.cells(), &palette)?;
+        if matrix.poll_events() == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,48 @@
+extern crate failure;
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::lattice_gas::Fhp;
+use my_alife::visualizer::colormap::Colormap;
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+use rand::Rng;
+
+const SPACE_GRID_SIZE: usize = 512;
+const BLOCK_SIZE: usize = 4;
+const BACKGROUND_DENSITY: f32 = 0.15;
+const DROP_DENSITY: f32 = 0.6;
+const DROP_RADIUS: f32 = 40.0;
+
+fn main() -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new(
+        "FHP lattice gas",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    matrix.set_value_range(0.0, 6.0 * DROP_DENSITY);
+    matrix.set_colormap(Colormap::Viridis);
+    let mut rng = rand::thread_rng();
+    let mut gas = Fhp::random((SPACE_GRID_SIZE, SPACE_GRID_SIZE), BACKGROUND_DENSITY, &mut rng);
+    // 中央の円を密にすると、等方的に広がる圧力波ができる
+    let center = SPACE_GRID_SIZE as f32 / 2.0;
+    for ((row, col), e) in gas.cells_mut().indexed_iter_mut() {
+        let (dr, dc) = (row as f32 - center, col as f32 - center);
+        if dr * dr + dc * dc < DROP_RADIUS * DROP_RADIUS {
+            *e = (0..6).fold(0, |cell, i| {
+                if rng.gen::<f32>() < DROP_DENSITY {
+                    cell | 1 << i
+                } else {
+                    cell
+                }
+            });
+        }
+    }
+    loop {
+        gas.step();
+        if matrix.render_frame(&gas.moments(BLOCK_SIZE).density)? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
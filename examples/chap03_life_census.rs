@@ -0,0 +1,23 @@
+extern crate my_alife;
+
+use my_alife::algorithm::game_of_life::{random_cells, step};
+use my_alife::algorithm::life_patterns::{census, PatternCatalog};
+
+const SPACE_GRID_SIZE: usize = 128;
+const GENERATIONS: usize = 1000;
+
+fn main() {
+    let catalog = PatternCatalog::default();
+    let mut cells = random_cells((SPACE_GRID_SIZE, SPACE_GRID_SIZE), 0.3);
+    for generation in 0..=GENERATIONS {
+        if generation % 100 == 0 {
+            let counts = census(&cells, &catalog, 16);
+            let summary: Vec<String> = counts
+                .iter()
+                .map(|(name, count)| format!("{}={}", name, count))
+                .collect();
+            println!("generation {}: {}", generation, summary.join(", "));
+        }
+        cells = step(&cells);
+    }
+}
@@ -0,0 +1,37 @@
+extern crate failure;
+extern crate my_alife;
+
+use my_alife::algorithm::game_of_life::random_cells;
+use my_alife::algorithm::life_like::LifeRule;
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+use std::env;
+use std::time::Instant;
+
+// 1024×1024の盤面を、SWARで近傍を数えて1セル1byteのまま描画する。引数でルールを指定できる(省略するとB3/S23)
+fn main() -> Result<(), failure::Error> {
+    let rule: LifeRule = env::args().nth(1).unwrap_or_else(|| "B3/S23".to_string()).parse()?;
+    let mut matrix = MatrixVisualizer::new(
+        "Life (SWAR)",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let mut cells = random_cells((1024, 1024), 0.3);
+    let mut generation = 0;
+    loop {
+        let start = Instant::now();
+        cells = rule.step_swar(&cells);
+        generation += 1;
+        matrix.set_title(&format!(
+            "{} generation: {} ({:?} / step)",
+            rule,
+            generation,
+            start.elapsed()
+        ));
+        matrix.draw_states(&cells, rule.states())?;
+        if matrix.poll_events() == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,42 @@
+extern crate failure;
+extern crate my_alife;
+
+use my_alife::algorithm::game_of_life::random_cells;
+use my_alife::algorithm::margolus::{BlockRule, Margolus};
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+
+const SPACE_GRID_SIZE: usize = 256;
+const SQUARE_SIZE: usize = 64;
+const BACKGROUND_DENSITY: f32 = 0.05;
+const STEPS: usize = 300;
+
+fn main() -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new(
+        "Margolus HPP gas",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    // 薄い気体の中央に、密な正方形を置く
+    let mut cells = random_cells((SPACE_GRID_SIZE, SPACE_GRID_SIZE), BACKGROUND_DENSITY);
+    let start = (SPACE_GRID_SIZE - SQUARE_SIZE) / 2;
+    let square = random_cells((SQUARE_SIZE, SQUARE_SIZE), 0.8);
+    for ((row, col), &e) in square.indexed_iter() {
+        cells[[start + row, start + col]] = e;
+    }
+    let mut gas = Margolus::new(cells, BlockRule::hpp_gas());
+    // 拡散した後に時間を逆向きに進めると、正方形が元に戻る
+    loop {
+        for _ in 0..STEPS {
+            gas.step();
+            if matrix.render_frame(gas.cells())? == ControlFlow::Stop {
+                return Ok(());
+            }
+        }
+        while gas.step_back().is_some() {
+            if matrix.render_frame(gas.cells())? == ControlFlow::Stop {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,40 @@
+extern crate failure;
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::graph::{Graph, GraphSir};
+use my_alife::visualizer::graph_visualizer::GraphVisualizer;
+use my_alife::visualizer::palette::Palette;
+use my_alife::visualizer::ControlFlow;
+
+const NODES: usize = 200;
+// 新しい頂点が結ぶ辺の数
+const ATTACHMENT: usize = 2;
+const INFECTION: f32 = 0.05;
+const RECOVERY: f32 = 0.02;
+
+// スケールフリー・ネットワーク上でSIRモデルの流行が広がる様子を描く
+fn main() -> Result<(), failure::Error> {
+    let mut rng = rand::thread_rng();
+    let graph = Graph::barabasi_albert(NODES, ATTACHMENT, &mut rng);
+    let mut window = GraphVisualizer::new("Network SIR", graph.len(), graph.edges(), &mut rng)?;
+    // SUSCEPTIBLE, INFECTED, RECOVEREDの順
+    let palette = Palette::new()
+        .with("Susceptible", [60, 120, 230])
+        .with("Infected", [230, 50, 40])
+        .with("Recovered", [120, 120, 120]);
+    let mut sir = GraphSir::new(graph, INFECTION, RECOVERY);
+    sir.infect(0);
+    loop {
+        sir.step(&mut rng);
+        let (susceptible, infected, recovered) = sir.counts();
+        window.set_title(&format!(
+            "Network SIR (S={}, I={}, R={})",
+            susceptible, infected, recovered
+        ));
+        if window.draw_states(sir.states(), &palette)? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
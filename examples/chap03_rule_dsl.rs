@@ -0,0 +1,41 @@
+extern crate failure;
+extern crate my_alife;
+extern crate ndarray;
+extern crate rand;
+
+use my_alife::algorithm::rule_dsl::RuleProgram;
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+use ndarray::Array2;
+use rand::Rng;
+use std::env;
+
+const SPACE_GRID_SIZE: usize = 128;
+const DEFAULT_RULE: &str = "res/rules/life.carule";
+
+// 引数で規則のファイルを指定する(例: res/rules/wireworld.carule)
+fn main() -> Result<(), failure::Error> {
+    let path = env::args().nth(1).unwrap_or_else(|| DEFAULT_RULE.to_string());
+    let program = RuleProgram::load(&path)?;
+    for line in program.unreachable_lines() {
+        println!("warning: {}: the rule on line {} is never used", path, line);
+    }
+    println!("states: {}", program.states().join(", "));
+    let rule = program.compile();
+    let mut rng = rand::thread_rng();
+    let num_states = program.num_states();
+    let mut cells = Array2::from_shape_fn((SPACE_GRID_SIZE, SPACE_GRID_SIZE), |_| rng.gen_range(0, num_states));
+    let mut matrix = MatrixVisualizer::new(
+        "Rule DSL",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    matrix.set_value_range(0.0, (num_states - 1) as f32);
+    loop {
+        cells = rule.step(&cells);
+        if matrix.render_frame(&cells)? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,34 @@
+extern crate failure;
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::schelling::Schelling;
+use my_alife::visualizer::colormap::Colormap;
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+
+const SPACE_GRID_SIZE: usize = 128;
+const GROUPS: u8 = 3;
+
+fn main() -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new(
+        "Schelling",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    matrix.set_value_range(0.0, f32::from(GROUPS));
+    matrix.set_colormap(Colormap::Heat);
+    let mut rng = rand::thread_rng();
+    let mut model = Schelling::random((SPACE_GRID_SIZE, SPACE_GRID_SIZE), GROUPS, 0.1, 0.4, &mut rng);
+    matrix.on_frame(|window, info| window.set_title(&format!("Schelling frame: {}", info.frame)));
+    loop {
+        let moved = model.step(&mut rng);
+        if moved > 0 {
+            println!("moved: {}, segregation index: {:.3}", moved, model.segregation_index());
+        }
+        if matrix.render_frame(model.cells())? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,48 @@
+extern crate failure;
+extern crate my_alife;
+
+use my_alife::algorithm::game_of_life::random_cells;
+use my_alife::scripting::Script;
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+use std::env;
+
+const SPACE_GRID_SIZE: usize = 128;
+const DEFAULT_SCRIPT: &str = "res/scripts/life.rhai";
+
+// `cargo run --features scripting --example chap03_scripted_life [script]`
+// 実行中にスクリプトを保存すると規則が入れ替わる
+fn main() -> Result<(), failure::Error> {
+    let path = env::args().nth(1).unwrap_or_else(|| DEFAULT_SCRIPT.to_string());
+    let mut script = Script::load(&path)?;
+    script.set_param("paused", 0.0);
+    script.set_param("reset", 0.0);
+    let mut matrix = MatrixVisualizer::new(
+        "Scripted Life",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let mut cells = random_cells((SPACE_GRID_SIZE, SPACE_GRID_SIZE), 0.2);
+    loop {
+        match script.reload_if_changed() {
+            Ok(true) => println!("reloaded {}", path),
+            Ok(false) => {}
+            // 書きかけのスクリプトでは止めずに前の規則を使い続ける
+            Err(e) => println!("{}", e),
+        }
+        for key in matrix.pressed_keys().to_vec() {
+            script.handle("key", vec![format!("{:?}", key).into()])?;
+        }
+        if script.param("reset") == Some(1.0) {
+            cells = random_cells((SPACE_GRID_SIZE, SPACE_GRID_SIZE), 0.2);
+            script.set_param("reset", 0.0);
+        }
+        if script.param("paused") != Some(1.0) {
+            cells = script.step(&cells)?;
+        }
+        if matrix.render_frame(&cells)? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
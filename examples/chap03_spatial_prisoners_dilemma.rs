@@ -0,0 +1,45 @@
+extern crate failure;
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::graph::{Payoff, UpdateRule};
+use my_alife::algorithm::neighborhood::Neighborhood;
+use my_alife::algorithm::spatial_game::{transition_palette, SpatialGame};
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+
+// 対称な模様にするため奇数にする
+const SPACE_GRID_SIZE: usize = 199;
+// 裏切りの誘惑T。1.8〜2.0で万華鏡のような模様になる
+const TEMPTATION: f32 = 1.85;
+
+// Nowak & Mayの空間的な囚人のジレンマ。協力者の中に置いた1人の裏切り者から模様が広がる
+fn main() -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new(
+        "Spatial Prisoner's Dilemma",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let palette = transition_palette();
+    matrix.set_legend(Some(&palette));
+    let mut rng = rand::thread_rng();
+    let mut game = SpatialGame::single_defector(
+        (SPACE_GRID_SIZE, SPACE_GRID_SIZE),
+        Payoff::weak_prisoners_dilemma(TEMPTATION),
+        UpdateRule::Imitation,
+        Neighborhood::Moore,
+    );
+    loop {
+        game.step(&mut rng);
+        matrix.set_title(&format!(
+            "Spatial Prisoner's Dilemma (t={}, cooperation={:.3})",
+            game.time(),
+            game.cooperation()
+        ));
+        matrix.draw_palette(&game.transitions(), &palette)?;
+        if matrix.poll_events() == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,38 @@
+extern crate failure;
+extern crate my_alife;
+
+use my_alife::algorithm::von_neumann::{jvn_palette, VonNeumann};
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+use std::env;
+
+const DEFAULT_FILE: &str = "res/patterns/jvn_pulser_arm.rle";
+// パターンの周りにつける余白(セル)
+const MARGIN: usize = 64;
+
+// von Neumannの29状態セル・オートマトン
+// 引数でGollyのJvN29のパターン(.rleか.mc)を指定すると、万能構築機や自己複製機械も動かせる
+fn main() -> Result<(), failure::Error> {
+    let path = env::args().nth(1).unwrap_or_else(|| DEFAULT_FILE.to_string());
+    let mut ca = VonNeumann::load(&path, MARGIN)?;
+    let mut matrix = MatrixVisualizer::new(
+        "von Neumann 29-state",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let palette = jvn_palette();
+    matrix.set_legend(Some(&palette));
+    loop {
+        ca.step();
+        matrix.set_title(&format!(
+            "von Neumann 29-state (t={}, population={})",
+            ca.time(),
+            ca.population()
+        ));
+        matrix.draw_palette(ca.cells(), &palette)?;
+        if matrix.poll_events() == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
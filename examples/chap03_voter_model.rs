@@ -0,0 +1,43 @@
+extern crate failure;
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::neighborhood::Neighborhood;
+use my_alife::algorithm::opinion::{OpinionDynamics, OpinionRule};
+use my_alife::visualizer::colormap::Colormap;
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+
+const SPACE_GRID_SIZE: usize = 128;
+const NUM_OPINIONS: u8 = 4;
+
+fn main() -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new(
+        "Voter model",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    matrix.set_value_range(0.0, f32::from(NUM_OPINIONS - 1));
+    matrix.set_colormap(Colormap::Viridis);
+    let mut rng = rand::thread_rng();
+    let mut model = OpinionDynamics::random(
+        (SPACE_GRID_SIZE, SPACE_GRID_SIZE),
+        NUM_OPINIONS,
+        OpinionRule::Voter,
+        Neighborhood::VonNeumann,
+        &mut rng,
+    );
+    // 中央に意見0のzealotを置く
+    model.add_zealot((SPACE_GRID_SIZE / 2, SPACE_GRID_SIZE / 2), 0);
+    model.set_noise(1e-4);
+    loop {
+        model.step(&mut rng);
+        if model.is_consensus() {
+            println!("consensus at t={}", model.time());
+        }
+        if matrix.render_frame(model.opinions())? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,193 @@
+extern crate failure;
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::abm::{Agent, GridSpace, Model, Scheduler};
+use my_alife::algorithm::metabolism::{Accounting, EnergyLedger, Metabolic, Metabolism, MetabolismParams};
+use my_alife::algorithm::neighborhood::Neighborhood;
+use my_alife::visualizer::colormap::Colormap;
+use my_alife::visualizer::matrix_visualizer::{Matrix, MatrixVisualizer};
+use my_alife::visualizer::ControlFlow;
+use rand::Rng;
+use std::collections::HashSet;
+
+const SPACE_GRID_SIZE: usize = 128;
+const FISH_BREED: u32 = 3;
+const FISH_ENERGY: f32 = 2.0;
+const SHARK_METABOLISM: MetabolismParams = MetabolismParams {
+    maintenance: 1.0,
+    reproduction_threshold: 8.0,
+    offspring_share: 0.5,
+};
+
+const EMPTY: u8 = 0;
+const FISH: u8 = 1;
+const SHARK: u8 = 2;
+
+// 魚とサメのいる海。セルごとに誰がいるかを持つ
+struct Ocean {
+    space: GridSpace,
+    kinds: Matrix<u8>,
+    ids: Matrix<u32>,
+    dead: HashSet<u32>,
+    next_id: u32,
+    ledger: EnergyLedger,
+}
+
+impl Ocean {
+    fn spawn(&mut self, kind: u8, position: (usize, usize), metabolism: Option<Metabolism>) -> Creature {
+        self.next_id += 1;
+        self.kinds[position] = kind;
+        self.ids[position] = self.next_id;
+        Creature {
+            id: self.next_id,
+            kind,
+            position,
+            age: 0,
+            metabolism,
+        }
+    }
+}
+
+impl Accounting for Ocean {
+    fn ledger(&self) -> &EnergyLedger {
+        &self.ledger
+    }
+
+    fn ledger_mut(&mut self) -> &mut EnergyLedger {
+        &mut self.ledger
+    }
+}
+
+// 魚は年齢で、サメはエネルギーで繁殖する
+struct Creature {
+    id: u32,
+    kind: u8,
+    position: (usize, usize),
+    age: u32,
+    metabolism: Option<Metabolism>,
+}
+
+impl Metabolic for Creature {
+    fn metabolism(&self) -> Option<&Metabolism> {
+        self.metabolism.as_ref()
+    }
+}
+
+impl Agent<Ocean> for Creature {
+    // 空いているセルと魚のいるセル
+    type Perception = (Vec<(usize, usize)>, Vec<(usize, usize)>);
+    type Action = Option<(usize, usize)>;
+
+    fn perceive(&self, env: &Ocean, _agents: &[Creature]) -> Self::Perception {
+        let neighbors = env.space.free_neighbors(self.position);
+        let empty = neighbors
+            .iter()
+            .cloned()
+            .filter(|&cell| env.kinds[cell] == EMPTY)
+            .collect();
+        let fish = neighbors.into_iter().filter(|&cell| env.kinds[cell] == FISH).collect();
+        (empty, fish)
+    }
+
+    fn decide<R: Rng>(&mut self, (empty, fish): Self::Perception, rng: &mut R) -> Self::Action {
+        if self.kind == SHARK && !fish.is_empty() {
+            return rng.choose(&fish).cloned();
+        }
+        rng.choose(&empty).cloned()
+    }
+
+    fn act<R: Rng>(&mut self, target: Self::Action, env: &mut Ocean, _rng: &mut R) -> Vec<Creature> {
+        let mut offspring = Vec::new();
+        self.age += 1;
+        if let Some(target) = target {
+            if env.kinds[target] == FISH {
+                env.dead.insert(env.ids[target]);
+                if let Some(ref mut metabolism) = self.metabolism {
+                    metabolism.intake(FISH_ENERGY, &mut env.ledger);
+                }
+            }
+            let origin = self.position;
+            env.kinds[origin] = EMPTY;
+            env.kinds[target] = self.kind;
+            env.ids[target] = self.id;
+            self.position = target;
+            let child = match self.metabolism {
+                Some(ref mut metabolism) => metabolism.reproduce(&mut env.ledger).map(Some),
+                None if self.age >= FISH_BREED => Some(None),
+                None => None,
+            };
+            if let Some(metabolism) = child {
+                self.age = 0;
+                offspring.push(env.spawn(self.kind, origin, metabolism));
+            }
+        }
+        if let Some(ref mut metabolism) = self.metabolism {
+            metabolism.tick(&mut env.ledger);
+            if !metabolism.is_alive() {
+                env.dead.insert(self.id);
+                env.kinds[self.position] = EMPTY;
+            }
+        }
+        offspring
+    }
+
+    fn is_alive(&self, env: &Ocean) -> bool {
+        !env.dead.contains(&self.id)
+    }
+}
+
+fn main() -> Result<(), failure::Error> {
+    let dim = (SPACE_GRID_SIZE, SPACE_GRID_SIZE);
+    let mut space = GridSpace::new(dim);
+    space.set_neighborhood(Neighborhood::VonNeumann);
+    let ocean = Ocean {
+        space,
+        kinds: Matrix::zeros(dim),
+        ids: Matrix::zeros(dim),
+        dead: HashSet::new(),
+        next_id: 0,
+        ledger: EnergyLedger::new(),
+    };
+    let mut model = Model::new(ocean, Scheduler::Random);
+    let mut rng = rand::thread_rng();
+    for row in 0..SPACE_GRID_SIZE {
+        for col in 0..SPACE_GRID_SIZE {
+            let r = rng.gen::<f32>();
+            let creature = if r < 0.3 {
+                model.env_mut().spawn(FISH, (row, col), None)
+            } else if r < 0.35 {
+                let metabolism = Metabolism::new(SHARK_METABOLISM.reproduction_threshold / 2.0, SHARK_METABOLISM);
+                model.env_mut().spawn(SHARK, (row, col), Some(metabolism))
+            } else {
+                continue;
+            };
+            model.add_agent(creature);
+        }
+    }
+
+    let mut matrix = MatrixVisualizer::new(
+        "Wa-Tor",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    matrix.set_value_range(0.0, f32::from(SHARK));
+    matrix.set_colormap(Colormap::Heat);
+    loop {
+        let flow = model.step_accounted(&mut rng)?;
+        model.env_mut().dead.clear();
+        let sharks = model.agents().iter().filter(|creature| creature.kind == SHARK).count();
+        println!(
+            "fish: {}, sharks: {}, shark energy: {:.1} (intake {:.1}, maintenance {:.1})",
+            model.agents().len() - sharks,
+            sharks,
+            flow.total,
+            flow.intake,
+            flow.maintenance
+        );
+        if matrix.render_frame(&model.env().kinds)? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,64 @@
+extern crate failure;
+extern crate my_alife;
+extern crate ndarray;
+
+use my_alife::algorithm::game_of_life::{random_cells, ALIVE, DEAD};
+use my_alife::algorithm::life_like::LifeRule;
+use my_alife::algorithm::reaction_diffusion::discrete_laplacian;
+use my_alife::algorithm::world::World;
+use my_alife::visualizer::matrix_visualizer::{Matrix, MatrixVisualizer};
+use my_alife::visualizer::ControlFlow;
+use ndarray::Zip;
+
+const SPACE_GRID_SIZE: usize = 256;
+const DIFFUSION: f32 = 0.2;
+const REGROWTH: f32 = 0.002;
+const CONSUMPTION: f32 = 0.05;
+const HUNGER: f32 = 0.2;
+
+fn main() -> Result<(), failure::Error> {
+    let mut world = World::new();
+    world.add_layer("nutrient", Matrix::<f32>::ones((SPACE_GRID_SIZE, SPACE_GRID_SIZE)));
+    world.add_layer("life", random_cells((SPACE_GRID_SIZE, SPACE_GRID_SIZE), 0.3));
+
+    // 栄養は拡散しながらゆっくり回復する
+    world.add_update("nutrient", |nutrient: &mut Matrix<f32>, dt| {
+        let diffused = discrete_laplacian(nutrient, 1.0) * (DIFFUSION * dt);
+        *nutrient += &diffused;
+        nutrient.mapv_inplace(|n| (n + REGROWTH * dt).min(1.0));
+    });
+    // Game of Lifeのルールで1世代進める
+    let rule = LifeRule::conway();
+    world.add_update("life", move |life: &mut Matrix<u8>, _| *life = rule.step(life));
+    // 生きたセルは栄養を消費し、栄養の足りない場所では死ぬ
+    world.add_coupling(
+        "life",
+        "nutrient",
+        |life: &mut Matrix<u8>, nutrient: &mut Matrix<f32>, dt| {
+            Zip::from(life).and(nutrient).apply(|cell, n| {
+                if *cell == ALIVE {
+                    *n = (*n - CONSUMPTION * dt).max(0.0);
+                    if *n < HUNGER {
+                        *cell = DEAD;
+                    }
+                }
+            });
+        },
+    );
+
+    let mut matrix = MatrixVisualizer::new(
+        "World: life and nutrient",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    loop {
+        world.step(1.0)?;
+        let life = world.layer::<Matrix<u8>>("life").unwrap().mapv(f32::from);
+        let nutrient = world.layer::<Matrix<f32>>("nutrient").unwrap();
+        // 赤が生きたセル、緑が栄養
+        if matrix.render_channels(&[&life, nutrient], &[(0.0, 1.0), (0.0, 1.0)])? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,143 @@
+extern crate failure;
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::abm::{Agent, ContinuousSpace, Model, Scheduler};
+use my_alife::algorithm::evolution::{random_genome, GaParams, GeneticAlgorithm};
+use my_alife::algorithm::neural::{Controller, FeedForward};
+use my_alife::visualizer::matrix_visualizer::{Matrix, MatrixVisualizer};
+use my_alife::visualizer::ControlFlow;
+use rand::{Rng, SeedableRng, XorShiftRng};
+
+const SPACE_SIZE: f32 = 128.0;
+const FOOD_COUNT: usize = 40;
+const EAT_RADIUS: f32 = 2.0;
+const SPEED: f32 = 1.0;
+const MAX_TURN: f32 = 0.5;
+const SIMULATION_STEPS: usize = 300;
+const POPULATION: usize = 40;
+const GENERATIONS: usize = 30;
+// 入力は最も近い餌の方向(sin, cos)と距離、出力は回転の速さ
+const LAYERS: [usize; 3] = [3, 4, 1];
+
+struct Pond {
+    space: ContinuousSpace,
+    food: Vec<(f32, f32)>,
+}
+
+struct Forager {
+    position: (f32, f32),
+    heading: f32,
+    brain: FeedForward,
+    eaten: usize,
+}
+
+impl Agent<Pond> for Forager {
+    type Perception = Vec<f32>;
+    type Action = f32;
+
+    fn perceive(&self, env: &Pond, _agents: &[Forager]) -> Vec<f32> {
+        let nearest = env
+            .food
+            .iter()
+            .map(|&food| env.space.displacement(self.position, food))
+            .min_by(|a, b| (a.0.hypot(a.1)).partial_cmp(&b.0.hypot(b.1)).unwrap());
+        match nearest {
+            Some((dx, dy)) => {
+                let angle = dy.atan2(dx) - self.heading;
+                vec![angle.sin(), angle.cos(), dx.hypot(dy) / SPACE_SIZE]
+            }
+            None => vec![0.0, 0.0, 1.0],
+        }
+    }
+
+    fn decide<R: Rng>(&mut self, inputs: Vec<f32>, _rng: &mut R) -> f32 {
+        self.brain.act(&inputs)[0] * MAX_TURN
+    }
+
+    fn act<R: Rng>(&mut self, turn: f32, env: &mut Pond, rng: &mut R) -> Vec<Forager> {
+        self.heading += turn;
+        let (x, y) = self.position;
+        self.position = env
+            .space
+            .wrap((x + SPEED * self.heading.cos(), y + SPEED * self.heading.sin()));
+        let position = self.position;
+        let space = env.space;
+        if let Some(food) = env
+            .food
+            .iter()
+            .position(|&food| space.distance(position, food) <= EAT_RADIUS)
+        {
+            // 食べた餌は別の場所に生える
+            env.food[food] = space.random_position(rng);
+            self.eaten += 1;
+        }
+        Vec::new()
+    }
+}
+
+fn pond<R: Rng>(foragers: Vec<Forager>, rng: &mut R) -> Model<Forager, Pond> {
+    let space = ContinuousSpace::new(SPACE_SIZE, SPACE_SIZE);
+    let food = (0..FOOD_COUNT).map(|_| space.random_position(rng)).collect();
+    let mut model = Model::new(Pond { space, food }, Scheduler::Sequential);
+    for forager in foragers {
+        model.add_agent(forager);
+    }
+    model
+}
+
+fn forager<R: Rng>(genome: &[f32], rng: &mut R) -> Forager {
+    Forager {
+        position: (rng.gen_range(0.0, SPACE_SIZE), rng.gen_range(0.0, SPACE_SIZE)),
+        heading: rng.gen_range(0.0, 2.0 * std::f32::consts::PI),
+        brain: FeedForward::from_genome(&LAYERS, genome),
+        eaten: 0,
+    }
+}
+
+fn main() -> Result<(), failure::Error> {
+    let mut rng = rand::thread_rng();
+    let population = (0..POPULATION)
+        .map(|_| random_genome(FeedForward::genome_len(&LAYERS), 1.0, &mut rng))
+        .collect();
+    let mut ga = GeneticAlgorithm::new(population, GaParams::default());
+    for generation in 0..GENERATIONS {
+        // 同じ世代の個体は同じ餌の配置で比べる
+        let seed = [generation as u32 + 1, 2, 3, 4];
+        ga.evaluate(|genome: &Vec<f32>| {
+            let mut world_rng = XorShiftRng::from_seed(seed);
+            let agent = forager(genome, &mut world_rng);
+            let mut model = pond(vec![agent], &mut world_rng);
+            for _ in 0..SIMULATION_STEPS {
+                model.step(&mut world_rng);
+            }
+            model.agents()[0].eaten as f32
+        });
+        println!("generation {}: best food eaten {}", generation, ga.best().unwrap().1);
+        ga.next_generation(&mut rng);
+    }
+
+    // 最後の世代の個体を同じ池に放す
+    let foragers = ga.population().iter().map(|genome| forager(genome, &mut rng)).collect();
+    let mut model = pond(foragers, &mut rng);
+    let mut matrix = MatrixVisualizer::new(
+        "Evolved foragers",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let cell = |(x, y): (f32, f32)| (y as usize % SPACE_SIZE as usize, x as usize % SPACE_SIZE as usize);
+    loop {
+        model.step(&mut rng);
+        let mut image = Matrix::<f32>::zeros((SPACE_SIZE as usize, SPACE_SIZE as usize));
+        for &food in &model.env().food {
+            image[cell(food)] = 0.5;
+        }
+        for forager in model.agents() {
+            image[cell(forager.position)] = 1.0;
+        }
+        if matrix.render_frame(&image)? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,53 @@
+extern crate failure;
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::evolution::{random_genome, GaParams, GeneticAlgorithm};
+use my_alife::visualizer::colormap::Colormap;
+use my_alife::visualizer::landscape::FitnessLandscape;
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+use std::f32::consts::PI;
+
+const RESOLUTION: usize = 256;
+const POPULATION: usize = 40;
+
+// 局所解の多いRastrigin関数の符号を反転したもの。最大値は原点
+fn fitness(genes: &[f32]) -> f32 {
+    -genes
+        .iter()
+        .map(|x| x * x - 10.0 * (2.0 * PI * x).cos() + 10.0)
+        .sum::<f32>()
+}
+
+fn main() -> Result<(), failure::Error> {
+    let mut rng = rand::thread_rng();
+    let mut landscape = FitnessLandscape::evaluate(
+        &[0.0, 0.0],
+        (0, 1),
+        (-5.12, 5.12),
+        (-5.12, 5.12),
+        (RESOLUTION, RESOLUTION),
+        fitness,
+    );
+    // 原点から離れた場所から探索を始める
+    let population = (0..POPULATION)
+        .map(|_| random_genome(2, 0.5, &mut rng).iter().map(|g| g + 3.5).collect())
+        .collect();
+    let mut ga = GeneticAlgorithm::new(population, GaParams::default());
+
+    let mut matrix = MatrixVisualizer::new(
+        "Fitness landscape",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    matrix.set_colormap(Colormap::Heat);
+    loop {
+        ga.run(1, |genes: &Vec<f32>| fitness(genes), &mut rng);
+        println!("generation {}: best {:.3}", ga.generation(), ga.best().unwrap().1);
+        if landscape.render(&mut matrix, ga.population())? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
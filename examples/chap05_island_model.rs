@@ -0,0 +1,50 @@
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::evolution::{random_genome, GaParams};
+use my_alife::algorithm::island::{IslandModel, Migration, Topology};
+use std::f32::consts::PI;
+
+const ISLANDS: usize = 8;
+const POPULATION: usize = 50;
+const DIMENSION: usize = 10;
+const GENERATIONS: usize = 300;
+
+// 局所解の多いRastrigin関数を最小化する
+fn fitness(genes: &[f32]) -> f32 {
+    let rastrigin: f32 = genes.iter().map(|x| x * x - 10.0 * (2.0 * PI * x).cos() + 10.0).sum();
+    -rastrigin
+}
+
+fn main() {
+    let mut rng = rand::thread_rng();
+    for &(name, ref topology) in &[
+        ("isolated", Topology::Isolated),
+        ("ring", Topology::Ring),
+        ("fully connected", Topology::FullyConnected),
+    ] {
+        let islands = (0..ISLANDS)
+            .map(|_| {
+                (0..POPULATION)
+                    .map(|_| random_genome(DIMENSION, 5.12, &mut rng))
+                    .collect()
+            })
+            .collect();
+        let migration = Migration {
+            interval: 20,
+            migrants: 2,
+        };
+        let mut model = IslandModel::new(islands, GaParams::default(), topology.clone(), migration, &mut rng);
+        model.run(GENERATIONS, &|genes: &Vec<f32>| fitness(genes));
+        println!("{}:", name);
+        for (i, history) in model.history().iter().enumerate() {
+            let bests: Vec<String> = history
+                .iter()
+                .step_by(5)
+                .map(|&(_, best)| format!("{:.1}", best))
+                .collect();
+            println!("  island {}: {}", i, bests.join(" "));
+        }
+        println!("  best: {:.3}", model.best().unwrap().1);
+    }
+}
@@ -0,0 +1,57 @@
+extern crate failure;
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::evolution::random_genome;
+use my_alife::algorithm::quality_diversity::MapElites;
+use my_alife::visualizer::colormap::Colormap;
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+use std::f32::consts::PI;
+
+const JOINTS: usize = 8;
+const BINS: usize = 64;
+const BATCH: usize = 100;
+
+// 平面上の多関節アーム。行動は先端の位置、適応度は関節角のばらつきの小ささ
+fn evaluate(angles: &Vec<f32>) -> (f32, Vec<f32>) {
+    let mut heading = 0.0;
+    let (mut x, mut y) = (0.0, 0.0);
+    for &angle in angles {
+        heading += angle * PI;
+        x += heading.cos() / JOINTS as f32;
+        y += heading.sin() / JOINTS as f32;
+    }
+    let mean = angles.iter().sum::<f32>() / JOINTS as f32;
+    let variance = angles.iter().map(|a| (a - mean).powi(2)).sum::<f32>() / JOINTS as f32;
+    (-variance, vec![y, x])
+}
+
+fn main() -> Result<(), failure::Error> {
+    let mut rng = rand::thread_rng();
+    let mut map = MapElites::new(&[BINS, BINS], &[(-1.0, 1.0), (-1.0, 1.0)], 0.2);
+    let initial = (0..BATCH).map(|_| random_genome(JOINTS, 1.0, &mut rng)).collect();
+    map.initialize(initial, evaluate);
+
+    let mut matrix = MatrixVisualizer::new(
+        "MAP-Elites: fitness of the elites",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    matrix.set_value_range(-0.5, 0.0);
+    matrix.set_colormap(Colormap::Heat);
+    loop {
+        map.step(BATCH, evaluate, &mut rng);
+        println!(
+            "evaluations: {}, coverage: {:.3}, QD score: {:.2}",
+            map.evaluations(),
+            map.coverage(),
+            map.qd_score()
+        );
+        // 空の格子は最も低い色で表示する
+        if matrix.render_frame(&map.fitness_map(-0.5))? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
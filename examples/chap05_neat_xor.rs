@@ -0,0 +1,84 @@
+extern crate failure;
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::neat::{Neat, NeatGenome, NeatParams};
+use my_alife::algorithm::neural::Controller;
+use std::env;
+use std::fs;
+
+const GENERATIONS: usize = 200;
+const XOR: [([f32; 2], f32); 4] = [
+    ([0.0, 0.0], 0.0),
+    ([0.0, 1.0], 1.0),
+    ([1.0, 0.0], 1.0),
+    ([1.0, 1.0], 0.0),
+];
+
+// 誤差の二乗和を4から引いたもの。すべて正解すると4になる
+fn fitness(genome: &NeatGenome) -> f32 {
+    let mut network = genome.network();
+    let error: f32 = XOR
+        .iter()
+        .map(|&(inputs, expected)| (network.act(&inputs)[0] - expected).powi(2))
+        .sum();
+    (4.0 - error).max(0.0)
+}
+
+fn solves(genome: &NeatGenome) -> bool {
+    let mut network = genome.network();
+    XOR.iter()
+        .all(|&(inputs, expected)| (network.act(&inputs)[0] - expected).abs() < 0.5)
+}
+
+// 引数にパスを渡すと、見つかった最良の遺伝子型をそこに、系統をNewick形式とCSVで書き出す
+fn main() -> Result<(), failure::Error> {
+    let mut rng = rand::thread_rng();
+    let mut neat = Neat::new(2, 1, NeatParams::default(), &mut rng);
+    neat.enable_phylogeny();
+    for _ in 0..GENERATIONS {
+        neat.evaluate(fitness);
+        let (champion, best) = neat.champion().unwrap();
+        println!(
+            "generation {}: species {}, best fitness {:.3}, hidden nodes {}",
+            neat.generation(),
+            neat.species().len(),
+            best,
+            champion.nodes().len() - 4
+        );
+        if solves(champion) {
+            break;
+        }
+        neat.next_generation(&mut rng);
+    }
+
+    let (champion, _) = neat.champion().unwrap();
+    let mut network = champion.network();
+    for &(inputs, expected) in &XOR {
+        println!("{:?} -> {:.3} (expected {})", inputs, network.act(&inputs)[0], expected);
+    }
+    // チャンピオンに至るまでに加わった構造の変化
+    let phylogeny = neat.phylogeny().unwrap();
+    for id in phylogeny.lineage(neat.champion_id().unwrap()).into_iter().rev() {
+        let record = phylogeny.get(id).unwrap();
+        let structural: Vec<&String> = record.mutations.iter().filter(|m| m.starts_with("add_")).collect();
+        if !structural.is_empty() {
+            println!("generation {}: {:?}", record.generation, structural);
+        }
+    }
+    match env::args().nth(1) {
+        Some(path) => {
+            champion.save(&path)?;
+            let mut phylogeny = phylogeny.clone();
+            phylogeny.prune(neat.ids().unwrap());
+            fs::write(format!("{}.newick", path), phylogeny.to_newick())?;
+            fs::write(format!("{}.csv", path), phylogeny.to_csv())?;
+            println!(
+                "saved the champion to {} and its phylogeny to {}.newick / {}.csv",
+                path, path, path
+            );
+        }
+        None => print!("{}", champion),
+    }
+    Ok(())
+}
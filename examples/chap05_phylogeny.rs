@@ -0,0 +1,37 @@
+extern crate failure;
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::evolution::{random_genome, GaParams, GeneticAlgorithm};
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+
+const POPULATION: usize = 30;
+const GENERATIONS: usize = 100;
+
+// 世代を重ねるごとに、今の集団の祖先だけを残した系統樹を表示する
+fn main() -> Result<(), failure::Error> {
+    let mut rng = rand::thread_rng();
+    let population = (0..POPULATION).map(|_| random_genome(4, 1.0, &mut rng)).collect();
+    let mut ga = GeneticAlgorithm::new(population, GaParams::default());
+    ga.enable_phylogeny();
+    let fitness = |genes: &Vec<f32>| -genes.iter().map(|g| (g - 0.5).powi(2)).sum::<f32>();
+
+    let mut matrix = MatrixVisualizer::new(
+        "Phylogeny of the living population",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    loop {
+        if ga.generation() < GENERATIONS {
+            ga.run(1, fitness, &mut rng);
+        }
+        let mut phylogeny = ga.phylogeny().unwrap().clone();
+        phylogeny.prune(ga.ids().unwrap());
+        if matrix.render_frame(&phylogeny.tree_image())? == ControlFlow::Stop {
+            break;
+        }
+    }
+    println!("{}", ga.phylogeny().unwrap().to_newick());
+    Ok(())
+}
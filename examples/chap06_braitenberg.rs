@@ -0,0 +1,89 @@
+extern crate failure;
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::abm::ContinuousSpace;
+use my_alife::algorithm::braitenberg::{Braitenberg, Source, StimulusField, Wiring};
+use my_alife::visualizer::agent_visualizer::{AgentVisualizer, RenderMode};
+use my_alife::visualizer::colormap::Colormap;
+use my_alife::visualizer::texture::ValueMapping;
+use my_alife::visualizer::ControlFlow;
+use my_alife::visualizer::VirtualKeyCode;
+
+const WORLD_SIZE: f32 = 200.0;
+// ヒートマップの解像度
+const FIELD_GRID_SIZE: usize = 100;
+const VEHICLES_PER_WIRING: usize = 4;
+const DT: f32 = 0.05;
+// ビークルが出す刺激の(強さ, 広がり)
+const EMISSION: (f32, f32) = (0.5, 6.0);
+
+// 光源の場の中を走るBraitenbergのビークル。色はつなぎ方ごとに違い、通った跡が薄れながら残る
+// クリックで光源を置き、Eキーでビークルどうしが刺激を出し合うかどうかを切り替える
+fn main() -> Result<(), failure::Error> {
+    let space = ContinuousSpace::new(WORLD_SIZE, WORLD_SIZE);
+    let mut field = StimulusField::new(space);
+    for &position in &[(60.0, 60.0), (140.0, 90.0), (90.0, 150.0)] {
+        field.add_source(Source {
+            position,
+            intensity: 1.0,
+            radius: 15.0,
+        });
+    }
+    let mut world = Braitenberg::new(field);
+    world.set_noise(0.3);
+    let mut rng = rand::thread_rng();
+    for &wiring in &[
+        Wiring::Type1,
+        Wiring::Type2a,
+        Wiring::Type2b,
+        Wiring::Type3a,
+        Wiring::Type3b,
+        Wiring::Type4a,
+        Wiring::Type4b,
+    ] {
+        world.add_random_vehicles(VEHICLES_PER_WIRING, wiring, &mut rng);
+    }
+
+    let mut visualizer = AgentVisualizer::new("Braitenberg vehicles", (WORLD_SIZE, WORLD_SIZE))?;
+    visualizer.set_mode(RenderMode::Decay(0.97));
+    visualizer.set_mapping(ValueMapping {
+        range: (0.0, 1.5),
+        colormap: Colormap::Viridis,
+        ..ValueMapping::default()
+    });
+    let mut heatmap = world.field().render((FIELD_GRID_SIZE, FIELD_GRID_SIZE));
+    let mut clicked = false;
+    loop {
+        world.step(DT, &mut rng);
+        // クリックした場所に光源を置く
+        if visualizer.mouse().left && !clicked {
+            if let Some(position) = visualizer.mouse_position() {
+                world.field_mut().add_source(Source {
+                    position,
+                    intensity: 1.0,
+                    radius: 15.0,
+                });
+                heatmap = world.field().render((FIELD_GRID_SIZE, FIELD_GRID_SIZE));
+            }
+        }
+        clicked = visualizer.mouse().left;
+        if visualizer.pressed_keys().contains(&VirtualKeyCode::E) {
+            let emission = match world.emission() {
+                Some(_) => None,
+                None => Some(EMISSION),
+            };
+            world.set_emission(emission);
+        }
+        visualizer.set_title(&format!(
+            "Braitenberg vehicles (t={:.1}, sources={}, interaction={})",
+            world.time(),
+            world.field().sources().len(),
+            world.emission().is_some()
+        ));
+        if visualizer.draw(Some(&heatmap), &world.sprites())? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
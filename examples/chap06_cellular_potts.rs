@@ -0,0 +1,67 @@
+extern crate failure;
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::potts::{cell_type_palette, id_palette, CellularPotts, PottsParams};
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+use my_alife::visualizer::VirtualKeyCode;
+
+const SPACE_GRID_SIZE: usize = 100;
+const CELL_SIZE: usize = 5;
+const TISSUE_RADIUS: usize = 40;
+const TEMPERATURE_STEP: f32 = 1.0;
+
+// Cellular Pottsモデルの細胞選別。ばらばらに混ざった2種類の細胞が、暗い細胞を内側にして分かれていく
+// 細胞ごとに色を変えて境界を白く描く。Tキーで種類ごとの表示に切り替え、上下キーで温度を変える
+fn main() -> Result<(), failure::Error> {
+    let mut rng = rand::thread_rng();
+    let mut potts = CellularPotts::cell_sorting(
+        (SPACE_GRID_SIZE, SPACE_GRID_SIZE),
+        CELL_SIZE,
+        TISSUE_RADIUS,
+        PottsParams::cell_sorting(),
+        &mut rng,
+    );
+    let mut matrix = MatrixVisualizer::new(
+        "Cellular Potts model",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    let (ids, types) = (id_palette(), cell_type_palette());
+    matrix.set_legend(Some(&ids));
+    let mut show_types = false;
+    loop {
+        potts.step(&mut rng);
+        for key in matrix.pressed_keys().to_vec() {
+            match key {
+                VirtualKeyCode::T => {
+                    show_types = !show_types;
+                    matrix.set_legend(Some(if show_types { &types } else { &ids }));
+                }
+                VirtualKeyCode::Up => potts.params_mut().temperature += TEMPERATURE_STEP,
+                VirtualKeyCode::Down => {
+                    let temperature = potts.params().temperature;
+                    potts.params_mut().temperature = (temperature - TEMPERATURE_STEP).max(TEMPERATURE_STEP);
+                }
+                _ => {}
+            }
+        }
+        matrix.set_title(&format!(
+            "Cellular Potts model (MCS={}, T={}, dark-light boundary={}, energy={:.0})",
+            potts.time(),
+            potts.params().temperature,
+            potts.boundary_length(1, 2),
+            potts.energy()
+        ));
+        if show_types {
+            matrix.draw_palette(&potts.cell_types(), &types)?;
+        } else {
+            matrix.draw_palette(&potts.id_map(), &ids)?;
+        }
+        if matrix.poll_events() == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+extern crate failure;
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::chemotaxis::{Chemotaxis, NutrientField};
+use my_alife::visualizer::agent_visualizer::AgentVisualizer;
+use my_alife::visualizer::colormap::Colormap;
+use my_alife::visualizer::texture::ValueMapping;
+use my_alife::visualizer::ControlFlow;
+use my_alife::visualizer::VirtualKeyCode;
+
+const GRID_SIZE: usize = 80;
+const DX: f32 = 2.5;
+const BACTERIA: usize = 400;
+const DT: f32 = 0.1;
+const GAIN: f32 = 5.0;
+const UPTAKE: f32 = 0.5;
+const SECRETION: f32 = 0.3;
+
+// 栄養の湧き出すところへ、走って向きを変えながら集まる細菌
+// クリックで栄養の湧き出すところを置く。Gキーで走化性、Uキーで栄養を食べるか、Sキーで誘引物質を出すかを切り替える
+fn main() -> Result<(), failure::Error> {
+    let mut nutrient = NutrientField::new((GRID_SIZE, GRID_SIZE), DX);
+    nutrient.diffusion = 4.0;
+    nutrient.add_source((GRID_SIZE / 3, GRID_SIZE / 3), 2, 1.0);
+    nutrient.add_source((GRID_SIZE * 2 / 3, GRID_SIZE * 3 / 5), 2, 0.6);
+    let mut model = Chemotaxis::new(nutrient);
+    model.set_speed(5.0);
+    model.set_gain(GAIN);
+    model.equilibrate(300, 1.0);
+    let mut rng = rand::thread_rng();
+    model.add_random_bacteria(BACTERIA, &mut rng);
+
+    let world = (model.space().width, model.space().height);
+    let mut visualizer = AgentVisualizer::new("Chemotaxis", world)?;
+    visualizer.set_agent_radius(2.0);
+    visualizer.set_mapping(ValueMapping {
+        range: (0.0, 5.0),
+        colormap: Colormap::Viridis,
+        ..ValueMapping::default()
+    });
+    let (mut gain, mut uptake, mut secretion) = (true, false, false);
+    let mut clicked = false;
+    loop {
+        model.step(DT, &mut rng);
+        // クリックしたセルから栄養を湧き出させる
+        if visualizer.mouse().left && !clicked {
+            if let Some((x, y)) = visualizer.mouse_position() {
+                let cell = ((y / DX) as usize, (x / DX) as usize);
+                model.nutrient_mut().add_source(cell, 2, 1.0);
+            }
+        }
+        clicked = visualizer.mouse().left;
+        for key in visualizer.pressed_keys() {
+            match *key {
+                VirtualKeyCode::G => gain = !gain,
+                VirtualKeyCode::U => uptake = !uptake,
+                VirtualKeyCode::S => secretion = !secretion,
+                _ => {}
+            }
+        }
+        model.set_gain(if gain { GAIN } else { 0.0 });
+        model.set_uptake(if uptake { UPTAKE } else { 0.0 });
+        model.set_secretion(if secretion { SECRETION } else { 0.0 });
+        visualizer.set_title(&format!(
+            "Chemotaxis (t={:.1}, sensed={:.2}, up-gradient={:.2}, mean run={:.2}, gain={}, uptake={}, secretion={})",
+            model.time(),
+            model.mean_sensed(),
+            model.up_gradient_fraction(),
+            model.mean_run().unwrap_or(0.0),
+            gain,
+            uptake,
+            secretion
+        ));
+        if visualizer.draw(Some(model.concentration()), &model.sprites())? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
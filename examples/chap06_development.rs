@@ -0,0 +1,86 @@
+extern crate failure;
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::development::{cell_type_palette, CellType, Development, DevelopmentRules};
+use my_alife::visualizer::colormap::Colormap;
+use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+use my_alife::visualizer::ControlFlow;
+use my_alife::visualizer::VirtualKeyCode;
+
+const ROWS: usize = 48;
+const COLS: usize = 96;
+const SEED_SPACING: usize = 6;
+// 右クリックで細胞を取り除く半径
+const ABLATION_RADIUS: isize = 3;
+
+#[derive(Clone, Copy, PartialEq)]
+enum View {
+    CellType,
+    Lineage,
+    Morphogen,
+}
+
+// オーガナイザー(左端)の出すモルフォゲンで、幹細胞が分裂しながら青・白・赤の帯に分化する
+// 左クリックでオーガナイザーを移植し、右クリックで細胞を取り除く。Lキーで系統、Mキーでモルフォゲンの表示に切り替える
+fn main() -> Result<(), failure::Error> {
+    let mut development = Development::french_flag((ROWS, COLS), SEED_SPACING, DevelopmentRules::default());
+    let mut matrix = MatrixVisualizer::new(
+        "Development",
+        "res/shaders/matrix_visualizer_vertex.glsl",
+        "res/shaders/matrix_visualizer_fragment.glsl",
+    )?;
+    matrix.set_colormap(Colormap::Heat);
+    let cell_palette = cell_type_palette();
+    matrix.set_legend(Some(&cell_palette));
+    let mut view = View::CellType;
+    let mut rng = rand::thread_rng();
+    loop {
+        development.step(&mut rng);
+        if let Some((row, col)) = matrix.mouse_cell() {
+            let organizer = development.get((row, col)).map(|cell| cell.cell_type) == Some(CellType::Organizer);
+            if matrix.mouse().left && !organizer {
+                development.place((row, col), CellType::Organizer);
+            } else if matrix.mouse().right {
+                for dr in -ABLATION_RADIUS..=ABLATION_RADIUS {
+                    for dc in -ABLATION_RADIUS..=ABLATION_RADIUS {
+                        let (r, c) = (row as isize + dr, col as isize + dc);
+                        if r >= 0 && c >= 0 && (r as usize) < ROWS && (c as usize) < COLS {
+                            development.remove((r as usize, c as usize));
+                        }
+                    }
+                }
+            }
+        }
+        for key in matrix.pressed_keys().to_vec() {
+            let next = match key {
+                VirtualKeyCode::L => View::Lineage,
+                VirtualKeyCode::M => View::Morphogen,
+                _ => continue,
+            };
+            view = if view == next { View::CellType } else { next };
+            matrix.set_legend(if view == View::CellType {
+                Some(&cell_palette)
+            } else {
+                None
+            });
+        }
+        matrix.set_title(&format!(
+            "Development (t={}, cells={}, stem={}, divisions={}, deaths={})",
+            development.time(),
+            development.population(),
+            development.count(CellType::Stem),
+            development.divisions(),
+            development.deaths()
+        ));
+        match view {
+            View::CellType => matrix.draw_palette(&development.cell_types(), &cell_palette)?,
+            View::Lineage => matrix.draw_palette(&development.lineages(), &development.lineage_palette())?,
+            View::Morphogen => matrix.draw(development.morphogen())?,
+        }
+        if matrix.poll_events() == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
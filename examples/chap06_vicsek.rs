@@ -0,0 +1,79 @@
+extern crate failure;
+extern crate my_alife;
+extern crate rand;
+
+use my_alife::algorithm::vicsek::{order_parameter_curve, Vicsek, VicsekParams};
+use my_alife::visualizer::agent_visualizer::{AgentVisualizer, RenderMode};
+use my_alife::visualizer::ControlFlow;
+use my_alife::visualizer::VirtualKeyCode;
+use std::env;
+use std::f32::consts::PI;
+
+const PARTICLES: usize = 400;
+const DENSITY: f32 = 4.0;
+const NOISE_STEP: f32 = 0.1;
+// 左右キーで増減させる粒子の数
+const PARTICLE_STEP: usize = 50;
+// 相転移の曲線を測るときのノイズの刻みと、ステップ数
+const SWEEP_POINTS: usize = 13;
+const SWEEP_TRANSIENT: usize = 300;
+const SWEEP_SAMPLES: usize = 200;
+
+// Vicsekモデルの群れ。色は進む向きで、向きがそろうと同じ色の群れになる
+// 上下キーでノイズ、左右キーで粒子の数(密度)を変える
+// 引数に`sweep`を付けると、ウィンドウを開かずにノイズごとの秩序変数を表にして出力する
+fn main() -> Result<(), failure::Error> {
+    let mut rng = rand::thread_rng();
+    if env::args().nth(1).as_deref() == Some("sweep") {
+        let noises: Vec<_> = (0..SWEEP_POINTS)
+            .map(|i| i as f32 * 2.0 * PI / (SWEEP_POINTS - 1) as f32)
+            .collect();
+        println!("noise\tmean\tstd_dev");
+        let curve = order_parameter_curve(
+            PARTICLES,
+            DENSITY,
+            VicsekParams::default(),
+            &noises,
+            SWEEP_TRANSIENT,
+            SWEEP_SAMPLES,
+            &mut rng,
+        );
+        for (noise, order) in curve {
+            println!("{:.3}\t{:.3}\t{:.3}", noise, order.mean(), order.std_dev());
+        }
+        return Ok(());
+    }
+
+    let mut vicsek = Vicsek::with_density(PARTICLES, DENSITY, VicsekParams::default(), &mut rng);
+    let world = (vicsek.space().width, vicsek.space().height);
+    let mut visualizer = AgentVisualizer::new("Vicsek model", world)?;
+    visualizer.set_mode(RenderMode::Decay(0.8));
+    visualizer.set_agent_radius(2.0);
+    loop {
+        vicsek.step(&mut rng);
+        for key in visualizer.pressed_keys().to_vec() {
+            let noise = vicsek.params().noise;
+            match key {
+                VirtualKeyCode::Up => vicsek.set_noise(noise + NOISE_STEP),
+                VirtualKeyCode::Down => vicsek.set_noise(noise - NOISE_STEP),
+                VirtualKeyCode::Right => vicsek.add_random_particles(PARTICLE_STEP, &mut rng),
+                VirtualKeyCode::Left => {
+                    let count = vicsek.particles().len().saturating_sub(PARTICLE_STEP);
+                    vicsek.particles_mut().truncate(count);
+                }
+                _ => {}
+            }
+        }
+        visualizer.set_title(&format!(
+            "Vicsek model (t={}, noise={:.2}, density={:.2}, order={:.3})",
+            vicsek.time(),
+            vicsek.params().noise,
+            vicsek.density(),
+            vicsek.order_parameter()
+        ));
+        if visualizer.draw(None, &vicsek.sprites())? == ControlFlow::Stop {
+            break;
+        }
+    }
+    Ok(())
+}
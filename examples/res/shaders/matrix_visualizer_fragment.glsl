@@ -1,10 +1,46 @@
 #version 140
 
 uniform sampler2D u_texture;
+// R8やR32Fのようにrチャンネルしか持たないテクスチャのときtrue
+uniform bool u_single_channel;
+// rチャンネルの値を0〜1に正規化するときの(最小値, 最大値)
+uniform vec2 u_value_range;
+// 0: grayscale, 1: heat, 2: viridis (visualizer::colormap::Colormapと対応)
+uniform int u_colormap;
+// NaN/Infをマゼンタで表示するかどうか
+uniform bool u_highlight_non_finite;
 in vec2 v_texcoord;
 out vec4 flagColor;
+
+vec3 colormap(float x)
+{
+    if (u_colormap == 1) {
+        return clamp(vec3(3.0 * x, 3.0 * x - 1.0, 3.0 * x - 2.0), 0.0, 1.0);
+    }
+    if (u_colormap == 2) {
+        vec3 c0 = vec3(0.2777273, 0.0054073, 0.3340998);
+        vec3 c1 = vec3(0.1050930, 1.4046135, 1.3845902);
+        vec3 c2 = vec3(-0.3308618, 0.2148476, 0.0950952);
+        vec3 c3 = vec3(-4.6342305, -5.7991010, -19.3324410);
+        vec3 c4 = vec3(6.2282699, 14.1799334, 56.6905526);
+        vec3 c5 = vec3(4.7763850, -13.7451454, -65.3530326);
+        vec3 c6 = vec3(-5.4354559, 4.6458526, 26.3124352);
+        return clamp(c0 + x * (c1 + x * (c2 + x * (c3 + x * (c4 + x * (c5 + x * c6))))), 0.0, 1.0);
+    }
+    return vec3(x);
+}
+
 void main()
 {
-    float r = texture(u_texture, v_texcoord).r;
-    flagColor = vec4(r,r,r,1);
+    vec4 t = texture(u_texture, v_texcoord);
+    if (!u_single_channel) {
+        flagColor = vec4(t.rgb, 1);
+        return;
+    }
+    if (u_highlight_non_finite && (isnan(t.r) || isinf(t.r))) {
+        flagColor = vec4(1, 0, 1, 1);
+        return;
+    }
+    float x = clamp((t.r - u_value_range.x) / (u_value_range.y - u_value_range.x), 0.0, 1.0);
+    flagColor = vec4(colormap(x), 1);
 }
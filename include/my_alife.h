@@ -0,0 +1,130 @@
+#ifndef MY_ALIFE_H
+#define MY_ALIFE_H
+
+/* This file is generated by cbindgen. Do not edit it by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Game of Lifeの状態
+ */
+typedef struct MyAlifeGameOfLife MyAlifeGameOfLife;
+
+/**
+ * Gray-Scottモデルの状態
+ */
+typedef struct MyAlifeGrayScott MyAlifeGrayScott;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Gray-Scottモデルを生成する
+ */
+struct MyAlifeGrayScott *my_alife_gray_scott_new(float f, float k);
+
+/**
+ * 描画1フレーム分の計算を`frames`回行う
+ *
+ * # Safety
+ * `model`は`my_alife_gray_scott_new`で生成して、まだ破棄していないものであること
+ */
+void my_alife_gray_scott_step(struct MyAlifeGrayScott *model,
+                              uintptr_t frames);
+
+/**
+ * パラメータを変更する
+ *
+ * # Safety
+ * `model`は`my_alife_gray_scott_new`で生成して、まだ破棄していないものであること
+ */
+void my_alife_gray_scott_set_params(struct MyAlifeGrayScott *model,
+                                    float f,
+                                    float k);
+
+/**
+ * uの濃度のbuffer(行優先)。`rows`と`cols`に大きさを書き込む
+ *
+ * # Safety
+ * `model`は`my_alife_gray_scott_new`で生成して、まだ破棄していないものであること。
+ * `rows`と`cols`はnullか書き込めるポインタであること
+ */
+const float *my_alife_gray_scott_u(const struct MyAlifeGrayScott *model,
+                                   uintptr_t *rows,
+                                   uintptr_t *cols);
+
+/**
+ * vの濃度のbuffer(行優先)。`rows`と`cols`に大きさを書き込む
+ *
+ * # Safety
+ * `my_alife_gray_scott_u`と同じ
+ */
+const float *my_alife_gray_scott_v(const struct MyAlifeGrayScott *model,
+                                   uintptr_t *rows,
+                                   uintptr_t *cols);
+
+/**
+ * Gray-Scottモデルを破棄する。nullの場合は何もしない
+ *
+ * # Safety
+ * `model`は`my_alife_gray_scott_new`で生成して、まだ破棄していないものであること
+ */
+void my_alife_gray_scott_free(struct MyAlifeGrayScott *model);
+
+/**
+ * 生きているセルの割合が`density`のGame of Lifeを生成する
+ *
+ * # Example
+ * ```
+ * use my_alife::ffi::*;
+ *
+ * let (mut rows, mut cols) = (0, 0);
+ * unsafe {
+ *     let life = my_alife_game_of_life_new(32, 16, 0.2);
+ *     my_alife_game_of_life_step(life, 10);
+ *     let cells = my_alife_game_of_life_cells(life, &mut rows, &mut cols);
+ *     assert!(!cells.is_null());
+ *     assert_eq!((rows, cols), (32, 16));
+ *     my_alife_game_of_life_free(life);
+ * }
+ * ```
+ */
+struct MyAlifeGameOfLife *my_alife_game_of_life_new(uintptr_t rows, uintptr_t cols, float density);
+
+/**
+ * `steps`ステップ進める
+ *
+ * # Safety
+ * `model`は`my_alife_game_of_life_new`で生成して、まだ破棄していないものであること
+ */
+void my_alife_game_of_life_step(struct MyAlifeGameOfLife *model,
+                                uintptr_t steps);
+
+/**
+ * セルのbuffer(行優先、生きているセルが1)。`rows`と`cols`に大きさを書き込む
+ *
+ * # Safety
+ * `model`は`my_alife_game_of_life_new`で生成して、まだ破棄していないものであること。
+ * `rows`と`cols`はnullか書き込めるポインタであること
+ */
+const uint8_t *my_alife_game_of_life_cells(const struct MyAlifeGameOfLife *model,
+                                           uintptr_t *rows,
+                                           uintptr_t *cols);
+
+/**
+ * Game of Lifeを破棄する。nullの場合は何もしない
+ *
+ * # Safety
+ * `model`は`my_alife_game_of_life_new`で生成して、まだ破棄していないものであること
+ */
+void my_alife_game_of_life_free(struct MyAlifeGameOfLife *model);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* MY_ALIFE_H */
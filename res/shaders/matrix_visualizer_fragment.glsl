@@ -1,10 +1,91 @@
 #version 140
 
 uniform sampler2D u_texture;
+// R8やR32Fのようにrチャンネルしか持たないテクスチャのときtrue
+uniform bool u_single_channel;
+// rチャンネルの値を0〜1に正規化するときの(最小値, 最大値)
+uniform vec2 u_value_range;
+// 0: linear, 1: log, 2: signed (visualizer::colormap::Transformと対応)
+uniform int u_transform;
+// 0: grayscale, 1: heat, 2: viridis, 3: diverging (visualizer::colormap::Colormapと対応)
+uniform int u_colormap;
+// NaN/Infをマゼンタで表示するかどうか
+uniform bool u_highlight_non_finite;
+// sRGBのテクスチャのときtrue。サンプリングで線形に戻された色をsRGBに戻す
+uniform bool u_srgb_texture;
+// 正規化した値が線形な明るさのときtrue(visualizer::texture::ColorSpace::Linear)
+uniform bool u_linear_values;
+// 正規化した値にかける補正(visualizer::texture::Adjustment)
+uniform float u_exposure;
+uniform float u_contrast;
+uniform float u_gamma;
 in vec2 v_texcoord;
 out vec4 flagColor;
+
+float linear_to_srgb(float x)
+{
+    return x <= 0.0031308 ? x * 12.92 : 1.055 * pow(x, 1.0 / 2.4) - 0.055;
+}
+
+vec3 colormap(float x)
+{
+    if (u_colormap == 1) {
+        return clamp(vec3(3.0 * x, 3.0 * x - 1.0, 3.0 * x - 2.0), 0.0, 1.0);
+    }
+    if (u_colormap == 2) {
+        vec3 c0 = vec3(0.2777273, 0.0054073, 0.3340998);
+        vec3 c1 = vec3(0.1050930, 1.4046135, 1.3845902);
+        vec3 c2 = vec3(-0.3308618, 0.2148476, 0.0950952);
+        vec3 c3 = vec3(-4.6342305, -5.7991010, -19.3324410);
+        vec3 c4 = vec3(6.2282699, 14.1799334, 56.6905526);
+        vec3 c5 = vec3(4.7763850, -13.7451454, -65.3530326);
+        vec3 c6 = vec3(-5.4354559, 4.6458526, 26.3124352);
+        return clamp(c0 + x * (c1 + x * (c2 + x * (c3 + x * (c4 + x * (c5 + x * c6))))), 0.0, 1.0);
+    }
+    if (u_colormap == 3) {
+        if (x < 0.5) {
+            return mix(vec3(1.0), vec3(0.13, 0.4, 0.67), 1.0 - 2.0 * x);
+        }
+        return mix(vec3(1.0), vec3(0.7, 0.09, 0.17), 2.0 * x - 1.0);
+    }
+    return vec3(x);
+}
+
+float normalize_value(float value)
+{
+    float low = u_value_range.x;
+    float high = u_value_range.y;
+    if (u_transform == 1 && low > 0.0) {
+        return clamp(log(max(value, low) / low) / log(high / low), 0.0, 1.0);
+    }
+    if (u_transform == 1) {
+        return clamp(log(1.0 + max(value - low, 0.0)) / log(1.0 + high - low), 0.0, 1.0);
+    }
+    if (u_transform == 2) {
+        return clamp(0.5 + 0.5 * value / max(abs(low), abs(high)), 0.0, 1.0);
+    }
+    return clamp((value - low) / (high - low), 0.0, 1.0);
+}
+
 void main()
 {
-    float r = texture(u_texture, v_texcoord).r;
-    flagColor = vec4(r,r,r,1);
+    vec4 t = texture(u_texture, v_texcoord);
+    if (u_srgb_texture) {
+        t.rgb = vec3(linear_to_srgb(t.r), linear_to_srgb(t.g), linear_to_srgb(t.b));
+    }
+    if (!u_single_channel) {
+        flagColor = vec4(t.rgb, 1);
+        return;
+    }
+    if (u_highlight_non_finite && (isnan(t.r) || isinf(t.r))) {
+        flagColor = vec4(1, 0, 1, 1);
+        return;
+    }
+    float x = normalize_value(t.r);
+    x = clamp((x * exp2(u_exposure) - 0.5) * u_contrast + 0.5, 0.0, 1.0);
+    x = pow(x, 1.0 / u_gamma);
+    if (u_linear_values) {
+        x = linear_to_srgb(x);
+    }
+    flagColor = vec4(colormap(x), 1);
 }
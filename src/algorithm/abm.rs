@@ -0,0 +1,348 @@
+use algorithm::geometry::Geometry;
+use algorithm::neighborhood::Neighborhood;
+use rand::Rng;
+use std::cmp::Ordering;
+use visualizer::Matrix;
+
+/// 環境`E`の中で、知覚(perceive)・判断(decide)・行動(act)を繰り返すエージェント
+pub trait Agent<E>: Sized {
+    /// 知覚した内容
+    type Perception;
+    /// 行動の内容
+    type Action;
+
+    /// 環境と他のエージェント(自分自身を含む)を知覚する
+    fn perceive(&self, env: &E, agents: &[Self]) -> Self::Perception;
+
+    /// 知覚した内容から行動を決める
+    fn decide<R: Rng>(&mut self, perception: Self::Perception, rng: &mut R) -> Self::Action;
+
+    /// 行動して環境を変える。生まれた子を返す
+    fn act<R: Rng>(&mut self, action: Self::Action, env: &mut E, rng: &mut R) -> Vec<Self>;
+
+    /// 生きているかどうか。falseになったエージェントは行動せず、ステップの終わりに取り除かれる
+    fn is_alive(&self, _env: &E) -> bool {
+        true
+    }
+
+    /// `Scheduler::Priority`で使う優先度。大きいほど先に行動する
+    fn priority(&self) -> f32 {
+        0.0
+    }
+}
+
+/// エージェントを行動させる順番
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheduler {
+    /// 追加した順に1体ずつ知覚・判断・行動する
+    Sequential,
+    /// 毎ステップ順番を入れ替えて1体ずつ行動する。順番による偏りがなくなる
+    Random,
+    /// 全員が知覚・判断してから、全員が行動する(同期更新)
+    Staged,
+    /// 優先度の高い順に1体ずつ行動する
+    Priority,
+}
+
+/// エージェントの集団と環境を持つモデル
+///
+/// # Example
+/// ```
+/// extern crate my_alife;
+/// extern crate rand;
+///
+/// use my_alife::algorithm::abm::{Agent, GridSpace, Model, Scheduler};
+/// use rand::Rng;
+///
+/// // 格子の上をランダムに歩き、足跡を残すエージェント
+/// struct Walker {
+///     position: (usize, usize),
+/// }
+///
+/// struct Trail {
+///     space: GridSpace,
+///     visits: Vec<u32>,
+/// }
+///
+/// impl Agent<Trail> for Walker {
+///     type Perception = Vec<(usize, usize)>;
+///     type Action = (usize, usize);
+///
+///     fn perceive(&self, env: &Trail, _agents: &[Walker]) -> Vec<(usize, usize)> {
+///         env.space.free_neighbors(self.position)
+///     }
+///
+///     fn decide<R: Rng>(&mut self, moves: Vec<(usize, usize)>, rng: &mut R) -> (usize, usize) {
+///         *rng.choose(&moves).unwrap_or(&self.position)
+///     }
+///
+///     fn act<R: Rng>(&mut self, target: (usize, usize), env: &mut Trail, _rng: &mut R) -> Vec<Walker> {
+///         self.position = target;
+///         let (_, cols) = env.space.dim();
+///         env.visits[target.0 * cols + target.1] += 1;
+///         Vec::new()
+///     }
+/// }
+///
+/// let env = Trail { space: GridSpace::new((16, 16)), visits: vec![0; 256] };
+/// let mut model = Model::new(env, Scheduler::Random);
+/// for i in 0..10 {
+///     model.add_agent(Walker { position: (i, i) });
+/// }
+/// let mut rng = rand::thread_rng();
+/// for _ in 0..5 {
+///     model.step(&mut rng);
+/// }
+/// assert_eq!(model.time(), 5);
+/// assert_eq!(model.env().visits.iter().sum::<u32>(), 50);
+/// ```
+pub struct Model<A, E> {
+    agents: Vec<A>,
+    env: E,
+    scheduler: Scheduler,
+    time: usize,
+}
+
+impl<A: Agent<E>, E> Model<A, E> {
+    /// エージェントのいないモデルを生成する
+    pub fn new(env: E, scheduler: Scheduler) -> Model<A, E> {
+        Model {
+            agents: Vec::new(),
+            env,
+            scheduler,
+            time: 0,
+        }
+    }
+
+    /// エージェントを追加する
+    pub fn add_agent(&mut self, agent: A) {
+        self.agents.push(agent);
+    }
+
+    /// エージェント
+    pub fn agents(&self) -> &[A] {
+        &self.agents
+    }
+
+    /// エージェントを変更するための参照
+    pub fn agents_mut(&mut self) -> &mut Vec<A> {
+        &mut self.agents
+    }
+
+    /// 環境
+    pub fn env(&self) -> &E {
+        &self.env
+    }
+
+    /// 環境を変更するための参照
+    pub fn env_mut(&mut self) -> &mut E {
+        &mut self.env
+    }
+
+    /// 行動させる順番を変更する
+    pub fn set_scheduler(&mut self, scheduler: Scheduler) {
+        self.scheduler = scheduler;
+    }
+
+    /// 経過したステップ数
+    pub fn time(&self) -> usize {
+        self.time
+    }
+
+    /// 全員を1回ずつ行動させ、生まれた子を加えて死んだエージェントを取り除く
+    pub fn step<R: Rng>(&mut self, rng: &mut R) {
+        let mut order: Vec<usize> = (0..self.agents.len()).collect();
+        let mut offspring = Vec::new();
+        match self.scheduler {
+            Scheduler::Staged => {
+                let actions: Vec<Option<A::Action>> = (0..self.agents.len())
+                    .map(|i| {
+                        if !self.agents[i].is_alive(&self.env) {
+                            return None;
+                        }
+                        let perception = self.agents[i].perceive(&self.env, &self.agents);
+                        Some(self.agents[i].decide(perception, rng))
+                    })
+                    .collect();
+                for (i, action) in actions.into_iter().enumerate() {
+                    if let Some(action) = action {
+                        if self.agents[i].is_alive(&self.env) {
+                            offspring.extend(self.agents[i].act(action, &mut self.env, rng));
+                        }
+                    }
+                }
+            }
+            scheduler => {
+                if scheduler == Scheduler::Random {
+                    rng.shuffle(&mut order);
+                } else if scheduler == Scheduler::Priority {
+                    let agents = &self.agents;
+                    order.sort_by(|&a, &b| {
+                        agents[b]
+                            .priority()
+                            .partial_cmp(&agents[a].priority())
+                            .unwrap_or(Ordering::Equal)
+                    });
+                }
+                for i in order {
+                    if !self.agents[i].is_alive(&self.env) {
+                        continue;
+                    }
+                    let perception = self.agents[i].perceive(&self.env, &self.agents);
+                    let action = self.agents[i].decide(perception, rng);
+                    offspring.extend(self.agents[i].act(action, &mut self.env, rng));
+                }
+            }
+        }
+        let env = &self.env;
+        self.agents.retain(|agent| agent.is_alive(env));
+        self.agents.extend(offspring);
+        self.time += 1;
+    }
+}
+
+/// 周期境界条件の格子の空間。壁を設定すると、壁のセルには移動できない
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridSpace {
+    dim: (usize, usize),
+    walls: Option<Matrix<bool>>,
+    neighborhood: Neighborhood,
+}
+
+impl GridSpace {
+    /// 壁のない`dim`の大きさの空間を生成する。近傍はMoore近傍
+    pub fn new(dim: (usize, usize)) -> GridSpace {
+        GridSpace {
+            dim,
+            walls: None,
+            neighborhood: Neighborhood::Moore,
+        }
+    }
+
+    /// 大きさ
+    pub fn dim(&self) -> (usize, usize) {
+        self.dim
+    }
+
+    /// 移動できる近傍の取り方を変更する
+    pub fn set_neighborhood(&mut self, neighborhood: Neighborhood) {
+        self.neighborhood = neighborhood;
+    }
+
+    /// `geometry`の壁を、移動できないセルにする
+    pub fn set_geometry(&mut self, geometry: &Geometry) {
+        assert_eq!(geometry.dim(), self.dim);
+        self.walls = Some(geometry.walls());
+    }
+
+    /// セル(row, col)が壁かどうか
+    pub fn is_wall(&self, cell: (usize, usize)) -> bool {
+        self.walls.as_ref().is_some_and(|walls| walls[cell])
+    }
+
+    /// 周期境界条件で、(row, col)を空間の中に戻す
+    pub fn wrap(&self, (row, col): (isize, isize)) -> (usize, usize) {
+        (
+            row.rem_euclid(self.dim.0 as isize) as usize,
+            col.rem_euclid(self.dim.1 as isize) as usize,
+        )
+    }
+
+    /// `cell`の近傍のうち、壁でないセル
+    pub fn free_neighbors(&self, cell: (usize, usize)) -> Vec<(usize, usize)> {
+        self.neighborhood
+            .around(cell, self.dim)
+            .filter(|&neighbor| !self.is_wall(neighbor))
+            .collect()
+    }
+
+    /// 壁でないセルを一様に選ぶ。すべて壁ならば`None`
+    pub fn random_cell<R: Rng>(&self, rng: &mut R) -> Option<(usize, usize)> {
+        let free = self.dim.0 * self.dim.1
+            - self
+                .walls
+                .as_ref()
+                .map_or(0, |walls| walls.iter().filter(|&&w| w).count());
+        if free == 0 {
+            return None;
+        }
+        loop {
+            let cell = (rng.gen_range(0, self.dim.0), rng.gen_range(0, self.dim.1));
+            if !self.is_wall(cell) {
+                return Some(cell);
+            }
+        }
+    }
+}
+
+/// 周期境界条件の連続空間。幅`width`、高さ`height`のトーラスになる
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::abm::ContinuousSpace;
+///
+/// let space = ContinuousSpace::new(10.0, 10.0);
+/// assert_eq!(space.wrap((11.0, -1.0)), (1.0, 9.0));
+/// // 端をまたいだ方が近い
+/// assert_eq!(space.displacement((9.0, 5.0), (1.0, 5.0)), (2.0, 0.0));
+/// assert_eq!(space.distance((9.0, 5.0), (1.0, 5.0)), 2.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContinuousSpace {
+    /// 幅
+    pub width: f32,
+    /// 高さ
+    pub height: f32,
+}
+
+impl ContinuousSpace {
+    /// 空間を生成する
+    pub fn new(width: f32, height: f32) -> ContinuousSpace {
+        ContinuousSpace { width, height }
+    }
+
+    /// 位置を空間の中に戻す
+    pub fn wrap(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (x.rem_euclid(self.width), y.rem_euclid(self.height))
+    }
+
+    /// `from`から`to`への最短のずれ
+    pub fn displacement(&self, from: (f32, f32), to: (f32, f32)) -> (f32, f32) {
+        let shortest = |d: f32, size: f32| {
+            let d = d.rem_euclid(size);
+            if d > size / 2.0 {
+                d - size
+            } else {
+                d
+            }
+        };
+        (
+            shortest(to.0 - from.0, self.width),
+            shortest(to.1 - from.1, self.height),
+        )
+    }
+
+    /// 2点間の最短距離
+    pub fn distance(&self, a: (f32, f32), b: (f32, f32)) -> f32 {
+        let (dx, dy) = self.displacement(a, b);
+        (dx * dx + dy * dy).sqrt()
+    }
+
+    /// 一様に選んだ位置
+    pub fn random_position<R: Rng>(&self, rng: &mut R) -> (f32, f32) {
+        (rng.gen_range(0.0, self.width), rng.gen_range(0.0, self.height))
+    }
+
+    /// `positions`のうち、`center`から距離`radius`以内にあるものの番号
+    pub fn within<I>(&self, center: (f32, f32), radius: f32, positions: I) -> Vec<usize>
+    where
+        I: IntoIterator<Item = (f32, f32)>,
+    {
+        positions
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, position)| self.distance(center, position) <= radius)
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
@@ -0,0 +1,67 @@
+use std::f32;
+
+/// CFL条件を元に、陽解法が発散しない時間刻みを見積もる
+///
+/// 2次元の拡散方程式を陽的Euler法で解くときの安定条件 `dt <= dx^2 / (4 * D)` と、
+/// 反応項の変化の速さ`r`に対する条件 `dt <= 1 / r` のうち小さい方に安全係数を掛けて使う
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CflController {
+    /// 安定限界に掛ける安全係数。0より大きく1以下
+    pub safety: f32,
+    /// 時間刻みの最大値
+    pub max_dt: f32,
+    /// 時間刻みの最小値。安定限界がこれより小さくてもこの値で進める
+    pub min_dt: f32,
+}
+
+impl Default for CflController {
+    fn default() -> CflController {
+        CflController {
+            safety: 0.8,
+            max_dt: 1.0,
+            min_dt: 1e-4,
+        }
+    }
+}
+
+impl CflController {
+    /// 拡散係数`diffusion`、格子間隔`dx`での拡散の安定限界
+    pub fn diffusion_limit(dx: f32, diffusion: f32) -> f32 {
+        if diffusion <= 0.0 {
+            f32::INFINITY
+        } else {
+            dx * dx / (4.0 * diffusion)
+        }
+    }
+
+    /// 時間刻みを見積もる
+    ///
+    /// # Arguments
+    /// * `dx` - 格子間隔
+    /// * `diffusions` - 各物質の拡散係数
+    /// * `reaction_rate` - 反応項の変化の速さ(ヤコビアンの大きさ)の上限
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::algorithm::adaptive::CflController;
+    ///
+    /// let controller = CflController::default();
+    /// // 拡散係数を大きくすると時間刻みが小さくなる
+    /// let slow = controller.dt(0.01, &[2e-5, 1e-5], 0.1);
+    /// let fast = controller.dt(0.01, &[2e-4, 1e-5], 0.1);
+    /// assert!(fast < slow);
+    /// assert!(slow <= controller.max_dt);
+    /// ```
+    pub fn dt(&self, dx: f32, diffusions: &[f32], reaction_rate: f32) -> f32 {
+        let diffusion_dt = diffusions
+            .iter()
+            .map(|d| Self::diffusion_limit(dx, *d))
+            .fold(f32::INFINITY, f32::min);
+        let reaction_dt = if reaction_rate <= 0.0 {
+            f32::INFINITY
+        } else {
+            1.0 / reaction_rate
+        };
+        (self.safety * diffusion_dt.min(reaction_dt)).max(self.min_dt).min(self.max_dt)
+    }
+}
@@ -0,0 +1,620 @@
+use algorithm::bit_life::BitLife;
+use algorithm::domain::Decomposition;
+use algorithm::game_of_life;
+#[cfg(feature = "gl")]
+use algorithm::gpu::GpuStepper;
+use algorithm::gray_scott::{self, GrayScott, DT, VISUALIZATION_STEP};
+use algorithm::memory::MemoryUsage;
+use algorithm::pool::BufferPool;
+use algorithm::reaction_diffusion::ReactionDiffusion;
+use failure;
+#[cfg(feature = "gl")]
+use glium::backend::Facade;
+use ndarray::Array2;
+use std::env;
+use std::fmt;
+use std::mem;
+use std::str::FromStr;
+use std::thread;
+use visualizer::Matrix;
+
+/// 計算方法を上書きする環境変数。`naive`, `threads`, `simd`, `bits`, `gpu`のどれかを入れる
+pub const BACKEND_VARIABLE: &str = "MY_ALIFE_BACKEND";
+
+/// 計算方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// ndarrayの演算をそのまま使う
+    Naive,
+    /// 盤面を`Decomposition`で帯に分けて、CPUの数だけのスレッドで計算する
+    Threads,
+    /// 行ごとの計算をSIMD命令(AVX2など)が使えるようにコンパイルしたもので計算する
+    Simd,
+    /// 1セルを1bitに詰めて64セルずつ計算する(`BitLife`)。Game of Lifeにしか使えない
+    Bits,
+    /// compute shaderで計算する
+    Gpu,
+}
+
+impl Backend {
+    /// すべての計算方法。速いと思われる順に並んでいる
+    pub const ALL: [Backend; 5] = [Backend::Gpu, Backend::Bits, Backend::Threads, Backend::Simd, Backend::Naive];
+
+    /// このマシンで使えるCPUの計算方法。速いと思われる順に並んでいる
+    ///
+    /// モデルによらず使えるものだけを返すので、`Backend::Bits`は含まない
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::algorithm::backend::Backend;
+    ///
+    /// let available = Backend::cpu_available();
+    /// assert_eq!(available.last(), Some(&Backend::Naive));
+    /// assert!(!available.contains(&Backend::Gpu));
+    /// assert!(!available.contains(&Backend::Bits));
+    /// ```
+    pub fn cpu_available() -> Vec<Backend> {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        Backend::ALL
+            .iter()
+            .cloned()
+            .filter(|&backend| match backend {
+                Backend::Naive => true,
+                Backend::Threads => threads > 1,
+                Backend::Simd => simd_supported(),
+                Backend::Bits | Backend::Gpu => false,
+            })
+            .collect()
+    }
+
+    /// `available`の中から計算方法を選ぶ
+    ///
+    /// `preferred`、環境変数`BACKEND_VARIABLE`の順に指定があればそれを使い、なければ`available`で一番速いものを使う。
+    /// 指定されたものが使えないときはErrを返す
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::algorithm::backend::Backend;
+    ///
+    /// let available = [Backend::Simd, Backend::Naive];
+    /// assert_eq!(Backend::choose(&available, Some(Backend::Naive)).unwrap(), Backend::Naive);
+    /// assert!(Backend::choose(&available, Some(Backend::Gpu)).is_err());
+    /// ```
+    pub fn choose(available: &[Backend], preferred: Option<Backend>) -> Result<Backend, failure::Error> {
+        let preferred = match preferred {
+            Some(backend) => Some(backend),
+            None => match env::var(BACKEND_VARIABLE) {
+                Ok(name) => Some(name.parse()?),
+                Err(_) => None,
+            },
+        };
+        match preferred {
+            Some(backend) if available.contains(&backend) => Ok(backend),
+            Some(backend) => Err(format_err!("backend {} is not available", backend)),
+            None => Backend::ALL
+                .iter()
+                .cloned()
+                .find(|backend| available.contains(backend))
+                .ok_or_else(|| format_err!("no backend is available")),
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            Backend::Naive => "naive",
+            Backend::Threads => "threads",
+            Backend::Simd => "simd",
+            Backend::Bits => "bits",
+            Backend::Gpu => "gpu",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Backend {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Backend, failure::Error> {
+        Backend::ALL
+            .iter()
+            .cloned()
+            .find(|backend| backend.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| format_err!("unknown backend {}", s))
+    }
+}
+
+/// 計算方法によらない、盤面を進めるための共通のtrait
+///
+/// `Box<dyn Stepper<Cell = f32>>`として持てば、計算方法が変わっても使う側のコードは変わらない
+pub trait Stepper {
+    /// セルの型
+    type Cell: Copy;
+
+    /// 使っている計算方法
+    fn backend(&self) -> Backend;
+
+    /// `steps`ステップ進める。`tracing`フィーチャーが有効なときは、計算方法とステップ数を持つ`step`のspanになる
+    fn step(&mut self, steps: usize) -> Result<(), failure::Error>;
+
+    /// 表示する盤面
+    fn snapshot(&mut self) -> Result<&Matrix<Self::Cell>, failure::Error>;
+
+    /// パラメータを変える。そのパラメータがなければfalseを返す
+    fn set_parameter(&mut self, _name: &str, _value: f32) -> bool {
+        false
+    }
+}
+
+/// `gray_scott::laplacian`と同じだけ進めてuを返す。`try_draw_loop`にそのまま渡せる
+pub fn update_gray_scott<S>(stepper: &mut S, f: f32, k: f32) -> Result<&Matrix<f32>, failure::Error>
+where
+    S: Stepper<Cell = f32> + ?Sized,
+{
+    stepper.set_parameter("f", f);
+    stepper.set_parameter("k", k);
+    stepper.step(VISUALIZATION_STEP)?;
+    stepper.snapshot()
+}
+
+/// GPUも含めて一番速い計算方法でGray-Scottモデルを計算する`Stepper`を作る
+///
+/// # Example
+/// ```no_run
+/// extern crate my_alife;
+///
+/// use my_alife::algorithm::backend::{gray_scott_stepper, update_gray_scott};
+/// use my_alife::algorithm::gray_scott::{initial_matrix, GrayScott};
+/// use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+///
+/// let matrix = MatrixVisualizer::new(
+///     "Gray Scott",
+///     "res/shaders/matrix_visualizer_vertex.glsl",
+///     "res/shaders/matrix_visualizer_fragment.glsl",
+/// ).unwrap();
+/// let stepper = gray_scott_stepper(matrix.display(), &initial_matrix(), GrayScott::new(0.04, 0.06), None).unwrap();
+/// matrix.try_draw_loop(stepper, 0.04, 0.06, |stepper, f, k| update_gray_scott(&mut **stepper, f, k)).unwrap();
+/// ```
+#[cfg(feature = "gl")]
+pub fn gray_scott_stepper<F: Facade>(
+    facade: &F,
+    uv: &(Matrix<f32>, Matrix<f32>),
+    model: GrayScott,
+    preferred: Option<Backend>,
+) -> Result<Box<dyn Stepper<Cell = f32>>, failure::Error> {
+    let mut available = Backend::cpu_available();
+    if GpuStepper::is_supported(facade) {
+        available.insert(0, Backend::Gpu);
+    }
+    match Backend::choose(&available, preferred)? {
+        Backend::Gpu => Ok(Box::new(GpuStepper::gray_scott(facade, uv, model)?)),
+        backend => Ok(Box::new(CpuGrayScott::new(backend, uv, model)?)),
+    }
+}
+
+/// CPUでGray-Scottモデルを計算する`Stepper`
+///
+/// どの計算方法でも`gray_scott::laplacian`と同じ結果になる
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::backend::{Backend, CpuGrayScott, Stepper};
+/// use my_alife::algorithm::gray_scott::{initial_matrix, laplacian, GrayScott};
+///
+/// let mut uv = initial_matrix();
+/// let mut steppers: Vec<Box<dyn Stepper<Cell = f32>>> = [Backend::Naive, Backend::Simd, Backend::Threads]
+///     .iter()
+///     .map(|&backend| Box::new(CpuGrayScott::new(backend, &uv, GrayScott::new(0.04, 0.06)).unwrap()) as Box<_>)
+///     .collect();
+/// let expected = laplacian(&mut uv, 0.04, 0.06).clone();
+/// for stepper in &mut steppers {
+///     let backend = stepper.backend();
+///     stepper.step(8).unwrap();
+///     assert_eq!(stepper.snapshot().unwrap(), &expected, "{}", backend);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CpuGrayScott {
+    backend: Backend,
+    model: GrayScott,
+    fields: Vec<Matrix<f32>>,
+    decomposition: Decomposition,
+    pool: BufferPool<f32>,
+}
+
+impl CpuGrayScott {
+    /// `backend`で計算する。`Backend::Gpu`, `Backend::Bits`のときはErrを返す
+    pub fn new(
+        backend: Backend,
+        uv: &(Matrix<f32>, Matrix<f32>),
+        model: GrayScott,
+    ) -> Result<CpuGrayScott, failure::Error> {
+        match backend {
+            Backend::Gpu => return Err(format_err!("use GpuStepper to compute on the GPU")),
+            Backend::Bits => return Err(format_err!("Gray-Scott model has no bits backend")),
+            _ => (),
+        }
+        Ok(CpuGrayScott {
+            backend,
+            model,
+            fields: vec![standard_layout(&uv.0), standard_layout(&uv.1)],
+            decomposition: threads_for(uv.0.dim().0),
+            pool: BufferPool::new(),
+        })
+    }
+
+    /// このマシンで使えるCPUの計算方法のうち一番速いもので計算する
+    pub fn auto(uv: &(Matrix<f32>, Matrix<f32>), model: GrayScott) -> Result<CpuGrayScott, failure::Error> {
+        CpuGrayScott::new(Backend::choose(&Backend::cpu_available(), None)?, uv, model)
+    }
+
+    /// モデルのパラメータ
+    pub fn model(&self) -> &GrayScott {
+        &self.model
+    }
+
+    /// uとv
+    pub fn fields(&self) -> (&Matrix<f32>, &Matrix<f32>) {
+        (&self.fields[0], &self.fields[1])
+    }
+
+    /// 計算に使うバッファのためにメモリを確保した回数。`Backend::Simd`では最初のステップのあと増えない
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::algorithm::backend::{Backend, CpuGrayScott, Stepper};
+    /// use my_alife::algorithm::gray_scott::{initial_matrix, GrayScott};
+    ///
+    /// let mut stepper = CpuGrayScott::new(Backend::Simd, &initial_matrix(), GrayScott::new(0.04, 0.06)).unwrap();
+    /// stepper.step(1).unwrap();
+    /// let allocations = stepper.allocations();
+    /// stepper.step(100).unwrap();
+    /// assert_eq!(stepper.allocations(), allocations);
+    /// ```
+    pub fn allocations(&self) -> usize {
+        self.pool.allocations()
+    }
+}
+
+/// 盤面と、使い回すために取っておいているバッファの合計
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::backend::{Backend, CpuGrayScott, Stepper};
+/// use my_alife::algorithm::gray_scott::{initial_matrix, GrayScott};
+/// use my_alife::algorithm::memory::MemoryUsage;
+///
+/// let mut stepper = CpuGrayScott::new(Backend::Simd, &initial_matrix(), GrayScott::new(0.04, 0.06)).unwrap();
+/// assert_eq!(stepper.memory_usage(), 256 * 256 * 4 * 2);
+/// stepper.step(1).unwrap();
+/// assert_eq!(stepper.memory_usage(), 256 * 256 * 4 * 4);
+/// ```
+impl MemoryUsage for CpuGrayScott {
+    fn memory_usage(&self) -> usize {
+        self.fields.memory_usage() + self.pool.memory_usage()
+    }
+}
+
+impl Stepper for CpuGrayScott {
+    type Cell = f32;
+
+    fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    fn step(&mut self, steps: usize) -> Result<(), failure::Error> {
+        let _span = trace_span!("step", backend = %self.backend, steps);
+        let (model, dt) = (self.model, DT as f32);
+        match self.backend {
+            Backend::Naive => {
+                let (u, v) = self.fields.split_at_mut(1);
+                for _ in 0..steps {
+                    gray_scott::step(&mut u[0], &mut v[0], model.f, model.k, model.du, model.dv, dt);
+                }
+            }
+            Backend::Simd => {
+                let dim = self.fields[0].dim();
+                for _ in 0..steps {
+                    let mut next_u = self.pool.take_matrix(dim, 0.0);
+                    let mut next_v = self.pool.take_matrix(dim, 0.0);
+                    gray_scott_simd(&self.fields[0], &self.fields[1], &mut next_u, &mut next_v, &model, dt);
+                    let u = mem::replace(&mut self.fields[0], next_u);
+                    let v = mem::replace(&mut self.fields[1], next_v);
+                    self.pool.recycle(u);
+                    self.pool.recycle(v);
+                }
+            }
+            Backend::Threads => self.decomposition.run(&mut self.fields, steps, |fields| {
+                let dim = fields[0].dim();
+                let (mut next_u, mut next_v) = (Array2::zeros(dim), Array2::zeros(dim));
+                gray_scott_simd(&fields[0], &fields[1], &mut next_u, &mut next_v, &model, dt);
+                fields[0] = next_u;
+                fields[1] = next_v;
+            }),
+            Backend::Bits | Backend::Gpu => unreachable!(),
+        }
+        Ok(())
+    }
+
+    fn snapshot(&mut self) -> Result<&Matrix<f32>, failure::Error> {
+        Ok(&self.fields[0])
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f32) -> bool {
+        match name {
+            "f" => self.model.f = value,
+            "k" => self.model.k = value,
+            "du" => self.model.du = value,
+            "dv" => self.model.dv = value,
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// CPUでGame of Lifeを計算する`Stepper`。GPUの計算方法はない
+///
+/// `Backend::Bits`のときは盤面を`BitLife`に詰めて計算し、`snapshot`のときだけ1セル1byteに戻す
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::backend::{Backend, CpuLife, Stepper};
+/// use my_alife::algorithm::game_of_life::{random_cells, step};
+///
+/// let cells = random_cells((64, 64), 0.3);
+/// let mut life = CpuLife::new(Backend::Simd, cells.clone()).unwrap();
+/// life.step(2).unwrap();
+/// assert_eq!(life.snapshot().unwrap(), &step(&step(&cells)));
+///
+/// let mut bits = CpuLife::new(Backend::Bits, cells.clone()).unwrap();
+/// bits.step(2).unwrap();
+/// assert_eq!(bits.snapshot().unwrap(), &step(&step(&cells)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CpuLife {
+    backend: Backend,
+    cells: Vec<Matrix<u8>>,
+    bits: Option<BitLife>,
+    decomposition: Decomposition,
+    pool: BufferPool<u8>,
+}
+
+impl CpuLife {
+    /// `backend`で計算する。`Backend::Gpu`のときはErrを返す
+    pub fn new(backend: Backend, cells: Matrix<u8>) -> Result<CpuLife, failure::Error> {
+        if backend == Backend::Gpu {
+            return Err(format_err!("Game of Life has no GPU backend"));
+        }
+        Ok(CpuLife {
+            backend,
+            decomposition: threads_for(cells.dim().0),
+            bits: if backend == Backend::Bits {
+                Some(BitLife::from_cells(&cells))
+            } else {
+                None
+            },
+            cells: vec![standard_layout(&cells)],
+            pool: BufferPool::new(),
+        })
+    }
+
+    /// このマシンで使えるCPUの計算方法のうち一番速いもので計算する。環境変数で指定がなければ`Backend::Bits`になる
+    pub fn auto(cells: Matrix<u8>) -> Result<CpuLife, failure::Error> {
+        let mut available = Backend::cpu_available();
+        available.insert(0, Backend::Bits);
+        CpuLife::new(Backend::choose(&available, None)?, cells)
+    }
+}
+
+impl MemoryUsage for CpuLife {
+    fn memory_usage(&self) -> usize {
+        self.cells.memory_usage() + self.bits.as_ref().map_or(0, BitLife::memory_usage) + self.pool.memory_usage()
+    }
+}
+
+impl Stepper for CpuLife {
+    type Cell = u8;
+
+    fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    fn step(&mut self, steps: usize) -> Result<(), failure::Error> {
+        let _span = trace_span!("step", backend = %self.backend, steps);
+        match self.backend {
+            Backend::Naive => {
+                for _ in 0..steps {
+                    self.cells[0] = game_of_life::step(&self.cells[0]);
+                }
+            }
+            Backend::Simd => {
+                let dim = self.cells[0].dim();
+                for _ in 0..steps {
+                    let mut next = self.pool.take_matrix(dim, game_of_life::DEAD);
+                    life_simd(&self.cells[0], &mut next);
+                    let cells = mem::replace(&mut self.cells[0], next);
+                    self.pool.recycle(cells);
+                }
+            }
+            Backend::Threads => self.decomposition.run(&mut self.cells, steps, |cells| {
+                let mut next = Array2::zeros(cells[0].dim());
+                life_simd(&cells[0], &mut next);
+                cells[0] = next;
+            }),
+            Backend::Bits => {
+                let bits = self.bits.as_mut().unwrap();
+                for _ in 0..steps {
+                    bits.step();
+                }
+            }
+            Backend::Gpu => unreachable!(),
+        }
+        Ok(())
+    }
+
+    fn snapshot(&mut self) -> Result<&Matrix<u8>, failure::Error> {
+        if let Some(ref bits) = self.bits {
+            self.cells[0] = bits.to_cells();
+        }
+        Ok(&self.cells[0])
+    }
+}
+
+fn simd_supported() -> bool {
+    #[cfg(target_arch = "x86_64")]
+    {
+        is_x86_feature_detected!("avx2")
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        true
+    }
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    {
+        false
+    }
+}
+
+// 帯の行数が1行以上になるだけのスレッドを使う
+fn threads_for(rows: usize) -> Decomposition {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    Decomposition::new(threads.min(rows).max(1), 1)
+}
+
+fn standard_layout<A: Copy>(a: &Matrix<A>) -> Matrix<A> {
+    Array2::from_shape_vec(a.dim(), a.iter().cloned().collect()).unwrap()
+}
+
+fn gray_scott_simd(
+    u: &Matrix<f32>,
+    v: &Matrix<f32>,
+    next_u: &mut Matrix<f32>,
+    next_v: &mut Matrix<f32>,
+    model: &GrayScott,
+    dt: f32,
+) {
+    let dim = u.dim();
+    let (u, v) = (u.as_slice().unwrap(), v.as_slice().unwrap());
+    let (next_u, next_v) = (next_u.as_slice_mut().unwrap(), next_v.as_slice_mut().unwrap());
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            unsafe { gray_scott_avx2(u, v, next_u, next_v, dim, model, dt) };
+            return;
+        }
+    }
+    gray_scott_rows(u, v, next_u, next_v, dim, model, dt);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn gray_scott_avx2(
+    u: &[f32],
+    v: &[f32],
+    next_u: &mut [f32],
+    next_v: &mut [f32],
+    dim: (usize, usize),
+    model: &GrayScott,
+    dt: f32,
+) {
+    gray_scott_rows(u, v, next_u, next_v, dim, model, dt)
+}
+
+// gray_scott::stepと同じ順番で演算するので、結果も一致する
+#[inline(always)]
+fn gray_scott_rows(
+    u: &[f32],
+    v: &[f32],
+    next_u: &mut [f32],
+    next_v: &mut [f32],
+    (rows, cols): (usize, usize),
+    model: &GrayScott,
+    dt: f32,
+) {
+    let dx = model.dx();
+    let (f, du, dv, d2, fk) = (model.f, model.du, model.dv, dx * dx, model.f + model.k);
+    for row in 0..rows {
+        let (top, middle, bottom) = ((row + rows - 1) % rows * cols, row * cols, (row + 1) % rows * cols);
+        let (u_top, u_middle, u_bottom) = (
+            &u[top..top + cols],
+            &u[middle..middle + cols],
+            &u[bottom..bottom + cols],
+        );
+        let (v_top, v_middle, v_bottom) = (
+            &v[top..top + cols],
+            &v[middle..middle + cols],
+            &v[bottom..bottom + cols],
+        );
+        let (next_u, next_v) = (&mut next_u[middle..middle + cols], &mut next_v[middle..middle + cols]);
+        let mut update = |col: usize, left: usize, right: usize| {
+            let (uc, vc) = (u_middle[col], v_middle[col]);
+            let laplacian_u = (u_top[col] + u_bottom[col] + u_middle[left] + u_middle[right] - uc * 4.0) / d2;
+            let laplacian_v = (v_top[col] + v_bottom[col] + v_middle[left] + v_middle[right] - vc * 4.0) / d2;
+            let uvv = uc * vc * vc;
+            next_u[col] = dt * (laplacian_u * du - uvv + f * (1.0 - uc)) + uc;
+            next_v[col] = dt * (laplacian_v * dv + uvv - fk * vc) + vc;
+        };
+        update(0, cols - 1, 1 % cols);
+        for col in 1..cols.saturating_sub(1) {
+            update(col, col - 1, col + 1);
+        }
+        if cols > 1 {
+            update(cols - 1, cols - 2, 0);
+        }
+    }
+}
+
+fn life_simd(cells: &Matrix<u8>, next: &mut Matrix<u8>) {
+    let dim = cells.dim();
+    let (cells, next) = (cells.as_slice().unwrap(), next.as_slice_mut().unwrap());
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            unsafe { life_avx2(cells, next, dim) };
+            return;
+        }
+    }
+    life_rows(cells, next, dim);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn life_avx2(cells: &[u8], next: &mut [u8], dim: (usize, usize)) {
+    life_rows(cells, next, dim)
+}
+
+// game_of_life::next_stateと同じ規則を分岐なしで計算する
+#[inline(always)]
+fn life_rows(cells: &[u8], next: &mut [u8], (rows, cols): (usize, usize)) {
+    for row in 0..rows {
+        let (top, middle, bottom) = ((row + rows - 1) % rows * cols, row * cols, (row + 1) % rows * cols);
+        let (top, middle, bottom) = (
+            &cells[top..top + cols],
+            &cells[middle..middle + cols],
+            &cells[bottom..bottom + cols],
+        );
+        let next = &mut next[row * cols..row * cols + cols];
+        let mut update = |col: usize, left: usize, right: usize| {
+            let neighbors = top[left]
+                .wrapping_add(top[col])
+                .wrapping_add(top[right])
+                .wrapping_add(middle[left])
+                .wrapping_add(middle[right])
+                .wrapping_add(bottom[left])
+                .wrapping_add(bottom[col])
+                .wrapping_add(bottom[right]);
+            let state = middle[col];
+            let alive =
+                (neighbors == 3 && state <= game_of_life::ALIVE) | (neighbors == 2 && state == game_of_life::ALIVE);
+            next[col] = if alive { game_of_life::ALIVE } else { game_of_life::DEAD };
+        };
+        update(0, cols - 1, 1 % cols);
+        for col in 1..cols.saturating_sub(1) {
+            update(col, col - 1, col + 1);
+        }
+        if cols > 1 {
+            update(cols - 1, cols - 2, 0);
+        }
+    }
+}
@@ -0,0 +1,382 @@
+use algorithm::abm::ContinuousSpace;
+use ndarray::Array2;
+use rand::distributions::{IndependentSample, Normal};
+use rand::Rng;
+use std::f32::consts::PI;
+use visualizer::agent_visualizer::AgentSprite;
+use visualizer::Matrix;
+
+// Type 4の、モーターが一番強く回る刺激の強さと、その広がり
+const TYPE4_PREFERRED: f32 = 0.4;
+const TYPE4_WIDTH: f32 = 0.15;
+
+/// Braitenbergのビークルのセンサーとモーターのつなぎ方
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wiring {
+    /// 1つのセンサーが両方のモーターを回す。刺激が強いところで速くなる(alive)
+    Type1,
+    /// 同じ側のモーターを強める。刺激から逃げる(fear)
+    Type2a,
+    /// 反対側のモーターを強める。刺激に向かって突っ込む(aggression)
+    Type2b,
+    /// 同じ側のモーターを弱める。刺激の方を向いて近くで止まる(love)
+    Type3a,
+    /// 反対側のモーターを弱める。刺激から離れながらゆっくり次を探す(explorer)
+    Type3b,
+    /// 同じ側のモーターを、ちょうどよい強さの刺激で一番強める。刺激源の周りを回る
+    Type4a,
+    /// 反対側のモーターを、ちょうどよい強さの刺激で一番強める
+    Type4b,
+}
+
+impl Wiring {
+    /// 左右のセンサーの値から(左, 右)のモーターの出力
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::algorithm::braitenberg::Wiring;
+    ///
+    /// assert_eq!(Wiring::Type2a.motors(0.8, 0.2), (0.8, 0.2));
+    /// assert_eq!(Wiring::Type2b.motors(0.8, 0.2), (0.2, 0.8));
+    /// assert_eq!(Wiring::Type3a.motors(1.0, 0.0), (0.0, 1.0));
+    /// ```
+    pub fn motors(self, left: f32, right: f32) -> (f32, f32) {
+        let inhibit = |s: f32| (1.0 - s).max(0.0);
+        let tuned = |s: f32| (-(s - TYPE4_PREFERRED).powi(2) / (2.0 * TYPE4_WIDTH * TYPE4_WIDTH)).exp();
+        match self {
+            Wiring::Type1 => {
+                let s = (left + right) / 2.0;
+                (s, s)
+            }
+            Wiring::Type2a => (left, right),
+            Wiring::Type2b => (right, left),
+            Wiring::Type3a => (inhibit(left), inhibit(right)),
+            Wiring::Type3b => (inhibit(right), inhibit(left)),
+            Wiring::Type4a => (tuned(left), tuned(right)),
+            Wiring::Type4b => (tuned(right), tuned(left)),
+        }
+    }
+
+    /// 描くときの色
+    pub fn color(self) -> [u8; 3] {
+        match self {
+            Wiring::Type1 => [200, 200, 200],
+            Wiring::Type2a => [80, 160, 255],
+            Wiring::Type2b => [255, 60, 60],
+            Wiring::Type3a => [255, 120, 220],
+            Wiring::Type3b => [80, 230, 120],
+            Wiring::Type4a => [255, 200, 40],
+            Wiring::Type4b => [170, 100, 255],
+        }
+    }
+}
+
+/// 刺激(光)の源。強さは距離dで`intensity / (1 + (d / radius)²)`になる
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Source {
+    /// 位置
+    pub position: (f32, f32),
+    /// 源での強さ
+    pub intensity: f32,
+    /// 強さが半分になる距離
+    pub radius: f32,
+}
+
+/// 刺激の源が置かれた2次元の場(周期境界条件)
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::abm::ContinuousSpace;
+/// use my_alife::algorithm::braitenberg::{Source, StimulusField};
+///
+/// let mut field = StimulusField::new(ContinuousSpace::new(100.0, 100.0));
+/// field.add_source(Source { position: (10.0, 10.0), intensity: 1.0, radius: 5.0 });
+/// assert_eq!(field.intensity((10.0, 10.0)), 1.0);
+/// // 端をまたいで距離5
+/// assert_eq!(field.intensity((10.0, 105.0)), 0.5);
+/// let heatmap = field.render((10, 10));
+/// assert_eq!(heatmap.dim(), (10, 10));
+/// assert!(heatmap[[0, 0]] > heatmap[[5, 5]]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct StimulusField {
+    space: ContinuousSpace,
+    sources: Vec<Source>,
+}
+
+impl StimulusField {
+    /// 刺激の源のない場
+    pub fn new(space: ContinuousSpace) -> StimulusField {
+        StimulusField {
+            space,
+            sources: Vec::new(),
+        }
+    }
+
+    /// 空間
+    pub fn space(&self) -> &ContinuousSpace {
+        &self.space
+    }
+
+    /// 刺激の源を加える
+    pub fn add_source(&mut self, source: Source) {
+        self.sources.push(source);
+    }
+
+    /// 刺激の源
+    pub fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    /// 刺激の源。動かしたり消したりするときに使う
+    pub fn sources_mut(&mut self) -> &mut Vec<Source> {
+        &mut self.sources
+    }
+
+    /// 位置`point`での刺激の強さ
+    pub fn intensity(&self, point: (f32, f32)) -> f32 {
+        self.sources
+            .iter()
+            .map(|source| falloff(&self.space, source, point))
+            .sum()
+    }
+
+    /// セルの中心での刺激の強さを、`dim`(行数, 列数)の行列にする。ヒートマップを描くのに使う
+    pub fn render(&self, dim: (usize, usize)) -> Matrix<f32> {
+        let (rows, cols) = dim;
+        Array2::from_shape_fn(dim, |(row, col)| {
+            self.intensity((
+                (col as f32 + 0.5) * self.space.width / cols as f32,
+                (row as f32 + 0.5) * self.space.height / rows as f32,
+            ))
+        })
+    }
+}
+
+fn falloff(space: &ContinuousSpace, source: &Source, point: (f32, f32)) -> f32 {
+    let distance = space.distance(source.position, point) / source.radius;
+    source.intensity / (1.0 + distance * distance)
+}
+
+/// 1台のビークル
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vehicle {
+    /// 位置
+    pub position: (f32, f32),
+    /// 向き(ラジアン)。x軸の正の向きが0で、y軸の正の向き(画面の下)に回る
+    pub heading: f32,
+    /// センサーと車輪のつなぎ方
+    pub wiring: Wiring,
+}
+
+/// 刺激の場の中を動くBraitenbergのビークルの集まり
+///
+/// 左右のセンサーで刺激を感じ、`Wiring`に従って左右の車輪を回す。
+/// `set_emission`で各ビークルにも刺激を出させると、ビークルどうしが追いかけたり避けたりする
+///
+/// # Example
+/// ```
+/// extern crate rand;
+/// extern crate my_alife;
+///
+/// use rand::{SeedableRng, XorShiftRng};
+/// use my_alife::algorithm::abm::ContinuousSpace;
+/// use my_alife::algorithm::braitenberg::{Braitenberg, Source, StimulusField, Vehicle, Wiring};
+///
+/// let space = ContinuousSpace::new(200.0, 200.0);
+/// let mut field = StimulusField::new(space);
+/// field.add_source(Source { position: (100.0, 70.0), intensity: 1.0, radius: 20.0 });
+/// let mut world = Braitenberg::new(field);
+/// // 刺激源の左下から右向きに走り出す
+/// for &wiring in &[Wiring::Type2a, Wiring::Type2b] {
+///     world.add_vehicle(Vehicle { position: (60.0, 100.0), heading: 0.0, wiring });
+/// }
+/// let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+/// for _ in 0..30 {
+///     world.step(0.1, &mut rng);
+/// }
+/// let distance = |i: usize| space.distance(world.vehicles()[i].position, (100.0, 70.0));
+/// // aggressionは刺激源に向かって曲がり、fearは遠ざかる
+/// assert!(distance(1) < distance(0));
+/// assert_eq!(world.sprites().len(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Braitenberg {
+    field: StimulusField,
+    vehicles: Vec<Vehicle>,
+    // 出力1のモーターで進む速さ、車輪の間隔
+    speed: f32,
+    axle: f32,
+    // センサーの中心からの距離と、正面からの角度
+    sensor_distance: f32,
+    sensor_angle: f32,
+    // 各ビークルが出す刺激(強さ, 広がり)
+    emission: Option<(f32, f32)>,
+    noise: f32,
+    time: f32,
+}
+
+impl Braitenberg {
+    /// ビークルのいない世界。速さ20、車輪の間隔4、センサーは正面から±45°で距離3、ビークルは刺激を出さず、ノイズはない
+    pub fn new(field: StimulusField) -> Braitenberg {
+        Braitenberg {
+            field,
+            vehicles: Vec::new(),
+            speed: 20.0,
+            axle: 4.0,
+            sensor_distance: 3.0,
+            sensor_angle: PI / 4.0,
+            emission: None,
+            noise: 0.0,
+            time: 0.0,
+        }
+    }
+
+    /// ビークルを加える
+    pub fn add_vehicle(&mut self, vehicle: Vehicle) {
+        self.vehicles.push(vehicle);
+    }
+
+    /// `wiring`のビークルを`count`台、ランダムな位置と向きに加える
+    pub fn add_random_vehicles<R: Rng>(&mut self, count: usize, wiring: Wiring, rng: &mut R) {
+        for _ in 0..count {
+            let position = self.field.space().random_position(rng);
+            let heading = rng.gen_range(-PI, PI);
+            self.add_vehicle(Vehicle {
+                position,
+                heading,
+                wiring,
+            });
+        }
+    }
+
+    /// 出力1のモーターで進む速さを設定する
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    /// 車輪の間隔を設定する。狭いほど鋭く曲がる
+    pub fn set_axle(&mut self, axle: f32) {
+        self.axle = axle;
+    }
+
+    /// センサーの中心からの距離と、正面からの角度(ラジアン)を設定する
+    pub fn set_sensors(&mut self, distance: f32, angle: f32) {
+        self.sensor_distance = distance;
+        self.sensor_angle = angle;
+    }
+
+    /// 各ビークルが出す刺激の強さと広がりを設定する。`None`ならビークルどうしは影響しない
+    pub fn set_emission(&mut self, emission: Option<(f32, f32)>) {
+        self.emission = emission;
+    }
+
+    /// 各ビークルが出す刺激の強さと広がり
+    pub fn emission(&self) -> Option<(f32, f32)> {
+        self.emission
+    }
+
+    /// 向きに加えるノイズの標準偏差(ラジアン/秒の平方根)を設定する
+    pub fn set_noise(&mut self, noise: f32) {
+        self.noise = noise;
+    }
+
+    /// 刺激の場
+    pub fn field(&self) -> &StimulusField {
+        &self.field
+    }
+
+    /// 刺激の場。刺激の源を動かすときに使う
+    pub fn field_mut(&mut self) -> &mut StimulusField {
+        &mut self.field
+    }
+
+    /// ビークル
+    pub fn vehicles(&self) -> &[Vehicle] {
+        &self.vehicles
+    }
+
+    /// ビークル
+    pub fn vehicles_mut(&mut self) -> &mut Vec<Vehicle> {
+        &mut self.vehicles
+    }
+
+    /// 経過した時間
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// `exclude`番目以外のビークルが感じる、位置`point`での刺激の強さ
+    pub fn stimulus(&self, point: (f32, f32), exclude: Option<usize>) -> f32 {
+        let mut stimulus = self.field.intensity(point);
+        if let Some((intensity, radius)) = self.emission {
+            let space = self.field.space();
+            for (i, vehicle) in self.vehicles.iter().enumerate() {
+                if Some(i) != exclude {
+                    let source = Source {
+                        position: vehicle.position,
+                        intensity,
+                        radius,
+                    };
+                    stimulus += falloff(space, &source, point);
+                }
+            }
+        }
+        stimulus
+    }
+
+    /// `index`番目のビークルの(左, 右)のセンサーの値
+    pub fn sense(&self, index: usize) -> (f32, f32) {
+        let vehicle = &self.vehicles[index];
+        // y軸が下向きなので、左は向きから反時計回り(角度が減る向き)
+        let sensor = |angle: f32| {
+            self.field.space().wrap((
+                vehicle.position.0 + self.sensor_distance * angle.cos(),
+                vehicle.position.1 + self.sensor_distance * angle.sin(),
+            ))
+        };
+        (
+            self.stimulus(sensor(vehicle.heading - self.sensor_angle), Some(index)),
+            self.stimulus(sensor(vehicle.heading + self.sensor_angle), Some(index)),
+        )
+    }
+
+    /// 時間`dt`だけ進める。全てのビークルが同時に感じてから動く
+    pub fn step<R: Rng>(&mut self, dt: f32, rng: &mut R) {
+        let motors: Vec<_> = (0..self.vehicles.len())
+            .map(|i| {
+                let (left, right) = self.sense(i);
+                self.vehicles[i].wiring.motors(left, right)
+            })
+            .collect();
+        let normal = Normal::new(0.0, 1.0);
+        let space = *self.field.space();
+        for (vehicle, (left, right)) in self.vehicles.iter_mut().zip(motors) {
+            let forward = self.speed * (left + right) / 2.0;
+            let turn = self.speed * (left - right) / self.axle;
+            let noise = if self.noise > 0.0 {
+                self.noise * dt.sqrt() * normal.ind_sample(rng) as f32
+            } else {
+                0.0
+            };
+            vehicle.heading = (vehicle.heading + turn * dt + noise + PI).rem_euclid(2.0 * PI) - PI;
+            vehicle.position = space.wrap((
+                vehicle.position.0 + forward * dt * vehicle.heading.cos(),
+                vehicle.position.1 + forward * dt * vehicle.heading.sin(),
+            ));
+        }
+        self.time += dt;
+    }
+
+    /// `AgentVisualizer`で描くための、つなぎ方ごとの色をつけたビークル
+    pub fn sprites(&self) -> Vec<AgentSprite> {
+        self.vehicles
+            .iter()
+            .map(|vehicle| AgentSprite {
+                position: vehicle.position,
+                heading: vehicle.heading,
+                color: vehicle.wiring.color(),
+            })
+            .collect()
+    }
+}
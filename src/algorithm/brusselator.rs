@@ -0,0 +1,88 @@
+use algorithm::reaction_diffusion::{CellReaction, ReactionDiffusion};
+use ndarray::Array2;
+use rand::Rng;
+use visualizer::Matrix;
+
+/// Brusselatorモデル `du/dt = a - (b + 1)u + u²v`, `dv/dt = bu - u²v`
+///
+/// `b > 1 + a²`で一様に振動し、`dv`が`du`より十分大きいとTuringパターンができる
+///
+/// # Example
+/// ```
+/// extern crate my_alife;
+/// extern crate rand;
+///
+/// use my_alife::algorithm::brusselator::Brusselator;
+/// use my_alife::algorithm::integrator::Integrator;
+///
+/// let model = Brusselator::default();
+/// let mut fields = model.initial_fields((32, 32), &mut rand::thread_rng());
+/// for _ in 0..100 {
+///     Integrator::SemiImplicitEuler.step(&model, &mut fields, 0.01);
+/// }
+/// assert!(fields.iter().all(|field| field.iter().all(|e| e.is_finite())));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Brusselator {
+    /// 原料Aの濃度
+    pub a: f32,
+    /// 原料Bの濃度
+    pub b: f32,
+    /// uの拡散係数
+    pub du: f32,
+    /// vの拡散係数
+    pub dv: f32,
+}
+
+impl Default for Brusselator {
+    /// Turingパターンができるパラメータ
+    fn default() -> Brusselator {
+        Brusselator {
+            a: 4.5,
+            b: 7.5,
+            du: 2.0,
+            dv: 16.0,
+        }
+    }
+}
+
+impl Brusselator {
+    /// 一様な定常状態(u, v) = (a, b/a)に少しノイズを加えた初期状態
+    pub fn initial_fields<R: Rng>(&self, dim: (usize, usize), rng: &mut R) -> Vec<Matrix<f32>> {
+        let steady = [self.a, self.b / self.a];
+        steady
+            .iter()
+            .map(|&value| Array2::from_shape_fn(dim, |_| value * (1.0 + rng.gen_range(-0.05, 0.05))))
+            .collect()
+    }
+
+    /// 描画するときの各物質の濃度の範囲の目安
+    pub fn ranges(&self) -> Vec<(f32, f32)> {
+        vec![(0.0, 2.0 * self.a), (0.0, 2.0 * self.b / self.a)]
+    }
+}
+
+impl ReactionDiffusion for Brusselator {
+    fn dx(&self) -> f32 {
+        1.0
+    }
+
+    fn diffusions(&self) -> Vec<f32> {
+        vec![self.du, self.dv]
+    }
+
+    fn reaction(&self, fields: &[Matrix<f32>]) -> Vec<Matrix<f32>> {
+        let (u, v) = (&fields[0], &fields[1]);
+        let uuv = u * u * v;
+        vec![&uuv - &(u * (self.b + 1.0)) + self.a, u * self.b - &uuv]
+    }
+}
+
+impl CellReaction for Brusselator {
+    fn cell_reaction(&self, cell: &[f32], rates: &mut [f32]) {
+        let (u, v) = (cell[0], cell[1]);
+        let uuv = u * u * v;
+        rates[0] = uuv - u * (self.b + 1.0) + self.a;
+        rates[1] = u * self.b - uuv;
+    }
+}
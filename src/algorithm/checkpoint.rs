@@ -0,0 +1,244 @@
+use failure;
+use rand::Rng;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// 状態を書き出して再開できる乱数生成器(xorshift128)
+///
+/// 途中から再開しても、中断しなかったときと同じ乱数列が続く
+///
+/// # Example
+/// ```
+/// extern crate my_alife;
+/// extern crate rand;
+///
+/// use my_alife::algorithm::checkpoint::CheckpointRng;
+/// use rand::Rng;
+///
+/// let mut rng = CheckpointRng::new(42);
+/// rng.gen::<f32>();
+/// let mut resumed: CheckpointRng = rng.to_string().parse().unwrap();
+/// assert_eq!(rng.gen::<u32>(), resumed.gen::<u32>());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckpointRng {
+    state: [u32; 4],
+}
+
+impl CheckpointRng {
+    /// `seed`から生成する
+    pub fn new(seed: u64) -> CheckpointRng {
+        // SplitMix64で状態を作る。状態がすべて0になることはない
+        let mut z = seed;
+        let mut next = || {
+            z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut x = z;
+            x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            x ^ (x >> 31)
+        };
+        let (a, b) = (next(), next());
+        let mut state = [a as u32, (a >> 32) as u32, b as u32, (b >> 32) as u32];
+        if state.iter().all(|&s| s == 0) {
+            state[0] = 1;
+        }
+        CheckpointRng { state }
+    }
+
+    /// 内部状態
+    pub fn state(&self) -> [u32; 4] {
+        self.state
+    }
+
+    /// 内部状態から復元する
+    ///
+    /// # Panics
+    /// 状態がすべて0のとき
+    pub fn from_state(state: [u32; 4]) -> CheckpointRng {
+        assert!(state.iter().any(|&s| s != 0), "xorshift state must not be all zero");
+        CheckpointRng { state }
+    }
+}
+
+impl Rng for CheckpointRng {
+    fn next_u32(&mut self) -> u32 {
+        let [x, y, z, w] = self.state;
+        let t = x ^ (x << 11);
+        let next = w ^ (w >> 19) ^ (t ^ (t >> 8));
+        self.state = [y, z, w, next];
+        next
+    }
+}
+
+impl fmt::Display for CheckpointRng {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [a, b, c, d] = self.state;
+        write!(f, "{} {} {} {}", a, b, c, d)
+    }
+}
+
+impl FromStr for CheckpointRng {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<CheckpointRng, failure::Error> {
+        let words: Vec<u32> = parse_list(s)?;
+        if words.len() != 4 || words.iter().all(|&w| w == 0) {
+            return Err(format_err!("invalid rng state \"{}\"", s));
+        }
+        Ok(CheckpointRng::from_state([words[0], words[1], words[2], words[3]]))
+    }
+}
+
+/// 1行の文字列との相互変換。チェックポイントに遺伝子型などを書き出すのに使う
+pub trait Persist: Sized {
+    /// 改行を含まない文字列にする
+    fn encode(&self) -> String;
+
+    /// `encode`した文字列から復元する
+    fn decode(s: &str) -> Result<Self, failure::Error>;
+}
+
+/// 空白区切りの実数。f32の`Display`は読み戻すと同じ値になるので、復元した値は元と完全に一致する
+impl Persist for Vec<f32> {
+    fn encode(&self) -> String {
+        join(self)
+    }
+
+    fn decode(s: &str) -> Result<Vec<f32>, failure::Error> {
+        parse_list(s)
+    }
+}
+
+/// 値を空白区切りの1行にする
+pub fn join<T: fmt::Display>(values: &[T]) -> String {
+    values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+/// 空白区切りの1行を値の並びにする
+pub fn parse_list<T: FromStr>(s: &str) -> Result<Vec<T>, failure::Error> {
+    s.split_whitespace()
+        .map(|word| word.parse().map_err(|_| format_err!("invalid value \"{}\"", word)))
+        .collect()
+}
+
+/// 「キー 値」の行を並べたチェックポイント。同じキーを何度も使える
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::checkpoint::Checkpoint;
+///
+/// let mut checkpoint = Checkpoint::new("example");
+/// checkpoint.push("generation", 12);
+/// checkpoint.push("genome", "0.5 1");
+/// checkpoint.push("genome", "2 -3");
+/// let restored: Checkpoint = checkpoint.to_string().parse().unwrap();
+/// assert_eq!(restored.kind(), "example");
+/// assert_eq!(restored.parse::<usize>("generation").unwrap(), 12);
+/// assert_eq!(restored.all("genome"), vec!["0.5 1", "2 -3"]);
+/// assert!(restored.get("fitness").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    kind: String,
+    entries: Vec<(String, String)>,
+}
+
+impl Checkpoint {
+    /// 種類`kind`の空のチェックポイントを生成する
+    pub fn new(kind: &str) -> Checkpoint {
+        Checkpoint {
+            kind: kind.to_string(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// 何の状態を書き出したものか
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// 種類が`kind`であることを確かめる
+    pub fn expect_kind(&self, kind: &str) -> Result<(), failure::Error> {
+        if self.kind != kind {
+            return Err(format_err!("expected a {} checkpoint, but got {}", kind, self.kind));
+        }
+        Ok(())
+    }
+
+    /// 行を追加する。値は改行を含んではいけない
+    pub fn push<T: fmt::Display>(&mut self, key: &str, value: T) {
+        let value = value.to_string();
+        assert!(!value.contains('\n'), "checkpoint values must be single line");
+        self.entries.push((key.to_string(), value));
+    }
+
+    /// キー`key`の最初の値
+    pub fn get(&self, key: &str) -> Result<&str, failure::Error> {
+        self.entries
+            .iter()
+            .find(|entry| entry.0 == key)
+            .map(|entry| entry.1.as_str())
+            .ok_or_else(|| format_err!("checkpoint has no \"{}\"", key))
+    }
+
+    /// キー`key`の最初の値を`T`として読む
+    pub fn parse<T: FromStr>(&self, key: &str) -> Result<T, failure::Error> {
+        let value = self.get(key)?;
+        value
+            .parse()
+            .map_err(|_| format_err!("invalid value \"{}\" for \"{}\"", value, key))
+    }
+
+    /// キー`key`のすべての値
+    pub fn all(&self, key: &str) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.0 == key)
+            .map(|entry| entry.1.as_str())
+            .collect()
+    }
+
+    /// ファイルに書き出す
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), failure::Error> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    /// `save`で書き出したファイルから読み込む
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Checkpoint, failure::Error> {
+        fs::read_to_string(path)?.parse()
+    }
+}
+
+impl fmt::Display for Checkpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "checkpoint {}", self.kind)?;
+        for (key, value) in &self.entries {
+            writeln!(f, "{} {}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Checkpoint {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Checkpoint, failure::Error> {
+        let mut lines = s.lines();
+        let kind = match lines.next().map(|line| line.splitn(2, ' ').collect::<Vec<_>>()) {
+            Some(ref header) if header.len() == 2 && header[0] == "checkpoint" => header[1].to_string(),
+            _ => return Err(format_err!("not a checkpoint")),
+        };
+        let entries = lines
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let mut parts = line.splitn(2, ' ');
+                let key = parts.next().unwrap_or("").to_string();
+                (key, parts.next().unwrap_or("").to_string())
+            })
+            .collect();
+        Ok(Checkpoint { kind, entries })
+    }
+}
@@ -0,0 +1,363 @@
+use algorithm::gillespie::{Gillespie, ReactionNetwork};
+use failure;
+use rand::Rng;
+use std::collections::HashMap;
+
+/// 触媒を持つ反応。分子は`Chemistry::molecules`の番号で表す
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalyzedReaction {
+    /// 消費される分子
+    pub reactants: Vec<usize>,
+    /// 生成される分子
+    pub products: Vec<usize>,
+    /// この反応を触媒する分子
+    pub catalysts: Vec<usize>,
+}
+
+/// 分子と触媒反応の一覧からなる人工化学
+///
+/// 外から供給される食料(food set)の分子から、触媒と反応物を互いに作り合う自己触媒集合(RAF集合)を調べられる
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::chemistry::Chemistry;
+///
+/// let mut chemistry = Chemistry::new(&["a", "b", "ab", "abb", "x"], &["a", "b"]);
+/// // abは自分自身を作る反応を触媒する
+/// chemistry.add_reaction(&["a", "b"], &["ab"], &["ab"]).unwrap();
+/// // 食料から作れないxが触媒する反応は、自己触媒集合に入らない
+/// chemistry.add_reaction(&["ab", "b"], &["abb"], &["x"]).unwrap();
+/// assert_eq!(chemistry.max_raf(), vec![0]);
+/// assert!(chemistry.is_raf(&[0]));
+/// assert!(!chemistry.is_raf(&[0, 1]));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chemistry {
+    molecules: Vec<String>,
+    index: HashMap<String, usize>,
+    food: Vec<usize>,
+    reactions: Vec<CatalyzedReaction>,
+}
+
+impl Chemistry {
+    /// 分子`molecules`のうち、`food`を外から供給される食料とする、反応のない化学
+    ///
+    /// # Panics
+    /// `food`に`molecules`にない分子が含まれるとき
+    pub fn new(molecules: &[&str], food: &[&str]) -> Chemistry {
+        let molecules: Vec<String> = molecules.iter().map(|name| name.to_string()).collect();
+        let index = molecules
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.clone(), i))
+            .collect::<HashMap<_, _>>();
+        let food = food
+            .iter()
+            .map(|name| *index.get(*name).expect("food must be one of the molecules"))
+            .collect();
+        Chemistry {
+            molecules,
+            index,
+            food,
+            reactions: Vec::new(),
+        }
+    }
+
+    /// Kauffmanの2進ポリマーのモデル
+    ///
+    /// 長さ`max_length`までの0と1の列を分子とし、長さ`food_length`までを食料とする。
+    /// 2つの分子をつなぐ反応(ligation)と1つの分子を2つに切る反応(cleavage)を全て考え、
+    /// それぞれの反応を各分子が確率`catalysis`で触媒する
+    ///
+    /// # Example
+    /// ```
+    /// extern crate rand;
+    /// extern crate my_alife;
+    ///
+    /// use rand::{SeedableRng, XorShiftRng};
+    /// use my_alife::algorithm::chemistry::Chemistry;
+    ///
+    /// let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    /// let chemistry = Chemistry::binary_polymer(6, 2, 0.004, &mut rng);
+    /// assert_eq!(chemistry.molecules().len(), 126);
+    /// assert_eq!(chemistry.food().len(), 6);
+    /// // 1分子あたり平均4個くらいの反応を触媒すると、ほぼ確実に自己触媒集合ができる
+    /// let raf = chemistry.max_raf();
+    /// assert!(!raf.is_empty());
+    /// assert!(chemistry.is_raf(&raf));
+    ///
+    /// // 触媒がなければ自己触媒集合はできない
+    /// assert!(Chemistry::binary_polymer(6, 2, 0.0, &mut rng).max_raf().is_empty());
+    /// ```
+    pub fn binary_polymer<R: Rng>(max_length: usize, food_length: usize, catalysis: f32, rng: &mut R) -> Chemistry {
+        let mut molecules = Vec::new();
+        for length in 1..=max_length {
+            for bits in 0..1usize << length {
+                molecules.push(format!("{:01$b}", bits, length));
+            }
+        }
+        let names: Vec<&str> = molecules.iter().map(String::as_str).collect();
+        let food: Vec<&str> = names.iter().cloned().filter(|name| name.len() <= food_length).collect();
+        let mut chemistry = Chemistry::new(&names, &food);
+        for i in 0..chemistry.molecules.len() {
+            let polymer = chemistry.molecules[i].clone();
+            for split in 1..polymer.len() {
+                let (left, right) = (chemistry.index[&polymer[..split]], chemistry.index[&polymer[split..]]);
+                for (reactants, products) in &[(vec![left, right], vec![i]), (vec![i], vec![left, right])] {
+                    let catalysts = (0..chemistry.molecules.len())
+                        .filter(|_| rng.gen::<f32>() < catalysis)
+                        .collect();
+                    chemistry.reactions.push(CatalyzedReaction {
+                        reactants: reactants.clone(),
+                        products: products.clone(),
+                        catalysts,
+                    });
+                }
+            }
+        }
+        chemistry
+    }
+
+    /// 名前で指定した反応を加える
+    pub fn add_reaction(
+        &mut self,
+        reactants: &[&str],
+        products: &[&str],
+        catalysts: &[&str],
+    ) -> Result<usize, failure::Error> {
+        let lookup = |names: &[&str]| -> Result<Vec<usize>, failure::Error> {
+            names
+                .iter()
+                .map(|name| {
+                    self.molecule_index(name)
+                        .ok_or_else(|| format_err!("unknown molecule \"{}\"", name))
+                })
+                .collect()
+        };
+        let reaction = CatalyzedReaction {
+            reactants: lookup(reactants)?,
+            products: lookup(products)?,
+            catalysts: lookup(catalysts)?,
+        };
+        self.reactions.push(reaction);
+        Ok(self.reactions.len() - 1)
+    }
+
+    /// 分子の名前
+    pub fn molecules(&self) -> &[String] {
+        &self.molecules
+    }
+
+    /// 名前が`name`の分子の番号
+    pub fn molecule_index(&self, name: &str) -> Option<usize> {
+        self.index.get(name).cloned()
+    }
+
+    /// 食料の分子
+    pub fn food(&self) -> &[usize] {
+        &self.food
+    }
+
+    /// 反応
+    pub fn reactions(&self) -> &[CatalyzedReaction] {
+        &self.reactions
+    }
+
+    /// 食料から`reactions`の反応だけで作れる分子(触媒は考えない)。分子ごとに作れるかどうかを返す
+    pub fn closure(&self, reactions: &[usize]) -> Vec<bool> {
+        let mut reachable = vec![false; self.molecules.len()];
+        for &food in &self.food {
+            reachable[food] = true;
+        }
+        loop {
+            let mut changed = false;
+            for &r in reactions {
+                let reaction = &self.reactions[r];
+                if reaction.reactants.iter().all(|&m| reachable[m]) {
+                    for &product in &reaction.products {
+                        if !reachable[product] {
+                            reachable[product] = true;
+                            changed = true;
+                        }
+                    }
+                }
+            }
+            if !changed {
+                return reachable;
+            }
+        }
+    }
+
+    /// `reactions`がRAF集合(どの反応も集合の中の反応で食料から作れる分子に触媒され、反応物も作れる)かどうか
+    pub fn is_raf(&self, reactions: &[usize]) -> bool {
+        if reactions.is_empty() {
+            return false;
+        }
+        let reachable = self.closure(reactions);
+        reactions.iter().all(|&r| self.supported(r, &reachable))
+    }
+
+    /// 最大のRAF集合の反応の番号。Hordijk & Steelのアルゴリズムで求める。RAF集合がなければ空
+    pub fn max_raf(&self) -> Vec<usize> {
+        self.max_raf_within((0..self.reactions.len()).collect(), |_| true)
+    }
+
+    // `present`な分子だけを触媒として使うときの、`reactions`の中の最大のRAF集合
+    fn max_raf_within<P: Fn(usize) -> bool>(&self, mut reactions: Vec<usize>, present: P) -> Vec<usize> {
+        loop {
+            let reachable = self.closure(&reactions);
+            let before = reactions.len();
+            reactions.retain(|&r| {
+                let reaction = &self.reactions[r];
+                reaction.reactants.iter().all(|&m| reachable[m])
+                    && reaction.catalysts.iter().any(|&m| reachable[m] && present(m))
+            });
+            if reactions.len() == before {
+                return reactions;
+            }
+        }
+    }
+
+    fn supported(&self, r: usize, reachable: &[bool]) -> bool {
+        let reaction = &self.reactions[r];
+        reaction.reactants.iter().all(|&m| reachable[m]) && reaction.catalysts.iter().any(|&m| reachable[m])
+    }
+}
+
+/// よく混ざった反応槽(流通型の反応器)のパラメータ
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReactorParams {
+    /// 食料の分子が1種類あたり流れ込む速さ
+    pub inflow: f64,
+    /// 1分子が流れ出る速さ
+    pub outflow: f64,
+    /// 触媒がないときの反応速度定数
+    pub spontaneous: f64,
+    /// 触媒が1分子あるときの反応速度定数
+    pub catalyzed: f64,
+}
+
+impl Default for ReactorParams {
+    fn default() -> ReactorParams {
+        ReactorParams {
+            inflow: 10.0,
+            outflow: 0.01,
+            spontaneous: 1e-6,
+            catalyzed: 1e-2,
+        }
+    }
+}
+
+/// `Chemistry`を流通型の反応槽で動かす。時間発展は`Gillespie`で正確に解く
+///
+/// 食料が流れ込み、全ての分子が一定の速さで流れ出る中で、触媒された反応が自己触媒集合を作って増えていく様子を調べる
+///
+/// # Example
+/// ```
+/// extern crate rand;
+/// extern crate my_alife;
+///
+/// use rand::{SeedableRng, XorShiftRng};
+/// use my_alife::algorithm::chemistry::{Chemistry, Reactor, ReactorParams};
+///
+/// let mut chemistry = Chemistry::new(&["a", "b", "ab"], &["a", "b"]);
+/// chemistry.add_reaction(&["a", "b"], &["ab"], &["ab"]).unwrap();
+/// let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+/// let mut reactor = Reactor::new(chemistry, ReactorParams::default());
+/// // 始めはabがないので、自己触媒集合は動いていない
+/// assert!(reactor.active_raf(1).is_empty());
+/// reactor.run_until(200.0, &mut rng);
+/// // 自然に起きた反応でabができると、abが自分を増やすようになる
+/// assert_eq!(reactor.active_raf(1), vec![0]);
+/// assert!(reactor.raf_fraction(&[0]) > 0.0);
+/// ```
+pub struct Reactor {
+    chemistry: Chemistry,
+    ssa: Gillespie,
+}
+
+impl Reactor {
+    /// 分子のない反応槽
+    pub fn new(chemistry: Chemistry, params: ReactorParams) -> Reactor {
+        let names: Vec<&str> = chemistry.molecules.iter().map(String::as_str).collect();
+        let mut network = ReactionNetwork::new(&names);
+        let count = |molecules: &[usize]| molecules.iter().map(|&m| (m, 1)).collect::<Vec<_>>();
+        for reaction in &chemistry.reactions {
+            let (reactants, products) = (count(&reaction.reactants), count(&reaction.products));
+            network = network.reaction(&reactants, &products, params.spontaneous);
+            for &catalyst in &reaction.catalysts {
+                let with = |terms: &[(usize, u32)]| {
+                    let mut terms = terms.to_vec();
+                    terms.push((catalyst, 1));
+                    terms
+                };
+                network = network.reaction(&with(&reactants), &with(&products), params.catalyzed);
+            }
+        }
+        for &food in &chemistry.food {
+            network = network.reaction(&[], &[(food, 1)], params.inflow);
+        }
+        for m in 0..chemistry.molecules.len() {
+            network = network.reaction(&[(m, 1)], &[], params.outflow);
+        }
+        let counts = vec![0; chemistry.molecules.len()];
+        Reactor {
+            ssa: Gillespie::new(network, counts),
+            chemistry,
+        }
+    }
+
+    /// 人工化学
+    pub fn chemistry(&self) -> &Chemistry {
+        &self.chemistry
+    }
+
+    /// 分子ごとの数
+    pub fn counts(&self) -> &[u64] {
+        self.ssa.counts()
+    }
+
+    /// 時刻
+    pub fn time(&self) -> f64 {
+        self.ssa.time()
+    }
+
+    /// 時刻`end`まで進める
+    pub fn run_until<R: Rng>(&mut self, end: f64, rng: &mut R) {
+        self.ssa.run_until(end, rng);
+    }
+
+    /// 今`threshold`個以上ある分子だけが触媒になるときの最大のRAF集合。反応槽の中で実際に動いている自己触媒集合
+    pub fn active_raf(&self, threshold: u64) -> Vec<usize> {
+        let counts = self.ssa.counts();
+        self.chemistry
+            .max_raf_within((0..self.chemistry.reactions.len()).collect(), |m| {
+                counts[m] >= threshold
+            })
+    }
+
+    /// 食料以外の分子のうち、`raf`の反応で作られる分子の割合
+    pub fn raf_fraction(&self, raf: &[usize]) -> f64 {
+        let counts = self.ssa.counts();
+        let mut produced = vec![false; counts.len()];
+        for &r in raf {
+            for &product in &self.chemistry.reactions[r].products {
+                produced[product] = true;
+            }
+        }
+        let mut food = vec![false; counts.len()];
+        for &m in &self.chemistry.food {
+            food[m] = true;
+        }
+        let (inside, total) = (0..counts.len())
+            .filter(|&m| !food[m])
+            .fold((0, 0), |(inside, total), m| {
+                (inside + if produced[m] { counts[m] } else { 0 }, total + counts[m])
+            });
+        if total == 0 {
+            0.0
+        } else {
+            inside as f64 / total as f64
+        }
+    }
+}
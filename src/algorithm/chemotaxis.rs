@@ -0,0 +1,456 @@
+use algorithm::abm::ContinuousSpace;
+use algorithm::integrator::Integrator;
+use algorithm::reaction_diffusion::ReactionDiffusion;
+use ndarray::Array2;
+use rand::distributions::{IndependentSample, Normal};
+use rand::Rng;
+use std::f32::consts::PI;
+use visualizer::agent_visualizer::AgentSprite;
+use visualizer::Matrix;
+
+// 大腸菌の方向転換の角度の平均と標準偏差(度)
+const TUMBLE_ANGLE_MEAN: f64 = 68.0;
+const TUMBLE_ANGLE_STD: f64 = 36.0;
+// 濃度が下がっているときに向きを変える頻度を上げる倍率の上限(exp)
+const MAX_RESPONSE: f32 = 3.0;
+
+/// 拡散して分解される栄養(誘引物質)の場。`supply`のところで湧き出す
+///
+/// `du/dt = D * ∇²u + supply - decay * u`を`ReactionDiffusion`として解く
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::algorithm::chemotaxis::NutrientField;
+///
+/// let mut nutrient = NutrientField::new((32, 32), 1.0);
+/// nutrient.add_source((16, 16), 2, 1.0);
+/// let mut fields = vec![Array2::zeros((32, 32))];
+/// nutrient.equilibrate(&mut fields, 200, 1.0);
+/// // 湧き出すところから離れるほど薄い
+/// assert!(fields[0][[16, 16]] > fields[0][[16, 24]]);
+/// assert!(fields[0][[16, 24]] > fields[0][[0, 0]]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct NutrientField {
+    /// 拡散係数
+    pub diffusion: f32,
+    /// 分解の速さ
+    pub decay: f32,
+    dx: f32,
+    supply: Matrix<f32>,
+}
+
+impl NutrientField {
+    /// 大きさ`dim`(行, 列)で、1セルの一辺が`dx`の場。初期値は拡散係数1.0、分解の速さ0.01で、湧き出すところはない
+    pub fn new(dim: (usize, usize), dx: f32) -> NutrientField {
+        NutrientField {
+            diffusion: 1.0,
+            decay: 0.01,
+            dx,
+            supply: Array2::zeros(dim),
+        }
+    }
+
+    /// 場の大きさ(行, 列)
+    pub fn dim(&self) -> (usize, usize) {
+        self.supply.dim()
+    }
+
+    /// セルごとの湧き出す速さ
+    pub fn supply(&self) -> &Matrix<f32> {
+        &self.supply
+    }
+
+    /// セルごとの湧き出す速さを変更する
+    pub fn supply_mut(&mut self) -> &mut Matrix<f32> {
+        &mut self.supply
+    }
+
+    /// `center`(行, 列)を中心とした半径`radius`セルの円から、速さ`rate`で湧き出させる
+    pub fn add_source(&mut self, center: (usize, usize), radius: usize, rate: f32) {
+        let (rows, cols) = self.dim();
+        let r = radius as isize;
+        for dr in -r..=r {
+            for dc in -r..=r {
+                if dr * dr + dc * dc <= r * r {
+                    let row = (center.0 as isize + dr).rem_euclid(rows as isize) as usize;
+                    let col = (center.1 as isize + dc).rem_euclid(cols as isize) as usize;
+                    self.supply[[row, col]] += rate;
+                }
+            }
+        }
+    }
+
+    /// 半陰解法で`steps`回、時間`dt`ずつ進めて定常状態に近づける
+    pub fn equilibrate(&self, fields: &mut [Matrix<f32>], steps: usize, dt: f32) {
+        for _ in 0..steps {
+            Integrator::SemiImplicitEuler.step(self, fields, dt);
+        }
+    }
+}
+
+impl ReactionDiffusion for NutrientField {
+    fn dx(&self) -> f32 {
+        self.dx
+    }
+
+    fn diffusions(&self) -> Vec<f32> {
+        vec![self.diffusion]
+    }
+
+    fn reaction(&self, fields: &[Matrix<f32>]) -> Vec<Matrix<f32>> {
+        vec![&self.supply - &(&fields[0] * self.decay)]
+    }
+}
+
+/// 1匹の走って向きを変えた記録
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TumbleStats {
+    /// 向きを変えた回数
+    pub tumbles: usize,
+    /// 今の走りの長さ(時間)
+    pub current_run: f32,
+    /// 終わった走りの長さの合計
+    pub completed_run_time: f32,
+    /// 一番長かった走り
+    pub longest_run: f32,
+    /// 濃度が上がっている間に走った時間
+    pub up_gradient_time: f32,
+    /// 濃度が下がっている間に走った時間
+    pub down_gradient_time: f32,
+}
+
+impl TumbleStats {
+    /// 終わった走りの長さの平均。まだ向きを変えていないときは`None`
+    pub fn mean_run(&self) -> Option<f32> {
+        if self.tumbles == 0 {
+            None
+        } else {
+            Some(self.completed_run_time / self.tumbles as f32)
+        }
+    }
+
+    /// 濃度が上がっている間に走った時間の割合
+    pub fn up_gradient_fraction(&self) -> f32 {
+        let total = self.up_gradient_time + self.down_gradient_time;
+        if total > 0.0 {
+            self.up_gradient_time / total
+        } else {
+            0.0
+        }
+    }
+}
+
+/// 走って(run)時々向きを変える(tumble)細菌
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bacterium {
+    /// 位置
+    pub position: (f32, f32),
+    /// 向き(ラジアン)
+    pub heading: f32,
+    /// 少し前までに感じた濃度の平均。今の濃度と比べて濃度が上がっているかを知る
+    pub memory: f32,
+    /// 走りと方向転換の記録
+    pub stats: TumbleStats,
+}
+
+/// 拡散する栄養の場の中で、走って向きを変える細菌(大腸菌)の走化性のモデル
+///
+/// 細菌は濃度を空間的には比べられず、少し前に感じた濃度と今の濃度を比べる。
+/// 濃度が上がっている間は向きを変える頻度`tumble_rate * exp(-gain * (c - memory))`が下がり、長く走るので、
+/// ランダムに動きながらも濃い方へ集まる
+///
+/// # Example
+/// ```
+/// extern crate rand;
+/// extern crate my_alife;
+///
+/// use rand::{SeedableRng, XorShiftRng};
+/// use my_alife::algorithm::chemotaxis::{Chemotaxis, NutrientField};
+///
+/// let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+/// let mut nutrient = NutrientField::new((40, 40), 1.0);
+/// nutrient.add_source((20, 20), 2, 1.0);
+/// let mut model = Chemotaxis::new(nutrient);
+/// model.equilibrate(300, 1.0);
+/// model.add_random_bacteria(200, &mut rng);
+/// let before = model.mean_sensed();
+/// for _ in 0..500 {
+///     model.step(0.1, &mut rng);
+/// }
+/// // 濃い方を登って集まる
+/// assert!(model.mean_sensed() > 2.0 * before);
+/// assert!(model.bacteria().iter().all(|b| b.stats.tumbles > 0));
+/// assert!(model.up_gradient_fraction() > 0.5);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Chemotaxis {
+    nutrient: NutrientField,
+    fields: Vec<Matrix<f32>>,
+    space: ContinuousSpace,
+    bacteria: Vec<Bacterium>,
+    integrator: Integrator,
+    speed: f32,
+    tumble_rate: f32,
+    gain: f32,
+    memory_time: f32,
+    uptake: f32,
+    secretion: f32,
+    time: f32,
+}
+
+impl Chemotaxis {
+    /// 栄養の場`nutrient`の中に細菌のいないモデルを作る。濃度は0から始まる
+    ///
+    /// 初期値は速さ2.0、向きを変える頻度1.0(平均1秒走る)、感度`gain`5.0、記憶の時間1.0で、
+    /// 細菌は栄養を食べず、出しもしない
+    pub fn new(nutrient: NutrientField) -> Chemotaxis {
+        let (rows, cols) = nutrient.dim();
+        let space = ContinuousSpace::new(cols as f32 * nutrient.dx, rows as f32 * nutrient.dx);
+        Chemotaxis {
+            fields: vec![Array2::zeros((rows, cols))],
+            nutrient,
+            space,
+            bacteria: Vec::new(),
+            integrator: Integrator::SemiImplicitEuler,
+            speed: 2.0,
+            tumble_rate: 1.0,
+            gain: 5.0,
+            memory_time: 1.0,
+            uptake: 0.0,
+            secretion: 0.0,
+            time: 0.0,
+        }
+    }
+
+    /// 細菌が動く空間
+    pub fn space(&self) -> &ContinuousSpace {
+        &self.space
+    }
+
+    /// 栄養の場の設定
+    pub fn nutrient(&self) -> &NutrientField {
+        &self.nutrient
+    }
+
+    /// 栄養の場の設定を変更する
+    pub fn nutrient_mut(&mut self) -> &mut NutrientField {
+        &mut self.nutrient
+    }
+
+    /// 栄養の濃度
+    pub fn concentration(&self) -> &Matrix<f32> {
+        &self.fields[0]
+    }
+
+    /// 栄養の濃度を変更する
+    pub fn concentration_mut(&mut self) -> &mut Matrix<f32> {
+        &mut self.fields[0]
+    }
+
+    /// 栄養の場を解く方法を設定する。初期値は`Integrator::SemiImplicitEuler`
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        self.integrator = integrator;
+    }
+
+    /// 走る速さを設定する
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    /// 濃度が変わらないときに向きを変える頻度(1秒あたり)を設定する
+    pub fn set_tumble_rate(&mut self, tumble_rate: f32) {
+        self.tumble_rate = tumble_rate;
+    }
+
+    /// 濃度の上がり方に対する感度を設定する。0にすると走化性のないランダムウォークになる
+    pub fn set_gain(&mut self, gain: f32) {
+        self.gain = gain;
+    }
+
+    /// 濃度を覚えている時間を設定する
+    ///
+    /// # Panics
+    /// `memory_time`が正でないとき
+    pub fn set_memory_time(&mut self, memory_time: f32) {
+        assert!(memory_time > 0.0, "memory time must be positive");
+        self.memory_time = memory_time;
+    }
+
+    /// 細菌がいるセルの栄養を食べる速さ(濃度に比例)を設定する
+    pub fn set_uptake(&mut self, uptake: f32) {
+        self.uptake = uptake;
+    }
+
+    /// 細菌が誘引物質を出す速さを設定する。ほかの細菌を呼び寄せて集まりやすくなる
+    pub fn set_secretion(&mut self, secretion: f32) {
+        self.secretion = secretion;
+    }
+
+    /// 細菌を動かさずに栄養の場だけを`steps`回、時間`dt`ずつ進める
+    pub fn equilibrate(&mut self, steps: usize, dt: f32) {
+        for _ in 0..steps {
+            self.integrator.step(&self.nutrient, &mut self.fields, dt);
+        }
+    }
+
+    /// 位置`position`、向き`heading`の細菌を加える。今いるところの濃度を覚えた状態から始まる
+    pub fn add_bacterium(&mut self, position: (f32, f32), heading: f32) {
+        let position = self.space.wrap(position);
+        let memory = self.sense(position);
+        self.bacteria.push(Bacterium {
+            position,
+            heading,
+            memory,
+            stats: TumbleStats::default(),
+        });
+    }
+
+    /// ランダムな位置と向きの細菌を`count`匹加える
+    pub fn add_random_bacteria<R: Rng>(&mut self, count: usize, rng: &mut R) {
+        for _ in 0..count {
+            let position = self.space.random_position(rng);
+            let heading = rng.gen_range(0.0, 2.0 * PI);
+            self.add_bacterium(position, heading);
+        }
+    }
+
+    /// 細菌たち
+    pub fn bacteria(&self) -> &[Bacterium] {
+        &self.bacteria
+    }
+
+    /// 細菌たちを変更する
+    pub fn bacteria_mut(&mut self) -> &mut Vec<Bacterium> {
+        &mut self.bacteria
+    }
+
+    /// 経過した時間
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// 位置`point`の濃度。周りの4セルから双線形補間する
+    pub fn sense(&self, point: (f32, f32)) -> f32 {
+        let field = &self.fields[0];
+        let (rows, cols) = field.dim();
+        let dx = self.nutrient.dx;
+        let (x, y) = (point.0 / dx - 0.5, point.1 / dx - 0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let wrap = |v: f32, n: usize| (v as isize).rem_euclid(n as isize) as usize;
+        let (c0, c1) = (wrap(x0, cols), wrap(x0 + 1.0, cols));
+        let (r0, r1) = (wrap(y0, rows), wrap(y0 + 1.0, rows));
+        let top = field[[r0, c0]] * (1.0 - fx) + field[[r0, c1]] * fx;
+        let bottom = field[[r1, c0]] * (1.0 - fx) + field[[r1, c1]] * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+
+    /// 細菌が今いるところの濃度の平均。濃い方へ集まるほど大きくなる
+    pub fn mean_sensed(&self) -> f32 {
+        if self.bacteria.is_empty() {
+            return 0.0;
+        }
+        self.bacteria.iter().map(|b| self.sense(b.position)).sum::<f32>() / self.bacteria.len() as f32
+    }
+
+    /// 全ての細菌の、濃度が上がっている間に走った時間の割合
+    pub fn up_gradient_fraction(&self) -> f32 {
+        let (up, down) = self.bacteria.iter().fold((0.0, 0.0), |(up, down), b| {
+            (up + b.stats.up_gradient_time, down + b.stats.down_gradient_time)
+        });
+        if up + down > 0.0 {
+            up / (up + down)
+        } else {
+            0.0
+        }
+    }
+
+    /// 全ての細菌の、終わった走りの長さの平均
+    pub fn mean_run(&self) -> Option<f32> {
+        let (time, tumbles) = self.bacteria.iter().fold((0.0, 0), |(time, tumbles), b| {
+            (time + b.stats.completed_run_time, tumbles + b.stats.tumbles)
+        });
+        if tumbles == 0 {
+            None
+        } else {
+            Some(time / tumbles as f32)
+        }
+    }
+
+    /// 大きさ`dim`(行, 列)の格子のセルごとの細菌の数
+    pub fn density(&self, dim: (usize, usize)) -> Matrix<f32> {
+        let mut density = Array2::zeros(dim);
+        for b in &self.bacteria {
+            let col = ((b.position.0 / self.space.width * dim.1 as f32) as usize).min(dim.1 - 1);
+            let row = ((b.position.1 / self.space.height * dim.0 as f32) as usize).min(dim.0 - 1);
+            density[[row, col]] += 1.0;
+        }
+        density
+    }
+
+    /// 時間`dt`だけ進める。細菌が走るか向きを変えたあと、細菌が食べたり出したりした分を含めて栄養の場を進める
+    pub fn step<R: Rng>(&mut self, dt: f32, rng: &mut R) {
+        let angle = Normal::new(TUMBLE_ANGLE_MEAN, TUMBLE_ANGLE_STD);
+        let mut bacteria = ::std::mem::take(&mut self.bacteria);
+        for b in &mut bacteria {
+            let concentration = self.sense(b.position);
+            let change = concentration - b.memory;
+            b.memory += (concentration - b.memory) * (dt / self.memory_time).min(1.0);
+            let rate = self.tumble_rate * (-self.gain * change).min(MAX_RESPONSE).exp();
+            if rng.next_f32() < 1.0 - (-rate * dt).exp() {
+                let turn = angle.ind_sample(rng).to_radians() as f32;
+                b.heading += if rng.gen() { turn } else { -turn };
+                b.stats.tumbles += 1;
+                b.stats.completed_run_time += b.stats.current_run;
+                b.stats.longest_run = b.stats.longest_run.max(b.stats.current_run);
+                b.stats.current_run = 0.0;
+            } else {
+                let distance = self.speed * dt;
+                b.position = self.space.wrap((
+                    b.position.0 + distance * b.heading.cos(),
+                    b.position.1 + distance * b.heading.sin(),
+                ));
+                b.stats.current_run += dt;
+                if change > 0.0 {
+                    b.stats.up_gradient_time += dt;
+                } else {
+                    b.stats.down_gradient_time += dt;
+                }
+            }
+        }
+        self.bacteria = bacteria;
+        if self.uptake != 0.0 || self.secretion != 0.0 {
+            let (rows, cols) = self.fields[0].dim();
+            let dx = self.nutrient.dx;
+            for b in &self.bacteria {
+                let col = ((b.position.0 / dx) as usize).min(cols - 1);
+                let row = ((b.position.1 / dx) as usize).min(rows - 1);
+                let cell = &mut self.fields[0][[row, col]];
+                *cell = (*cell + dt * (self.secretion - self.uptake * *cell)).max(0.0);
+            }
+        }
+        self.integrator.step(&self.nutrient, &mut self.fields, dt);
+        self.time += dt;
+    }
+
+    /// 描画するための細菌の絵。走っている細菌は黄色、向きを変えたばかりの細菌は赤
+    pub fn sprites(&self) -> Vec<AgentSprite> {
+        self.bacteria
+            .iter()
+            .map(|b| AgentSprite {
+                position: b.position,
+                heading: b.heading,
+                color: if b.stats.current_run == 0.0 {
+                    [255, 60, 60]
+                } else {
+                    [255, 230, 80]
+                },
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,178 @@
+use ndarray::Array2;
+use visualizer::Matrix;
+
+/// 縮小するときの1ブロックの値のまとめ方
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Downsampling {
+    /// ブロック内の平均
+    BlockAverage,
+    /// ブロック内の最大値。まばらな構造(Game of Lifeの生きたセルなど)が消えない
+    MaxPool,
+    /// Gaussianでぼかしてから間引く。エイリアシングが最も少ない
+    Gaussian,
+}
+
+impl Downsampling {
+    /// `matrix`を縦横`factor`分の1に縮小する。端の半端なブロックは含まれるセルだけでまとめる
+    ///
+    /// # Example
+    /// ```
+    /// extern crate ndarray;
+    /// extern crate my_alife;
+    ///
+    /// use ndarray::arr2;
+    /// use my_alife::algorithm::coarse_grain::Downsampling;
+    ///
+    /// let matrix = arr2(&[[1.0, 0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 0.0], [0.0, 0.0, 1.0, 1.0], [0.0, 0.0, 1.0, 1.0]]);
+    /// assert_eq!(Downsampling::BlockAverage.apply(&matrix, 2), arr2(&[[0.25, 0.0], [0.0, 1.0]]));
+    /// assert_eq!(Downsampling::MaxPool.apply(&matrix, 2), arr2(&[[1.0, 0.0], [0.0, 1.0]]));
+    /// assert_eq!(Downsampling::Gaussian.apply(&matrix, 2).dim(), (2, 2));
+    /// ```
+    pub fn apply(&self, matrix: &Matrix<f32>, factor: usize) -> Matrix<f32> {
+        match *self {
+            Downsampling::BlockAverage => block_average(matrix, factor),
+            Downsampling::MaxPool => max_pool(matrix, factor),
+            Downsampling::Gaussian => gaussian(matrix, factor),
+        }
+    }
+
+    /// `apply`と同じだが、結果を`out`に書き込む。BlockAverageとMaxPoolはメモリを確保しない
+    ///
+    /// # Example
+    /// ```
+    /// extern crate ndarray;
+    /// extern crate my_alife;
+    ///
+    /// use ndarray::Array2;
+    /// use my_alife::algorithm::coarse_grain::{reduced_dim, Downsampling};
+    ///
+    /// let matrix = Array2::from_shape_fn((5, 4), |(r, c)| (r * 4 + c) as f32);
+    /// let mut out = Array2::zeros(reduced_dim(matrix.dim(), 2));
+    /// Downsampling::BlockAverage.apply_into(&matrix, 2, &mut out);
+    /// assert_eq!(out, Downsampling::BlockAverage.apply(&matrix, 2));
+    /// ```
+    ///
+    /// # Panics
+    /// `out`の大きさが`reduced_dim`と違うとき
+    pub fn apply_into<A>(&self, matrix: &Matrix<A>, factor: usize, out: &mut Matrix<f32>)
+    where
+        A: Copy + Into<f32>,
+    {
+        let factor = factor.max(1);
+        let (rows, cols) = matrix.dim();
+        assert_eq!(out.dim(), reduced_dim((rows, cols), factor), "output has a wrong size");
+        let blocks = |r: usize, c: usize| {
+            (r * factor..((r + 1) * factor).min(rows))
+                .flat_map(move |row| (c * factor..((c + 1) * factor).min(cols)).map(move |col| (row, col)))
+        };
+        match *self {
+            Downsampling::BlockAverage => {
+                for ((r, c), e) in out.indexed_iter_mut() {
+                    let (sum, count) = blocks(r, c).fold((0.0, 0), |(sum, count), (row, col)| {
+                        (sum + matrix[[row, col]].into(), count + 1)
+                    });
+                    *e = sum / count as f32;
+                }
+            }
+            Downsampling::MaxPool => {
+                for ((r, c), e) in out.indexed_iter_mut() {
+                    *e = blocks(r, c).fold(f32::NEG_INFINITY, |max, (row, col)| max.max(matrix[[row, col]].into()));
+                }
+            }
+            Downsampling::Gaussian => out.assign(&gaussian(&matrix.mapv(|x| x.into()), factor)),
+        }
+    }
+}
+
+/// 縦横とも`max_size`以下に収めるための縮小率
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::coarse_grain::factor_to_fit;
+///
+/// assert_eq!(factor_to_fit((4096, 4096), 512), 8);
+/// assert_eq!(factor_to_fit((300, 100), 512), 1);
+/// ```
+pub fn factor_to_fit(dim: (usize, usize), max_size: usize) -> usize {
+    let max_size = max_size.max(1);
+    dim.0.max(dim.1).div_ceil(max_size).max(1)
+}
+
+/// `factor`分の1に縮小したときの大きさ
+pub fn reduced_dim(dim: (usize, usize), factor: usize) -> (usize, usize) {
+    let factor = factor.max(1);
+    (dim.0.div_ceil(factor), dim.1.div_ceil(factor))
+}
+
+/// ブロックごとの平均で縮小する。繰り込み群のような粗視化の解析にも使える
+pub fn block_average(matrix: &Matrix<f32>, factor: usize) -> Matrix<f32> {
+    reduce_blocks(matrix, factor, |block| block.iter().sum::<f32>() / block.len() as f32)
+}
+
+/// ブロックごとの最大値で縮小する
+pub fn max_pool(matrix: &Matrix<f32>, factor: usize) -> Matrix<f32> {
+    reduce_blocks(matrix, factor, |block| {
+        block.iter().cloned().fold(f32::NEG_INFINITY, f32::max)
+    })
+}
+
+/// 標準偏差`factor / 2`のGaussianでぼかしてから、各ブロックの中心の値を取り出す(周期境界条件)
+pub fn gaussian(matrix: &Matrix<f32>, factor: usize) -> Matrix<f32> {
+    let factor = factor.max(1);
+    let (rows, cols) = matrix.dim();
+    if factor == 1 || rows == 0 || cols == 0 {
+        return matrix.clone();
+    }
+    let sigma = factor as f32 / 2.0;
+    let radius = (3.0 * sigma).ceil() as isize;
+    let weights: Vec<f32> = (-radius..=radius)
+        .map(|d| (-(d * d) as f32 / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total: f32 = weights.iter().sum();
+    let weights: Vec<f32> = weights.iter().map(|w| w / total).collect();
+
+    let out_rows = rows.div_ceil(factor);
+    let out_cols = cols.div_ceil(factor);
+    let center = |i: usize, len: usize| (i * factor + factor / 2).min(len - 1);
+    // 横方向にぼかすのは、取り出す列だけでよい
+    let horizontal = Array2::from_shape_fn((rows, out_cols), |(r, c)| {
+        let col = center(c, cols) as isize;
+        weights
+            .iter()
+            .enumerate()
+            .map(|(i, w)| w * matrix[[r, wrap(col + i as isize - radius, cols)]])
+            .sum::<f32>()
+    });
+    Array2::from_shape_fn((out_rows, out_cols), |(r, c)| {
+        let row = center(r, rows) as isize;
+        weights
+            .iter()
+            .enumerate()
+            .map(|(i, w)| w * horizontal[[wrap(row + i as isize - radius, rows), c]])
+            .sum()
+    })
+}
+
+fn wrap(i: isize, len: usize) -> usize {
+    i.rem_euclid(len as isize) as usize
+}
+
+fn reduce_blocks<F>(matrix: &Matrix<f32>, factor: usize, reduce: F) -> Matrix<f32>
+where
+    F: Fn(&[f32]) -> f32,
+{
+    let factor = factor.max(1);
+    let (rows, cols) = matrix.dim();
+    let mut block = Vec::with_capacity(factor * factor);
+    let mut reduced = Array2::zeros((rows.div_ceil(factor), cols.div_ceil(factor)));
+    for ((r, c), e) in reduced.indexed_iter_mut() {
+        block.clear();
+        for row in r * factor..((r + 1) * factor).min(rows) {
+            for col in c * factor..((c + 1) * factor).min(cols) {
+                block.push(matrix[[row, col]]);
+            }
+        }
+        *e = reduce(&block);
+    }
+    reduced
+}
@@ -0,0 +1,140 @@
+use algorithm::backend::Stepper;
+use failure;
+use ndarray::{Array2, Zip};
+use visualizer::Matrix;
+
+/// 2つの盤面の並べ方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ComparisonView {
+    /// Aを左、Bを右に並べる。間には`separator`の値の列を1本入れる
+    #[default]
+    SideBySide,
+    /// A - Bの差。`Transform::Signed`と`Colormap::Diverging`で表示すると、どちらが大きいかがわかる
+    Difference,
+}
+
+impl ComparisonView {
+    /// もう一方の並べ方
+    pub fn toggled(self) -> ComparisonView {
+        match self {
+            ComparisonView::SideBySide => ComparisonView::Difference,
+            ComparisonView::Difference => ComparisonView::SideBySide,
+        }
+    }
+}
+
+// AとBの盤面
+type Pair<'a, A> = (&'a Matrix<A>, &'a Matrix<A>);
+
+/// 同じモデルをパラメータや乱数の種だけ変えた2つのシミュレーションを、同じステップ数ずつ進めて比べる
+///
+/// 初期値鋭敏性やパラメータへの感度を調べるために、2つの盤面を並べた画像か差の画像にする
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::backend::{Backend, CpuGrayScott};
+/// use my_alife::algorithm::comparison::{Comparison, ComparisonView};
+/// use my_alife::algorithm::gray_scott::{initial_matrix, GrayScott};
+///
+/// let uv = initial_matrix();
+/// let a = CpuGrayScott::new(Backend::Naive, &uv, GrayScott::new(0.04, 0.06)).unwrap();
+/// let b = CpuGrayScott::new(Backend::Naive, &uv, GrayScott::new(0.035, 0.065)).unwrap();
+/// let mut comparison = Comparison::new(a, b);
+/// assert_eq!(comparison.distance().unwrap(), 0.0);
+///
+/// comparison.step(100).unwrap();
+/// assert_eq!(comparison.steps(), 100);
+/// assert!(comparison.distance().unwrap() > 0.0);
+///
+/// let side_by_side = comparison.compose(ComparisonView::SideBySide, 1.0).unwrap();
+/// assert_eq!(side_by_side.dim(), (256, 256 * 2 + 1));
+/// let difference = comparison.compose(ComparisonView::Difference, 1.0).unwrap();
+/// assert_eq!(difference.dim(), (256, 256));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Comparison<S> {
+    a: S,
+    b: S,
+    steps: u64,
+}
+
+impl<S> Comparison<S>
+where
+    S: Stepper,
+    S::Cell: Into<f32>,
+{
+    /// 比べる2つのシミュレーション。盤面の大きさは同じであること
+    pub fn new(a: S, b: S) -> Comparison<S> {
+        Comparison { a, b, steps: 0 }
+    }
+
+    /// 両方を`steps`ステップずつ進める
+    pub fn step(&mut self, steps: usize) -> Result<(), failure::Error> {
+        self.a.step(steps)?;
+        self.b.step(steps)?;
+        self.steps += steps as u64;
+        Ok(())
+    }
+
+    /// 進めたステップ数
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// (A, B)
+    pub fn steppers(&self) -> (&S, &S) {
+        (&self.a, &self.b)
+    }
+
+    /// (A, B)を変更するための参照。片方にだけ摂動を加えるときに使う
+    pub fn steppers_mut(&mut self) -> (&mut S, &mut S) {
+        (&mut self.a, &mut self.b)
+    }
+
+    /// (A, B)を返す
+    pub fn into_steppers(self) -> (S, S) {
+        (self.a, self.b)
+    }
+
+    fn snapshots(&mut self) -> Result<Pair<'_, S::Cell>, failure::Error> {
+        let a = self.a.snapshot()?;
+        let b = self.b.snapshot()?;
+        if a.dim() != b.dim() {
+            return Err(format_err!("size mismatch: {:?} and {:?}", a.dim(), b.dim()));
+        }
+        Ok((a, b))
+    }
+
+    /// セルごとの差の絶対値の平均。2つが同じなら0
+    pub fn distance(&mut self) -> Result<f32, failure::Error> {
+        let (a, b) = self.snapshots()?;
+        if a.is_empty() {
+            return Ok(0.0);
+        }
+        let mut sum = 0.0;
+        Zip::from(a).and(b).apply(|&x, &y| sum += (x.into() - y.into()).abs());
+        Ok(sum / a.len() as f32)
+    }
+
+    /// `view`の並べ方で1つの盤面にする。`separator`は`SideBySide`の間の列の値
+    pub fn compose(&mut self, view: ComparisonView, separator: f32) -> Result<Matrix<f32>, failure::Error> {
+        let (a, b) = self.snapshots()?;
+        let (rows, cols) = a.dim();
+        Ok(match view {
+            ComparisonView::SideBySide => {
+                let mut composed = Array2::from_elem((rows, cols * 2 + 1), separator);
+                composed.slice_mut(s![.., ..cols]).assign(&a.mapv(Into::into));
+                composed.slice_mut(s![.., cols + 1..]).assign(&b.mapv(Into::into));
+                composed
+            }
+            ComparisonView::Difference => {
+                let mut difference = Array2::zeros((rows, cols));
+                Zip::from(&mut difference)
+                    .and(a)
+                    .and(b)
+                    .apply(|d, &x, &y| *d = x.into() - y.into());
+                difference
+            }
+        })
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use visualizer::Matrix;
+
+/// 決定的なシミュレーションが入った周期軌道(リミットサイクル)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// 周期軌道に入るまでのステップ数
+    pub transient: usize,
+    /// 周期
+    pub period: usize,
+}
+
+/// 状態をハッシュ値にする。浮動小数点数はbit列をそのまま使うので、完全に同じ値の場合だけ同じになる
+pub fn state_hash<A>(matrix: &Matrix<A>) -> u64
+where
+    A: Copy + Into<f32>,
+{
+    let mut hasher = DefaultHasher::new();
+    matrix.dim().hash(&mut hasher);
+    for &e in matrix.iter() {
+        let e: f32 = e.into();
+        e.to_bits().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Brentの方法で、`initial`から`step`を繰り返したときの周期軌道を調べる
+///
+/// 状態を2つしか保持しないので、大きな状態でもメモリを使わない。`max_steps`ステップ以内に見つからなければ`None`
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::arr2;
+/// use my_alife::algorithm::cycle::{brent, Cycle};
+/// use my_alife::algorithm::game_of_life::step;
+///
+/// let blinker = arr2(&[[0, 0, 0, 0, 0], [0, 0, 1, 0, 0], [0, 0, 1, 0, 0], [0, 0, 1, 0, 0], [0, 0, 0, 0, 0]]);
+/// assert_eq!(brent(blinker, step, 100), Some(Cycle { transient: 0, period: 2 }));
+///
+/// // 3から始めて、xを x² + 1 (mod 255)で更新する
+/// assert_eq!(brent(3u32, |x| (x * x + 1) % 255, 1000), Some(Cycle { transient: 2, period: 6 }));
+/// ```
+pub fn brent<T, F>(initial: T, step: F, max_steps: usize) -> Option<Cycle>
+where
+    T: Clone + PartialEq,
+    F: Fn(&T) -> T,
+{
+    // 周期を求める。tortoiseは2の冪ごとにhareの位置に移動する
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    let mut steps = 1;
+    while tortoise != hare {
+        if steps >= max_steps {
+            return None;
+        }
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+        steps += 1;
+    }
+
+    // 周期だけ離れた2つの状態を同時に進めて、最初に一致した位置が周期軌道の入り口
+    let mut tortoise = initial.clone();
+    let mut hare = initial;
+    for _ in 0..period {
+        hare = step(&hare);
+    }
+    let mut transient = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        transient += 1;
+    }
+    Some(Cycle { transient, period })
+}
+
+/// シミュレーションを進めながら、状態のハッシュ値を記録して周期軌道を見つける
+///
+/// 描画ループのように、状態を自由に巻き戻せない場合に使う。
+/// ハッシュ値が衝突すると誤検出することがあるが、64bitなので実用上は問題にならない。
+/// 覚えておくのは直近の`window`個の状態だけなので、周期が`window`より長い周期軌道は見つけられない
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::cycle::{Cycle, CycleDetector};
+///
+/// let mut detector = CycleDetector::new();
+/// let mut x = 3u64;
+/// let cycle = loop {
+///     if let Some(cycle) = detector.observe(x) {
+///         break cycle;
+///     }
+///     x = (x * x + 1) % 255;
+/// };
+/// assert_eq!(cycle, Cycle { transient: 2, period: 6 });
+///
+/// // 周期6の軌道は直近の4個の状態からは見つからない
+/// let mut short = CycleDetector::with_window(4);
+/// let mut x = 3u64;
+/// for _ in 0..100 {
+///     assert_eq!(short.observe(x), None);
+///     x = (x * x + 1) % 255;
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CycleDetector {
+    seen: HashMap<u64, usize>,
+    // `seen`に入っている状態を記録した順に並べたもの。古いものから捨てる
+    history: VecDeque<(u64, usize)>,
+    window: usize,
+    steps: usize,
+}
+
+/// `CycleDetector::new`が覚えておく状態の数
+pub const DEFAULT_WINDOW: usize = 1 << 16;
+
+impl Default for CycleDetector {
+    fn default() -> CycleDetector {
+        CycleDetector::with_window(DEFAULT_WINDOW)
+    }
+}
+
+impl CycleDetector {
+    /// 直近の`DEFAULT_WINDOW`個の状態を覚えておくCycleDetectorを生成する
+    pub fn new() -> CycleDetector {
+        CycleDetector::default()
+    }
+
+    /// 直近の`window`個の状態を覚えておくCycleDetectorを生成する
+    pub fn with_window(window: usize) -> CycleDetector {
+        CycleDetector {
+            seen: HashMap::new(),
+            history: VecDeque::new(),
+            window: window.max(1),
+            steps: 0,
+        }
+    }
+
+    /// これまでに記録した状態の数
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// 次の状態のハッシュ値を記録する。以前と同じ状態に戻ったら周期軌道を返す
+    pub fn observe(&mut self, hash: u64) -> Option<Cycle> {
+        let step = self.steps;
+        self.steps += 1;
+        let cycle = self.seen.insert(hash, step).map(|first| Cycle {
+            transient: first,
+            period: step - first,
+        });
+        self.history.push_back((hash, step));
+        while self.history.len() > self.window {
+            let (old, recorded) = self.history.pop_front().unwrap();
+            // 同じ状態をもう一度記録していれば、新しい方を残す
+            if self.seen.get(&old) == Some(&recorded) {
+                self.seen.remove(&old);
+            }
+        }
+        cycle
+    }
+
+    /// 記録を消す
+    pub fn reset(&mut self) {
+        self.seen.clear();
+        self.history.clear();
+        self.steps = 0;
+    }
+}
@@ -0,0 +1,276 @@
+use algorithm::neighborhood::Neighborhood;
+use algorithm::reaction_diffusion::discrete_laplacian;
+use ndarray::Array2;
+use rand::Rng;
+use std::fmt::Write;
+use visualizer::Matrix;
+
+// Watson & Lovelock (1983)のパラメータ
+const SOLAR_FLUX: f32 = 917.0;
+const STEFAN_BOLTZMANN: f32 = 5.67e-8;
+const ALBEDO_GROUND: f32 = 0.5;
+const ALBEDO_WHITE: f32 = 0.75;
+const ALBEDO_BLACK: f32 = 0.25;
+// 局所的な温度が惑星の平均からどれだけずれるか(K^4)
+const HEAT_TRANSFER: f32 = 2.06e9;
+const DEATH_RATE: f32 = 0.3;
+const OPTIMAL_TEMPERATURE: f32 = 295.5;
+// 絶滅しないように残しておく最小の被覆率
+const MIN_COVERAGE: f32 = 0.01;
+// 格子版で、絶滅した後も外から種が飛んでくる確率
+const SEEDING_RATE: f32 = 0.001;
+
+/// 何も生えていない地面
+pub const BARE: u8 = 0;
+/// 白いデイジー
+pub const WHITE: u8 = 1;
+/// 黒いデイジー
+pub const BLACK: u8 = 2;
+
+/// 温度`temperature`(K)でのデイジーの成長率。295.5Kで最大の1.0になり、278K〜313Kの外では0
+pub fn growth_rate(temperature: f32) -> f32 {
+    (1.0 - 0.003_265 * (OPTIMAL_TEMPERATURE - temperature).powi(2)).max(0.0)
+}
+
+// アルベドが`albedo`のときの放射平衡温度(K)
+fn equilibrium_temperature(luminosity: f32, albedo: f32) -> f32 {
+    (SOLAR_FLUX * luminosity * (1.0 - albedo) / STEFAN_BOLTZMANN).powf(0.25)
+}
+
+/// ある時刻のDaisyworldの状態をまとめたもの
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DaisyStats {
+    /// 時刻
+    pub time: f32,
+    /// 太陽の明るさ(現在の太陽を1とする)
+    pub luminosity: f32,
+    /// 惑星の平均温度(K)
+    pub temperature: f32,
+    /// 白いデイジーの被覆率
+    pub white: f32,
+    /// 黒いデイジーの被覆率
+    pub black: f32,
+}
+
+fn albedo(cell: u8) -> f32 {
+    match cell {
+        WHITE => ALBEDO_WHITE,
+        BLACK => ALBEDO_BLACK,
+        _ => ALBEDO_GROUND,
+    }
+}
+
+/// 時系列をCSV(ヘッダ付き)にする
+pub fn to_csv(series: &[DaisyStats]) -> String {
+    let mut csv = String::from("time,luminosity,temperature,white,black\n");
+    for stats in series {
+        writeln!(
+            csv,
+            "{},{},{},{},{}",
+            stats.time, stats.luminosity, stats.temperature, stats.white, stats.black
+        )
+        .unwrap();
+    }
+    csv
+}
+
+/// 平均場近似のDaisyworld(常微分方程式)
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::daisyworld::MeanFieldDaisyworld;
+///
+/// let mut world = MeanFieldDaisyworld::new(1.0);
+/// for _ in 0..2000 {
+///     world.step(0.05);
+/// }
+/// // 太陽の明るさが変わっても、デイジーが温度を成長に適した範囲に保つ
+/// let stats = world.stats();
+/// assert!((stats.temperature - 295.5).abs() < 10.0);
+/// assert!(stats.white > 0.1 && stats.black > 0.1);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeanFieldDaisyworld {
+    luminosity: f32,
+    white: f32,
+    black: f32,
+    time: f32,
+}
+
+impl MeanFieldDaisyworld {
+    /// 白と黒のデイジーが少しだけ生えている状態から始める
+    pub fn new(luminosity: f32) -> MeanFieldDaisyworld {
+        MeanFieldDaisyworld {
+            luminosity,
+            white: MIN_COVERAGE,
+            black: MIN_COVERAGE,
+            time: 0.0,
+        }
+    }
+
+    /// 太陽の明るさを変更する
+    pub fn set_luminosity(&mut self, luminosity: f32) {
+        self.luminosity = luminosity;
+    }
+
+    fn planet_albedo(&self) -> f32 {
+        (1.0 - self.white - self.black) * ALBEDO_GROUND + self.white * ALBEDO_WHITE + self.black * ALBEDO_BLACK
+    }
+
+    fn planet_temperature(&self) -> f32 {
+        equilibrium_temperature(self.luminosity, self.planet_albedo())
+    }
+
+    // アルベドが`albedo`の場所の温度
+    fn local_temperature(&self, albedo: f32) -> f32 {
+        (HEAT_TRANSFER * (self.planet_albedo() - albedo) + self.planet_temperature().powi(4))
+            .max(0.0)
+            .powf(0.25)
+    }
+
+    /// 時間`dt`だけ進める(Euler法)
+    pub fn step(&mut self, dt: f32) {
+        let bare = 1.0 - self.white - self.black;
+        let white_growth = growth_rate(self.local_temperature(ALBEDO_WHITE));
+        let black_growth = growth_rate(self.local_temperature(ALBEDO_BLACK));
+        let white = self.white + dt * self.white * (bare * white_growth - DEATH_RATE);
+        let black = self.black + dt * self.black * (bare * black_growth - DEATH_RATE);
+        self.white = white.max(MIN_COVERAGE);
+        self.black = black.max(MIN_COVERAGE);
+        self.time += dt;
+    }
+
+    /// 現在の状態
+    pub fn stats(&self) -> DaisyStats {
+        DaisyStats {
+            time: self.time,
+            luminosity: self.luminosity,
+            temperature: self.planet_temperature(),
+            white: self.white,
+            black: self.black,
+        }
+    }
+}
+
+/// 格子上のDaisyworld(周期境界条件)
+///
+/// 各セルは地面か白か黒のデイジーで、アルベドは周囲と混ざり合いながら拡散する。
+/// 温度は平均場近似と同じ式で、周囲のアルベドとそのセルのアルベドの差から決まる。
+/// デイジーは確率`DEATH_RATE`で枯れ、地面には近傍のデイジーがその場所の温度での成長率で種をまく
+///
+/// # Example
+/// ```
+/// extern crate my_alife;
+/// extern crate rand;
+///
+/// use my_alife::algorithm::daisyworld::LatticeDaisyworld;
+///
+/// let mut rng = rand::thread_rng();
+/// let mut world = LatticeDaisyworld::random((32, 32), 1.0, &mut rng);
+/// let series: Vec<_> = (0..50).map(|_| world.step(&mut rng)).collect();
+/// assert_eq!(series.len(), 50);
+/// assert!(world.temperature().iter().all(|t| t.is_finite()));
+/// ```
+pub struct LatticeDaisyworld {
+    cells: Matrix<u8>,
+    // 周囲と混ざったアルベド
+    albedo: Matrix<f32>,
+    temperature: Matrix<f32>,
+    luminosity: f32,
+    diffusion: f32,
+    time: f32,
+}
+
+impl LatticeDaisyworld {
+    /// 白と黒のデイジーがランダムに少しずつ生えている状態から始める
+    pub fn random<R: Rng>(dim: (usize, usize), luminosity: f32, rng: &mut R) -> LatticeDaisyworld {
+        let cells = Array2::from_shape_fn(dim, |_| match rng.gen::<f32>() {
+            x if x < 0.05 => WHITE,
+            x if x < 0.1 => BLACK,
+            _ => BARE,
+        });
+        let temperature = Array2::from_elem(dim, equilibrium_temperature(luminosity, ALBEDO_GROUND));
+        LatticeDaisyworld {
+            albedo: cells.mapv(albedo),
+            cells,
+            temperature,
+            luminosity,
+            diffusion: 0.2,
+            time: 0.0,
+        }
+    }
+
+    /// 各セルの状態(`BARE`, `WHITE`, `BLACK`)
+    pub fn cells(&self) -> &Matrix<u8> {
+        &self.cells
+    }
+
+    /// 各セルの温度(K)
+    pub fn temperature(&self) -> &Matrix<f32> {
+        &self.temperature
+    }
+
+    /// 太陽の明るさを変更する
+    pub fn set_luminosity(&mut self, luminosity: f32) {
+        self.luminosity = luminosity;
+    }
+
+    /// アルベドの拡散の速さ(0.0〜0.25)を変更する
+    pub fn set_diffusion(&mut self, diffusion: f32) {
+        self.diffusion = diffusion.clamp(0.0, 0.25);
+    }
+
+    /// 1ステップ進め、進めた後の状態を返す
+    pub fn step<R: Rng>(&mut self, rng: &mut R) -> DaisyStats {
+        let local = self.cells.mapv(albedo);
+        let laplacian = discrete_laplacian(&self.albedo, 1.0);
+        self.albedo = &self.albedo + &((&local - &self.albedo) * 0.1 + laplacian * self.diffusion);
+        let luminosity = self.luminosity;
+        self.temperature = Array2::from_shape_fn(self.cells.dim(), |cell| {
+            let surrounding = self.albedo[cell];
+            let planet = equilibrium_temperature(luminosity, surrounding);
+            (HEAT_TRANSFER * (surrounding - local[cell]) + planet.powi(4))
+                .max(0.0)
+                .powf(0.25)
+        });
+
+        let dim = self.cells.dim();
+        let mut next = self.cells.clone();
+        for ((row, col), e) in next.indexed_iter_mut() {
+            if self.cells[[row, col]] != BARE {
+                if rng.gen::<f32>() < DEATH_RATE {
+                    *e = BARE;
+                }
+                continue;
+            }
+            let offsets = Neighborhood::Moore.size();
+            let neighbor = Neighborhood::Moore
+                .around((row, col), dim)
+                .nth(rng.gen_range(0, offsets))
+                .unwrap();
+            let mut seed = self.cells[neighbor];
+            if seed == BARE && rng.gen::<f32>() < SEEDING_RATE {
+                seed = *rng.choose(&[WHITE, BLACK]).unwrap();
+            }
+            if seed != BARE && rng.gen::<f32>() < growth_rate(self.temperature[[row, col]]) {
+                *e = seed;
+            }
+        }
+        self.cells = next;
+        self.time += 1.0;
+        self.stats()
+    }
+
+    /// 現在の状態
+    pub fn stats(&self) -> DaisyStats {
+        let cells = self.cells.len() as f32;
+        let count = |state| self.cells.iter().filter(|&&e| e == state).count() as f32 / cells;
+        DaisyStats {
+            time: self.time,
+            luminosity: self.luminosity,
+            temperature: self.temperature.scalar_sum() / cells,
+            white: count(WHITE),
+            black: count(BLACK),
+        }
+    }
+}
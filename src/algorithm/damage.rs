@@ -0,0 +1,169 @@
+use visualizer::Matrix;
+
+// 損傷が初期値から増えなければ秩序的とみなす
+const ORDERED_GROWTH: f32 = 1.0;
+// 損傷が1ステップあたりこのセル数以上の速さで広がれば混沌的とみなす
+const CHAOTIC_SPEED: f32 = 0.45;
+
+/// 2つの状態を比べて、異なるセルの数(Hamming距離)を数えられる状態
+pub trait Damage {
+    /// セルの数
+    fn size(&self) -> usize;
+    /// `other`と状態が異なるセルの数
+    fn hamming(&self, other: &Self) -> usize;
+    /// `index`番目のセルを反転する(0ならば1、それ以外ならば0)
+    fn flip(&mut self, index: usize);
+}
+
+impl Damage for Vec<u8> {
+    fn size(&self) -> usize {
+        self.len()
+    }
+
+    fn hamming(&self, other: &Self) -> usize {
+        self.iter().zip(other).filter(|&(a, b)| a != b).count()
+    }
+
+    fn flip(&mut self, index: usize) {
+        let cell = &mut self[index];
+        *cell = if *cell == 0 { 1 } else { 0 };
+    }
+}
+
+impl Damage for Matrix<u8> {
+    fn size(&self) -> usize {
+        self.len()
+    }
+
+    fn hamming(&self, other: &Self) -> usize {
+        self.iter().zip(other.iter()).filter(|&(a, b)| a != b).count()
+    }
+
+    fn flip(&mut self, index: usize) {
+        let cols = self.cols();
+        let cell = &mut self[[index / cols, index % cols]];
+        *cell = if *cell == 0 { 1 } else { 0 };
+    }
+}
+
+/// 損傷の広がり方から見たルールの性質
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dynamics {
+    /// 損傷が消えるか、広がらない
+    Ordered,
+    /// 損傷が広がるが、一定の速さには達しない
+    Critical,
+    /// 損傷が一定の速さで広がる
+    Chaotic,
+}
+
+/// 1セルだけ異なる2つの状態を同時に時間発展させたときの、Hamming距離の推移
+#[derive(Debug, Clone, PartialEq)]
+pub struct DamageSpreading {
+    /// 各ステップのHamming距離。最初の要素は摂動を加えた直後の1
+    pub distances: Vec<f32>,
+    /// セルの数
+    pub size: usize,
+}
+
+impl DamageSpreading {
+    /// `initial`の`index`番目のセルを反転したコピーを作り、両方を`steps`ステップ進めて距離を記録する
+    ///
+    /// # Arguments
+    /// * `initial` - 初期状態
+    /// * `index` - 反転するセルの位置
+    /// * `steps` - 進めるステップ数
+    /// * `step` - 状態を1ステップ進める関数
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::algorithm::damage::{DamageSpreading, Dynamics};
+    /// use my_alife::algorithm::elementary_ca::step;
+    ///
+    /// let initial = vec![0u8; 101];
+    /// let chaotic = DamageSpreading::run(&initial, 50, 40, |cells| step(cells, 30));
+    /// assert_eq!(chaotic.classify(), Dynamics::Chaotic);
+    ///
+    /// let ordered = DamageSpreading::run(&initial, 50, 40, |cells| step(cells, 4));
+    /// assert_eq!(ordered.classify(), Dynamics::Ordered);
+    /// ```
+    pub fn run<T, F>(initial: &T, index: usize, steps: usize, mut step: F) -> DamageSpreading
+    where
+        T: Damage + Clone,
+        F: FnMut(&T) -> T,
+    {
+        let mut original = initial.clone();
+        let mut perturbed = initial.clone();
+        perturbed.flip(index);
+        let mut distances = vec![original.hamming(&perturbed) as f32];
+        for _ in 0..steps {
+            original = step(&original);
+            perturbed = step(&perturbed);
+            distances.push(original.hamming(&perturbed) as f32);
+        }
+        DamageSpreading {
+            distances,
+            size: initial.size(),
+        }
+    }
+
+    /// 複数回の結果のHamming距離を平均する。ステップ数は最も短いものに揃える
+    pub fn mean(runs: &[DamageSpreading]) -> Option<DamageSpreading> {
+        let len = runs.iter().map(|run| run.distances.len()).min()?;
+        let distances = (0..len)
+            .map(|t| runs.iter().map(|run| run.distances[t]).sum::<f32>() / runs.len() as f32)
+            .collect();
+        Some(DamageSpreading {
+            distances,
+            size: runs[0].size,
+        })
+    }
+
+    /// 最後のステップで損傷を受けているセルの割合
+    pub fn final_density(&self) -> f32 {
+        self.distances.last().map_or(0.0, |&d| d / self.size as f32)
+    }
+
+    /// 距離の対数が1ステップあたりに増える割合(Lyapunov指数に相当する値)
+    ///
+    /// 損傷が盤面全体に飽和すると増えなくなるので、距離がセル数の半分に達するまでを最小二乗法で直線近似する。
+    /// 損傷が消えた場合は負の無限大になる
+    pub fn exponent(&self) -> f32 {
+        let saturation = self.size as f32 / 2.0;
+        let points: Vec<(f32, f32)> = self
+            .distances
+            .iter()
+            .take_while(|&&d| d < saturation)
+            .enumerate()
+            .map(|(t, &d)| (t as f32, d.ln()))
+            .collect();
+        if points.iter().any(|&(_, y)| y == f32::NEG_INFINITY) {
+            return f32::NEG_INFINITY;
+        }
+        if points.len() < 2 {
+            return 0.0;
+        }
+        let n = points.len() as f32;
+        let mean_x = points.iter().map(|p| p.0).sum::<f32>() / n;
+        let mean_y = points.iter().map(|p| p.1).sum::<f32>() / n;
+        let covariance: f32 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+        let variance: f32 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+        covariance / variance
+    }
+
+    /// 損傷が広がる速さから、ルールが秩序的・臨界的・混沌的のどれに近いかを判定する
+    pub fn classify(&self) -> Dynamics {
+        let steps = self.distances.len().saturating_sub(1);
+        let (first, last) = match (self.distances.first(), self.distances.last()) {
+            (Some(&first), Some(&last)) if steps > 0 => (first, last),
+            _ => return Dynamics::Ordered,
+        };
+        if last <= first * ORDERED_GROWTH {
+            Dynamics::Ordered
+        } else if last >= CHAOTIC_SPEED * steps as f32 {
+            Dynamics::Chaotic
+        } else {
+            Dynamics::Critical
+        }
+    }
+}
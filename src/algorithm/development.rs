@@ -0,0 +1,371 @@
+use algorithm::chemotaxis::NutrientField;
+use algorithm::geometry::Geometry;
+use algorithm::integrator::Integrator;
+use ndarray::Array2;
+use rand::Rng;
+use visualizer::palette::Palette;
+use visualizer::Matrix;
+
+/// 細胞の種類。`state`は`cell_types`で描くときの状態で、0は細胞のないセル
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellType {
+    /// まだ分化していない、分裂できる細胞
+    Stem,
+    /// モルフォゲンを出す細胞。分裂も分化もしない
+    Organizer,
+    /// モルフォゲンが濃いところで分化した細胞(フランス国旗の青)
+    Blue,
+    /// モルフォゲンが中くらいのところで分化した細胞(白)
+    White,
+    /// モルフォゲンが薄いところで分化した細胞(赤)
+    Red,
+}
+
+impl CellType {
+    /// 描くときの状態(1〜5)
+    pub fn state(self) -> u8 {
+        match self {
+            CellType::Stem => 1,
+            CellType::Organizer => 2,
+            CellType::Blue => 3,
+            CellType::White => 4,
+            CellType::Red => 5,
+        }
+    }
+}
+
+/// 1つの細胞
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cell {
+    /// 種類
+    pub cell_type: CellType,
+    /// 祖先の番号。`Development::place`で置いた細胞ごとに0から振られ、子孫に受け継がれる
+    pub lineage: usize,
+    /// 祖先から何回分裂したか
+    pub generation: usize,
+    /// 生まれてからのステップ数
+    pub age: usize,
+}
+
+/// 細胞が分裂・分化・死ぬ条件
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DevelopmentRules {
+    /// オーガナイザーがモルフォゲンを出す速さ
+    pub production: f32,
+    /// モルフォゲンの拡散係数
+    pub diffusion: f32,
+    /// モルフォゲンの分解の速さ
+    pub decay: f32,
+    /// 幹細胞が1ステップに分裂する確率
+    pub division_rate: f32,
+    /// 幹細胞が分裂できるモルフォゲンの濃さの下限
+    pub growth_threshold: f32,
+    /// 幹細胞が分化するまでのステップ数
+    pub differentiation_age: usize,
+    /// 分化するときの(青, 白)になる濃さの下限。どちらより薄ければ赤になる
+    pub thresholds: (f32, f32),
+    /// この濃さより薄いところの細胞は死ぬことがある
+    pub death_threshold: f32,
+    /// `death_threshold`より薄いところの細胞が1ステップに死ぬ確率
+    pub death_rate: f32,
+}
+
+impl Default for DevelopmentRules {
+    fn default() -> DevelopmentRules {
+        DevelopmentRules {
+            production: 0.1,
+            diffusion: 1.0,
+            decay: 0.01,
+            division_rate: 0.2,
+            growth_threshold: 0.1,
+            differentiation_age: 40,
+            thresholds: (0.4, 0.2),
+            death_threshold: 0.05,
+            death_rate: 0.05,
+        }
+    }
+}
+
+impl DevelopmentRules {
+    /// 濃さ`concentration`のところで幹細胞が分化してなる種類
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::algorithm::development::{CellType, DevelopmentRules};
+    ///
+    /// let rules = DevelopmentRules::default();
+    /// assert_eq!(rules.fate(0.9), CellType::Blue);
+    /// assert_eq!(rules.fate(0.3), CellType::White);
+    /// assert_eq!(rules.fate(0.0), CellType::Red);
+    /// ```
+    pub fn fate(&self, concentration: f32) -> CellType {
+        if concentration >= self.thresholds.0 {
+            CellType::Blue
+        } else if concentration >= self.thresholds.1 {
+            CellType::White
+        } else {
+            CellType::Red
+        }
+    }
+}
+
+/// モルフォゲンの濃さに従って細胞が分裂・分化・死ぬ多細胞の発生のモデル
+///
+/// オーガナイザーの出すモルフォゲンは組織の中だけを拡散して分解される。
+/// 幹細胞はモルフォゲンが十分に濃いところで空いている隣のセルに分裂し、一定の年齢になるとその場の濃さで
+/// 青・白・赤のどれかに分化する(Wolpertのフランス国旗モデル)。モルフォゲンが薄すぎるところの細胞は死ぬ
+///
+/// # Example
+/// ```
+/// extern crate rand;
+/// extern crate my_alife;
+///
+/// use rand::{SeedableRng, XorShiftRng};
+/// use my_alife::algorithm::development::{CellType, Development, DevelopmentRules};
+///
+/// let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+/// let mut development = Development::french_flag((12, 48), 4, DevelopmentRules::default());
+/// // オーガナイザー12個と幹細胞3個がそれぞれの系統の祖先になる
+/// assert_eq!(development.lineage_count(), 15);
+/// development.run(300, &mut rng);
+/// assert!(development.count(CellType::Stem) < development.population() / 10);
+/// // オーガナイザーから近い順に青・白・赤の帯ができる
+/// let types = development.cell_types();
+/// let row = types.row(6);
+/// let first = |state: u8| row.iter().position(|&s| s == state).unwrap();
+/// assert!(first(CellType::Blue.state()) < first(CellType::White.state()));
+/// assert!(first(CellType::White.state()) < first(CellType::Red.state()));
+/// // 組織はオーガナイザーから離れた右端までは育たない
+/// assert_eq!(types[[6, 47]], 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Development {
+    cells: Matrix<Option<Cell>>,
+    rules: DevelopmentRules,
+    morphogen: NutrientField,
+    fields: Vec<Matrix<f32>>,
+    integrator: Integrator,
+    lineages: usize,
+    divisions: usize,
+    deaths: usize,
+    time: usize,
+}
+
+impl Development {
+    /// 大きさ`dim`(行, 列)の、細胞のない盤面を作る
+    pub fn new(dim: (usize, usize), rules: DevelopmentRules) -> Development {
+        Development {
+            cells: Array2::from_elem(dim, None),
+            rules,
+            morphogen: NutrientField::new(dim, 1.0),
+            fields: vec![Array2::zeros(dim)],
+            integrator: Integrator::SemiImplicitEuler,
+            lineages: 0,
+            divisions: 0,
+            deaths: 0,
+            time: 0,
+        }
+    }
+
+    /// 左端の列をオーガナイザーにし、その右隣に`spacing`行おきに幹細胞を置いた盤面を作る
+    ///
+    /// # Panics
+    /// `spacing`が0のとき
+    pub fn french_flag(dim: (usize, usize), spacing: usize, rules: DevelopmentRules) -> Development {
+        assert!(spacing > 0, "spacing must be positive");
+        let mut development = Development::new(dim, rules);
+        for row in 0..dim.0 {
+            development.place((row, 0), CellType::Organizer);
+        }
+        for row in (spacing / 2..dim.0).step_by(spacing) {
+            development.place((row, 1), CellType::Stem);
+        }
+        development
+    }
+
+    /// 盤面の大きさ
+    pub fn dim(&self) -> (usize, usize) {
+        self.cells.dim()
+    }
+
+    /// 細胞が分裂・分化・死ぬ条件
+    pub fn rules(&self) -> &DevelopmentRules {
+        &self.rules
+    }
+
+    /// 細胞が分裂・分化・死ぬ条件を変更する
+    pub fn rules_mut(&mut self) -> &mut DevelopmentRules {
+        &mut self.rules
+    }
+
+    /// モルフォゲンを解く方法を設定する。初期値は`Integrator::SemiImplicitEuler`
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        self.integrator = integrator;
+    }
+
+    /// セルごとの細胞
+    pub fn cells(&self) -> &Matrix<Option<Cell>> {
+        &self.cells
+    }
+
+    /// セル`cell`(行, 列)の細胞
+    pub fn get(&self, cell: (usize, usize)) -> Option<&Cell> {
+        self.cells[[cell.0, cell.1]].as_ref()
+    }
+
+    /// セル`cell`に新しい系統の祖先として`cell_type`の細胞を置き、その系統の番号を返す。元の細胞は上書きされる
+    pub fn place(&mut self, cell: (usize, usize), cell_type: CellType) -> usize {
+        let lineage = self.lineages;
+        self.lineages += 1;
+        self.cells[[cell.0, cell.1]] = Some(Cell {
+            cell_type,
+            lineage,
+            generation: 0,
+            age: 0,
+        });
+        lineage
+    }
+
+    /// セル`cell`の細胞を取り除く
+    pub fn remove(&mut self, cell: (usize, usize)) {
+        self.cells[[cell.0, cell.1]] = None;
+    }
+
+    /// モルフォゲンの濃さ
+    pub fn morphogen(&self) -> &Matrix<f32> {
+        &self.fields[0]
+    }
+
+    /// 経過したステップ数
+    pub fn time(&self) -> usize {
+        self.time
+    }
+
+    /// これまでに置いた祖先の数
+    pub fn lineage_count(&self) -> usize {
+        self.lineages
+    }
+
+    /// これまでに分裂した回数
+    pub fn divisions(&self) -> usize {
+        self.divisions
+    }
+
+    /// これまでに死んだ細胞の数
+    pub fn deaths(&self) -> usize {
+        self.deaths
+    }
+
+    /// 細胞の数
+    pub fn population(&self) -> usize {
+        self.cells.iter().filter(|cell| cell.is_some()).count()
+    }
+
+    /// 種類が`cell_type`の細胞の数
+    pub fn count(&self, cell_type: CellType) -> usize {
+        self.cells
+            .iter()
+            .filter(|cell| cell.is_some_and(|cell| cell.cell_type == cell_type))
+            .count()
+    }
+
+    /// 細胞の種類の地図。細胞のないセルは0で、ほかは`CellType::state`。`cell_type_palette`で描く
+    pub fn cell_types(&self) -> Matrix<u8> {
+        self.cells.mapv(|cell| cell.map_or(0, |cell| cell.cell_type.state()))
+    }
+
+    /// 系統の地図。細胞のないセルは0で、ほかは系統の番号+1(255で打ち切る)。`lineage_palette`で描く
+    pub fn lineages(&self) -> Matrix<u8> {
+        self.cells
+            .mapv(|cell| cell.map_or(0, |cell| (cell.lineage + 1).min(255) as u8))
+    }
+
+    /// 系統の地図を描くためのパレット
+    pub fn lineage_palette(&self) -> Palette {
+        let mut palette = Palette::categorical(self.lineages + 1);
+        palette.set(0, "Empty", [0, 0, 0]);
+        palette
+    }
+
+    /// 1ステップ進める。モルフォゲンを組織の中で拡散させたあと、ランダムな順に細胞が死ぬか、分化するか、分裂する
+    pub fn step<R: Rng>(&mut self, rng: &mut R) {
+        self.update_morphogen();
+        let (rows, cols) = self.dim();
+        let mut order: Vec<_> = self
+            .cells
+            .indexed_iter()
+            .filter(|&(_, cell)| cell.is_some_and(|cell| cell.cell_type != CellType::Organizer))
+            .map(|(index, _)| index)
+            .collect();
+        rng.shuffle(&mut order);
+        for (row, col) in order {
+            let mut cell = match self.cells[[row, col]] {
+                Some(cell) => cell,
+                None => continue,
+            };
+            let concentration = self.fields[0][[row, col]];
+            if cell.age > 0 && concentration < self.rules.death_threshold && rng.next_f32() < self.rules.death_rate {
+                self.cells[[row, col]] = None;
+                self.deaths += 1;
+                continue;
+            }
+            if cell.cell_type == CellType::Stem {
+                if cell.age >= self.rules.differentiation_age {
+                    cell.cell_type = self.rules.fate(concentration);
+                } else if concentration >= self.rules.growth_threshold && rng.next_f32() < self.rules.division_rate {
+                    let empty: Vec<_> = [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                        .iter()
+                        .map(|&(dr, dc)| (row as isize + dr, col as isize + dc))
+                        .filter(|&(r, c)| r >= 0 && c >= 0 && (r as usize) < rows && (c as usize) < cols)
+                        .map(|(r, c)| (r as usize, c as usize))
+                        .filter(|&(r, c)| self.cells[[r, c]].is_none())
+                        .collect();
+                    if let Some(&(r, c)) = rng.choose(&empty) {
+                        cell.generation += 1;
+                        self.cells[[r, c]] = Some(Cell { age: 0, ..cell });
+                        self.divisions += 1;
+                    }
+                }
+            }
+            cell.age += 1;
+            self.cells[[row, col]] = Some(cell);
+        }
+        self.time += 1;
+    }
+
+    /// `steps`ステップ進める
+    pub fn run<R: Rng>(&mut self, steps: usize, rng: &mut R) {
+        for _ in 0..steps {
+            self.step(rng);
+        }
+    }
+
+    // オーガナイザーから湧き出させ、細胞のないセルを壁にしてモルフォゲンを1ステップ進める
+    fn update_morphogen(&mut self) {
+        self.morphogen.diffusion = self.rules.diffusion;
+        self.morphogen.decay = self.rules.decay;
+        let production = self.rules.production;
+        *self.morphogen.supply_mut() = self.cells.mapv(|cell| match cell {
+            Some(Cell {
+                cell_type: CellType::Organizer,
+                ..
+            }) => production,
+            _ => 0.0,
+        });
+        let geometry = Geometry::from_walls(&self.cells.mapv(|cell| cell.is_none()));
+        self.integrator
+            .step(&geometry.with_geometry(&self.morphogen), &mut self.fields, 1.0);
+        geometry.apply(&mut self.fields[0]);
+    }
+}
+
+/// 細胞の種類の地図を描くためのパレット
+pub fn cell_type_palette() -> Palette {
+    Palette::new()
+        .with("Empty", [0, 0, 0])
+        .with("Stem", [120, 220, 120])
+        .with("Organizer", [255, 200, 0])
+        .with("Blue", [0, 85, 164])
+        .with("White", [240, 240, 240])
+        .with("Red", [239, 65, 53])
+}
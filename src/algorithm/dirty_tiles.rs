@@ -0,0 +1,156 @@
+/// グリッド上の長方形の範囲(セル単位)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileRect {
+    /// 左上の行
+    pub row: usize,
+    /// 左上の列
+    pub col: usize,
+    /// 行数
+    pub rows: usize,
+    /// 列数
+    pub cols: usize,
+}
+
+/// グリッドを`tile_size`四方のタイルに分け、変化したタイルを記録する  
+/// 変化した部分だけを計算し直したり、テクスチャに転送し直したりするのに使う
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::dirty_tiles::{DirtyTiles, TileRect};
+///
+/// let mut tiles = DirtyTiles::new((100, 100), 32);
+/// assert_eq!(tiles.tile_counts(), (4, 4));
+/// tiles.mark_cell(70, 99);
+/// assert_eq!(tiles.iter().collect::<Vec<_>>(), vec![(2, 3)]);
+/// // 端のタイルはグリッドの大きさで切り詰められる
+/// assert_eq!(tiles.rect(2, 3), TileRect { row: 64, col: 96, rows: 32, cols: 4 });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirtyTiles {
+    dim: (usize, usize),
+    tile_size: usize,
+    tiles: (usize, usize),
+    dirty: Vec<bool>,
+}
+
+impl DirtyTiles {
+    /// すべてのタイルが変化していない状態で生成する
+    ///
+    /// # Arguments
+    /// * `dim` - グリッドの大きさ(行数, 列数)
+    /// * `tile_size` - タイル1辺のセル数
+    pub fn new(dim: (usize, usize), tile_size: usize) -> DirtyTiles {
+        assert!(tile_size > 0, "tile_size must be positive");
+        let tiles = (dim.0.div_ceil(tile_size), dim.1.div_ceil(tile_size));
+        DirtyTiles {
+            dim,
+            tile_size,
+            tiles,
+            dirty: vec![false; tiles.0 * tiles.1],
+        }
+    }
+
+    /// すべてのタイルが変化した状態で生成する
+    pub fn all(dim: (usize, usize), tile_size: usize) -> DirtyTiles {
+        let mut tiles = DirtyTiles::new(dim, tile_size);
+        tiles.mark_all();
+        tiles
+    }
+
+    /// グリッドの大きさ
+    pub fn dim(&self) -> (usize, usize) {
+        self.dim
+    }
+
+    /// タイル1辺のセル数
+    pub fn tile_size(&self) -> usize {
+        self.tile_size
+    }
+
+    /// 縦横のタイル数
+    pub fn tile_counts(&self) -> (usize, usize) {
+        self.tiles
+    }
+
+    /// セル(row, col)を含むタイルを変化したことにする
+    pub fn mark_cell(&mut self, row: usize, col: usize) {
+        self.mark_tile(row / self.tile_size, col / self.tile_size);
+    }
+
+    /// タイルを変化したことにする
+    pub fn mark_tile(&mut self, tile_row: usize, tile_col: usize) {
+        let index = tile_row * self.tiles.1 + tile_col;
+        self.dirty[index] = true;
+    }
+
+    /// すべてのタイルを変化したことにする
+    pub fn mark_all(&mut self) {
+        for d in self.dirty.iter_mut() {
+            *d = true;
+        }
+    }
+
+    /// すべてのタイルを変化していないことにする
+    pub fn clear(&mut self) {
+        for d in self.dirty.iter_mut() {
+            *d = false;
+        }
+    }
+
+    /// タイルが変化したかどうか
+    pub fn is_dirty(&self, tile_row: usize, tile_col: usize) -> bool {
+        self.dirty[tile_row * self.tiles.1 + tile_col]
+    }
+
+    /// 変化したタイルがひとつもないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.dirty.iter().all(|d| !d)
+    }
+
+    /// 変化したタイルの数
+    pub fn count(&self) -> usize {
+        self.dirty.iter().filter(|d| **d).count()
+    }
+
+    /// 変化したタイルの(行, 列)を列挙する
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let cols = self.tiles.1;
+        self.dirty
+            .iter()
+            .enumerate()
+            .filter(|&(_, d)| *d)
+            .map(move |(i, _)| (i / cols, i % cols))
+    }
+
+    /// 変化したタイルのセル単位の範囲を列挙する
+    pub fn rects(&self) -> impl Iterator<Item = TileRect> + '_ {
+        self.iter().map(move |(r, c)| self.rect(r, c))
+    }
+
+    /// タイルのセル単位の範囲
+    pub fn rect(&self, tile_row: usize, tile_col: usize) -> TileRect {
+        let row = tile_row * self.tile_size;
+        let col = tile_col * self.tile_size;
+        TileRect {
+            row,
+            col,
+            rows: self.tile_size.min(self.dim.0 - row),
+            cols: self.tile_size.min(self.dim.1 - col),
+        }
+    }
+
+    /// 変化したタイルとその8近傍のタイル(周期境界)を変化したことにしたものを返す  
+    /// 1ステップで1セルしか影響が広がらないモデルでは、次に計算し直すべき範囲になる
+    pub fn dilate(&self) -> DirtyTiles {
+        let mut dilated = DirtyTiles::new(self.dim, self.tile_size);
+        let (rows, cols) = self.tiles;
+        for (r, c) in self.iter() {
+            for dr in 0..3 {
+                for dc in 0..3 {
+                    dilated.mark_tile((r + rows + dr - 1) % rows, (c + cols + dc - 1) % cols);
+                }
+            }
+        }
+        dilated
+    }
+}
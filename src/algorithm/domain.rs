@@ -0,0 +1,204 @@
+use ndarray::{Array2, Axis};
+use std::ops::Range;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use visualizer::Matrix;
+
+// 隣の帯とやりとりする境界の行。場ごとに1つ
+type Halo<A> = Vec<Matrix<A>>;
+
+/// 盤面を行方向の帯に分け、帯ごとのスレッドで計算する領域分割
+///
+/// それぞれの帯は上下に`halo`行の袖(隣の帯の境界の行の写し)を持つ。
+/// 袖の幅の分だけ計算を進めたら、隣の帯と境界の行をチャンネルで送りあって袖を更新する(MPIのhalo exchange)。
+/// 帯の上下と盤面の左右は周期境界条件でつながる
+///
+/// 1ステップの計算には盤面全体を周期境界条件で計算する関数をそのまま使える。
+/// 袖に近いところの値は正しくなくなるが、帯の内側は近傍の半径×ステップ数が袖の幅を超えない限り正しい
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::domain::Decomposition;
+/// use my_alife::algorithm::game_of_life::{random_cells, step};
+///
+/// let cells = random_cells((64, 48), 0.3);
+/// let mut expected = cells.clone();
+/// for _ in 0..10 {
+///     expected = step(&expected);
+/// }
+///
+/// let mut decomposition = Decomposition::new(4, 1);
+/// decomposition.set_exchange_interval(3);
+/// let mut fields = vec![cells];
+/// decomposition.run(&mut fields, 10, |local| local[0] = step(&local[0]));
+/// assert_eq!(fields[0], expected);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decomposition {
+    workers: usize,
+    radius: usize,
+    exchange_interval: usize,
+}
+
+impl Decomposition {
+    /// `workers`本の帯に分ける。`radius`は1ステップで参照する近傍の半径
+    ///
+    /// # Panics
+    /// `workers`か`radius`が0のとき
+    pub fn new(workers: usize, radius: usize) -> Decomposition {
+        assert!(workers > 0, "workers must be positive");
+        assert!(radius > 0, "radius must be positive");
+        Decomposition {
+            workers,
+            radius,
+            exchange_interval: 1,
+        }
+    }
+
+    /// 帯の数
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    /// 境界を送りあう間隔(ステップ数)。大きくすると袖が広がり、やりとりの回数が減るかわりに重複した計算が増える
+    pub fn set_exchange_interval(&mut self, interval: usize) {
+        self.exchange_interval = interval.max(1);
+    }
+
+    /// 袖の行数
+    pub fn halo(&self) -> usize {
+        self.radius * self.exchange_interval
+    }
+
+    /// `rows`行の盤面をどう分けるか。帯の行数の差は1行以内になる
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::algorithm::domain::Decomposition;
+    ///
+    /// assert_eq!(Decomposition::new(3, 1).strips(10), vec![0..4, 4..7, 7..10]);
+    /// ```
+    ///
+    /// # Panics
+    /// 帯の行数が袖の行数より少なくなるとき
+    pub fn strips(&self, rows: usize) -> Vec<Range<usize>> {
+        let (base, extra) = (rows / self.workers, rows % self.workers);
+        assert!(
+            base >= self.halo(),
+            "strips of {} rows are thinner than the halo of {} rows",
+            base,
+            self.halo()
+        );
+        let mut start = 0;
+        (0..self.workers)
+            .map(|i| {
+                let end = start + base + if i < extra { 1 } else { 0 };
+                let strip = start..end;
+                start = end;
+                strip
+            })
+            .collect()
+    }
+
+    /// 大きさの等しい`fields`を`steps`ステップ進める
+    ///
+    /// `step`は袖の付いた帯の場をその場で1ステップ進める関数で、すべてのスレッドから同時に呼ばれる
+    ///
+    /// # Panics
+    /// 場の大きさがそろっていないとき、`strips`がpanicするとき
+    pub fn run<A, F>(&self, fields: &mut [Matrix<A>], steps: usize, step: F)
+    where
+        A: Copy + Send + Sync,
+        F: Fn(&mut [Matrix<A>]) + Sync,
+    {
+        if fields.is_empty() || steps == 0 {
+            return;
+        }
+        let (rows, cols) = fields[0].dim();
+        assert!(
+            fields.iter().all(|field| field.dim() == (rows, cols)),
+            "fields must have the same size"
+        );
+        let strips = self.strips(rows);
+        let (halo, interval) = (self.halo(), self.exchange_interval);
+        let locals: Vec<Vec<Matrix<A>>> = strips
+            .iter()
+            .map(|strip| {
+                fields
+                    .iter()
+                    .map(|field| {
+                        Array2::from_shape_fn((strip.len() + 2 * halo, cols), |(row, col)| {
+                            field[[(strip.start + rows + row - halo) % rows, col]]
+                        })
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // from_north[i]には北(上)の帯の下端が、from_south[i]には南(下)の帯の上端が届く
+        let n = strips.len();
+        let (to_norths, from_norths): (Vec<_>, Vec<_>) = (0..n).map(|_| channel::<Halo<A>>()).unzip();
+        let (to_souths, from_souths): (Vec<_>, Vec<_>) = (0..n).map(|_| channel::<Halo<A>>()).unzip();
+        let step = &step;
+        let results: Vec<Vec<Matrix<A>>> = thread::scope(|scope| {
+            let workers: Vec<_> = locals
+                .into_iter()
+                .zip(from_norths.into_iter().zip(from_souths))
+                .enumerate()
+                .map(|(i, (mut local, (from_north, from_south)))| {
+                    // 自分の上端は北の帯の南の袖に、下端は南の帯の北の袖になる
+                    let north = to_souths[(i + n - 1) % n].clone();
+                    let south = to_norths[(i + 1) % n].clone();
+                    scope.spawn(move || {
+                        let mut remaining = steps;
+                        while remaining > 0 {
+                            let count = remaining.min(interval);
+                            for _ in 0..count {
+                                step(&mut local);
+                            }
+                            remaining -= count;
+                            if remaining > 0 {
+                                exchange(&mut local, halo, (&north, &south), (&from_north, &from_south));
+                            }
+                        }
+                        local
+                    })
+                })
+                .collect();
+            drop((to_norths, to_souths));
+            workers.into_iter().map(|worker| worker.join().unwrap()).collect()
+        });
+
+        for (strip, local) in strips.into_iter().zip(results) {
+            for (field, local) in fields.iter_mut().zip(local) {
+                field
+                    .slice_mut(s![strip.clone(), ..])
+                    .assign(&local.slice(s![halo as isize..-(halo as isize), ..]));
+            }
+        }
+    }
+}
+
+fn exchange<A: Copy>(
+    local: &mut [Matrix<A>],
+    halo: usize,
+    (north, south): (&Sender<Halo<A>>, &Sender<Halo<A>>),
+    (from_north, from_south): (&Receiver<Halo<A>>, &Receiver<Halo<A>>),
+) {
+    let rows = local[0].len_of(Axis(0));
+    let edge = |start: usize| -> Halo<A> {
+        local
+            .iter()
+            .map(|field| field.slice(s![start..start + halo, ..]).to_owned())
+            .collect()
+    };
+    // 受け取る側がいなくなるのは、ほかのスレッドがpanicしたときだけ
+    let _ = north.send(edge(halo));
+    let _ = south.send(edge(rows - 2 * halo));
+    let from_north = from_north.recv().expect("the northern worker stopped");
+    let from_south = from_south.recv().expect("the southern worker stopped");
+    for (field, (top, bottom)) in local.iter_mut().zip(from_north.into_iter().zip(from_south)) {
+        field.slice_mut(s![..halo, ..]).assign(&top);
+        field.slice_mut(s![rows - halo.., ..]).assign(&bottom);
+    }
+}
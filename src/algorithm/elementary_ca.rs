@@ -0,0 +1,58 @@
+use ndarray::Array2;
+use visualizer::Matrix;
+
+/// Wolframの1次元セル・オートマトン(elementary cellular automaton)を1ステップ進める(周期境界条件)
+///
+/// # Arguments
+/// * `cells` - 各セルの状態(0か1)
+/// * `rule` - ルール番号(0〜255)。近傍(左, 自分, 右)を2進数とみなした値のbitが次の状態になる
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::elementary_ca::step;
+///
+/// // rule 90はSierpinskiの三角形を描く
+/// assert_eq!(step(&[0, 0, 1, 0, 0], 90), vec![0, 1, 0, 1, 0]);
+/// ```
+pub fn step(cells: &[u8], rule: u8) -> Vec<u8> {
+    let len = cells.len();
+    (0..len)
+        .map(|i| {
+            let left = cells[(i + len - 1) % len];
+            let right = cells[(i + 1) % len];
+            let pattern = left << 2 | cells[i] << 1 | right;
+            rule >> pattern & 1
+        })
+        .collect()
+}
+
+/// 中央のセルだけが1の初期状態
+pub fn single_seed(width: usize) -> Vec<u8> {
+    let mut cells = vec![0; width];
+    if width > 0 {
+        cells[width / 2] = 1;
+    }
+    cells
+}
+
+/// `initial`から`steps`ステップ分の時間発展を、上から下へ時間が進む行列にする
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::elementary_ca::{single_seed, space_time};
+///
+/// let history = space_time(&single_seed(9), 110, 4);
+/// assert_eq!(history.dim(), (4, 9));
+/// assert_eq!(history.row(1).to_vec(), vec![0, 0, 0, 1, 1, 0, 0, 0, 0]);
+/// ```
+pub fn space_time(initial: &[u8], rule: u8, steps: usize) -> Matrix<u8> {
+    let mut history = Array2::zeros((steps, initial.len()));
+    let mut cells = initial.to_vec();
+    for mut row in history.outer_iter_mut() {
+        for (e, &cell) in row.iter_mut().zip(&cells) {
+            *e = cell;
+        }
+        cells = step(&cells, rule);
+    }
+    history
+}
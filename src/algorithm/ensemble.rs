@@ -0,0 +1,234 @@
+use ndarray::{Array2, Zip};
+use rand::{SeedableRng, XorShiftRng};
+use std::thread;
+use visualizer::Matrix;
+
+/// セルごとの、レプリカ全体での平均と分散
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldStatistics {
+    /// 平均
+    pub mean: Matrix<f32>,
+    /// 分散(レプリカの数で割る)
+    pub variance: Matrix<f32>,
+}
+
+/// レプリカごとに1つの値をとる観測量の分布
+#[derive(Debug, Clone, PartialEq)]
+pub struct Distribution {
+    /// レプリカの順に並んだ値
+    pub values: Vec<f32>,
+}
+
+impl Distribution {
+    /// 平均。値がなければ0
+    pub fn mean(&self) -> f32 {
+        match self.values.len() {
+            0 => 0.0,
+            n => self.values.iter().sum::<f32>() / n as f32,
+        }
+    }
+
+    /// 分散(値の数で割る)。値がなければ0
+    pub fn variance(&self) -> f32 {
+        let mean = self.mean();
+        match self.values.len() {
+            0 => 0.0,
+            n => self.values.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / n as f32,
+        }
+    }
+
+    /// 標準偏差
+    pub fn std_dev(&self) -> f32 {
+        self.variance().sqrt()
+    }
+
+    /// (最小値, 最大値)。値がなければ`None`
+    pub fn range(&self) -> Option<(f32, f32)> {
+        let first = *self.values.first()?;
+        Some(
+            self.values
+                .iter()
+                .fold((first, first), |(min, max), &v| (min.min(v), max.max(v))),
+        )
+    }
+
+    /// 最小値から最大値までを`bins`個に等分したヒストグラム
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::algorithm::ensemble::Distribution;
+    ///
+    /// let distribution = Distribution { values: vec![0.0, 0.1, 0.5, 0.9, 1.0] };
+    /// assert_eq!(distribution.histogram(2), vec![2, 3]);
+    /// assert_eq!(distribution.mean(), 0.5);
+    /// ```
+    pub fn histogram(&self, bins: usize) -> Vec<usize> {
+        let mut histogram = vec![0; bins];
+        let (min, max) = match self.range() {
+            Some(range) if bins > 0 => range,
+            _ => return histogram,
+        };
+        let width = (max - min).max(f32::MIN_POSITIVE);
+        for &v in &self.values {
+            let bin = (((v - min) / width) * bins as f32) as usize;
+            histogram[bin.min(bins - 1)] += 1;
+        }
+        histogram
+    }
+}
+
+/// 乱数の種だけを変えた同じ確率的なシミュレーションを`n`個並列に動かし、場の平均・分散や観測量の分布を求める
+///
+/// レプリカごとの乱数は`seed`から決まるので、同じ`seed`なら同じ結果になる
+///
+/// # Example
+/// ```
+/// extern crate my_alife;
+/// extern crate rand;
+///
+/// use my_alife::algorithm::ensemble::Ensemble;
+/// use my_alife::algorithm::game_of_life::random_cells_using;
+/// use my_alife::algorithm::neighborhood::Neighborhood;
+/// use my_alife::algorithm::stochastic_ca::StochasticCa;
+///
+/// // 近傍に1があれば確率0.5で1になり、1は確率0.2で0に戻る接触過程
+/// let ca = StochasticCa::new(2, Neighborhood::VonNeumann, |state, counts| match state {
+///     0 if counts[1] > 0 => vec![0.5, 0.5],
+///     0 => vec![1.0, 0.0],
+///     _ => vec![0.2, 0.8],
+/// });
+/// let mut ensemble = Ensemble::new(8, 42, |rng| random_cells_using((16, 16), 0.5, rng));
+/// ensemble.step(20, |cells, rng| *cells = ca.step(cells, rng));
+/// assert_eq!(ensemble.len(), 8);
+/// assert_eq!(ensemble.steps(), 20);
+///
+/// let statistics = ensemble.field_statistics(|cells| cells);
+/// assert!(statistics.mean.iter().all(|&m| (0.0..=1.0).contains(&m)));
+/// let density = ensemble.observable(|cells| cells.iter().map(|&e| f32::from(e)).sum::<f32>() / 256.0);
+/// assert_eq!(density.values.len(), 8);
+/// assert!(density.mean() > 0.0);
+///
+/// // 同じ種なら同じ結果になる
+/// let run = || {
+///     let mut ensemble = Ensemble::new(4, 7, |rng| random_cells_using((16, 16), 0.5, rng));
+///     ensemble.step(5, |cells, rng| *cells = ca.step(cells, rng));
+///     ensemble.replicas().to_vec()
+/// };
+/// assert_eq!(run(), run());
+/// ```
+pub struct Ensemble<S> {
+    replicas: Vec<S>,
+    rngs: Vec<XorShiftRng>,
+    steps: usize,
+}
+
+impl<S: Send> Ensemble<S> {
+    /// `n`個のレプリカを`init`で作る。`init`にはそのレプリカの乱数生成器が渡される
+    pub fn new<F>(n: usize, seed: u64, mut init: F) -> Ensemble<S>
+    where
+        F: FnMut(&mut XorShiftRng) -> S,
+    {
+        let mut rngs: Vec<XorShiftRng> = (0..n as u64).map(|i| replica_rng(seed, i)).collect();
+        let replicas = rngs.iter_mut().map(&mut init).collect();
+        Ensemble {
+            replicas,
+            rngs,
+            steps: 0,
+        }
+    }
+
+    /// レプリカの数
+    pub fn len(&self) -> usize {
+        self.replicas.len()
+    }
+
+    /// レプリカがないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.replicas.is_empty()
+    }
+
+    /// 進めたステップ数
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// すべてのレプリカ
+    pub fn replicas(&self) -> &[S] {
+        &self.replicas
+    }
+
+    /// すべてのレプリカを`step`で`steps`回ずつ進める。レプリカはCPUのコアの数に分けてスレッドで並列に進める
+    pub fn step<F>(&mut self, steps: usize, step: F)
+    where
+        F: Fn(&mut S, &mut XorShiftRng) + Sync,
+    {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk = self.replicas.len().div_ceil(threads).max(1);
+        let step = &step;
+        thread::scope(|scope| {
+            for (replicas, rngs) in self.replicas.chunks_mut(chunk).zip(self.rngs.chunks_mut(chunk)) {
+                scope.spawn(move || {
+                    for (replica, rng) in replicas.iter_mut().zip(rngs.iter_mut()) {
+                        for _ in 0..steps {
+                            step(replica, rng);
+                        }
+                    }
+                });
+            }
+        });
+        self.steps += steps;
+    }
+
+    /// `field`で取り出した場の、セルごとの平均と分散
+    ///
+    /// # Panics
+    /// レプリカがないか、場の大きさがレプリカによって違うとき
+    pub fn field_statistics<A, F>(&self, field: F) -> FieldStatistics
+    where
+        A: Copy + Into<f32>,
+        F: Fn(&S) -> &Matrix<A>,
+    {
+        assert!(!self.replicas.is_empty(), "ensemble has no replicas");
+        let dim = field(&self.replicas[0]).dim();
+        let mut mean = Array2::<f32>::zeros(dim);
+        let mut square = Array2::<f32>::zeros(dim);
+        for replica in &self.replicas {
+            let values = field(replica);
+            assert_eq!(values.dim(), dim, "field size differs between replicas");
+            Zip::from(&mut mean).and(&mut square).and(values).apply(|m, s, &v| {
+                let v = v.into();
+                *m += v;
+                *s += v * v;
+            });
+        }
+        let n = self.replicas.len() as f32;
+        mean /= n;
+        let mut variance = square / n;
+        Zip::from(&mut variance)
+            .and(&mean)
+            .apply(|v, &m| *v = (*v - m * m).max(0.0));
+        FieldStatistics { mean, variance }
+    }
+
+    /// `observable`で求めたレプリカごとの値の分布
+    pub fn observable<F: Fn(&S) -> f32>(&self, observable: F) -> Distribution {
+        Distribution {
+            values: self.replicas.iter().map(observable).collect(),
+        }
+    }
+}
+
+// `seed`のi番目のレプリカの乱数生成器。レプリカごとに別の2つの値をsplitmix64で混ぜて種にする(XorShiftRngの種は0だけではいけない)
+fn replica_rng(seed: u64, i: u64) -> XorShiftRng {
+    let mut state = seed.wrapping_add(i.wrapping_mul(2).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    let mut next = || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    };
+    let (a, b) = (next(), next());
+    let words = [a as u32, (a >> 32) as u32, b as u32, (b >> 32) as u32 | 1];
+    XorShiftRng::from_seed(words)
+}
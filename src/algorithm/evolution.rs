@@ -0,0 +1,400 @@
+use algorithm::checkpoint::{join, parse_list, Checkpoint, Persist};
+use algorithm::phylogeny::Phylogeny;
+use failure;
+use rand::distributions::{IndependentSample, Normal};
+use rand::Rng;
+use std::cmp::Ordering;
+
+// 実数の遺伝子に加える突然変異の標準偏差
+const MUTATION_SIGMA: f64 = 0.3;
+
+/// 遺伝的アルゴリズムで進化させられる遺伝子型
+pub trait Genome: Clone {
+    /// 遺伝子ごとに確率`rate`で突然変異させる
+    fn mutate<R: Rng>(&mut self, rate: f32, rng: &mut R);
+
+    /// `other`と交叉させた子を返す
+    fn crossover<R: Rng>(&self, other: &Self, rng: &mut R) -> Self;
+}
+
+/// 実数の並び。突然変異では正規分布のノイズを加え、交叉では遺伝子ごとにどちらかの親から受け継ぐ(一様交叉)
+impl Genome for Vec<f32> {
+    fn mutate<R: Rng>(&mut self, rate: f32, rng: &mut R) {
+        let normal = Normal::new(0.0, MUTATION_SIGMA);
+        for gene in self.iter_mut() {
+            if rng.gen::<f32>() < rate {
+                *gene += normal.ind_sample(rng) as f32;
+            }
+        }
+    }
+
+    fn crossover<R: Rng>(&self, other: &Vec<f32>, rng: &mut R) -> Vec<f32> {
+        self.iter()
+            .zip(other)
+            .map(|(&a, &b)| if rng.gen() { a } else { b })
+            .collect()
+    }
+}
+
+/// 実数の遺伝子型を一様乱数で生成する
+pub fn random_genome<R: Rng>(len: usize, scale: f32, rng: &mut R) -> Vec<f32> {
+    (0..len).map(|_| rng.gen_range(-scale, scale)).collect()
+}
+
+/// 遺伝的アルゴリズムのパラメータ
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GaParams {
+    /// そのまま次の世代に残す上位の個体数
+    pub elites: usize,
+    /// トーナメント選択で比べる個体数
+    pub tournament_size: usize,
+    /// 交叉させる確率。交叉しないときは親をそのまま複製する
+    pub crossover_rate: f32,
+    /// 遺伝子ごとの突然変異率
+    pub mutation_rate: f32,
+}
+
+impl Default for GaParams {
+    fn default() -> GaParams {
+        GaParams {
+            elites: 1,
+            tournament_size: 3,
+            crossover_rate: 0.7,
+            mutation_rate: 0.1,
+        }
+    }
+}
+
+/// エリート保存とトーナメント選択による遺伝的アルゴリズム
+///
+/// # Example
+/// ```
+/// extern crate my_alife;
+/// extern crate rand;
+///
+/// use my_alife::algorithm::evolution::{random_genome, GaParams, GeneticAlgorithm};
+///
+/// // すべての遺伝子を1に近づける
+/// let mut rng = rand::thread_rng();
+/// let population = (0..30).map(|_| random_genome(5, 1.0, &mut rng)).collect();
+/// let mut ga = GeneticAlgorithm::new(population, GaParams::default());
+/// let fitness = |genes: &Vec<f32>| -genes.iter().map(|g| (g - 1.0).powi(2)).sum::<f32>();
+/// ga.run(50, fitness, &mut rng);
+/// assert_eq!(ga.generation(), 50);
+/// let (_, best) = ga.best().unwrap();
+/// assert!(best > -0.5);
+/// // エリート保存により最良の適応度は下がらない
+/// assert!(ga.history().windows(2).all(|w| w[1] >= w[0]));
+/// ```
+pub struct GeneticAlgorithm<G> {
+    population: Vec<G>,
+    fitness: Vec<f32>,
+    params: GaParams,
+    generation: usize,
+    history: Vec<f32>,
+    // 系統の記録と、集団の各個体の番号
+    lineage: Option<(Phylogeny, Vec<u64>)>,
+}
+
+impl<G: Genome> GeneticAlgorithm<G> {
+    /// 初期集団`population`から始める
+    ///
+    /// # Panics
+    /// 集団が空のとき
+    pub fn new(population: Vec<G>, params: GaParams) -> GeneticAlgorithm<G> {
+        assert!(!population.is_empty(), "population must not be empty");
+        GeneticAlgorithm {
+            population,
+            fitness: Vec::new(),
+            params,
+            generation: 0,
+            history: Vec::new(),
+            lineage: None,
+        }
+    }
+
+    /// 系統の記録を始める。現在の集団が系統の根になる
+    ///
+    /// # Example
+    /// ```
+    /// extern crate my_alife;
+    /// extern crate rand;
+    ///
+    /// use my_alife::algorithm::evolution::{random_genome, GaParams, GeneticAlgorithm};
+    ///
+    /// let mut rng = rand::thread_rng();
+    /// let population = (0..10).map(|_| random_genome(2, 1.0, &mut rng)).collect();
+    /// let mut ga = GeneticAlgorithm::new(population, GaParams::default());
+    /// ga.enable_phylogeny();
+    /// ga.run(5, |genes: &Vec<f32>| genes[0], &mut rng);
+    /// let phylogeny = ga.phylogeny().unwrap();
+    /// let best = ga.ids().unwrap()[0];
+    /// // 集団の個体の系統は初期集団までさかのぼれる
+    /// let lineage = phylogeny.lineage(best);
+    /// assert_eq!(phylogeny.get(*lineage.last().unwrap()).unwrap().generation, 0);
+    /// assert!(phylogeny.to_newick().ends_with(';'));
+    /// ```
+    pub fn enable_phylogeny(&mut self) {
+        let mut phylogeny = Phylogeny::new();
+        let generation = self.generation;
+        let ids = self.population.iter().map(|_| phylogeny.root(generation)).collect();
+        self.lineage = Some((phylogeny, ids));
+    }
+
+    /// 記録している系統
+    pub fn phylogeny(&self) -> Option<&Phylogeny> {
+        self.lineage.as_ref().map(|lineage| &lineage.0)
+    }
+
+    /// 系統を記録しているとき、集団の各個体の番号
+    pub fn ids(&self) -> Option<&[u64]> {
+        self.lineage.as_ref().map(|lineage| &lineage.1[..])
+    }
+
+    fn record_fitness(&mut self) {
+        if let Some((ref mut phylogeny, ref ids)) = self.lineage {
+            for (&id, &fitness) in ids.iter().zip(&self.fitness) {
+                phylogeny.set_fitness(id, fitness);
+            }
+        }
+    }
+
+    /// 現在の集団
+    pub fn population(&self) -> &[G] {
+        &self.population
+    }
+
+    /// 現在の集団の適応度。まだ評価していなければ空
+    pub fn fitness(&self) -> &[f32] {
+        &self.fitness
+    }
+
+    /// パラメータ
+    pub fn params(&self) -> &GaParams {
+        &self.params
+    }
+
+    /// 世代数
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// 世代ごとの最良の適応度
+    pub fn history(&self) -> &[f32] {
+        &self.history
+    }
+
+    /// 集団の全員を`fitness`で評価する
+    pub fn evaluate<F: FnMut(&G) -> f32>(&mut self, mut fitness: F) {
+        self.fitness = self.population.iter().map(&mut fitness).collect();
+        self.record_fitness();
+    }
+
+    /// 集団全体を見て計算した適応度(新規性など)を設定する
+    ///
+    /// # Panics
+    /// `fitness`の数が集団の大きさと合わないとき
+    pub fn set_fitness(&mut self, fitness: Vec<f32>) {
+        assert_eq!(
+            fitness.len(),
+            self.population.len(),
+            "fitness length does not match the population"
+        );
+        self.fitness = fitness;
+        self.record_fitness();
+    }
+
+    /// 評価済みの集団で最も適応度の高い個体とその適応度
+    pub fn best(&self) -> Option<(&G, f32)> {
+        ranking(&self.fitness)
+            .first()
+            .map(|&i| (&self.population[i], self.fitness[i]))
+    }
+
+    /// 評価済みの集団から次の世代を作る
+    ///
+    /// # Panics
+    /// `evaluate`で評価していないとき
+    pub fn next_generation<R: Rng>(&mut self, rng: &mut R) {
+        assert_eq!(self.fitness.len(), self.population.len(), "population is not evaluated");
+        if let Some((_, best)) = self.best() {
+            self.history.push(best);
+        }
+        let ranking = ranking(&self.fitness);
+        let elites: Vec<usize> = ranking.iter().take(self.params.elites).cloned().collect();
+        let mut next: Vec<G> = elites.iter().map(|&i| self.population[i].clone()).collect();
+        // エリート以外の子の(親, 加わった変化)
+        let mut births = Vec::new();
+        while next.len() < self.population.len() {
+            let mother = self.tournament(rng);
+            let (mut child, parents, mut mutations) = if rng.gen::<f32>() < self.params.crossover_rate {
+                let father = self.tournament(rng);
+                let child = self.population[mother].crossover(&self.population[father], rng);
+                (child, vec![mother, father], vec!["crossover".to_string()])
+            } else {
+                (self.population[mother].clone(), vec![mother], Vec::new())
+            };
+            child.mutate(self.params.mutation_rate, rng);
+            mutations.push("mutation".to_string());
+            next.push(child);
+            births.push((parents, mutations));
+        }
+        if let Some((ref mut phylogeny, ref mut ids)) = self.lineage {
+            let generation = self.generation + 1;
+            let mut next_ids: Vec<u64> = elites.iter().map(|&i| ids[i]).collect();
+            for (parents, mutations) in births {
+                let parents: Vec<u64> = parents.iter().map(|&i| ids[i]).collect();
+                next_ids.push(phylogeny.birth(&parents, generation, mutations));
+            }
+            *ids = next_ids;
+        }
+        self.population = next;
+        self.fitness.clear();
+        self.generation += 1;
+    }
+
+    /// 評価済みの集団のうち、適応度の高い`count`体の複製と適応度
+    pub fn emigrants(&self, count: usize) -> Vec<(G, f32)> {
+        ranking(&self.fitness)
+            .into_iter()
+            .take(count)
+            .map(|i| (self.population[i].clone(), self.fitness[i]))
+            .collect()
+    }
+
+    /// 評価済みの集団の適応度の低い個体を、他の集団から来た`migrants`で置き換える
+    ///
+    /// # Panics
+    /// `evaluate`で評価していないとき
+    pub fn immigrate(&mut self, migrants: Vec<(G, f32)>) {
+        assert_eq!(self.fitness.len(), self.population.len(), "population is not evaluated");
+        let worst = ranking(&self.fitness).into_iter().rev();
+        for (i, (genome, fitness)) in worst.zip(migrants) {
+            self.population[i] = genome;
+            self.fitness[i] = fitness;
+            // 他の集団から来た個体は、この集団の系統では根になる
+            if let Some((ref mut phylogeny, ref mut ids)) = self.lineage {
+                ids[i] = phylogeny.birth(&[], self.generation, vec!["migration".to_string()]);
+                phylogeny.set_fitness(ids[i], fitness);
+            }
+        }
+    }
+
+    /// 評価と世代交代を`generations`回繰り返し、最後の集団を評価した状態で終わる
+    pub fn run<F: FnMut(&G) -> f32, R: Rng>(&mut self, generations: usize, mut fitness: F, rng: &mut R) {
+        for _ in 0..generations {
+            self.evaluate(&mut fitness);
+            self.next_generation(rng);
+        }
+        self.evaluate(&mut fitness);
+    }
+
+    /// 集団・適応度・世代数・履歴をチェックポイントに書き出す
+    ///
+    /// 乱数生成器の状態は含まないので、`CheckpointRng`を使って別に書き出す
+    ///
+    /// # Example
+    /// ```
+    /// extern crate my_alife;
+    /// extern crate rand;
+    ///
+    /// use my_alife::algorithm::checkpoint::CheckpointRng;
+    /// use my_alife::algorithm::evolution::{random_genome, GaParams, GeneticAlgorithm};
+    ///
+    /// let fitness = |genes: &Vec<f32>| -genes.iter().map(|g| g * g).sum::<f32>();
+    /// let mut rng = CheckpointRng::new(1);
+    /// let population = (0..10).map(|_| random_genome(3, 1.0, &mut rng)).collect();
+    /// let mut ga = GeneticAlgorithm::new(population, GaParams::default());
+    /// ga.run(5, fitness, &mut rng);
+    ///
+    /// let mut checkpoint = ga.to_checkpoint();
+    /// checkpoint.push("rng", &rng);
+    /// let text = checkpoint.to_string();
+    /// ga.run(5, fitness, &mut rng);
+    ///
+    /// // 中断せずに進めたときと完全に同じ結果になる
+    /// let checkpoint = text.parse().unwrap();
+    /// let mut resumed = GeneticAlgorithm::<Vec<f32>>::from_checkpoint(&checkpoint).unwrap();
+    /// let mut resumed_rng: CheckpointRng = checkpoint.parse("rng").unwrap();
+    /// resumed.run(5, fitness, &mut resumed_rng);
+    /// assert_eq!(resumed.population(), ga.population());
+    /// assert_eq!(resumed.history(), ga.history());
+    /// ```
+    pub fn to_checkpoint(&self) -> Checkpoint
+    where
+        G: Persist,
+    {
+        let mut checkpoint = Checkpoint::new("ga");
+        checkpoint.push("generation", self.generation);
+        checkpoint.push("elites", self.params.elites);
+        checkpoint.push("tournament_size", self.params.tournament_size);
+        checkpoint.push("crossover_rate", self.params.crossover_rate);
+        checkpoint.push("mutation_rate", self.params.mutation_rate);
+        checkpoint.push("history", join(&self.history));
+        checkpoint.push("fitness", join(&self.fitness));
+        for genome in &self.population {
+            checkpoint.push("genome", genome.encode());
+        }
+        if let Some((ref phylogeny, ref ids)) = self.lineage {
+            checkpoint.push("ids", join(ids));
+            for line in phylogeny.to_csv().lines().skip(1) {
+                checkpoint.push("lineage", line);
+            }
+        }
+        checkpoint
+    }
+
+    /// `to_checkpoint`で書き出した状態から復元する
+    pub fn from_checkpoint(checkpoint: &Checkpoint) -> Result<GeneticAlgorithm<G>, failure::Error>
+    where
+        G: Persist,
+    {
+        checkpoint.expect_kind("ga")?;
+        let population = checkpoint
+            .all("genome")
+            .into_iter()
+            .map(G::decode)
+            .collect::<Result<Vec<G>, failure::Error>>()?;
+        let fitness: Vec<f32> = parse_list(checkpoint.get("fitness")?)?;
+        if population.is_empty() || !(fitness.is_empty() || fitness.len() == population.len()) {
+            return Err(format_err!("checkpoint has an invalid population"));
+        }
+        Ok(GeneticAlgorithm {
+            population,
+            fitness,
+            params: GaParams {
+                elites: checkpoint.parse("elites")?,
+                tournament_size: checkpoint.parse("tournament_size")?,
+                crossover_rate: checkpoint.parse("crossover_rate")?,
+                mutation_rate: checkpoint.parse("mutation_rate")?,
+            },
+            generation: checkpoint.parse("generation")?,
+            history: parse_list(checkpoint.get("history")?)?,
+            lineage: match checkpoint.get("ids") {
+                Ok(ids) => {
+                    let csv = checkpoint.all("lineage").join("\n");
+                    Some((Phylogeny::from_csv(&csv)?, parse_list(ids)?))
+                }
+                Err(_) => None,
+            },
+        })
+    }
+
+    fn tournament<R: Rng>(&self, rng: &mut R) -> usize {
+        (0..self.params.tournament_size.max(1))
+            .map(|_| rng.gen_range(0, self.population.len()))
+            .max_by(|&a, &b| compare(self.fitness[a], self.fitness[b]))
+            .unwrap()
+    }
+}
+
+// 適応度の高い順に並べた番号
+fn ranking(fitness: &[f32]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..fitness.len()).collect();
+    order.sort_by(|&a, &b| compare(fitness[b], fitness[a]));
+    order
+}
+
+fn compare(a: f32, b: f32) -> Ordering {
+    a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+}
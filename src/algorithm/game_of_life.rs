@@ -0,0 +1,163 @@
+use algorithm::dirty_tiles::DirtyTiles;
+use ndarray::{Array, Array2};
+use ndarray_rand::RandomExt;
+use ndarray_rand::F32;
+use rand;
+use rand::distributions::Range;
+use rand::Rng;
+use std::mem;
+use visualizer::Matrix;
+
+/// 生きているセル
+pub const ALIVE: u8 = 1;
+/// 死んでいるセル
+pub const DEAD: u8 = 0;
+
+/// 生きているセルの割合が`density`のランダムな初期状態
+pub fn random_cells(dim: (usize, usize), density: f32) -> Matrix<u8> {
+    random_cells_using(dim, density, &mut rand::thread_rng())
+}
+
+/// `random_cells`と同じだが、`rng`を使う。seedを固定すれば同じ初期状態になる
+pub fn random_cells_using<R: Rng>(dim: (usize, usize), density: f32, rng: &mut R) -> Matrix<u8> {
+    Array::random_using(dim, F32(Range::new(0., 1.)), rng).mapv(|e| if e < density { ALIVE } else { DEAD })
+}
+
+/// 周期境界条件で、セル(row, col)のMoore近傍(8近傍)で生きているセルの数
+pub fn alive_neighbors(cells: &Matrix<u8>, row: usize, col: usize) -> u8 {
+    let (rows, cols) = cells.dim();
+    let mut count = 0;
+    for dr in 0..3 {
+        for dc in 0..3 {
+            if dr == 1 && dc == 1 {
+                continue;
+            }
+            count += cells[[(row + rows + dr - 1) % rows, (col + cols + dc - 1) % cols]];
+        }
+    }
+    count
+}
+
+/// B3/S23のルールで次の状態を決める
+pub fn next_state(state: u8, alive_neighbors: u8) -> u8 {
+    match (state, alive_neighbors) {
+        (ALIVE, 2) | (ALIVE, 3) | (DEAD, 3) => ALIVE,
+        _ => DEAD,
+    }
+}
+
+/// `before`から`after`の間に生まれたセルの数と死んだセルの数
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::arr2;
+/// use my_alife::algorithm::game_of_life::{births_and_deaths, step};
+///
+/// let blinker = arr2(&[[0, 0, 0, 0, 0], [0, 0, 1, 0, 0], [0, 0, 1, 0, 0], [0, 0, 1, 0, 0], [0, 0, 0, 0, 0]]);
+/// assert_eq!(births_and_deaths(&blinker, &step(&blinker)), (2, 2));
+/// ```
+pub fn births_and_deaths(before: &Matrix<u8>, after: &Matrix<u8>) -> (usize, usize) {
+    before.iter().zip(after.iter()).fold((0, 0), |(births, deaths), (&b, &a)| match (b, a) {
+        (DEAD, ALIVE) => (births + 1, deaths),
+        (ALIVE, DEAD) => (births, deaths + 1),
+        _ => (births, deaths),
+    })
+}
+
+/// Game of Lifeを1ステップ進める(周期境界条件)
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::arr2;
+/// use my_alife::algorithm::game_of_life::step;
+///
+/// // blinkerは周期2で振動する
+/// let blinker = arr2(&[[0, 0, 0, 0, 0], [0, 0, 1, 0, 0], [0, 0, 1, 0, 0], [0, 0, 1, 0, 0], [0, 0, 0, 0, 0]]);
+/// let next = step(&blinker);
+/// assert_eq!(next.row(2).to_vec(), vec![0, 1, 1, 1, 0]);
+/// assert_eq!(step(&next), blinker);
+/// ```
+pub fn step(cells: &Matrix<u8>) -> Matrix<u8> {
+    let mut next = Array2::zeros(cells.dim());
+    for ((row, col), e) in next.indexed_iter_mut() {
+        *e = next_state(cells[[row, col]], alive_neighbors(cells, row, col));
+    }
+    next
+}
+
+/// 変化したタイルの周りだけを計算し直すGame of Life  
+/// 盤面のほとんどが静止している終盤では、全体を計算するよりずっと速い
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::game_of_life::{random_cells, step, SparseLife};
+///
+/// let cells = random_cells((64, 64), 0.3);
+/// let mut dense = cells.clone();
+/// let mut sparse = SparseLife::new(cells, 16);
+/// for _ in 0..20 {
+///     dense = step(&dense);
+///     sparse.step();
+/// }
+/// assert_eq!(sparse.cells(), &dense);
+/// ```
+pub struct SparseLife {
+    cells: Matrix<u8>,
+    // cellsと常に同じ内容を保つ書き込み先
+    next: Matrix<u8>,
+    dirty: DirtyTiles,
+}
+
+impl SparseLife {
+    /// 初期状態`cells`から、すべてのタイルを変化したものとして開始する
+    pub fn new(cells: Matrix<u8>, tile_size: usize) -> SparseLife {
+        let dirty = DirtyTiles::all(cells.dim(), tile_size);
+        SparseLife {
+            next: cells.clone(),
+            cells,
+            dirty,
+        }
+    }
+
+    /// 現在の状態
+    pub fn cells(&self) -> &Matrix<u8> {
+        &self.cells
+    }
+
+    /// 直前のステップで変化したタイル
+    pub fn dirty(&self) -> &DirtyTiles {
+        &self.dirty
+    }
+
+    /// 1ステップ進め、変化したタイルを返す
+    pub fn step(&mut self) -> &DirtyTiles {
+        let active = self.dirty.dilate();
+        let mut changed = DirtyTiles::new(self.cells.dim(), self.dirty.tile_size());
+        for rect in active.rects() {
+            for row in rect.row..rect.row + rect.rows {
+                for col in rect.col..rect.col + rect.cols {
+                    let state = self.cells[[row, col]];
+                    let next = next_state(state, alive_neighbors(&self.cells, row, col));
+                    self.next[[row, col]] = next;
+                    if next != state {
+                        changed.mark_cell(row, col);
+                    }
+                }
+            }
+        }
+        mem::swap(&mut self.cells, &mut self.next);
+        // 変化したタイルだけを書き戻し、2つのバッファを同じ内容に保つ
+        for rect in changed.rects() {
+            let region = s![rect.row..rect.row + rect.rows, rect.col..rect.col + rect.cols];
+            self.next.slice_mut(region).assign(&self.cells.slice(region));
+        }
+        self.dirty = changed;
+        &self.dirty
+    }
+}
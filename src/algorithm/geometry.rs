@@ -0,0 +1,215 @@
+use algorithm::reaction_diffusion::ReactionDiffusion;
+use ndarray::{Array2, Zip};
+use visualizer::Matrix;
+
+/// セルの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellKind {
+    /// 何もない
+    Open,
+    /// 壁。拡散も流体も粒子も通さない
+    Wall,
+    /// 値を一定の高い値に保つ湧き出し口
+    Source,
+    /// 値を一定の低い値に保つ吸い込み口
+    Sink,
+}
+
+/// 拡散・流体・粒子のシミュレーションで共通して使う、壁や湧き出し口の配置
+///
+/// 一度作れば、`with_geometry`で反応拡散系に、`walls()`で`Lbm`や格子気体に渡して使い回せる
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::algorithm::geometry::{CellKind, Geometry};
+/// use my_alife::algorithm::reaction_diffusion::discrete_laplacian;
+///
+/// let mut geometry = Geometry::new((8, 8));
+/// geometry.paint(4, 4, 0, CellKind::Wall);
+/// geometry.paint(0, 0, 1, CellKind::Source);
+/// assert_eq!(geometry.get(1, 0), CellKind::Source);
+/// assert_eq!(geometry.walls().iter().filter(|&&w| w).count(), 1);
+///
+/// // 壁の隣では、壁との間で拡散しない
+/// let mut field = Array2::<f32>::zeros((8, 8));
+/// field[[4, 3]] = 1.0;
+/// assert_eq!(geometry.laplacian(&field, 1.0)[[4, 3]], -3.0);
+/// assert_eq!(discrete_laplacian(&field, 1.0)[[4, 3]], -4.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Geometry {
+    cells: Matrix<CellKind>,
+    source_value: f32,
+    sink_value: f32,
+}
+
+impl Geometry {
+    /// 全体が`CellKind::Open`の配置を生成する。湧き出し口の値は1.0、吸い込み口の値は0.0
+    pub fn new(dim: (usize, usize)) -> Geometry {
+        Geometry {
+            cells: Array2::from_elem(dim, CellKind::Open),
+            source_value: 1.0,
+            sink_value: 0.0,
+        }
+    }
+
+    /// `true`のセルを壁にした配置を生成する。画像から読み込んだマスクを使うときに便利
+    pub fn from_walls(walls: &Matrix<bool>) -> Geometry {
+        let mut geometry = Geometry::new(walls.dim());
+        geometry.cells = walls.mapv(|wall| if wall { CellKind::Wall } else { CellKind::Open });
+        geometry
+    }
+
+    /// 大きさ
+    pub fn dim(&self) -> (usize, usize) {
+        self.cells.dim()
+    }
+
+    /// 各セルの種類
+    pub fn cells(&self) -> &Matrix<CellKind> {
+        &self.cells
+    }
+
+    /// セル(row, col)の種類
+    pub fn get(&self, row: usize, col: usize) -> CellKind {
+        self.cells[[row, col]]
+    }
+
+    /// セル(row, col)の種類を変更する
+    pub fn set(&mut self, row: usize, col: usize, kind: CellKind) {
+        self.cells[[row, col]] = kind;
+    }
+
+    /// セル(row, col)を中心とする半径`radius`の円を`kind`で塗る。マウスで編集するときに使う
+    pub fn paint(&mut self, row: usize, col: usize, radius: usize, kind: CellKind) {
+        let (rows, cols) = self.dim();
+        let r = radius as isize;
+        for dr in -r..=r {
+            for dc in -r..=r {
+                let (target_row, target_col) = (row as isize + dr, col as isize + dc);
+                let inside =
+                    target_row >= 0 && target_col >= 0 && (target_row as usize) < rows && (target_col as usize) < cols;
+                if inside && dr * dr + dc * dc <= r * r {
+                    self.cells[[target_row as usize, target_col as usize]] = kind;
+                }
+            }
+        }
+    }
+
+    /// 湧き出し口と吸い込み口で保つ値を変更する
+    pub fn set_values(&mut self, source: f32, sink: f32) {
+        self.source_value = source;
+        self.sink_value = sink;
+    }
+
+    /// 壁のセルが`true`のMatrix
+    pub fn walls(&self) -> Matrix<bool> {
+        self.cells.mapv(|kind| kind == CellKind::Wall)
+    }
+
+    /// 描画用に、壁を1.0、湧き出し口を0.75、吸い込み口を0.25、それ以外を0.0にしたMatrix
+    pub fn to_matrix(&self) -> Matrix<f32> {
+        self.cells.mapv(|kind| match kind {
+            CellKind::Open => 0.0,
+            CellKind::Wall => 1.0,
+            CellKind::Source => 0.75,
+            CellKind::Sink => 0.25,
+        })
+    }
+
+    /// `field`の壁を0、湧き出し口と吸い込み口をそれぞれの値にする。時間発展の各ステップの後に呼ぶ
+    pub fn apply(&self, field: &mut Matrix<f32>) {
+        let (source, sink) = (self.source_value, self.sink_value);
+        Zip::from(field).and(&self.cells).apply(|e, &kind| match kind {
+            CellKind::Open => {}
+            CellKind::Wall => *e = 0.0,
+            CellKind::Source => *e = source,
+            CellKind::Sink => *e = sink,
+        });
+    }
+
+    /// 壁を通して拡散しない(壁との境界で流束が0になる)離散ラプラシアン。壁のセル自身は0になる(周期境界条件)
+    pub fn laplacian(&self, field: &Matrix<f32>, dx: f32) -> Matrix<f32> {
+        let (rows, cols) = field.dim();
+        Array2::from_shape_fn((rows, cols), |(row, col)| {
+            if self.cells[[row, col]] == CellKind::Wall {
+                return 0.0;
+            }
+            let neighbors = [
+                ((row + rows - 1) % rows, col),
+                ((row + 1) % rows, col),
+                (row, (col + cols - 1) % cols),
+                (row, (col + 1) % cols),
+            ];
+            let center = field[[row, col]];
+            let flux: f32 = neighbors
+                .iter()
+                .filter(|&&neighbor| self.cells[neighbor] != CellKind::Wall)
+                .map(|&neighbor| field[neighbor] - center)
+                .sum();
+            flux / (dx * dx)
+        })
+    }
+
+    /// `system`の拡散を、この配置の壁を通さないようにした反応拡散系
+    pub fn with_geometry<'a, S: ReactionDiffusion>(&'a self, system: &'a S) -> WithGeometry<'a, S> {
+        WithGeometry { system, geometry: self }
+    }
+}
+
+/// 壁を通して拡散しないようにした反応拡散系。`Geometry::with_geometry`で作る
+///
+/// # Example
+/// ```
+/// #[macro_use(s)]
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::algorithm::geometry::{CellKind, Geometry};
+/// use my_alife::algorithm::gray_scott::GrayScott;
+/// use my_alife::algorithm::integrator::Integrator;
+///
+/// let mut geometry = Geometry::new((32, 32));
+/// // 周期境界条件でつながらないように、2本の壁で区切る
+/// for row in 0..32 {
+///     geometry.set(row, 0, CellKind::Wall);
+///     geometry.set(row, 16, CellKind::Wall);
+/// }
+/// let model = GrayScott { f: 0.04, k: 0.06, du: 2e-5, dv: 1e-5 };
+/// let mut fields = [Array2::<f32>::ones((32, 32)), Array2::<f32>::zeros((32, 32))];
+/// fields[1][[16, 8]] = 1.0;
+/// for _ in 0..50 {
+///     Integrator::SemiImplicitEuler.step(&geometry.with_geometry(&model), &mut fields, 1.0);
+///     geometry.apply(&mut fields[1]);
+/// }
+/// // 壁の右側には広がらない
+/// assert!(fields[1].slice(s![.., 17..]).iter().all(|&v| v == 0.0));
+/// assert!(fields[1][[16, 10]] > 0.0);
+/// ```
+pub struct WithGeometry<'a, S: 'a> {
+    system: &'a S,
+    geometry: &'a Geometry,
+}
+
+impl<'a, S: ReactionDiffusion> ReactionDiffusion for WithGeometry<'a, S> {
+    fn dx(&self) -> f32 {
+        self.system.dx()
+    }
+
+    fn diffusions(&self) -> Vec<f32> {
+        self.system.diffusions()
+    }
+
+    fn reaction(&self, fields: &[Matrix<f32>]) -> Vec<Matrix<f32>> {
+        self.system.reaction(fields)
+    }
+
+    fn laplacian(&self, field: &Matrix<f32>, dx: f32) -> Matrix<f32> {
+        self.geometry.laplacian(field, dx)
+    }
+}
@@ -0,0 +1,551 @@
+use failure;
+use ndarray::Array2;
+use rand::distributions::{IndependentSample, Normal};
+use rand::Rng;
+use visualizer::Matrix;
+
+// これより平均が大きいPoisson分布は正規分布で近似する
+const POISSON_NORMAL_THRESHOLD: f64 = 30.0;
+// tau-leapingで分子の数が負になったときに、tauを半分にしてやり直す回数の上限
+const MAX_LEAP_RETRIES: usize = 16;
+
+/// 質量作用の法則に従う1つの反応
+///
+/// 時間の計算は誤差が溜まりやすいので、反応速度と時刻は`f64`で扱う
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reaction {
+    /// 消費される(物質の番号, 分子の数)
+    pub reactants: Vec<(usize, u32)>,
+    /// 生成される(物質の番号, 分子の数)
+    pub products: Vec<(usize, u32)>,
+    /// 反応速度定数
+    pub rate: f64,
+}
+
+impl Reaction {
+    /// 分子の数が`counts`のときの反応の起こりやすさ(propensity)。速度定数 × 反応物の組み合わせの数
+    pub fn propensity(&self, counts: &[u64]) -> f64 {
+        self.reactants.iter().fold(self.rate, |a, &(species, n)| {
+            let available = counts[species];
+            if available < u64::from(n) {
+                return 0.0;
+            }
+            // available C n
+            (0..u64::from(n)).fold(a, |a, i| a * (available - i) as f64 / (i + 1) as f64)
+        })
+    }
+
+    /// `times`回起きたときの分子の数の変化を`counts`に加える。足りない分子は0で止める
+    pub fn apply(&self, counts: &mut [u64], times: u64) {
+        for &(species, n) in &self.reactants {
+            counts[species] = counts[species].saturating_sub(u64::from(n) * times);
+        }
+        for &(species, n) in &self.products {
+            counts[species] += u64::from(n) * times;
+        }
+    }
+
+    // `times`回起きても分子の数が負にならないかどうか
+    fn can_apply(&self, counts: &[u64], times: u64) -> bool {
+        self.reactants
+            .iter()
+            .all(|&(species, n)| counts[species] >= u64::from(n) * times)
+    }
+}
+
+/// 物質と反応の一覧
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::gillespie::ReactionNetwork;
+///
+/// let network = ReactionNetwork::new(&["A", "B", "C"])
+///     .with("2A + B -> C", 0.5)
+///     .unwrap()
+///     .with("C -> 0", 1.0)
+///     .unwrap();
+/// assert_eq!(network.species_index("C"), Some(2));
+/// assert_eq!(network.reactions()[0].reactants, vec![(0, 2), (1, 1)]);
+/// assert!(network.reactions()[1].products.is_empty());
+/// // A3個から2個を選ぶ3通り × B2個 × 0.5
+/// assert_eq!(network.reactions()[0].propensity(&[3, 2, 0]), 3.0);
+///
+/// assert!(ReactionNetwork::new(&["A"]).with("A -> X", 1.0).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReactionNetwork {
+    species: Vec<String>,
+    reactions: Vec<Reaction>,
+}
+
+impl ReactionNetwork {
+    /// 名前が`species`の物質を持ち、反応のないネットワーク
+    pub fn new(species: &[&str]) -> ReactionNetwork {
+        ReactionNetwork {
+            species: species.iter().map(|name| name.to_string()).collect(),
+            reactions: Vec::new(),
+        }
+    }
+
+    /// 反応を加える
+    ///
+    /// # Panics
+    /// 存在しない物質の番号が含まれるとき
+    pub fn reaction(mut self, reactants: &[(usize, u32)], products: &[(usize, u32)], rate: f64) -> ReactionNetwork {
+        assert!(
+            reactants
+                .iter()
+                .chain(products)
+                .all(|&(species, _)| species < self.species.len()),
+            "reaction refers to a species that does not exist"
+        );
+        self.reactions.push(Reaction {
+            reactants: reactants.to_vec(),
+            products: products.to_vec(),
+            rate,
+        });
+        self
+    }
+
+    /// `"2A + B -> C"`のような式で反応を加える。何もないことは`0`か空で表す
+    pub fn with(self, equation: &str, rate: f64) -> Result<ReactionNetwork, failure::Error> {
+        let mut sides = equation.split("->");
+        let (left, right) = match (sides.next(), sides.next(), sides.next()) {
+            (Some(left), Some(right), None) => (left, right),
+            _ => return Err(format_err!("reaction \"{}\" needs exactly one \"->\"", equation)),
+        };
+        let reactants = self.parse_side(left)?;
+        let products = self.parse_side(right)?;
+        Ok(self.reaction(&reactants, &products, rate))
+    }
+
+    fn parse_side(&self, side: &str) -> Result<Vec<(usize, u32)>, failure::Error> {
+        let mut terms = Vec::new();
+        for term in side.split('+').map(str::trim) {
+            if term.is_empty() || term == "0" {
+                continue;
+            }
+            let digits = term.find(|c: char| !c.is_ascii_digit()).unwrap_or(term.len());
+            let n = if digits == 0 {
+                1
+            } else {
+                term[..digits].parse().map_err(|e| format_err!("{}: {}", term, e))?
+            };
+            let name = term[digits..].trim();
+            let species = self
+                .species_index(name)
+                .ok_or_else(|| format_err!("unknown species \"{}\"", name))?;
+            terms.push((species, n));
+        }
+        Ok(terms)
+    }
+
+    /// 物質の名前
+    pub fn species(&self) -> &[String] {
+        &self.species
+    }
+
+    /// 名前が`name`の物質の番号
+    pub fn species_index(&self, name: &str) -> Option<usize> {
+        self.species.iter().position(|species| species == name)
+    }
+
+    /// 反応
+    pub fn reactions(&self) -> &[Reaction] {
+        &self.reactions
+    }
+
+    // 全ての反応のpropensityを`out`に書き込み、合計を返す
+    fn propensities(&self, counts: &[u64], out: &mut Vec<f64>) -> f64 {
+        out.clear();
+        out.extend(self.reactions.iter().map(|reaction| reaction.propensity(counts)));
+        out.iter().sum()
+    }
+}
+
+// 合計が`total`の重み`weights`から、重みに比例する確率で1つ選ぶ
+fn choose_weighted<R: Rng>(weights: &[f64], total: f64, rng: &mut R) -> usize {
+    let mut target = rng.gen::<f64>() * total;
+    for (i, &weight) in weights.iter().enumerate() {
+        if target < weight {
+            return i;
+        }
+        target -= weight;
+    }
+    // 丸め誤差で最後まで来たときは、重みが正の最後のもの
+    weights.iter().rposition(|&weight| weight > 0.0).unwrap_or(0)
+}
+
+// 平均`rate`の指数分布に従う待ち時間
+fn exponential<R: Rng>(rate: f64, rng: &mut R) -> f64 {
+    -(1.0 - rng.gen::<f64>()).ln() / rate
+}
+
+// 平均`mean`のPoisson分布に従う乱数
+fn poisson<R: Rng>(mean: f64, rng: &mut R) -> u64 {
+    if mean <= 0.0 {
+        return 0;
+    }
+    if mean > POISSON_NORMAL_THRESHOLD {
+        return Normal::new(mean, mean.sqrt()).ind_sample(rng).round().max(0.0) as u64;
+    }
+    // Knuthの方法
+    let limit = (-mean).exp();
+    let (mut k, mut p) = (0, 1.0);
+    loop {
+        p *= rng.gen::<f64>();
+        if p <= limit {
+            return k;
+        }
+        k += 1;
+    }
+}
+
+/// よく混ざった(空間のない)反応系の確率的な時間発展
+///
+/// `step`はGillespieの直接法(SSA)で反応を1回ずつ正確に起こし、`tau_leap`は時間`tau`の間に起きる反応の回数をまとめてPoisson分布から決める
+///
+/// # Example
+/// ```
+/// extern crate rand;
+/// extern crate my_alife;
+///
+/// use rand::{SeedableRng, XorShiftRng};
+/// use my_alife::algorithm::gillespie::{Gillespie, ReactionNetwork};
+///
+/// let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+/// let network = ReactionNetwork::new(&["A"]).with("A -> 0", 1.0).unwrap();
+/// let mut ssa = Gillespie::new(network, vec![1000]);
+/// ssa.run_until(1.0, &mut rng);
+/// // 平均は1000 × e^-1 ≒ 368
+/// assert!((ssa.counts()[0] as f64 - 368.0).abs() < 60.0);
+/// assert_eq!(ssa.time(), 1.0);
+///
+/// // 分子がなくなると、それ以上反応は起きない
+/// ssa.run_until(100.0, &mut rng);
+/// assert_eq!(ssa.counts()[0], 0);
+/// assert_eq!(ssa.step(&mut rng), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Gillespie {
+    network: ReactionNetwork,
+    counts: Vec<u64>,
+    time: f64,
+    propensities: Vec<f64>,
+}
+
+impl Gillespie {
+    /// 分子の数`counts`から始める
+    ///
+    /// # Panics
+    /// `counts`の数が物質の数と違うとき
+    pub fn new(network: ReactionNetwork, counts: Vec<u64>) -> Gillespie {
+        assert_eq!(
+            counts.len(),
+            network.species().len(),
+            "one count is needed for each species"
+        );
+        Gillespie {
+            network,
+            counts,
+            time: 0.0,
+            propensities: Vec::new(),
+        }
+    }
+
+    /// 反応のネットワーク
+    pub fn network(&self) -> &ReactionNetwork {
+        &self.network
+    }
+
+    /// 物質ごとの分子の数
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// 物質ごとの分子の数。分子を外から加えるときに使う
+    pub fn counts_mut(&mut self) -> &mut [u64] {
+        &mut self.counts
+    }
+
+    /// 時刻
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// 次の反応を1回起こし、その番号を返す。どの反応も起こりえないときは`None`
+    pub fn step<R: Rng>(&mut self, rng: &mut R) -> Option<usize> {
+        let total = self.network.propensities(&self.counts, &mut self.propensities);
+        if total <= 0.0 {
+            return None;
+        }
+        self.time += exponential(total, rng);
+        let reaction = choose_weighted(&self.propensities, total, rng);
+        self.network.reactions[reaction].apply(&mut self.counts, 1);
+        Some(reaction)
+    }
+
+    /// 時刻`end`まで正確に進め、起きた反応の回数を返す
+    pub fn run_until<R: Rng>(&mut self, end: f64, rng: &mut R) -> usize {
+        let mut fired = 0;
+        loop {
+            let total = self.network.propensities(&self.counts, &mut self.propensities);
+            if total <= 0.0 {
+                break;
+            }
+            let wait = exponential(total, rng);
+            if self.time + wait > end {
+                break;
+            }
+            self.time += wait;
+            let reaction = choose_weighted(&self.propensities, total, rng);
+            self.network.reactions[reaction].apply(&mut self.counts, 1);
+            fired += 1;
+        }
+        self.time = self.time.max(end);
+        fired
+    }
+
+    /// 時間`tau`の間に起きる反応をまとめて起こし、実際に進めた時間を返す
+    ///
+    /// 分子の数が負になるときは、tauを半分にしてやり直す。分子の数が多く、tauの間にpropensityがあまり変わらないときに速い
+    ///
+    /// # Example
+    /// ```
+    /// extern crate rand;
+    /// extern crate my_alife;
+    ///
+    /// use rand::{SeedableRng, XorShiftRng};
+    /// use my_alife::algorithm::gillespie::{Gillespie, ReactionNetwork};
+    ///
+    /// let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    /// // 生成と分解が釣り合う平均は10 / 0.1 = 100
+    /// let network = ReactionNetwork::new(&["A"])
+    ///     .with("0 -> A", 10.0)
+    ///     .unwrap()
+    ///     .with("A -> 0", 0.1)
+    ///     .unwrap();
+    /// let mut leap = Gillespie::new(network, vec![0]);
+    /// while leap.time() < 100.0 {
+    ///     leap.tau_leap(0.1, &mut rng);
+    /// }
+    /// assert!((leap.counts()[0] as f64 - 100.0).abs() < 40.0);
+    /// ```
+    pub fn tau_leap<R: Rng>(&mut self, tau: f64, rng: &mut R) -> f64 {
+        let total = self.network.propensities(&self.counts, &mut self.propensities);
+        if total <= 0.0 {
+            self.time += tau;
+            return tau;
+        }
+        let mut tau = tau;
+        for _ in 0..MAX_LEAP_RETRIES {
+            let firings: Vec<u64> = self.propensities.iter().map(|&a| poisson(a * tau, rng)).collect();
+            let mut next = self.counts.clone();
+            let mut valid = true;
+            for (reaction, &times) in self.network.reactions.iter().zip(&firings) {
+                if !reaction.can_apply(&next, times) {
+                    valid = false;
+                    break;
+                }
+                reaction.apply(&mut next, times);
+            }
+            if valid {
+                self.counts = next;
+                self.time += tau;
+                return tau;
+            }
+            tau /= 2.0;
+        }
+        // やり直しても負になるときは、正確に1回だけ進める
+        let before = self.time;
+        self.step(rng);
+        self.time - before
+    }
+}
+
+/// 格子に分けた小さな体積(subvolume)ごとに反応し、隣の体積に分子が飛び移る反応拡散のマスター方程式(RDME)
+///
+/// 周期境界条件で、各分子は上下左右の隣に、それぞれ拡散係数 / 格子間隔²の速さで飛び移る。
+/// 反応の速度定数は1つの体積の中での値として扱う
+///
+/// # Example
+/// ```
+/// extern crate rand;
+/// extern crate my_alife;
+///
+/// use rand::{SeedableRng, XorShiftRng};
+/// use my_alife::algorithm::gillespie::{ReactionNetwork, SpatialGillespie};
+///
+/// let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+/// let network = ReactionNetwork::new(&["A"]);
+/// let mut rdme = SpatialGillespie::new(network, (9, 9), 1.0, &[1.0]);
+/// rdme.set_count((4, 4), 0, 500);
+/// rdme.run_until(2.0, &mut rng);
+/// // 反応がなければ分子の総数は変わらず、周りに広がる
+/// assert_eq!(rdme.total(0), 500);
+/// assert!(rdme.count((4, 4), 0) < 200);
+/// assert!(rdme.concentration(0)[[4, 5]] > 0.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpatialGillespie {
+    network: ReactionNetwork,
+    dim: (usize, usize),
+    // 物質ごとの、1分子が1方向に飛び移る速さ
+    jump_rates: Vec<f64>,
+    // 体積(行優先)ごとの物質ごとの分子の数
+    counts: Vec<Vec<u64>>,
+    // 体積ごとの、反応と拡散のpropensityの合計
+    totals: Vec<f64>,
+    time: f64,
+    propensities: Vec<f64>,
+}
+
+impl SpatialGillespie {
+    /// 分子のない`dim`の格子
+    ///
+    /// # Arguments
+    /// * `h` - 格子間隔
+    /// * `diffusions` - 物質ごとの拡散係数
+    ///
+    /// # Panics
+    /// `diffusions`の数が物質の数と違うとき
+    pub fn new(network: ReactionNetwork, dim: (usize, usize), h: f64, diffusions: &[f64]) -> SpatialGillespie {
+        let species = network.species().len();
+        assert_eq!(
+            diffusions.len(),
+            species,
+            "one diffusion coefficient is needed for each species"
+        );
+        let cells = dim.0 * dim.1;
+        let mut rdme = SpatialGillespie {
+            network,
+            dim,
+            jump_rates: diffusions.iter().map(|d| d / (h * h)).collect(),
+            counts: vec![vec![0; species]; cells],
+            totals: vec![0.0; cells],
+            time: 0.0,
+            propensities: Vec::new(),
+        };
+        for cell in 0..cells {
+            rdme.update_total(cell);
+        }
+        rdme
+    }
+
+    /// 反応のネットワーク
+    pub fn network(&self) -> &ReactionNetwork {
+        &self.network
+    }
+
+    /// 時刻
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// (row, col)の体積の`species`の分子の数
+    pub fn count(&self, (row, col): (usize, usize), species: usize) -> u64 {
+        self.counts[row * self.dim.1 + col][species]
+    }
+
+    /// (row, col)の体積の`species`の分子の数を設定する
+    pub fn set_count(&mut self, (row, col): (usize, usize), species: usize, count: u64) {
+        let cell = row * self.dim.1 + col;
+        self.counts[cell][species] = count;
+        self.update_total(cell);
+    }
+
+    /// 全ての体積の`species`の分子の数の合計
+    pub fn total(&self, species: usize) -> u64 {
+        self.counts.iter().map(|counts| counts[species]).sum()
+    }
+
+    /// `species`の分子の数の分布。`MatrixVisualizer`で描くときに使う
+    pub fn concentration(&self, species: usize) -> Matrix<f32> {
+        Array2::from_shape_fn(self.dim, |(row, col)| self.count((row, col), species) as f32)
+    }
+
+    fn update_total(&mut self, cell: usize) {
+        let counts = &self.counts[cell];
+        let reactions = self.network.propensities(counts, &mut self.propensities);
+        let diffusion: f64 = counts
+            .iter()
+            .zip(&self.jump_rates)
+            .map(|(&n, &rate)| 4.0 * rate * n as f64)
+            .sum();
+        self.totals[cell] = reactions + diffusion;
+    }
+
+    /// 次の反応か拡散を1回起こす。何も起こりえないときは`false`を返す
+    pub fn step<R: Rng>(&mut self, rng: &mut R) -> bool {
+        let total: f64 = self.totals.iter().sum();
+        if total <= 0.0 {
+            return false;
+        }
+        self.time += exponential(total, rng);
+        self.fire(total, rng);
+        true
+    }
+
+    /// 時刻`end`まで進め、起きた反応と拡散の回数を返す
+    pub fn run_until<R: Rng>(&mut self, end: f64, rng: &mut R) -> usize {
+        let mut fired = 0;
+        loop {
+            let total: f64 = self.totals.iter().sum();
+            if total <= 0.0 {
+                break;
+            }
+            let wait = exponential(total, rng);
+            if self.time + wait > end {
+                break;
+            }
+            self.time += wait;
+            self.fire(total, rng);
+            fired += 1;
+        }
+        self.time = self.time.max(end);
+        fired
+    }
+
+    // propensityに比例する確率で体積を選び、その中の反応か拡散を1回起こす
+    fn fire<R: Rng>(&mut self, total: f64, rng: &mut R) {
+        let cell = choose_weighted(&self.totals, total, rng);
+        let reactions = self.network.propensities(&self.counts[cell], &mut self.propensities);
+        let mut target = rng.gen::<f64>() * self.totals[cell];
+        if target < reactions {
+            let reaction = choose_weighted(&self.propensities, reactions, rng);
+            self.network.reactions[reaction].apply(&mut self.counts[cell], 1);
+            self.update_total(cell);
+            return;
+        }
+        target -= reactions;
+        let species_count = self.jump_rates.len();
+        for species in 0..species_count {
+            let rate = 4.0 * self.jump_rates[species] * self.counts[cell][species] as f64;
+            if target < rate || species == species_count - 1 {
+                if self.counts[cell][species] == 0 {
+                    return;
+                }
+                let neighbor = self.neighbor(cell, rng.gen_range(0, 4));
+                self.counts[cell][species] -= 1;
+                self.counts[neighbor][species] += 1;
+                self.update_total(cell);
+                self.update_total(neighbor);
+                return;
+            }
+            target -= rate;
+        }
+    }
+
+    // 体積`cell`の上下左右(`direction`が0〜3)の隣
+    fn neighbor(&self, cell: usize, direction: usize) -> usize {
+        let (rows, cols) = self.dim;
+        let (row, col) = (cell / cols, cell % cols);
+        let (row, col) = match direction {
+            0 => ((row + rows - 1) % rows, col),
+            1 => ((row + 1) % rows, col),
+            2 => (row, (col + cols - 1) % cols),
+            _ => (row, (col + 1) % cols),
+        };
+        row * cols + col
+    }
+}
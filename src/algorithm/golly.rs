@@ -0,0 +1,880 @@
+use algorithm::neighborhood::Neighborhood;
+use failure;
+use ndarray::Array2;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use visualizer::Matrix;
+
+// Gollyの近傍の並び順(北から時計回り)
+const MOORE: [(isize, isize); 8] = [(-1, 0), (-1, 1), (0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1)];
+const VON_NEUMANN: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+// Macrocellの根のレベルの上限。一辺2^levelの正方形の座標をi64で表せる範囲
+const MAX_MACROCELL_LEVEL: u8 = 62;
+
+// 遷移の1つの項。状態そのものか変数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Term {
+    State(u8),
+    Var(usize),
+}
+
+// `C,N,NE,...,C'`の1行
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Transition {
+    inputs: Vec<Term>,
+    output: Term,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Symmetry {
+    // 近傍の並べ替え方の一覧
+    Permutations(Vec<Vec<usize>>),
+    // 近傍の順番を問わない
+    Permute,
+}
+
+// @TABLE
+#[derive(Debug, Clone)]
+struct Table {
+    vars: Vec<Vec<u8>>,
+    symmetry: Symmetry,
+    transitions: Vec<Transition>,
+    // (中心, 近傍...)から次の状態。同じ組み合わせを調べ直さないように覚えておく
+    cache: RefCell<HashMap<Vec<u8>, u8>>,
+}
+
+impl Table {
+    fn next_state(&self, center: u8, neighbors: &[u8]) -> u8 {
+        let mut key = vec![center];
+        key.extend_from_slice(neighbors);
+        if let Some(&next) = self.cache.borrow().get(&key) {
+            return next;
+        }
+        let next = self
+            .transitions
+            .iter()
+            .filter_map(|transition| self.apply(transition, center, neighbors))
+            .next()
+            .unwrap_or(center);
+        self.cache.borrow_mut().insert(key, next);
+        next
+    }
+
+    fn apply(&self, transition: &Transition, center: u8, neighbors: &[u8]) -> Option<u8> {
+        let mut bindings = vec![None; self.vars.len()];
+        if !self.bind(transition.inputs[0], center, &mut bindings) {
+            return None;
+        }
+        let bound = match self.symmetry {
+            Symmetry::Permutations(ref permutations) => permutations.iter().find_map(|permutation| {
+                let mut bindings = bindings.clone();
+                let all = permutation
+                    .iter()
+                    .enumerate()
+                    .all(|(i, &j)| self.bind(transition.inputs[1 + i], neighbors[j], &mut bindings));
+                if all {
+                    Some(bindings)
+                } else {
+                    None
+                }
+            }),
+            Symmetry::Permute => {
+                let mut used = vec![false; neighbors.len()];
+                if self.bind_any(&transition.inputs[1..], neighbors, &mut used, &mut bindings) {
+                    Some(bindings)
+                } else {
+                    None
+                }
+            }
+        }?;
+        match transition.output {
+            Term::State(state) => Some(state),
+            Term::Var(var) => bound[var],
+        }
+    }
+
+    // 同じ変数は同じ値をとる(bound variables)
+    fn bind(&self, term: Term, value: u8, bindings: &mut [Option<u8>]) -> bool {
+        match term {
+            Term::State(state) => state == value,
+            Term::Var(var) => match bindings[var] {
+                Some(bound) => bound == value,
+                None if self.vars[var].contains(&value) => {
+                    bindings[var] = Some(value);
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+
+    // 近傍の順番を問わずに`terms`を割り当てる
+    fn bind_any(&self, terms: &[Term], neighbors: &[u8], used: &mut [bool], bindings: &mut Vec<Option<u8>>) -> bool {
+        let (first, rest) = match terms.split_first() {
+            Some(split) => split,
+            None => return true,
+        };
+        for j in 0..neighbors.len() {
+            if used[j] {
+                continue;
+            }
+            let mut candidate = bindings.clone();
+            if self.bind(*first, neighbors[j], &mut candidate) {
+                used[j] = true;
+                if self.bind_any(rest, neighbors, used, &mut candidate) {
+                    *bindings = candidate;
+                    return true;
+                }
+                used[j] = false;
+            }
+        }
+        false
+    }
+}
+
+// @TREE。各ノードは状態ごとに次のノード(レベル1では次の状態)を持つ
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Tree {
+    nodes: Vec<Vec<usize>>,
+}
+
+impl Tree {
+    fn next_state(&self, center: u8, neighbors: &[u8]) -> u8 {
+        // Gollyと同じく、Mooreは北西・北東・南西・南東・北・西・東・南・中心、
+        // von Neumannは北・西・東・南・中心の順に木をたどる
+        let order: Vec<u8> = if neighbors.len() == 8 {
+            vec![
+                neighbors[7],
+                neighbors[1],
+                neighbors[5],
+                neighbors[3],
+                neighbors[0],
+                neighbors[6],
+                neighbors[2],
+                neighbors[4],
+                center,
+            ]
+        } else {
+            vec![neighbors[0], neighbors[3], neighbors[1], neighbors[2], center]
+        };
+        let mut node = self.nodes.len() - 1;
+        for (i, &state) in order.iter().enumerate() {
+            let next = self.nodes[node][state as usize];
+            if i + 1 == order.len() {
+                return next as u8;
+            }
+            node = next;
+        }
+        center
+    }
+}
+
+#[derive(Debug, Clone)]
+enum RuleKind {
+    Table(Table),
+    Tree(Tree),
+}
+
+/// Gollyの`.rule`ファイル(@TABLEか@TREE)で定義されるセル・オートマトン(周期境界条件)
+///
+/// 近傍は`Moore`か`vonNeumann`に対応している。近傍の状態はGollyと同じく北から時計回りに並べる
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::arr2;
+/// use my_alife::algorithm::golly::GollyRule;
+///
+/// let rule: GollyRule = "
+/// @RULE WireWorld
+/// @TABLE
+/// n_states:4
+/// neighborhood:Moore
+/// symmetries:permute
+/// var a={0,1,2,3}
+/// var b={0,1,2,3}
+/// var c={0,1,2,3}
+/// var d={0,1,2,3}
+/// var e={0,1,2,3}
+/// var f={0,1,2,3}
+/// var g={0,1,2,3}
+/// var h={0,2,3}
+/// var i={0,2,3}
+/// var j={0,2,3}
+/// var k={0,2,3}
+/// var l={0,2,3}
+/// var m={0,2,3}
+/// 1,a,b,c,d,e,f,g,a,2
+/// 2,a,b,c,d,e,f,g,a,3
+/// 3,1,h,i,j,k,l,m,h,1
+/// 3,1,1,i,j,k,l,m,h,1
+/// "
+/// .parse()
+/// .unwrap();
+/// assert_eq!(rule.name(), "WireWorld");
+/// // 電子の頭(1)は尻尾(2)に、尻尾は導線(3)になり、頭が1つ隣にある導線は頭になる
+/// let wire = arr2(&[[0, 0, 0, 0, 0], [0, 2, 1, 3, 0], [0, 0, 0, 0, 0]]);
+/// assert_eq!(rule.step(&wire), arr2(&[[0, 0, 0, 0, 0], [0, 3, 2, 1, 0], [0, 0, 0, 0, 0]]));
+/// ```
+///
+/// @TREEの場合
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::arr2;
+/// use my_alife::algorithm::golly::GollyRule;
+///
+/// // 北のセルの状態をそのまま写す(von Neumann近傍)
+/// let rule: GollyRule = "
+/// @RULE CopyNorth
+/// @TREE
+/// num_states=2
+/// num_neighbors=4
+/// num_nodes=9
+/// 1 0 0
+/// 1 1 1
+/// 2 0 0
+/// 2 1 1
+/// 3 2 2
+/// 3 3 3
+/// 4 4 4
+/// 4 5 5
+/// 5 6 7
+/// "
+/// .parse()
+/// .unwrap();
+/// let cells = arr2(&[[1, 0], [0, 0], [0, 1]]);
+/// assert_eq!(rule.step(&cells), arr2(&[[0, 1], [1, 0], [0, 0]]));
+/// // 状態は255個まで
+/// assert!("@RULE Big\n@TREE\nnum_states=256\n".parse::<GollyRule>().is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct GollyRule {
+    name: String,
+    num_states: u8,
+    neighborhood: Neighborhood,
+    kind: RuleKind,
+}
+
+impl GollyRule {
+    /// Gollyの`.rule`ファイルを読み込む
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<GollyRule, failure::Error> {
+        let path = path.as_ref();
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|e| format_err!("{}: {}", path.display(), e))
+    }
+
+    /// `@RULE`の名前
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 状態の数
+    pub fn num_states(&self) -> u8 {
+        self.num_states
+    }
+
+    /// 近傍の取り方
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+
+    /// 中心の状態が`center`で、近傍を北から時計回りに並べた状態が`neighbors`のセルの次の状態
+    pub fn next_state(&self, center: u8, neighbors: &[u8]) -> u8 {
+        match self.kind {
+            RuleKind::Table(ref table) => table.next_state(center, neighbors),
+            RuleKind::Tree(ref tree) => tree.next_state(center, neighbors),
+        }
+    }
+
+    /// 1ステップ進める。範囲外の状態はそのまま
+    pub fn step(&self, cells: &Matrix<u8>) -> Matrix<u8> {
+        let (rows, cols) = cells.dim();
+        let offsets: &[(isize, isize)] = match self.neighborhood {
+            Neighborhood::Moore => &MOORE,
+            Neighborhood::VonNeumann => &VON_NEUMANN,
+        };
+        let mut neighbors = vec![0; offsets.len()];
+        let mut next = Array2::zeros((rows, cols));
+        for ((row, col), e) in next.indexed_iter_mut() {
+            let center = cells[[row, col]];
+            if center >= self.num_states {
+                *e = center;
+                continue;
+            }
+            for (neighbor, &(dr, dc)) in neighbors.iter_mut().zip(offsets) {
+                let r = (row as isize + dr).rem_euclid(rows as isize) as usize;
+                let c = (col as isize + dc).rem_euclid(cols as isize) as usize;
+                // 範囲外の状態は0とみなす
+                *neighbor = Some(cells[[r, c]]).filter(|&s| s < self.num_states).unwrap_or(0);
+            }
+            *e = self.next_state(center, &neighbors);
+        }
+        next
+    }
+}
+
+impl FromStr for GollyRule {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<GollyRule, failure::Error> {
+        let mut name = String::new();
+        let mut section = "";
+        let mut table = Vec::new();
+        let mut tree = Vec::new();
+        for (i, line) in s.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.starts_with('@') {
+                let mut parts = trimmed.splitn(2, char::is_whitespace);
+                section = parts.next().unwrap_or("");
+                if section == "@RULE" {
+                    name = parts.next().unwrap_or("").trim().to_string();
+                }
+                continue;
+            }
+            let content = trimmed.split('#').next().unwrap_or("").trim();
+            if content.is_empty() {
+                continue;
+            }
+            match section {
+                "@TABLE" => table.push((i + 1, content)),
+                "@TREE" => tree.push((i + 1, content)),
+                _ => {}
+            }
+        }
+        if !table.is_empty() {
+            parse_table(name, &table)
+        } else if !tree.is_empty() {
+            parse_tree(name, &tree)
+        } else {
+            Err(format_err!("no @TABLE or @TREE section"))
+        }
+    }
+}
+
+fn key_value(line: &str, separator: char) -> Option<(&str, &str)> {
+    let mut parts = line.splitn(2, separator);
+    let key = parts.next()?.trim();
+    let value = parts.next()?.trim();
+    Some((key, value))
+}
+
+fn parse_table(name: String, lines: &[(usize, &str)]) -> Result<GollyRule, failure::Error> {
+    let mut num_states: Option<u8> = None;
+    let mut neighborhood = None;
+    let mut symmetry_name = "none".to_string();
+    let mut var_names: Vec<String> = Vec::new();
+    let mut vars: Vec<Vec<u8>> = Vec::new();
+    let mut transitions = Vec::new();
+    for &(number, line) in lines {
+        let at = |message: String| format_err!("line {}: {}", number, message);
+        if let Some((key, value)) = key_value(line, ':') {
+            match key {
+                "n_states" => num_states = Some(value.parse().map_err(|_| at(format!("invalid n_states {}", value)))?),
+                "neighborhood" => {
+                    neighborhood = Some(match value {
+                        "Moore" => Neighborhood::Moore,
+                        "vonNeumann" => Neighborhood::VonNeumann,
+                        _ => return Err(at(format!("unsupported neighborhood {}", value))),
+                    })
+                }
+                "symmetries" => symmetry_name = value.to_string(),
+                _ => return Err(at(format!("unknown key {}", key))),
+            }
+            continue;
+        }
+        let num_states = num_states.ok_or_else(|| at("n_states must come first".to_string()))?;
+        let neighborhood = neighborhood.ok_or_else(|| at("neighborhood must come first".to_string()))?;
+        let parse_value = |token: &str| -> Result<Vec<u8>, failure::Error> {
+            if let Some(var) = var_names.iter().position(|name| name == token) {
+                return Ok(vars[var].clone());
+            }
+            match token.parse::<u8>() {
+                Ok(state) if state < num_states => Ok(vec![state]),
+                _ => Err(at(format!("unknown state or variable \"{}\"", token))),
+            }
+        };
+        if let Some(rest) = line.strip_prefix("var ") {
+            let (var, values) =
+                key_value(rest, '=').ok_or_else(|| at("var should look like var a={0,1}".to_string()))?;
+            let values = values.trim_start_matches('{').trim_end_matches('}');
+            let mut set = Vec::new();
+            for token in values.split(',').map(str::trim).filter(|token| !token.is_empty()) {
+                for value in parse_value(token)? {
+                    if !set.contains(&value) {
+                        set.push(value);
+                    }
+                }
+            }
+            match var_names.iter().position(|name| name == var) {
+                Some(index) => vars[index] = set,
+                None => {
+                    var_names.push(var.to_string());
+                    vars.push(set);
+                }
+            }
+            continue;
+        }
+        let tokens: Vec<String> = if line.contains(',') {
+            line.split(',').map(|token| token.trim().to_string()).collect()
+        } else if line.contains(char::is_whitespace) {
+            line.split_whitespace().map(str::to_string).collect()
+        } else {
+            line.chars().map(|c| c.to_string()).collect()
+        };
+        let expected = neighborhood.size() + 2;
+        if tokens.len() != expected {
+            return Err(at(format!("expected {} values but found {}", expected, tokens.len())));
+        }
+        let mut terms = Vec::new();
+        for token in &tokens {
+            terms.push(match var_names.iter().position(|name| name == token) {
+                Some(var) => Term::Var(var),
+                None => Term::State(parse_value(token)?[0]),
+            });
+        }
+        let output = terms.pop().unwrap();
+        if let Term::Var(var) = output {
+            if !terms.contains(&output) {
+                return Err(at(format!(
+                    "output variable {} is not bound by the inputs",
+                    var_names[var]
+                )));
+            }
+        }
+        transitions.push(Transition { inputs: terms, output });
+    }
+    let num_states = num_states.ok_or_else(|| format_err!("missing n_states"))?;
+    let neighborhood = neighborhood.ok_or_else(|| format_err!("missing neighborhood"))?;
+    let symmetry = symmetry(&symmetry_name, neighborhood)?;
+    Ok(GollyRule {
+        name,
+        num_states,
+        neighborhood,
+        kind: RuleKind::Table(Table {
+            vars,
+            symmetry,
+            transitions,
+            cache: RefCell::new(HashMap::new()),
+        }),
+    })
+}
+
+// 対称性から近傍の並べ替え方を作る
+fn symmetry(name: &str, neighborhood: Neighborhood) -> Result<Symmetry, failure::Error> {
+    let n = neighborhood.size();
+    let rotate = |k: usize| -> Vec<usize> { (0..n).map(|i| (i + k) % n).collect() };
+    let reflect = |permutation: &Vec<usize>| -> Vec<usize> { (0..n).map(|i| permutation[(n - i) % n]).collect() };
+    // 90度ごとの回転に何個ずらせばよいか
+    let quarter = n / 4;
+    let rotations: Vec<Vec<usize>> = match name {
+        "none" | "reflect_horizontal" => vec![rotate(0)],
+        "rotate4" | "rotate4reflect" => (0..4).map(|k| rotate(k * quarter)).collect(),
+        "rotate8" | "rotate8reflect" if n == 8 => (0..8).map(rotate).collect(),
+        "permute" => return Ok(Symmetry::Permute),
+        _ => return Err(format_err!("unsupported symmetries {} for this neighborhood", name)),
+    };
+    let mut permutations = rotations.clone();
+    if name.ends_with("reflect") || name == "reflect_horizontal" {
+        permutations.extend(rotations.iter().map(reflect));
+    }
+    Ok(Symmetry::Permutations(permutations))
+}
+
+fn parse_tree(name: String, lines: &[(usize, &str)]) -> Result<GollyRule, failure::Error> {
+    let mut num_states = None;
+    let mut num_neighbors = None;
+    let mut num_nodes = None;
+    let mut nodes: Vec<Vec<usize>> = Vec::new();
+    let mut levels = Vec::new();
+    for &(number, line) in lines {
+        let at = |message: String| format_err!("line {}: {}", number, message);
+        if let Some((key, value)) = key_value(line, '=') {
+            let value: usize = value.parse().map_err(|_| at(format!("invalid {} {}", key, value)))?;
+            match key {
+                "num_states" if (1..=255).contains(&value) => num_states = Some(value),
+                "num_neighbors" if value == 4 || value == 8 => num_neighbors = Some(value),
+                "num_nodes" => num_nodes = Some(value),
+                _ => return Err(at(format!("unsupported {}={}", key, value))),
+            }
+            continue;
+        }
+        let states = num_states.ok_or_else(|| at("num_states must come first".to_string()))?;
+        let values: Vec<usize> = line
+            .split_whitespace()
+            .map(|token| token.parse().map_err(|_| at(format!("invalid number {}", token))))
+            .collect::<Result<_, _>>()?;
+        if values.len() != states + 1 {
+            return Err(at(format!("expected a level and {} values", states)));
+        }
+        let level = values[0];
+        for &value in &values[1..] {
+            let valid = if level == 1 {
+                value < states
+            } else {
+                value < nodes.len() && levels[value] + 1 == level
+            };
+            if !valid {
+                return Err(at(format!("invalid value {} for a node of level {}", value, level)));
+            }
+        }
+        levels.push(level);
+        nodes.push(values[1..].to_vec());
+    }
+    let num_states = num_states.ok_or_else(|| format_err!("missing num_states"))?;
+    let num_neighbors = num_neighbors.ok_or_else(|| format_err!("missing num_neighbors"))?;
+    if num_nodes.is_some_and(|n| n != nodes.len()) {
+        return Err(format_err!(
+            "num_nodes is {} but {} nodes are given",
+            num_nodes.unwrap(),
+            nodes.len()
+        ));
+    }
+    if levels.last() != Some(&(num_neighbors + 1)) {
+        return Err(format_err!(
+            "the last node should be the root of level {}",
+            num_neighbors + 1
+        ));
+    }
+    Ok(GollyRule {
+        name,
+        num_states: num_states as u8,
+        neighborhood: if num_neighbors == 8 {
+            Neighborhood::Moore
+        } else {
+            Neighborhood::VonNeumann
+        },
+        kind: RuleKind::Tree(Tree { nodes }),
+    })
+}
+
+/// Macrocellの4分木のノード
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum MacroNode {
+    /// 大きさ1のセル
+    Cell(u8),
+    /// レベルと(北西, 北東, 南西, 南東)。0はすべて状態0のノード
+    Branch(u8, [usize; 4]),
+}
+
+/// Gollyのmacrocell形式(`.mc`)のパターン
+///
+/// 4分木をそのまま読むので、巨大でも繰り返しの多いパターンを小さなメモリで扱える。
+/// 座標は(x: 列, y: 行)で、Gollyと同じく根の正方形の中心が原点になる
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::golly::Macrocell;
+/// use my_alife::algorithm::hashlife::HashLife;
+///
+/// let glider: Macrocell = "[M2] (golly 3.0)\n#R B3/S23\n#G 12\n.*$..*$***$\n4 0 1 0 0\n".parse().unwrap();
+/// assert_eq!(glider.rule(), Some("B3/S23"));
+/// assert_eq!(glider.population(), 5);
+/// // 北東の8x8の左上は(0, -8)
+/// assert_eq!(glider.bounds(), Some((0, -8, 3, 3)));
+/// assert_eq!(glider.viewport(0, -8, 3, 3).row(2).to_vec(), vec![1, 1, 1]);
+///
+/// let mut life = HashLife::from_macrocell(&glider).unwrap();
+/// assert_eq!(life.generation(), 12);
+/// assert!(life.get_cell(1, -8));
+/// life.step(4).unwrap();
+/// assert!(life.get_cell(2, -7));
+///
+/// // 座標をi64で表せるのはレベル62まで
+/// assert!("[M2]\n62 0 0 0 0\n".parse::<Macrocell>().is_ok());
+/// assert!("[M2]\n63 0 0 0 0\n".parse::<Macrocell>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Macrocell {
+    rule: Option<String>,
+    generation: u64,
+    // 0番目は使わない(すべて状態0のノードを表す)
+    nodes: Vec<MacroNode>,
+    root: usize,
+    level: u8,
+}
+
+impl Macrocell {
+    /// Gollyのマクロセル(`.mc`)ファイルを読み込む
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Macrocell, failure::Error> {
+        let path = path.as_ref();
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|e| format_err!("{}: {}", path.display(), e))
+    }
+
+    /// `#R`の規則
+    pub fn rule(&self) -> Option<&str> {
+        self.rule.as_deref()
+    }
+
+    /// `#G`の世代数
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// 根のレベル。一辺は2^level
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    pub(crate) fn root(&self) -> usize {
+        self.root
+    }
+
+    pub(crate) fn node(&self, id: usize) -> MacroNode {
+        self.nodes[id]
+    }
+
+    /// 使われている最大の状態+1(2以上)
+    pub fn num_states(&self) -> u8 {
+        self.nodes
+            .iter()
+            .skip(1)
+            .filter_map(|node| match *node {
+                MacroNode::Cell(state) => Some(state),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(1)
+            .max(1)
+            + 1
+    }
+
+    /// 状態が0でないセルの数
+    pub fn population(&self) -> u64 {
+        let mut memo = vec![None; self.nodes.len()];
+        self.count(self.root, &mut memo)
+    }
+
+    fn count(&self, id: usize, memo: &mut Vec<Option<u64>>) -> u64 {
+        if id == 0 {
+            return 0;
+        }
+        if let Some(count) = memo[id] {
+            return count;
+        }
+        let count = match self.nodes[id] {
+            MacroNode::Cell(_) => 1,
+            MacroNode::Branch(_, children) => children.iter().map(|&child| self.count(child, memo)).sum(),
+        };
+        memo[id] = Some(count);
+        count
+    }
+
+    /// 状態が0でないセルをすべて含む最小の長方形(x, y, 幅, 高さ)。空ならば`None`
+    pub fn bounds(&self) -> Option<(i64, i64, u64, u64)> {
+        let half = 1i64 << (self.level - 1);
+        let mut bounds = None;
+        self.extend_bounds(self.root, self.level, -half, -half, &mut bounds);
+        bounds.map(|(left, top, right, bottom)| (left, top, (right - left + 1) as u64, (bottom - top + 1) as u64))
+    }
+
+    fn extend_bounds(&self, id: usize, level: u8, x: i64, y: i64, bounds: &mut Option<(i64, i64, i64, i64)>) {
+        if id == 0 {
+            return;
+        }
+        let size = 1i64 << level;
+        // すでに含まれている範囲の中ならば調べなくてよい
+        if let Some((left, top, right, bottom)) = *bounds {
+            if left <= x && top <= y && x + size - 1 <= right && y + size - 1 <= bottom {
+                return;
+            }
+        }
+        match self.nodes[id] {
+            MacroNode::Cell(_) => {
+                *bounds = Some(match *bounds {
+                    Some((left, top, right, bottom)) => (left.min(x), top.min(y), right.max(x), bottom.max(y)),
+                    None => (x, y, x, y),
+                });
+            }
+            MacroNode::Branch(level, children) => {
+                let half = 1i64 << (level - 1);
+                for (k, &child) in children.iter().enumerate() {
+                    self.extend_bounds(
+                        child,
+                        level - 1,
+                        x + half * (k % 2) as i64,
+                        y + half * (k / 2) as i64,
+                        bounds,
+                    );
+                }
+            }
+        }
+    }
+
+    /// 左上が(x, y)で`width`x`height`の範囲を切り出す
+    pub fn viewport(&self, x: i64, y: i64, width: usize, height: usize) -> Matrix<u8> {
+        let mut view = Array2::zeros((height, width));
+        let half = 1i64 << (self.level - 1);
+        self.fill(self.root, self.level, -half, -half, &mut view, x, y);
+        view
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn fill(&self, id: usize, level: u8, node_x: i64, node_y: i64, view: &mut Matrix<u8>, x: i64, y: i64) {
+        let size = 1i64 << level;
+        let (height, width) = view.dim();
+        if id == 0
+            || node_x >= x + width as i64
+            || node_y >= y + height as i64
+            || node_x + size <= x
+            || node_y + size <= y
+        {
+            return;
+        }
+        match self.nodes[id] {
+            MacroNode::Cell(state) => view[[(node_y - y) as usize, (node_x - x) as usize]] = state,
+            MacroNode::Branch(level, children) => {
+                let half = size / 2;
+                for (k, &child) in children.iter().enumerate() {
+                    let (cx, cy) = (node_x + half * (k % 2) as i64, node_y + half * (k / 2) as i64);
+                    self.fill(child, level - 1, cx, cy, view, x, y);
+                }
+            }
+        }
+    }
+}
+
+// 同じ内容のノードを1つにまとめながら4分木を作る
+struct MacroBuilder {
+    nodes: Vec<MacroNode>,
+    index: HashMap<MacroNode, usize>,
+}
+
+impl MacroBuilder {
+    fn intern(&mut self, node: MacroNode) -> usize {
+        match node {
+            MacroNode::Cell(0) | MacroNode::Branch(_, [0, 0, 0, 0]) => return 0,
+            _ => {}
+        }
+        if let Some(&id) = self.index.get(&node) {
+            return id;
+        }
+        self.nodes.push(node);
+        self.index.insert(node, self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    // 8x8の2状態のビットマップのうち、左上が(x, y)で一辺2^levelの部分
+    fn bitmap(&mut self, rows: &[[u8; 8]; 8], level: u8, x: usize, y: usize) -> usize {
+        if level == 0 {
+            return self.intern(MacroNode::Cell(rows[y][x]));
+        }
+        let half = 1 << (level - 1);
+        let nw = self.bitmap(rows, level - 1, x, y);
+        let ne = self.bitmap(rows, level - 1, x + half, y);
+        let sw = self.bitmap(rows, level - 1, x, y + half);
+        let se = self.bitmap(rows, level - 1, x + half, y + half);
+        self.intern(MacroNode::Branch(level, [nw, ne, sw, se]))
+    }
+}
+
+impl FromStr for Macrocell {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Macrocell, failure::Error> {
+        let mut lines = s.lines().enumerate();
+        match lines.next() {
+            Some((_, header)) if header.starts_with("[M2]") => {}
+            _ => return Err(format_err!("not a macrocell file (missing [M2] header)")),
+        }
+        let mut builder = MacroBuilder {
+            nodes: vec![MacroNode::Cell(0)],
+            index: HashMap::new(),
+        };
+        let mut rule = None;
+        let mut generation = 0;
+        // ファイルの中のノード番号(1から)から(作ったノード, レベル)
+        let mut ids: Vec<(usize, u8)> = vec![(0, 0)];
+        for (i, line) in lines {
+            let at = |message: String| format_err!("line {}: {}", i + 1, message);
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(comment) = line.strip_prefix('#') {
+                if let Some(value) = comment.strip_prefix('R') {
+                    rule = Some(value.trim().to_string());
+                } else if let Some(value) = comment.strip_prefix('G') {
+                    generation = value
+                        .trim()
+                        .parse()
+                        .map_err(|_| at(format!("invalid generation {}", value)))?;
+                }
+                continue;
+            }
+            if line.starts_with(['.', '*', '$']) {
+                let mut rows = [[0u8; 8]; 8];
+                let (mut x, mut y) = (0, 0);
+                for c in line.chars() {
+                    match c {
+                        '$' => {
+                            x = 0;
+                            y += 1;
+                        }
+                        '.' | '*' => {
+                            if x >= 8 || y >= 8 {
+                                return Err(at("leaf is larger than 8x8".to_string()));
+                            }
+                            rows[y][x] = (c == '*') as u8;
+                            x += 1;
+                        }
+                        _ => return Err(at(format!("unexpected character '{}' in a leaf", c))),
+                    }
+                }
+                ids.push((builder.bitmap(&rows, 3, 0, 0), 3));
+                continue;
+            }
+            let values: Vec<usize> = line
+                .split_whitespace()
+                .map(|token| token.parse().map_err(|_| at(format!("invalid number {}", token))))
+                .collect::<Result<_, _>>()?;
+            if values.len() != 5 || values[0] == 0 {
+                return Err(at("a node should be \"level nw ne sw se\"".to_string()));
+            }
+            if values[0] > MAX_MACROCELL_LEVEL as usize {
+                return Err(at(format!(
+                    "level {} is too large (at most {})",
+                    values[0], MAX_MACROCELL_LEVEL
+                )));
+            }
+            let level = values[0] as u8;
+            let mut children = [0; 4];
+            for (child, &value) in children.iter_mut().zip(&values[1..]) {
+                *child = if level == 1 {
+                    if value > 255 {
+                        return Err(at(format!("invalid state {}", value)));
+                    }
+                    builder.intern(MacroNode::Cell(value as u8))
+                } else {
+                    match ids.get(value) {
+                        Some(&(_, _)) if value == 0 => 0,
+                        Some(&(id, child_level)) if child_level + 1 == level => id,
+                        _ => return Err(at(format!("invalid node {} for level {}", value, level))),
+                    }
+                };
+            }
+            ids.push((builder.intern(MacroNode::Branch(level, children)), level));
+        }
+        let &(root, level) = ids
+            .last()
+            .filter(|_| ids.len() > 1)
+            .ok_or_else(|| format_err!("no nodes"))?;
+        Ok(Macrocell {
+            rule,
+            generation,
+            nodes: builder.nodes,
+            root,
+            level,
+        })
+    }
+}
@@ -0,0 +1,410 @@
+use algorithm::backend::{Backend, Stepper};
+use algorithm::gray_scott::{GrayScott, DT, VISUALIZATION_STEP};
+use algorithm::lenia::{GrowthFunction, Lenia};
+use algorithm::reaction_diffusion::ReactionDiffusion;
+use failure;
+use glium::backend::Facade;
+use glium::program::ComputeShader;
+use glium::uniforms::UniformBuffer;
+use ndarray::Array2;
+use visualizer::Matrix;
+
+// compute shaderのワークグループの大きさ
+const LOCAL_SIZE: u32 = 16;
+
+const GRAY_SCOTT_SHADER: &str = r#"
+    #version 430
+    layout(local_size_x = 16, local_size_y = 16) in;
+
+    layout(std430) buffer UIn { float u_in[]; };
+    layout(std430) buffer VIn { float v_in[]; };
+    layout(std430) buffer UOut { float u_out[]; };
+    layout(std430) buffer VOut { float v_out[]; };
+
+    uniform uint width;
+    uniform uint height;
+    uniform float f;
+    uniform float k;
+    uniform float du;
+    uniform float dv;
+    uniform float dx;
+    uniform float dt;
+
+    uint index(uint x, uint y) {
+        return (y % height) * width + (x % width);
+    }
+
+    void main() {
+        uint x = gl_GlobalInvocationID.x;
+        uint y = gl_GlobalInvocationID.y;
+        if (x >= width || y >= height) {
+            return;
+        }
+        uint i = index(x, y);
+        float u = u_in[i];
+        float v = v_in[i];
+
+        // 周期境界条件でのラプラシアン
+        uint l = index(x + width - 1, y);
+        uint r = index(x + 1, y);
+        uint t = index(x, y + height - 1);
+        uint b = index(x, y + 1);
+        float laplacian_u = (u_in[l] + u_in[r] + u_in[t] + u_in[b] - 4.0 * u) / (dx * dx);
+        float laplacian_v = (v_in[l] + v_in[r] + v_in[t] + v_in[b] - 4.0 * v) / (dx * dx);
+
+        // Gray-Scottモデル方程式
+        float uvv = u * v * v;
+        u_out[i] = u + dt * (du * laplacian_u - uvv + f * (1.0 - u));
+        v_out[i] = v + dt * (dv * laplacian_v + uvv - (f + k) * v);
+    }
+"#;
+
+const LENIA_SHADER: &str = r#"
+    #version 430
+    layout(local_size_x = 16, local_size_y = 16) in;
+
+    layout(std430) buffer CellsIn { float cells_in[]; };
+    layout(std430) buffer CellsOut { float cells_out[]; };
+    // カーネルごとの、合計が1の(2R+1)x(2R+1)の重み
+    layout(std430) buffer Weights { float weights[]; };
+    // カーネルごとの(m, s, 重みの合計で割ったh, 成長関数の番号)
+    layout(std430) buffer Kernels { vec4 kernels[]; };
+
+    uniform int width;
+    uniform int height;
+    uniform int radius;
+    uniform int num_kernels;
+    uniform float dt;
+
+    int index(int x, int y) {
+        return ((y % height + height) % height) * width + ((x % width + width) % width);
+    }
+
+    float growth(float u, vec4 kernel) {
+        float d = u - kernel.x;
+        float s = kernel.y;
+        if (kernel.w < 0.5) {
+            return 2.0 * pow(max(0.0, 1.0 - d * d / (9.0 * s * s)), 4.0) - 1.0;
+        } else if (kernel.w < 1.5) {
+            return 2.0 * exp(-d * d / (2.0 * s * s)) - 1.0;
+        }
+        return abs(d) <= s ? 1.0 : -1.0;
+    }
+
+    void main() {
+        int x = int(gl_GlobalInvocationID.x);
+        int y = int(gl_GlobalInvocationID.y);
+        if (x >= width || y >= height) {
+            return;
+        }
+        int size = 2 * radius + 1;
+        float g = 0.0;
+        for (int n = 0; n < num_kernels; n++) {
+            float potential = 0.0;
+            for (int dy = -radius; dy <= radius; dy++) {
+                for (int dx = -radius; dx <= radius; dx++) {
+                    float w = weights[(n * size + dy + radius) * size + dx + radius];
+                    if (w > 0.0) {
+                        potential += w * cells_in[index(x + dx, y + dy)];
+                    }
+                }
+            }
+            g += kernels[n].z * growth(potential, kernels[n]);
+        }
+        int i = index(x, y);
+        cells_out[i] = clamp(cells_in[i] + dt * g, 0.0, 1.0);
+    }
+"#;
+
+// 計算するモデルと、そのモデルだけが使うバッファ
+enum GpuModel {
+    GrayScott {
+        model: GrayScott,
+        v: [UniformBuffer<[f32]>; 2],
+    },
+    Lenia {
+        lenia: Lenia,
+        weights: UniformBuffer<[f32]>,
+        kernels: UniformBuffer<[f32]>,
+    },
+}
+
+/// compute shader(OpenGL 4.3以上)を使って、状態をGPU上に置いたまま計算する
+///
+/// 状態はSSBOに置かれ、`snapshot`を呼んだときだけCPU側に読み戻される。
+/// Gray-ScottモデルとLeniaを計算できる
+pub struct GpuStepper {
+    shader: ComputeShader,
+    // Gray-Scottモデルのu、Leniaのセル
+    u: [UniformBuffer<[f32]>; 2],
+    // 最新の状態が入っているバッファの添字
+    current: usize,
+    model: GpuModel,
+    dim: (usize, usize),
+    snapshot: Matrix<f32>,
+}
+
+impl GpuStepper {
+    /// compute shaderが使えるかどうか
+    pub fn is_supported<F: Facade>(facade: &F) -> bool {
+        ComputeShader::is_supported(facade.get_context())
+    }
+
+    /// Gray-Scottモデルを計算するGpuStepperを生成する
+    ///
+    /// # Arguments
+    /// * `facade` - OpenGLのコンテキスト。`MatrixVisualizer::display`で取得できる
+    /// * `uv` - 初期状態。uとvは同じ大きさでなければならない
+    /// * `model` - モデルのパラメータ。`du`と`dv`が使われ、`f`と`k`は`update`で上書きされる
+    ///
+    /// # Example
+    /// ```no_run
+    /// extern crate my_alife;
+    ///
+    /// use my_alife::algorithm::gpu::GpuStepper;
+    /// use my_alife::algorithm::gray_scott::{initial_matrix, GrayScott};
+    /// use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+    ///
+    /// let matrix = MatrixVisualizer::new(
+    ///     "Gray Scott",
+    ///     "res/shaders/matrix_visualizer_vertex.glsl",
+    ///     "res/shaders/matrix_visualizer_fragment.glsl",
+    /// ).unwrap();
+    /// let stepper = GpuStepper::gray_scott(matrix.display(), &initial_matrix(), GrayScott::new(0.04, 0.06)).unwrap();
+    /// matrix.draw_loop(stepper, 0.04, 0.06, |stepper, f, k| stepper.update(f, k).unwrap()).unwrap();
+    /// ```
+    pub fn gray_scott<F: Facade>(
+        facade: &F,
+        uv: &(Matrix<f32>, Matrix<f32>),
+        model: GrayScott,
+    ) -> Result<GpuStepper, failure::Error> {
+        if uv.0.dim() != uv.1.dim() {
+            return Err(format_err!(
+                "u and v must have the same shape, got {:?} and {:?}",
+                uv.0.dim(),
+                uv.1.dim()
+            ));
+        }
+        let shader = compile(facade, GRAY_SCOTT_SHADER)?;
+        let v = [state_buffer(facade, &uv.1)?, state_buffer(facade, &uv.1)?];
+        Ok(GpuStepper {
+            shader,
+            u: [state_buffer(facade, &uv.0)?, state_buffer(facade, &uv.0)?],
+            current: 0,
+            model: GpuModel::GrayScott { model, v },
+            dim: uv.0.dim(),
+            snapshot: uv.0.clone(),
+        })
+    }
+
+    /// Leniaを計算するGpuStepperを生成する。カーネルはCPUで`Lenia::kernel_matrix`と同じものを作って転送する
+    ///
+    /// # Arguments
+    /// * `facade` - OpenGLのコンテキスト。`MatrixVisualizer::display`で取得できる
+    /// * `cells` - 初期状態
+    /// * `lenia` - 規則
+    ///
+    /// # Example
+    /// ```no_run
+    /// #[macro_use(s)]
+    /// extern crate ndarray;
+    /// extern crate my_alife;
+    ///
+    /// use my_alife::algorithm::gpu::GpuStepper;
+    /// use my_alife::algorithm::lenia::Animal;
+    /// use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+    /// use ndarray::Array2;
+    ///
+    /// # fn main() {
+    /// let matrix = MatrixVisualizer::builder("Lenia").build().unwrap();
+    /// let orbium = Animal::orbium();
+    /// let mut cells = Array2::zeros((256, 256));
+    /// cells.slice_mut(s![118..138, 118..138]).assign(&orbium.cells);
+    /// let stepper = GpuStepper::lenia(matrix.display(), &cells, orbium.lenia).unwrap();
+    /// matrix.draw_loop(stepper, 0.0, 0.0, |stepper, f, k| stepper.update(f, k).unwrap()).unwrap();
+    /// # }
+    /// ```
+    pub fn lenia<F: Facade>(facade: &F, cells: &Matrix<f32>, lenia: Lenia) -> Result<GpuStepper, failure::Error> {
+        if lenia.kernels.is_empty() {
+            return Err(format_err!("Lenia needs at least one kernel"));
+        }
+        let shader = compile(facade, LENIA_SHADER)?;
+        let weights: Vec<f32> = (0..lenia.kernels.len())
+            .flat_map(|index| lenia.kernel_matrix(index).into_iter().cloned().collect::<Vec<_>>())
+            .collect();
+        let weights_buffer = UniformBuffer::empty_unsized(facade, weights.len() * 4)?;
+        weights_buffer.write(&weights[..]);
+        let kernels = UniformBuffer::empty_unsized(facade, lenia.kernels.len() * 16)?;
+        kernels.write(&kernel_parameters(&lenia)[..]);
+        Ok(GpuStepper {
+            shader,
+            u: [state_buffer(facade, cells)?, state_buffer(facade, cells)?],
+            current: 0,
+            model: GpuModel::Lenia {
+                lenia,
+                weights: weights_buffer,
+                kernels,
+            },
+            dim: cells.dim(),
+            snapshot: cells.clone(),
+        })
+    }
+
+    /// `steps`回だけ時間`dt`ずつ進める。CPU側への読み戻しは行わない
+    ///
+    /// Leniaでは`f`と`k`は使わない
+    pub fn step(&mut self, f: f32, k: f32, steps: usize, dt: f32) {
+        let (height, width) = self.dim;
+        let groups_x = (width as u32).div_ceil(LOCAL_SIZE);
+        let groups_y = (height as u32).div_ceil(LOCAL_SIZE);
+        for _ in 0..steps {
+            let next = 1 - self.current;
+            match self.model {
+                GpuModel::GrayScott { ref mut model, ref v } => {
+                    model.f = f;
+                    model.k = k;
+                    self.shader.execute(
+                        uniform! {
+                            UIn: &*self.u[self.current],
+                            VIn: &*v[self.current],
+                            UOut: &*self.u[next],
+                            VOut: &*v[next],
+                            width: width as u32,
+                            height: height as u32,
+                            f: model.f,
+                            k: model.k,
+                            du: model.du,
+                            dv: model.dv,
+                            dx: model.dx(),
+                            dt: dt,
+                        },
+                        groups_x,
+                        groups_y,
+                        1,
+                    );
+                }
+                GpuModel::Lenia {
+                    ref lenia,
+                    ref weights,
+                    ref kernels,
+                } => self.shader.execute(
+                    uniform! {
+                        CellsIn: &*self.u[self.current],
+                        CellsOut: &*self.u[next],
+                        Weights: &**weights,
+                        Kernels: &**kernels,
+                        width: width as i32,
+                        height: height as i32,
+                        radius: lenia.radius as i32,
+                        num_kernels: lenia.kernels.len() as i32,
+                        dt: dt,
+                    },
+                    groups_x,
+                    groups_y,
+                    1,
+                ),
+            }
+            self.current = next;
+        }
+    }
+
+    /// 描画1フレーム分だけ進め、uを読み戻して返す。`draw_loop`にそのまま渡せる
+    ///
+    /// Gray-Scottモデルは`gray_scott::laplacian`と同じだけ進め、Leniaは1ステップ(1/T)進める
+    pub fn update(&mut self, f: f32, k: f32) -> Result<&Matrix<f32>, failure::Error> {
+        match self.model {
+            GpuModel::GrayScott { .. } => self.step(f, k, VISUALIZATION_STEP, DT as f32),
+            GpuModel::Lenia { ref lenia, .. } => {
+                let dt = 1.0 / lenia.time;
+                self.step(f, k, 1, dt)
+            }
+        }
+        self.snapshot()
+    }
+
+    /// uかLeniaのセルをCPU側に読み戻して返す
+    pub fn snapshot(&mut self) -> Result<&Matrix<f32>, failure::Error> {
+        self.snapshot = Array2::from_shape_vec(self.dim, self.u[self.current].read()?)?;
+        Ok(&self.snapshot)
+    }
+
+    /// Gray-Scottモデルのuとvの両方をCPU側に読み戻して返す。Leniaではエラーになる
+    pub fn fields(&self) -> Result<(Matrix<f32>, Matrix<f32>), failure::Error> {
+        match self.model {
+            GpuModel::GrayScott { ref v, .. } => {
+                let u = Array2::from_shape_vec(self.dim, self.u[self.current].read()?)?;
+                let v = Array2::from_shape_vec(self.dim, v[self.current].read()?)?;
+                Ok((u, v))
+            }
+            GpuModel::Lenia { .. } => Err(format_err!("Lenia has only one field")),
+        }
+    }
+}
+
+fn compile<F: Facade>(facade: &F, source: &str) -> Result<ComputeShader, failure::Error> {
+    if !GpuStepper::is_supported(facade) {
+        return Err(format_err!(
+            "compute shaders are not supported (OpenGL 4.3 is required)"
+        ));
+    }
+    Ok(ComputeShader::from_source(facade, source)?)
+}
+
+// `matrix`を書き込んだ状態のバッファ
+fn state_buffer<F: Facade>(facade: &F, matrix: &Matrix<f32>) -> Result<UniformBuffer<[f32]>, failure::Error> {
+    let buffer = UniformBuffer::empty_unsized(facade, matrix.len() * 4)?;
+    buffer.write(&matrix.iter().cloned().collect::<Vec<_>>()[..]);
+    Ok(buffer)
+}
+
+// シェーダーの`kernels`に渡す、カーネルごとの(m, s, 重みの合計で割ったh, 成長関数の番号)
+fn kernel_parameters(lenia: &Lenia) -> Vec<f32> {
+    let total: f32 = lenia.kernels.iter().map(|kernel| kernel.weight).sum();
+    lenia
+        .kernels
+        .iter()
+        .flat_map(|kernel| {
+            let growth = match kernel.growth {
+                GrowthFunction::Polynomial => 0.0,
+                GrowthFunction::Exponential => 1.0,
+                GrowthFunction::Step => 2.0,
+            };
+            vec![kernel.mu, kernel.sigma, kernel.weight / total.max(f32::EPSILON), growth]
+        })
+        .collect()
+}
+
+impl Stepper for GpuStepper {
+    type Cell = f32;
+
+    fn backend(&self) -> Backend {
+        Backend::Gpu
+    }
+
+    fn step(&mut self, steps: usize) -> Result<(), failure::Error> {
+        let _span = trace_span!("step", backend = %Backend::Gpu, steps);
+        let (f, k, dt) = match self.model {
+            GpuModel::GrayScott { ref model, .. } => (model.f, model.k, DT as f32),
+            GpuModel::Lenia { ref lenia, .. } => (0.0, 0.0, 1.0 / lenia.time),
+        };
+        GpuStepper::step(self, f, k, steps, dt);
+        Ok(())
+    }
+
+    fn snapshot(&mut self) -> Result<&Matrix<f32>, failure::Error> {
+        GpuStepper::snapshot(self)
+    }
+
+    /// Gray-Scottモデルは`f`, `k`, `du`, `dv`、Leniaは`T`(時間の分解能)を変えられる
+    fn set_parameter(&mut self, name: &str, value: f32) -> bool {
+        match (&mut self.model, name) {
+            (&mut GpuModel::GrayScott { ref mut model, .. }, "f") => model.f = value,
+            (&mut GpuModel::GrayScott { ref mut model, .. }, "k") => model.k = value,
+            (&mut GpuModel::GrayScott { ref mut model, .. }, "du") => model.du = value,
+            (&mut GpuModel::GrayScott { ref mut model, .. }, "dv") => model.dv = value,
+            (&mut GpuModel::Lenia { ref mut lenia, .. }, "T") if value > 0.0 => lenia.time = value,
+            _ => return false,
+        }
+        true
+    }
+}
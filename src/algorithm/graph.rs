@@ -0,0 +1,632 @@
+use algorithm::neighborhood::Neighborhood;
+use rand::Rng;
+
+/// ネットワーク上の感染症で、感受性(まだ感染していない)
+pub const SUSCEPTIBLE: u8 = 0;
+/// ネットワーク上の感染症で、感染している
+pub const INFECTED: u8 = 1;
+/// ネットワーク上の感染症で、回復して免疫を持っている
+pub const RECOVERED: u8 = 2;
+
+/// 囚人のジレンマなどの2人ゲームで、協力する戦略
+pub const COOPERATE: u8 = 0;
+/// 囚人のジレンマなどの2人ゲームで、裏切る戦略
+pub const DEFECT: u8 = 1;
+
+/// 自己ループと多重辺のない無向グラフ。頂点は0から`len() - 1`の番号で表す
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::graph::Graph;
+///
+/// let mut graph = Graph::new(4);
+/// assert!(graph.add_edge(0, 1));
+/// assert!(graph.add_edge(1, 2));
+/// // 同じ辺と自己ループは加えない
+/// assert!(!graph.add_edge(2, 1));
+/// assert!(!graph.add_edge(3, 3));
+/// assert_eq!(graph.edge_count(), 2);
+/// assert_eq!(graph.neighbors(1), &[0, 2]);
+/// assert_eq!(graph.degree(3), 0);
+/// assert_eq!(graph.edges(), vec![(0, 1), (1, 2)]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Graph {
+    adjacency: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    /// `nodes`個の頂点を持ち、辺のないグラフ
+    pub fn new(nodes: usize) -> Graph {
+        Graph {
+            adjacency: vec![Vec::new(); nodes],
+        }
+    }
+
+    /// 周期境界条件の格子。(row, col)のセルが`row * cols + col`番の頂点になる
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::algorithm::graph::Graph;
+    /// use my_alife::algorithm::neighborhood::Neighborhood;
+    ///
+    /// let graph = Graph::lattice((4, 5), Neighborhood::Moore);
+    /// assert_eq!(graph.len(), 20);
+    /// assert!((0..20).all(|node| graph.degree(node) == 8));
+    /// ```
+    pub fn lattice(dim: (usize, usize), neighborhood: Neighborhood) -> Graph {
+        let (rows, cols) = dim;
+        let mut graph = Graph::new(rows * cols);
+        for row in 0..rows {
+            for col in 0..cols {
+                for &(dr, dc) in neighborhood.offsets() {
+                    let r = (row as isize + dr).rem_euclid(rows as isize) as usize;
+                    let c = (col as isize + dc).rem_euclid(cols as isize) as usize;
+                    graph.add_edge(row * cols + col, r * cols + c);
+                }
+            }
+        }
+        graph
+    }
+
+    /// 各頂点を両隣の`k / 2`個ずつの頂点と結んだ輪
+    pub fn ring(nodes: usize, k: usize) -> Graph {
+        let mut graph = Graph::new(nodes);
+        for node in 0..nodes {
+            for offset in 1..=k / 2 {
+                graph.add_edge(node, (node + offset) % nodes);
+            }
+        }
+        graph
+    }
+
+    /// Erdős–Rényiのランダムグラフ。全ての頂点の組を確率`p`で結ぶ
+    ///
+    /// # Example
+    /// ```
+    /// extern crate rand;
+    /// extern crate my_alife;
+    ///
+    /// use rand::{SeedableRng, XorShiftRng};
+    /// use my_alife::algorithm::graph::Graph;
+    ///
+    /// let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    /// let graph = Graph::erdos_renyi(200, 0.05, &mut rng);
+    /// // 平均次数はおよそ(n - 1)p = 9.95
+    /// assert!((graph.mean_degree() - 9.95).abs() < 1.0);
+    /// ```
+    pub fn erdos_renyi<R: Rng>(nodes: usize, p: f32, rng: &mut R) -> Graph {
+        let mut graph = Graph::new(nodes);
+        for a in 0..nodes {
+            for b in (a + 1)..nodes {
+                if rng.gen::<f32>() < p {
+                    graph.add_edge(a, b);
+                }
+            }
+        }
+        graph
+    }
+
+    /// Barabási–Albertのスケールフリー・ネットワーク
+    ///
+    /// `m + 1`個の頂点の完全グラフから始め、次数に比例する確率で選んだ`m`個の頂点と結んだ頂点を1つずつ加える
+    ///
+    /// # Example
+    /// ```
+    /// extern crate rand;
+    /// extern crate my_alife;
+    ///
+    /// use rand::{SeedableRng, XorShiftRng};
+    /// use my_alife::algorithm::graph::Graph;
+    ///
+    /// let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    /// let graph = Graph::barabasi_albert(500, 2, &mut rng);
+    /// assert_eq!(graph.edge_count(), 3 + 2 * (500 - 3));
+    /// // 優先的選択で、平均次数(約4)よりずっと次数の大きいハブができる
+    /// let max_degree = (0..graph.len()).map(|node| graph.degree(node)).max().unwrap();
+    /// assert!(max_degree > 20);
+    /// ```
+    ///
+    /// # Panics
+    /// `m`が0か、`nodes`が`m`以下のとき
+    pub fn barabasi_albert<R: Rng>(nodes: usize, m: usize, rng: &mut R) -> Graph {
+        assert!(m > 0 && nodes > m, "barabasi_albert needs 0 < m < nodes");
+        let mut graph = Graph::new(nodes);
+        // 辺の端点を並べたもの。ここから一様に選ぶと、次数に比例する確率で頂点を選べる
+        let mut endpoints = Vec::new();
+        for a in 0..=m {
+            for b in (a + 1)..=m {
+                graph.add_edge(a, b);
+                endpoints.push(a);
+                endpoints.push(b);
+            }
+        }
+        for node in (m + 1)..nodes {
+            let mut targets = Vec::with_capacity(m);
+            while targets.len() < m {
+                let target = endpoints[rng.gen_range(0, endpoints.len())];
+                if !targets.contains(&target) {
+                    targets.push(target);
+                }
+            }
+            for target in targets {
+                graph.add_edge(node, target);
+                endpoints.push(node);
+                endpoints.push(target);
+            }
+        }
+        graph
+    }
+
+    /// Watts–Strogatzのスモールワールド・ネットワーク
+    ///
+    /// `ring(nodes, k)`の各辺の片方の端を、確率`beta`でランダムな頂点につなぎ替える
+    ///
+    /// # Example
+    /// ```
+    /// extern crate rand;
+    /// extern crate my_alife;
+    ///
+    /// use rand::{SeedableRng, XorShiftRng};
+    /// use my_alife::algorithm::graph::Graph;
+    ///
+    /// let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    /// let ring = Graph::watts_strogatz(100, 4, 0.0, &mut rng);
+    /// let small_world = Graph::watts_strogatz(100, 4, 0.1, &mut rng);
+    /// // つなぎ替えても辺の数は変わらず、少しのつなぎ替えで頂点の間の距離が大きく縮む
+    /// assert_eq!(small_world.edge_count(), ring.edge_count());
+    /// assert!(small_world.mean_distance() < ring.mean_distance() * 0.6);
+    /// ```
+    pub fn watts_strogatz<R: Rng>(nodes: usize, k: usize, beta: f32, rng: &mut R) -> Graph {
+        let mut graph = Graph::ring(nodes, k);
+        for offset in 1..=k / 2 {
+            for node in 0..nodes {
+                let old = (node + offset) % nodes;
+                if rng.gen::<f32>() >= beta || !graph.has_edge(node, old) || graph.degree(node) + 1 >= nodes {
+                    continue;
+                }
+                let new = loop {
+                    let candidate = rng.gen_range(0, nodes);
+                    if candidate != node && !graph.has_edge(node, candidate) {
+                        break candidate;
+                    }
+                };
+                graph.remove_edge(node, old);
+                graph.add_edge(node, new);
+            }
+        }
+        graph
+    }
+
+    /// 頂点の数
+    pub fn len(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    /// 頂点がないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.adjacency.is_empty()
+    }
+
+    /// 頂点を1つ加え、その番号を返す
+    pub fn add_node(&mut self) -> usize {
+        self.adjacency.push(Vec::new());
+        self.adjacency.len() - 1
+    }
+
+    /// `a`と`b`を結ぶ。自己ループか、すでに結ばれているときは何もせず`false`を返す
+    ///
+    /// # Panics
+    /// 存在しない頂点を指定したとき
+    pub fn add_edge(&mut self, a: usize, b: usize) -> bool {
+        assert!(a < self.len() && b < self.len(), "node is out of bounds");
+        if a == b || self.has_edge(a, b) {
+            return false;
+        }
+        self.adjacency[a].push(b);
+        self.adjacency[b].push(a);
+        true
+    }
+
+    /// `a`と`b`を結ぶ辺を取り除く。辺がなかったときは`false`を返す
+    pub fn remove_edge(&mut self, a: usize, b: usize) -> bool {
+        if !self.has_edge(a, b) {
+            return false;
+        }
+        self.adjacency[a].retain(|&node| node != b);
+        self.adjacency[b].retain(|&node| node != a);
+        true
+    }
+
+    /// `a`と`b`が結ばれているかどうか
+    pub fn has_edge(&self, a: usize, b: usize) -> bool {
+        self.adjacency.get(a).is_some_and(|neighbors| neighbors.contains(&b))
+    }
+
+    /// `node`と結ばれている頂点
+    pub fn neighbors(&self, node: usize) -> &[usize] {
+        &self.adjacency[node]
+    }
+
+    /// `node`の次数
+    pub fn degree(&self, node: usize) -> usize {
+        self.adjacency[node].len()
+    }
+
+    /// 辺の数
+    pub fn edge_count(&self) -> usize {
+        self.adjacency.iter().map(Vec::len).sum::<usize>() / 2
+    }
+
+    /// 平均次数
+    pub fn mean_degree(&self) -> f32 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        2.0 * self.edge_count() as f32 / self.len() as f32
+    }
+
+    /// 全ての辺(小さい方の番号, 大きい方の番号)。`ForceLayout`に渡して描くときに使う
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        let mut edges = Vec::with_capacity(self.edge_count());
+        for (a, neighbors) in self.adjacency.iter().enumerate() {
+            for &b in neighbors.iter().filter(|&&b| a < b) {
+                edges.push((a, b));
+            }
+        }
+        edges.sort_unstable();
+        edges
+    }
+
+    /// `source`から各頂点までの最短距離(辺の数)。たどり着けない頂点は`None`
+    pub fn distances(&self, source: usize) -> Vec<Option<usize>> {
+        let mut distances = vec![None; self.len()];
+        let mut queue = ::std::collections::VecDeque::new();
+        distances[source] = Some(0);
+        queue.push_back(source);
+        while let Some(node) = queue.pop_front() {
+            let d = distances[node].unwrap_or(0);
+            for &next in &self.adjacency[node] {
+                if distances[next].is_none() {
+                    distances[next] = Some(d + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+        distances
+    }
+
+    /// たどり着ける全ての頂点の組の平均の最短距離
+    pub fn mean_distance(&self) -> f32 {
+        let (sum, count) = (0..self.len())
+            .flat_map(|source| self.distances(source).into_iter().enumerate().map(move |d| (source, d)))
+            .filter_map(|(source, (target, d))| if source != target { d } else { None })
+            .fold((0usize, 0usize), |(sum, count), d| (sum + d, count + 1));
+        if count == 0 {
+            0.0
+        } else {
+            sum as f32 / count as f32
+        }
+    }
+}
+
+/// ネットワーク上のSIRモデル
+///
+/// 各ステップで、感染している頂点は結ばれた感受性の頂点をそれぞれ確率`infection`で感染させ、確率`recovery`で回復する
+///
+/// # Example
+/// ```
+/// extern crate rand;
+/// extern crate my_alife;
+///
+/// use rand::{SeedableRng, XorShiftRng};
+/// use my_alife::algorithm::graph::{Graph, GraphSir, INFECTED};
+///
+/// let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+/// let graph = Graph::barabasi_albert(300, 3, &mut rng);
+/// let mut sir = GraphSir::new(graph, 0.2, 0.1);
+/// sir.infect(0);
+/// assert_eq!(sir.states()[0], INFECTED);
+///
+/// let steps = sir.run_until_extinct(10_000, &mut rng);
+/// let (susceptible, infected, recovered) = sir.counts();
+/// assert!(steps.is_some());
+/// assert_eq!(infected, 0);
+/// assert_eq!(susceptible + recovered, 300);
+/// // ハブを通って広く流行する
+/// assert!(recovered > 100);
+/// ```
+#[derive(Debug, Clone)]
+pub struct GraphSir {
+    graph: Graph,
+    states: Vec<u8>,
+    infection: f32,
+    recovery: f32,
+    time: usize,
+}
+
+impl GraphSir {
+    /// 全ての頂点が感受性の状態から始める
+    ///
+    /// # Arguments
+    /// * `infection` - 1ステップに1本の辺を通して感染する確率
+    /// * `recovery` - 1ステップに回復する確率
+    pub fn new(graph: Graph, infection: f32, recovery: f32) -> GraphSir {
+        let states = vec![SUSCEPTIBLE; graph.len()];
+        GraphSir {
+            graph,
+            states,
+            infection,
+            recovery,
+            time: 0,
+        }
+    }
+
+    /// グラフ
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    /// 頂点の状態(`SUSCEPTIBLE`, `INFECTED`, `RECOVERED`)
+    pub fn states(&self) -> &[u8] {
+        &self.states
+    }
+
+    /// 経過したステップ数
+    pub fn time(&self) -> usize {
+        self.time
+    }
+
+    /// `node`を感染させる
+    pub fn infect(&mut self, node: usize) {
+        self.states[node] = INFECTED;
+    }
+
+    /// (感受性, 感染, 回復)の頂点の数
+    pub fn counts(&self) -> (usize, usize, usize) {
+        self.states.iter().fold((0, 0, 0), |(s, i, r), &state| match state {
+            SUSCEPTIBLE => (s + 1, i, r),
+            INFECTED => (s, i + 1, r),
+            _ => (s, i, r + 1),
+        })
+    }
+
+    /// 全ての頂点を同時に1ステップ進める
+    pub fn step<R: Rng>(&mut self, rng: &mut R) {
+        let mut next = self.states.clone();
+        for (node, &state) in self.states.iter().enumerate() {
+            if state != INFECTED {
+                continue;
+            }
+            for &neighbor in self.graph.neighbors(node) {
+                if self.states[neighbor] == SUSCEPTIBLE && rng.gen::<f32>() < self.infection {
+                    next[neighbor] = INFECTED;
+                }
+            }
+            if rng.gen::<f32>() < self.recovery {
+                next[node] = RECOVERED;
+            }
+        }
+        self.states = next;
+        self.time += 1;
+    }
+
+    /// 感染している頂点がなくなるまで進め、かかったステップ数を返す。`max_steps`で終わらなければ`None`
+    pub fn run_until_extinct<R: Rng>(&mut self, max_steps: usize, rng: &mut R) -> Option<usize> {
+        for steps in 0..max_steps {
+            if self.counts().1 == 0 {
+                return Some(steps);
+            }
+            self.step(rng);
+        }
+        if self.counts().1 == 0 {
+            Some(max_steps)
+        } else {
+            None
+        }
+    }
+}
+
+/// 協力(`COOPERATE`)と裏切り(`DEFECT`)の2つの戦略の2人ゲームの利得表
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Payoff {
+    /// 互いに協力したときの利得R
+    pub reward: f32,
+    /// 自分が協力して相手が裏切ったときの利得S
+    pub sucker: f32,
+    /// 自分が裏切って相手が協力したときの利得T
+    pub temptation: f32,
+    /// 互いに裏切ったときの利得P
+    pub punishment: f32,
+}
+
+impl Payoff {
+    /// Nowak & May (1992)の弱い囚人のジレンマ。R = 1, S = P = 0, T = `temptation`(> 1)
+    pub fn weak_prisoners_dilemma(temptation: f32) -> Payoff {
+        Payoff {
+            reward: 1.0,
+            sucker: 0.0,
+            temptation,
+            punishment: 0.0,
+        }
+    }
+
+    /// 雪かきゲーム(snowdrift game)。協力の利益`benefit`と費用`cost`から決める
+    pub fn snowdrift(benefit: f32, cost: f32) -> Payoff {
+        Payoff {
+            reward: benefit - cost / 2.0,
+            sucker: benefit - cost,
+            temptation: benefit,
+            punishment: 0.0,
+        }
+    }
+
+    /// 戦略`me`で相手の戦略`other`と対戦したときの利得
+    pub fn get(&self, me: u8, other: u8) -> f32 {
+        match (me, other) {
+            (COOPERATE, COOPERATE) => self.reward,
+            (COOPERATE, _) => self.sucker,
+            (_, COOPERATE) => self.temptation,
+            _ => self.punishment,
+        }
+    }
+}
+
+/// 進化ゲームで戦略を更新するときの規則
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UpdateRule {
+    /// 自分と近傍の中で、合計の利得が最も大きいものの戦略をまねる。同数なら自分の戦略を保つ
+    Imitation,
+    /// ランダムに選んだ近傍1つの戦略を、利得の差に応じたFermi関数の確率でまねる。値は選択の温度(ノイズ)
+    Fermi(f32),
+}
+
+impl UpdateRule {
+    /// 利得`mine`の頂点が選んだ候補`candidates`(戦略, 利得)から、次の戦略を決める
+    pub(crate) fn choose<R: Rng>(self, strategy: u8, mine: f32, candidates: &[(u8, f32)], rng: &mut R) -> u8 {
+        match self {
+            UpdateRule::Imitation => {
+                candidates
+                    .iter()
+                    .fold(
+                        (strategy, mine),
+                        |best, &candidate| {
+                            if candidate.1 > best.1 {
+                                candidate
+                            } else {
+                                best
+                            }
+                        },
+                    )
+                    .0
+            }
+            UpdateRule::Fermi(temperature) => match rng.choose(candidates) {
+                Some(&(other, payoff)) => {
+                    let p = 1.0 / (1.0 + ((mine - payoff) / temperature.max(f32::EPSILON)).exp());
+                    if rng.gen::<f32>() < p {
+                        other
+                    } else {
+                        strategy
+                    }
+                }
+                None => strategy,
+            },
+        }
+    }
+}
+
+/// ネットワーク上の進化ゲーム
+///
+/// 各ステップで全ての頂点が結ばれた頂点と対戦して合計の利得を求め、`UpdateRule`に従って一斉に戦略を更新する
+///
+/// # Example
+/// ```
+/// extern crate rand;
+/// extern crate my_alife;
+///
+/// use rand::{SeedableRng, XorShiftRng};
+/// use my_alife::algorithm::graph::{Graph, GraphGame, Payoff, UpdateRule, COOPERATE, DEFECT};
+///
+/// let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+/// let graph = Graph::ring(10, 2);
+/// // 裏切り者が1人だけいる輪
+/// let mut strategies = vec![COOPERATE; 10];
+/// strategies[0] = DEFECT;
+/// let mut game = GraphGame::new(graph, strategies, Payoff::weak_prisoners_dilemma(1.2), UpdateRule::Imitation);
+/// game.step(&mut rng);
+/// // 裏切り者の利得2.4が隣の協力者の利得1.0より大きいので、裏切りが広がる
+/// assert_eq!(game.strategies()[1], DEFECT);
+/// assert_eq!(game.payoffs()[0], 2.4);
+/// assert!(game.cooperation() < 0.9);
+/// ```
+#[derive(Debug, Clone)]
+pub struct GraphGame {
+    graph: Graph,
+    strategies: Vec<u8>,
+    payoffs: Vec<f32>,
+    payoff: Payoff,
+    rule: UpdateRule,
+}
+
+impl GraphGame {
+    /// 頂点ごとの戦略`strategies`から始める
+    ///
+    /// # Panics
+    /// `strategies`の数が頂点の数と違うとき
+    pub fn new(graph: Graph, strategies: Vec<u8>, payoff: Payoff, rule: UpdateRule) -> GraphGame {
+        assert_eq!(graph.len(), strategies.len(), "one strategy is needed for each node");
+        let payoffs = vec![0.0; graph.len()];
+        GraphGame {
+            graph,
+            strategies,
+            payoffs,
+            payoff,
+            rule,
+        }
+    }
+
+    /// 確率`cooperators`で協力者、それ以外を裏切り者にして始める
+    pub fn random<R: Rng>(graph: Graph, cooperators: f32, payoff: Payoff, rule: UpdateRule, rng: &mut R) -> GraphGame {
+        let strategies = (0..graph.len())
+            .map(|_| {
+                if rng.gen::<f32>() < cooperators {
+                    COOPERATE
+                } else {
+                    DEFECT
+                }
+            })
+            .collect();
+        GraphGame::new(graph, strategies, payoff, rule)
+    }
+
+    /// グラフ
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    /// 頂点ごとの戦略
+    pub fn strategies(&self) -> &[u8] {
+        &self.strategies
+    }
+
+    /// 直前の`step`で頂点ごとに得た合計の利得
+    pub fn payoffs(&self) -> &[f32] {
+        &self.payoffs
+    }
+
+    /// 協力者の割合
+    pub fn cooperation(&self) -> f32 {
+        if self.strategies.is_empty() {
+            return 0.0;
+        }
+        self.strategies.iter().filter(|&&s| s == COOPERATE).count() as f32 / self.strategies.len() as f32
+    }
+
+    /// 対戦して、全ての頂点の戦略を一斉に更新する
+    pub fn step<R: Rng>(&mut self, rng: &mut R) {
+        let graph = &self.graph;
+        let strategies = &self.strategies;
+        let payoff = self.payoff;
+        for (node, total) in self.payoffs.iter_mut().enumerate() {
+            *total = graph
+                .neighbors(node)
+                .iter()
+                .map(|&other| payoff.get(strategies[node], strategies[other]))
+                .sum();
+        }
+        let mut candidates = Vec::new();
+        let next = (0..graph.len())
+            .map(|node| {
+                candidates.clear();
+                candidates.extend(
+                    graph
+                        .neighbors(node)
+                        .iter()
+                        .map(|&other| (strategies[other], self.payoffs[other])),
+                );
+                self.rule.choose(strategies[node], self.payoffs[node], &candidates, rng)
+            })
+            .collect();
+        self.strategies = next;
+    }
+}
@@ -0,0 +1,289 @@
+use algorithm::integrator::Integrator;
+use algorithm::reaction_diffusion::ReactionDiffusion;
+use ndarray::{Array2, Axis};
+use visualizer::Matrix;
+
+// 長さの丸め誤差で格子を作り直すのが遅れないように、これだけ足りなくても1セル分伸びたとみなす(セル)
+const REGRID_TOLERANCE: f32 = 1e-3;
+
+/// 領域のどこが伸びるか
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrowthMode {
+    /// 領域全体が一様に引き伸ばされる。伸びた分だけ濃度が薄まり、格子を作り直すときは全体を補間し直す
+    Uniform,
+    /// 端(右端と下端)だけが伸びる。濃度は薄まらず、格子を作り直すときは端のセルの間に新しいセルを補間して挟む
+    Tip,
+}
+
+/// どの方向に伸びるか
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrowthAxes {
+    /// 横(列の方向)だけ
+    Horizontal,
+    /// 縦と横
+    Both,
+}
+
+/// 領域の長さLの伸び方
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GrowthRate {
+    /// dL/dt = 値。長さが一定の速さで伸びる
+    Linear(f32),
+    /// dL/dt = 値 × L。長さが指数関数的に伸びる
+    Exponential(f32),
+}
+
+impl GrowthRate {
+    /// 長さ`length`から時間`dt`だけ伸ばした長さ
+    pub fn advance(self, length: f32, dt: f32) -> f32 {
+        match self {
+            GrowthRate::Linear(rate) => length + rate * dt,
+            GrowthRate::Exponential(rate) => length * (rate * dt).exp(),
+        }
+    }
+}
+
+/// 格子を作り直すときの補間の方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    /// 一番近いセルの値
+    Nearest,
+    /// 周りのセルの値の線形補間
+    Linear,
+}
+
+/// `field`を`dim`の大きさに補間し直す。セルの中心どうしを合わせ、はみ出した位置は端のセルの値にする
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::arr2;
+/// use my_alife::algorithm::growing_domain::{resample, Interpolation};
+///
+/// let field = arr2(&[[0.0, 1.0]]);
+/// let linear = resample(&field, (1, 4), Interpolation::Linear);
+/// assert_eq!(linear, arr2(&[[0.0, 0.25, 0.75, 1.0]]));
+/// let nearest = resample(&field, (1, 4), Interpolation::Nearest);
+/// assert_eq!(nearest, arr2(&[[0.0, 0.0, 1.0, 1.0]]));
+/// ```
+pub fn resample(field: &Matrix<f32>, dim: (usize, usize), interpolation: Interpolation) -> Matrix<f32> {
+    let (rows, cols) = field.dim();
+    if rows == 0 || cols == 0 {
+        return Array2::zeros(dim);
+    }
+    // 新しいセルの中心が、元の格子のどこ(セルの中心を整数とする座標)に来るか
+    let source = |i: usize, from: usize, to: usize| ((i as f32 + 0.5) * from as f32 / to as f32 - 0.5).max(0.0);
+    Array2::from_shape_fn(dim, |(row, col)| {
+        let (y, x) = (source(row, rows, dim.0), source(col, cols, dim.1));
+        match interpolation {
+            Interpolation::Nearest => field[[(y.round() as usize).min(rows - 1), (x.round() as usize).min(cols - 1)]],
+            Interpolation::Linear => {
+                let (r0, c0) = ((y as usize).min(rows - 1), (x as usize).min(cols - 1));
+                let (r1, c1) = ((r0 + 1).min(rows - 1), (c0 + 1).min(cols - 1));
+                let (ty, tx) = (y - r0 as f32, x - c0 as f32);
+                let top = field[[r0, c0]] * (1.0 - tx) + field[[r0, c1]] * tx;
+                let bottom = field[[r1, c0]] * (1.0 - tx) + field[[r1, c1]] * tx;
+                top * (1.0 - ty) + bottom * ty
+            }
+        }
+    })
+}
+
+/// 時間とともに伸びる領域の上の反応拡散系
+///
+/// 格子間隔は`system.dx()`のまま変えず、領域の長さが1セル分以上伸びるたびに格子を作り直してセルを増やす。
+/// 領域が伸びるとTuringパターンの縞や斑点が分裂して数が増え、パターンの間隔はほぼ一定に保たれる
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::algorithm::gray_scott::GrayScott;
+/// use my_alife::algorithm::growing_domain::{GrowingDomain, GrowthMode, GrowthRate};
+/// use my_alife::algorithm::integrator::Integrator;
+/// use my_alife::algorithm::reaction_diffusion::ReactionDiffusion;
+///
+/// // 供給も分解もしないので、反応が起きない
+/// let model = GrayScott::new(0.0, 0.0);
+/// let fields = vec![Array2::<f32>::ones((16, 16)), Array2::<f32>::zeros((16, 16))];
+/// // 1ステップに0.1セル分ずつ横に伸びる
+/// let mut domain = GrowingDomain::new(model, fields, GrowthMode::Uniform, GrowthRate::Linear(0.1 * model.dx()));
+/// let before = domain.total(0);
+/// for _ in 0..20 {
+///     domain.step(Integrator::Euler, 1.0);
+/// }
+/// assert_eq!(domain.dim(), (16, 18));
+/// // 一様に伸びても物質の総量は変わらず、濃度が薄まる
+/// assert!((domain.total(0) - before).abs() < 1e-3 * before);
+/// assert!(domain.fields()[0][[0, 0]] < 1.0);
+/// ```
+pub struct GrowingDomain<S> {
+    system: S,
+    fields: Vec<Matrix<f32>>,
+    mode: GrowthMode,
+    axes: GrowthAxes,
+    rate: GrowthRate,
+    interpolation: Interpolation,
+    // 領域の(縦, 横)の長さ
+    size: (f32, f32),
+    time: f32,
+}
+
+impl<S: ReactionDiffusion> GrowingDomain<S> {
+    /// 各物質の濃度`fields`から始める。初期値は横だけに伸び、線形補間で格子を作り直す
+    ///
+    /// # Panics
+    /// `fields`が空か、大きさがそろっていないとき
+    pub fn new(system: S, fields: Vec<Matrix<f32>>, mode: GrowthMode, rate: GrowthRate) -> GrowingDomain<S> {
+        assert!(!fields.is_empty(), "at least one field is needed");
+        let dim = fields[0].dim();
+        assert!(
+            fields.iter().all(|field| field.dim() == dim),
+            "fields must have the same size"
+        );
+        let dx = system.dx();
+        GrowingDomain {
+            system,
+            fields,
+            mode,
+            axes: GrowthAxes::Horizontal,
+            rate,
+            interpolation: Interpolation::Linear,
+            size: (dim.0 as f32 * dx, dim.1 as f32 * dx),
+            time: 0.0,
+        }
+    }
+
+    /// 伸びる方向を設定する
+    pub fn set_axes(&mut self, axes: GrowthAxes) {
+        self.axes = axes;
+    }
+
+    /// 領域の長さの伸び方を設定する
+    pub fn set_rate(&mut self, rate: GrowthRate) {
+        self.rate = rate;
+    }
+
+    /// 格子を作り直すときの補間の方法を設定する
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+        self.interpolation = interpolation;
+    }
+
+    /// 解いている反応拡散系
+    pub fn system(&self) -> &S {
+        &self.system
+    }
+
+    /// 各物質の濃度
+    pub fn fields(&self) -> &[Matrix<f32>] {
+        &self.fields
+    }
+
+    /// 格子の(行数, 列数)
+    pub fn dim(&self) -> (usize, usize) {
+        self.fields[0].dim()
+    }
+
+    /// 領域の(縦, 横)の長さ。格子の大きさ × `dx`より最大1セル分長い
+    pub fn size(&self) -> (f32, f32) {
+        self.size
+    }
+
+    /// `species`番目の物質の総量(濃度 × 面積)
+    pub fn total(&self, species: usize) -> f32 {
+        let (rows, cols) = self.dim();
+        self.fields[species].scalar_sum() * self.size.0 * self.size.1 / (rows * cols) as f32
+    }
+
+    /// 経過した時間
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// `integrator`で時間`dt`だけ進めて領域を伸ばす。格子を作り直したときは`true`を返す
+    pub fn step(&mut self, integrator: Integrator, dt: f32) -> bool {
+        integrator.step(&self.system, &mut self.fields, dt);
+        let old = self.size;
+        self.size.1 = self.rate.advance(old.1, dt);
+        if self.axes == GrowthAxes::Both {
+            self.size.0 = self.rate.advance(old.0, dt);
+        }
+        self.time += dt;
+        if self.mode == GrowthMode::Uniform {
+            // 一様に伸びると、物質の量は変わらずに体積だけが増える
+            let dilution = (old.0 / self.size.0) * (old.1 / self.size.1);
+            for field in &mut self.fields {
+                field.mapv_inplace(|e| e * dilution);
+            }
+        }
+        self.regrid()
+    }
+
+    // 領域の長さに足りない分だけセルを増やす
+    fn regrid(&mut self) -> bool {
+        let dx = self.system.dx();
+        let (rows, cols) = self.dim();
+        let target = (
+            ((self.size.0 / dx + REGRID_TOLERANCE) as usize).max(rows),
+            ((self.size.1 / dx + REGRID_TOLERANCE) as usize).max(cols),
+        );
+        if target == (rows, cols) {
+            return false;
+        }
+        let interpolation = self.interpolation;
+        for field in &mut self.fields {
+            let before = field.scalar_sum();
+            *field = match self.mode {
+                GrowthMode::Uniform => {
+                    let resampled = resample(field, target, interpolation);
+                    // 補間し直しても物質の総量が変わらないようにする
+                    let after = resampled.scalar_sum();
+                    let correction = if after.abs() > f32::EPSILON {
+                        before / after * (target.0 * target.1) as f32 / (rows * cols) as f32
+                    } else {
+                        1.0
+                    };
+                    resampled * correction
+                }
+                GrowthMode::Tip => {
+                    let grown = insert_at_tip(field, Axis(1), target.1 - cols, interpolation);
+                    insert_at_tip(&grown, Axis(0), target.0 - rows, interpolation)
+                }
+            };
+        }
+        true
+    }
+}
+
+// `axis`の方向の最後の2つのセルの間に、`count`個のセルを補間して挟む
+fn insert_at_tip(field: &Matrix<f32>, axis: Axis, count: usize, interpolation: Interpolation) -> Matrix<f32> {
+    let len = field.len_of(axis);
+    if count == 0 || len == 0 {
+        return field.clone();
+    }
+    let tip = field.subview(axis, len - 1).to_owned();
+    let behind = field.subview(axis, len.saturating_sub(2)).to_owned();
+    let mut dim = field.dim();
+    match axis {
+        Axis(0) => dim.0 += count,
+        _ => dim.1 += count,
+    }
+    let mut grown = Array2::zeros(dim);
+    for i in 0..len - 1 {
+        grown.subview_mut(axis, i).assign(&field.subview(axis, i));
+    }
+    for i in 0..count {
+        let t = (i + 1) as f32 / (count + 1) as f32;
+        let inserted = match interpolation {
+            Interpolation::Nearest => tip.clone(),
+            Interpolation::Linear => &behind * (1.0 - t) + &tip * t,
+        };
+        grown.subview_mut(axis, len - 1 + i).assign(&inserted);
+    }
+    grown.subview_mut(axis, len - 1 + count).assign(&tip);
+    grown
+}
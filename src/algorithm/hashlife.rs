@@ -0,0 +1,456 @@
+use algorithm::game_of_life::{next_state, ALIVE, DEAD};
+use algorithm::golly::{MacroNode, Macrocell};
+use failure;
+use ndarray::Array2;
+use std::collections::HashMap;
+use visualizer::Matrix;
+
+type NodeId = usize;
+
+// 大きさ1の死んでいるセルと生きているセル
+const DEAD_LEAF: NodeId = 0;
+const ALIVE_LEAF: NodeId = 1;
+// 根のレベルの上限。一辺2^levelの正方形の座標をi64で表せる範囲(`Macrocell`と同じ)
+const MAX_LEVEL: u8 = 62;
+
+// 一辺2^levelの正方形を表す4分木のノード。同じ内容のノードは1つしか作られない
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    level: u8,
+    nw: NodeId,
+    ne: NodeId,
+    sw: NodeId,
+    se: NodeId,
+    population: u64,
+}
+
+/// HashLife(4分木と計算結果のメモ化)によるGame of Lifeのエンジン
+///
+/// 盤面の大きさに上限がなく、同じパターンの計算結果を使い回すので、
+/// 周期的な構造が多い盤面では`step`で何百万世代も一気に進められる。
+/// 座標は(x: 列, y: 行)で、yは下向きに増える。盤面は原点を中心とする一辺2^62の正方形まで広げられる
+///
+/// ノードとメモ化した計算結果の表は増える一方で、小さくなるのは`clear_cache`で計算結果を捨てたときだけ
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::hashlife::HashLife;
+///
+/// let mut life = HashLife::new();
+/// // glider
+/// for &(x, y) in &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+///     life.set_cell(x, y, true);
+/// }
+/// // gliderは4世代で右下に1マス進む
+/// life.step(4).unwrap();
+/// assert!(life.get_cell(2, 1) && life.get_cell(3, 2) && life.get_cell(1, 3));
+///
+/// // 1万世代も一気に進められる
+/// life.step(10_000).unwrap();
+/// assert_eq!(life.generation(), 10_004);
+/// assert_eq!(life.population(), 5);
+/// assert!(life.get_cell(2 + 2_501, 1 + 2_501));
+///
+/// // 一度に進められるのは2^60 - 1世代まで
+/// assert!(life.step(1 << 60).is_err());
+/// ```
+pub struct HashLife {
+    nodes: Vec<Node>,
+    index: HashMap<(NodeId, NodeId, NodeId, NodeId), NodeId>,
+    empty: Vec<NodeId>,
+    results: HashMap<(NodeId, u8), NodeId>,
+    root: NodeId,
+    generation: u64,
+}
+
+impl Default for HashLife {
+    fn default() -> HashLife {
+        HashLife::new()
+    }
+}
+
+impl HashLife {
+    /// すべてのセルが死んでいる盤面を生成する
+    pub fn new() -> HashLife {
+        let leaf = |population| Node {
+            level: 0,
+            nw: DEAD_LEAF,
+            ne: DEAD_LEAF,
+            sw: DEAD_LEAF,
+            se: DEAD_LEAF,
+            population,
+        };
+        let mut life = HashLife {
+            nodes: vec![leaf(0), leaf(1)],
+            index: HashMap::new(),
+            empty: vec![DEAD_LEAF],
+            results: HashMap::new(),
+            root: DEAD_LEAF,
+            generation: 0,
+        };
+        life.root = life.empty_node(3);
+        life
+    }
+
+    /// `cells`の生きているセルを、`cells[[0, 0]]`が(x, y)に来るように置いた盤面を生成する
+    pub fn from_cells(cells: &Matrix<u8>, x: i64, y: i64) -> HashLife {
+        let mut life = HashLife::new();
+        for ((row, col), e) in cells.indexed_iter() {
+            if *e != DEAD {
+                life.set_cell(x + col as i64, y + row as i64, true);
+            }
+        }
+        life
+    }
+
+    /// Gollyのmacrocell形式のパターンから盤面を生成する。世代数も引き継ぐ
+    ///
+    /// 2状態のパターンでなければエラーになる
+    pub fn from_macrocell(pattern: &Macrocell) -> Result<HashLife, failure::Error> {
+        if pattern.num_states() > 2 {
+            return Err(format_err!(
+                "HashLife supports only two states, but the pattern has {}",
+                pattern.num_states()
+            ));
+        }
+        let mut life = HashLife::new();
+        let mut memo = HashMap::new();
+        life.root = life.import(pattern, pattern.root(), pattern.level(), &mut memo);
+        while life.level() < 3 {
+            life.expand();
+        }
+        life.generation = pattern.generation();
+        Ok(life)
+    }
+
+    fn import(&mut self, pattern: &Macrocell, id: usize, level: u8, memo: &mut HashMap<usize, NodeId>) -> NodeId {
+        if id == 0 {
+            return self.empty_node(level);
+        }
+        if let Some(&node) = memo.get(&id) {
+            return node;
+        }
+        let node = match pattern.node(id) {
+            MacroNode::Cell(_) => ALIVE_LEAF,
+            MacroNode::Branch(level, children) => {
+                let mut quadrants = [DEAD_LEAF; 4];
+                for (quadrant, &child) in quadrants.iter_mut().zip(&children) {
+                    *quadrant = self.import(pattern, child, level - 1, memo);
+                }
+                self.join(quadrants[0], quadrants[1], quadrants[2], quadrants[3])
+            }
+        };
+        memo.insert(id, node);
+        node
+    }
+
+    fn level(&self) -> u8 {
+        self.nodes[self.root].level
+    }
+
+    /// 進めた世代数
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// 生きているセルの数
+    pub fn population(&self) -> u64 {
+        self.nodes[self.root].population
+    }
+
+    /// 作られたノードの数。メモリ使用量の目安になる
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// メモ化した計算結果を捨ててメモリを空ける。盤面は変わらない。ノードの表は捨てない
+    pub fn clear_cache(&mut self) {
+        self.results.clear();
+    }
+
+    /// セル(x, y)の生死を設定する
+    ///
+    /// # Panics
+    /// xかyが-2^61以上2^61未満でないとき
+    pub fn set_cell(&mut self, x: i64, y: i64, alive: bool) {
+        let limit = 1 << (MAX_LEVEL - 1);
+        assert!(
+            -limit <= x && x < limit && -limit <= y && y < limit,
+            "cell ({}, {}) is outside the largest square",
+            x,
+            y
+        );
+        while !self.contains(x, y) {
+            self.expand();
+        }
+        let half = self.half_size();
+        let root = self.root;
+        self.root = self.set_node(root, (x + half) as u64, (y + half) as u64, alive);
+    }
+
+    /// セル(x, y)が生きているかどうか
+    pub fn get_cell(&self, x: i64, y: i64) -> bool {
+        if !self.contains(x, y) {
+            return false;
+        }
+        let half = self.half_size();
+        let mut node = self.root;
+        let (mut x, mut y) = ((x + half) as u64, (y + half) as u64);
+        while self.nodes[node].level > 0 {
+            let n = self.nodes[node];
+            let half = 1 << (n.level - 1);
+            node = match (x >= half, y >= half) {
+                (false, false) => n.nw,
+                (true, false) => n.ne,
+                (false, true) => n.sw,
+                (true, true) => n.se,
+            };
+            x %= half;
+            y %= half;
+        }
+        node == ALIVE_LEAF
+    }
+
+    /// `generations`世代進める。2の冪に分解し、それぞれを再帰的な計算1回で進める
+    ///
+    /// 一度に進められるのは2^60 - 1世代まで。それを超えるときや、世代数がu64に収まらないときはエラーになる。
+    /// 途中で盤面が一辺2^62の正方形に収まらなくなったときもエラーになり、それまでに進めた分は残る
+    pub fn step(&mut self, generations: u64) -> Result<(), failure::Error> {
+        let max_pow2 = MAX_LEVEL - 3;
+        if generations >> (max_pow2 + 1) != 0 {
+            return Err(format_err!(
+                "cannot advance {} generations at once (at most 2^{} - 1)",
+                generations,
+                max_pow2 + 1
+            ));
+        }
+        if self.generation.checked_add(generations).is_none() {
+            return Err(format_err!(
+                "generation {} + {} overflows",
+                self.generation,
+                generations
+            ));
+        }
+        for j in 0..=max_pow2 {
+            if generations & (1 << j) != 0 {
+                self.step_pow2(j)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 左上が(x, y)で`width`x`height`の範囲を切り出す。生きているセルは`ALIVE`になる
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::algorithm::hashlife::HashLife;
+    ///
+    /// let mut life = HashLife::new();
+    /// life.set_cell(-1_000_000, 5, true);
+    /// let view = life.viewport(-1_000_001, 4, 3, 3);
+    /// assert_eq!(view[[1, 1]], 1);
+    /// assert_eq!(view.scalar_sum(), 1);
+    /// ```
+    pub fn viewport(&self, x: i64, y: i64, width: usize, height: usize) -> Matrix<u8> {
+        let mut view = Array2::zeros((height, width));
+        let half = self.half_size();
+        self.fill(self.root, -half, -half, &mut view, x, y);
+        view
+    }
+
+    fn fill(&self, node: NodeId, node_x: i64, node_y: i64, view: &mut Matrix<u8>, x: i64, y: i64) {
+        let n = self.nodes[node];
+        let size = 1i64 << n.level;
+        let (height, width) = view.dim();
+        if n.population == 0
+            || node_x >= x + width as i64
+            || node_y >= y + height as i64
+            || node_x + size <= x
+            || node_y + size <= y
+        {
+            return;
+        }
+        if n.level == 0 {
+            view[[(node_y - y) as usize, (node_x - x) as usize]] = ALIVE;
+            return;
+        }
+        let half = size / 2;
+        self.fill(n.nw, node_x, node_y, view, x, y);
+        self.fill(n.ne, node_x + half, node_y, view, x, y);
+        self.fill(n.sw, node_x, node_y + half, view, x, y);
+        self.fill(n.se, node_x + half, node_y + half, view, x, y);
+    }
+
+    fn half_size(&self) -> i64 {
+        1 << (self.nodes[self.root].level - 1)
+    }
+
+    fn contains(&self, x: i64, y: i64) -> bool {
+        let half = self.half_size();
+        -half <= x && x < half && -half <= y && y < half
+    }
+
+    fn step_pow2(&mut self, j: u8) -> Result<(), failure::Error> {
+        // 2^j世代の間に生きているセルが広がっても、返ってくる中央の正方形からはみ出さないように広げておく
+        loop {
+            let root = self.nodes[self.root];
+            let centre = self.centre(self.root);
+            let inner = self.centre(centre);
+            if root.level >= j + 3 && self.nodes[inner].population == root.population {
+                break;
+            }
+            if root.level >= MAX_LEVEL {
+                return Err(format_err!(
+                    "pattern grew beyond the largest square at generation {}",
+                    self.generation
+                ));
+            }
+            self.expand();
+        }
+        let root = self.root;
+        self.root = self.advance(root, j);
+        self.generation += 1 << j;
+        Ok(())
+    }
+
+    fn join(&mut self, nw: NodeId, ne: NodeId, sw: NodeId, se: NodeId) -> NodeId {
+        if let Some(id) = self.index.get(&(nw, ne, sw, se)) {
+            return *id;
+        }
+        let population = self.nodes[nw].population
+            + self.nodes[ne].population
+            + self.nodes[sw].population
+            + self.nodes[se].population;
+        let node = Node {
+            level: self.nodes[nw].level + 1,
+            nw,
+            ne,
+            sw,
+            se,
+            population,
+        };
+        let id = self.nodes.len();
+        self.nodes.push(node);
+        self.index.insert((nw, ne, sw, se), id);
+        id
+    }
+
+    fn empty_node(&mut self, level: u8) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let e = *self.empty.last().unwrap();
+            let next = self.join(e, e, e, e);
+            self.empty.push(next);
+        }
+        self.empty[level as usize]
+    }
+
+    // 中心を変えずに一辺を2倍にする
+    fn expand(&mut self) {
+        let root = self.nodes[self.root];
+        let e = self.empty_node(root.level - 1);
+        let nw = self.join(e, e, e, root.nw);
+        let ne = self.join(e, e, root.ne, e);
+        let sw = self.join(e, root.sw, e, e);
+        let se = self.join(root.se, e, e, e);
+        self.root = self.join(nw, ne, sw, se);
+    }
+
+    fn set_node(&mut self, node: NodeId, x: u64, y: u64, alive: bool) -> NodeId {
+        let n = self.nodes[node];
+        if n.level == 0 {
+            return if alive { ALIVE_LEAF } else { DEAD_LEAF };
+        }
+        let half = 1 << (n.level - 1);
+        let (mut nw, mut ne, mut sw, mut se) = (n.nw, n.ne, n.sw, n.se);
+        match (x >= half, y >= half) {
+            (false, false) => nw = self.set_node(nw, x, y, alive),
+            (true, false) => ne = self.set_node(ne, x - half, y, alive),
+            (false, true) => sw = self.set_node(sw, x, y - half, alive),
+            (true, true) => se = self.set_node(se, x - half, y - half, alive),
+        }
+        self.join(nw, ne, sw, se)
+    }
+
+    // 一辺が半分の中央の正方形
+    fn centre(&mut self, node: NodeId) -> NodeId {
+        let n = self.nodes[node];
+        let (nw, ne, sw, se) = (self.nodes[n.nw], self.nodes[n.ne], self.nodes[n.sw], self.nodes[n.se]);
+        self.join(nw.se, ne.sw, sw.ne, se.nw)
+    }
+
+    // レベルkのノードの中央(レベルk-1)を2^j世代進めたもの。0 <= j <= k-2
+    fn advance(&mut self, node: NodeId, j: u8) -> NodeId {
+        let n = self.nodes[node];
+        if n.population == 0 {
+            return self.empty_node(n.level - 1);
+        }
+        if let Some(result) = self.results.get(&(node, j)) {
+            return *result;
+        }
+        let result = if n.level == 2 {
+            self.advance_4x4(node)
+        } else {
+            let (nw, ne, sw, se) = (self.nodes[n.nw], self.nodes[n.ne], self.nodes[n.sw], self.nodes[n.se]);
+            // 重なり合う9個のレベルk-1の正方形
+            let n00 = n.nw;
+            let n01 = self.join(nw.ne, ne.nw, nw.se, ne.sw);
+            let n02 = n.ne;
+            let n10 = self.join(nw.sw, nw.se, sw.nw, sw.ne);
+            let n11 = self.join(nw.se, ne.sw, sw.ne, se.nw);
+            let n12 = self.join(ne.sw, ne.se, se.nw, se.ne);
+            let n20 = n.sw;
+            let n21 = self.join(sw.ne, se.nw, sw.se, se.sw);
+            let n22 = n.se;
+            let full_speed = j + 2 == n.level;
+            let mut s = [n00, n01, n02, n10, n11, n12, n20, n21, n22];
+            for e in s.iter_mut() {
+                *e = if full_speed {
+                    self.advance(*e, j - 1)
+                } else {
+                    self.centre(*e)
+                };
+            }
+            let j2 = if full_speed { j - 1 } else { j };
+            let a = self.join(s[0], s[1], s[3], s[4]);
+            let b = self.join(s[1], s[2], s[4], s[5]);
+            let c = self.join(s[3], s[4], s[6], s[7]);
+            let d = self.join(s[4], s[5], s[7], s[8]);
+            let a = self.advance(a, j2);
+            let b = self.advance(b, j2);
+            let c = self.advance(c, j2);
+            let d = self.advance(d, j2);
+            self.join(a, b, c, d)
+        };
+        self.results.insert((node, j), result);
+        result
+    }
+
+    // 4x4の中央2x2を1世代進める
+    fn advance_4x4(&mut self, node: NodeId) -> NodeId {
+        let mut cells = [[DEAD; 4]; 4];
+        let n = self.nodes[node];
+        for (i, quadrant) in [n.nw, n.ne, n.sw, n.se].iter().enumerate() {
+            let q = self.nodes[*quadrant];
+            let (ox, oy) = ((i % 2) * 2, (i / 2) * 2);
+            for (k, leaf) in [q.nw, q.ne, q.sw, q.se].iter().enumerate() {
+                cells[oy + k / 2][ox + k % 2] = if *leaf == ALIVE_LEAF { ALIVE } else { DEAD };
+            }
+        }
+        let mut next = [DEAD_LEAF; 4];
+        for (k, e) in next.iter_mut().enumerate() {
+            let (y, x) = (1 + k / 2, 1 + k % 2);
+            let mut neighbors = 0;
+            for dy in 0..3 {
+                for dx in 0..3 {
+                    if dy != 1 || dx != 1 {
+                        neighbors += cells[y + dy - 1][x + dx - 1];
+                    }
+                }
+            }
+            if next_state(cells[y][x], neighbors) == ALIVE {
+                *e = ALIVE_LEAF;
+            }
+        }
+        self.join(next[0], next[1], next[2], next[3])
+    }
+}
@@ -0,0 +1,92 @@
+use algorithm::reaction_diffusion::ReactionDiffusion;
+use visualizer::Matrix;
+
+// 半陰解法で拡散項の連立方程式を解くときのJacobi法の反復回数
+const JACOBI_ITERATIONS: usize = 20;
+
+/// 連続時間モデルの時間発展の解き方
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Integrator {
+    /// 陽的Euler法。速いが、拡散係数や反応速度が大きいと発散しやすい
+    Euler,
+    /// 4次のRunge-Kutta法。1ステップあたり4回微分を計算するが精度が高い
+    RungeKutta4,
+    /// 拡散項を陰的に、反応項を陽的に解く半陰解法(IMEX Euler)。拡散係数が大きくても発散しない
+    SemiImplicitEuler,
+}
+
+impl Integrator {
+    /// `fields`を時間`dt`だけ進める
+    ///
+    /// # Arguments
+    /// * `system` - 解く反応拡散系
+    /// * `fields` - 各物質の濃度。その場で更新される
+    /// * `dt` - 時間刻み
+    ///
+    /// # Example
+    /// ```
+    /// extern crate ndarray;
+    /// extern crate my_alife;
+    ///
+    /// use ndarray::Array2;
+    /// use my_alife::algorithm::gray_scott::GrayScott;
+    /// use my_alife::algorithm::integrator::Integrator;
+    ///
+    /// // 陽的Euler法では発散する大きさの拡散係数
+    /// let model = GrayScott { f: 0.04, k: 0.06, du: 2e-3, dv: 1e-3 };
+    /// let mut fields = [Array2::<f32>::ones((32, 32)), Array2::<f32>::zeros((32, 32))];
+    /// fields[1][[16, 16]] = 1.0;
+    /// for _ in 0..10 {
+    ///     Integrator::SemiImplicitEuler.step(&model, &mut fields, 1.0);
+    /// }
+    /// assert!(fields.iter().all(|field| field.iter().all(|e| e.is_finite())));
+    /// ```
+    pub fn step<S: ReactionDiffusion>(&self, system: &S, fields: &mut [Matrix<f32>], dt: f32) {
+        match *self {
+            Integrator::Euler => {
+                let k1 = system.derivative(fields);
+                add_scaled_assign(fields, &k1, dt);
+            }
+            Integrator::RungeKutta4 => {
+                let k1 = system.derivative(fields);
+                let k2 = system.derivative(&add_scaled(fields, &k1, dt / 2.0));
+                let k3 = system.derivative(&add_scaled(fields, &k2, dt / 2.0));
+                let k4 = system.derivative(&add_scaled(fields, &k3, dt));
+                add_scaled_assign(fields, &k1, dt / 6.0);
+                add_scaled_assign(fields, &k2, dt / 3.0);
+                add_scaled_assign(fields, &k3, dt / 3.0);
+                add_scaled_assign(fields, &k4, dt / 6.0);
+            }
+            Integrator::SemiImplicitEuler => {
+                // (1 - dt * D * ∇²) u' = u + dt * R(u) を拡散ごとにJacobi法で解く
+                let reaction = system.reaction(fields);
+                let dx = system.dx();
+                for ((field, reaction), diffusion) in fields.iter_mut().zip(reaction).zip(system.diffusions()) {
+                    let rhs = &*field + &(reaction * dt);
+                    let a = dt * diffusion / (dx * dx);
+                    let mut next = rhs.clone();
+                    for _ in 0..JACOBI_ITERATIONS {
+                        // ラプラシアンの対角成分(-4)以外を右辺に移す
+                        let off_diagonal = system.laplacian(&next, 1.0) + &next * 4.0;
+                        next = (&rhs + &(off_diagonal * a)) / (1.0 + 4.0 * a);
+                    }
+                    *field = next;
+                }
+            }
+        }
+    }
+}
+
+fn add_scaled(fields: &[Matrix<f32>], derivative: &[Matrix<f32>], h: f32) -> Vec<Matrix<f32>> {
+    fields
+        .iter()
+        .zip(derivative)
+        .map(|(field, d)| field + &(d * h))
+        .collect()
+}
+
+fn add_scaled_assign(fields: &mut [Matrix<f32>], derivative: &[Matrix<f32>], h: f32) {
+    for (field, d) in fields.iter_mut().zip(derivative) {
+        field.scaled_add(h, d);
+    }
+}
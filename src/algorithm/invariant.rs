@@ -0,0 +1,304 @@
+use algorithm::backend::{Backend, Stepper};
+use failure;
+use visualizer::Matrix;
+
+type Quantity<T> = Box<dyn Fn(&T) -> f64>;
+
+enum Rule<T> {
+    // 最初に調べたときの値から`tolerance`より離れてはいけない量
+    Conserved {
+        quantity: Quantity<T>,
+        tolerance: f64,
+        initial: Option<f64>,
+    },
+    // [min, max]に入っていなければいけない量
+    Bounded {
+        quantity: Quantity<T>,
+        min: f64,
+        max: f64,
+    },
+    // 成り立っていなければいけない条件
+    Holds(Box<dyn Fn(&T) -> bool>),
+}
+
+/// シミュレーションの状態が満たすべき性質(保存量、値の範囲、状態の正しさ)の集まり
+///
+/// `verify`や`run`はデバッグビルドのときだけ`interval`ステップごとに性質を調べるので、
+/// リリースビルドの速さを落とさずに実装の誤りを早く見つけられる
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::invariant::Invariants;
+///
+/// // 粒子を左右に交換するだけなので、粒子の数は変わらない
+/// let mut invariants = Invariants::new(4)
+///     .conserved("particles", 0.0, |cells: &Vec<u32>| cells.iter().sum::<u32>() as f64)
+///     .bounded("max", 0.0, 10.0, |cells: &Vec<u32>| *cells.iter().max().unwrap() as f64)
+///     .holds("length", |cells: &Vec<u32>| cells.len() == 4);
+/// let mut cells = vec![1, 2, 3, 4];
+/// invariants.run(&mut cells, 10, |cells| cells.rotate_left(1)).unwrap();
+/// assert_eq!(invariants.steps(), 10);
+///
+/// // 粒子が増える誤りは見つかる
+/// let error = invariants.run(&mut cells, 10, |cells| cells[0] += 1).unwrap_err();
+/// assert!(error.to_string().starts_with("invariant \"particles\" violated at step 12"));
+/// ```
+pub struct Invariants<T> {
+    rules: Vec<(String, Rule<T>)>,
+    interval: u64,
+    steps: u64,
+}
+
+impl<T> Invariants<T> {
+    /// `interval`ステップごとに調べる、性質のない集まり。`interval`が0なら1とする
+    pub fn new(interval: usize) -> Invariants<T> {
+        Invariants {
+            rules: Vec::new(),
+            interval: interval.max(1) as u64,
+            steps: 0,
+        }
+    }
+
+    /// 最初に調べたときの値から`tolerance`より離れてはいけない量(質量、粒子の数など)を加える
+    pub fn conserved<F>(mut self, name: &str, tolerance: f64, quantity: F) -> Invariants<T>
+    where
+        F: Fn(&T) -> f64 + 'static,
+    {
+        let rule = Rule::Conserved {
+            quantity: Box::new(quantity),
+            tolerance,
+            initial: None,
+        };
+        self.rules.push((name.to_string(), rule));
+        self
+    }
+
+    /// [`min`, `max`]に入っていなければいけない量を加える
+    pub fn bounded<F>(mut self, name: &str, min: f64, max: f64, quantity: F) -> Invariants<T>
+    where
+        F: Fn(&T) -> f64 + 'static,
+    {
+        let rule = Rule::Bounded {
+            quantity: Box::new(quantity),
+            min,
+            max,
+        };
+        self.rules.push((name.to_string(), rule));
+        self
+    }
+
+    /// 成り立っていなければいけない条件を加える
+    pub fn holds<F>(mut self, name: &str, condition: F) -> Invariants<T>
+    where
+        F: Fn(&T) -> bool + 'static,
+    {
+        self.rules.push((name.to_string(), Rule::Holds(Box::new(condition))));
+        self
+    }
+
+    /// 性질の数
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// 性質がないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// 調べる間隔のステップ数
+    pub fn interval(&self) -> usize {
+        self.interval as usize
+    }
+
+    /// `run`で進めたステップ数
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// 保存量の最初の値を忘れる。状態を作り直したときに使う
+    pub fn reset(&mut self) {
+        self.steps = 0;
+        for (_, rule) in &mut self.rules {
+            if let Rule::Conserved { initial, .. } = rule {
+                *initial = None;
+            }
+        }
+    }
+
+    /// ビルドによらず、`step`ステップ目の`state`がすべての性質を満たしているかを調べる。保存量は最初に調べたときの値を覚える
+    pub fn check(&mut self, step: u64, state: &T) -> Result<(), failure::Error> {
+        for (name, rule) in &mut self.rules {
+            let violation = match rule {
+                Rule::Conserved {
+                    quantity,
+                    tolerance,
+                    initial,
+                } => {
+                    let value = quantity(state);
+                    let initial = *initial.get_or_insert(value);
+                    // NaNになったときも違反とみなす
+                    if (value - initial).abs() <= *tolerance {
+                        None
+                    } else {
+                        Some(format!("{} != initial {} (tolerance {})", value, initial, tolerance))
+                    }
+                }
+                Rule::Bounded { quantity, min, max } => {
+                    let value = quantity(state);
+                    if *min <= value && value <= *max {
+                        None
+                    } else {
+                        Some(format!("{} is out of [{}, {}]", value, min, max))
+                    }
+                }
+                Rule::Holds(condition) => match condition(state) {
+                    true => None,
+                    false => Some("condition does not hold".to_string()),
+                },
+            };
+            if let Some(violation) = violation {
+                return Err(format_err!(
+                    "invariant \"{}\" violated at step {}: {}",
+                    name,
+                    step,
+                    violation
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// デバッグビルドで`step`が`interval`の倍数のときだけ`check`する
+    pub fn verify(&mut self, step: u64, state: &T) -> Result<(), failure::Error> {
+        if cfg!(debug_assertions) && step.is_multiple_of(self.interval) {
+            self.check(step, state)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// `state`を`step`で`steps`回進め、デバッグビルドでは`interval`ステップごとに性質を調べる
+    ///
+    /// 最初の呼び出しでは進める前の状態も調べる。違反があればそこで止めてエラーを返す
+    pub fn run<F>(&mut self, state: &mut T, steps: usize, mut step: F) -> Result<(), failure::Error>
+    where
+        F: FnMut(&mut T),
+    {
+        if self.steps == 0 {
+            self.verify(0, state)?;
+        }
+        for _ in 0..steps {
+            step(state);
+            self.steps += 1;
+            self.verify(self.steps, state)?;
+        }
+        Ok(())
+    }
+}
+
+impl<A: Copy + Into<f32>> Invariants<Matrix<A>> {
+    /// セルの値の合計が保存される(質量保存)という性質を加える
+    pub fn conserved_sum(self, tolerance: f64) -> Invariants<Matrix<A>> {
+        self.conserved("sum", tolerance, |cells: &Matrix<A>| {
+            cells.iter().map(|&e| f64::from(e.into())).sum()
+        })
+    }
+
+    /// すべてのセルの値が[`min`, `max`]に入っているという性質を加える。セルの最小値と最大値を調べる
+    pub fn values_within(self, min: f32, max: f32) -> Invariants<Matrix<A>> {
+        let (low, high) = (f64::from(min), f64::from(max));
+        self.bounded("min value", low, high, |cells: &Matrix<A>| {
+            cells.iter().map(|&e| f64::from(e.into())).fold(f64::INFINITY, f64::min)
+        })
+        .bounded("max value", low, high, |cells: &Matrix<A>| {
+            cells
+                .iter()
+                .map(|&e| f64::from(e.into()))
+                .fold(f64::NEG_INFINITY, f64::max)
+        })
+    }
+}
+
+/// 進めるたびに盤面の性質を調べる`Stepper`
+///
+/// デバッグビルドでは`interval`ステップごとに止めて盤面を調べ、違反があれば`step`がエラーを返す。
+/// リリースビルドではそのまま進める
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::backend::{Backend, CpuGrayScott, Stepper};
+/// use my_alife::algorithm::gray_scott::{initial_matrix, GrayScott};
+/// use my_alife::algorithm::invariant::{Checked, Invariants};
+///
+/// let stepper = CpuGrayScott::new(Backend::Naive, &initial_matrix(), GrayScott::new(0.04, 0.06)).unwrap();
+/// // 初期値のノイズで1を少し超えるセルがある
+/// let invariants = Invariants::new(16).values_within(0.0, 1.5);
+/// let mut checked = Checked::new(stepper, invariants);
+/// checked.step(64).unwrap();
+/// assert_eq!(checked.snapshot().unwrap().dim(), (256, 256));
+/// ```
+pub struct Checked<S: Stepper> {
+    stepper: S,
+    invariants: Invariants<Matrix<S::Cell>>,
+}
+
+impl<S: Stepper> Checked<S> {
+    /// `stepper`を進めるたびに`invariants`を調べる
+    pub fn new(stepper: S, invariants: Invariants<Matrix<S::Cell>>) -> Checked<S> {
+        Checked { stepper, invariants }
+    }
+
+    /// 包んでいる`Stepper`
+    pub fn stepper(&self) -> &S {
+        &self.stepper
+    }
+
+    /// 調べている性質
+    pub fn invariants(&self) -> &Invariants<Matrix<S::Cell>> {
+        &self.invariants
+    }
+
+    /// 包んでいる`Stepper`を返す
+    pub fn into_inner(self) -> S {
+        self.stepper
+    }
+}
+
+impl<S: Stepper> Stepper for Checked<S> {
+    type Cell = S::Cell;
+
+    fn backend(&self) -> Backend {
+        self.stepper.backend()
+    }
+
+    fn step(&mut self, steps: usize) -> Result<(), failure::Error> {
+        if !cfg!(debug_assertions) {
+            self.invariants.steps += steps as u64;
+            return self.stepper.step(steps);
+        }
+        if self.invariants.steps == 0 {
+            self.invariants.check(0, self.stepper.snapshot()?)?;
+        }
+        let interval = self.invariants.interval;
+        let end = self.invariants.steps + steps as u64;
+        while self.invariants.steps < end {
+            // 次に調べるステップまでまとめて進める
+            let next = ((self.invariants.steps / interval + 1) * interval).min(end);
+            self.stepper.step((next - self.invariants.steps) as usize)?;
+            self.invariants.steps = next;
+            if next.is_multiple_of(interval) {
+                self.invariants.check(next, self.stepper.snapshot()?)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn snapshot(&mut self) -> Result<&Matrix<S::Cell>, failure::Error> {
+        self.stepper.snapshot()
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f32) -> bool {
+        self.stepper.set_parameter(name, value)
+    }
+}
@@ -0,0 +1,172 @@
+use algorithm::evolution::{GaParams, GeneticAlgorithm, Genome};
+use rand::{Rng, XorShiftRng};
+use std::cmp::Ordering;
+use std::thread;
+
+/// 島の間で個体が移住する経路
+#[derive(Debug, Clone, PartialEq)]
+pub enum Topology {
+    /// 島iから島i+1へ一方向に移住する
+    Ring,
+    /// すべての島の間を移住する
+    FullyConnected,
+    /// 移住しない
+    Isolated,
+    /// 島ごとの移住先
+    Custom(Vec<Vec<usize>>),
+}
+
+impl Topology {
+    /// 島`island`からの移住先
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::algorithm::island::Topology;
+    ///
+    /// assert_eq!(Topology::Ring.targets(3, 4), vec![0]);
+    /// assert_eq!(Topology::FullyConnected.targets(1, 3), vec![0, 2]);
+    /// ```
+    pub fn targets(&self, island: usize, islands: usize) -> Vec<usize> {
+        match *self {
+            Topology::Ring if islands > 1 => vec![(island + 1) % islands],
+            Topology::FullyConnected => (0..islands).filter(|&i| i != island).collect(),
+            Topology::Custom(ref targets) => targets.get(island).cloned().unwrap_or_default(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// 移住のパラメータ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Migration {
+    /// 移住させる間隔(世代数)
+    pub interval: usize,
+    /// 1回に移住先へ送る、適応度の高い個体の数
+    pub migrants: usize,
+}
+
+impl Default for Migration {
+    fn default() -> Migration {
+        Migration {
+            interval: 10,
+            migrants: 2,
+        }
+    }
+}
+
+/// 島ごとの部分集団を別々のスレッドで進化させ、ときどき個体を移住させる島モデル
+///
+/// # Example
+/// ```
+/// extern crate my_alife;
+/// extern crate rand;
+///
+/// use my_alife::algorithm::evolution::{random_genome, GaParams};
+/// use my_alife::algorithm::island::{IslandModel, Migration, Topology};
+///
+/// let mut rng = rand::thread_rng();
+/// let islands = (0..4)
+///     .map(|_| (0..20).map(|_| random_genome(3, 1.0, &mut rng)).collect())
+///     .collect();
+/// let mut model = IslandModel::new(islands, GaParams::default(), Topology::Ring, Migration::default(), &mut rng);
+/// let fitness = |genes: &Vec<f32>| -genes.iter().map(|g| g * g).sum::<f32>();
+/// model.run(30, &fitness);
+/// assert_eq!(model.generation(), 30);
+/// // 移住のたびに島ごとの最良の適応度が記録される
+/// assert!(model.history().iter().all(|island| island.len() == 3));
+/// assert!(model.best().unwrap().1 > -0.5);
+/// ```
+pub struct IslandModel<G> {
+    islands: Vec<GeneticAlgorithm<G>>,
+    rngs: Vec<XorShiftRng>,
+    topology: Topology,
+    migration: Migration,
+    generation: usize,
+    history: Vec<Vec<(usize, f32)>>,
+}
+
+impl<G: Genome + Send> IslandModel<G> {
+    /// 島ごとの初期集団`islands`から始める。島ごとの乱数は`rng`から作る
+    pub fn new<R: Rng>(
+        islands: Vec<Vec<G>>,
+        params: GaParams,
+        topology: Topology,
+        migration: Migration,
+        rng: &mut R,
+    ) -> IslandModel<G> {
+        let history = vec![Vec::new(); islands.len()];
+        IslandModel {
+            rngs: islands.iter().map(|_| rng.gen()).collect(),
+            islands: islands
+                .into_iter()
+                .map(|population| GeneticAlgorithm::new(population, params))
+                .collect(),
+            topology,
+            migration,
+            generation: 0,
+            history,
+        }
+    }
+
+    /// 島ごとの遺伝的アルゴリズム
+    pub fn islands(&self) -> &[GeneticAlgorithm<G>] {
+        &self.islands
+    }
+
+    /// 世代数
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// 島ごとの、移住の直前の(世代数, 最良の適応度)
+    pub fn history(&self) -> &[Vec<(usize, f32)>] {
+        &self.history
+    }
+
+    /// すべての島で最も適応度の高い個体とその適応度
+    pub fn best(&self) -> Option<(&G, f32)> {
+        self.islands
+            .iter()
+            .filter_map(|island| island.best())
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+    }
+
+    /// `generations`世代進める。`migration.interval`世代ごとに、島をスレッドで並列に進めてから移住させる
+    pub fn run<F: Fn(&G) -> f32 + Sync>(&mut self, generations: usize, fitness: &F) {
+        let mut remaining = generations;
+        while remaining > 0 {
+            let epoch = self.migration.interval.max(1).min(remaining);
+            thread::scope(|scope| {
+                for (island, rng) in self.islands.iter_mut().zip(self.rngs.iter_mut()) {
+                    scope.spawn(move || island.run(epoch, fitness, rng));
+                }
+            });
+            remaining -= epoch;
+            self.generation += epoch;
+            self.migrate();
+        }
+    }
+
+    fn migrate(&mut self) {
+        let count = self.islands.len();
+        let emigrants: Vec<Vec<(G, f32)>> = self
+            .islands
+            .iter()
+            .map(|island| island.emigrants(self.migration.migrants))
+            .collect();
+        for (i, island) in self.islands.iter().enumerate() {
+            if let Some((_, best)) = island.best() {
+                self.history[i].push((self.generation, best));
+            }
+        }
+        let mut arrivals: Vec<Vec<(G, f32)>> = (0..count).map(|_| Vec::new()).collect();
+        for (from, migrants) in emigrants.iter().enumerate() {
+            for to in self.topology.targets(from, count) {
+                arrivals[to].extend(migrants.iter().cloned());
+            }
+        }
+        for (island, migrants) in self.islands.iter_mut().zip(arrivals) {
+            island.immigrate(migrants);
+        }
+    }
+}
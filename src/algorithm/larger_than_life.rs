@@ -0,0 +1,151 @@
+use algorithm::game_of_life::{ALIVE, DEAD};
+use algorithm::neighborhood::{ExtendedNeighborhood, NeighborhoodShape};
+use failure;
+use ndarray::Zip;
+use std::fmt;
+use std::str::FromStr;
+use visualizer::Matrix;
+
+/// 半径の大きな近傍で、生きたセルの数が範囲に入るかどうかで次の状態を決めるLarger than Life(LtL)のルール
+///
+/// Gollyと同じ`R5,C0,M1,S34..58,B34..45,NM`の形式で表す。
+/// Rは半径、Cは状態の数(0か2ならば2状態)、Mは中心のセルを数えるかどうか、
+/// SとBは生存と誕生の範囲、Nは近傍の形(Mは正方形、Nは菱形、Cは円)
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::larger_than_life::LtlRule;
+///
+/// let bosco: LtlRule = "R5,C0,M1,S34..58,B34..45,NM".parse().unwrap();
+/// assert_eq!(bosco, LtlRule::bosco());
+/// assert_eq!(bosco.to_string(), "R5,C0,M1,S34..58,B34..45,NM");
+/// assert!("R5,S34..58".parse::<LtlRule>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LtlRule {
+    /// 近傍
+    pub neighborhood: ExtendedNeighborhood,
+    /// 状態の数。3以上ならば生き残れなかったセルはGenerationsと同じように減衰する
+    pub states: u8,
+    /// 生き残る生きた近傍の数の範囲(両端を含む)
+    pub survival: (u32, u32),
+    /// 誕生する生きた近傍の数の範囲(両端を含む)
+    pub birth: (u32, u32),
+}
+
+impl LtlRule {
+    /// 滑るように動くパターン(Bosco's bug)を持つBoscoのルール
+    pub fn bosco() -> LtlRule {
+        LtlRule {
+            neighborhood: ExtendedNeighborhood::new(NeighborhoodShape::Moore, 5, true),
+            states: 2,
+            survival: (34, 58),
+            birth: (34, 45),
+        }
+    }
+
+    /// 近傍の多数派に揃う、模様が粗くなっていくルール
+    pub fn majority() -> LtlRule {
+        LtlRule {
+            neighborhood: ExtendedNeighborhood::new(NeighborhoodShape::Moore, 4, true),
+            states: 2,
+            survival: (41, 81),
+            birth: (41, 81),
+        }
+    }
+
+    /// 格子状の模様(waffle)ができるルール
+    pub fn waffle() -> LtlRule {
+        LtlRule {
+            neighborhood: ExtendedNeighborhood::new(NeighborhoodShape::Moore, 7, true),
+            states: 2,
+            survival: (100, 200),
+            birth: (75, 170),
+        }
+    }
+
+    /// 1ステップ進める(周期境界条件)
+    pub fn step(&self, cells: &Matrix<u8>) -> Matrix<u8> {
+        let alive = self.neighborhood.count(cells, ALIVE);
+        let in_range = |n: u32, (min, max): (u32, u32)| min <= n && n <= max;
+        let mut next = cells.clone();
+        Zip::from(&mut next).and(&alive).apply(|state, &n| {
+            *state = match *state {
+                DEAD if in_range(n, self.birth) => ALIVE,
+                DEAD => DEAD,
+                ALIVE if in_range(n, self.survival) => ALIVE,
+                state => (state + 1) % self.states.max(2),
+            }
+        });
+        next
+    }
+}
+
+impl fmt::Display for LtlRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let shape = match self.neighborhood.shape {
+            NeighborhoodShape::Moore => 'M',
+            NeighborhoodShape::VonNeumann => 'N',
+            NeighborhoodShape::Circular => 'C',
+        };
+        write!(
+            f,
+            "R{},C{},M{},S{}..{},B{}..{},N{}",
+            self.neighborhood.radius,
+            if self.states == 2 { 0 } else { self.states },
+            self.neighborhood.include_center as u8,
+            self.survival.0,
+            self.survival.1,
+            self.birth.0,
+            self.birth.1,
+            shape
+        )
+    }
+}
+
+fn parse_range(s: &str) -> Result<(u32, u32), failure::Error> {
+    let mut bounds = s.splitn(2, "..");
+    match (bounds.next(), bounds.next()) {
+        (Some(min), Some(max)) => Ok((min.parse()?, max.parse()?)),
+        _ => Err(format_err!("range \"{}\" should look like 34..58", s)),
+    }
+}
+
+impl FromStr for LtlRule {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<LtlRule, failure::Error> {
+        let (mut radius, mut states, mut middle, mut survival, mut birth, mut shape) =
+            (None, 0, false, None, None, NeighborhoodShape::Moore);
+        for part in s.trim().split(',').map(|part| part.trim()) {
+            let mut chars = part.chars();
+            let tag = chars.next().map(|c| c.to_ascii_uppercase());
+            let body = chars.as_str();
+            match tag {
+                Some('R') => radius = Some(body.parse()?),
+                Some('C') => states = body.parse()?,
+                Some('M') => middle = body == "1",
+                Some('S') => survival = Some(parse_range(body)?),
+                Some('B') => birth = Some(parse_range(body)?),
+                Some('N') => {
+                    shape = match body {
+                        "M" => NeighborhoodShape::Moore,
+                        "N" => NeighborhoodShape::VonNeumann,
+                        "C" => NeighborhoodShape::Circular,
+                        _ => return Err(format_err!("unknown neighborhood \"{}\" in rule \"{}\"", body, s)),
+                    }
+                }
+                _ => return Err(format_err!("unknown part \"{}\" in rule \"{}\"", part, s)),
+            }
+        }
+        match (radius, survival, birth) {
+            (Some(radius), Some(survival), Some(birth)) => Ok(LtlRule {
+                neighborhood: ExtendedNeighborhood::new(shape, radius, middle),
+                states: states.max(2),
+                survival,
+                birth,
+            }),
+            _ => Err(format_err!("rule \"{}\" needs R, S and B", s)),
+        }
+    }
+}
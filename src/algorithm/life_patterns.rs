@@ -0,0 +1,320 @@
+use algorithm::game_of_life::{step, ALIVE, DEAD};
+use ndarray::Array2;
+use std::collections::{BTreeMap, HashMap};
+use visualizer::Matrix;
+
+/// パターンの振る舞い
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PatternKind {
+    /// 変化しない(固定物体)
+    StillLife,
+    /// 同じ場所で周期`period`で振動する(振動子)
+    Oscillator {
+        /// 周期
+        period: usize,
+    },
+    /// 周期`period`ごとに(`dx`, `dy`)だけ移動する(宇宙船)
+    Spaceship {
+        /// 周期
+        period: usize,
+        /// 1周期で右に進むセル数
+        dx: isize,
+        /// 1周期で下に進むセル数
+        dy: isize,
+    },
+    /// 調べた周期の範囲では元に戻らない
+    Unknown,
+}
+
+/// 盤面から見つけたパターン
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectedPattern {
+    /// パターンを囲む長方形の左上の行
+    pub row: usize,
+    /// パターンを囲む長方形の左上の列
+    pub col: usize,
+    /// パターンを囲む長方形の範囲のセル
+    pub cells: Matrix<u8>,
+    /// 振る舞い
+    pub kind: PatternKind,
+    /// 既知のパターンであればその名前
+    pub name: Option<String>,
+}
+
+impl DetectedPattern {
+    /// 既知のパターンであればその名前、そうでなければ振る舞いを表す文字列
+    pub fn label(&self) -> String {
+        if let Some(ref name) = self.name {
+            return name.clone();
+        }
+        match self.kind {
+            PatternKind::StillLife => "still life".to_string(),
+            PatternKind::Oscillator { period } => format!("p{} oscillator", period),
+            PatternKind::Spaceship { period, .. } => format!("p{} spaceship", period),
+            PatternKind::Unknown => "unknown".to_string(),
+        }
+    }
+}
+
+/// `.`を死んだセル、それ以外を生きたセルとして、行ごとに書いたパターンを読む
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::life_patterns::parse_pattern;
+///
+/// let glider = parse_pattern(".O.\n..O\nOOO");
+/// assert_eq!(glider.dim(), (3, 3));
+/// assert_eq!(glider[[0, 1]], 1);
+/// ```
+pub fn parse_pattern(text: &str) -> Matrix<u8> {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+    let cols = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    let mut cells = Array2::zeros((lines.len(), cols));
+    for (row, line) in lines.iter().enumerate() {
+        for (col, c) in line.chars().enumerate() {
+            cells[[row, col]] = if c == '.' { DEAD } else { ALIVE };
+        }
+    }
+    cells
+}
+
+/// 回転と鏡映(8通り)で同じになるパターンが同じ値になるように符号化する
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::life_patterns::{canonical, parse_pattern};
+///
+/// let glider = parse_pattern(".O.\n..O\nOOO");
+/// let mirrored = parse_pattern(".O.\nO..\nOOO");
+/// assert_eq!(canonical(&glider), canonical(&mirrored));
+/// ```
+pub fn canonical(pattern: &Matrix<u8>) -> Vec<u8> {
+    symmetries(&trim(pattern)).iter().map(encode).min().unwrap_or_default()
+}
+
+// 大きさと各セルを並べたもの
+fn encode(pattern: &Matrix<u8>) -> Vec<u8> {
+    let (rows, cols) = pattern.dim();
+    let mut code = vec![rows as u8, cols as u8];
+    code.extend(pattern.iter().map(|&e| (e == ALIVE) as u8));
+    code
+}
+
+fn symmetries(pattern: &Matrix<u8>) -> Vec<Matrix<u8>> {
+    let mut result = Vec::with_capacity(8);
+    let mut current = pattern.clone();
+    for _ in 0..4 {
+        let mirrored = current.slice(s![.., ..;-1]).to_owned();
+        // 90度回転 = 転置してから左右反転
+        let rotated = current.t().slice(s![.., ..;-1]).to_owned();
+        result.push(current);
+        result.push(mirrored);
+        current = rotated;
+    }
+    result
+}
+
+// 生きたセルを囲む最小の長方形に切り詰め、その左上の位置も返す
+fn trim_with_offset(pattern: &Matrix<u8>) -> (Matrix<u8>, (usize, usize)) {
+    let alive: Vec<(usize, usize)> = pattern
+        .indexed_iter()
+        .filter(|&(_, &e)| e == ALIVE)
+        .map(|(cell, _)| cell)
+        .collect();
+    if alive.is_empty() {
+        return (Array2::zeros((0, 0)), (0, 0));
+    }
+    let top = alive.iter().map(|c| c.0).min().unwrap();
+    let bottom = alive.iter().map(|c| c.0).max().unwrap();
+    let left = alive.iter().map(|c| c.1).min().unwrap();
+    let right = alive.iter().map(|c| c.1).max().unwrap();
+    (
+        pattern.slice(s![top..bottom + 1, left..right + 1]).to_owned(),
+        (top, left),
+    )
+}
+
+fn trim(pattern: &Matrix<u8>) -> Matrix<u8> {
+    trim_with_offset(pattern).0
+}
+
+/// 孤立したパターン`pattern`を最大`max_period`世代進めて、振る舞いを調べる
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::life_patterns::{classify, parse_pattern, PatternKind};
+///
+/// assert_eq!(classify(&parse_pattern("OO\nOO"), 8), PatternKind::StillLife);
+/// assert_eq!(classify(&parse_pattern("OOO"), 8), PatternKind::Oscillator { period: 2 });
+/// assert_eq!(
+///     classify(&parse_pattern(".O.\n..O\nOOO"), 8),
+///     PatternKind::Spaceship { period: 4, dx: 1, dy: 1 }
+/// );
+/// ```
+pub fn classify(pattern: &Matrix<u8>, max_period: usize) -> PatternKind {
+    let (pattern, _) = trim_with_offset(pattern);
+    let (rows, cols) = pattern.dim();
+    if rows == 0 {
+        return PatternKind::Unknown;
+    }
+    // 宇宙船は1世代に最大1セルしか進まないので、この余白があれば周期境界で自分とぶつからない
+    let pad = max_period + 2;
+    let mut space = Array2::zeros((rows + 2 * pad, cols + 2 * pad));
+    space.slice_mut(s![pad..pad + rows, pad..pad + cols]).assign(&pattern);
+    for period in 1..=max_period {
+        space = step(&space);
+        let (current, (top, left)) = trim_with_offset(&space);
+        if current == pattern {
+            let (dx, dy) = (left as isize - pad as isize, top as isize - pad as isize);
+            return if dx == 0 && dy == 0 {
+                if period == 1 {
+                    PatternKind::StillLife
+                } else {
+                    PatternKind::Oscillator { period }
+                }
+            } else {
+                PatternKind::Spaceship { period, dx, dy }
+            };
+        }
+    }
+    PatternKind::Unknown
+}
+
+/// 名前のついたパターンの一覧。振動子や宇宙船はすべての位相を登録する
+pub struct PatternCatalog {
+    names: HashMap<Vec<u8>, String>,
+}
+
+impl Default for PatternCatalog {
+    /// よく現れる固定物体、振動子、宇宙船を含む一覧
+    fn default() -> PatternCatalog {
+        let mut catalog = PatternCatalog { names: HashMap::new() };
+        let known = [
+            ("block", "OO\nOO"),
+            ("beehive", ".OO.\nO..O\n.OO."),
+            ("loaf", ".OO.\nO..O\n.O.O\n..O."),
+            ("boat", "OO.\nO.O\n.O."),
+            ("tub", ".O.\nO.O\n.O."),
+            ("pond", ".OO.\nO..O\nO..O\n.OO."),
+            ("ship", "OO.\nO.O\n.OO"),
+            ("blinker", "OOO"),
+            ("toad", ".OOO\nOOO."),
+            ("beacon", "OO..\nOO..\n..OO\n..OO"),
+            ("glider", ".O.\n..O\nOOO"),
+            ("lwss", ".O..O\nO....\nO...O\nOOOO."),
+        ];
+        for &(name, text) in &known {
+            catalog.register(name, &parse_pattern(text));
+        }
+        catalog
+    }
+}
+
+impl PatternCatalog {
+    /// パターン`pattern`を`name`として登録する。振動子や宇宙船であれば他の位相も登録する
+    pub fn register(&mut self, name: &str, pattern: &Matrix<u8>) {
+        let period = match classify(pattern, 32) {
+            PatternKind::Oscillator { period } | PatternKind::Spaceship { period, .. } => period,
+            _ => 1,
+        };
+        let pad = period + 2;
+        let (rows, cols) = pattern.dim();
+        let mut space = Array2::zeros((rows + 2 * pad, cols + 2 * pad));
+        space.slice_mut(s![pad..pad + rows, pad..pad + cols]).assign(pattern);
+        for _ in 0..period {
+            self.names.entry(canonical(&space)).or_insert_with(|| name.to_string());
+            space = step(&space);
+        }
+    }
+
+    /// `pattern`の名前
+    pub fn name(&self, pattern: &Matrix<u8>) -> Option<&str> {
+        self.names.get(&canonical(pattern)).map(|name| name.as_str())
+    }
+}
+
+/// Moore近傍(8近傍)で連結した生きたセルの塊ごとにパターンを取り出して分類する
+///
+/// 塊ごとに孤立させて調べるので、近くの塊と相互作用している場合は実際の振る舞いと異なることがある。
+/// 盤面の端をまたぐ塊は別々の塊として扱う
+///
+/// # Arguments
+/// * `cells` - 盤面
+/// * `catalog` - 名前を調べるための一覧
+/// * `max_period` - 調べる最大の周期
+pub fn detect(cells: &Matrix<u8>, catalog: &PatternCatalog, max_period: usize) -> Vec<DetectedPattern> {
+    let (rows, cols) = cells.dim();
+    let mut visited = Array2::from_elem((rows, cols), false);
+    let mut patterns = Vec::new();
+    for ((row, col), &e) in cells.indexed_iter() {
+        if e != ALIVE || visited[[row, col]] {
+            continue;
+        }
+        let mut component = vec![(row, col)];
+        let mut stack = vec![(row, col)];
+        visited[[row, col]] = true;
+        while let Some((r, c)) = stack.pop() {
+            for nr in r.saturating_sub(1)..(r + 2).min(rows) {
+                for nc in c.saturating_sub(1)..(c + 2).min(cols) {
+                    if cells[[nr, nc]] == ALIVE && !visited[[nr, nc]] {
+                        visited[[nr, nc]] = true;
+                        component.push((nr, nc));
+                        stack.push((nr, nc));
+                    }
+                }
+            }
+        }
+        let top = component.iter().map(|c| c.0).min().unwrap();
+        let bottom = component.iter().map(|c| c.0).max().unwrap();
+        let left = component.iter().map(|c| c.1).min().unwrap();
+        let right = component.iter().map(|c| c.1).max().unwrap();
+        let mut pattern = Array2::zeros((bottom - top + 1, right - left + 1));
+        for &(r, c) in &component {
+            pattern[[r - top, c - left]] = ALIVE;
+        }
+        patterns.push(DetectedPattern {
+            row: top,
+            col: left,
+            kind: classify(&pattern, max_period),
+            name: catalog.name(&pattern).map(|name| name.to_string()),
+            cells: pattern,
+        });
+    }
+    patterns
+}
+
+/// 盤面にあるパターンの種類ごとの数
+///
+/// 毎世代呼んで記録すれば、パターンの数の時間変化がわかる
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::life_patterns::{census, parse_pattern, PatternCatalog};
+///
+/// let cells = parse_pattern(
+///     "..........
+///      .OO.......
+///      .OO...OOO.
+///      ..........
+///      ..........
+///      ...O......
+///      ....O.....
+///      ..OOO.....
+///      ..........",
+/// );
+/// let counts = census(&cells, &PatternCatalog::default(), 8);
+/// assert_eq!(counts["block"], 1);
+/// assert_eq!(counts["blinker"], 1);
+/// assert_eq!(counts["glider"], 1);
+/// ```
+pub fn census(cells: &Matrix<u8>, catalog: &PatternCatalog, max_period: usize) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for pattern in detect(cells, catalog, max_period) {
+        *counts.entry(pattern.label()).or_insert(0) += 1;
+    }
+    counts
+}
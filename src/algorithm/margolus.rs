@@ -0,0 +1,216 @@
+use ndarray::Array2;
+use visualizer::Matrix;
+
+/// 2×2のブロックの状態を、左上を1、右上を2、左下を4、右下を8のbitとして表した値から、次の状態への表
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::margolus::BlockRule;
+///
+/// let bbm = BlockRule::billiard_ball();
+/// // 1つだけの粒子は対角に進む
+/// assert_eq!(bbm.apply(0b0001), 0b1000);
+/// // 対角にある2つの粒子は衝突して、もう一方の対角に曲がる
+/// assert_eq!(bbm.apply(0b1001), 0b0110);
+/// assert!(bbm.is_reversible() && bbm.is_conservative());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockRule {
+    table: [u8; 16],
+}
+
+const TOP_LEFT: u8 = 1;
+const TOP_RIGHT: u8 = 2;
+const BOTTOM_LEFT: u8 = 4;
+const BOTTOM_RIGHT: u8 = 8;
+
+// 180度回転したブロック
+fn rotate_half(block: u8) -> u8 {
+    (block & TOP_LEFT) << 3 | (block & TOP_RIGHT) << 1 | (block & BOTTOM_LEFT) >> 1 | (block & BOTTOM_RIGHT) >> 3
+}
+
+impl BlockRule {
+    /// 表から作る。値は下位4bitだけを使う
+    pub fn new(table: [u8; 16]) -> BlockRule {
+        let mut masked = [0; 16];
+        for (m, t) in masked.iter_mut().zip(table.iter()) {
+            *m = t & 0xf;
+        }
+        BlockRule { table: masked }
+    }
+
+    /// 各ブロックの状態から次の状態を返す関数から作る
+    pub fn from_fn<F: Fn(u8) -> u8>(f: F) -> BlockRule {
+        let mut table = [0; 16];
+        for (block, next) in table.iter_mut().enumerate() {
+            *next = f(block as u8);
+        }
+        BlockRule::new(table)
+    }
+
+    /// MargolusのBilliard Ball Model。ビリヤードの球の衝突を模して、論理回路を作ることができる
+    ///
+    /// 1つだけの粒子は対角に進み、対角にある2つの粒子はもう一方の対角に曲がる。それ以外は変化しない(壁になる)
+    pub fn billiard_ball() -> BlockRule {
+        BlockRule::from_fn(|block| match block {
+            0b0001 | 0b0010 | 0b0100 | 0b1000 => rotate_half(block),
+            0b1001 => 0b0110,
+            0b0110 => 0b1001,
+            _ => block,
+        })
+    }
+
+    /// Margolus近傍で書いたHPP格子気体。対角にある2つの粒子だけが曲がり、それ以外はすべて対角に進む
+    pub fn hpp_gas() -> BlockRule {
+        BlockRule::from_fn(|block| match block {
+            0b1001 => 0b0110,
+            0b0110 => 0b1001,
+            _ => rotate_half(block),
+        })
+    }
+
+    /// 空のブロックと埋まったブロックだけを反転するTron
+    pub fn tron() -> BlockRule {
+        BlockRule::from_fn(|block| match block {
+            0b0000 => 0b1111,
+            0b1111 => 0b0000,
+            _ => block,
+        })
+    }
+
+    /// ブロックの状態`block`の次の状態
+    pub fn apply(&self, block: u8) -> u8 {
+        self.table[(block & 0xf) as usize]
+    }
+
+    /// 表が全単射かどうか。全単射ならば逆向きに時間発展できる
+    pub fn is_reversible(&self) -> bool {
+        let mut seen = [false; 16];
+        for &next in &self.table {
+            seen[next as usize] = true;
+        }
+        seen.iter().all(|&s| s)
+    }
+
+    /// 粒子(1のセル)の数を保存するかどうか
+    pub fn is_conservative(&self) -> bool {
+        self.table
+            .iter()
+            .enumerate()
+            .all(|(block, next)| (block as u8).count_ones() == next.count_ones())
+    }
+
+    /// 逆向きの表。全単射でなければ`None`
+    pub fn inverse(&self) -> Option<BlockRule> {
+        if !self.is_reversible() {
+            return None;
+        }
+        let mut table = [0; 16];
+        for (block, &next) in self.table.iter().enumerate() {
+            table[next as usize] = block as u8;
+        }
+        Some(BlockRule::new(table))
+    }
+}
+
+/// 盤面を2×2のブロックに分けて、ブロックごとに更新するセル・オートマトン(Margolus近傍)
+///
+/// ブロックの区切り方を1ステップごとに縦横1セルずつずらす(周期境界条件)。
+/// 可逆なルールならば`step_back`で元の状態に戻せる
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::game_of_life::random_cells;
+/// use my_alife::algorithm::margolus::{BlockRule, Margolus};
+///
+/// let cells = random_cells((32, 32), 0.2);
+/// let mut gas = Margolus::new(cells.clone(), BlockRule::hpp_gas());
+/// for _ in 0..10 {
+///     gas.step();
+/// }
+/// assert_eq!(gas.particles(), cells.iter().filter(|&&e| e == 1).count());
+/// for _ in 0..10 {
+///     gas.step_back().unwrap();
+/// }
+/// assert_eq!(gas.cells(), &cells);
+/// ```
+pub struct Margolus {
+    cells: Matrix<u8>,
+    rule: BlockRule,
+    inverse: Option<BlockRule>,
+    time: usize,
+}
+
+impl Margolus {
+    /// `cells`から始めるMargolus近傍のセル・オートマトンを生成する
+    ///
+    /// # Panics
+    /// 盤面の行数か列数が奇数のとき
+    pub fn new(cells: Matrix<u8>, rule: BlockRule) -> Margolus {
+        let (rows, cols) = cells.dim();
+        assert!(
+            rows % 2 == 0 && cols % 2 == 0,
+            "Margolus neighborhood needs an even-sized grid"
+        );
+        Margolus {
+            cells,
+            rule,
+            inverse: rule.inverse(),
+            time: 0,
+        }
+    }
+
+    /// 現在の状態
+    pub fn cells(&self) -> &Matrix<u8> {
+        &self.cells
+    }
+
+    /// これまでに進めたステップ数
+    pub fn time(&self) -> usize {
+        self.time
+    }
+
+    /// 粒子(1のセル)の数
+    pub fn particles(&self) -> usize {
+        self.cells.iter().filter(|&&e| e != 0).count()
+    }
+
+    /// 1ステップ進める
+    pub fn step(&mut self) {
+        let rule = self.rule;
+        self.apply(&rule, self.time % 2);
+        self.time += 1;
+    }
+
+    /// 1ステップ戻す。ルールが可逆でないか、最初の状態ならば`None`
+    pub fn step_back(&mut self) -> Option<()> {
+        let inverse = self.inverse?;
+        if self.time == 0 {
+            return None;
+        }
+        self.time -= 1;
+        self.apply(&inverse, self.time % 2);
+        Some(())
+    }
+
+    fn apply(&mut self, rule: &BlockRule, offset: usize) {
+        let (rows, cols) = self.cells.dim();
+        let mut next = Array2::zeros((rows, cols));
+        for block_row in 0..rows / 2 {
+            for block_col in 0..cols / 2 {
+                let r = [(2 * block_row + offset) % rows, (2 * block_row + 1 + offset) % rows];
+                let c = [(2 * block_col + offset) % cols, (2 * block_col + 1 + offset) % cols];
+                let corners = [(r[0], c[0]), (r[0], c[1]), (r[1], c[0]), (r[1], c[1])];
+                let block = corners
+                    .iter()
+                    .enumerate()
+                    .fold(0, |block, (bit, &cell)| block | ((self.cells[cell] != 0) as u8) << bit);
+                let block = rule.apply(block);
+                for (bit, &cell) in corners.iter().enumerate() {
+                    next[cell] = block >> bit & 1;
+                }
+            }
+        }
+        self.cells = next;
+    }
+}
@@ -0,0 +1,335 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::mem;
+use visualizer::Matrix;
+
+/// 使っているメモリの見積もり(byte)を返す
+///
+/// 要素が持つヒープ上のデータも含めるが、アロケーターの管理領域などは含まない
+pub trait MemoryUsage {
+    /// 使っているメモリのbyte数
+    fn memory_usage(&self) -> usize;
+}
+
+impl<A> MemoryUsage for Matrix<A> {
+    fn memory_usage(&self) -> usize {
+        self.len() * mem::size_of::<A>()
+    }
+}
+
+impl<T: MemoryUsage> MemoryUsage for Vec<T> {
+    fn memory_usage(&self) -> usize {
+        self.iter().map(MemoryUsage::memory_usage).sum::<usize>() + (self.capacity() - self.len()) * mem::size_of::<T>()
+    }
+}
+
+impl<T: MemoryUsage> MemoryUsage for VecDeque<T> {
+    fn memory_usage(&self) -> usize {
+        self.iter().map(MemoryUsage::memory_usage).sum()
+    }
+}
+
+impl MemoryUsage for u64 {
+    fn memory_usage(&self) -> usize {
+        mem::size_of::<u64>()
+    }
+}
+
+impl<T: MemoryUsage, U: MemoryUsage> MemoryUsage for (T, U) {
+    fn memory_usage(&self) -> usize {
+        self.0.memory_usage() + self.1.memory_usage()
+    }
+}
+
+/// byte数を`1.50 MiB`のように読みやすくする
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::memory::format_bytes;
+///
+/// assert_eq!(format_bytes(512), "512 B");
+/// assert_eq!(format_bytes(1536), "1.50 KiB");
+/// assert_eq!(format_bytes(256 * 256 * 4 * 2), "512.00 KiB");
+/// assert_eq!(format_bytes(3 << 30), "3.00 GiB");
+/// ```
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.2} {}", value, UNITS[unit])
+}
+
+/// 名前をつけた項目ごとのメモリ使用量の一覧
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use my_alife::algorithm::gray_scott::initial_matrix;
+/// use my_alife::algorithm::memory::{History, MemoryReport};
+///
+/// let uv = initial_matrix();
+/// let mut history = History::new(1 << 20);
+/// history.push(uv.clone());
+///
+/// let mut report = MemoryReport::new();
+/// report.add("state", &uv);
+/// report.add("history", &history);
+/// assert_eq!(report.total(), 2 * 256 * 256 * 4 * 2);
+/// assert!(report.to_string().contains("history"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    entries: Vec<(String, usize)>,
+}
+
+impl MemoryReport {
+    /// 空の一覧
+    pub fn new() -> MemoryReport {
+        MemoryReport::default()
+    }
+
+    /// `name`として`item`の使用量を加える
+    pub fn add<T: MemoryUsage + ?Sized>(&mut self, name: &str, item: &T) -> &mut MemoryReport {
+        self.entries.push((name.to_string(), item.memory_usage()));
+        self
+    }
+
+    /// (名前, byte数)の一覧
+    pub fn entries(&self) -> &[(String, usize)] {
+        &self.entries
+    }
+
+    /// 合計のbyte数
+    pub fn total(&self) -> usize {
+        self.entries.iter().map(|&(_, bytes)| bytes).sum()
+    }
+}
+
+impl fmt::Display for MemoryReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, bytes) in &self.entries {
+            writeln!(f, "{:<16} {:>12}", name, format_bytes(*bytes))?;
+        }
+        write!(f, "{:<16} {:>12}", "total", format_bytes(self.total()))
+    }
+}
+
+/// 最近の状態を覚えておく履歴。合計の使用量が`cap`を超えたら古いものから捨てる
+///
+/// 長く動かしても上限より多くのメモリを使わない。ただし最新の状態は`cap`より大きくても必ず覚える
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::algorithm::memory::{History, MemoryUsage};
+///
+/// // 1つ1KiBの状態を4KiBまで覚える
+/// let mut history = History::new(4096);
+/// for i in 0..10 {
+///     history.push(Array2::from_elem((16, 16), i as f32));
+/// }
+/// assert_eq!(history.len(), 4);
+/// assert_eq!(history.evicted(), 6);
+/// assert_eq!(history.memory_usage(), 4096);
+/// assert_eq!(history.get(0).unwrap()[[0, 0]], 6.0);
+/// assert_eq!(history.latest().unwrap()[[0, 0]], 9.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct History<T> {
+    states: VecDeque<T>,
+    cap: usize,
+    used: usize,
+    evicted: usize,
+}
+
+impl<T: MemoryUsage> History<T> {
+    /// 使用量の上限`cap`(byte)の空の履歴
+    pub fn new(cap: usize) -> History<T> {
+        History {
+            states: VecDeque::new(),
+            cap,
+            used: 0,
+            evicted: 0,
+        }
+    }
+
+    /// 最新の状態として加え、上限を超えた分だけ古いものを捨てる
+    pub fn push(&mut self, state: T) {
+        self.used += state.memory_usage();
+        self.states.push_back(state);
+        self.evict();
+    }
+
+    /// 上限を変える。小さくしたときはすぐに古いものを捨てる
+    pub fn set_cap(&mut self, cap: usize) {
+        self.cap = cap;
+        self.evict();
+    }
+
+    /// 使用量の上限
+    pub fn cap(&self) -> usize {
+        self.cap
+    }
+
+    /// 覚えている状態の数
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    /// 何も覚えていないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    /// 上限を超えて捨てた状態の数
+    pub fn evicted(&self) -> usize {
+        self.evicted
+    }
+
+    /// 古い方からi番目の状態
+    pub fn get(&self, i: usize) -> Option<&T> {
+        self.states.get(i)
+    }
+
+    /// 最新の状態
+    pub fn latest(&self) -> Option<&T> {
+        self.states.back()
+    }
+
+    /// 古い順に状態を返す
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.states.iter()
+    }
+
+    /// 古い方から`len`個だけ残して、それより新しい状態を捨てる
+    pub fn truncate(&mut self, len: usize) {
+        while self.states.len() > len {
+            let newest = self.states.pop_back().unwrap();
+            self.used -= newest.memory_usage();
+        }
+    }
+
+    /// 覚えている状態をすべて捨てる
+    pub fn clear(&mut self) {
+        self.states.clear();
+        self.used = 0;
+    }
+
+    fn evict(&mut self) {
+        while self.used > self.cap && self.states.len() > 1 {
+            let oldest = self.states.pop_front().unwrap();
+            self.used -= oldest.memory_usage();
+            self.evicted += 1;
+        }
+    }
+}
+
+impl<T> MemoryUsage for History<T> {
+    fn memory_usage(&self) -> usize {
+        self.used
+    }
+}
+
+/// 動画にするためのフレームをメモリに溜める。合計の使用量が`cap`を超えたら記録の間隔を2倍にする
+///
+/// 間隔を広げるときは溜めたフレームも1つおきに捨てるので、長く動かしても
+/// 最初から最後までを等間隔に記録したまま、使用量は上限に収まる
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::algorithm::memory::{MemoryUsage, Recorder};
+///
+/// // 1フレーム1KiBを8KiBまで溜める
+/// let mut recorder = Recorder::new(8192);
+/// for step in 0..100 {
+///     recorder.push(step, &Array2::from_elem((16, 16), step as f32));
+/// }
+/// assert_eq!(recorder.stride(), 16);
+/// assert!(recorder.memory_usage() <= 8192);
+/// let steps: Vec<u64> = recorder.frames().iter().map(|&(step, _)| step).collect();
+/// assert_eq!(steps, vec![0, 16, 32, 48, 64, 80, 96]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Recorder<T> {
+    frames: Vec<(u64, T)>,
+    cap: usize,
+    used: usize,
+    stride: u64,
+}
+
+impl<T: MemoryUsage + Clone> Recorder<T> {
+    /// 使用量の上限`cap`(byte)で、すべてのステップを記録する
+    pub fn new(cap: usize) -> Recorder<T> {
+        Recorder {
+            frames: Vec::new(),
+            cap,
+            used: 0,
+            stride: 1,
+        }
+    }
+
+    /// `step`が記録の間隔に当たっていれば`frame`を記録して、記録したかどうかを返す
+    pub fn push(&mut self, step: u64, frame: &T) -> bool {
+        if !step.is_multiple_of(self.stride) {
+            return false;
+        }
+        self.used += frame.memory_usage();
+        self.frames.push((step, frame.clone()));
+        while self.used > self.cap && self.frames.len() > 1 {
+            self.downsample();
+        }
+        step.is_multiple_of(self.stride)
+    }
+
+    /// 記録の間隔(ステップ数)
+    pub fn stride(&self) -> u64 {
+        self.stride
+    }
+
+    /// 記録した(ステップ, フレーム)
+    pub fn frames(&self) -> &[(u64, T)] {
+        &self.frames
+    }
+
+    /// 記録したフレームを取り出す
+    pub fn into_frames(self) -> Vec<(u64, T)> {
+        self.frames
+    }
+
+    // 間隔を2倍にして、新しい間隔に当たらないフレームを捨てる
+    fn downsample(&mut self) {
+        self.stride *= 2;
+        let stride = self.stride;
+        let mut used = 0;
+        self.frames.retain(|&(step, ref frame)| {
+            let keep = step.is_multiple_of(stride);
+            if keep {
+                used += frame.memory_usage();
+            }
+            keep
+        });
+        self.used = used;
+    }
+}
+
+impl<T> MemoryUsage for Recorder<T> {
+    fn memory_usage(&self) -> usize {
+        self.used
+    }
+}
@@ -0,0 +1,228 @@
+use algorithm::abm::{Agent, Model};
+use failure;
+use rand::Rng;
+
+// 保存則の確認で許す誤差(総エネルギーに対する相対誤差)
+const TOLERANCE: f32 = 1e-4;
+
+/// 代謝のパラメータ
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MetabolismParams {
+    /// 1ステップあたりの維持コスト
+    pub maintenance: f32,
+    /// このエネルギーを超えると繁殖できる
+    pub reproduction_threshold: f32,
+    /// 繁殖のときに子に渡すエネルギーの割合
+    pub offspring_share: f32,
+}
+
+/// エージェントに持たせるエネルギーの収支。出入りはすべて`EnergyLedger`に記録する
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::metabolism::{EnergyLedger, Metabolism, MetabolismParams};
+///
+/// let params = MetabolismParams { maintenance: 1.0, reproduction_threshold: 10.0, offspring_share: 0.5 };
+/// let mut ledger = EnergyLedger::new();
+/// let mut parent = Metabolism::new(8.0, params);
+/// let before = parent.energy();
+/// parent.intake(4.0, &mut ledger);
+/// parent.tick(&mut ledger);
+/// let child = parent.reproduce(&mut ledger).unwrap();
+/// assert_eq!((parent.energy(), child.energy()), (5.5, 5.5));
+/// // 増えた分と減った分が帳簿と合っている
+/// let flow = ledger.close(before, parent.energy() + child.energy()).unwrap();
+/// assert_eq!((flow.intake, flow.maintenance, flow.births), (4.0, 1.0, 1));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Metabolism {
+    energy: f32,
+    params: MetabolismParams,
+}
+
+impl Metabolism {
+    /// エネルギー`energy`を持った状態で生成する
+    pub fn new(energy: f32, params: MetabolismParams) -> Metabolism {
+        Metabolism { energy, params }
+    }
+
+    /// 持っているエネルギー
+    pub fn energy(&self) -> f32 {
+        self.energy
+    }
+
+    /// パラメータ
+    pub fn params(&self) -> &MetabolismParams {
+        &self.params
+    }
+
+    /// エネルギーが残っているかどうか
+    pub fn is_alive(&self) -> bool {
+        self.energy > 0.0
+    }
+
+    /// 外からエネルギー`amount`を取り込む
+    pub fn intake(&mut self, amount: f32, ledger: &mut EnergyLedger) {
+        self.energy += amount;
+        ledger.current.intake += amount;
+    }
+
+    /// 1ステップ分の維持コストを払う。持っている以上には払わない。払いきれずに尽きたら死ぬ
+    pub fn tick(&mut self, ledger: &mut EnergyLedger) {
+        let paid = self.params.maintenance.min(self.energy.max(0.0));
+        self.energy -= paid;
+        ledger.current.maintenance += paid;
+        if !self.is_alive() {
+            ledger.current.deaths += 1;
+        }
+    }
+
+    /// エネルギーが閾値を超えていれば、その一部を子に分けて返す
+    pub fn reproduce(&mut self, ledger: &mut EnergyLedger) -> Option<Metabolism> {
+        if self.energy <= self.params.reproduction_threshold {
+            return None;
+        }
+        let share = self.energy * self.params.offspring_share;
+        self.energy -= share;
+        ledger.current.births += 1;
+        Some(Metabolism::new(share, self.params))
+    }
+
+    /// 食べられるなどして死ぬ。残っていたエネルギーを返す
+    pub fn kill(&mut self, ledger: &mut EnergyLedger) -> f32 {
+        let remaining = self.energy;
+        ledger.record_death(remaining);
+        self.energy = 0.0;
+        remaining
+    }
+}
+
+/// 1ステップの間のエネルギーの流れ
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct EnergyFlow {
+    /// 外から取り込んだエネルギー
+    pub intake: f32,
+    /// 維持コストとして失われたエネルギー
+    pub maintenance: f32,
+    /// 死んだエージェントとともに失われたエネルギー
+    pub death_loss: f32,
+    /// 生まれた数
+    pub births: usize,
+    /// 死んだ数
+    pub deaths: usize,
+    /// ステップの終わりの総エネルギー
+    pub total: f32,
+}
+
+/// エネルギーの出入りを記録して、保存則を確かめる帳簿
+#[derive(Debug, Clone, Default)]
+pub struct EnergyLedger {
+    current: EnergyFlow,
+    history: Vec<EnergyFlow>,
+}
+
+impl EnergyLedger {
+    /// 空の帳簿を生成する
+    pub fn new() -> EnergyLedger {
+        EnergyLedger::default()
+    }
+
+    /// 代謝を持たない相手などが、エネルギー`energy`を持ったまま死んだことを記録する
+    ///
+    /// 捕食では、食べた側の`Metabolism::intake`と食べられた側のこの記録が対になる
+    pub fn record_death(&mut self, energy: f32) {
+        self.current.death_loss += energy;
+        self.current.deaths += 1;
+    }
+
+    /// 記録中のステップの流れ
+    pub fn current(&self) -> &EnergyFlow {
+        &self.current
+    }
+
+    /// ステップを締めくくる。総エネルギーの変化が記録した出入りと合っているか確かめて、履歴に加える
+    ///
+    /// # Arguments
+    /// * `before` - ステップの始めの総エネルギー
+    /// * `after` - ステップの終わりの総エネルギー
+    pub fn close(&mut self, before: f32, after: f32) -> Result<EnergyFlow, failure::Error> {
+        let mut flow = self.current;
+        self.current = EnergyFlow::default();
+        flow.total = after;
+        let expected = before + flow.intake - flow.maintenance - flow.death_loss;
+        let scale = before.abs().max(after.abs()).max(1.0);
+        if (expected - after).abs() > TOLERANCE * scale {
+            return Err(format_err!(
+                "energy is not conserved: expected {}, but got {} (intake {}, maintenance {}, death loss {})",
+                expected,
+                after,
+                flow.intake,
+                flow.maintenance,
+                flow.death_loss
+            ));
+        }
+        self.history.push(flow);
+        Ok(flow)
+    }
+
+    /// これまでに締めくくったステップの流れ
+    pub fn history(&self) -> &[EnergyFlow] {
+        &self.history
+    }
+
+    /// 履歴全体の合計。`total`は最後のステップの値
+    pub fn cumulative(&self) -> EnergyFlow {
+        self.history.iter().fold(EnergyFlow::default(), |sum, flow| EnergyFlow {
+            intake: sum.intake + flow.intake,
+            maintenance: sum.maintenance + flow.maintenance,
+            death_loss: sum.death_loss + flow.death_loss,
+            births: sum.births + flow.births,
+            deaths: sum.deaths + flow.deaths,
+            total: flow.total,
+        })
+    }
+
+    /// 取り込んだエネルギーのうち、維持コストに使われた割合
+    pub fn maintenance_ratio(&self) -> Option<f32> {
+        let sum = self.cumulative();
+        if sum.intake > 0.0 {
+            Some(sum.maintenance / sum.intake)
+        } else {
+            None
+        }
+    }
+}
+
+/// 代謝を持つエージェント
+pub trait Metabolic {
+    /// 代謝。持たないエージェントは`None`
+    fn metabolism(&self) -> Option<&Metabolism>;
+}
+
+/// 帳簿を持つ環境
+pub trait Accounting {
+    /// 帳簿
+    fn ledger(&self) -> &EnergyLedger;
+
+    /// 帳簿を変更するための参照
+    fn ledger_mut(&mut self) -> &mut EnergyLedger;
+}
+
+/// エージェントの持つエネルギーの合計
+pub fn total_energy<A: Metabolic>(agents: &[A]) -> f32 {
+    agents
+        .iter()
+        .filter_map(|agent| agent.metabolism())
+        .map(|metabolism| metabolism.energy())
+        .sum()
+}
+
+impl<A: Agent<E> + Metabolic, E: Accounting> Model<A, E> {
+    /// 1ステップ進め、エネルギーの保存則を確かめる
+    pub fn step_accounted<R: Rng>(&mut self, rng: &mut R) -> Result<EnergyFlow, failure::Error> {
+        let before = total_energy(self.agents());
+        self.step(rng);
+        let after = total_energy(self.agents());
+        self.env_mut().ledger_mut().close(before, after)
+    }
+}
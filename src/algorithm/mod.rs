@@ -1,2 +1,127 @@
+/// エージェント・ベース・モデルを組み立てるためのモジュール
+pub mod abm;
+/// 時間刻みを自動で調整するためのモジュール
+pub mod adaptive;
+/// CPU・SIMD・GPUの計算方法を選んで盤面を進めるための共通のtrait
+pub mod backend;
+/// 1セルを1bitに詰めて64セルずつ計算するGame of Life
+pub mod bit_life;
+/// Braitenbergのビークル
+pub mod braitenberg;
+/// 振動する化学反応のBrusselatorモデル
+pub mod brusselator;
+/// 進化の計算を中断して再開するためのチェックポイント
+pub mod checkpoint;
+/// 人工化学と自己触媒集合(RAF集合)の検出
+pub mod chemistry;
+/// 走って向きを変える細菌の走化性のモデル
+pub mod chemotaxis;
+/// 盤面を粗視化・縮小するためのモジュール
+pub mod coarse_grain;
+/// 2つのシミュレーションを並べて比べるためのモジュール
+pub mod comparison;
+/// 決定的なシミュレーションの周期軌道を見つけるためのモジュール
+pub mod cycle;
+/// 惑星の恒常性のモデルDaisyworld
+pub mod daisyworld;
+/// 1セルの摂動が広がる様子からルールの性質を調べるためのモジュール
+pub mod damage;
+/// モルフォゲンで細胞が分裂・分化する多細胞の発生のモデル
+pub mod development;
+/// 変化した領域をタイル単位で記録するためのモジュール
+pub mod dirty_tiles;
+/// 大きな盤面を分割して複数のスレッドで計算する領域分割
+pub mod domain;
+/// 1次元のセル・オートマトン
+pub mod elementary_ca;
+/// 乱数の種を変えた多数のシミュレーションをまとめて動かすためのモジュール
+pub mod ensemble;
+/// 遺伝的アルゴリズムで遺伝子型を進化させるためのモジュール
+pub mod evolution;
+/// Game of Lifeのアルゴリズム
+pub mod game_of_life;
+/// 壁や湧き出し口など、シミュレーションに共通するセルの配置
+pub mod geometry;
+/// Gillespie法(SSA)とtau-leapingによる確率的な化学反応
+pub mod gillespie;
+/// Gollyのルールファイルとmacrocell形式の読み込み
+pub mod golly;
+/// GPU(compute shader)を使って計算するためのモジュール
+#[cfg(feature = "gl")]
+pub mod gpu;
+/// ネットワーク(グラフ)と、その上の感染症や進化ゲーム
+pub mod graph;
 /// GrayScottモデルのアルゴリズム
 pub mod gray_scott;
+/// 時間とともに伸びる領域の上の反応拡散系
+pub mod growing_domain;
+/// HashLifeによる大きさに上限のないGame of Life
+pub mod hashlife;
+/// 連続時間モデルの時間発展を解くためのモジュール
+pub mod integrator;
+/// シミュレーションが満たすべき保存量や値の範囲を調べるためのモジュール
+pub mod invariant;
+/// 部分集団を並列に進化させて移住させる島モデル
+pub mod island;
+/// 半径の大きな近傍を使うLarger than Lifeのルール
+pub mod larger_than_life;
+/// 粒子の衝突と移動で流体を表す格子気体(HPP, FHP)
+pub mod lattice_gas;
+/// 格子Boltzmann法(D2Q9)による流体
+pub mod lbm;
+/// 連続的なセル・オートマトンLeniaとコミュニティのJSON形式
+pub mod lenia;
+/// B/S表記で表せるGame of Lifeの仲間のルール
+pub mod life_like;
+/// Game of Lifeの盤面から既知のパターンを見つけるためのモジュール
+pub mod life_patterns;
+/// 2×2のブロックごとに更新するMargolus近傍のセル・オートマトン
+pub mod margolus;
+/// 使っているメモリを見積もり、上限を超えないように履歴や記録を減らすためのモジュール
+pub mod memory;
+/// エージェントのエネルギーの収支を記録する代謝のモジュール
+pub mod metabolism;
+/// トポロジーも進化させるニューロエボリューションのNEAT
+pub mod neat;
+/// 格子上の近傍の取り方
+pub mod neighborhood;
+/// エージェントの脳にするニューラルネットワークの制御器
+pub mod neural;
+/// 投票者モデルなどの意見のダイナミクス
+pub mod opinion;
+/// Belousov-Zhabotinsky反応のOregonatorモデル
+pub mod oregonator;
+/// 再利用できるパターン(スタンプ)と、RLE・plaintext形式の読み込み
+pub mod patterns;
+/// 個体の系統を記録して書き出すためのモジュール
+pub mod phylogeny;
+/// 毎フレーム使うバッファを使い回すためのプール
+pub mod pool;
+/// 細胞の形と選別を表すCellular Pottsモデル
+pub mod potts;
+/// 新規性探索とMAP-Elitesによる多様性の探索
+pub mod quality_diversity;
+/// 反応拡散系に共通する計算
+pub mod reaction_diffusion;
+/// 盤面の一部を切り出したり、別の場所に書き込んだりするためのモジュール
+pub mod region;
+/// テキストで書いたセル・オートマトンの遷移規則
+pub mod rule_dsl;
+/// Schellingの分居モデル
+pub mod schelling;
+/// Langtonのループなどの自己複製ループ
+pub mod self_replication;
+/// 格子上の囚人のジレンマなど、空間的な進化ゲーム
+pub mod spatial_game;
+/// 遷移確率の表で定義する確率的なセル・オートマトン
+pub mod stochastic_ca;
+/// 1語に8セルを詰めて近傍を数えるSWARの計算
+pub mod swar;
+/// 忙しいビーバーなどのTuring機械
+pub mod turing;
+/// 集団運動のVicsekモデル
+pub mod vicsek;
+/// von Neumannの29状態セル・オートマトン
+pub mod von_neumann;
+/// 複数の層を組み合わせてシミュレーションを組み立てるためのモジュール
+pub mod world;
@@ -0,0 +1,1017 @@
+use algorithm::checkpoint::{join, parse_list, Checkpoint, Persist};
+use algorithm::neural::Controller;
+use algorithm::phylogeny::Phylogeny;
+use failure;
+use rand::distributions::{IndependentSample, Normal};
+use rand::Rng;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+// 接続を追加するときに、つなげる2つのニューロンを選び直す回数
+const ADD_CONNECTION_ATTEMPTS: usize = 20;
+// 親のどちらかで無効になっている遺伝子が、子でも無効になる確率
+const INHERIT_DISABLED: f32 = 0.75;
+// 互換性距離で遺伝子数による正規化をしない、小さな遺伝子型の大きさ
+const SMALL_GENOME: usize = 20;
+
+/// ニューロンの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    /// 入力
+    Input,
+    /// 常に1を出力するバイアス
+    Bias,
+    /// 出力
+    Output,
+    /// 突然変異で加わった隠れニューロン
+    Hidden,
+}
+
+impl NodeKind {
+    fn name(self) -> &'static str {
+        match self {
+            NodeKind::Input => "input",
+            NodeKind::Bias => "bias",
+            NodeKind::Output => "output",
+            NodeKind::Hidden => "hidden",
+        }
+    }
+}
+
+/// 接続の遺伝子
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConnectionGene {
+    /// イノベーション番号。同じ構造の変化には集団全体で同じ番号がつく
+    pub innovation: usize,
+    /// 接続元のニューロン
+    pub from: usize,
+    /// 接続先のニューロン
+    pub to: usize,
+    /// 重み
+    pub weight: f32,
+    /// 有効かどうか
+    pub enabled: bool,
+}
+
+/// 集団全体で共有する、ニューロンとイノベーション番号の台帳
+#[derive(Debug, Clone, Default)]
+pub struct Innovations {
+    next_innovation: usize,
+    next_node: usize,
+    connections: HashMap<(usize, usize), usize>,
+    splits: HashMap<usize, usize>,
+}
+
+impl Innovations {
+    /// 空の台帳を生成する
+    pub fn new() -> Innovations {
+        Innovations::default()
+    }
+
+    fn connection(&mut self, from: usize, to: usize) -> usize {
+        let next = &mut self.next_innovation;
+        *self.connections.entry((from, to)).or_insert_with(|| {
+            *next += 1;
+            *next - 1
+        })
+    }
+
+    fn node(&mut self) -> usize {
+        self.next_node += 1;
+        self.next_node - 1
+    }
+
+    fn split(&mut self, innovation: usize) -> usize {
+        if let Some(&node) = self.splits.get(&innovation) {
+            return node;
+        }
+        let node = self.node();
+        self.splits.insert(innovation, node);
+        node
+    }
+}
+
+/// NEATのパラメータ
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NeatParams {
+    /// 集団の大きさ
+    pub population: usize,
+    /// 互換性距離での、末端の異なる遺伝子(excess)の係数
+    pub c1: f32,
+    /// 互換性距離での、途中の異なる遺伝子(disjoint)の係数
+    pub c2: f32,
+    /// 互換性距離での、重みの差の係数
+    pub c3: f32,
+    /// この距離より近い個体は同じ種になる
+    pub compatibility_threshold: f32,
+    /// 重みを突然変異させる確率
+    pub weight_mutation_rate: f32,
+    /// 重みの突然変異で加える正規分布の標準偏差
+    pub weight_perturbation: f32,
+    /// 重みを突然変異させるとき、加算ではなく新しい値に置き換える確率
+    pub weight_replace_rate: f32,
+    /// 接続を追加する確率
+    pub add_connection_rate: f32,
+    /// 接続を分割してニューロンを追加する確率
+    pub add_node_rate: f32,
+    /// 交叉で子を作る確率
+    pub crossover_rate: f32,
+    /// 種の中で親になれる上位の割合
+    pub survival_rate: f32,
+}
+
+impl Default for NeatParams {
+    fn default() -> NeatParams {
+        NeatParams {
+            population: 150,
+            c1: 1.0,
+            c2: 1.0,
+            c3: 0.4,
+            compatibility_threshold: 3.0,
+            weight_mutation_rate: 0.8,
+            weight_perturbation: 0.5,
+            weight_replace_rate: 0.1,
+            add_connection_rate: 0.05,
+            add_node_rate: 0.03,
+            crossover_rate: 0.75,
+            survival_rate: 0.2,
+        }
+    }
+}
+
+/// ニューロンと接続の遺伝子からなる、NEATの遺伝子型
+///
+/// `Display`で書き出した文字列は`FromStr`で読み戻せる
+///
+/// # Example
+/// ```
+/// extern crate my_alife;
+/// extern crate rand;
+///
+/// use my_alife::algorithm::neat::{Innovations, NeatGenome};
+/// use my_alife::algorithm::neural::Controller;
+///
+/// let mut rng = rand::thread_rng();
+/// let mut innovations = Innovations::new();
+/// let mut genome = NeatGenome::minimal(2, 1, &mut innovations, &mut rng);
+/// // 2入力とバイアスが出力に全結合している
+/// assert_eq!(genome.connections().len(), 3);
+/// assert!(genome.add_node(&mut innovations, &mut rng));
+/// assert_eq!(genome.nodes().len(), 5);
+///
+/// let text = genome.to_string();
+/// let restored: NeatGenome = text.parse().unwrap();
+/// assert_eq!(restored, genome);
+/// assert_eq!(restored.network().act(&[1.0, 0.0]), genome.network().act(&[1.0, 0.0]));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct NeatGenome {
+    nodes: Vec<(usize, NodeKind)>,
+    connections: Vec<ConnectionGene>,
+}
+
+impl NeatGenome {
+    /// 入力とバイアスが出力に全結合した、隠れニューロンのない遺伝子型を生成する
+    ///
+    /// 同じ台帳を使う個体は、同じ番号のニューロンを入力と出力に使う
+    pub fn minimal<R: Rng>(inputs: usize, outputs: usize, innovations: &mut Innovations, rng: &mut R) -> NeatGenome {
+        let mut nodes: Vec<(usize, NodeKind)> = (0..inputs).map(|i| (i, NodeKind::Input)).collect();
+        nodes.push((inputs, NodeKind::Bias));
+        nodes.extend((0..outputs).map(|i| (inputs + 1 + i, NodeKind::Output)));
+        innovations.next_node = innovations.next_node.max(nodes.len());
+        let mut connections = Vec::new();
+        for to in inputs + 1..inputs + 1 + outputs {
+            for from in 0..inputs + 1 {
+                connections.push(ConnectionGene {
+                    innovation: innovations.connection(from, to),
+                    from,
+                    to,
+                    weight: rng.gen_range(-1.0, 1.0),
+                    enabled: true,
+                });
+            }
+        }
+        connections.sort_by_key(|c| c.innovation);
+        NeatGenome { nodes, connections }
+    }
+
+    /// ニューロン(番号, 種類)
+    pub fn nodes(&self) -> &[(usize, NodeKind)] {
+        &self.nodes
+    }
+
+    /// イノベーション番号の順に並んだ接続
+    pub fn connections(&self) -> &[ConnectionGene] {
+        &self.connections
+    }
+
+    /// 入力の数
+    pub fn inputs(&self) -> usize {
+        self.count(NodeKind::Input)
+    }
+
+    /// 出力の数
+    pub fn outputs(&self) -> usize {
+        self.count(NodeKind::Output)
+    }
+
+    fn count(&self, kind: NodeKind) -> usize {
+        self.nodes.iter().filter(|node| node.1 == kind).count()
+    }
+
+    fn kind(&self, node: usize) -> Option<NodeKind> {
+        self.nodes.iter().find(|n| n.0 == node).map(|n| n.1)
+    }
+
+    /// `params`の確率で、重み・接続・ニューロンを突然変異させる。加わった突然変異の種類を返す
+    pub fn mutate<R: Rng>(
+        &mut self,
+        params: &NeatParams,
+        innovations: &mut Innovations,
+        rng: &mut R,
+    ) -> Vec<&'static str> {
+        let mut applied = Vec::new();
+        if rng.gen::<f32>() < params.weight_mutation_rate {
+            applied.push("weights");
+            let normal = Normal::new(0.0, f64::from(params.weight_perturbation));
+            for connection in &mut self.connections {
+                if rng.gen::<f32>() < params.weight_replace_rate {
+                    connection.weight = rng.gen_range(-1.0, 1.0);
+                } else {
+                    connection.weight += normal.ind_sample(rng) as f32;
+                }
+            }
+        }
+        if rng.gen::<f32>() < params.add_connection_rate && self.add_connection(innovations, rng) {
+            applied.push("add_connection");
+        }
+        if rng.gen::<f32>() < params.add_node_rate && self.add_node(innovations, rng) {
+            applied.push("add_node");
+        }
+        applied
+    }
+
+    /// まだつながっていない2つのニューロンを、循環ができないように接続する。接続できたかどうかを返す
+    pub fn add_connection<R: Rng>(&mut self, innovations: &mut Innovations, rng: &mut R) -> bool {
+        for _ in 0..ADD_CONNECTION_ATTEMPTS {
+            let from = rng.choose(&self.nodes).unwrap().0;
+            let to = rng.choose(&self.nodes).unwrap().0;
+            let to_kind = self.kind(to).unwrap();
+            if from == to
+                || to_kind == NodeKind::Input
+                || to_kind == NodeKind::Bias
+                || self.kind(from) == Some(NodeKind::Output)
+            {
+                continue;
+            }
+            if self.connections.iter().any(|c| c.from == from && c.to == to) || self.reaches(to, from) {
+                continue;
+            }
+            self.insert(ConnectionGene {
+                innovation: innovations.connection(from, to),
+                from,
+                to,
+                weight: rng.gen_range(-1.0, 1.0),
+                enabled: true,
+            });
+            return true;
+        }
+        false
+    }
+
+    /// 有効な接続を1つ選んで無効にし、その間に新しいニューロンを挟む。挟めたかどうかを返す
+    pub fn add_node<R: Rng>(&mut self, innovations: &mut Innovations, rng: &mut R) -> bool {
+        let enabled: Vec<usize> = (0..self.connections.len())
+            .filter(|&i| self.connections[i].enabled)
+            .collect();
+        let index = match rng.choose(&enabled) {
+            Some(&index) => index,
+            None => return false,
+        };
+        let old = self.connections[index];
+        self.connections[index].enabled = false;
+        let mut node = innovations.split(old.innovation);
+        if self.kind(node).is_some() {
+            // 同じ接続を2度分割したときは別のニューロンにする
+            node = innovations.node();
+        }
+        self.nodes.push((node, NodeKind::Hidden));
+        self.insert(ConnectionGene {
+            innovation: innovations.connection(old.from, node),
+            from: old.from,
+            to: node,
+            weight: 1.0,
+            enabled: true,
+        });
+        self.insert(ConnectionGene {
+            innovation: innovations.connection(node, old.to),
+            from: node,
+            to: old.to,
+            weight: old.weight,
+            enabled: true,
+        });
+        true
+    }
+
+    fn insert(&mut self, connection: ConnectionGene) {
+        let position = self
+            .connections
+            .binary_search_by_key(&connection.innovation, |c| c.innovation)
+            .unwrap_or_else(|p| p);
+        self.connections.insert(position, connection);
+    }
+
+    // 有効な接続をたどって`from`から`to`に行けるかどうか
+    fn reaches(&self, from: usize, to: usize) -> bool {
+        let mut stack = vec![from];
+        let mut visited = HashSet::new();
+        while let Some(node) = stack.pop() {
+            if node == to {
+                return true;
+            }
+            if visited.insert(node) {
+                stack.extend(self.connections.iter().filter(|c| c.from == node).map(|c| c.to));
+            }
+        }
+        false
+    }
+
+    /// 適応度の高い`self`と`other`の子を作る。同じイノベーション番号の遺伝子はどちらかから、それ以外は`self`から受け継ぐ
+    pub fn crossover<R: Rng>(&self, other: &NeatGenome, rng: &mut R) -> NeatGenome {
+        let others: HashMap<usize, &ConnectionGene> = other.connections.iter().map(|c| (c.innovation, c)).collect();
+        let connections = self
+            .connections
+            .iter()
+            .map(|gene| match others.get(&gene.innovation) {
+                Some(matching) => {
+                    let mut child = if rng.gen() { *gene } else { **matching };
+                    child.enabled = !((!gene.enabled || !matching.enabled) && rng.gen::<f32>() < INHERIT_DISABLED);
+                    child
+                }
+                None => *gene,
+            })
+            .collect();
+        NeatGenome {
+            nodes: self.nodes.clone(),
+            connections,
+        }
+    }
+
+    /// 互換性距離 c1 E / N + c2 D / N + c3 W。Eは末端の異なる遺伝子、Dは途中の異なる遺伝子の数、Wは共通の遺伝子の重みの差の平均
+    pub fn distance(&self, other: &NeatGenome, params: &NeatParams) -> f32 {
+        let last = |genome: &NeatGenome| genome.connections.last().map_or(0, |c| c.innovation);
+        let (mine, theirs) = (last(self), last(other));
+        let others: HashMap<usize, f32> = other.connections.iter().map(|c| (c.innovation, c.weight)).collect();
+        let (mut excess, mut disjoint, mut matching, mut weight_difference) = (0, 0, 0, 0.0);
+        for gene in &self.connections {
+            match others.get(&gene.innovation) {
+                Some(weight) => {
+                    matching += 1;
+                    weight_difference += (gene.weight - weight).abs();
+                }
+                None if gene.innovation > theirs => excess += 1,
+                None => disjoint += 1,
+            }
+        }
+        let mine_innovations: HashSet<usize> = self.connections.iter().map(|c| c.innovation).collect();
+        for gene in other
+            .connections
+            .iter()
+            .filter(|c| !mine_innovations.contains(&c.innovation))
+        {
+            if gene.innovation > mine {
+                excess += 1;
+            } else {
+                disjoint += 1;
+            }
+        }
+        // 小さな遺伝子型では遺伝子数で割らない(Stanley and Miikkulainen, 2002)
+        let genes = self.connections.len().max(other.connections.len());
+        let n = if genes < SMALL_GENOME { 1.0 } else { genes as f32 };
+        let w = if matching > 0 {
+            weight_difference / matching as f32
+        } else {
+            0.0
+        };
+        params.c1 * excess as f32 / n + params.c2 * disjoint as f32 / n + params.c3 * w
+    }
+
+    /// 遺伝子型が表すネットワーク
+    pub fn network(&self) -> NeatNetwork {
+        NeatNetwork::new(self)
+    }
+
+    /// 文字列にしてファイルに書き出す
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), failure::Error> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    /// `save`で書き出したファイルから読み込む
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<NeatGenome, failure::Error> {
+        fs::read_to_string(path)?.parse()
+    }
+}
+
+impl fmt::Display for NeatGenome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for &(id, kind) in &self.nodes {
+            writeln!(f, "node {} {}", id, kind.name())?;
+        }
+        for c in &self.connections {
+            writeln!(
+                f,
+                "connection {} {} {} {} {}",
+                c.innovation, c.from, c.to, c.weight, c.enabled
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for NeatGenome {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<NeatGenome, failure::Error> {
+        let mut nodes = Vec::new();
+        let mut connections = Vec::new();
+        for line in s
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let invalid = || format_err!("invalid genome line \"{}\"", line);
+            match fields[0] {
+                "node" if fields.len() == 3 => {
+                    let kind = match fields[2] {
+                        "input" => NodeKind::Input,
+                        "bias" => NodeKind::Bias,
+                        "output" => NodeKind::Output,
+                        "hidden" => NodeKind::Hidden,
+                        _ => return Err(invalid()),
+                    };
+                    nodes.push((fields[1].parse().map_err(|_| invalid())?, kind));
+                }
+                "connection" if fields.len() == 6 => connections.push(ConnectionGene {
+                    innovation: fields[1].parse().map_err(|_| invalid())?,
+                    from: fields[2].parse().map_err(|_| invalid())?,
+                    to: fields[3].parse().map_err(|_| invalid())?,
+                    weight: fields[4].parse().map_err(|_| invalid())?,
+                    enabled: fields[5].parse().map_err(|_| invalid())?,
+                }),
+                _ => return Err(invalid()),
+            }
+        }
+        let ids: HashSet<usize> = nodes.iter().map(|node| node.0).collect();
+        if let Some(c) = connections
+            .iter()
+            .find(|c| !ids.contains(&c.from) || !ids.contains(&c.to))
+        {
+            return Err(format_err!("connection {} refers to an unknown node", c.innovation));
+        }
+        connections.sort_by_key(|c| c.innovation);
+        Ok(NeatGenome { nodes, connections })
+    }
+}
+
+/// `Display`の改行を`;`に置き換えた1行
+impl Persist for NeatGenome {
+    fn encode(&self) -> String {
+        self.to_string().trim_end().replace('\n', ";")
+    }
+
+    fn decode(s: &str) -> Result<NeatGenome, failure::Error> {
+        s.replace(';', "\n").parse()
+    }
+}
+
+/// `NeatGenome`から組み立てた順伝播型ネットワーク。活性化関数は傾きを4.9倍にしたシグモイド
+#[derive(Debug, Clone)]
+pub struct NeatNetwork {
+    inputs: Vec<usize>,
+    bias: Vec<usize>,
+    outputs: Vec<usize>,
+    // 計算する順に並んだ(ニューロン, 入ってくる接続(接続元, 重み))
+    order: Vec<(usize, Vec<(usize, f32)>)>,
+    values: Vec<f32>,
+}
+
+impl NeatNetwork {
+    fn new(genome: &NeatGenome) -> NeatNetwork {
+        let index: HashMap<usize, usize> = genome.nodes.iter().enumerate().map(|(i, node)| (node.0, i)).collect();
+        let select = |kind: NodeKind| -> Vec<usize> {
+            genome
+                .nodes
+                .iter()
+                .enumerate()
+                .filter(|&(_, node)| node.1 == kind)
+                .map(|(i, _)| i)
+                .collect()
+        };
+        let enabled: Vec<&ConnectionGene> = genome.connections.iter().filter(|c| c.enabled).collect();
+        let mut incoming = vec![Vec::new(); genome.nodes.len()];
+        let mut pending = vec![0; genome.nodes.len()];
+        for c in &enabled {
+            incoming[index[&c.to]].push((index[&c.from], c.weight));
+            pending[index[&c.to]] += 1;
+        }
+        // Kahnのアルゴリズムで計算順を決める
+        let mut ready: Vec<usize> = (0..genome.nodes.len()).filter(|&i| pending[i] == 0).collect();
+        let mut order = Vec::new();
+        while let Some(node) = ready.pop() {
+            if genome.nodes[node].1 != NodeKind::Input && genome.nodes[node].1 != NodeKind::Bias {
+                order.push((node, incoming[node].clone()));
+            }
+            for c in enabled.iter().filter(|c| index[&c.from] == node) {
+                let to = index[&c.to];
+                pending[to] -= 1;
+                if pending[to] == 0 {
+                    ready.push(to);
+                }
+            }
+        }
+        NeatNetwork {
+            inputs: select(NodeKind::Input),
+            bias: select(NodeKind::Bias),
+            outputs: select(NodeKind::Output),
+            order,
+            values: vec![0.0; genome.nodes.len()],
+        }
+    }
+}
+
+impl Controller for NeatNetwork {
+    fn act(&mut self, inputs: &[f32]) -> Vec<f32> {
+        assert_eq!(inputs.len(), self.inputs.len());
+        for (&node, &input) in self.inputs.iter().zip(inputs) {
+            self.values[node] = input;
+        }
+        for &node in &self.bias {
+            self.values[node] = 1.0;
+        }
+        for &(node, ref incoming) in &self.order {
+            let sum: f32 = incoming.iter().map(|&(from, weight)| self.values[from] * weight).sum();
+            self.values[node] = 1.0 / (1.0 + (-4.9 * sum).exp());
+        }
+        self.outputs.iter().map(|&node| self.values[node]).collect()
+    }
+}
+
+/// 互換性距離で分けられた種
+#[derive(Debug, Clone)]
+pub struct Species {
+    /// 種を識別する番号
+    pub id: usize,
+    /// 新しい個体と比べる代表
+    pub representative: NeatGenome,
+    /// 集団の中の番号
+    pub members: Vec<usize>,
+}
+
+/// NEATの集団。種ごとの適応度共有で多様な構造を守りながら進化させる
+///
+/// 適応度は0以上にする
+///
+/// # Example
+/// ```
+/// extern crate my_alife;
+/// extern crate rand;
+///
+/// use my_alife::algorithm::neat::{Neat, NeatParams};
+/// use my_alife::algorithm::neural::Controller;
+///
+/// let mut rng = rand::thread_rng();
+/// let params = NeatParams { population: 50, ..NeatParams::default() };
+/// let mut neat = Neat::new(2, 1, params, &mut rng);
+/// // 2つの入力の和を出力させる
+/// let fitness = |genome: &my_alife::algorithm::neat::NeatGenome| {
+///     let mut network = genome.network();
+///     let error: f32 = [(0.0, 0.0), (0.5, 0.25), (0.25, 0.25)]
+///         .iter()
+///         .map(|&(a, b)| (network.act(&[a, b])[0] - (a + b)).abs())
+///         .sum();
+///     3.0 - error
+/// };
+/// neat.run(10, fitness, &mut rng);
+/// assert_eq!(neat.generation(), 10);
+/// assert_eq!(neat.population().len(), 50);
+/// assert!(!neat.species().is_empty());
+/// let (_, best) = neat.champion().unwrap();
+/// assert!(neat.history().iter().all(|&f| f <= best));
+/// ```
+pub struct Neat {
+    params: NeatParams,
+    innovations: Innovations,
+    population: Vec<NeatGenome>,
+    fitness: Vec<f32>,
+    species: Vec<Species>,
+    next_species: usize,
+    generation: usize,
+    champion: Option<(NeatGenome, f32)>,
+    history: Vec<f32>,
+    lineage: Option<(Phylogeny, Vec<u64>)>,
+    champion_id: Option<u64>,
+}
+
+impl Neat {
+    /// 最小構造の個体`params.population`体から始める
+    pub fn new<R: Rng>(inputs: usize, outputs: usize, params: NeatParams, rng: &mut R) -> Neat {
+        let mut innovations = Innovations::new();
+        let population = (0..params.population)
+            .map(|_| NeatGenome::minimal(inputs, outputs, &mut innovations, rng))
+            .collect();
+        Neat {
+            params,
+            innovations,
+            population,
+            fitness: Vec::new(),
+            species: Vec::new(),
+            next_species: 0,
+            generation: 0,
+            champion: None,
+            history: Vec::new(),
+            lineage: None,
+            champion_id: None,
+        }
+    }
+
+    /// 系統の記録を始める。現在の集団が系統の根になる
+    pub fn enable_phylogeny(&mut self) {
+        let mut phylogeny = Phylogeny::new();
+        let generation = self.generation;
+        let ids = self.population.iter().map(|_| phylogeny.root(generation)).collect();
+        self.lineage = Some((phylogeny, ids));
+    }
+
+    /// 記録している系統
+    pub fn phylogeny(&self) -> Option<&Phylogeny> {
+        self.lineage.as_ref().map(|lineage| &lineage.0)
+    }
+
+    /// 系統を記録しているとき、集団の各個体の番号
+    pub fn ids(&self) -> Option<&[u64]> {
+        self.lineage.as_ref().map(|lineage| &lineage.1[..])
+    }
+
+    /// 系統を記録しているとき、チャンピオンの番号
+    pub fn champion_id(&self) -> Option<u64> {
+        self.champion_id
+    }
+
+    /// パラメータ
+    pub fn params(&self) -> &NeatParams {
+        &self.params
+    }
+
+    /// 現在の集団
+    pub fn population(&self) -> &[NeatGenome] {
+        &self.population
+    }
+
+    /// 現在の集団の適応度。まだ評価していなければ空
+    pub fn fitness(&self) -> &[f32] {
+        &self.fitness
+    }
+
+    /// 直前の世代交代で分けた種
+    pub fn species(&self) -> &[Species] {
+        &self.species
+    }
+
+    /// 世代数
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// 世代ごとの最良の適応度
+    pub fn history(&self) -> &[f32] {
+        &self.history
+    }
+
+    /// これまでに評価した中で最も適応度の高い個体とその適応度
+    pub fn champion(&self) -> Option<(&NeatGenome, f32)> {
+        self.champion.as_ref().map(|&(ref genome, fitness)| (genome, fitness))
+    }
+
+    /// 集団の全員を`fitness`で評価する
+    pub fn evaluate<F: FnMut(&NeatGenome) -> f32>(&mut self, fitness: F) {
+        self.fitness = self.population.iter().map(fitness).collect();
+        let best = (0..self.fitness.len()).max_by(|&a, &b| compare(self.fitness[a], self.fitness[b]));
+        if let Some(best) = best {
+            let fitness = self.fitness[best];
+            if self.champion.as_ref().is_none_or(|champion| fitness > champion.1) {
+                self.champion = Some((self.population[best].clone(), fitness));
+                self.champion_id = self.lineage.as_ref().map(|lineage| lineage.1[best]);
+            }
+        }
+        if let Some((ref mut phylogeny, ref ids)) = self.lineage {
+            for (&id, &fitness) in ids.iter().zip(&self.fitness) {
+                phylogeny.set_fitness(id, fitness);
+            }
+        }
+    }
+
+    fn speciate<R: Rng>(&mut self, rng: &mut R) {
+        for species in &mut self.species {
+            species.members.clear();
+        }
+        for (i, genome) in self.population.iter().enumerate() {
+            let params = &self.params;
+            match self
+                .species
+                .iter_mut()
+                .find(|species| genome.distance(&species.representative, params) < params.compatibility_threshold)
+            {
+                Some(species) => species.members.push(i),
+                None => {
+                    self.species.push(Species {
+                        id: self.next_species,
+                        representative: genome.clone(),
+                        members: vec![i],
+                    });
+                    self.next_species += 1;
+                }
+            }
+        }
+        self.species.retain(|species| !species.members.is_empty());
+        for species in &mut self.species {
+            let representative = *rng.choose(&species.members).unwrap();
+            species.representative = self.population[representative].clone();
+        }
+    }
+
+    /// 評価済みの集団を種に分け、種の平均適応度に比例した数の子を作る
+    ///
+    /// # Panics
+    /// `evaluate`で評価していないとき
+    pub fn next_generation<R: Rng>(&mut self, rng: &mut R) {
+        assert_eq!(self.fitness.len(), self.population.len(), "population is not evaluated");
+        if let Some(best) = self.fitness.iter().cloned().max_by(|&a, &b| compare(a, b)) {
+            self.history.push(best);
+        }
+        self.speciate(rng);
+        let means: Vec<f32> = self
+            .species
+            .iter()
+            .map(|species| {
+                species.members.iter().map(|&i| self.fitness[i].max(0.0)).sum::<f32>() / species.members.len() as f32
+            })
+            .collect();
+        let quotas = allocate(&means, self.params.population);
+
+        let mut next = Vec::with_capacity(self.params.population);
+        // 子ごとの(親, 加わった変化)。種の代表としてそのまま残る個体は`None`
+        let mut births: Vec<(Vec<usize>, Option<Vec<String>>)> = Vec::with_capacity(self.params.population);
+        for (species, quota) in self.species.iter().zip(quotas) {
+            if quota == 0 {
+                continue;
+            }
+            let mut members = species.members.clone();
+            members.sort_by(|&a, &b| compare(self.fitness[b], self.fitness[a]));
+            next.push(self.population[members[0]].clone());
+            births.push((vec![members[0]], None));
+            let survivors = ((members.len() as f32 * self.params.survival_rate).ceil() as usize).max(1);
+            let parents = &members[..survivors];
+            for _ in 1..quota {
+                let mother = *rng.choose(parents).unwrap();
+                let (mut child, parents, mut mutations) =
+                    if parents.len() > 1 && rng.gen::<f32>() < self.params.crossover_rate {
+                        let father = *rng.choose(parents).unwrap();
+                        let (fitter, other) = if self.fitness[mother] >= self.fitness[father] {
+                            (mother, father)
+                        } else {
+                            (father, mother)
+                        };
+                        let child = self.population[fitter].crossover(&self.population[other], rng);
+                        (child, vec![fitter, other], vec!["crossover".to_string()])
+                    } else {
+                        (self.population[mother].clone(), vec![mother], Vec::new())
+                    };
+                let applied = child.mutate(&self.params, &mut self.innovations, rng);
+                mutations.extend(applied.into_iter().map(str::to_string));
+                next.push(child);
+                births.push((parents, Some(mutations)));
+            }
+        }
+        if let Some((ref mut phylogeny, ref mut ids)) = self.lineage {
+            let generation = self.generation + 1;
+            *ids = births
+                .into_iter()
+                .map(|(parents, mutations)| match mutations {
+                    Some(mutations) => {
+                        let parents: Vec<u64> = parents.iter().map(|&i| ids[i]).collect();
+                        phylogeny.birth(&parents, generation, mutations)
+                    }
+                    None => ids[parents[0]],
+                })
+                .collect();
+        }
+        self.population = next;
+        self.fitness.clear();
+        self.generation += 1;
+    }
+
+    /// 評価と世代交代を`generations`回繰り返し、最後の集団を評価した状態で終わる
+    pub fn run<F: FnMut(&NeatGenome) -> f32, R: Rng>(&mut self, generations: usize, mut fitness: F, rng: &mut R) {
+        for _ in 0..generations {
+            self.evaluate(&mut fitness);
+            self.next_generation(rng);
+        }
+        self.evaluate(&mut fitness);
+    }
+
+    /// 集団・種・台帳・チャンピオン・世代数・履歴をチェックポイントに書き出す
+    ///
+    /// 乱数生成器の状態は含まないので、`CheckpointRng`を使って別に書き出す
+    ///
+    /// # Example
+    /// ```
+    /// extern crate my_alife;
+    ///
+    /// use my_alife::algorithm::checkpoint::CheckpointRng;
+    /// use my_alife::algorithm::neat::{Neat, NeatGenome, NeatParams};
+    /// use my_alife::algorithm::neural::Controller;
+    ///
+    /// let fitness = |genome: &NeatGenome| genome.network().act(&[1.0, 0.5])[0];
+    /// let params = NeatParams { population: 30, add_node_rate: 0.2, ..NeatParams::default() };
+    /// let mut rng = CheckpointRng::new(7);
+    /// let mut neat = Neat::new(2, 1, params, &mut rng);
+    /// neat.run(5, fitness, &mut rng);
+    /// let saved = (neat.to_checkpoint().to_string(), rng.clone());
+    /// neat.run(5, fitness, &mut rng);
+    ///
+    /// let mut resumed = Neat::from_checkpoint(&saved.0.parse().unwrap()).unwrap();
+    /// let mut resumed_rng = saved.1;
+    /// resumed.run(5, fitness, &mut resumed_rng);
+    /// assert_eq!(resumed.population(), neat.population());
+    /// assert_eq!(resumed.history(), neat.history());
+    /// ```
+    pub fn to_checkpoint(&self) -> Checkpoint {
+        let mut checkpoint = Checkpoint::new("neat");
+        let p = &self.params;
+        let params = [
+            p.c1,
+            p.c2,
+            p.c3,
+            p.compatibility_threshold,
+            p.weight_mutation_rate,
+            p.weight_perturbation,
+            p.weight_replace_rate,
+            p.add_connection_rate,
+            p.add_node_rate,
+            p.crossover_rate,
+            p.survival_rate,
+        ];
+        checkpoint.push("population_size", p.population);
+        checkpoint.push("params", join(&params));
+        checkpoint.push("generation", self.generation);
+        checkpoint.push("next_species", self.next_species);
+        checkpoint.push("next_innovation", self.innovations.next_innovation);
+        checkpoint.push("next_node", self.innovations.next_node);
+        let mut connections: Vec<_> = self.innovations.connections.iter().collect();
+        connections.sort();
+        for (&(from, to), innovation) in connections {
+            checkpoint.push("innovation", join(&[*innovation, from, to]));
+        }
+        let mut splits: Vec<_> = self.innovations.splits.iter().collect();
+        splits.sort();
+        for (innovation, node) in splits {
+            checkpoint.push("split", join(&[*innovation, *node]));
+        }
+        checkpoint.push("history", join(&self.history));
+        checkpoint.push("fitness", join(&self.fitness));
+        if let Some((ref genome, fitness)) = self.champion {
+            checkpoint.push("champion_fitness", fitness);
+            checkpoint.push("champion", genome.encode());
+        }
+        for species in &self.species {
+            let mut ids = vec![species.id];
+            ids.extend(&species.members);
+            checkpoint.push("species", join(&ids));
+            checkpoint.push("representative", species.representative.encode());
+        }
+        for genome in &self.population {
+            checkpoint.push("genome", genome.encode());
+        }
+        if let Some((ref phylogeny, ref ids)) = self.lineage {
+            checkpoint.push("ids", join(ids));
+            if let Some(id) = self.champion_id {
+                checkpoint.push("champion_id", id);
+            }
+            for line in phylogeny.to_csv().lines().skip(1) {
+                checkpoint.push("lineage", line);
+            }
+        }
+        checkpoint
+    }
+
+    /// `to_checkpoint`で書き出した状態から復元する
+    pub fn from_checkpoint(checkpoint: &Checkpoint) -> Result<Neat, failure::Error> {
+        checkpoint.expect_kind("neat")?;
+        let decode_all = |key: &str| -> Result<Vec<NeatGenome>, failure::Error> {
+            checkpoint.all(key).into_iter().map(NeatGenome::decode).collect()
+        };
+        let values: Vec<f32> = parse_list(checkpoint.get("params")?)?;
+        if values.len() != 11 {
+            return Err(format_err!("checkpoint has invalid NEAT parameters"));
+        }
+        let params = NeatParams {
+            population: checkpoint.parse("population_size")?,
+            c1: values[0],
+            c2: values[1],
+            c3: values[2],
+            compatibility_threshold: values[3],
+            weight_mutation_rate: values[4],
+            weight_perturbation: values[5],
+            weight_replace_rate: values[6],
+            add_connection_rate: values[7],
+            add_node_rate: values[8],
+            crossover_rate: values[9],
+            survival_rate: values[10],
+        };
+        let mut innovations = Innovations {
+            next_innovation: checkpoint.parse("next_innovation")?,
+            next_node: checkpoint.parse("next_node")?,
+            ..Innovations::default()
+        };
+        for line in checkpoint.all("innovation") {
+            match parse_list::<usize>(line)?[..] {
+                [innovation, from, to] => innovations.connections.insert((from, to), innovation),
+                _ => return Err(format_err!("invalid innovation \"{}\"", line)),
+            };
+        }
+        for line in checkpoint.all("split") {
+            match parse_list::<usize>(line)?[..] {
+                [innovation, node] => innovations.splits.insert(innovation, node),
+                _ => return Err(format_err!("invalid split \"{}\"", line)),
+            };
+        }
+        let mut species = Vec::new();
+        for (line, representative) in checkpoint.all("species").into_iter().zip(decode_all("representative")?) {
+            let ids: Vec<usize> = parse_list(line)?;
+            if ids.is_empty() {
+                return Err(format_err!("invalid species \"{}\"", line));
+            }
+            species.push(Species {
+                id: ids[0],
+                representative,
+                members: ids[1..].to_vec(),
+            });
+        }
+        let champion = match decode_all("champion")?.pop() {
+            Some(genome) => Some((genome, checkpoint.parse("champion_fitness")?)),
+            None => None,
+        };
+        let population = decode_all("genome")?;
+        let fitness: Vec<f32> = parse_list(checkpoint.get("fitness")?)?;
+        if !(fitness.is_empty() || fitness.len() == population.len()) {
+            return Err(format_err!("checkpoint has an invalid population"));
+        }
+        Ok(Neat {
+            params,
+            innovations,
+            population,
+            fitness,
+            species,
+            next_species: checkpoint.parse("next_species")?,
+            generation: checkpoint.parse("generation")?,
+            champion,
+            history: parse_list(checkpoint.get("history")?)?,
+            lineage: match checkpoint.get("ids") {
+                Ok(ids) => {
+                    let csv = checkpoint.all("lineage").join("\n");
+                    Some((Phylogeny::from_csv(&csv)?, parse_list(ids)?))
+                }
+                Err(_) => None,
+            },
+            champion_id: checkpoint.parse("champion_id").ok(),
+        })
+    }
+}
+
+// 重み`weights`に比例して`total`個を配る。端数は端数の大きい順に配る
+fn allocate(weights: &[f32], total: usize) -> Vec<usize> {
+    let sum: f32 = weights.iter().sum();
+    let shares: Vec<f32> = if sum > 0.0 {
+        weights.iter().map(|w| w / sum * total as f32).collect()
+    } else {
+        vec![total as f32 / weights.len() as f32; weights.len()]
+    };
+    let mut quotas: Vec<usize> = shares.iter().map(|s| s.floor() as usize).collect();
+    let mut order: Vec<usize> = (0..shares.len()).collect();
+    order.sort_by(|&a, &b| compare(shares[b].fract(), shares[a].fract()));
+    let rest = total - quotas.iter().sum::<usize>();
+    for &i in order.iter().cycle().take(rest) {
+        quotas[i] += 1;
+    }
+    quotas
+}
+
+fn compare(a: f32, b: f32) -> Ordering {
+    a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+}
@@ -0,0 +1,176 @@
+use ndarray::Array2;
+use visualizer::Matrix;
+
+/// 格子上の近傍の取り方
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// 上下左右の4近傍
+    VonNeumann,
+    /// 斜めを含む8近傍
+    Moore,
+}
+
+const VON_NEUMANN: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+const MOORE: [(isize, isize); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
+
+impl Neighborhood {
+    /// 近傍のセルへの(行, 列)方向のずれ
+    pub fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Neighborhood::VonNeumann => &VON_NEUMANN,
+            Neighborhood::Moore => &MOORE,
+        }
+    }
+
+    /// 近傍のセルの数
+    pub fn size(self) -> usize {
+        self.offsets().len()
+    }
+
+    /// 周期境界条件で、セル(row, col)の近傍のセルの位置
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::algorithm::neighborhood::Neighborhood;
+    ///
+    /// let neighbors: Vec<_> = Neighborhood::VonNeumann.around((0, 0), (4, 4)).collect();
+    /// assert_eq!(neighbors, vec![(3, 0), (0, 3), (0, 1), (1, 0)]);
+    /// ```
+    pub fn around(
+        self,
+        (row, col): (usize, usize),
+        (rows, cols): (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> {
+        self.offsets().iter().map(move |&(dr, dc)| {
+            (
+                (row as isize + dr).rem_euclid(rows as isize) as usize,
+                (col as isize + dc).rem_euclid(cols as isize) as usize,
+            )
+        })
+    }
+
+    /// 周期境界条件で、セル(row, col)の近傍にある各状態のセルの数を`counts`に書き込む
+    pub fn count_states(self, cells: &Matrix<u8>, (row, col): (usize, usize), counts: &mut [u8]) {
+        for count in counts.iter_mut() {
+            *count = 0;
+        }
+        for neighbor in self.around((row, col), cells.dim()) {
+            let state = cells[neighbor] as usize;
+            if state < counts.len() {
+                counts[state] += 1;
+            }
+        }
+    }
+}
+
+/// 半径を指定する広い近傍の形
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NeighborhoodShape {
+    /// 一辺`2r + 1`の正方形
+    Moore,
+    /// マンハッタン距離が`r`以下の菱形
+    VonNeumann,
+    /// 中心からの距離が`r + 0.5`未満の円
+    Circular,
+}
+
+/// Larger than Lifeのような、半径`radius`の広い近傍
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::neighborhood::{ExtendedNeighborhood, NeighborhoodShape};
+///
+/// assert_eq!(ExtendedNeighborhood::new(NeighborhoodShape::Moore, 1, false).offsets().len(), 8);
+/// assert_eq!(ExtendedNeighborhood::new(NeighborhoodShape::VonNeumann, 2, true).offsets().len(), 13);
+/// assert_eq!(ExtendedNeighborhood::new(NeighborhoodShape::Circular, 2, false).offsets().len(), 20);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtendedNeighborhood {
+    /// 形
+    pub shape: NeighborhoodShape,
+    /// 半径
+    pub radius: usize,
+    /// 中心のセル自身を含めるかどうか
+    pub include_center: bool,
+}
+
+impl ExtendedNeighborhood {
+    /// 近傍を生成する
+    pub fn new(shape: NeighborhoodShape, radius: usize, include_center: bool) -> ExtendedNeighborhood {
+        ExtendedNeighborhood {
+            shape,
+            radius,
+            include_center,
+        }
+    }
+
+    /// 近傍のセルへの(行, 列)方向のずれ
+    pub fn offsets(&self) -> Vec<(isize, isize)> {
+        let r = self.radius as isize;
+        let mut offsets = Vec::new();
+        for dr in -r..=r {
+            for dc in -r..=r {
+                let inside = match self.shape {
+                    NeighborhoodShape::Moore => true,
+                    NeighborhoodShape::VonNeumann => dr.abs() + dc.abs() <= r,
+                    NeighborhoodShape::Circular => dr * dr + dc * dc <= r * r + r,
+                };
+                if inside && (self.include_center || (dr, dc) != (0, 0)) {
+                    offsets.push((dr, dc));
+                }
+            }
+        }
+        offsets
+    }
+
+    /// 周期境界条件で、各セルの近傍で`state`になっているセルの数
+    ///
+    /// Moore近傍では累積和を使うので、半径が大きくても計算量は変わらない
+    pub fn count(&self, cells: &Matrix<u8>, state: u8) -> Matrix<u32> {
+        let (rows, cols) = cells.dim();
+        let is_state = |row: isize, col: isize| {
+            let row = row.rem_euclid(rows as isize) as usize;
+            let col = col.rem_euclid(cols as isize) as usize;
+            (cells[[row, col]] == state) as u32
+        };
+        let center = |row: usize, col: usize| {
+            if self.include_center {
+                0
+            } else {
+                is_state(row as isize, col as isize)
+            }
+        };
+        match self.shape {
+            NeighborhoodShape::Moore => {
+                // 周期境界条件のために半径分だけ広げた盤面の累積和
+                let r = self.radius as isize;
+                let size = |len: usize| len + 2 * self.radius + 1;
+                let mut sums = Array2::<u32>::zeros((size(rows), size(cols)));
+                for i in 1..size(rows) {
+                    for j in 1..size(cols) {
+                        sums[[i, j]] =
+                            is_state(i as isize - 1 - r, j as isize - 1 - r) + sums[[i - 1, j]] + sums[[i, j - 1]]
+                                - sums[[i - 1, j - 1]];
+                    }
+                }
+                let width = 2 * self.radius + 1;
+                Array2::from_shape_fn((rows, cols), |(row, col)| {
+                    let (bottom, right) = (row + width, col + width);
+                    sums[[bottom, right]] + sums[[row, col]]
+                        - sums[[row, right]]
+                        - sums[[bottom, col]]
+                        - center(row, col)
+                })
+            }
+            _ => {
+                let offsets = self.offsets();
+                Array2::from_shape_fn((rows, cols), |(row, col)| {
+                    offsets
+                        .iter()
+                        .map(|&(dr, dc)| is_state(row as isize + dr, col as isize + dc))
+                        .sum()
+                })
+            }
+        }
+    }
+}
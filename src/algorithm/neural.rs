@@ -0,0 +1,192 @@
+/// 入力を受け取って出力を返す、エージェントの脳
+pub trait Controller {
+    /// `inputs`から出力を計算する
+    fn act(&mut self, inputs: &[f32]) -> Vec<f32>;
+}
+
+/// 全結合の順伝播型ニューラルネットワーク。活性化関数はtanh
+///
+/// 重みは層ごとに、出力ニューロンごとの(バイアス, 入力の重み...)の順で遺伝子型に並ぶ
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::neural::{Controller, FeedForward};
+///
+/// let sizes = [2, 3, 1];
+/// assert_eq!(FeedForward::genome_len(&sizes), 3 * 3 + 1 * 4);
+/// let genome = vec![0.5; FeedForward::genome_len(&sizes)];
+/// let mut network = FeedForward::from_genome(&sizes, &genome);
+/// let output = network.act(&[1.0, -1.0]);
+/// assert_eq!(output.len(), 1);
+/// assert!(output[0] > 0.0 && output[0] < 1.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedForward {
+    sizes: Vec<usize>,
+    weights: Vec<f32>,
+}
+
+impl FeedForward {
+    /// 層の大きさが`sizes`のネットワークに必要な遺伝子の数
+    pub fn genome_len(sizes: &[usize]) -> usize {
+        sizes.windows(2).map(|w| (w[0] + 1) * w[1]).sum()
+    }
+
+    /// 遺伝子型`genome`を重みとしてネットワークを生成する
+    ///
+    /// # Panics
+    /// 層が2つ未満のときと、遺伝子の数が`genome_len`と合わないとき
+    pub fn from_genome(sizes: &[usize], genome: &[f32]) -> FeedForward {
+        assert!(sizes.len() >= 2, "network needs input and output layers");
+        assert_eq!(
+            genome.len(),
+            FeedForward::genome_len(sizes),
+            "genome length does not match the network"
+        );
+        FeedForward {
+            sizes: sizes.to_vec(),
+            weights: genome.to_vec(),
+        }
+    }
+
+    /// 層の大きさ
+    pub fn sizes(&self) -> &[usize] {
+        &self.sizes
+    }
+
+    /// 遺伝子型
+    pub fn genome(&self) -> &[f32] {
+        &self.weights
+    }
+}
+
+impl Controller for FeedForward {
+    fn act(&mut self, inputs: &[f32]) -> Vec<f32> {
+        assert_eq!(inputs.len(), self.sizes[0]);
+        let mut activation = inputs.to_vec();
+        let mut weights = self.weights.iter();
+        for &size in &self.sizes[1..] {
+            activation = (0..size)
+                .map(|_| {
+                    let bias = *weights.next().unwrap();
+                    let sum: f32 = activation.iter().map(|a| a * weights.next().unwrap()).sum();
+                    (bias + sum).tanh()
+                })
+                .collect();
+        }
+        activation
+    }
+}
+
+/// 連続時間リカレントニューラルネットワーク(CTRNN)
+///
+/// ニューロンiの状態y_iは τ_i dy_i/dt = -y_i + Σ_j w_ji σ(y_j + θ_j) + I_i に従う。
+/// 入力は先頭の`inputs`個のニューロンに加わり、出力は末尾の`outputs`個のニューロンの発火率σ(y + θ)
+///
+/// 遺伝子型は(ニューロン間の重み n×n, バイアス n, 時定数 n)の順に並ぶ。時定数は遺伝子gから 1 + e^g で得る
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::neural::{Controller, Ctrnn};
+///
+/// let size = 3;
+/// let genome = vec![0.1; Ctrnn::genome_len(size)];
+/// let mut network = Ctrnn::from_genome(size, 1, 1, &genome, 0.1);
+/// let first = network.act(&[1.0]);
+/// let second = network.act(&[1.0]);
+/// // 状態を持つので同じ入力でも出力が変わる
+/// assert!(first[0] != second[0]);
+/// network.reset();
+/// assert_eq!(network.act(&[1.0]), first);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ctrnn {
+    size: usize,
+    inputs: usize,
+    outputs: usize,
+    weights: Vec<f32>,
+    biases: Vec<f32>,
+    taus: Vec<f32>,
+    state: Vec<f32>,
+    dt: f32,
+}
+
+impl Ctrnn {
+    /// ニューロン`size`個のネットワークに必要な遺伝子の数
+    pub fn genome_len(size: usize) -> usize {
+        size * size + 2 * size
+    }
+
+    /// 遺伝子型`genome`からネットワークを生成する
+    ///
+    /// # Arguments
+    /// * `size` - ニューロンの数
+    /// * `inputs` - 入力を受け取るニューロンの数
+    /// * `outputs` - 出力するニューロンの数
+    /// * `genome` - 遺伝子型
+    /// * `dt` - 1回の`act`で進める時間
+    ///
+    /// # Panics
+    /// 入力や出力の数が`size`を超えるときと、遺伝子の数が`genome_len`と合わないとき
+    pub fn from_genome(size: usize, inputs: usize, outputs: usize, genome: &[f32], dt: f32) -> Ctrnn {
+        assert!(inputs <= size && outputs <= size);
+        assert_eq!(
+            genome.len(),
+            Ctrnn::genome_len(size),
+            "genome length does not match the network"
+        );
+        let (weights, rest) = genome.split_at(size * size);
+        let (biases, taus) = rest.split_at(size);
+        Ctrnn {
+            size,
+            inputs,
+            outputs,
+            weights: weights.to_vec(),
+            biases: biases.to_vec(),
+            taus: taus.iter().map(|g| 1.0 + g.exp()).collect(),
+            state: vec![0.0; size],
+            dt,
+        }
+    }
+
+    /// ニューロンの状態
+    pub fn state(&self) -> &[f32] {
+        &self.state
+    }
+
+    /// 状態を0に戻す
+    pub fn reset(&mut self) {
+        self.state = vec![0.0; self.size];
+    }
+
+    fn firing(&self) -> Vec<f32> {
+        self.state
+            .iter()
+            .zip(&self.biases)
+            .map(|(y, theta)| sigmoid(y + theta))
+            .collect()
+    }
+}
+
+impl Controller for Ctrnn {
+    fn act(&mut self, inputs: &[f32]) -> Vec<f32> {
+        assert_eq!(inputs.len(), self.inputs);
+        let firing = self.firing();
+        let size = self.size;
+        let derivative: Vec<f32> = (0..size)
+            .map(|i| {
+                let synaptic: f32 = (0..size).map(|j| self.weights[j * size + i] * firing[j]).sum();
+                let input = inputs.get(i).cloned().unwrap_or(0.0);
+                (-self.state[i] + synaptic + input) / self.taus[i]
+            })
+            .collect();
+        for (y, dy) in self.state.iter_mut().zip(derivative) {
+            *y += dy * self.dt;
+        }
+        self.firing().split_off(size - self.outputs)
+    }
+}
+
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
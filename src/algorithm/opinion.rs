@@ -0,0 +1,170 @@
+use algorithm::neighborhood::Neighborhood;
+use ndarray::Array2;
+use rand::distributions::{IndependentSample, Range};
+use rand::Rng;
+use visualizer::Matrix;
+
+/// 意見を更新するときの規則
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpinionRule {
+    /// 近傍からランダムに選んだ1人の意見をまねる(voter model)
+    Voter,
+    /// 近傍で最も多い意見に従う。同数の場合はその中からランダムに選ぶ(majority rule)
+    Majority,
+}
+
+/// 格子上の意見のダイナミクス(周期境界条件)
+///
+/// 各ステップでセルの数だけランダムにセルを選んで非同期に更新する。
+/// `noise`の確率でランダムな意見になり、zealot(頑固者)は意見を変えない
+///
+/// # Example
+/// ```
+/// extern crate my_alife;
+/// extern crate rand;
+///
+/// use my_alife::algorithm::neighborhood::Neighborhood;
+/// use my_alife::algorithm::opinion::{OpinionDynamics, OpinionRule};
+///
+/// let mut rng = rand::thread_rng();
+/// // 有限の格子のvoter modelはいずれ必ず合意に達する
+/// let mut model = OpinionDynamics::random((6, 6), 3, OpinionRule::Voter, Neighborhood::VonNeumann, &mut rng);
+/// let time = model.run_until_consensus(100_000, &mut rng);
+/// assert!(time.is_some());
+/// assert!(model.is_consensus());
+/// ```
+pub struct OpinionDynamics {
+    opinions: Matrix<u8>,
+    zealots: Matrix<bool>,
+    num_opinions: u8,
+    rule: OpinionRule,
+    neighborhood: Neighborhood,
+    noise: f32,
+    time: usize,
+}
+
+impl OpinionDynamics {
+    /// 初期状態`opinions`から始める
+    ///
+    /// # Arguments
+    /// * `opinions` - 各セルの意見(0から`num_opinions - 1`)
+    /// * `num_opinions` - 意見の種類の数
+    /// * `rule` - 意見を更新するときの規則
+    /// * `neighborhood` - 近傍の取り方
+    pub fn new(
+        opinions: Matrix<u8>,
+        num_opinions: u8,
+        rule: OpinionRule,
+        neighborhood: Neighborhood,
+    ) -> OpinionDynamics {
+        OpinionDynamics {
+            zealots: Array2::from_elem(opinions.dim(), false),
+            opinions,
+            num_opinions,
+            rule,
+            neighborhood,
+            noise: 0.0,
+            time: 0,
+        }
+    }
+
+    /// 意見が一様にランダムな初期状態から始める
+    pub fn random<R: Rng>(
+        dim: (usize, usize),
+        num_opinions: u8,
+        rule: OpinionRule,
+        neighborhood: Neighborhood,
+        rng: &mut R,
+    ) -> OpinionDynamics {
+        let opinions = Array2::from_shape_fn(dim, |_| rng.gen_range(0, num_opinions));
+        OpinionDynamics::new(opinions, num_opinions, rule, neighborhood)
+    }
+
+    /// 各セルの意見
+    pub fn opinions(&self) -> &Matrix<u8> {
+        &self.opinions
+    }
+
+    /// これまでに進めたステップ数
+    pub fn time(&self) -> usize {
+        self.time
+    }
+
+    /// 更新のたびにランダムな意見になる確率を変更する
+    pub fn set_noise(&mut self, noise: f32) {
+        self.noise = noise.clamp(0.0, 1.0);
+    }
+
+    /// セル(row, col)を意見`opinion`を変えないzealotにする
+    pub fn add_zealot(&mut self, (row, col): (usize, usize), opinion: u8) {
+        self.opinions[[row, col]] = opinion;
+        self.zealots[[row, col]] = true;
+    }
+
+    /// 各意見を持つセルの数
+    pub fn counts(&self) -> Vec<usize> {
+        let mut counts = vec![0; self.num_opinions as usize];
+        for &opinion in self.opinions.iter() {
+            counts[opinion as usize] += 1;
+        }
+        counts
+    }
+
+    /// すべてのセルが同じ意見かどうか
+    pub fn is_consensus(&self) -> bool {
+        self.counts().iter().filter(|&&count| count > 0).count() <= 1
+    }
+
+    /// 1ステップ(セルの数だけの非同期更新)進める
+    pub fn step<R: Rng>(&mut self, rng: &mut R) {
+        let (rows, cols) = self.opinions.dim();
+        let row_range = Range::new(0, rows);
+        let col_range = Range::new(0, cols);
+        let mut counts = vec![0; self.num_opinions as usize];
+        for _ in 0..rows * cols {
+            let cell = (row_range.ind_sample(rng), col_range.ind_sample(rng));
+            if self.zealots[cell] {
+                continue;
+            }
+            if self.noise > 0.0 && rng.gen::<f32>() < self.noise {
+                self.opinions[cell] = rng.gen_range(0, self.num_opinions);
+                continue;
+            }
+            self.opinions[cell] = match self.rule {
+                OpinionRule::Voter => {
+                    let offsets = self.neighborhood.offsets();
+                    let neighbor = self
+                        .neighborhood
+                        .around(cell, (rows, cols))
+                        .nth(rng.gen_range(0, offsets.len()))
+                        .unwrap();
+                    self.opinions[neighbor]
+                }
+                OpinionRule::Majority => {
+                    self.neighborhood.count_states(&self.opinions, cell, &mut counts);
+                    let max = *counts.iter().max().unwrap();
+                    let candidates: Vec<u8> = (0..self.num_opinions).filter(|&o| counts[o as usize] == max).collect();
+                    *rng.choose(&candidates).unwrap()
+                }
+            };
+        }
+        self.time += 1;
+    }
+
+    /// 合意に達するまで(最大`max_steps`ステップ)進め、合意に達した時刻を返す
+    ///
+    /// noiseがあると合意しても崩れることがあるので、合意した最初の時刻を返して止める
+    pub fn run_until_consensus<R: Rng>(&mut self, max_steps: usize, rng: &mut R) -> Option<usize> {
+        for _ in 0..max_steps {
+            if self.is_consensus() {
+                return Some(self.time);
+            }
+            self.step(rng);
+        }
+        if self.is_consensus() {
+            Some(self.time)
+        } else {
+            None
+        }
+    }
+}
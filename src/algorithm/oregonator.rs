@@ -0,0 +1,114 @@
+use algorithm::reaction_diffusion::{CellReaction, ReactionDiffusion};
+use ndarray::Array2;
+use rand::Rng;
+use visualizer::Matrix;
+
+/// Belousov-Zhabotinsky反応を表す3変数のOregonatorモデル(Tysonによる無次元化)
+///
+/// * `ε dx/dt = qy - xy + x(1 - x)`
+/// * `δ dy/dt = -qy - xy + 2fz`
+/// * `dz/dt = x - z`
+///
+/// xが活性化因子(HBrO2)、yが抑制因子(Br⁻)、zが触媒(酸化型のCe)の濃度で、
+/// 局所的に振動しながら螺旋波や標的パターンが広がる。`δ`が小さいほど硬い方程式になるので、時間刻みは`δ`より十分小さくする
+///
+/// # Example
+/// ```
+/// extern crate my_alife;
+/// extern crate rand;
+///
+/// use my_alife::algorithm::integrator::Integrator;
+/// use my_alife::algorithm::oregonator::Oregonator;
+///
+/// let model = Oregonator::default();
+/// let mut fields = model.initial_fields((16, 16), &mut rand::thread_rng());
+/// assert_eq!(fields.len(), 3);
+/// for _ in 0..1000 {
+///     Integrator::Euler.step(&model, &mut fields, 5e-4);
+/// }
+/// assert!(fields.iter().all(|field| field.iter().all(|e| e.is_finite())));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Oregonator {
+    /// xの時間スケール
+    pub epsilon: f32,
+    /// yの時間スケール
+    pub delta: f32,
+    /// 反応速度の比
+    pub q: f32,
+    /// 化学量論係数
+    pub f: f32,
+    /// x, y, zの拡散係数
+    pub diffusions: [f32; 3],
+}
+
+impl Default for Oregonator {
+    /// 局所的に振動するパラメータ。触媒zは拡散しない
+    fn default() -> Oregonator {
+        Oregonator {
+            epsilon: 0.1,
+            delta: 0.01,
+            q: 0.002,
+            f: 0.7,
+            diffusions: [1.0, 1.0, 0.0],
+        }
+    }
+}
+
+impl Oregonator {
+    /// 静止状態に、振動の位相がずれた斑点をランダムに置いた初期状態
+    pub fn initial_fields<R: Rng>(&self, dim: (usize, usize), rng: &mut R) -> Vec<Matrix<f32>> {
+        let mut fields = vec![
+            Array2::from_elem(dim, self.q),
+            Array2::from_elem(dim, 0.1),
+            Array2::from_elem(dim, self.q),
+        ];
+        let (rows, cols) = dim;
+        for _ in 0..(rows * cols / 1024).max(1) {
+            let (row, col) = (rng.gen_range(0, rows), rng.gen_range(0, cols));
+            let x = rng.gen_range(0.1, 0.8);
+            for r in row.saturating_sub(2)..(row + 2).min(rows) {
+                for c in col.saturating_sub(2)..(col + 2).min(cols) {
+                    fields[0][[r, c]] = x;
+                    fields[1][[r, c]] = 0.0;
+                }
+            }
+        }
+        fields
+    }
+
+    /// 描画するときの各物質の濃度の範囲の目安
+    pub fn ranges(&self) -> Vec<(f32, f32)> {
+        vec![(0.0, 0.8), (0.0, 1.0), (0.0, 0.3)]
+    }
+}
+
+impl ReactionDiffusion for Oregonator {
+    fn dx(&self) -> f32 {
+        1.0
+    }
+
+    fn diffusions(&self) -> Vec<f32> {
+        self.diffusions.to_vec()
+    }
+
+    fn reaction(&self, fields: &[Matrix<f32>]) -> Vec<Matrix<f32>> {
+        let (x, y, z) = (&fields[0], &fields[1], &fields[2]);
+        let xy = x * y;
+        vec![
+            (y * self.q - &xy + &(x * &x.mapv(|e| 1.0 - e))) / self.epsilon,
+            (-(y * self.q) - &xy + &(z * (2.0 * self.f))) / self.delta,
+            x - z,
+        ]
+    }
+}
+
+impl CellReaction for Oregonator {
+    fn cell_reaction(&self, cell: &[f32], rates: &mut [f32]) {
+        let (x, y, z) = (cell[0], cell[1], cell[2]);
+        let xy = x * y;
+        rates[0] = (y * self.q - xy + x * (1.0 - x)) / self.epsilon;
+        rates[1] = (-(y * self.q) - xy + z * (2.0 * self.f)) / self.delta;
+        rates[2] = x - z;
+    }
+}
@@ -0,0 +1,292 @@
+use algorithm::game_of_life::{ALIVE, DEAD};
+use algorithm::lenia::Animal;
+use algorithm::region::paste;
+use failure;
+use ndarray::Array2;
+use std::collections::BTreeMap;
+use visualizer::Matrix;
+
+/// パターンを置くときの回転(時計回り)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    /// 回転しない
+    Deg0,
+    /// 90度
+    Deg90,
+    /// 180度
+    Deg180,
+    /// 270度
+    Deg270,
+}
+
+/// `pattern`を左右反転(`flip`がtrueのとき)してから回転する
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::arr2;
+/// use my_alife::algorithm::patterns::{transform, Rotation};
+///
+/// let pattern = arr2(&[[1, 2, 3], [4, 5, 6]]);
+/// assert_eq!(transform(&pattern, Rotation::Deg90, false), arr2(&[[4, 1], [5, 2], [6, 3]]));
+/// assert_eq!(transform(&pattern, Rotation::Deg0, true), arr2(&[[3, 2, 1], [6, 5, 4]]));
+/// ```
+pub fn transform<A: Copy>(pattern: &Matrix<A>, rotation: Rotation, flip: bool) -> Matrix<A> {
+    let (rows, cols) = pattern.dim();
+    let flipped = |r: usize, c: usize| pattern[[r, if flip { cols - 1 - c } else { c }]];
+    match rotation {
+        Rotation::Deg0 => Array2::from_shape_fn((rows, cols), |(r, c)| flipped(r, c)),
+        Rotation::Deg90 => Array2::from_shape_fn((cols, rows), |(r, c)| flipped(rows - 1 - c, r)),
+        Rotation::Deg180 => Array2::from_shape_fn((rows, cols), |(r, c)| flipped(rows - 1 - r, cols - 1 - c)),
+        Rotation::Deg270 => Array2::from_shape_fn((cols, rows), |(r, c)| flipped(c, cols - 1 - r)),
+    }
+}
+
+/// `pattern`を回転・反転して、左上が(`x`, `y`)になるように`state`に書き込む(周期境界条件)
+///
+/// # Arguments
+/// * `state` - 書き込まれる盤面
+/// * `pattern` - 書き込むパターン
+/// * `x`, `y` - 左上の列と行
+/// * `rotation` - 回転
+/// * `flip` - trueならば回転の前に左右反転する
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::algorithm::patterns::{stamp, PatternLibrary, Rotation};
+///
+/// let library = PatternLibrary::default();
+/// let mut cells = Array2::zeros((32, 32));
+/// stamp(&mut cells, &library.cells("glider").unwrap(), 10, 5, Rotation::Deg90, false);
+/// assert_eq!(cells.iter().filter(|&&e| e == 1).count(), 5);
+/// ```
+pub fn stamp<A: Copy>(state: &mut Matrix<A>, pattern: &Matrix<A>, x: isize, y: isize, rotation: Rotation, flip: bool) {
+    paste(state, &transform(pattern, rotation, flip), y, x, true);
+}
+
+/// Run Length Encoded(RLE)形式のパターンを読み込む
+///
+/// `b`は死んだセル、`o`は生きたセル、`$`は改行、`!`は終わりを表す。
+/// 多状態のパターンの`.`と`A`〜`X`にも対応し、それぞれ0と1〜24になる。
+/// 25以上の状態は`p`〜`y`を前につけて表す(`pA`が25、`qA`が49)
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::patterns::parse_rle;
+///
+/// let glider = parse_rle("#N Glider\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!").unwrap();
+/// assert_eq!(glider.row(2).to_vec(), vec![1, 1, 1]);
+/// assert!(parse_rle("x = 3, y = 3\n3z!").is_err());
+///
+/// let states = parse_rle("x = 4, y = 1, rule = JvN29\nX2pDqA!").unwrap();
+/// assert_eq!(states.row(0).to_vec(), vec![24, 28, 28, 49]);
+/// ```
+pub fn parse_rle(text: &str) -> Result<Matrix<u8>, failure::Error> {
+    let mut lines = text
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.starts_with('#'));
+    let mut dim = (0, 0);
+    let mut body = String::new();
+    for line in &mut lines {
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('x') {
+            dim = parse_header(line)?;
+        } else {
+            body.push_str(line);
+        }
+        break;
+    }
+    for line in lines {
+        body.push_str(line);
+    }
+
+    let mut rows: Vec<Vec<u8>> = vec![Vec::new()];
+    let mut count = String::new();
+    // `p`〜`y`の接頭辞で、続く`A`〜`X`に足す数
+    let mut offset = 0;
+    for c in body.chars() {
+        let run = || count.parse::<usize>().unwrap_or(1);
+        if offset > 0 && !('A'..='X').contains(&c) {
+            return Err(format_err!("'{}' must follow a multi-state prefix in RLE", c));
+        }
+        match c {
+            '0'..='9' => {
+                count.push(c);
+                continue;
+            }
+            'p'..='y' => {
+                offset = (c as usize - 'p' as usize + 1) * 24;
+                continue;
+            }
+            '!' => break,
+            '$' => {
+                for _ in 0..run() {
+                    rows.push(Vec::new());
+                }
+            }
+            'b' | '.' => push_run(&mut rows, DEAD, run()),
+            'o' => push_run(&mut rows, ALIVE, run()),
+            'A'..='X' => {
+                let state = offset + (c as usize - 'A' as usize + 1);
+                if state > u8::MAX as usize {
+                    return Err(format_err!("state {} is out of range in RLE", state));
+                }
+                push_run(&mut rows, state as u8, run());
+                offset = 0;
+            }
+            c if c.is_whitespace() => {}
+            c => return Err(format_err!("unexpected character '{}' in RLE", c)),
+        }
+        count.clear();
+    }
+    let cols = rows.iter().map(|row| row.len()).max().unwrap_or(0).max(dim.0);
+    let height = rows.len().max(dim.1);
+    let mut cells = Array2::zeros((height, cols));
+    for (r, row) in rows.iter().enumerate() {
+        for (c, &state) in row.iter().enumerate() {
+            cells[[r, c]] = state;
+        }
+    }
+    Ok(cells)
+}
+
+// `x = 3, y = 3, rule = B3/S23`の形の行から(x, y)を読む
+fn parse_header(line: &str) -> Result<(usize, usize), failure::Error> {
+    let mut dim = (0, 0);
+    for item in line.split(',') {
+        let mut kv = item.splitn(2, '=').map(|s| s.trim());
+        match (kv.next(), kv.next()) {
+            (Some("x"), Some(value)) => dim.0 = value.parse()?,
+            (Some("y"), Some(value)) => dim.1 = value.parse()?,
+            _ => {}
+        }
+    }
+    Ok(dim)
+}
+
+fn push_run(rows: &mut [Vec<u8>], state: u8, run: usize) {
+    if let Some(row) = rows.last_mut() {
+        row.extend((0..run).map(|_| state));
+    }
+}
+
+/// plaintext(.cells)形式のパターンを読み込む。`!`で始まる行はコメント、`.`は死んだセル、`O`は生きたセル
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::patterns::parse_cells;
+///
+/// let blinker = parse_cells("!Name: Blinker\n.O.\n.O.\n.O.\n");
+/// assert_eq!(blinker.dim(), (3, 3));
+/// assert_eq!(blinker.column(1).to_vec(), vec![1, 1, 1]);
+/// ```
+pub fn parse_cells(text: &str) -> Matrix<u8> {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(|line| line.trim_end())
+        .filter(|line| !line.starts_with('!'))
+        .collect();
+    let cols = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    let mut cells = Array2::zeros((lines.len(), cols));
+    for (row, line) in lines.iter().enumerate() {
+        for (col, c) in line.chars().enumerate() {
+            cells[[row, col]] = if c == 'O' || c == '*' { ALIVE } else { DEAD };
+        }
+    }
+    cells
+}
+
+/// 名前をつけて再利用できるパターン(スタンプ)の一覧
+///
+/// 値は0.0〜1.0のMatrixで持つ。Game of Lifeでは`cells()`で0と1に変換し、
+/// Gray-Scottモデルでは`get()`で得たものを濃度vとして使う。Leniaでは`get()`で得たものをそのままセルにする
+pub struct PatternLibrary {
+    stamps: BTreeMap<String, Matrix<f32>>,
+}
+
+impl Default for PatternLibrary {
+    /// Game of Lifeのよく知られたパターンと、Gray-Scottモデルの種と、Leniaの生き物を含む一覧
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::algorithm::patterns::PatternLibrary;
+    ///
+    /// let library = PatternLibrary::default();
+    /// assert_eq!(library.get("orbium").unwrap().dim(), (20, 20));
+    /// ```
+    fn default() -> PatternLibrary {
+        let mut library = PatternLibrary {
+            stamps: BTreeMap::new(),
+        };
+        let life = [
+            ("glider", "bob$2bo$3o!"),
+            ("lwss", "bo2bo$o4b$o3bo$4o!"),
+            ("r-pentomino", "b2o$2ob$bo!"),
+            ("acorn", "bo5b$3bo3b$2o2b3o!"),
+            ("diehard", "6bob$2o6b$bo3b3o!"),
+            (
+                "gosper-glider-gun",
+                "24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4bobo$10bo5bo7bo$11bo3bo$12b2o!",
+            ),
+        ];
+        for &(name, rle) in &life {
+            let cells = parse_rle(rle).expect("builtin pattern should be valid RLE");
+            library.register(name, cells.mapv(|e| e as f32));
+        }
+        // initial_matrixと同じ大きさの正方形と、小さな円
+        library.register("gray-scott-square", Array2::from_elem((20, 20), 0.25));
+        library.register(
+            "gray-scott-dot",
+            Array2::from_shape_fn((7, 7), |(r, c)| {
+                let (dr, dc) = (r as f32 - 3.0, c as f32 - 3.0);
+                if dr * dr + dc * dc <= 9.0 {
+                    0.25
+                } else {
+                    0.0
+                }
+            }),
+        );
+        // Leniaの規則(`Animal::orbium().lenia`)の上で滑るように進む
+        library.register("orbium", Animal::orbium().cells);
+        library
+    }
+}
+
+impl PatternLibrary {
+    /// 何も登録されていない一覧
+    pub fn empty() -> PatternLibrary {
+        PatternLibrary {
+            stamps: BTreeMap::new(),
+        }
+    }
+
+    /// `pattern`を`name`として登録する。同じ名前があれば置き換える
+    pub fn register(&mut self, name: &str, pattern: Matrix<f32>) {
+        self.stamps.insert(name.to_string(), pattern);
+    }
+
+    /// `name`のパターン
+    pub fn get(&self, name: &str) -> Option<&Matrix<f32>> {
+        self.stamps.get(name)
+    }
+
+    /// `name`のパターンを、正の値を生きたセルとしてGame of Lifeの盤面にする
+    pub fn cells(&self, name: &str) -> Option<Matrix<u8>> {
+        self.get(name)
+            .map(|pattern| pattern.mapv(|e| if e > 0.0 { ALIVE } else { DEAD }))
+    }
+
+    /// 登録されている名前(辞書順)
+    pub fn names(&self) -> Vec<&str> {
+        self.stamps.keys().map(|name| name.as_str()).collect()
+    }
+}
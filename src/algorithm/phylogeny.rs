@@ -0,0 +1,346 @@
+use failure;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+use visualizer::Matrix;
+
+/// 1個体の誕生の記録
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    /// 個体の番号
+    pub id: u64,
+    /// 親の番号。交叉で生まれた個体は2つ持つ。先頭の親を系統樹での親とする
+    pub parents: Vec<u64>,
+    /// 生まれた世代
+    pub generation: usize,
+    /// 生まれるときに加わった変化(交叉や突然変異の種類)
+    pub mutations: Vec<String>,
+    /// 評価した適応度
+    pub fitness: Option<f32>,
+}
+
+impl Record {
+    fn to_csv(&self) -> String {
+        let parents: Vec<String> = self.parents.iter().map(|p| p.to_string()).collect();
+        format!(
+            "{},{},{},{},{}",
+            self.id,
+            parents.join(";"),
+            self.generation,
+            self.fitness.map_or(String::new(), |f| f.to_string()),
+            self.mutations.iter().map(|m| escape(m)).collect::<Vec<_>>().join(";")
+        )
+    }
+
+    fn from_csv(line: &str) -> Result<Record, failure::Error> {
+        let invalid = || format_err!("invalid lineage record \"{}\"", line);
+        let fields: Vec<&str> = line.splitn(5, ',').collect();
+        if fields.len() != 5 {
+            return Err(invalid());
+        }
+        let split =
+            |field: &str| -> Vec<String> { field.split(';').filter(|s| !s.is_empty()).map(str::to_string).collect() };
+        Ok(Record {
+            id: fields[0].parse().map_err(|_| invalid())?,
+            parents: split(fields[1])
+                .iter()
+                .map(|p| p.parse().map_err(|_| invalid()))
+                .collect::<Result<_, _>>()?,
+            generation: fields[2].parse().map_err(|_| invalid())?,
+            fitness: if fields[3].is_empty() {
+                None
+            } else {
+                Some(fields[3].parse().map_err(|_| invalid())?)
+            },
+            mutations: split(fields[4]).iter().map(|m| unescape(m)).collect(),
+        })
+    }
+}
+
+// 変化の名前に含まれる区切り文字を`%XX`にする
+fn escape(label: &str) -> String {
+    let mut escaped = String::new();
+    for c in label.chars() {
+        match c {
+            '%' | ';' | ',' | '\n' | '\r' => write!(escaped, "%{:02X}", c as u8).unwrap(),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(label: &str) -> String {
+    let mut unescaped = String::new();
+    let mut rest = label;
+    while let Some(i) = rest.find('%') {
+        unescaped.push_str(&rest[..i]);
+        match rest.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+            Some(byte) => {
+                unescaped.push(byte as char);
+                rest = &rest[i + 3..];
+            }
+            None => {
+                unescaped.push('%');
+                rest = &rest[i + 1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// 個体の親子関係の記録
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::phylogeny::Phylogeny;
+///
+/// let mut phylogeny = Phylogeny::new();
+/// let root = phylogeny.root(0);
+/// let a = phylogeny.birth(&[root], 1, vec!["mutation".to_string()]);
+/// let b = phylogeny.birth(&[root], 1, Vec::new());
+/// let c = phylogeny.birth(&[a, b], 3, vec!["crossover".to_string()]);
+/// assert_eq!(phylogeny.lineage(c), vec![c, a, root]);
+/// assert_eq!(phylogeny.common_ancestor(c, b), Some(b));
+/// // 枝の長さは世代の差
+/// assert_eq!(phylogeny.to_newick(), "((3:2)1:1,2:1)0;");
+/// let d = phylogeny.birth(&[c], 4, vec!["rate=0.1;0.2, swap".to_string()]);
+/// let restored = Phylogeny::from_csv(&phylogeny.to_csv()).unwrap();
+/// assert_eq!(restored.to_newick(), phylogeny.to_newick());
+/// assert_eq!(restored.get(d), phylogeny.get(d));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Phylogeny {
+    records: BTreeMap<u64, Record>,
+    next_id: u64,
+}
+
+impl Phylogeny {
+    /// 空の記録を生成する
+    pub fn new() -> Phylogeny {
+        Phylogeny::default()
+    }
+
+    /// 親のいない個体(初期集団)を記録し、番号を返す
+    pub fn root(&mut self, generation: usize) -> u64 {
+        self.birth(&[], generation, Vec::new())
+    }
+
+    /// `parents`から生まれた個体を記録し、番号を返す
+    pub fn birth(&mut self, parents: &[u64], generation: usize, mutations: Vec<String>) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.records.insert(
+            id,
+            Record {
+                id,
+                parents: parents.to_vec(),
+                generation,
+                mutations,
+                fitness: None,
+            },
+        );
+        id
+    }
+
+    /// 個体`id`の適応度を記録する
+    pub fn set_fitness(&mut self, id: u64, fitness: f32) {
+        if let Some(record) = self.records.get_mut(&id) {
+            record.fitness = Some(fitness);
+        }
+    }
+
+    /// 個体`id`の記録
+    pub fn get(&self, id: u64) -> Option<&Record> {
+        self.records.get(&id)
+    }
+
+    /// 番号順のすべての記録
+    pub fn records(&self) -> impl Iterator<Item = &Record> {
+        self.records.values()
+    }
+
+    /// 記録の数
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// 何も記録されていないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// `id`から先頭の親をたどった系統。自分自身から始まり、根で終わる
+    pub fn lineage(&self, id: u64) -> Vec<u64> {
+        let mut lineage = Vec::new();
+        let mut current = self.records.get(&id);
+        while let Some(record) = current {
+            lineage.push(record.id);
+            current = record.parents.first().and_then(|p| self.records.get(p));
+        }
+        lineage
+    }
+
+    /// いずれかの親をたどって行ける祖先(自分自身を含む)
+    pub fn ancestors(&self, id: u64) -> HashSet<u64> {
+        let mut ancestors = HashSet::new();
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            if let Some(record) = self.records.get(&id) {
+                if ancestors.insert(id) {
+                    stack.extend(&record.parents);
+                }
+            }
+        }
+        ancestors
+    }
+
+    /// 2個体に共通する祖先(自分自身を含む)のうち、最も新しい世代のもの
+    pub fn common_ancestor(&self, a: u64, b: u64) -> Option<u64> {
+        let ancestors = self.ancestors(a);
+        self.ancestors(b)
+            .into_iter()
+            .filter(|id| ancestors.contains(id))
+            .max_by_key(|id| (self.records[id].generation, *id))
+    }
+
+    /// `living`の祖先(いずれかの親をたどれるもの)以外の記録を捨てる
+    pub fn prune(&mut self, living: &[u64]) {
+        let keep: HashSet<u64> = living.iter().flat_map(|&id| self.ancestors(id)).collect();
+        self.records.retain(|id, _| keep.contains(id));
+    }
+
+    /// CSV(id,parents,generation,fitness,mutations)に書き出す。複数の親と変化は`;`で区切る
+    ///
+    /// 変化の名前に含まれる`%`, `;`, `,`と改行は`%3B`のように書き、`from_csv`で元に戻す
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("id,parents,generation,fitness,mutations\n");
+        for record in self.records.values() {
+            csv.push_str(&record.to_csv());
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// `to_csv`で書き出したCSVから復元する。見出しの行はなくてもよい
+    pub fn from_csv(csv: &str) -> Result<Phylogeny, failure::Error> {
+        let mut phylogeny = Phylogeny::new();
+        for line in csv.lines().filter(|line| !line.is_empty() && !line.starts_with("id,")) {
+            let record = Record::from_csv(line)?;
+            phylogeny.next_id = phylogeny.next_id.max(record.id + 1);
+            phylogeny.records.insert(record.id, record);
+        }
+        Ok(phylogeny)
+    }
+
+    // 系統樹での親(記録に残っている先頭の親)ごとの子
+    fn tree(&self) -> (Vec<u64>, BTreeMap<u64, Vec<u64>>) {
+        let mut roots = Vec::new();
+        let mut children: BTreeMap<u64, Vec<u64>> = BTreeMap::new();
+        for record in self.records.values() {
+            match record.parents.first().filter(|p| self.records.contains_key(p)) {
+                Some(&parent) => children.entry(parent).or_default().push(record.id),
+                None => roots.push(record.id),
+            }
+        }
+        (roots, children)
+    }
+
+    /// Newick形式の系統樹。節の名前は番号、枝の長さは世代の差。根が複数あるときは1つにまとめる
+    ///
+    /// 親より前の世代に生まれたと記録された個体の枝の長さは0にする
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::algorithm::phylogeny::Phylogeny;
+    ///
+    /// let mut phylogeny = Phylogeny::new();
+    /// let a = phylogeny.root(5);
+    /// let b = phylogeny.root(0);
+    /// phylogeny.birth(&[a], 3, Vec::new());
+    /// phylogeny.birth(&[b], 1, Vec::new());
+    /// assert_eq!(phylogeny.to_newick(), "((2:0)0,(3:1)1);");
+    /// ```
+    pub fn to_newick(&self) -> String {
+        let (roots, children) = self.tree();
+        let mut newick = String::new();
+        if roots.len() > 1 {
+            newick.push('(');
+        }
+        // (個体, 系統樹での親, 子を書き終えたか)。深い系統樹でもスタックが溢れないように再帰しない
+        let mut stack: Vec<(u64, Option<u64>, bool)> = roots.iter().rev().map(|&r| (r, None, false)).collect();
+        while let Some((id, parent, visited)) = stack.pop() {
+            let first = parent.map_or(roots.first(), |p| children[&p].first()) == Some(&id);
+            match children.get(&id) {
+                Some(kids) if !visited => {
+                    if !first {
+                        newick.push(',');
+                    }
+                    newick.push('(');
+                    stack.push((id, parent, true));
+                    stack.extend(kids.iter().rev().map(|&k| (k, Some(id), false)));
+                    continue;
+                }
+                Some(_) => newick.push(')'),
+                None if !first => newick.push(','),
+                None => {}
+            }
+            write!(newick, "{}", id).unwrap();
+            if let Some(parent) = parent {
+                let length = self.records[&id]
+                    .generation
+                    .saturating_sub(self.records[&parent].generation);
+                write!(newick, ":{}", length).unwrap();
+            }
+        }
+        if roots.len() > 1 {
+            newick.push(')');
+        }
+        newick.push(';');
+        newick
+    }
+
+    /// 系統樹を描いた画像。行が世代、列が葉の並びで、枝は1、背景は0になる
+    ///
+    /// `MatrixVisualizer`に渡すとそのまま表示できる
+    pub fn tree_image(&self) -> Matrix<f32> {
+        let (roots, children) = self.tree();
+        let generations = self.records.values().map(|r| r.generation).max().map_or(0, |g| g + 1);
+        // 葉を左から並べ、節は子の列の平均に置く
+        let mut columns = BTreeMap::new();
+        let mut leaves = 0;
+        let mut stack: Vec<(u64, bool)> = roots.iter().rev().map(|&r| (r, false)).collect();
+        while let Some((id, visited)) = stack.pop() {
+            match children.get(&id) {
+                Some(kids) if !visited => {
+                    stack.push((id, true));
+                    stack.extend(kids.iter().rev().map(|&k| (k, false)));
+                }
+                Some(kids) => {
+                    let sum: usize = kids.iter().map(|k| columns[k]).sum();
+                    columns.insert(id, sum / kids.len());
+                }
+                None => {
+                    columns.insert(id, leaves);
+                    leaves += 1;
+                }
+            }
+        }
+        let mut image = Matrix::zeros((generations.max(1), leaves.max(1)));
+        for record in self.records.values() {
+            let (row, col) = (record.generation, columns[&record.id]);
+            image[[row, col]] = 1.0;
+            if let Some(kids) = children.get(&record.id) {
+                for kid in kids {
+                    let (kid_row, kid_col) = (self.records[kid].generation, columns[kid]);
+                    for c in col.min(kid_col)..=col.max(kid_col) {
+                        image[[row, c]] = 1.0;
+                    }
+                    for r in row..=kid_row {
+                        image[[r, kid_col]] = 1.0;
+                    }
+                }
+            }
+        }
+        image
+    }
+}
@@ -0,0 +1,94 @@
+use algorithm::memory::MemoryUsage;
+use ndarray::Array2;
+use std::mem;
+use visualizer::Matrix;
+
+/// 毎フレーム使う同じ大きさのバッファを使い回すためのプール
+///
+/// 返されたバッファを取っておき、次に`take`されたときに容量が足りればそれを渡す。
+/// 大きさが変わらない限り、最初の数フレームのあとはメモリ確保が起きない
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::pool::BufferPool;
+///
+/// let mut pool = BufferPool::new();
+/// for _ in 0..100 {
+///     let next = pool.take_matrix((64, 64), 0.0f32);
+///     pool.recycle(next);
+/// }
+/// assert_eq!(pool.allocations(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BufferPool<A> {
+    free: Vec<Vec<A>>,
+    allocations: usize,
+}
+
+impl<A> Default for BufferPool<A> {
+    fn default() -> BufferPool<A> {
+        BufferPool {
+            free: Vec::new(),
+            allocations: 0,
+        }
+    }
+}
+
+impl<A: Copy> BufferPool<A> {
+    /// 空のプール
+    pub fn new() -> BufferPool<A> {
+        BufferPool::default()
+    }
+
+    /// 長さ`len`ですべて`value`のバッファを取り出す
+    pub fn take(&mut self, len: usize, value: A) -> Vec<A> {
+        let mut buffer = match self.free.iter().position(|buffer| buffer.capacity() >= len) {
+            Some(i) => self.free.swap_remove(i),
+            None => {
+                self.allocations += 1;
+                Vec::with_capacity(len)
+            }
+        };
+        buffer.clear();
+        buffer.resize(len, value);
+        buffer
+    }
+
+    /// 大きさ`dim`ですべて`value`のMatrixを取り出す
+    pub fn take_matrix(&mut self, dim: (usize, usize), value: A) -> Matrix<A> {
+        Array2::from_shape_vec(dim, self.take(dim.0 * dim.1, value)).unwrap()
+    }
+
+    /// バッファを返す
+    pub fn put(&mut self, buffer: Vec<A>) {
+        if buffer.capacity() > 0 {
+            self.free.push(buffer);
+        }
+    }
+
+    /// 使い終わったMatrixを返す
+    pub fn recycle(&mut self, matrix: Matrix<A>) {
+        self.put(matrix.into_raw_vec());
+    }
+
+    /// 容量が足りずに新しくメモリを確保した回数
+    pub fn allocations(&self) -> usize {
+        self.allocations
+    }
+
+    /// 取っておいているバッファの数
+    pub fn len(&self) -> usize {
+        self.free.len()
+    }
+
+    /// 取っておいているバッファがないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.free.is_empty()
+    }
+}
+
+impl<A> MemoryUsage for BufferPool<A> {
+    fn memory_usage(&self) -> usize {
+        self.free.iter().map(|buffer| buffer.capacity() * mem::size_of::<A>()).sum()
+    }
+}
@@ -0,0 +1,374 @@
+use algorithm::neighborhood::Neighborhood;
+use ndarray::Array2;
+use rand::Rng;
+use visualizer::palette::Palette;
+use visualizer::Matrix;
+
+/// 培地(どの細胞でもないセル)の細胞の番号と種類
+pub const MEDIUM: usize = 0;
+
+// `id_map`で培地と境界に使う状態と、細胞の色に使う状態の数
+const MEDIUM_STATE: u8 = 0;
+const BOUNDARY_STATE: u8 = 1;
+const CELL_STATES: usize = 254;
+
+/// Cellular Pottsモデルのエネルギーと温度
+#[derive(Debug, Clone, PartialEq)]
+pub struct PottsParams {
+    /// 温度。高いほどエネルギーの上がる変化も受け入れられ、細胞の膜が揺らぐ
+    pub temperature: f32,
+    /// 体積を目標に保つ強さ`λ`。体積のエネルギーは`λ * (V - V_target)²`
+    pub lambda_volume: f32,
+    /// 種類どうしの接着のエネルギー`J`。培地を0とした種類の番号で引く対称行列で、小さいほどよくくっつく
+    pub adhesion: Vec<Vec<f32>>,
+    /// 接着のエネルギーを数えるのと、隣のセルをコピーするときの近傍
+    pub neighborhood: Neighborhood,
+}
+
+impl PottsParams {
+    /// GranerとGlazierの細胞選別(cell sorting)のパラメーター。種類1(暗い細胞)どうしが一番強くくっつき、
+    /// 種類2(明るい細胞)に包まれるように集まる
+    pub fn cell_sorting() -> PottsParams {
+        PottsParams {
+            temperature: 10.0,
+            lambda_volume: 2.0,
+            adhesion: vec![vec![0.0, 16.0, 16.0], vec![16.0, 2.0, 11.0], vec![16.0, 11.0, 14.0]],
+            neighborhood: Neighborhood::Moore,
+        }
+    }
+
+    /// 種類`a`と`b`の接着のエネルギー
+    ///
+    /// # Panics
+    /// `adhesion`にない種類のとき
+    pub fn adhesion(&self, a: usize, b: usize) -> f32 {
+        self.adhesion[a][b]
+    }
+}
+
+/// 1つの細胞
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PottsCell {
+    /// 種類。`PottsParams::adhesion`を引くときの番号で、1から始まる
+    pub cell_type: usize,
+    /// 目標の体積(セル数)
+    pub target_volume: f32,
+    /// 今の体積(セル数)
+    pub volume: usize,
+}
+
+/// Cellular Pottsモデル(Glazier–Graner–Hogewegモデル)
+///
+/// 格子の各セルに細胞の番号を持ち、隣のセルの番号をコピーする変化を、体積と接着の
+/// エネルギー(ハミルトニアン)の変化`ΔH`からMetropolis法で受け入れる。`ΔH <= 0`なら必ず、
+/// そうでなければ確率`exp(-ΔH / T)`で受け入れる。周期境界条件
+///
+/// # Example
+/// ```
+/// extern crate rand;
+/// extern crate my_alife;
+///
+/// use rand::{SeedableRng, XorShiftRng};
+/// use my_alife::algorithm::potts::{CellularPotts, PottsParams};
+///
+/// let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+/// let mut potts = CellularPotts::cell_sorting((40, 40), 4, 16, PottsParams::cell_sorting(), &mut rng);
+/// assert_eq!(potts.cells().len(), 40);
+/// let (mixed, energy) = (potts.boundary_length(1, 2), potts.energy());
+/// potts.run(20, &mut rng);
+/// // 同じ種類の細胞どうしが集まり、違う種類との境界が短くなる
+/// assert!(potts.boundary_length(1, 2) < mixed);
+/// assert!(potts.energy() < energy);
+/// // どの細胞も消えずに、体積は目標の16セルの近くに保たれる
+/// assert!(potts.cells().iter().all(|cell| cell.volume > 4 && cell.volume < 28));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CellularPotts {
+    sigma: Matrix<usize>,
+    cells: Vec<PottsCell>,
+    params: PottsParams,
+    time: usize,
+}
+
+impl CellularPotts {
+    /// 大きさ`dim`(行, 列)の、全体が培地の格子を作る
+    pub fn new(dim: (usize, usize), params: PottsParams) -> CellularPotts {
+        CellularPotts {
+            sigma: Array2::from_elem(dim, MEDIUM),
+            cells: Vec::new(),
+            params,
+            time: 0,
+        }
+    }
+
+    /// 格子の中央の半径`radius`の円を一辺`size`の正方形の細胞で敷き詰める。種類は1と2からランダムに選ぶ
+    ///
+    /// 目標の体積は`size * size`。円からはみ出す細胞は置かない
+    ///
+    /// # Panics
+    /// `size`が0のとき
+    pub fn cell_sorting<R: Rng>(
+        dim: (usize, usize),
+        size: usize,
+        radius: usize,
+        params: PottsParams,
+        rng: &mut R,
+    ) -> CellularPotts {
+        assert!(size > 0, "cell size must be positive");
+        let mut potts = CellularPotts::new(dim, params);
+        let center = (dim.0 as f32 / 2.0, dim.1 as f32 / 2.0);
+        let inside = |(row, col): (usize, usize)| {
+            let (dr, dc) = (row as f32 + 0.5 - center.0, col as f32 + 0.5 - center.1);
+            dr * dr + dc * dc <= (radius * radius) as f32
+        };
+        for top in (0..dim.0.saturating_sub(size - 1)).step_by(size) {
+            for left in (0..dim.1.saturating_sub(size - 1)).step_by(size) {
+                let corners = [
+                    (top, left),
+                    (top + size - 1, left),
+                    (top, left + size - 1),
+                    (top + size - 1, left + size - 1),
+                ];
+                if corners.iter().all(|&corner| inside(corner)) {
+                    let cell_type = rng.gen_range(1, 3);
+                    potts.add_cell(cell_type, (size * size) as f32, (top, left), (size, size));
+                }
+            }
+        }
+        potts
+    }
+
+    /// 左上が`origin`で大きさ`size`(行, 列)の長方形に、種類`cell_type`の細胞を置いて番号を返す
+    ///
+    /// 番号は1から順に振られる。長方形のうち格子からはみ出す部分は無視し、元の細胞を上書きする
+    pub fn add_cell(
+        &mut self,
+        cell_type: usize,
+        target_volume: f32,
+        origin: (usize, usize),
+        size: (usize, usize),
+    ) -> usize {
+        self.cells.push(PottsCell {
+            cell_type,
+            target_volume,
+            volume: 0,
+        });
+        let id = self.cells.len();
+        let (rows, cols) = self.dim();
+        for row in origin.0..(origin.0 + size.0).min(rows) {
+            for col in origin.1..(origin.1 + size.1).min(cols) {
+                self.set(row, col, id);
+            }
+        }
+        id
+    }
+
+    /// 格子の大きさ
+    pub fn dim(&self) -> (usize, usize) {
+        self.sigma.dim()
+    }
+
+    /// エネルギーと温度
+    pub fn params(&self) -> &PottsParams {
+        &self.params
+    }
+
+    /// エネルギーと温度を変更する
+    pub fn params_mut(&mut self) -> &mut PottsParams {
+        &mut self.params
+    }
+
+    /// セルごとの細胞の番号。培地は`MEDIUM`
+    pub fn sigma(&self) -> &Matrix<usize> {
+        &self.sigma
+    }
+
+    /// 細胞たち。番号`id`の細胞は`cells()[id - 1]`
+    pub fn cells(&self) -> &[PottsCell] {
+        &self.cells
+    }
+
+    /// 番号`id`の細胞。培地のときは`None`
+    pub fn cell(&self, id: usize) -> Option<&PottsCell> {
+        id.checked_sub(1).and_then(|index| self.cells.get(index))
+    }
+
+    /// 番号`id`の細胞の種類。培地は`MEDIUM`
+    pub fn cell_type(&self, id: usize) -> usize {
+        self.cell(id).map_or(MEDIUM, |cell| cell.cell_type)
+    }
+
+    /// 経過したモンテカルロステップ数
+    pub fn time(&self) -> usize {
+        self.time
+    }
+
+    /// セル(row, col)を番号`id`の細胞にする。体積も更新する
+    pub fn set(&mut self, row: usize, col: usize, id: usize) {
+        let old = self.sigma[[row, col]];
+        if old != MEDIUM {
+            self.cells[old - 1].volume -= 1;
+        }
+        if id != MEDIUM {
+            self.cells[id - 1].volume += 1;
+        }
+        self.sigma[[row, col]] = id;
+    }
+
+    /// 全体のエネルギー。違う細胞どうしが接する近傍の組ごとの接着のエネルギーと、細胞ごとの体積のエネルギーの和
+    pub fn energy(&self) -> f32 {
+        let (rows, cols) = self.dim();
+        let mut adhesion = 0.0;
+        for ((row, col), &id) in self.sigma.indexed_iter() {
+            let cell_type = self.cell_type(id);
+            for neighbor in self.params.neighborhood.around((row, col), (rows, cols)) {
+                let other = self.sigma[neighbor];
+                if other != id {
+                    adhesion += self.params.adhesion(cell_type, self.cell_type(other));
+                }
+            }
+        }
+        let volume: f32 = self
+            .cells
+            .iter()
+            .map(|cell| self.params.lambda_volume * (cell.volume as f32 - cell.target_volume).powi(2))
+            .sum();
+        // 近傍の組は両側から2回数えている
+        adhesion / 2.0 + volume
+    }
+
+    /// セル(row, col)を番号`id`の細胞にしたときのエネルギーの変化`ΔH`
+    pub fn energy_change(&self, (row, col): (usize, usize), id: usize) -> f32 {
+        let old = self.sigma[[row, col]];
+        if old == id {
+            return 0.0;
+        }
+        let (old_type, new_type) = (self.cell_type(old), self.cell_type(id));
+        let mut delta = 0.0;
+        for neighbor in self.params.neighborhood.around((row, col), self.dim()) {
+            let other = self.sigma[neighbor];
+            let other_type = self.cell_type(other);
+            if other != old {
+                delta -= self.params.adhesion(old_type, other_type);
+            }
+            if other != id {
+                delta += self.params.adhesion(new_type, other_type);
+            }
+        }
+        let lambda = self.params.lambda_volume;
+        if let Some(cell) = self.cell(old) {
+            delta += lambda * (1.0 - 2.0 * (cell.volume as f32 - cell.target_volume));
+        }
+        if let Some(cell) = self.cell(id) {
+            delta += lambda * (1.0 + 2.0 * (cell.volume as f32 - cell.target_volume));
+        }
+        delta
+    }
+
+    /// 1モンテカルロステップ(セルの数だけのコピーの試行)進める
+    pub fn step<R: Rng>(&mut self, rng: &mut R) {
+        let (rows, cols) = self.dim();
+        let offsets = self.params.neighborhood.offsets();
+        for _ in 0..rows * cols {
+            let (row, col) = (rng.gen_range(0, rows), rng.gen_range(0, cols));
+            let (dr, dc) = offsets[rng.gen_range(0, offsets.len())];
+            let source = (
+                (row as isize + dr).rem_euclid(rows as isize) as usize,
+                (col as isize + dc).rem_euclid(cols as isize) as usize,
+            );
+            let id = self.sigma[source];
+            if id == self.sigma[[row, col]] {
+                continue;
+            }
+            let delta = self.energy_change((row, col), id);
+            if delta <= 0.0 || rng.next_f32() < (-delta / self.params.temperature).exp() {
+                self.set(row, col, id);
+            }
+        }
+        self.time += 1;
+    }
+
+    /// `steps`モンテカルロステップ進める
+    pub fn run<R: Rng>(&mut self, steps: usize, rng: &mut R) {
+        for _ in 0..steps {
+            self.step(rng);
+        }
+    }
+
+    /// 種類`a`と`b`の細胞が上下左右で接している辺の数。細胞選別が進むほど、違う種類どうしの境界は短くなる
+    pub fn boundary_length(&self, a: usize, b: usize) -> usize {
+        let (rows, cols) = self.dim();
+        let mut count = 0;
+        for ((row, col), &id) in self.sigma.indexed_iter() {
+            for &neighbor in &[((row + 1) % rows, col), (row, (col + 1) % cols)] {
+                let other = self.sigma[neighbor];
+                let (s, t) = (self.cell_type(id), self.cell_type(other));
+                if other != id && ((s, t) == (a, b) || (s, t) == (b, a)) {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    /// 上下左右のどれかが違う細胞のセルが`true`のMatrix。培地のセルは含まない
+    pub fn boundaries(&self) -> Matrix<bool> {
+        let (rows, cols) = self.dim();
+        Array2::from_shape_fn((rows, cols), |(row, col)| {
+            let id = self.sigma[[row, col]];
+            id != MEDIUM
+                && Neighborhood::VonNeumann
+                    .around((row, col), (rows, cols))
+                    .any(|neighbor| self.sigma[neighbor] != id)
+        })
+    }
+
+    /// 細胞の種類の地図。培地は0
+    pub fn cell_types(&self) -> Matrix<u8> {
+        self.sigma.mapv(|id| self.cell_type(id).min(255) as u8)
+    }
+
+    /// 細胞の番号を`id_palette`で描くための地図。培地は0、細胞の境界は1で、ほかは番号ごとに2〜255を繰り返す
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::algorithm::potts::{id_palette, CellularPotts, PottsParams};
+    ///
+    /// let mut potts = CellularPotts::new((8, 8), PottsParams::cell_sorting());
+    /// potts.add_cell(1, 9.0, (1, 1), (3, 3));
+    /// let map = potts.id_map();
+    /// assert_eq!(map[[0, 0]], 0);
+    /// assert_eq!(map[[1, 1]], 1);
+    /// assert_eq!(map[[2, 2]], 2);
+    /// assert_eq!(id_palette().name(1), Some("Boundary"));
+    /// ```
+    pub fn id_map(&self) -> Matrix<u8> {
+        let boundaries = self.boundaries();
+        let mut map = self.sigma.mapv(|id| match id {
+            MEDIUM => MEDIUM_STATE,
+            id => (2 + (id - 1) % CELL_STATES) as u8,
+        });
+        map.zip_mut_with(&boundaries, |state, &boundary| {
+            if boundary {
+                *state = BOUNDARY_STATE;
+            }
+        });
+        map
+    }
+}
+
+/// `CellularPotts::id_map`を描くためのパレット。細胞の番号ごとに違う色で、境界は白
+pub fn id_palette() -> Palette {
+    let mut palette = Palette::categorical(CELL_STATES + 2);
+    palette.set(MEDIUM_STATE, "Medium", [20, 20, 20]);
+    palette.set(BOUNDARY_STATE, "Boundary", [255, 255, 255]);
+    palette
+}
+
+/// `CellularPotts::cell_types`を描くためのパレット。細胞選別の暗い細胞と明るい細胞
+pub fn cell_type_palette() -> Palette {
+    Palette::new()
+        .with("Medium", [20, 20, 20])
+        .with("Dark", [180, 40, 40])
+        .with("Light", [240, 220, 120])
+}
@@ -0,0 +1,375 @@
+use algorithm::checkpoint::{join, parse_list, Checkpoint, Persist};
+use algorithm::evolution::Genome;
+use failure;
+use rand::Rng;
+use std::cmp::Ordering;
+use visualizer::Matrix;
+
+/// 行動記述子の間のユークリッド距離
+pub fn behavior_distance(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum::<f32>().sqrt()
+}
+
+/// 新規性探索(Novelty Search)のための、これまでに見つかった珍しい行動の記録
+///
+/// # Example
+/// ```
+/// extern crate my_alife;
+/// extern crate rand;
+///
+/// use my_alife::algorithm::evolution::{random_genome, GaParams, GeneticAlgorithm};
+/// use my_alife::algorithm::quality_diversity::NoveltyArchive;
+///
+/// let mut rng = rand::thread_rng();
+/// let population = (0..20).map(|_| random_genome(2, 0.1, &mut rng)).collect();
+/// let mut ga = GeneticAlgorithm::new(population, GaParams::default());
+/// let mut archive = NoveltyArchive::new(5, 0.5);
+/// for _ in 0..30 {
+///     // 遺伝子型そのものを行動とみなし、適応度の代わりに新規性で選択する
+///     let behaviors: Vec<Vec<f32>> = ga.population().to_vec();
+///     let novelty = archive.score(&behaviors);
+///     ga.set_fitness(novelty);
+///     ga.next_generation(&mut rng);
+/// }
+/// // 珍しい行動が記録されていき、集団は初期値の近くから広がっていく
+/// assert!(!archive.is_empty());
+/// let spread = archive.behaviors().iter().map(|b| b[0].abs().max(b[1].abs())).fold(0.0, f32::max);
+/// assert!(spread > 0.5);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoveltyArchive {
+    behaviors: Vec<Vec<f32>>,
+    k: usize,
+    threshold: f32,
+}
+
+impl NoveltyArchive {
+    /// 空の記録を生成する
+    ///
+    /// # Arguments
+    /// * `k` - 新規性を測るときに使う最近傍の数
+    /// * `threshold` - 新規性がこれを超えた行動を記録する
+    pub fn new(k: usize, threshold: f32) -> NoveltyArchive {
+        NoveltyArchive {
+            behaviors: Vec::new(),
+            k,
+            threshold,
+        }
+    }
+
+    /// 記録された行動
+    pub fn behaviors(&self) -> &[Vec<f32>] {
+        &self.behaviors
+    }
+
+    /// 記録された行動の数
+    pub fn len(&self) -> usize {
+        self.behaviors.len()
+    }
+
+    /// 何も記録されていないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.behaviors.is_empty()
+    }
+
+    /// `behavior`の新規性。`population`と記録の中で近いk個の行動までの平均距離
+    pub fn novelty(&self, behavior: &[f32], population: &[Vec<f32>]) -> f32 {
+        let mut distances: Vec<f32> = population
+            .iter()
+            .chain(&self.behaviors)
+            .map(|other| behavior_distance(behavior, other))
+            .collect();
+        distances.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        // 先頭は自分自身との距離0なので飛ばす
+        let neighbors: Vec<f32> = distances.into_iter().skip(1).take(self.k).collect();
+        if neighbors.is_empty() {
+            return 0.0;
+        }
+        neighbors.iter().sum::<f32>() / neighbors.len() as f32
+    }
+
+    /// 集団の行動それぞれの新規性を返し、閾値を超えたものを記録する
+    pub fn score(&mut self, population: &[Vec<f32>]) -> Vec<f32> {
+        let novelty: Vec<f32> = population
+            .iter()
+            .map(|behavior| self.novelty(behavior, population))
+            .collect();
+        for (behavior, &n) in population.iter().zip(&novelty) {
+            if n > self.threshold {
+                self.behaviors.push(behavior.clone());
+            }
+        }
+        novelty
+    }
+
+    /// 記録をチェックポイントに書き出す
+    pub fn to_checkpoint(&self) -> Checkpoint {
+        let mut checkpoint = Checkpoint::new("novelty");
+        checkpoint.push("k", self.k);
+        checkpoint.push("threshold", self.threshold);
+        for behavior in &self.behaviors {
+            checkpoint.push("behavior", join(behavior));
+        }
+        checkpoint
+    }
+
+    /// `to_checkpoint`で書き出した記録から復元する
+    pub fn from_checkpoint(checkpoint: &Checkpoint) -> Result<NoveltyArchive, failure::Error> {
+        checkpoint.expect_kind("novelty")?;
+        Ok(NoveltyArchive {
+            behaviors: checkpoint
+                .all("behavior")
+                .into_iter()
+                .map(parse_list)
+                .collect::<Result<_, _>>()?,
+            k: checkpoint.parse("k")?,
+            threshold: checkpoint.parse("threshold")?,
+        })
+    }
+}
+
+/// MAP-Elitesのエリート
+#[derive(Debug, Clone, PartialEq)]
+pub struct Elite<G> {
+    /// 遺伝子型
+    pub genome: G,
+    /// 適応度
+    pub fitness: f32,
+    /// 行動記述子
+    pub behavior: Vec<f32>,
+}
+
+/// 行動記述子の空間を格子に分け、格子ごとに最も適応度の高い個体を残すMAP-Elites
+///
+/// # Example
+/// ```
+/// extern crate my_alife;
+/// extern crate rand;
+///
+/// use my_alife::algorithm::evolution::random_genome;
+/// use my_alife::algorithm::quality_diversity::MapElites;
+///
+/// let mut rng = rand::thread_rng();
+/// // 遺伝子の値を行動とし、原点に近いほど適応度が高い
+/// let evaluate = |genes: &Vec<f32>| (-genes.iter().map(|g| g * g).sum::<f32>(), genes.clone());
+/// let mut map = MapElites::new(&[10, 10], &[(-1.0, 1.0), (-1.0, 1.0)], 0.5);
+/// let initial = (0..20).map(|_| random_genome(2, 0.2, &mut rng)).collect();
+/// map.initialize(initial, &evaluate);
+/// let before = map.coverage();
+/// for _ in 0..50 {
+///     map.step(20, &evaluate, &mut rng);
+/// }
+/// assert!(map.coverage() > before);
+/// assert_eq!(map.fitness_map(-1.0).dim(), (10, 10));
+/// ```
+pub struct MapElites<G> {
+    bins: Vec<usize>,
+    ranges: Vec<(f32, f32)>,
+    mutation_rate: f32,
+    cells: Vec<Option<Elite<G>>>,
+    evaluations: usize,
+}
+
+impl<G: Genome> MapElites<G> {
+    /// 空の格子を生成する
+    ///
+    /// # Arguments
+    /// * `bins` - 行動記述子の次元ごとの分割数
+    /// * `ranges` - 行動記述子の次元ごとの範囲。範囲の外は端の格子に入る
+    /// * `mutation_rate` - 子を作るときの遺伝子ごとの突然変異率
+    ///
+    /// # Panics
+    /// `bins`と`ranges`の長さが違うとき
+    pub fn new(bins: &[usize], ranges: &[(f32, f32)], mutation_rate: f32) -> MapElites<G> {
+        assert_eq!(
+            bins.len(),
+            ranges.len(),
+            "bins and ranges should have the same dimension"
+        );
+        MapElites {
+            bins: bins.to_vec(),
+            ranges: ranges.to_vec(),
+            mutation_rate,
+            cells: vec![None; bins.iter().product()],
+            evaluations: 0,
+        }
+    }
+
+    /// 行動記述子`behavior`が入る格子の番号
+    pub fn cell(&self, behavior: &[f32]) -> usize {
+        assert_eq!(behavior.len(), self.bins.len());
+        behavior
+            .iter()
+            .zip(&self.bins)
+            .zip(&self.ranges)
+            .fold(0, |index, ((&b, &bins), &(min, max))| {
+                let bin = ((b - min) / (max - min) * bins as f32).floor().max(0.0) as usize;
+                index * bins + bin.min(bins - 1)
+            })
+    }
+
+    /// 個体を格子に入れる。格子が空いているか、今のエリートより適応度が高ければ入れ替わる。入ったかどうかを返す
+    pub fn insert(&mut self, genome: G, fitness: f32, behavior: Vec<f32>) -> bool {
+        let cell = self.cell(&behavior);
+        if self.cells[cell].as_ref().is_some_and(|elite| elite.fitness >= fitness) {
+            return false;
+        }
+        self.cells[cell] = Some(Elite {
+            genome,
+            fitness,
+            behavior,
+        });
+        true
+    }
+
+    /// 格子`cell`のエリート
+    pub fn get(&self, cell: usize) -> Option<&Elite<G>> {
+        self.cells[cell].as_ref()
+    }
+
+    /// すべてのエリート
+    pub fn elites(&self) -> impl Iterator<Item = &Elite<G>> {
+        self.cells.iter().filter_map(|cell| cell.as_ref())
+    }
+
+    /// 埋まっている格子の割合
+    pub fn coverage(&self) -> f32 {
+        self.elites().count() as f32 / self.cells.len() as f32
+    }
+
+    /// エリートの適応度の合計(QDスコア)
+    pub fn qd_score(&self) -> f32 {
+        self.elites().map(|elite| elite.fitness).sum()
+    }
+
+    /// これまでに評価した回数
+    pub fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    /// 初期個体を評価して格子に入れる。`evaluate`は(適応度, 行動記述子)を返す
+    pub fn initialize<F: Fn(&G) -> (f32, Vec<f32>)>(&mut self, genomes: Vec<G>, evaluate: F) {
+        for genome in genomes {
+            let (fitness, behavior) = evaluate(&genome);
+            self.evaluations += 1;
+            self.insert(genome, fitness, behavior);
+        }
+    }
+
+    /// ランダムに選んだエリートから`batch`体の子を作り、評価して格子に入れる。入った数を返す
+    ///
+    /// # Panics
+    /// 格子が空のとき
+    pub fn step<F: Fn(&G) -> (f32, Vec<f32>), R: Rng>(&mut self, batch: usize, evaluate: F, rng: &mut R) -> usize {
+        let occupied: Vec<usize> = (0..self.cells.len()).filter(|&i| self.cells[i].is_some()).collect();
+        assert!(!occupied.is_empty(), "map is empty; call initialize first");
+        let children: Vec<G> = (0..batch)
+            .map(|_| {
+                let mother = &self.cells[*rng.choose(&occupied).unwrap()].as_ref().unwrap().genome;
+                let father = &self.cells[*rng.choose(&occupied).unwrap()].as_ref().unwrap().genome;
+                let mut child = mother.crossover(father, rng);
+                child.mutate(self.mutation_rate, rng);
+                child
+            })
+            .collect();
+        let mut inserted = 0;
+        for child in children {
+            let (fitness, behavior) = evaluate(&child);
+            self.evaluations += 1;
+            if self.insert(child, fitness, behavior) {
+                inserted += 1;
+            }
+        }
+        inserted
+    }
+
+    /// 格子の形・エリート・評価回数をチェックポイントに書き出す
+    ///
+    /// # Example
+    /// ```
+    /// extern crate my_alife;
+    ///
+    /// use my_alife::algorithm::checkpoint::CheckpointRng;
+    /// use my_alife::algorithm::evolution::random_genome;
+    /// use my_alife::algorithm::quality_diversity::MapElites;
+    ///
+    /// let evaluate = |genes: &Vec<f32>| (-genes[0].abs(), genes.clone());
+    /// let mut rng = CheckpointRng::new(3);
+    /// let mut map = MapElites::new(&[8, 8], &[(-1.0, 1.0), (-1.0, 1.0)], 0.5);
+    /// map.initialize((0..10).map(|_| random_genome(2, 1.0, &mut rng)).collect(), &evaluate);
+    ///
+    /// let mut restored = MapElites::<Vec<f32>>::from_checkpoint(&map.to_checkpoint()).unwrap();
+    /// let mut restored_rng = rng.clone();
+    /// map.step(10, &evaluate, &mut rng);
+    /// restored.step(10, &evaluate, &mut restored_rng);
+    /// assert_eq!(restored.qd_score(), map.qd_score());
+    /// assert_eq!(restored.evaluations(), 20);
+    /// ```
+    pub fn to_checkpoint(&self) -> Checkpoint
+    where
+        G: Persist,
+    {
+        let mut checkpoint = Checkpoint::new("map_elites");
+        checkpoint.push("bins", join(&self.bins));
+        let ranges: Vec<f32> = self.ranges.iter().flat_map(|&(min, max)| vec![min, max]).collect();
+        checkpoint.push("ranges", join(&ranges));
+        checkpoint.push("mutation_rate", self.mutation_rate);
+        checkpoint.push("evaluations", self.evaluations);
+        for (cell, elite) in self.cells.iter().enumerate() {
+            if let Some(ref elite) = *elite {
+                checkpoint.push("elite", format!("{} {} {}", cell, elite.fitness, join(&elite.behavior)));
+                checkpoint.push("genome", elite.genome.encode());
+            }
+        }
+        checkpoint
+    }
+
+    /// `to_checkpoint`で書き出した状態から復元する
+    pub fn from_checkpoint(checkpoint: &Checkpoint) -> Result<MapElites<G>, failure::Error>
+    where
+        G: Persist,
+    {
+        checkpoint.expect_kind("map_elites")?;
+        let bins: Vec<usize> = parse_list(checkpoint.get("bins")?)?;
+        let ranges: Vec<f32> = parse_list(checkpoint.get("ranges")?)?;
+        if bins.is_empty() || ranges.len() != 2 * bins.len() {
+            return Err(format_err!("checkpoint has an invalid behavior space"));
+        }
+        let ranges: Vec<(f32, f32)> = ranges.chunks(2).map(|r| (r[0], r[1])).collect();
+        let mut map = MapElites::new(&bins, &ranges, checkpoint.parse("mutation_rate")?);
+        map.evaluations = checkpoint.parse("evaluations")?;
+        for (line, genome) in checkpoint.all("elite").into_iter().zip(checkpoint.all("genome")) {
+            let mut words = line.splitn(2, ' ');
+            let cell: usize = words
+                .next()
+                .unwrap_or("")
+                .parse()
+                .map_err(|_| format_err!("invalid elite \"{}\"", line))?;
+            let values: Vec<f32> = parse_list(words.next().unwrap_or(""))?;
+            if values.len() != bins.len() + 1 || cell >= map.cells.len() {
+                return Err(format_err!("invalid elite \"{}\"", line));
+            }
+            map.cells[cell] = Some(Elite {
+                genome: G::decode(genome)?,
+                fitness: values[0],
+                behavior: values[1..].to_vec(),
+            });
+        }
+        Ok(map)
+    }
+
+    /// 2次元の行動記述子について、格子ごとのエリートの適応度。空の格子は`empty`になる
+    ///
+    /// `MatrixVisualizer`に渡すとヒートマップとして表示できる
+    ///
+    /// # Panics
+    /// 行動記述子が2次元でないとき
+    pub fn fitness_map(&self, empty: f32) -> Matrix<f32> {
+        assert_eq!(self.bins.len(), 2, "fitness map needs a 2D behavior space");
+        Matrix::from_shape_fn((self.bins[0], self.bins[1]), |(row, col)| {
+            self.cells[row * self.bins[1] + col]
+                .as_ref()
+                .map_or(empty, |elite| elite.fitness)
+        })
+    }
+}
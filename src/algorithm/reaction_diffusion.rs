@@ -0,0 +1,206 @@
+use ndarray::{Array2, Zip};
+use num::cast as num_cast;
+use num::Integer;
+use num_traits::cast as num_trait_cast;
+use visualizer::Matrix;
+
+/// 反応拡散系 `du/dt = D * ∇²u + R(u)` を表すtrait  
+/// 実装すると`Integrator`で時間発展を解けるようになる
+pub trait ReactionDiffusion {
+    /// 格子間隔
+    fn dx(&self) -> f32;
+
+    /// 各物質の拡散係数。`reaction`に渡される物質の順番と合わせる
+    fn diffusions(&self) -> Vec<f32>;
+
+    /// 反応項R(u)
+    fn reaction(&self, fields: &[Matrix<f32>]) -> Vec<Matrix<f32>>;
+
+    /// 拡散項に使うラプラシアン。初期値は周期境界条件の`discrete_laplacian`
+    fn laplacian(&self, field: &Matrix<f32>, dx: f32) -> Matrix<f32> {
+        discrete_laplacian(field, dx)
+    }
+
+    /// 拡散項と反応項を合わせた時間微分
+    fn derivative(&self, fields: &[Matrix<f32>]) -> Vec<Matrix<f32>> {
+        let dx = self.dx();
+        self.reaction(fields)
+            .into_iter()
+            .zip(fields.iter().zip(self.diffusions()))
+            .map(|(reaction, (field, diffusion))| self.laplacian(field, dx) * diffusion + reaction)
+            .collect()
+    }
+}
+
+/// 周期境界条件での離散ラプラシアン(5点差分)
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::algorithm::reaction_diffusion::discrete_laplacian;
+///
+/// let mut a = Array2::<f32>::zeros((8, 8));
+/// a[[4, 4]] = 1.0;
+/// let l = discrete_laplacian(&a, 1.0);
+/// assert_eq!(l[[4, 4]], -4.0);
+/// assert_eq!(l[[3, 4]], 1.0);
+/// assert_eq!(l.scalar_sum(), 0.0);
+/// ```
+pub fn discrete_laplacian(a: &Matrix<f32>, dx: f32) -> Matrix<f32> {
+    (neighbor_sum(a) - a * 4.0) / (dx * dx)
+}
+
+/// `discrete_laplacian`と同じ値を、一時的な配列を作らずに`out`に書き込む
+///
+/// `discrete_laplacian`は`roll`した配列を4つと和の配列を作るが、こちらは内側を`Zip`で、周囲の1セルだけを添字で計算する。
+/// 結果は`discrete_laplacian`と一致する
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::algorithm::reaction_diffusion::{discrete_laplacian, laplacian_into};
+///
+/// let a = Array2::from_shape_fn((7, 5), |(r, c)| ((r * 5 + c) as f32).sin());
+/// let mut out = Array2::zeros((7, 5));
+/// laplacian_into(&a, 0.5, &mut out);
+/// assert_eq!(out, discrete_laplacian(&a, 0.5));
+/// ```
+///
+/// # Panics
+/// `out`の大きさが`a`と違うとき
+pub fn laplacian_into(a: &Matrix<f32>, dx: f32, out: &mut Matrix<f32>) {
+    assert_eq!(a.dim(), out.dim(), "output has a wrong size");
+    let (rows, cols) = a.dim();
+    let d2 = dx * dx;
+    let at = |row: usize, col: usize| {
+        let (up, down) = ((row + rows - 1) % rows, (row + 1) % rows);
+        let (left, right) = ((col + cols - 1) % cols, (col + 1) % cols);
+        (a[[up, col]] + a[[down, col]] + a[[row, left]] + a[[row, right]] - a[[row, col]] * 4.0) / d2
+    };
+    if rows < 3 || cols < 3 {
+        for ((row, col), e) in out.indexed_iter_mut() {
+            *e = at(row, col);
+        }
+        return;
+    }
+    // 行ごとにZipすると連続したメモリを順に読むだけになり、ベクトル化されやすい
+    for row in 1..rows - 1 {
+        Zip::from(out.slice_mut(s![row, 1..cols - 1]))
+            .and(a.slice(s![row - 1, 1..cols - 1]))
+            .and(a.slice(s![row + 1, 1..cols - 1]))
+            .and(a.slice(s![row, ..cols - 2]))
+            .and(a.slice(s![row, 2..]))
+            .and(a.slice(s![row, 1..cols - 1]))
+            .apply(|e, &up, &down, &left, &right, &center| {
+                *e = (up + down + left + right - center * 4.0) / d2;
+            });
+    }
+    for col in 0..cols {
+        out[[0, col]] = at(0, col);
+        out[[rows - 1, col]] = at(rows - 1, col);
+    }
+    for row in 1..rows - 1 {
+        out[[row, 0]] = at(row, 0);
+        out[[row, cols - 1]] = at(row, cols - 1);
+    }
+}
+
+/// 反応項をセルごとに計算できる反応拡散系。`euler_in_place`で一時的な配列を作らずに時間発展を解ける
+pub trait CellReaction: ReactionDiffusion {
+    /// 1セルの各物質の濃度`cell`から、反応項を`rates`に書き込む。`reaction`と同じ順番で演算すると結果も一致する
+    fn cell_reaction(&self, cell: &[f32], rates: &mut [f32]);
+}
+
+/// `CellReaction`で扱える物質の数の上限
+pub const MAX_SPECIES: usize = 8;
+
+/// `Integrator::Euler`と同じだけ進めるが、ラプラシアンを`scratch`に書き込み、ほかに配列を作らない
+///
+/// `scratch`の数や大きさが違うときは作り直すので、同じものを毎ステップ渡せば2ステップ目からはメモリを確保しない
+///
+/// # Example
+/// ```
+/// extern crate my_alife;
+/// extern crate rand;
+///
+/// use my_alife::algorithm::brusselator::Brusselator;
+/// use my_alife::algorithm::integrator::Integrator;
+/// use my_alife::algorithm::reaction_diffusion::euler_in_place;
+///
+/// let model = Brusselator::default();
+/// let mut fields = model.initial_fields((32, 32), &mut rand::thread_rng());
+/// let mut expected = fields.clone();
+/// let mut scratch = Vec::new();
+/// for _ in 0..10 {
+///     euler_in_place(&model, &mut fields, &mut scratch, 0.001);
+///     Integrator::Euler.step(&model, &mut expected, 0.001);
+/// }
+/// assert_eq!(fields, expected);
+/// ```
+///
+/// # Panics
+/// 物質の数が`MAX_SPECIES`を超えるとき、場の大きさがそろっていないとき
+pub fn euler_in_place<S: CellReaction>(
+    system: &S,
+    fields: &mut [Matrix<f32>],
+    scratch: &mut Vec<Matrix<f32>>,
+    dt: f32,
+) {
+    let species = fields.len();
+    assert!(species <= MAX_SPECIES, "too many species");
+    if species == 0 {
+        return;
+    }
+    let dim = fields[0].dim();
+    assert!(
+        fields.iter().all(|field| field.dim() == dim),
+        "fields must have the same size"
+    );
+    if scratch.len() != species || scratch.iter().any(|lap| lap.dim() != dim) {
+        *scratch = vec![Array2::zeros(dim); species];
+    }
+    let dx = system.dx();
+    for (field, lap) in fields.iter().zip(scratch.iter_mut()) {
+        laplacian_into(field, dx, lap);
+    }
+    let diffusions = system.diffusions();
+    let (mut cell, mut rates) = ([0.0; MAX_SPECIES], [0.0; MAX_SPECIES]);
+    for index in 0..dim.0 * dim.1 {
+        let (row, col) = (index / dim.1, index % dim.1);
+        for (value, field) in cell.iter_mut().zip(fields.iter()) {
+            *value = field[[row, col]];
+        }
+        system.cell_reaction(&cell[..species], &mut rates[..species]);
+        for (i, field) in fields.iter_mut().enumerate() {
+            field[[row, col]] += dt * (scratch[i][[row, col]] * diffusions[i] + rates[i]);
+        }
+    }
+}
+
+/// 上下左右の4近傍の和(周期境界条件)
+pub(crate) fn neighbor_sum(a: &Matrix<f32>) -> Matrix<f32> {
+    roll(a, 1, false) + roll(a, -1, false) + roll(a, 1, true) + roll(a, -1, true)
+}
+
+pub(crate) fn roll<A, T>(a: &Matrix<A>, shift: T, axis: bool) -> Matrix<A>
+where
+    A: Copy,
+    T: Integer + num_trait_cast::NumCast,
+{
+    let shift: i32 = num_cast(shift).unwrap();
+    let mut rotated = unsafe { Array2::uninitialized(a.dim()) };
+    if axis {
+        rotated.slice_mut(s![.., ..shift]).assign(&a.slice(s![.., -shift..]));
+        rotated.slice_mut(s![.., shift..]).assign(&a.slice(s![.., ..-shift]));
+    } else {
+        rotated.slice_mut(s![..shift, ..]).assign(&a.slice(s![-shift.., ..]));
+        rotated.slice_mut(s![shift.., ..]).assign(&a.slice(s![..-shift, ..]));
+    }
+    rotated
+}
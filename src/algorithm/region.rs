@@ -0,0 +1,207 @@
+use ndarray::Array2;
+use visualizer::Matrix;
+
+/// Matrixの外側のセルの値の決め方
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Boundary<A> {
+    /// 反対側の端につながっている(周期境界条件)
+    Periodic,
+    /// 最も近い端のセルと同じ値
+    Clamp,
+    /// 一定の値
+    Constant(A),
+}
+
+impl<A: Copy> Boundary<A> {
+    /// `(row, col)`のセルの値。Matrixの外側ならば境界条件に従う
+    ///
+    /// # Example
+    /// ```
+    /// extern crate ndarray;
+    /// extern crate my_alife;
+    ///
+    /// use ndarray::arr2;
+    /// use my_alife::algorithm::region::Boundary;
+    ///
+    /// let matrix = arr2(&[[1, 2], [3, 4]]);
+    /// assert_eq!(Boundary::Periodic.get(&matrix, -1, 0), 3);
+    /// assert_eq!(Boundary::Clamp.get(&matrix, 5, -5), 3);
+    /// assert_eq!(Boundary::Constant(0).get(&matrix, 2, 0), 0);
+    /// ```
+    pub fn get(&self, matrix: &Matrix<A>, row: isize, col: isize) -> A {
+        let (rows, cols) = matrix.dim();
+        let inside = row >= 0 && col >= 0 && (row as usize) < rows && (col as usize) < cols;
+        if inside {
+            return matrix[[row as usize, col as usize]];
+        }
+        match *self {
+            Boundary::Periodic => matrix[[wrap(row, rows), wrap(col, cols)]],
+            Boundary::Clamp => matrix[[clamp(row, rows), clamp(col, cols)]],
+            Boundary::Constant(value) => value,
+        }
+    }
+}
+
+fn wrap(i: isize, len: usize) -> usize {
+    i.rem_euclid(len as isize) as usize
+}
+
+fn clamp(i: isize, len: usize) -> usize {
+    i.clamp(0, len as isize - 1) as usize
+}
+
+/// Matrixの中の長方形の領域
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    /// 左上の行
+    pub row: usize,
+    /// 左上の列
+    pub col: usize,
+    /// 行数
+    pub rows: usize,
+    /// 列数
+    pub cols: usize,
+}
+
+/// `(row, col)`を左上とする`rows`×`cols`の領域を切り出す。はみ出した部分は`boundary`に従う
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::arr2;
+/// use my_alife::algorithm::region::{crop, Boundary};
+///
+/// let matrix = arr2(&[[1, 2, 3], [4, 5, 6], [7, 8, 9]]);
+/// assert_eq!(crop(&matrix, 1, 1, 2, 2, Boundary::Periodic), arr2(&[[5, 6], [8, 9]]));
+/// assert_eq!(crop(&matrix, 2, 2, 2, 2, Boundary::Periodic), arr2(&[[9, 7], [3, 1]]));
+/// ```
+pub fn crop<A: Copy>(
+    matrix: &Matrix<A>,
+    row: isize,
+    col: isize,
+    rows: usize,
+    cols: usize,
+    boundary: Boundary<A>,
+) -> Matrix<A> {
+    Array2::from_shape_fn((rows, cols), |(r, c)| {
+        boundary.get(matrix, row + r as isize, col + c as isize)
+    })
+}
+
+/// 四方に`margin`セルずつ広げる。広げた部分は`boundary`に従う
+pub fn pad<A: Copy>(matrix: &Matrix<A>, margin: usize, boundary: Boundary<A>) -> Matrix<A> {
+    let (rows, cols) = matrix.dim();
+    let offset = -(margin as isize);
+    crop(matrix, offset, offset, rows + 2 * margin, cols + 2 * margin, boundary)
+}
+
+/// 縦に`repeat.0`個、横に`repeat.1`個並べる
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::arr2;
+/// use my_alife::algorithm::region::tile;
+///
+/// assert_eq!(tile(&arr2(&[[1, 2]]), (2, 2)), arr2(&[[1, 2, 1, 2], [1, 2, 1, 2]]));
+/// ```
+pub fn tile<A: Copy>(matrix: &Matrix<A>, repeat: (usize, usize)) -> Matrix<A> {
+    let (rows, cols) = matrix.dim();
+    Array2::from_shape_fn((rows * repeat.0, cols * repeat.1), |(r, c)| {
+        matrix[[r % rows, c % cols]]
+    })
+}
+
+/// 周期境界条件のもとで、`(row, col)`のセルが中央に来るようにずらす
+pub fn recenter<A: Copy>(matrix: &Matrix<A>, row: usize, col: usize) -> Matrix<A> {
+    let (rows, cols) = matrix.dim();
+    let top = row as isize - (rows / 2) as isize;
+    let left = col as isize - (cols / 2) as isize;
+    crop(matrix, top, left, rows, cols, Boundary::Periodic)
+}
+
+/// `background`と異なるセルをすべて含む最小の領域。すべて`background`ならば`None`
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::arr2;
+/// use my_alife::algorithm::region::{bounding_box, Region};
+///
+/// let matrix = arr2(&[[0, 0, 0, 0], [0, 1, 0, 0], [0, 0, 1, 1], [0, 0, 0, 0]]);
+/// assert_eq!(bounding_box(&matrix, 0), Some(Region { row: 1, col: 1, rows: 2, cols: 3 }));
+/// ```
+pub fn bounding_box<A: Copy + PartialEq>(matrix: &Matrix<A>, background: A) -> Option<Region> {
+    let mut bounds: Option<(usize, usize, usize, usize)> = None;
+    for ((r, c), &e) in matrix.indexed_iter() {
+        if e == background {
+            continue;
+        }
+        bounds = Some(match bounds {
+            Some((top, left, bottom, right)) => (top.min(r), left.min(c), bottom.max(r), right.max(c)),
+            None => (r, c, r, c),
+        });
+    }
+    bounds.map(|(top, left, bottom, right)| Region {
+        row: top,
+        col: left,
+        rows: bottom - top + 1,
+        cols: right - left + 1,
+    })
+}
+
+/// `background`と異なるセルを囲む最小の領域を切り出す
+pub fn extract<A: Copy + PartialEq>(matrix: &Matrix<A>, background: A) -> Option<Matrix<A>> {
+    bounding_box(matrix, background).map(|region| {
+        crop(
+            matrix,
+            region.row as isize,
+            region.col as isize,
+            region.rows,
+            region.cols,
+            Boundary::Constant(background),
+        )
+    })
+}
+
+/// `source`を`target`の`(row, col)`を左上として書き込む
+///
+/// # Arguments
+/// * `target` - 書き込まれる盤面
+/// * `source` - 書き込む内容
+/// * `row`, `col` - 書き込む位置。負の値やはみ出す位置も指定できる
+/// * `periodic` - trueならばはみ出した部分を反対側に書き込み、falseならば捨てる
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::{arr2, Array2};
+/// use my_alife::algorithm::region::paste;
+///
+/// let mut target = Array2::zeros((3, 3));
+/// paste(&mut target, &arr2(&[[1, 2], [3, 4]]), 2, 2, true);
+/// assert_eq!(target, arr2(&[[4, 0, 3], [0, 0, 0], [2, 0, 1]]));
+/// ```
+pub fn paste<A: Copy>(target: &mut Matrix<A>, source: &Matrix<A>, row: isize, col: isize, periodic: bool) {
+    let (rows, cols) = target.dim();
+    if rows == 0 || cols == 0 {
+        return;
+    }
+    for ((r, c), &e) in source.indexed_iter() {
+        let (r, c) = (row + r as isize, col + c as isize);
+        let inside = r >= 0 && c >= 0 && (r as usize) < rows && (c as usize) < cols;
+        if inside {
+            target[[r as usize, c as usize]] = e;
+        } else if periodic {
+            target[[wrap(r, rows), wrap(c, cols)]] = e;
+        }
+    }
+}
@@ -0,0 +1,505 @@
+use algorithm::neighborhood::Neighborhood;
+use algorithm::stochastic_ca::{compositions, key, StochasticCa};
+use failure;
+use ndarray::Array2;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use visualizer::Matrix;
+
+/// 近傍の数の比べ方
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Eq(u8),
+    Ne(u8),
+    Lt(u8),
+    Le(u8),
+    Gt(u8),
+    Ge(u8),
+    // 両端を含む範囲
+    Range(u8, u8),
+}
+
+impl Comparison {
+    fn matches(self, n: u8) -> bool {
+        match self {
+            Comparison::Eq(m) => n == m,
+            Comparison::Ne(m) => n != m,
+            Comparison::Lt(m) => n < m,
+            Comparison::Le(m) => n <= m,
+            Comparison::Gt(m) => n > m,
+            Comparison::Ge(m) => n >= m,
+            Comparison::Range(low, high) => low <= n && n <= high,
+        }
+    }
+}
+
+// `neighbors(state) <comparison>`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Condition {
+    state: u8,
+    comparison: Comparison,
+}
+
+// `when <states> if <conditions> then <next>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Clause {
+    line: usize,
+    // 空ならばすべての状態に当てはまる
+    when: Vec<u8>,
+    conditions: Vec<Condition>,
+    next: u8,
+}
+
+/// 何にも当てはまらなかったセルの次の状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fallback {
+    /// 今の状態のまま
+    Same,
+    /// 指定した状態
+    State(u8),
+}
+
+/// テキストで書いたセル・オートマトンの遷移規則(周期境界条件)
+///
+/// 1行に1つずつ次のように書く。`#`から行末まではコメント
+/// * `states DEAD ALIVE` - 状態の名前。書いた順に0, 1, ...になる(必須)。数の上限は`StochasticCa::max_states`と同じ
+/// * `neighborhood moore` - 近傍。`moore`か`von_neumann`(省略するとmoore)
+/// * `default same` - どの規則にも当てはまらないときの次の状態。`same`か状態の名前(省略するとsame)
+/// * `when ALIVE if neighbors(ALIVE) in 2..=3 then ALIVE` - 規則。上から順に調べ、最初に当てはまったものを使う
+///
+/// 規則の`when`(今の状態。`,`で複数書ける)と`if`(条件。`and`でつなげる)はどちらかを省略できる。
+/// 条件には`neighbors(S) in a..=b`、`neighbors(S) in a..b`、`neighbors(S) == n`(`!=`, `<`, `<=`, `>`, `>=`も使える)を書ける
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::arr2;
+/// use my_alife::algorithm::rule_dsl::RuleProgram;
+///
+/// let program: RuleProgram = "
+///     states DEAD ALIVE  # Conway's Game of Life
+///     when ALIVE if neighbors(ALIVE) in 2..=3 then ALIVE
+///     if neighbors(ALIVE) == 3 then ALIVE
+///     default DEAD
+/// "
+/// .parse()
+/// .unwrap();
+/// let blinker = arr2(&[[0, 0, 0, 0, 0], [0, 0, 1, 0, 0], [0, 0, 1, 0, 0], [0, 0, 1, 0, 0], [0, 0, 0, 0, 0]]);
+/// assert_eq!(program.step(&blinker).row(2).to_vec(), vec![0, 1, 1, 1, 0]);
+/// assert_eq!(program.compile().step(&blinker), program.step(&blinker));
+/// assert_eq!(program.state("ALIVE"), Some(1));
+/// ```
+///
+/// 誤りがあるとその行の番号がエラーに含まれる
+/// ```
+/// use my_alife::algorithm::rule_dsl::RuleProgram;
+///
+/// let error = "states OFF ON\nif neighbors(ON) == 9 then ON".parse::<RuleProgram>().unwrap_err();
+/// assert_eq!(error.to_string(), "line 2: neighbor count 9 is larger than the neighborhood (8)");
+/// let error = "states OFF ON\nwhen OFF then BLUE".parse::<RuleProgram>().unwrap_err();
+/// assert_eq!(error.to_string(), "line 2: unknown state \"BLUE\"");
+/// let error = "states A B C D E F G H I J K L M".parse::<RuleProgram>().unwrap_err();
+/// assert_eq!(error.to_string(), "line 1: 13 states are too many for the 8 neighbors (at most 12)");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleProgram {
+    states: Vec<String>,
+    neighborhood: Neighborhood,
+    fallback: Fallback,
+    clauses: Vec<Clause>,
+}
+
+impl RuleProgram {
+    /// ファイルから読み込む
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<RuleProgram, failure::Error> {
+        let path = path.as_ref();
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|e| format_err!("{}: {}", path.display(), e))
+    }
+
+    /// 状態の名前
+    pub fn states(&self) -> &[String] {
+        &self.states
+    }
+
+    /// 状態の数
+    pub fn num_states(&self) -> u8 {
+        self.states.len() as u8
+    }
+
+    /// 名前が`name`の状態の番号
+    pub fn state(&self, name: &str) -> Option<u8> {
+        self.states.iter().position(|state| state == name).map(|i| i as u8)
+    }
+
+    /// 近傍の取り方
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+
+    // 最初に当てはまる規則の番号
+    fn matching(&self, state: u8, counts: &[u8]) -> Option<usize> {
+        self.clauses.iter().position(|clause| {
+            (clause.when.is_empty() || clause.when.contains(&state))
+                && clause
+                    .conditions
+                    .iter()
+                    .all(|condition| condition.comparison.matches(counts[condition.state as usize]))
+        })
+    }
+
+    /// 状態が`state`で、近傍にある各状態のセルの数が`counts`のセルの次の状態
+    pub fn next_state(&self, state: u8, counts: &[u8]) -> u8 {
+        match (self.matching(state, counts), self.fallback) {
+            (Some(index), _) => self.clauses[index].next,
+            (None, Fallback::Same) => state,
+            (None, Fallback::State(next)) => next,
+        }
+    }
+
+    /// 規則を1つずつ調べて1ステップ進める
+    pub fn step(&self, cells: &Matrix<u8>) -> Matrix<u8> {
+        let mut next = Array2::zeros(cells.dim());
+        let mut counts = vec![0; self.states.len()];
+        for ((row, col), e) in next.indexed_iter_mut() {
+            let state = cells[[row, col]];
+            if state as usize >= self.states.len() {
+                *e = state;
+                continue;
+            }
+            self.neighborhood.count_states(cells, (row, col), &mut counts);
+            *e = self.next_state(state, &counts);
+        }
+        next
+    }
+
+    /// 前の規則に隠されて一度も使われない規則の行番号
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::algorithm::rule_dsl::RuleProgram;
+    ///
+    /// let program: RuleProgram = "
+    ///     states OFF ON
+    ///     when ON then OFF
+    ///     when ON if neighbors(ON) == 2 then ON
+    ///     when OFF if neighbors(ON) == 2 then ON
+    /// "
+    /// .parse()
+    /// .unwrap();
+    /// assert_eq!(program.unreachable_lines(), vec![4]);
+    /// ```
+    pub fn unreachable_lines(&self) -> Vec<usize> {
+        let mut used = vec![false; self.clauses.len()];
+        for counts in compositions(self.neighborhood.size() as u8, self.states.len()) {
+            for state in 0..self.num_states() {
+                if let Some(index) = self.matching(state, &counts) {
+                    used[index] = true;
+                }
+            }
+        }
+        self.clauses
+            .iter()
+            .zip(used)
+            .filter(|&(_, used)| !used)
+            .map(|(clause, _)| clause.line)
+            .collect()
+    }
+
+    /// すべての(状態, 近傍の状態の数)の組について次の状態を計算しておいた表にする
+    pub fn compile(&self) -> RuleTable {
+        let mut table = HashMap::new();
+        let num_states = self.num_states();
+        for counts in compositions(self.neighborhood.size() as u8, num_states as usize) {
+            for state in 0..num_states {
+                table.insert(
+                    (state, key(&counts, self.neighborhood)),
+                    self.next_state(state, &counts),
+                );
+            }
+        }
+        RuleTable {
+            num_states,
+            neighborhood: self.neighborhood,
+            table,
+        }
+    }
+}
+
+/// `RuleProgram::compile`で作った遷移表
+#[derive(Debug, Clone)]
+pub struct RuleTable {
+    num_states: u8,
+    neighborhood: Neighborhood,
+    // (状態, 近傍の状態の数を`key`で数値にしたもの)から次の状態
+    table: HashMap<(u8, u64), u8>,
+}
+
+impl RuleTable {
+    /// 状態の数
+    pub fn num_states(&self) -> u8 {
+        self.num_states
+    }
+
+    /// 表を引いて1ステップ進める。範囲外の状態はそのまま
+    pub fn step(&self, cells: &Matrix<u8>) -> Matrix<u8> {
+        let mut next = Array2::zeros(cells.dim());
+        let mut counts = vec![0; self.num_states as usize];
+        for ((row, col), e) in next.indexed_iter_mut() {
+            self.neighborhood.count_states(cells, (row, col), &mut counts);
+            let state = cells[[row, col]];
+            *e = *self
+                .table
+                .get(&(state, key(&counts, self.neighborhood)))
+                .unwrap_or(&state);
+        }
+        next
+    }
+}
+
+impl FromStr for RuleProgram {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<RuleProgram, failure::Error> {
+        let mut states: Option<(usize, Vec<String>)> = None;
+        let mut neighborhood = Neighborhood::Moore;
+        let mut fallback = None;
+        let mut clauses = Vec::new();
+        for (i, line) in s.lines().enumerate() {
+            let number = i + 1;
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let at = |message: String| format_err!("line {}: {}", number, message);
+            let tokens = tokenize(line).map_err(&at)?;
+            match tokens[0].as_str() {
+                "states" => {
+                    if states.is_some() {
+                        return Err(at("states are declared twice".to_string()));
+                    }
+                    if tokens.len() < 2 || tokens.len() > 256 {
+                        return Err(at("states needs between 1 and 255 names".to_string()));
+                    }
+                    let names: Vec<String> = tokens[1..].to_vec();
+                    if let Some(name) = names.iter().find(|name| !is_identifier(name)) {
+                        return Err(at(format!("invalid state name \"{}\"", name)));
+                    }
+                    let duplicate = names.iter().enumerate().find(|&(j, name)| names[..j].contains(name));
+                    if let Some((_, name)) = duplicate {
+                        return Err(at(format!("state \"{}\" is declared twice", name)));
+                    }
+                    states = Some((number, names));
+                }
+                "neighborhood" => {
+                    neighborhood = match tokens.get(1).map(String::as_str) {
+                        Some("moore") if tokens.len() == 2 => Neighborhood::Moore,
+                        Some("von_neumann") if tokens.len() == 2 => Neighborhood::VonNeumann,
+                        _ => return Err(at("neighborhood should be moore or von_neumann".to_string())),
+                    };
+                    if !clauses.is_empty() {
+                        return Err(at("neighborhood must come before the rules".to_string()));
+                    }
+                }
+                "default" => {
+                    let (_, names) = states
+                        .as_ref()
+                        .ok_or_else(|| at("states must come first".to_string()))?;
+                    fallback = Some(match tokens.get(1).map(String::as_str) {
+                        Some("same") if tokens.len() == 2 => Fallback::Same,
+                        Some(name) if tokens.len() == 2 => Fallback::State(lookup(names, name).map_err(&at)?),
+                        _ => return Err(at("default should be same or a state name".to_string())),
+                    });
+                }
+                "when" | "if" => {
+                    let (_, names) = states
+                        .as_ref()
+                        .ok_or_else(|| at("states must come first".to_string()))?;
+                    let clause = Parser {
+                        tokens: &tokens,
+                        position: 0,
+                        states: names,
+                        neighbors: neighborhood.size() as u8,
+                    }
+                    .clause(number)
+                    .map_err(&at)?;
+                    clauses.push(clause);
+                }
+                other => return Err(at(format!("unknown statement \"{}\"", other))),
+            }
+        }
+        let (line, states) = states.ok_or_else(|| format_err!("missing states declaration"))?;
+        // すべての組について表を作れるのは`StochasticCa`と同じ数まで
+        let max_states = StochasticCa::max_states(neighborhood);
+        if states.len() > max_states as usize {
+            return Err(format_err!(
+                "line {}: {} states are too many for the {} neighbors (at most {})",
+                line,
+                states.len(),
+                neighborhood.size(),
+                max_states
+            ));
+        }
+        Ok(RuleProgram {
+            states,
+            neighborhood,
+            fallback: fallback.unwrap_or(Fallback::Same),
+            clauses,
+        })
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_alphabetic() || c == '_') && s.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn lookup(states: &[String], name: &str) -> Result<u8, String> {
+    states
+        .iter()
+        .position(|state| state == name)
+        .map(|i| i as u8)
+        .ok_or_else(|| format!("unknown state \"{}\"", name))
+}
+
+// 名前・数・記号に分ける
+fn tokenize(line: &str) -> Result<Vec<String>, String> {
+    const SYMBOLS: [&str; 11] = ["..=", "..", "==", "!=", "<=", ">=", "<", ">", "(", ")", ","];
+    let mut tokens = Vec::new();
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+        } else if c.is_alphanumeric() || c == '_' {
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            tokens.push(rest[..end].to_string());
+            rest = &rest[end..];
+        } else if let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(*symbol)) {
+            tokens.push(symbol.to_string());
+            rest = &rest[symbol.len()..];
+        } else {
+            return Err(format!("unexpected character '{}'", c));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [String],
+    position: usize,
+    states: &'a [String],
+    neighbors: u8,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Result<&'a str, String> {
+        let token = self.tokens.get(self.position).ok_or("unexpected end of line")?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), String> {
+        match self.next()? {
+            token if token == expected => Ok(()),
+            token => Err(format!("expected \"{}\" but found \"{}\"", expected, token)),
+        }
+    }
+
+    fn state(&mut self) -> Result<u8, String> {
+        let name = self.next()?;
+        lookup(self.states, name)
+    }
+
+    fn count(&mut self) -> Result<u8, String> {
+        let token = self.next()?;
+        let n: u8 = token
+            .parse()
+            .map_err(|_| format!("expected a number but found \"{}\"", token))?;
+        if n > self.neighbors {
+            return Err(format!(
+                "neighbor count {} is larger than the neighborhood ({})",
+                n, self.neighbors
+            ));
+        }
+        Ok(n)
+    }
+
+    fn clause(&mut self, line: usize) -> Result<Clause, String> {
+        let mut when = Vec::new();
+        if self.peek() == Some("when") {
+            self.position += 1;
+            when.push(self.state()?);
+            while self.peek() == Some(",") {
+                self.position += 1;
+                when.push(self.state()?);
+            }
+        }
+        let mut conditions = Vec::new();
+        if self.peek() == Some("if") {
+            self.position += 1;
+            conditions.push(self.condition()?);
+            while self.peek() == Some("and") {
+                self.position += 1;
+                conditions.push(self.condition()?);
+            }
+        }
+        self.expect("then")?;
+        let next = self.state()?;
+        if let Some(token) = self.peek() {
+            return Err(format!("unexpected \"{}\" after the next state", token));
+        }
+        Ok(Clause {
+            line,
+            when,
+            conditions,
+            next,
+        })
+    }
+
+    fn condition(&mut self) -> Result<Condition, String> {
+        self.expect("neighbors")?;
+        self.expect("(")?;
+        let state = self.state()?;
+        self.expect(")")?;
+        let comparison = match self.next()? {
+            "in" => {
+                let low = self.count()?;
+                let inclusive = match self.next()? {
+                    "..=" => true,
+                    ".." => false,
+                    token => return Err(format!("expected a range but found \"{}\"", token)),
+                };
+                let high = self.count()?;
+                let high = if inclusive {
+                    high
+                } else if high > low {
+                    high - 1
+                } else {
+                    return Err(format!("range {}..{} is empty", low, high));
+                };
+                if high < low {
+                    return Err(format!("range {}..={} is empty", low, high));
+                }
+                Comparison::Range(low, high)
+            }
+            "==" => Comparison::Eq(self.count()?),
+            "!=" => Comparison::Ne(self.count()?),
+            "<" => Comparison::Lt(self.count()?),
+            "<=" => Comparison::Le(self.count()?),
+            ">" => Comparison::Gt(self.count()?),
+            ">=" => Comparison::Ge(self.count()?),
+            token => return Err(format!("expected a comparison but found \"{}\"", token)),
+        };
+        Ok(Condition { state, comparison })
+    }
+}
@@ -0,0 +1,157 @@
+use algorithm::neighborhood::Neighborhood;
+use ndarray::Array2;
+use rand::Rng;
+use visualizer::Matrix;
+
+/// 空き地
+pub const VACANT: u8 = 0;
+
+/// Schellingの分居モデル(周期境界条件、Moore近傍)
+///
+/// セルの値は`VACANT`か所属するグループ(1から`groups`)。
+/// 近傍の住人のうち同じグループの割合が`tolerance`より小さい住人は不満で、ランダムな空き地に引っ越す
+///
+/// # Example
+/// ```
+/// extern crate my_alife;
+/// extern crate rand;
+///
+/// use my_alife::algorithm::schelling::Schelling;
+///
+/// let mut rng = rand::thread_rng();
+/// let mut model = Schelling::random((32, 32), 2, 0.1, 0.5, &mut rng);
+/// let before = model.segregation_index();
+/// for _ in 0..20 {
+///     model.step(&mut rng);
+/// }
+/// // 住人は少し不寛容なだけで、ランダムな配置よりも分かれて住むようになる
+/// assert!(model.segregation_index() > before);
+/// ```
+pub struct Schelling {
+    cells: Matrix<u8>,
+    groups: u8,
+    tolerance: f32,
+}
+
+impl Schelling {
+    /// 初期状態`cells`から始める
+    ///
+    /// # Arguments
+    /// * `cells` - 各セルの住人のグループ。`VACANT`は空き地
+    /// * `groups` - グループの数
+    /// * `tolerance` - 満足するために必要な、同じグループの近傍の割合
+    pub fn new(cells: Matrix<u8>, groups: u8, tolerance: f32) -> Schelling {
+        Schelling {
+            cells,
+            groups,
+            tolerance,
+        }
+    }
+
+    /// 空き地の割合が`vacancy`で、住人のグループがランダムな初期状態から始める
+    pub fn random<R: Rng>(dim: (usize, usize), groups: u8, vacancy: f32, tolerance: f32, rng: &mut R) -> Schelling {
+        let cells = Array2::from_shape_fn(dim, |_| {
+            if rng.gen::<f32>() < vacancy {
+                VACANT
+            } else {
+                rng.gen_range(1, groups + 1)
+            }
+        });
+        Schelling::new(cells, groups, tolerance)
+    }
+
+    /// 各セルの住人のグループ
+    pub fn cells(&self) -> &Matrix<u8> {
+        &self.cells
+    }
+
+    /// グループの数
+    pub fn groups(&self) -> u8 {
+        self.groups
+    }
+
+    /// 満足するために必要な、同じグループの近傍の割合を変更する
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.tolerance = tolerance;
+    }
+
+    // セル(row, col)の住人の近傍のうち同じグループの割合。近傍に住人がいなければNone
+    fn similarity(&self, cell: (usize, usize)) -> Option<f32> {
+        let group = self.cells[cell];
+        let (mut same, mut occupied) = (0, 0);
+        for neighbor in Neighborhood::Moore.around(cell, self.cells.dim()) {
+            match self.cells[neighbor] {
+                VACANT => {}
+                other => {
+                    occupied += 1;
+                    if other == group {
+                        same += 1;
+                    }
+                }
+            }
+        }
+        if occupied == 0 {
+            None
+        } else {
+            Some(same as f32 / occupied as f32)
+        }
+    }
+
+    fn is_unhappy(&self, cell: (usize, usize)) -> bool {
+        self.cells[cell] != VACANT && self.similarity(cell).is_some_and(|s| s < self.tolerance)
+    }
+
+    /// 不満な住人の数
+    pub fn unhappy_count(&self) -> usize {
+        self.cells
+            .indexed_iter()
+            .filter(|&(cell, _)| self.is_unhappy(cell))
+            .count()
+    }
+
+    /// 分居の度合い。住人の近傍のうち同じグループの割合の平均
+    ///
+    /// ランダムな配置ではおよそ1/`groups`、完全に分かれて住むと1.0になる
+    pub fn segregation_index(&self) -> f32 {
+        let similarities: Vec<f32> = self
+            .cells
+            .indexed_iter()
+            .filter(|&(_, &group)| group != VACANT)
+            .filter_map(|(cell, _)| self.similarity(cell))
+            .collect();
+        if similarities.is_empty() {
+            return 0.0;
+        }
+        similarities.iter().sum::<f32>() / similarities.len() as f32
+    }
+
+    /// 1ステップ進める。そのときに不満な住人が、ランダムな順にランダムな空き地へ引っ越す
+    ///
+    /// 引っ越した住人の数を返す
+    pub fn step<R: Rng>(&mut self, rng: &mut R) -> usize {
+        let mut unhappy: Vec<(usize, usize)> = self
+            .cells
+            .indexed_iter()
+            .filter(|&(cell, _)| self.is_unhappy(cell))
+            .map(|(cell, _)| cell)
+            .collect();
+        let mut vacant: Vec<(usize, usize)> = self
+            .cells
+            .indexed_iter()
+            .filter(|&(_, &group)| group == VACANT)
+            .map(|(cell, _)| cell)
+            .collect();
+        if vacant.is_empty() {
+            return 0;
+        }
+        rng.shuffle(&mut unhappy);
+        for &from in &unhappy {
+            let i = rng.gen_range(0, vacant.len());
+            let to = vacant[i];
+            self.cells[to] = self.cells[from];
+            self.cells[from] = VACANT;
+            vacant[i] = from;
+        }
+        unhappy.len()
+    }
+}
@@ -0,0 +1,205 @@
+use algorithm::golly::GollyRule;
+use algorithm::neighborhood::Neighborhood;
+use algorithm::patterns::parse_rle;
+use failure;
+use ndarray::Array2;
+use std::path::Path;
+use visualizer::palette::Palette;
+use visualizer::Matrix;
+
+/// Langtonのループの規則(Golly形式)。`res/rules/LangtonsLoops.rule`と同じ
+pub const LANGTONS_LOOPS_RULE: &str = include_str!("../../res/rules/LangtonsLoops.rule");
+
+/// Langtonのループの初期配置(RLE形式)。`res/patterns/langtons_loop.rle`と同じ
+pub const LANGTONS_LOOP_PATTERN: &str = include_str!("../../res/patterns/langtons_loop.rle");
+
+// ループの内側の穴とみなす、0のセルのつながった領域の最小の大きさ(コアの中の信号の隙間を数えないようにする)
+const MIN_HOLE_SIZE: usize = 4;
+
+/// Langtonの自己複製ループの規則(8状態, von Neumann近傍, 219個の遷移)
+///
+/// 状態0は空、1はコア、2は鞘、3〜7は遺伝子の信号など
+///
+/// Evoloopは`evoloop`でGollyの規則表から読み込む
+pub fn langtons_loops() -> GollyRule {
+    LANGTONS_LOOPS_RULE
+        .parse()
+        .expect("the bundled rule table must be valid")
+}
+
+/// Sayamaの進化するループ(Evoloop)の規則をGollyの`Evoloop.rule`から読み込む。
+/// 9状態・von Neumann近傍でない規則はエラーにする
+///
+/// Evoloopの規則表と初期配置は出典の表が手元にないため同梱していない(未完成)。
+/// Gollyに付属する`Rules/Evoloop.rule`と`Patterns/Loops/Evoloop-finite.rle`を使う
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::self_replication::evoloop;
+///
+/// // Langtonのループの規則は8状態なので受け付けない
+/// assert!(evoloop("res/rules/LangtonsLoops.rule").is_err());
+/// ```
+pub fn evoloop<P: AsRef<Path>>(path: P) -> Result<GollyRule, failure::Error> {
+    let rule = GollyRule::load(path)?;
+    if rule.num_states() != 9 || rule.neighborhood() != Neighborhood::VonNeumann {
+        return Err(format_err!(
+            "{} is not an Evoloop rule: expected 9 states in the von Neumann neighborhood",
+            rule.name()
+        ));
+    }
+    Ok(rule)
+}
+
+/// Langtonのループの初期配置(10行 × 15列)。151ステップで1つ目の娘ループを作る
+pub fn langtons_loop() -> Matrix<u8> {
+    parse_rle(LANGTONS_LOOP_PATTERN).expect("the bundled pattern must be valid")
+}
+
+/// ループを描くためのGollyと同じ色。読み込んだEvoloopも描けるように、状態8(解体)まで含めて9色ある
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::self_replication::loop_palette;
+///
+/// let palette = loop_palette();
+/// assert_eq!(palette.len(), 9);
+/// assert_eq!(palette.color(2), [255, 0, 0]);
+/// ```
+pub fn loop_palette() -> Palette {
+    Palette::new()
+        .with("empty", [0, 0, 0])
+        .with("core", [0, 0, 255])
+        .with("sheath", [255, 0, 0])
+        .with("3", [0, 255, 0])
+        .with("4", [255, 255, 0])
+        .with("5", [255, 0, 255])
+        .with("6", [255, 255, 255])
+        .with("7", [0, 255, 255])
+        .with("dissolve", [255, 128, 0])
+}
+
+/// 自己複製ループが増えていく盤面(周期境界条件)
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::self_replication::{langtons_loop, langtons_loops, LoopColony};
+///
+/// let mut colony = LoopColony::new(langtons_loops(), (40, 40));
+/// colony.place(&langtons_loop(), (10, 5));
+/// assert_eq!(colony.count_loops(), 1);
+/// colony.run(151);
+/// // 腕の先で娘ループが閉じる
+/// assert_eq!(colony.count_loops(), 2);
+/// assert_eq!(colony.time(), 151);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LoopColony {
+    rule: GollyRule,
+    cells: Matrix<u8>,
+    time: usize,
+}
+
+impl LoopColony {
+    /// 大きさ`dim`の空の盤面
+    pub fn new(rule: GollyRule, dim: (usize, usize)) -> LoopColony {
+        LoopColony {
+            rule,
+            cells: Array2::zeros(dim),
+            time: 0,
+        }
+    }
+
+    /// `pattern`の左上を`(row, col)`に合わせて置く。0のセルも上書きし、はみ出した部分は反対側に回り込む
+    pub fn place(&mut self, pattern: &Matrix<u8>, (row, col): (usize, usize)) {
+        let (rows, cols) = self.cells.dim();
+        for ((r, c), &state) in pattern.indexed_iter() {
+            self.cells[[(row + r) % rows, (col + c) % cols]] = state;
+        }
+    }
+
+    /// 盤面の中央に`pattern`を置く
+    pub fn place_center(&mut self, pattern: &Matrix<u8>) {
+        let (rows, cols) = self.cells.dim();
+        let (height, width) = pattern.dim();
+        self.place(
+            pattern,
+            (rows.saturating_sub(height) / 2, cols.saturating_sub(width) / 2),
+        );
+    }
+
+    /// 規則
+    pub fn rule(&self) -> &GollyRule {
+        &self.rule
+    }
+
+    /// 各セルの状態
+    pub fn cells(&self) -> &Matrix<u8> {
+        &self.cells
+    }
+
+    /// 経過したステップ数
+    pub fn time(&self) -> usize {
+        self.time
+    }
+
+    /// 1ステップ進める
+    pub fn step(&mut self) {
+        self.cells = self.rule.step(&self.cells);
+        self.time += 1;
+    }
+
+    /// `steps`ステップ進める
+    pub fn run(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+
+    /// 状態ごとのセルの数
+    pub fn census(&self) -> Vec<usize> {
+        let mut counts = vec![0; self.rule.num_states() as usize];
+        for &state in self.cells.iter() {
+            if let Some(count) = counts.get_mut(state as usize) {
+                *count += 1;
+            }
+        }
+        counts
+    }
+
+    /// 閉じたループの数。一番大きな外側を除いた、空のセルのつながった領域(ループの内側の穴)を数える。
+    /// 複製をやめた死んだループも数える
+    pub fn count_loops(&self) -> usize {
+        let (rows, cols) = self.cells.dim();
+        let mut visited = Array2::from_elem((rows, cols), false);
+        let mut sizes = Vec::new();
+        let mut stack = Vec::new();
+        for start in 0..rows * cols {
+            let start = (start / cols, start % cols);
+            if visited[start] || self.cells[start] != 0 {
+                continue;
+            }
+            visited[start] = true;
+            stack.push(start);
+            let mut size = 0;
+            while let Some((row, col)) = stack.pop() {
+                size += 1;
+                let neighbors = [
+                    ((row + rows - 1) % rows, col),
+                    ((row + 1) % rows, col),
+                    (row, (col + cols - 1) % cols),
+                    (row, (col + 1) % cols),
+                ];
+                for &next in &neighbors {
+                    if !visited[next] && self.cells[next] == 0 {
+                        visited[next] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+            sizes.push(size);
+        }
+        let outside = sizes.iter().cloned().max().unwrap_or(0);
+        sizes.iter().filter(|&&size| size >= MIN_HOLE_SIZE).count() - if outside >= MIN_HOLE_SIZE { 1 } else { 0 }
+    }
+}
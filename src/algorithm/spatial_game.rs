@@ -0,0 +1,215 @@
+use algorithm::graph::{Payoff, UpdateRule, COOPERATE, DEFECT};
+use algorithm::neighborhood::Neighborhood;
+use ndarray::Array2;
+use rand::Rng;
+use visualizer::palette::Palette;
+use visualizer::Matrix;
+
+/// `SpatialGame::transitions`で、協力し続けている
+pub const STAY_COOPERATE: u8 = 0;
+/// `SpatialGame::transitions`で、裏切り続けている
+pub const STAY_DEFECT: u8 = 1;
+/// `SpatialGame::transitions`で、裏切りから協力に変わった
+pub const BECOME_COOPERATE: u8 = 2;
+/// `SpatialGame::transitions`で、協力から裏切りに変わった
+pub const BECOME_DEFECT: u8 = 3;
+
+/// Nowak & May (1992)の空間的な囚人のジレンマ(周期境界条件)
+///
+/// 各ステップで全てのセルが近傍(と、`self_interaction`なら自分自身)と対戦して合計の利得を求め、
+/// `UpdateRule`に従って一斉に戦略を更新する。
+/// 全て協力者の中に裏切り者を1人置き、1.8 < T < 2.0で動かすと、対称な万華鏡のような模様ができる
+///
+/// # Example
+/// ```
+/// extern crate rand;
+/// extern crate my_alife;
+///
+/// use rand::{SeedableRng, XorShiftRng};
+/// use my_alife::algorithm::graph::{Payoff, UpdateRule, DEFECT};
+/// use my_alife::algorithm::neighborhood::Neighborhood;
+/// use my_alife::algorithm::spatial_game::{SpatialGame, BECOME_DEFECT, STAY_DEFECT};
+///
+/// let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+/// let payoff = Payoff::weak_prisoners_dilemma(1.9);
+/// let mut game = SpatialGame::single_defector((9, 9), payoff, UpdateRule::Imitation, Neighborhood::Moore);
+/// game.step(&mut rng);
+/// // 裏切り者の利得8 × 1.9 = 15.2は周りの協力者の利得8より大きいので、3×3に広がる
+/// assert_eq!(game.strategies().iter().filter(|&&s| s == DEFECT).count(), 9);
+/// assert_eq!(game.transitions()[[4, 4]], STAY_DEFECT);
+/// assert_eq!(game.transitions()[[3, 3]], BECOME_DEFECT);
+/// assert_eq!(game.time(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpatialGame {
+    strategies: Matrix<u8>,
+    previous: Matrix<u8>,
+    payoffs: Matrix<f32>,
+    payoff: Payoff,
+    rule: UpdateRule,
+    neighborhood: Neighborhood,
+    self_interaction: bool,
+    time: usize,
+}
+
+impl SpatialGame {
+    /// 各セルの戦略(`COOPERATE`か`DEFECT`)`strategies`から始める
+    pub fn new(strategies: Matrix<u8>, payoff: Payoff, rule: UpdateRule, neighborhood: Neighborhood) -> SpatialGame {
+        let payoffs = Array2::zeros(strategies.dim());
+        SpatialGame {
+            previous: strategies.clone(),
+            strategies,
+            payoffs,
+            payoff,
+            rule,
+            neighborhood,
+            self_interaction: true,
+            time: 0,
+        }
+    }
+
+    /// 確率`cooperators`で協力者、それ以外を裏切り者にして始める
+    pub fn random<R: Rng>(
+        dim: (usize, usize),
+        cooperators: f32,
+        payoff: Payoff,
+        rule: UpdateRule,
+        neighborhood: Neighborhood,
+        rng: &mut R,
+    ) -> SpatialGame {
+        let strategies = Array2::from_shape_fn(dim, |_| {
+            if rng.gen::<f32>() < cooperators {
+                COOPERATE
+            } else {
+                DEFECT
+            }
+        });
+        SpatialGame::new(strategies, payoff, rule, neighborhood)
+    }
+
+    /// 全て協力者で、中央の1セルだけ裏切り者にして始める
+    pub fn single_defector(
+        dim: (usize, usize),
+        payoff: Payoff,
+        rule: UpdateRule,
+        neighborhood: Neighborhood,
+    ) -> SpatialGame {
+        let mut strategies = Array2::from_elem(dim, COOPERATE);
+        if dim.0 > 0 && dim.1 > 0 {
+            strategies[[dim.0 / 2, dim.1 / 2]] = DEFECT;
+        }
+        SpatialGame::new(strategies, payoff, rule, neighborhood)
+    }
+
+    /// 自分自身とも対戦するかどうかを設定する。初期値はNowak & Mayと同じ`true`
+    pub fn set_self_interaction(&mut self, self_interaction: bool) {
+        self.self_interaction = self_interaction;
+    }
+
+    /// 利得表を設定する
+    pub fn set_payoff(&mut self, payoff: Payoff) {
+        self.payoff = payoff;
+    }
+
+    /// 各セルの戦略
+    pub fn strategies(&self) -> &Matrix<u8> {
+        &self.strategies
+    }
+
+    /// 直前の`step`で各セルが得た合計の利得
+    pub fn payoffs(&self) -> &Matrix<f32> {
+        &self.payoffs
+    }
+
+    /// 経過したステップ数
+    pub fn time(&self) -> usize {
+        self.time
+    }
+
+    /// 協力者の割合
+    pub fn cooperation(&self) -> f32 {
+        if self.strategies.is_empty() {
+            return 0.0;
+        }
+        self.strategies.iter().filter(|&&s| s == COOPERATE).count() as f32 / self.strategies.len() as f32
+    }
+
+    /// 直前の`step`での戦略の変わり方(`STAY_COOPERATE`, `STAY_DEFECT`, `BECOME_COOPERATE`, `BECOME_DEFECT`)
+    ///
+    /// `transition_palette`で描くと、Nowak & Mayの図と同じ色になる
+    pub fn transitions(&self) -> Matrix<u8> {
+        let mut transitions = Array2::zeros(self.strategies.dim());
+        ::ndarray::Zip::from(&mut transitions)
+            .and(&self.previous)
+            .and(&self.strategies)
+            .apply(|t, &before, &after| {
+                *t = match (before, after) {
+                    (COOPERATE, COOPERATE) => STAY_COOPERATE,
+                    (COOPERATE, _) => BECOME_DEFECT,
+                    (_, COOPERATE) => BECOME_COOPERATE,
+                    _ => STAY_DEFECT,
+                }
+            });
+        transitions
+    }
+
+    /// 対戦して、全てのセルの戦略を一斉に更新する
+    pub fn step<R: Rng>(&mut self, rng: &mut R) {
+        let (rows, cols) = self.strategies.dim();
+        let offsets = self.neighborhood.offsets();
+        let neighbor = |(row, col): (usize, usize), &(dr, dc): &(isize, isize)| {
+            (
+                (row as isize + dr).rem_euclid(rows as isize) as usize,
+                (col as isize + dc).rem_euclid(cols as isize) as usize,
+            )
+        };
+        for row in 0..rows {
+            for col in 0..cols {
+                let me = self.strategies[[row, col]];
+                let mut total: f32 = offsets
+                    .iter()
+                    .map(|offset| self.payoff.get(me, self.strategies[neighbor((row, col), offset)]))
+                    .sum();
+                if self.self_interaction {
+                    total += self.payoff.get(me, me);
+                }
+                self.payoffs[[row, col]] = total;
+            }
+        }
+        let mut next = self.strategies.clone();
+        let mut candidates = Vec::with_capacity(offsets.len());
+        for row in 0..rows {
+            for col in 0..cols {
+                candidates.clear();
+                candidates.extend(offsets.iter().map(|offset| {
+                    let cell = neighbor((row, col), offset);
+                    (self.strategies[cell], self.payoffs[cell])
+                }));
+                next[[row, col]] =
+                    self.rule
+                        .choose(self.strategies[[row, col]], self.payoffs[[row, col]], &candidates, rng);
+            }
+        }
+        self.previous = ::std::mem::replace(&mut self.strategies, next);
+        self.time += 1;
+    }
+}
+
+/// `SpatialGame::transitions`を描くためのNowak & Mayと同じ色。協力し続けると青、裏切り続けると赤、
+/// 協力に変わると緑、裏切りに変わると黄色になる
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::spatial_game::{transition_palette, BECOME_COOPERATE};
+///
+/// let palette = transition_palette();
+/// assert_eq!(palette.len(), 4);
+/// assert_eq!(palette.color(BECOME_COOPERATE), [0, 200, 0]);
+/// ```
+pub fn transition_palette() -> Palette {
+    Palette::new()
+        .with("C -> C", [0, 0, 220])
+        .with("D -> D", [220, 0, 0])
+        .with("D -> C", [0, 200, 0])
+        .with("C -> D", [240, 220, 0])
+}
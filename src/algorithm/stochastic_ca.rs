@@ -0,0 +1,224 @@
+use algorithm::neighborhood::Neighborhood;
+use ndarray::Array2;
+use rand::Rng;
+use std::collections::HashMap;
+use visualizer::Matrix;
+
+/// 遷移確率の表で定義する確率的なセル・オートマトン(周期境界条件)
+///
+/// 遷移確率はセルの状態と、近傍にある各状態のセルの数だけで決まる(totalistic)。
+/// 生成するときにすべての組み合わせについて確率を計算しておくので、ステップごとの計算は表を引くだけになる
+///
+/// # Example
+/// ```
+/// extern crate my_alife;
+/// extern crate rand;
+///
+/// use my_alife::algorithm::game_of_life::random_cells;
+/// use my_alife::algorithm::neighborhood::Neighborhood;
+/// use my_alife::algorithm::stochastic_ca::StochasticCa;
+///
+/// // 近傍に1が1つでもあれば確率0.5で1になり、1は確率0.1で0に戻る
+/// let ca = StochasticCa::new(2, Neighborhood::VonNeumann, |state, counts| match state {
+///     0 if counts[1] > 0 => vec![0.5, 0.5],
+///     0 => vec![1.0, 0.0],
+///     _ => vec![0.1, 0.9],
+/// });
+/// let cells = ca.step(&random_cells((32, 32), 0.5), &mut rand::thread_rng());
+/// assert!(cells.iter().all(|&e| e < 2));
+/// ```
+pub struct StochasticCa {
+    num_states: u8,
+    neighborhood: Neighborhood,
+    // (状態, 近傍の状態の数を`key`で数値にしたもの)から次の状態の累積確率
+    table: HashMap<(u8, u64), Vec<f32>>,
+}
+
+impl StochasticCa {
+    /// 遷移確率`rule`からStochasticCaを生成する
+    ///
+    /// # Arguments
+    /// * `num_states` - 状態の数。状態は0から`num_states - 1`
+    /// * `neighborhood` - 近傍の取り方
+    /// * `rule` - (セルの状態, 近傍にある状態ごとのセルの数)から、次の各状態になる確率を返す。合計が1でなければ正規化する
+    ///
+    /// # Panics
+    /// `num_states`が0か`StochasticCa::max_states(neighborhood)`を超えるとき、
+    /// または`rule`が長さ`num_states`でない確率や、合計が0の確率を返したとき
+    pub fn new<F>(num_states: u8, neighborhood: Neighborhood, rule: F) -> StochasticCa
+    where
+        F: Fn(u8, &[u8]) -> Vec<f32>,
+    {
+        assert!(
+            num_states > 0 && num_states <= StochasticCa::max_states(neighborhood),
+            "{} states do not fit in the transition table (at most {} with {} neighbors)",
+            num_states,
+            StochasticCa::max_states(neighborhood),
+            neighborhood.size()
+        );
+        let mut table = HashMap::new();
+        for counts in compositions(neighborhood.size() as u8, num_states as usize) {
+            for state in 0..num_states {
+                let probabilities = rule(state, &counts);
+                assert_eq!(
+                    probabilities.len(),
+                    num_states as usize,
+                    "rule returned {} probabilities for {} states (state {}, counts {:?})",
+                    probabilities.len(),
+                    num_states,
+                    state,
+                    counts
+                );
+                let total: f32 = probabilities.iter().sum();
+                assert!(
+                    total > 0.0 && total.is_finite(),
+                    "rule returned probabilities summing to {} (state {}, counts {:?})",
+                    total,
+                    state,
+                    counts
+                );
+                let mut cumulative = 0.0;
+                let cumulative_probabilities = probabilities
+                    .iter()
+                    .map(|p| {
+                        cumulative += p / total;
+                        cumulative
+                    })
+                    .collect();
+                table.insert((state, key(&counts, neighborhood)), cumulative_probabilities);
+            }
+        }
+        StochasticCa {
+            num_states,
+            neighborhood,
+            table,
+        }
+    }
+
+    /// 状態の数
+    pub fn num_states(&self) -> u8 {
+        self.num_states
+    }
+
+    /// `neighborhood`で使える状態の数の上限。(状態, 近傍の状態の数)の組の数が多すぎると表を作れない
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::algorithm::neighborhood::Neighborhood;
+    /// use my_alife::algorithm::stochastic_ca::StochasticCa;
+    ///
+    /// assert_eq!(StochasticCa::max_states(Neighborhood::Moore), 12);
+    /// assert_eq!(StochasticCa::max_states(Neighborhood::VonNeumann), 27);
+    /// ```
+    pub fn max_states(neighborhood: Neighborhood) -> u8 {
+        (1..=255)
+            .take_while(|&num_states| table_size(neighborhood, num_states).is_some())
+            .last()
+            .unwrap_or(0)
+    }
+
+    /// 1ステップ進める。すべてのセルを同時に更新する
+    pub fn step<R: Rng>(&self, cells: &Matrix<u8>, rng: &mut R) -> Matrix<u8> {
+        let mut next = Array2::zeros(cells.dim());
+        let mut counts = vec![0; self.num_states as usize];
+        for ((row, col), e) in next.indexed_iter_mut() {
+            self.neighborhood.count_states(cells, (row, col), &mut counts);
+            let state = cells[[row, col]];
+            *e = match self.table.get(&(state, key(&counts, self.neighborhood))) {
+                Some(cumulative) => {
+                    let x: f32 = rng.gen();
+                    cumulative.iter().position(|&c| x < c).unwrap_or(cumulative.len() - 1) as u8
+                }
+                // 範囲外の状態はそのまま
+                None => state,
+            };
+        }
+        next
+    }
+
+    /// 接触過程(contact process)。1が感染、0が未感染
+    ///
+    /// # Arguments
+    /// * `infection` - 感染している近傍1つあたりの感染確率
+    /// * `recovery` - 回復する確率
+    pub fn contact_process(neighborhood: Neighborhood, infection: f32, recovery: f32) -> StochasticCa {
+        StochasticCa::new(2, neighborhood, move |state, counts| {
+            if state == 1 {
+                vec![recovery, 1.0 - recovery]
+            } else {
+                let p = 1.0 - (1.0 - infection).powi(counts[1] as i32);
+                vec![1.0 - p, p]
+            }
+        })
+    }
+
+    /// 確率的なGreenberg-Hastingsモデル。0が静止、1が興奮、2以上が不応期
+    ///
+    /// # Arguments
+    /// * `num_states` - 状態の数(3以上)
+    /// * `excitation` - 興奮している近傍1つあたりの、静止状態から興奮する確率
+    ///
+    /// # Panics
+    /// `num_states`が`StochasticCa::max_states(neighborhood)`を超えるとき
+    pub fn greenberg_hastings(neighborhood: Neighborhood, num_states: u8, excitation: f32) -> StochasticCa {
+        StochasticCa::new(num_states, neighborhood, move |state, counts| {
+            let mut probabilities = vec![0.0; num_states as usize];
+            if state == 0 {
+                let p = 1.0 - (1.0 - excitation).powi(counts[1] as i32);
+                probabilities[0] = 1.0 - p;
+                probabilities[1] = p;
+            } else {
+                probabilities[((state + 1) % num_states) as usize] = 1.0;
+            }
+            probabilities
+        })
+    }
+
+    /// 投票者モデル(voter model)。近傍からランダムに選んだセルの状態になる
+    ///
+    /// # Panics
+    /// `num_states`が`StochasticCa::max_states(neighborhood)`を超えるとき
+    pub fn voter(neighborhood: Neighborhood, num_states: u8) -> StochasticCa {
+        StochasticCa::new(num_states, neighborhood, |_, counts| {
+            counts.iter().map(|&count| count as f32).collect()
+        })
+    }
+}
+
+// 表の大きさ((状態, 近傍の状態の数)の組の数)の上限
+const MAX_TABLE_SIZE: u64 = 1 << 20;
+
+// `num_states`個の状態のときの表の大きさ。`key`がu64に収まらないときや`MAX_TABLE_SIZE`を超えるときはNone
+pub(crate) fn table_size(neighborhood: Neighborhood, num_states: u8) -> Option<u64> {
+    let size = neighborhood.size() as u64;
+    (size + 1).checked_pow(u32::from(num_states))?;
+    // 近傍の状態の数の組み合わせは、`size`個のセルを`num_states`個の状態に分ける方法の数
+    let mut combinations: u64 = 1;
+    for i in 1..u64::from(num_states) {
+        combinations = combinations.checked_mul(size + i)? / i;
+    }
+    combinations
+        .checked_mul(u64::from(num_states))
+        .filter(|&entries| entries <= MAX_TABLE_SIZE)
+}
+
+// 近傍の状態の数を、近傍のセルの数+1を基数とする数値にする
+pub(crate) fn key(counts: &[u8], neighborhood: Neighborhood) -> u64 {
+    let base = neighborhood.size() as u64 + 1;
+    counts.iter().rev().fold(0, |key, &count| key * base + count as u64)
+}
+
+// 合計が`total`になる`parts`個の0以上の整数の組をすべて列挙する
+pub(crate) fn compositions(total: u8, parts: usize) -> Vec<Vec<u8>> {
+    if parts == 1 {
+        return vec![vec![total]];
+    }
+    (0..=total)
+        .flat_map(|first| {
+            compositions(total - first, parts - 1).into_iter().map(move |mut rest| {
+                rest.insert(0, first);
+                rest
+            })
+        })
+        .collect()
+}
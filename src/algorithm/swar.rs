@@ -0,0 +1,86 @@
+use algorithm::game_of_life::ALIVE;
+use ndarray::Array2;
+use visualizer::Matrix;
+
+// 1語に詰めるセルの数
+const LANES: usize = 8;
+
+/// 周期境界条件で、各セルのMoore近傍(8近傍)にある状態`ALIVE`のセルの数
+///
+/// 1セル1byteの盤面を8byteずつu64として読み、8セル分の足し算を1回の加算で行う(SWAR)。
+/// 近傍の数は8以下なので、byteの間で桁あふれは起きない。
+/// まず横に3セルずつ足し、それを縦に3行足してから自分を引く
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use my_alife::algorithm::game_of_life::{alive_neighbors, random_cells};
+/// use my_alife::algorithm::swar::moore_counts;
+///
+/// let cells = random_cells((20, 37), 0.4);
+/// let counts = moore_counts(&cells);
+/// for ((row, col), &count) in counts.indexed_iter() {
+///     assert_eq!(count, alive_neighbors(&cells, row, col));
+/// }
+/// ```
+pub fn moore_counts(cells: &Matrix<u8>) -> Matrix<u8> {
+    let (rows, cols) = cells.dim();
+    if rows == 0 || cols == 0 {
+        return Array2::zeros((rows, cols));
+    }
+    let alive: Vec<u8> = match cells.as_slice() {
+        Some(cells) => cells.iter().map(|&state| (state == ALIVE) as u8).collect(),
+        None => cells.iter().map(|&state| (state == ALIVE) as u8).collect(),
+    };
+
+    // horizontal[r * cols + c] = alive[r][c - 1] + alive[r][c] + alive[r][c + 1]
+    let mut horizontal = vec![0; rows * cols];
+    let mut padded = vec![0; cols + 2];
+    for row in 0..rows {
+        let line = &alive[row * cols..(row + 1) * cols];
+        padded[0] = line[cols - 1];
+        padded[1..=cols].copy_from_slice(line);
+        padded[cols + 1] = line[0];
+        let out = &mut horizontal[row * cols..(row + 1) * cols];
+        let words = cols / LANES;
+        for word in 0..words {
+            let at = word * LANES;
+            let sum = load(&padded, at) + load(&padded, at + 1) + load(&padded, at + 2);
+            store(out, at, sum);
+        }
+        for col in words * LANES..cols {
+            out[col] = padded[col] + padded[col + 1] + padded[col + 2];
+        }
+    }
+
+    let mut counts = vec![0; rows * cols];
+    for row in 0..rows {
+        let (up, down) = ((row + rows - 1) % rows * cols, (row + 1) % rows * cols);
+        let middle = row * cols;
+        let out = &mut counts[middle..middle + cols];
+        let words = cols / LANES;
+        for word in 0..words {
+            let at = word * LANES;
+            // 各byteは自分を含めて9以下で、自分の分を引いても負にならない
+            let sum = load(&horizontal, up + at) + load(&horizontal, middle + at) + load(&horizontal, down + at)
+                - load(&alive, middle + at);
+            store(out, at, sum);
+        }
+        for col in words * LANES..cols {
+            out[col] = horizontal[up + col] + horizontal[middle + col] + horizontal[down + col] - alive[middle + col];
+        }
+    }
+    Array2::from_shape_vec((rows, cols), counts).unwrap()
+}
+
+fn load(bytes: &[u8], at: usize) -> u64 {
+    let mut word = [0; LANES];
+    word.copy_from_slice(&bytes[at..at + LANES]);
+    u64::from_le_bytes(word)
+}
+
+fn store(bytes: &mut [u8], at: usize, word: u64) {
+    bytes[at..at + LANES].copy_from_slice(&word.to_le_bytes());
+}
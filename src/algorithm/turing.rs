@@ -0,0 +1,429 @@
+use failure;
+use ndarray::Array2;
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+use visualizer::colormap::Colormap;
+use visualizer::palette::Palette;
+use visualizer::Matrix;
+
+/// ヘッドの動き
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move {
+    /// 左に1マス
+    Left,
+    /// 右に1マス
+    Right,
+    /// 動かない
+    Stay,
+}
+
+/// 1つの遷移。`next`が`None`ならば書き込んで動いたあとに停止する
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    /// 書き込む記号
+    pub write: u8,
+    /// ヘッドの動き
+    pub movement: Move,
+    /// 次の状態
+    pub next: Option<usize>,
+}
+
+/// 有名なTuring機械の(名前, 遷移表, 停止するまでのステップ数, 停止したときに0でない記号の数)
+///
+/// 2状態〜5状態の忙しいビーバー(busy beaver)のチャンピオンなど。遷移表は`TransitionTable`の文字列の形式で書く
+pub const FAMOUS_MACHINES: [(&str, &str, u64, usize); 7] = [
+    ("BB(1)", "1RZ---", 1, 1),
+    ("BB(2)", "1RB1LB_1LA1RZ", 6, 4),
+    ("BB(3)", "1RB1RZ_1LB0RC_1LC1LA", 21, 5),
+    ("BB(3) sigma", "1RB1RZ_0RC1RB_1LC1LA", 14, 6),
+    ("BB(4)", "1RB1LB_1LA0LC_1RZ1LD_1RD0RA", 107, 13),
+    ("BB(5)", "1RB1LC_1RC1RB_1RD0LE_1LA1LD_1RZ0LA", 47_176_870, 4098),
+    ("BB(2, 3)", "1RB2LB1RZ_2LA2RB1LB", 38, 9),
+];
+
+/// Turing機械の遷移表。状態 × 記号ごとに遷移を持ち、遷移がない組み合わせでは停止する
+///
+/// 文字列では、状態ごとの遷移を`_`で区切り、記号ごとに「書く記号, 動き(`L`, `R`, `N`), 次の状態(`A`〜, 停止は`Z`か`H`)」の
+/// 3文字を並べる(bbchallengeと同じ形式)。遷移がない組み合わせは`---`と書く
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::turing::{Move, TransitionTable};
+///
+/// let table: TransitionTable = "1RB1LB_1LA1RZ".parse().unwrap();
+/// assert_eq!((table.num_states(), table.num_symbols()), (2, 2));
+/// let halt = table.get(1, 1).unwrap();
+/// assert_eq!((halt.write, halt.movement, halt.next), (1, Move::Right, None));
+/// assert_eq!(table.to_string(), "1RB1LB_1LA1RZ");
+/// assert!("1RB1LB_1LA".parse::<TransitionTable>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransitionTable {
+    num_symbols: u8,
+    transitions: Vec<Vec<Option<Transition>>>,
+}
+
+impl TransitionTable {
+    /// 全ての遷移がない(すぐに停止する)遷移表
+    ///
+    /// # Panics
+    /// `num_states`か`num_symbols`が0のとき
+    pub fn new(num_states: usize, num_symbols: u8) -> TransitionTable {
+        assert!(
+            num_states > 0 && num_symbols > 0,
+            "at least one state and one symbol are needed"
+        );
+        TransitionTable {
+            num_symbols,
+            transitions: vec![vec![None; num_symbols as usize]; num_states],
+        }
+    }
+
+    /// `FAMOUS_MACHINES`から名前で探す
+    pub fn famous(name: &str) -> Option<TransitionTable> {
+        FAMOUS_MACHINES
+            .iter()
+            .find(|machine| machine.0 == name)
+            .map(|machine| machine.1.parse().expect("bundled machines must be valid"))
+    }
+
+    /// 状態の数
+    pub fn num_states(&self) -> usize {
+        self.transitions.len()
+    }
+
+    /// 記号の数
+    pub fn num_symbols(&self) -> u8 {
+        self.num_symbols
+    }
+
+    /// 状態`state`で記号`symbol`を読んだときの遷移
+    pub fn get(&self, state: usize, symbol: u8) -> Option<Transition> {
+        self.transitions[state][symbol as usize]
+    }
+
+    /// 状態`state`で記号`symbol`を読んだときの遷移を設定する。`None`ならば停止する
+    pub fn set(&mut self, state: usize, symbol: u8, transition: Option<Transition>) {
+        self.transitions[state][symbol as usize] = transition;
+    }
+}
+
+impl FromStr for TransitionTable {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<TransitionTable, failure::Error> {
+        let groups: Vec<&str> = s.trim().split('_').collect();
+        let num_symbols = groups[0].len() / 3;
+        if num_symbols == 0 || groups.iter().any(|group| group.len() != 3 * num_symbols) {
+            return Err(format_err!("every state needs 3 characters for each symbol: {}", s));
+        }
+        if num_symbols > u8::MAX as usize {
+            return Err(format_err!("too many symbols: {}", num_symbols));
+        }
+        let mut table = TransitionTable::new(groups.len(), num_symbols as u8);
+        for (state, group) in groups.iter().enumerate() {
+            let chars: Vec<char> = group.chars().collect();
+            for (symbol, code) in chars.chunks(3).enumerate() {
+                if code == ['-', '-', '-'] {
+                    continue;
+                }
+                let write = code[0]
+                    .to_digit(10)
+                    .filter(|&write| (write as usize) < num_symbols)
+                    .ok_or_else(|| format_err!("invalid symbol '{}' in state {}", code[0], state))?;
+                let movement = match code[1] {
+                    'L' => Move::Left,
+                    'R' => Move::Right,
+                    'N' => Move::Stay,
+                    c => return Err(format_err!("invalid move '{}' in state {}", c, state)),
+                };
+                let next = match code[2] {
+                    'Z' | 'H' => None,
+                    c @ 'A'..='Y' if ((c as u8 - b'A') as usize) < groups.len() => Some((c as u8 - b'A') as usize),
+                    c => return Err(format_err!("invalid state '{}' in state {}", c, state)),
+                };
+                table.set(
+                    state,
+                    symbol as u8,
+                    Some(Transition {
+                        write: write as u8,
+                        movement,
+                        next,
+                    }),
+                );
+            }
+        }
+        Ok(table)
+    }
+}
+
+impl fmt::Display for TransitionTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (state, row) in self.transitions.iter().enumerate() {
+            if state > 0 {
+                write!(f, "_")?;
+            }
+            for transition in row {
+                match *transition {
+                    Some(Transition { write, movement, next }) => {
+                        let movement = match movement {
+                            Move::Left => 'L',
+                            Move::Right => 'R',
+                            Move::Stay => 'N',
+                        };
+                        let next = next.map_or('Z', |next| (b'A' + next as u8) as char);
+                        write!(f, "{}{}{}", write, movement, next)?;
+                    }
+                    None => write!(f, "---")?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 両側に無限に伸びるテープの上で動くTuring機械。テープは最初は全て記号0
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::turing::{TransitionTable, TuringMachine};
+///
+/// let mut machine = TuringMachine::new(TransitionTable::famous("BB(4)").unwrap());
+/// assert_eq!(machine.run(1000), Some(107));
+/// assert_eq!(machine.count_nonzero(), 13);
+/// assert!(machine.is_halted());
+/// // 停止したあとは進まない
+/// assert!(!machine.step());
+/// ```
+#[derive(Debug, Clone)]
+pub struct TuringMachine {
+    table: TransitionTable,
+    tape: VecDeque<u8>,
+    // `tape[0]`の位置
+    origin: i64,
+    head: i64,
+    state: Option<usize>,
+    steps: u64,
+}
+
+impl TuringMachine {
+    /// 状態0で、空のテープの位置0から始める
+    pub fn new(table: TransitionTable) -> TuringMachine {
+        TuringMachine {
+            table,
+            tape: VecDeque::from(vec![0]),
+            origin: 0,
+            head: 0,
+            state: Some(0),
+            steps: 0,
+        }
+    }
+
+    /// 遷移表
+    pub fn table(&self) -> &TransitionTable {
+        &self.table
+    }
+
+    /// ヘッドの位置
+    pub fn head(&self) -> i64 {
+        self.head
+    }
+
+    /// 今の状態。停止していれば`None`
+    pub fn state(&self) -> Option<usize> {
+        self.state
+    }
+
+    /// 停止したかどうか
+    pub fn is_halted(&self) -> bool {
+        self.state.is_none()
+    }
+
+    /// 進めたステップ数
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// 位置`position`の記号
+    pub fn read(&self, position: i64) -> u8 {
+        let index = position - self.origin;
+        if index < 0 {
+            return 0;
+        }
+        self.tape.get(index as usize).cloned().unwrap_or(0)
+    }
+
+    /// 位置`left`から`right`の手前までのテープ
+    pub fn window(&self, left: i64, right: i64) -> Vec<u8> {
+        (left..right).map(|position| self.read(position)).collect()
+    }
+
+    /// 書き込まれたテープの範囲[左端, 右端)
+    pub fn bounds(&self) -> (i64, i64) {
+        (self.origin, self.origin + self.tape.len() as i64)
+    }
+
+    /// テープの上の0でない記号の数
+    pub fn count_nonzero(&self) -> usize {
+        self.tape.iter().filter(|&&symbol| symbol != 0).count()
+    }
+
+    /// 1ステップ進める。停止していて進めなかったときは`false`を返す
+    pub fn step(&mut self) -> bool {
+        let state = match self.state {
+            Some(state) => state,
+            None => return false,
+        };
+        let index = (self.head - self.origin) as usize;
+        let transition = match self.table.get(state, self.tape[index]) {
+            Some(transition) => transition,
+            None => {
+                self.state = None;
+                return false;
+            }
+        };
+        self.tape[index] = transition.write;
+        match transition.movement {
+            Move::Left => self.head -= 1,
+            Move::Right => self.head += 1,
+            Move::Stay => {}
+        }
+        // 必要な分だけテープを伸ばす
+        if self.head < self.origin {
+            self.tape.push_front(0);
+            self.origin -= 1;
+        } else if self.head >= self.origin + self.tape.len() as i64 {
+            self.tape.push_back(0);
+        }
+        self.state = transition.next;
+        self.steps += 1;
+        true
+    }
+
+    /// 停止するか`max_steps`ステップに達するまで進める。停止したときは全体のステップ数を返す
+    pub fn run(&mut self, max_steps: u64) -> Option<u64> {
+        for _ in 0..max_steps {
+            if !self.step() {
+                break;
+            }
+        }
+        if self.is_halted() {
+            Some(self.steps)
+        } else {
+            None
+        }
+    }
+}
+
+/// テープの時間発展を、上から下へ時間が進む行列として記録する
+///
+/// 各行は位置`left`から`width`セル分のテープで、値は記号。ヘッドのあるセルだけは`記号の数 + 状態`
+/// (停止していれば`記号の数 + 状態の数`)になり、`tape_palette`で描くとヘッドの状態ごとに色が分かれる
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::turing::{TapeHistory, TransitionTable, TuringMachine};
+///
+/// let mut machine = TuringMachine::new(TransitionTable::famous("BB(2)").unwrap());
+/// let mut history = TapeHistory::new(-3, 6, 100);
+/// history.record(&machine);
+/// while machine.step() {
+///     history.record(&machine);
+/// }
+/// let matrix = history.matrix();
+/// assert_eq!(matrix.dim(), (7, 6));
+/// // 最初はヘッド(状態A = 2)だけ
+/// assert_eq!(matrix.row(0).to_vec(), vec![0, 0, 0, 2, 0, 0]);
+/// // 最後は1が4つ並び、停止したヘッド(2 + 2 = 4)がある
+/// assert_eq!(matrix.row(6).to_vec(), vec![0, 1, 1, 4, 1, 0]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TapeHistory {
+    left: i64,
+    width: usize,
+    capacity: usize,
+    rows: VecDeque<Vec<u8>>,
+}
+
+impl TapeHistory {
+    /// 位置`left`から`width`セル分を、新しい方から`capacity`行まで記録する
+    pub fn new(left: i64, width: usize, capacity: usize) -> TapeHistory {
+        TapeHistory {
+            left,
+            width,
+            capacity,
+            rows: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// 表示する範囲の左端を設定する。記録済みの行は変わらない
+    pub fn set_left(&mut self, left: i64) {
+        self.left = left;
+    }
+
+    /// 今のテープを1行記録する。`capacity`行を超えたら一番古い行を捨てる
+    pub fn record(&mut self, machine: &TuringMachine) {
+        let mut row = machine.window(self.left, self.left + self.width as i64);
+        let head = machine.head() - self.left;
+        if head >= 0 && (head as usize) < self.width {
+            let table = machine.table();
+            let state = machine.state().unwrap_or_else(|| table.num_states());
+            row[head as usize] = (table.num_symbols() as usize + state).min(u8::MAX as usize) as u8;
+        }
+        if self.rows.len() == self.capacity {
+            self.rows.pop_front();
+        }
+        self.rows.push_back(row);
+    }
+
+    /// 記録した行の数
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// 1行も記録していないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// 記録した行を、上が古い順に並べた行列
+    pub fn matrix(&self) -> Matrix<u8> {
+        let mut matrix = Array2::zeros((self.rows.len(), self.width));
+        for (mut line, row) in matrix.outer_iter_mut().zip(&self.rows) {
+            for (e, &symbol) in line.iter_mut().zip(row) {
+                *e = symbol;
+            }
+        }
+        matrix
+    }
+}
+
+/// `TapeHistory`を描くための色。記号は黒から白への灰色、ヘッドは状態ごとにviridisの色、停止したヘッドは赤
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::turing::{tape_palette, TransitionTable};
+///
+/// let table = TransitionTable::famous("BB(4)").unwrap();
+/// let palette = tape_palette(&table);
+/// // 記号2つ + 状態4つ + 停止
+/// assert_eq!(palette.len(), 7);
+/// assert_eq!(palette.color(1), [255, 255, 255]);
+/// assert_eq!(palette.color(6), [255, 0, 0]);
+/// ```
+pub fn tape_palette(table: &TransitionTable) -> Palette {
+    let mut palette = Palette::new();
+    let symbols = table.num_symbols();
+    for symbol in 0..symbols {
+        let x = symbol as f32 / (symbols as f32 - 1.0).max(1.0);
+        palette = palette.with(&symbol.to_string(), Colormap::Grayscale.color(x));
+    }
+    let states = table.num_states();
+    for state in 0..states {
+        let x = state as f32 / (states as f32 - 1.0).max(1.0);
+        let name = ((b'A' + state.min(25) as u8) as char).to_string();
+        palette = palette.with(&name, Colormap::Viridis.color(x));
+    }
+    palette.with("halt", [255, 0, 0])
+}
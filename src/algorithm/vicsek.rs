@@ -0,0 +1,261 @@
+use algorithm::abm::ContinuousSpace;
+use algorithm::ensemble::Distribution;
+use rand::Rng;
+use std::f32::consts::PI;
+use visualizer::agent_visualizer::AgentSprite;
+use visualizer::palette::hsv_to_rgb;
+
+/// Vicsekモデルのパラメーター
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VicsekParams {
+    /// 粒子の速さ`v0`
+    pub speed: f32,
+    /// 向きをそろえる相手を探す半径`r`
+    pub radius: f32,
+    /// ノイズの大きさ`η`(0〜2π)。新しい向きに[-η/2, η/2]の一様乱数を足す
+    pub noise: f32,
+}
+
+impl Default for VicsekParams {
+    fn default() -> VicsekParams {
+        VicsekParams {
+            speed: 0.05,
+            radius: 1.0,
+            noise: 1.0,
+        }
+    }
+}
+
+/// 1つの粒子
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Particle {
+    /// 位置
+    pub position: (f32, f32),
+    /// 向き(ラジアン)
+    pub heading: f32,
+}
+
+/// Vicsekの集団運動のモデル
+///
+/// 粒子は一定の速さで進み、毎ステップ、半径`r`以内の粒子(自分を含む)の向きの平均にノイズを足した向きになる。
+/// ノイズが小さいか密度が高いと全体の向きがそろい(群れ)、ノイズが大きいとばらばらに動く相転移が起こる
+///
+/// # Example
+/// ```
+/// extern crate rand;
+/// extern crate my_alife;
+///
+/// use rand::{SeedableRng, XorShiftRng};
+/// use my_alife::algorithm::vicsek::{Vicsek, VicsekParams};
+///
+/// let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+/// let params = VicsekParams { noise: 0.3, ..VicsekParams::default() };
+/// let mut vicsek = Vicsek::with_density(100, 2.0, params, &mut rng);
+/// assert_eq!(vicsek.density(), 2.0);
+/// // 初めは向きがばらばら
+/// assert!(vicsek.order_parameter() < 0.3);
+/// vicsek.run(200, &mut rng);
+/// // ノイズが小さいので向きがそろう
+/// assert!(vicsek.order_parameter() > 0.8);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Vicsek {
+    space: ContinuousSpace,
+    particles: Vec<Particle>,
+    params: VicsekParams,
+    time: usize,
+}
+
+impl Vicsek {
+    /// 空間`space`に粒子のいないモデルを作る
+    pub fn new(space: ContinuousSpace, params: VicsekParams) -> Vicsek {
+        Vicsek {
+            space,
+            particles: Vec::new(),
+            params,
+            time: 0,
+        }
+    }
+
+    /// 密度が`density`になる大きさの正方形の空間に、`count`個の粒子をランダムな位置と向きで置く
+    ///
+    /// # Panics
+    /// `density`が正でないとき
+    pub fn with_density<R: Rng>(count: usize, density: f32, params: VicsekParams, rng: &mut R) -> Vicsek {
+        assert!(density > 0.0, "density must be positive");
+        let size = (count as f32 / density).sqrt();
+        let mut vicsek = Vicsek::new(ContinuousSpace::new(size, size), params);
+        vicsek.add_random_particles(count, rng);
+        vicsek
+    }
+
+    /// ランダムな位置と向きの粒子を`count`個加える
+    pub fn add_random_particles<R: Rng>(&mut self, count: usize, rng: &mut R) {
+        for _ in 0..count {
+            let position = self.space.random_position(rng);
+            let heading = rng.gen_range(-PI, PI);
+            self.particles.push(Particle { position, heading });
+        }
+    }
+
+    /// 粒子が動く空間
+    pub fn space(&self) -> &ContinuousSpace {
+        &self.space
+    }
+
+    /// パラメーター
+    pub fn params(&self) -> &VicsekParams {
+        &self.params
+    }
+
+    /// パラメーターを変更する
+    pub fn params_mut(&mut self) -> &mut VicsekParams {
+        &mut self.params
+    }
+
+    /// ノイズの大きさを変更する。0〜2πに収める
+    pub fn set_noise(&mut self, noise: f32) {
+        self.params.noise = noise.clamp(0.0, 2.0 * PI);
+    }
+
+    /// 粒子たち
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    /// 粒子たちを変更する
+    pub fn particles_mut(&mut self) -> &mut Vec<Particle> {
+        &mut self.particles
+    }
+
+    /// 粒子の密度(面積あたりの数)
+    pub fn density(&self) -> f32 {
+        self.particles.len() as f32 / (self.space.width * self.space.height)
+    }
+
+    /// 経過したステップ数
+    pub fn time(&self) -> usize {
+        self.time
+    }
+
+    /// 秩序変数`|Σ v_i| / (N * v0)`。向きがそろうほど1に近く、ばらばらなら0に近い。粒子がいなければ0
+    pub fn order_parameter(&self) -> f32 {
+        if self.particles.is_empty() {
+            return 0.0;
+        }
+        let (x, y) = self.mean_direction();
+        (x * x + y * y).sqrt()
+    }
+
+    /// 粒子全体の平均の向き(ラジアン)
+    pub fn mean_heading(&self) -> f32 {
+        let (x, y) = self.mean_direction();
+        y.atan2(x)
+    }
+
+    fn mean_direction(&self) -> (f32, f32) {
+        let n = self.particles.len().max(1) as f32;
+        let (x, y) = self
+            .particles
+            .iter()
+            .fold((0.0, 0.0), |(x, y), p| (x + p.heading.cos(), y + p.heading.sin()));
+        (x / n, y / n)
+    }
+
+    /// 1ステップ進める。全ての粒子の向きを同時に更新してから、新しい向きに速さ`v0`で進める
+    pub fn step<R: Rng>(&mut self, rng: &mut R) {
+        let positions: Vec<_> = self.particles.iter().map(|p| p.position).collect();
+        let half_noise = self.params.noise / 2.0;
+        let headings: Vec<_> = self
+            .particles
+            .iter()
+            .map(|p| {
+                let (x, y) = self
+                    .space
+                    .within(p.position, self.params.radius, positions.iter().cloned())
+                    .into_iter()
+                    .fold((0.0, 0.0), |(x, y), j| {
+                        let heading = self.particles[j].heading;
+                        (x + heading.cos(), y + heading.sin())
+                    });
+                let noise = if half_noise > 0.0 {
+                    rng.gen_range(-half_noise, half_noise)
+                } else {
+                    0.0
+                };
+                y.atan2(x) + noise
+            })
+            .collect();
+        let speed = self.params.speed;
+        for (p, heading) in self.particles.iter_mut().zip(headings) {
+            p.heading = heading;
+            p.position = self.space.wrap((
+                p.position.0 + speed * heading.cos(),
+                p.position.1 + speed * heading.sin(),
+            ));
+        }
+        self.time += 1;
+    }
+
+    /// `steps`ステップ進める
+    pub fn run<R: Rng>(&mut self, steps: usize, rng: &mut R) {
+        for _ in 0..steps {
+            self.step(rng);
+        }
+    }
+
+    /// 描画するための粒子の絵。色は向きを色相にしたもので、群れになると同じ色がそろう
+    pub fn sprites(&self) -> Vec<AgentSprite> {
+        self.particles
+            .iter()
+            .map(|p| AgentSprite {
+                position: p.position,
+                heading: p.heading,
+                color: hsv_to_rgb((p.heading / (2.0 * PI)).rem_euclid(1.0), 0.8, 1.0),
+            })
+            .collect()
+    }
+}
+
+/// `noises`のノイズごとに、密度`density`の`count`個の粒子を`transient`ステップ動かしてから、
+/// `samples`ステップの間の秩序変数の分布を測る。ノイズを上げると秩序変数が下がる相転移が見える
+///
+/// # Example
+/// ```
+/// extern crate rand;
+/// extern crate my_alife;
+///
+/// use rand::{SeedableRng, XorShiftRng};
+/// use my_alife::algorithm::vicsek::{order_parameter_curve, VicsekParams};
+///
+/// let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+/// let curve = order_parameter_curve(100, 2.0, VicsekParams::default(), &[0.5, 5.0], 150, 50, &mut rng);
+/// assert_eq!(curve.len(), 2);
+/// assert_eq!(curve[0].1.values.len(), 50);
+/// assert!(curve[0].1.mean() > 0.8);
+/// assert!(curve[1].1.mean() < 0.4);
+/// ```
+pub fn order_parameter_curve<R: Rng>(
+    count: usize,
+    density: f32,
+    params: VicsekParams,
+    noises: &[f32],
+    transient: usize,
+    samples: usize,
+    rng: &mut R,
+) -> Vec<(f32, Distribution)> {
+    noises
+        .iter()
+        .map(|&noise| {
+            let mut vicsek = Vicsek::with_density(count, density, VicsekParams { noise, ..params }, rng);
+            vicsek.run(transient, rng);
+            let values = (0..samples)
+                .map(|_| {
+                    vicsek.step(rng);
+                    vicsek.order_parameter()
+                })
+                .collect();
+            (noise, Distribution { values })
+        })
+        .collect()
+}
@@ -0,0 +1,430 @@
+use algorithm::golly::Macrocell;
+use algorithm::patterns::parse_rle;
+use failure;
+use ndarray::Array2;
+use std::fs;
+use std::path::Path;
+use visualizer::palette::Palette;
+use visualizer::Matrix;
+
+/// 状態の数
+pub const NUM_STATES: u8 = 29;
+
+// 北・東・南・西の順(Gollyのvon Neumann近傍と同じ)
+const OFFSETS: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+/// 伝達状態の向き
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// 東(右)
+    East,
+    /// 北(上)
+    North,
+    /// 西(左)
+    West,
+    /// 南(下)
+    South,
+}
+
+impl Direction {
+    // Gollyの状態の並び順(東・北・西・南)
+    const ALL: [Direction; 4] = [Direction::East, Direction::North, Direction::West, Direction::South];
+
+    /// 向いている先のセルへの(行, 列)のずれ
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::East => (0, 1),
+            Direction::North => (-1, 0),
+            Direction::West => (0, -1),
+            Direction::South => (1, 0),
+        }
+    }
+
+    fn index(self) -> u8 {
+        Direction::ALL.iter().position(|&d| d == self).unwrap() as u8
+    }
+}
+
+/// von Neumannの29状態。`state`と`from_state`でGollyの`JvN29`と同じ番号に変換する
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::von_neumann::{Direction, JvnState};
+///
+/// let wire = JvnState::Ordinary { direction: Direction::East, excited: true };
+/// assert_eq!(wire.state(), 17);
+/// assert_eq!(JvnState::from_state(17), Some(wire));
+/// assert_eq!(JvnState::from_state(29), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JvnState {
+    /// 何もない状態U(0)
+    Ground,
+    /// 構築中の感受状態(1〜8)。値はGollyの番号で、S, S0, S1, S00, S01, S10, S11, S000の順
+    Sensitized(u8),
+    /// 合流状態(9〜12)
+    Confluent {
+        /// 今の励起
+        excited: bool,
+        /// 次のステップの励起
+        next: bool,
+    },
+    /// 通常の伝達状態(13〜20)
+    Ordinary {
+        /// 伝える向き
+        direction: Direction,
+        /// 励起しているかどうか
+        excited: bool,
+    },
+    /// 特殊な伝達状態(21〜28)
+    Special {
+        /// 伝える向き
+        direction: Direction,
+        /// 励起しているかどうか
+        excited: bool,
+    },
+}
+
+impl JvnState {
+    /// Gollyの番号から変換する。29以上は`None`
+    pub fn from_state(state: u8) -> Option<JvnState> {
+        Some(match state {
+            0 => JvnState::Ground,
+            1..=8 => JvnState::Sensitized(state),
+            9 => JvnState::Confluent {
+                excited: false,
+                next: false,
+            },
+            10 => JvnState::Confluent {
+                excited: true,
+                next: false,
+            },
+            11 => JvnState::Confluent {
+                excited: false,
+                next: true,
+            },
+            12 => JvnState::Confluent {
+                excited: true,
+                next: true,
+            },
+            13..=20 => JvnState::Ordinary {
+                direction: Direction::ALL[(state - 13) as usize % 4],
+                excited: state >= 17,
+            },
+            21..=28 => JvnState::Special {
+                direction: Direction::ALL[(state - 21) as usize % 4],
+                excited: state >= 25,
+            },
+            _ => return None,
+        })
+    }
+
+    /// Gollyの番号
+    pub fn state(self) -> u8 {
+        match self {
+            JvnState::Ground => 0,
+            JvnState::Sensitized(state) => state,
+            JvnState::Confluent { excited, next } => 9 + excited as u8 + 2 * next as u8,
+            JvnState::Ordinary { direction, excited } => 13 + 4 * excited as u8 + direction.index(),
+            JvnState::Special { direction, excited } => 21 + 4 * excited as u8 + direction.index(),
+        }
+    }
+
+    // 伝達状態ならば(向き, 特殊かどうか, 励起しているか)
+    fn transmission(self) -> Option<(Direction, bool, bool)> {
+        match self {
+            JvnState::Ordinary { direction, excited } => Some((direction, false, excited)),
+            JvnState::Special { direction, excited } => Some((direction, true, excited)),
+            _ => None,
+        }
+    }
+}
+
+// 感受状態が信号`input`を受け取った次の状態。信号の列10000〜1111で9種類の状態を作り分ける
+fn sensitized_next(state: u8, input: bool) -> JvnState {
+    let ordinary = |direction| JvnState::Ordinary {
+        direction,
+        excited: false,
+    };
+    let special = |direction| JvnState::Special {
+        direction,
+        excited: false,
+    };
+    match (state, input) {
+        (1, false) => JvnState::Sensitized(2),
+        (1, true) => JvnState::Sensitized(3),
+        (2, false) => JvnState::Sensitized(4),
+        (2, true) => JvnState::Sensitized(5),
+        (3, false) => JvnState::Sensitized(6),
+        (3, true) => JvnState::Sensitized(7),
+        (4, false) => JvnState::Sensitized(8),
+        (4, true) => ordinary(Direction::West),
+        (5, false) => ordinary(Direction::South),
+        (5, true) => special(Direction::East),
+        (6, false) => special(Direction::North),
+        (6, true) => special(Direction::West),
+        (7, false) => special(Direction::South),
+        (7, true) => JvnState::Confluent {
+            excited: false,
+            next: false,
+        },
+        (_, false) => ordinary(Direction::East),
+        (_, true) => ordinary(Direction::North),
+    }
+}
+
+/// 中心が`center`で、北・東・南・西の近傍が`neighbors`のときの次の状態
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::von_neumann::next_state;
+///
+/// // 西から励起した東向きの通常の伝達状態(17)に指されると、何もない状態(0)が感受状態(1)になる
+/// assert_eq!(next_state(0, [0, 0, 0, 17]), 1);
+/// // 特殊な伝達状態(25)に指されると、通常の伝達状態は壊れる
+/// assert_eq!(next_state(13, [0, 0, 0, 25]), 0);
+/// ```
+pub fn next_state(center: u8, neighbors: [u8; 4]) -> u8 {
+    let center = match JvnState::from_state(center) {
+        Some(center) => center,
+        None => return center,
+    };
+    // 近傍から中心に向かって届く信号
+    let mut ordinary_input = false;
+    let mut special_input = false;
+    // 中心を指している通常の伝達状態が全て励起しているか(合流状態の入力)
+    let mut confluent_inputs = (0, 0);
+    // 励起した合流状態がある向き
+    let mut confluent_excited = [false; 4];
+    for (i, &neighbor) in neighbors.iter().enumerate() {
+        let neighbor = match JvnState::from_state(neighbor) {
+            Some(neighbor) => neighbor,
+            None => continue,
+        };
+        let (dr, dc) = OFFSETS[i];
+        match neighbor.transmission() {
+            Some((direction, special, excited)) if direction.offset() == (-dr, -dc) => {
+                if special {
+                    special_input |= excited;
+                } else {
+                    ordinary_input |= excited;
+                    confluent_inputs.0 += 1;
+                    confluent_inputs.1 += excited as usize;
+                }
+            }
+            _ => {}
+        }
+        if let JvnState::Confluent { excited: true, .. } = neighbor {
+            confluent_excited[i] = true;
+        }
+    }
+    // 合流状態は自分を指していない伝達状態に信号を送る
+    let from_confluent = |direction: Direction| {
+        OFFSETS
+            .iter()
+            .zip(confluent_excited.iter())
+            .any(|(&offset, &excited)| excited && offset != direction.offset())
+    };
+    let next = match center {
+        JvnState::Ground if ordinary_input || special_input => JvnState::Sensitized(1),
+        JvnState::Ground => JvnState::Ground,
+        JvnState::Sensitized(state) => sensitized_next(state, ordinary_input || special_input),
+        JvnState::Confluent { .. } if special_input => JvnState::Ground,
+        JvnState::Confluent { next, .. } => JvnState::Confluent {
+            excited: next,
+            next: confluent_inputs.0 > 0 && confluent_inputs.0 == confluent_inputs.1,
+        },
+        JvnState::Ordinary { .. } if special_input => JvnState::Ground,
+        JvnState::Ordinary { direction, .. } => JvnState::Ordinary {
+            direction,
+            excited: ordinary_input || from_confluent(direction),
+        },
+        JvnState::Special { .. } if ordinary_input => JvnState::Ground,
+        JvnState::Special { direction, .. } => JvnState::Special {
+            direction,
+            excited: special_input || from_confluent(direction),
+        },
+    };
+    next.state()
+}
+
+/// von Neumannの29状態セル・オートマトン。盤面の外は何もない状態とみなす
+///
+/// 伝達状態は信号を向いている先に送り、合流状態は入力のANDをとって2ステップ遅れて周りに送る。
+/// 何もない状態に信号を送ると感受状態になり、続く信号の列でどの状態を作るかが決まる(構築腕)
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::von_neumann::{Direction, JvnState, VonNeumann};
+///
+/// let mut ca = VonNeumann::new((1, 4));
+/// let wire = JvnState::Ordinary { direction: Direction::East, excited: false };
+/// ca.set((0, 0), wire);
+/// ca.set((0, 1), wire);
+/// // 信号の列10000が導線の先に届くと、東向きの通常の伝達状態ができて導線が伸びる
+/// for &bit in &[true, false, false, false, false] {
+///     ca.set((0, 0), JvnState::Ordinary { direction: Direction::East, excited: bit });
+///     ca.step();
+/// }
+/// ca.run(2);
+/// assert_eq!(ca.get((0, 2)), wire);
+/// assert_eq!(ca.get((0, 3)), JvnState::Ground);
+/// assert_eq!(ca.time(), 7);
+/// ```
+#[derive(Debug, Clone)]
+pub struct VonNeumann {
+    cells: Matrix<u8>,
+    time: usize,
+}
+
+impl VonNeumann {
+    /// 何もない盤面
+    pub fn new(dim: (usize, usize)) -> VonNeumann {
+        VonNeumann::from_cells(Array2::zeros(dim))
+    }
+
+    /// Gollyの番号の盤面から始める
+    pub fn from_cells(cells: Matrix<u8>) -> VonNeumann {
+        VonNeumann { cells, time: 0 }
+    }
+
+    /// GollyのRLE(`.rle`)かmacrocell(`.mc`)のパターンを読み込み、周りに`margin`セルの余白をつける
+    ///
+    /// 巨大な自己複製機械のパターンでも、状態が0でない範囲だけを切り出す
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::algorithm::von_neumann::VonNeumann;
+    ///
+    /// // 周期15のパルス発生器が構築腕に10000を送り続け、腕が東に伸びていく
+    /// let mut ca = VonNeumann::load("res/patterns/jvn_pulser_arm.rle", 16).unwrap();
+    /// assert_eq!(ca.cells().dim(), (36, 40));
+    /// let before = ca.population();
+    /// ca.run(60);
+    /// assert!(ca.population() > before + 4);
+    /// ```
+    pub fn load<P: AsRef<Path>>(path: P, margin: usize) -> Result<VonNeumann, failure::Error> {
+        let path = path.as_ref();
+        let pattern = if path.extension() == Some("mc".as_ref()) {
+            let macrocell = Macrocell::load(path)?;
+            match macrocell.bounds() {
+                Some((x, y, width, height)) => macrocell.viewport(x, y, width as usize, height as usize),
+                None => Array2::zeros((0, 0)),
+            }
+        } else {
+            parse_rle(&fs::read_to_string(path)?)?
+        };
+        if let Some(&state) = pattern.iter().find(|&&state| state >= NUM_STATES) {
+            return Err(format_err!("state {} is not a von Neumann state", state));
+        }
+        let (rows, cols) = pattern.dim();
+        let mut cells = Array2::zeros((rows + 2 * margin, cols + 2 * margin));
+        cells
+            .slice_mut(s![margin..margin + rows, margin..margin + cols])
+            .assign(&pattern);
+        Ok(VonNeumann::from_cells(cells))
+    }
+
+    /// 各セルのGollyの番号
+    pub fn cells(&self) -> &Matrix<u8> {
+        &self.cells
+    }
+
+    /// `(row, col)`の状態
+    pub fn get(&self, (row, col): (usize, usize)) -> JvnState {
+        JvnState::from_state(self.cells[[row, col]]).unwrap_or(JvnState::Ground)
+    }
+
+    /// `(row, col)`の状態を設定する
+    pub fn set(&mut self, (row, col): (usize, usize), state: JvnState) {
+        self.cells[[row, col]] = state.state();
+    }
+
+    /// 経過したステップ数
+    pub fn time(&self) -> usize {
+        self.time
+    }
+
+    /// 何もない状態でないセルの数
+    pub fn population(&self) -> usize {
+        self.cells.iter().filter(|&&state| state != 0).count()
+    }
+
+    /// 1ステップ進める
+    pub fn step(&mut self) {
+        let (rows, cols) = self.cells.dim();
+        let cells = &self.cells;
+        let at = |row: usize, col: usize, (dr, dc): (isize, isize)| {
+            let (r, c) = (row as isize + dr, col as isize + dc);
+            if r < 0 || c < 0 || r >= rows as isize || c >= cols as isize {
+                0
+            } else {
+                cells[[r as usize, c as usize]]
+            }
+        };
+        let next = Array2::from_shape_fn((rows, cols), |(row, col)| {
+            let center = cells[[row, col]];
+            let neighbors = [
+                at(row, col, OFFSETS[0]),
+                at(row, col, OFFSETS[1]),
+                at(row, col, OFFSETS[2]),
+                at(row, col, OFFSETS[3]),
+            ];
+            if center == 0 && neighbors.iter().all(|&n| n < 17 || (21..25).contains(&n)) {
+                // 励起した伝達状態に指されない限り、何もない状態は変わらない
+                0
+            } else {
+                next_state(center, neighbors)
+            }
+        });
+        self.cells = next;
+        self.time += 1;
+    }
+
+    /// `steps`ステップ進める
+    pub fn run(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+}
+
+/// 29状態を描くための色。Gollyと同じく、感受状態は紫から黄色、合流状態は黄色、
+/// 通常の伝達状態は青、特殊な伝達状態は赤で、励起しているほど明るい
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::von_neumann::{jvn_palette, NUM_STATES};
+///
+/// let palette = jvn_palette();
+/// assert_eq!(palette.len(), NUM_STATES as usize);
+/// assert_eq!(palette.color(0), [48, 48, 48]);
+/// ```
+pub fn jvn_palette() -> Palette {
+    let mut palette = Palette::new().with("U", [48, 48, 48]);
+    for (i, name) in ["S", "S0", "S1", "S00", "S01", "S10", "S11", "S000"].iter().enumerate() {
+        let t = i as u8 * 16;
+        palette = palette.with(name, [255, 128 + t, 255 - 2 * t]);
+    }
+    let confluent = [
+        ("C00", [255, 255, 128]),
+        ("C10", [255, 255, 0]),
+        ("C01", [255, 224, 64]),
+        ("C11", [255, 255, 255]),
+    ];
+    for &(name, color) in &confluent {
+        palette = palette.with(name, color);
+    }
+    let arrows = ["E", "N", "W", "S"];
+    for &(prefix, quiescent, excited) in &[
+        ("ordinary", [106, 106, 255], [199, 199, 255]),
+        ("special", [255, 56, 56], [255, 191, 191]),
+    ] {
+        for &(suffix, color) in &[("", quiescent), ("*", excited)] {
+            for arrow in &arrows {
+                palette = palette.with(&format!("{} {}{}", prefix, arrow, suffix), color);
+            }
+        }
+    }
+    palette
+}
@@ -0,0 +1,176 @@
+use failure;
+use std::any::Any;
+
+type System = Box<dyn FnMut(&mut Layers, f32) -> Result<(), failure::Error>>;
+
+/// `World`が持つ名前つきの層。種類の異なる層(濃度場、個体群、温度など)を同時に持てる
+#[derive(Default)]
+pub struct Layers {
+    layers: Vec<(String, Box<dyn Any>)>,
+}
+
+impl Layers {
+    fn index(&self, name: &str) -> Option<usize> {
+        self.layers.iter().position(|layer| layer.0 == name)
+    }
+
+    /// 名前`name`の層。なければ、または型が`T`でなければ`None`
+    pub fn get<T: Any>(&self, name: &str) -> Option<&T> {
+        self.index(name).and_then(|i| self.layers[i].1.downcast_ref())
+    }
+
+    /// 名前`name`の層を変更するための参照
+    pub fn get_mut<T: Any>(&mut self, name: &str) -> Option<&mut T> {
+        self.index(name).and_then(move |i| self.layers[i].1.downcast_mut())
+    }
+
+    /// 異なる2つの層を同時に変更するための参照。層どうしを結合するときに使う
+    pub fn pair_mut<A: Any, B: Any>(&mut self, a: &str, b: &str) -> Option<(&mut A, &mut B)> {
+        let (i, j) = (self.index(a)?, self.index(b)?);
+        if i == j {
+            return None;
+        }
+        let (low, high) = self.layers.split_at_mut(i.max(j));
+        let (first, second) = (&mut low[i.min(j)].1, &mut high[0].1);
+        let (layer_a, layer_b) = if i < j { (first, second) } else { (second, first) };
+        Some((layer_a.downcast_mut()?, layer_b.downcast_mut()?))
+    }
+
+    /// 層の名前(追加した順)
+    pub fn names(&self) -> Vec<&str> {
+        self.layers.iter().map(|layer| layer.0.as_str()).collect()
+    }
+}
+
+fn missing(name: &str) -> failure::Error {
+    format_err!("layer \"{}\" does not exist or has a different type", name)
+}
+
+/// 複数の層を組み合わせた世界
+///
+/// 栄養の濃度場、個体群、温度のような層を`add_layer`で追加し、各層の更新と層どうしの結合を
+/// `add_update`、`add_coupling`、`add_system`で登録する。`step`は登録した順に実行する
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::algorithm::world::World;
+/// use my_alife::visualizer::Matrix;
+///
+/// let mut world = World::new();
+/// world.add_layer("nutrient", Array2::<f32>::ones((8, 8)));
+/// world.add_layer("population", Array2::<f32>::from_elem((8, 8), 0.5));
+/// // 栄養は一定の速さで回復する
+/// world.add_update("nutrient", |nutrient: &mut Matrix<f32>, dt| {
+///     nutrient.mapv_inplace(|n| (n + 0.1 * dt).min(1.0));
+/// });
+/// // 個体は栄養を食べて増える
+/// world.add_coupling("population", "nutrient", |population: &mut Matrix<f32>, nutrient: &mut Matrix<f32>, dt| {
+///     for (p, n) in population.iter_mut().zip(nutrient.iter_mut()) {
+///         let eaten = (*p * *n * dt).min(*n);
+///         *n -= eaten;
+///         *p += 0.5 * eaten;
+///     }
+/// });
+/// world.step(1.0).unwrap();
+/// assert_eq!(world.time(), 1.0);
+/// assert_eq!(world.layer::<Matrix<f32>>("population").unwrap()[[0, 0]], 0.75);
+/// assert!(world.layer::<Matrix<u8>>("population").is_none());
+/// ```
+#[derive(Default)]
+pub struct World {
+    layers: Layers,
+    systems: Vec<(String, System)>,
+    time: f32,
+}
+
+impl World {
+    /// 層も更新規則も持たない世界を生成する
+    pub fn new() -> World {
+        World::default()
+    }
+
+    /// 層を追加する。同じ名前の層があれば置き換える
+    pub fn add_layer<T: Any>(&mut self, name: &str, layer: T) {
+        match self.layers.index(name) {
+            Some(i) => self.layers.layers[i].1 = Box::new(layer),
+            None => self.layers.layers.push((name.to_string(), Box::new(layer))),
+        }
+    }
+
+    /// 名前`name`の層
+    pub fn layer<T: Any>(&self, name: &str) -> Option<&T> {
+        self.layers.get(name)
+    }
+
+    /// 名前`name`の層を変更するための参照
+    pub fn layer_mut<T: Any>(&mut self, name: &str) -> Option<&mut T> {
+        self.layers.get_mut(name)
+    }
+
+    /// すべての層
+    pub fn layers(&self) -> &Layers {
+        &self.layers
+    }
+
+    /// 1つの層を時間`dt`だけ進める規則を追加する
+    pub fn add_update<T, F>(&mut self, layer: &str, mut update: F)
+    where
+        T: Any,
+        F: FnMut(&mut T, f32) + 'static,
+    {
+        let name = layer.to_string();
+        self.add_system(layer, move |layers, dt| {
+            let layer = layers.get_mut(&name).ok_or_else(|| missing(&name))?;
+            update(layer, dt);
+            Ok(())
+        });
+    }
+
+    /// 2つの層の間の相互作用を追加する
+    pub fn add_coupling<A, B, F>(&mut self, a: &str, b: &str, mut coupling: F)
+    where
+        A: Any,
+        B: Any,
+        F: FnMut(&mut A, &mut B, f32) + 'static,
+    {
+        let (name_a, name_b) = (a.to_string(), b.to_string());
+        self.add_system(&format!("{}-{}", a, b), move |layers, dt| {
+            let (a, b) = layers
+                .pair_mut(&name_a, &name_b)
+                .ok_or_else(|| format_err!("layers \"{}\" and \"{}\" cannot be coupled", name_a, name_b))?;
+            coupling(a, b, dt);
+            Ok(())
+        });
+    }
+
+    /// すべての層にアクセスできる規則を追加する。3つ以上の層を結合するときに使う
+    pub fn add_system<F>(&mut self, name: &str, system: F)
+    where
+        F: FnMut(&mut Layers, f32) -> Result<(), failure::Error> + 'static,
+    {
+        self.systems.push((name.to_string(), Box::new(system)));
+    }
+
+    /// 登録されている規則の名前(実行する順)
+    pub fn system_names(&self) -> Vec<&str> {
+        self.systems.iter().map(|layer| layer.0.as_str()).collect()
+    }
+
+    /// 登録した順にすべての規則を実行して、時間を`dt`だけ進める
+    pub fn step(&mut self, dt: f32) -> Result<(), failure::Error> {
+        for &mut (ref name, ref mut system) in &mut self.systems {
+            system(&mut self.layers, dt).map_err(|e| format_err!("system \"{}\" failed: {}", name, e))?;
+        }
+        self.time += dt;
+        Ok(())
+    }
+
+    /// 経過時間
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+}
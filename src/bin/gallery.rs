@@ -0,0 +1,232 @@
+//! 同梱しているシミュレーションを一覧から選んで動かす
+//!
+//! `cargo run --bin gallery`で一覧を表示し、番号か名前を入力すると実行する。
+//! `cargo run --bin gallery -- coral`のように引数で渡すこともできる。
+//! `cargo run --bin gallery -- --list`でプリセットとそのパラメータを表示する。
+//! `cargo run --bin gallery -- --surprise [seed]`でモデルとパラメータを乱数で選ぶ
+extern crate failure;
+extern crate my_alife;
+extern crate ndarray;
+extern crate rand;
+
+use my_alife::algorithm::elementary_ca::{single_seed, step};
+use my_alife::algorithm::game_of_life::{random_cells_using, SparseLife};
+use my_alife::algorithm::gray_scott::{initial_matrix_using, laplacian};
+use my_alife::algorithm::hashlife::HashLife;
+use my_alife::algorithm::larger_than_life::LtlRule;
+use my_alife::algorithm::lenia::Animal;
+use my_alife::algorithm::life_like::LifeRule;
+use my_alife::algorithm::region::paste;
+use my_alife::presets::{surprise, ModelConfig, Preset, PresetRegistry};
+use my_alife::visualizer::camera::Camera;
+use my_alife::visualizer::matrix_visualizer::{Matrix, MatrixVisualizer};
+use my_alife::visualizer::ControlFlow;
+use ndarray::Array2;
+use rand::Rng;
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const VERTEX_SHADER: &str = "res/shaders/matrix_visualizer_vertex.glsl";
+const FRAGMENT_SHADER: &str = "res/shaders/matrix_visualizer_fragment.glsl";
+
+// プリセット以外に一覧に載せるもの
+struct Extra {
+    name: &'static str,
+    description: &'static str,
+    run: fn() -> Result<(), failure::Error>,
+}
+
+const EXTRAS: &[Extra] = &[Extra {
+    name: "hashlife-glider",
+    description: "大きさに上限のない平面を進むグライダーをカメラで追いかける",
+    run: hashlife_glider,
+}];
+
+fn run_config<R: Rng>(title: &str, config: &ModelConfig, rng: &mut R) -> Result<(), failure::Error> {
+    match *config {
+        ModelConfig::GrayScott { f, k } => {
+            let matrix = MatrixVisualizer::new(title, VERTEX_SHADER, FRAGMENT_SHADER)?;
+            matrix.draw_loop(initial_matrix_using(rng), f, k, laplacian)
+        }
+        ModelConfig::GameOfLife { density } => game_of_life(title, random_cells_using((256, 256), density, rng)),
+        ModelConfig::LifeLike { rule, density } => life_like(title, rule, random_cells_using((256, 256), density, rng)),
+        ModelConfig::LargerThanLife { rule, density } => {
+            larger_than_life(title, rule, random_cells_using((256, 256), density, rng))
+        }
+        ModelConfig::ElementaryCa { rule, width } => elementary_ca(title, rule, width),
+        ModelConfig::Lenia { ref animal, dim } => lenia(title, animal, dim),
+    }
+}
+
+fn life_like(title: &str, rule: LifeRule, mut cells: Matrix<u8>) -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new(title, VERTEX_SHADER, FRAGMENT_SHADER)?;
+    loop {
+        cells = rule.step(&cells);
+        if matrix.render_frame(&cells.mapv(|state| rule.intensity(state)))? == ControlFlow::Stop {
+            return Ok(());
+        }
+    }
+}
+
+fn game_of_life(title: &str, cells: Matrix<u8>) -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new(title, VERTEX_SHADER, FRAGMENT_SHADER)?;
+    let mut life = SparseLife::new(cells, 16);
+    matrix.draw(life.cells())?;
+    loop {
+        life.step();
+        matrix.draw_dirty(life.cells(), life.dirty())?;
+        if matrix.poll_events() == ControlFlow::Stop {
+            return Ok(());
+        }
+    }
+}
+
+fn larger_than_life(title: &str, rule: LtlRule, mut cells: Matrix<u8>) -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new(title, VERTEX_SHADER, FRAGMENT_SHADER)?;
+    loop {
+        cells = rule.step(&cells);
+        if matrix.render_frame(&cells)? == ControlFlow::Stop {
+            return Ok(());
+        }
+    }
+}
+
+fn elementary_ca(title: &str, rule: u8, width: usize) -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new(title, VERTEX_SHADER, FRAGMENT_SHADER)?;
+    // 一番下の行に新しい状態を追加して、古い状態を上に流す
+    let mut history = Array2::zeros((width, width));
+    let mut cells = single_seed(width);
+    loop {
+        for row in 1..width {
+            let next = history.row(row).to_owned();
+            history.row_mut(row - 1).assign(&next);
+        }
+        for (e, &cell) in history.row_mut(width - 1).iter_mut().zip(&cells) {
+            *e = cell;
+        }
+        cells = step(&cells, rule);
+        if matrix.render_frame(&history)? == ControlFlow::Stop {
+            return Ok(());
+        }
+    }
+}
+
+fn lenia(title: &str, animal: &Animal, dim: (usize, usize)) -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new(title, VERTEX_SHADER, FRAGMENT_SHADER)?;
+    // 生き物を盤面の中央に置く
+    let (rows, cols) = animal.cells.dim();
+    let mut cells = Array2::zeros(dim);
+    paste(
+        &mut cells,
+        &animal.cells,
+        (dim.0 as isize - rows as isize) / 2,
+        (dim.1 as isize - cols as isize) / 2,
+        true,
+    );
+    loop {
+        cells = animal.lenia.step(&cells);
+        if matrix.render_frame(&cells)? == ControlFlow::Stop {
+            return Ok(());
+        }
+    }
+}
+
+fn hashlife_glider() -> Result<(), failure::Error> {
+    let mut matrix = MatrixVisualizer::new("HashLife: glider", VERTEX_SHADER, FRAGMENT_SHADER)?;
+    let mut camera = Camera::new(600.0, 600.0);
+    camera.set_zoom(8.0);
+    matrix.set_camera(Some(camera));
+    let mut life = HashLife::new();
+    for &(x, y) in &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+        life.set_cell(x, y, true);
+    }
+    loop {
+        life.step(1)?;
+        let target = (
+            life.generation() as f64 / 4.0 + 1.0,
+            life.generation() as f64 / 4.0 + 1.0,
+        );
+        let rect = {
+            let camera = matrix.camera_mut().unwrap();
+            camera.follow(target, 0.1);
+            camera.visible_cells()
+        };
+        let view = life.viewport(rect.x, rect.y, rect.width, rect.height);
+        if matrix.render_frame(&view)? == ControlFlow::Stop {
+            return Ok(());
+        }
+    }
+}
+
+enum Selection {
+    Preset(Box<Preset>),
+    Extra(&'static Extra),
+}
+
+fn print_menu(registry: &PresetRegistry) {
+    println!("my-alife gallery");
+    let presets = registry
+        .iter()
+        .map(|preset| (preset.name.as_str(), preset.description.as_str()));
+    let extras = EXTRAS.iter().map(|extra| (extra.name, extra.description));
+    for (i, (name, description)) in presets.chain(extras).enumerate() {
+        println!("  {:>2}. {:<16} {}", i + 1, name, description);
+    }
+}
+
+// 番号(1始まり)か名前で選ぶ
+fn find(registry: &PresetRegistry, key: &str) -> Option<Selection> {
+    let presets = registry.names().len();
+    match key.parse::<usize>() {
+        Ok(n) if 1 <= n && n <= presets => registry.iter().nth(n - 1).cloned().map(Box::new).map(Selection::Preset),
+        Ok(n) if presets < n && n <= presets + EXTRAS.len() => Some(Selection::Extra(&EXTRAS[n - 1 - presets])),
+        Ok(_) => None,
+        Err(_) => registry
+            .get(key)
+            .cloned()
+            .map(Box::new)
+            .map(Selection::Preset)
+            .or_else(|| EXTRAS.iter().find(|extra| extra.name == key).map(Selection::Extra)),
+    }
+}
+
+fn main() -> Result<(), failure::Error> {
+    let registry = PresetRegistry::default();
+    let key = match env::args().nth(1) {
+        Some(ref arg) if arg == "--list" => {
+            for preset in registry.iter() {
+                println!("{}\t{}", preset.name, preset.config);
+            }
+            return Ok(());
+        }
+        Some(ref arg) if arg == "--surprise" => {
+            let seed = match env::args().nth(2) {
+                Some(seed) => seed.parse()?,
+                None => SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            };
+            let experiment = surprise(seed);
+            // 再現できるように設定をすべて表示する
+            println!("surprise: {}", experiment);
+            return run_config(
+                &format!("surprise: {}", experiment),
+                &experiment.config,
+                &mut experiment.rng(),
+            );
+        }
+        Some(arg) => arg,
+        None => {
+            print_menu(&registry);
+            print!("番号か名前を入力してください: ");
+            io::stdout().flush()?;
+            let mut line = String::new();
+            io::stdin().lock().read_line(&mut line)?;
+            line.trim().to_string()
+        }
+    };
+    match find(&registry, &key) {
+        Some(Selection::Preset(preset)) => run_config(&preset.name, &preset.config, &mut rand::thread_rng()),
+        Some(Selection::Extra(extra)) => (extra.run)(),
+        None => Err(failure::format_err!("\"{}\"というモデルはありません", key)),
+    }
+}
@@ -0,0 +1,171 @@
+//! 他の言語やゲームエンジン(Unity, openFrameworks, Processingなど)から使うためのC ABI
+//!
+//! モデルごとに`*_new`で生成、`*_step`で計算、`*_cells`などで結果のbufferを取得、`*_free`で破棄する。
+//! 取得したbufferは次に`*_step`か`*_free`を呼ぶまで有効。ヘッダは`include/my_alife.h`にある
+use algorithm::game_of_life;
+use algorithm::gray_scott::{initial_matrix, laplacian};
+use std::ptr;
+use visualizer::Matrix;
+
+/// Gray-Scottモデルの状態
+pub struct MyAlifeGrayScott {
+    uv: (Matrix<f32>, Matrix<f32>),
+    f: f32,
+    k: f32,
+}
+
+/// Game of Lifeの状態
+pub struct MyAlifeGameOfLife {
+    cells: Matrix<u8>,
+}
+
+// 行列の先頭へのポインタと大きさを返す。連続していない場合はnull
+unsafe fn buffer<A>(matrix: &Matrix<A>, rows: *mut usize, cols: *mut usize) -> *const A {
+    if !rows.is_null() {
+        *rows = matrix.rows();
+    }
+    if !cols.is_null() {
+        *cols = matrix.cols();
+    }
+    matrix.as_slice().map_or(ptr::null(), |slice| slice.as_ptr())
+}
+
+/// Gray-Scottモデルを生成する
+#[no_mangle]
+pub extern "C" fn my_alife_gray_scott_new(f: f32, k: f32) -> *mut MyAlifeGrayScott {
+    Box::into_raw(Box::new(MyAlifeGrayScott {
+        uv: initial_matrix(),
+        f,
+        k,
+    }))
+}
+
+/// 描画1フレーム分の計算を`frames`回行う
+///
+/// # Safety
+/// `model`は`my_alife_gray_scott_new`で生成して、まだ破棄していないものであること
+#[no_mangle]
+pub unsafe extern "C" fn my_alife_gray_scott_step(model: *mut MyAlifeGrayScott, frames: usize) {
+    if let Some(model) = model.as_mut() {
+        for _ in 0..frames {
+            laplacian(&mut model.uv, model.f, model.k);
+        }
+    }
+}
+
+/// パラメータを変更する
+///
+/// # Safety
+/// `model`は`my_alife_gray_scott_new`で生成して、まだ破棄していないものであること
+#[no_mangle]
+pub unsafe extern "C" fn my_alife_gray_scott_set_params(model: *mut MyAlifeGrayScott, f: f32, k: f32) {
+    if let Some(model) = model.as_mut() {
+        model.f = f;
+        model.k = k;
+    }
+}
+
+/// uの濃度のbuffer(行優先)。`rows`と`cols`に大きさを書き込む
+///
+/// # Safety
+/// `model`は`my_alife_gray_scott_new`で生成して、まだ破棄していないものであること。
+/// `rows`と`cols`はnullか書き込めるポインタであること
+#[no_mangle]
+pub unsafe extern "C" fn my_alife_gray_scott_u(
+    model: *const MyAlifeGrayScott,
+    rows: *mut usize,
+    cols: *mut usize,
+) -> *const f32 {
+    model
+        .as_ref()
+        .map_or(ptr::null(), |model| buffer(&model.uv.0, rows, cols))
+}
+
+/// vの濃度のbuffer(行優先)。`rows`と`cols`に大きさを書き込む
+///
+/// # Safety
+/// `my_alife_gray_scott_u`と同じ
+#[no_mangle]
+pub unsafe extern "C" fn my_alife_gray_scott_v(
+    model: *const MyAlifeGrayScott,
+    rows: *mut usize,
+    cols: *mut usize,
+) -> *const f32 {
+    model
+        .as_ref()
+        .map_or(ptr::null(), |model| buffer(&model.uv.1, rows, cols))
+}
+
+/// Gray-Scottモデルを破棄する。nullの場合は何もしない
+///
+/// # Safety
+/// `model`は`my_alife_gray_scott_new`で生成して、まだ破棄していないものであること
+#[no_mangle]
+pub unsafe extern "C" fn my_alife_gray_scott_free(model: *mut MyAlifeGrayScott) {
+    if !model.is_null() {
+        drop(Box::from_raw(model));
+    }
+}
+
+/// 生きているセルの割合が`density`のGame of Lifeを生成する
+///
+/// # Example
+/// ```
+/// use my_alife::ffi::*;
+///
+/// let (mut rows, mut cols) = (0, 0);
+/// unsafe {
+///     let life = my_alife_game_of_life_new(32, 16, 0.2);
+///     my_alife_game_of_life_step(life, 10);
+///     let cells = my_alife_game_of_life_cells(life, &mut rows, &mut cols);
+///     assert!(!cells.is_null());
+///     assert_eq!((rows, cols), (32, 16));
+///     my_alife_game_of_life_free(life);
+/// }
+/// ```
+#[no_mangle]
+pub extern "C" fn my_alife_game_of_life_new(rows: usize, cols: usize, density: f32) -> *mut MyAlifeGameOfLife {
+    Box::into_raw(Box::new(MyAlifeGameOfLife {
+        cells: game_of_life::random_cells((rows, cols), density),
+    }))
+}
+
+/// `steps`ステップ進める
+///
+/// # Safety
+/// `model`は`my_alife_game_of_life_new`で生成して、まだ破棄していないものであること
+#[no_mangle]
+pub unsafe extern "C" fn my_alife_game_of_life_step(model: *mut MyAlifeGameOfLife, steps: usize) {
+    if let Some(model) = model.as_mut() {
+        for _ in 0..steps {
+            model.cells = game_of_life::step(&model.cells);
+        }
+    }
+}
+
+/// セルのbuffer(行優先、生きているセルが1)。`rows`と`cols`に大きさを書き込む
+///
+/// # Safety
+/// `model`は`my_alife_game_of_life_new`で生成して、まだ破棄していないものであること。
+/// `rows`と`cols`はnullか書き込めるポインタであること
+#[no_mangle]
+pub unsafe extern "C" fn my_alife_game_of_life_cells(
+    model: *const MyAlifeGameOfLife,
+    rows: *mut usize,
+    cols: *mut usize,
+) -> *const u8 {
+    model
+        .as_ref()
+        .map_or(ptr::null(), |model| buffer(&model.cells, rows, cols))
+}
+
+/// Game of Lifeを破棄する。nullの場合は何もしない
+///
+/// # Safety
+/// `model`は`my_alife_game_of_life_new`で生成して、まだ破棄していないものであること
+#[no_mangle]
+pub unsafe extern "C" fn my_alife_game_of_life_free(model: *mut MyAlifeGameOfLife) {
+    if !model.is_null() {
+        drop(Box::from_raw(model));
+    }
+}
@@ -0,0 +1,306 @@
+use failure;
+use jpeg_encoder::{ColorType, Encoder};
+use std::io::{Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+use visualizer::image::to_rgba;
+use visualizer::Matrix;
+use visualizer::texture::ValueMapping;
+
+// リクエストのヘッダーとして読む最大の大きさ
+const MAX_REQUEST_SIZE: usize = 8192;
+// 取り出されずにたまる操作の数の上限。超えたら古いものから捨てる
+const MAX_COMMANDS: usize = 64;
+// 配信中に止めるかどうかを確かめる間隔
+const WAIT_INTERVAL: Duration = Duration::from_millis(500);
+const BOUNDARY: &str = "frame";
+const INDEX_HTML: &str = "<!DOCTYPE html>
+<html>
+<head><meta charset=\"utf-8\"><title>my-alife</title></head>
+<body style=\"background: #222; color: #eee; font-family: sans-serif\">
+<img src=\"/stream\" style=\"width: 512px; image-rendering: pixelated\"><br>
+<button onclick=\"fetch('/pause')\">pause</button>
+<button onclick=\"fetch('/resume')\">resume</button>
+<a href=\"/snapshot\" download=\"snapshot.jpg\"><button>snapshot</button></a>
+</body>
+</html>
+";
+
+/// ブラウザからの操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamCommand {
+    /// `/pause`
+    Pause,
+    /// `/resume`
+    Resume,
+    /// `/snapshot`。今の盤面を保存してほしい
+    Snapshot,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    // (何枚目か, JPEG)
+    frame: Mutex<(u64, Arc<Vec<u8>>)>,
+    updated: Condvar,
+    commands: Mutex<Vec<StreamCommand>>,
+    paused: AtomicBool,
+    stopped: AtomicBool,
+}
+
+/// 盤面をMJPEG(`multipart/x-mixed-replace`)で配信するHTTPサーバー
+///
+/// ウィンドウのない遠くのマシンで動かしているシミュレーションを、ブラウザで`http://<host>:<port>/`を開いて見られる。
+/// 次のURLを使える
+/// * `/` - 映像と操作ボタンのページ
+/// * `/stream` - MJPEGの映像
+/// * `/snapshot` - 最新の盤面のJPEG。シミュレーションには`StreamCommand::Snapshot`が届く
+/// * `/pause`, `/resume` - 一時停止と再開
+/// * `/status` - 状態のJSON
+///
+/// 接続ごとにスレッドを立てるので、`publish`はブロックしない
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::http::{MjpegServer, StreamCommand};
+/// use my_alife::visualizer::texture::ValueMapping;
+/// use std::io::{Read, Write};
+/// use std::net::TcpStream;
+///
+/// let server = MjpegServer::bind("127.0.0.1:0", ValueMapping::default()).unwrap();
+/// server.publish(&Array2::<f32>::eye(16)).unwrap();
+///
+/// let get = |path: &str| {
+///     let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+///     write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+///     let mut response = Vec::new();
+///     stream.read_to_end(&mut response).unwrap();
+///     response
+/// };
+/// assert!(get("/pause").starts_with(b"HTTP/1.1 200 OK"));
+/// assert!(server.is_paused());
+/// let snapshot = get("/snapshot");
+/// assert!(snapshot.windows(2).any(|w| w == [0xff, 0xd8]));
+/// assert_eq!(server.poll_commands(), vec![StreamCommand::Pause, StreamCommand::Snapshot]);
+/// assert!(get("/missing").starts_with(b"HTTP/1.1 404"));
+/// ```
+#[derive(Debug)]
+pub struct MjpegServer {
+    address: SocketAddr,
+    mapping: ValueMapping,
+    quality: u8,
+    shared: Arc<Shared>,
+}
+
+impl MjpegServer {
+    /// `address`で待ち受け、`mapping`の色で配信するサーバーを立てる
+    pub fn bind<A: ToSocketAddrs>(address: A, mapping: ValueMapping) -> Result<MjpegServer, failure::Error> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let shared = Arc::new(Shared::default());
+        let accepting = shared.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if accepting.stopped.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    let shared = accepting.clone();
+                    thread::spawn(move || {
+                        let _ = handle(stream, &shared);
+                    });
+                }
+            }
+        });
+        Ok(MjpegServer {
+            address,
+            mapping,
+            quality: 80,
+            shared,
+        })
+    }
+
+    /// 待ち受けているアドレス
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// JPEGの品質(1〜100)を変える
+    pub fn set_quality(&mut self, quality: u8) {
+        self.quality = quality.clamp(1, 100);
+    }
+
+    /// 盤面を配信する。1セルが1画素になる。一辺が65535を超える盤面はJPEGにできないのでエラーにする
+    ///
+    /// # Example
+    /// ```
+    /// extern crate ndarray;
+    /// extern crate my_alife;
+    ///
+    /// use ndarray::Array2;
+    /// use my_alife::http::MjpegServer;
+    /// use my_alife::visualizer::texture::ValueMapping;
+    ///
+    /// let server = MjpegServer::bind("127.0.0.1:0", ValueMapping::default()).unwrap();
+    /// assert!(server.publish(&Array2::<f32>::zeros((16, 16))).is_ok());
+    /// assert!(server.publish(&Array2::<f32>::zeros((1, 70000))).is_err());
+    /// ```
+    pub fn publish<A>(&self, matrix: &Matrix<A>) -> Result<(), failure::Error>
+    where
+        A: Copy + Into<f32>,
+    {
+        let (rows, cols) = matrix.dim();
+        if rows > u16::MAX as usize || cols > u16::MAX as usize {
+            return Err(format_err!("{}x{} matrix is too large for JPEG", rows, cols));
+        }
+        let mut jpeg = Vec::new();
+        Encoder::new(&mut jpeg, self.quality)
+            .encode(
+                &to_rgba(matrix, &self.mapping),
+                cols as u16,
+                rows as u16,
+                ColorType::Rgba,
+            )
+            .map_err(|e| format_err!("{}", e))?;
+        let mut frame = self.shared.frame.lock().unwrap();
+        *frame = (frame.0 + 1, Arc::new(jpeg));
+        self.shared.updated.notify_all();
+        Ok(())
+    }
+
+    /// ブラウザから一時停止されているかどうか
+    pub fn is_paused(&self) -> bool {
+        self.shared.paused.load(Ordering::SeqCst)
+    }
+
+    /// 一時停止の状態を変える
+    pub fn set_paused(&self, paused: bool) {
+        self.shared.paused.store(paused, Ordering::SeqCst);
+    }
+
+    /// 届いている操作をすべて取り出す。取り出さずにいると、新しいものから64個までしか残らない
+    pub fn poll_commands(&self) -> Vec<StreamCommand> {
+        self.shared.commands.lock().unwrap().drain(..).collect()
+    }
+}
+
+impl Drop for MjpegServer {
+    fn drop(&mut self) {
+        self.shared.stopped.store(true, Ordering::SeqCst);
+        self.shared.updated.notify_all();
+        // acceptで待っているスレッドを起こす
+        let _ = TcpStream::connect(self.address);
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &[u8]) -> Result<(), failure::Error> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    Ok(())
+}
+
+fn handle(mut stream: TcpStream, shared: &Shared) -> Result<(), failure::Error> {
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST_SIZE {
+        let len = stream.read(&mut buffer)?;
+        if len == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..len]);
+    }
+    let request = String::from_utf8_lossy(&request);
+    let path = request
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .unwrap_or("/");
+    let path = path.split('?').next().unwrap_or(path);
+    let command = |command: StreamCommand| {
+        let mut commands = shared.commands.lock().unwrap();
+        if commands.len() >= MAX_COMMANDS {
+            commands.remove(0);
+        }
+        commands.push(command);
+    };
+    match path {
+        "/" => respond(&mut stream, "200 OK", "text/html; charset=utf-8", INDEX_HTML.as_bytes())?,
+        "/stream" => stream_frames(&mut stream, shared)?,
+        "/snapshot" => {
+            command(StreamCommand::Snapshot);
+            let (sequence, jpeg) = shared.frame.lock().unwrap().clone();
+            if sequence == 0 {
+                respond(&mut stream, "503 Service Unavailable", "text/plain", b"no frames yet")?;
+            } else {
+                respond(&mut stream, "200 OK", "image/jpeg", &jpeg)?;
+            }
+        }
+        "/pause" | "/resume" => {
+            let paused = path == "/pause";
+            shared.paused.store(paused, Ordering::SeqCst);
+            command(if paused {
+                StreamCommand::Pause
+            } else {
+                StreamCommand::Resume
+            });
+            let body: &[u8] = if paused { b"paused" } else { b"running" };
+            respond(&mut stream, "200 OK", "text/plain", body)?;
+        }
+        "/status" => {
+            let status = format!(
+                "{{\"paused\": {}, \"frames\": {}}}",
+                shared.paused.load(Ordering::SeqCst),
+                shared.frame.lock().unwrap().0
+            );
+            respond(&mut stream, "200 OK", "application/json", status.as_bytes())?;
+        }
+        _ => respond(&mut stream, "404 Not Found", "text/plain", b"not found")?,
+    }
+    let _ = stream.shutdown(Shutdown::Both);
+    Ok(())
+}
+
+// 新しい盤面が届くたびに送る。接続が切れるかサーバーが止まるまで続く
+fn stream_frames(stream: &mut TcpStream, shared: &Shared) -> Result<(), failure::Error> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: multipart/x-mixed-replace; boundary={}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        BOUNDARY
+    )?;
+    let mut sent = 0;
+    while !shared.stopped.load(Ordering::SeqCst) {
+        let (sequence, jpeg) = {
+            let frame = shared.frame.lock().unwrap();
+            let (frame, _) = shared
+                .updated
+                .wait_timeout_while(frame, WAIT_INTERVAL, |frame| frame.0 == sent)
+                .unwrap();
+            frame.clone()
+        };
+        if sequence == sent {
+            continue;
+        }
+        sent = sequence;
+        write!(
+            stream,
+            "--{}\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\n\r\n",
+            BOUNDARY,
+            jpeg.len()
+        )?;
+        stream.write_all(&jpeg)?;
+        stream.write_all(b"\r\n")?;
+    }
+    Ok(())
+}
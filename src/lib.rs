@@ -8,8 +8,19 @@
 //! ## モジュール化の方針
 //! パターンの生成ロジックを担当するalgorithmと描画を担当するvisualizerに分けて実装していく
 //!
+//! ## フィーチャー
+//! ウィンドウを開いて描画する部分は`gl`フィーチャー(デフォルトで有効)にまとめてある。
+//! `default-features = false`にすると、glutin/gliumやそのシステムのライブラリなしで、シミュレーションや解析、進化の計算だけを使える
+//!
+#[cfg(feature = "audio")]
+extern crate cpal;
+#[cfg(feature = "gl")]
 extern crate gl;
+#[cfg(feature = "gl")]
 extern crate glutin;
+#[cfg(feature = "http")]
+extern crate jpeg_encoder;
+#[cfg(feature = "gl")]
 #[macro_use]
 extern crate glium;
 #[macro_use(s)]
@@ -17,11 +28,64 @@ extern crate ndarray;
 extern crate ndarray_rand;
 extern crate num;
 extern crate num_traits;
+extern crate png;
+// pyo3のmacroが生成する`::core::...`を2015 editionでも解決できるようにする
+#[cfg(feature = "python")]
+extern crate core;
+#[cfg(feature = "python")]
+extern crate numpy;
+#[cfg(feature = "python")]
+extern crate pyo3;
 extern crate rand;
+#[cfg(feature = "scripting")]
+extern crate rhai;
+extern crate serde_json;
+#[cfg(feature = "tracing")]
+extern crate tracing;
 
+#[macro_use]
 extern crate failure;
 
+// `tracing`フィーチャーが有効なときだけ`tracing::info_span!`でspanに入り、そのguardを返す。
+// 無効なときは何もしないので、計測したい範囲で`let _span = trace_span!("name");`と書けばよい
+macro_rules! trace_span {
+    ($($args:tt)*) => {{
+        #[cfg(feature = "tracing")]
+        let span = ::tracing::info_span!($($args)*).entered();
+        #[cfg(not(feature = "tracing"))]
+        let span = ::NoSpan;
+        span
+    }};
+}
+
+// `tracing`フィーチャーが無効なときに`trace_span!`が返す、何もしないguard
+#[cfg(not(feature = "tracing"))]
+struct NoSpan;
+
 /// パターン生成のアルゴリズム
 pub mod algorithm;
+/// C ABIで他の言語から使うためのモジュール
+pub mod ffi;
+/// ブラウザでシミュレーションを見るためのHTTPサーバー
+#[cfg(feature = "http")]
+pub mod http;
+/// WebSocketで盤面を配信するためのモジュール
+pub mod net;
+/// OSCでシミュレーションと外部のツールをつなぐためのモジュール
+pub mod osc;
+/// よく使う型をまとめて`use my_alife::prelude::*;`で使えるようにしたもの
+pub mod prelude;
+/// 名前をつけたモデルの設定
+pub mod presets;
+/// Pythonから使うためのbinding
+#[cfg(feature = "python")]
+pub mod python;
+/// Rhaiで書いたスクリプトでモデルを動かすためのモジュール
+#[cfg(feature = "scripting")]
+pub mod scripting;
+/// シミュレーションの値を音にするためのモジュール
+pub mod sonification;
+/// 盤面の比較や基準の状態を使って回帰テストを書くための関数
+pub mod test_utils;
 /// 複数の描画方法をまとめたもの
 pub mod visualizer;
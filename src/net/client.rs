@@ -0,0 +1,152 @@
+use failure;
+use net::protocol::{parse_observables, ClientCommand, StateFrame};
+use net::websocket::{Message, WebSocket};
+use std::net::ToSocketAddrs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use visualizer::matrix_visualizer::MatrixVisualizer;
+use visualizer::ControlFlow;
+
+// 新しい盤面が届いていないときに待つ時間
+const IDLE_INTERVAL: Duration = Duration::from_millis(5);
+
+#[derive(Debug, Default)]
+struct Received {
+    frame: Mutex<Option<StateFrame>>,
+    observables: Mutex<(u64, Vec<(String, f32)>)>,
+    connected: AtomicBool,
+}
+
+/// `StateServer`につないで、届いた盤面を表示するクライアント
+///
+/// シミュレーションは別のマシンの`StateServer`で動かし、手元では描画とキー・マウスの操作だけを行う
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::net::client::RemoteView;
+/// use my_alife::net::protocol::ClientCommand;
+/// use my_alife::net::server::StateServer;
+///
+/// let server = StateServer::bind("127.0.0.1:0").unwrap();
+/// let mut remote = RemoteView::connect(server.local_addr()).unwrap();
+/// while server.client_count() == 0 {}
+///
+/// server.publish(5, &Array2::<f32>::eye(4));
+/// server.publish_observables(5, &[("population", 4.0)]);
+/// while remote.observables().0 != 5 {}
+/// assert_eq!(remote.take_frame().unwrap().cells, Array2::<f32>::eye(4));
+/// assert!(remote.take_frame().is_none());
+///
+/// remote.send(&ClientCommand::Key("Space".to_string())).unwrap();
+/// let mut commands = Vec::new();
+/// while commands.is_empty() {
+///     commands = server.poll_commands();
+/// }
+/// assert_eq!(commands, vec![ClientCommand::Key("Space".to_string())]);
+/// ```
+#[derive(Debug)]
+pub struct RemoteView {
+    socket: WebSocket,
+    received: Arc<Received>,
+    factor: usize,
+    mouse: Option<ClientCommand>,
+}
+
+impl RemoteView {
+    /// `address`の`StateServer`につなぐ
+    pub fn connect<A: ToSocketAddrs>(address: A) -> Result<RemoteView, failure::Error> {
+        let socket = WebSocket::connect(address, "/")?;
+        let mut reader = socket.try_clone()?;
+        let received = Arc::new(Received::default());
+        received.connected.store(true, Ordering::SeqCst);
+        let receiving = received.clone();
+        thread::spawn(move || {
+            while let Ok(message) = reader.receive() {
+                match message {
+                    Message::Binary(bytes) => {
+                        if let Ok(frame) = StateFrame::decode(&bytes) {
+                            *receiving.frame.lock().unwrap() = Some(frame);
+                        }
+                    }
+                    Message::Text(text) => {
+                        if let Ok(observables) = parse_observables(&text) {
+                            *receiving.observables.lock().unwrap() = observables;
+                        }
+                    }
+                    Message::Close => break,
+                    _ => {}
+                }
+            }
+            receiving.connected.store(false, Ordering::SeqCst);
+        });
+        Ok(RemoteView {
+            socket,
+            received,
+            factor: 1,
+            mouse: None,
+        })
+    }
+
+    /// サーバーとつながっているかどうか
+    pub fn is_connected(&self) -> bool {
+        self.received.connected.load(Ordering::SeqCst)
+    }
+
+    /// 前に取り出してから届いた最新の盤面。届いていなければ`None`
+    pub fn take_frame(&self) -> Option<StateFrame> {
+        self.received.frame.lock().unwrap().take()
+    }
+
+    /// 最後に届いた観測量の(ステップ数, (名前, 値))
+    pub fn observables(&self) -> (u64, Vec<(String, f32)>) {
+        self.received.observables.lock().unwrap().clone()
+    }
+
+    /// サーバーに操作を送る
+    pub fn send(&mut self, command: &ClientCommand) -> Result<(), failure::Error> {
+        self.socket.send(&Message::Text(command.to_message()))
+    }
+
+    /// 最新の盤面を`matrix`に描画し、押されたキーとマウスの状態をサーバーに送る
+    ///
+    /// 盤面が届いていなければイベントの処理だけを行う。ウィンドウが閉じられるかサーバーとの接続が切れると`ControlFlow::Stop`を返す
+    pub fn render_frame(&mut self, matrix: &mut MatrixVisualizer) -> Result<ControlFlow, failure::Error> {
+        let flow = match self.take_frame() {
+            Some(frame) => {
+                self.factor = frame.factor.max(1);
+                matrix.set_value_range(frame.range.0, frame.range.1);
+                matrix.render_frame(&frame.cells)?
+            }
+            None => {
+                thread::sleep(IDLE_INTERVAL);
+                matrix.poll_events()
+            }
+        };
+        for key in matrix.pressed_keys().to_vec() {
+            self.send(&ClientCommand::Key(format!("{:?}", key)))?;
+        }
+        // マウスは状態が変わったときだけ送る。セルはもとの盤面の位置に戻す
+        let mouse = matrix.mouse_cell().map(|(row, col)| ClientCommand::Mouse {
+            row: row * self.factor,
+            col: col * self.factor,
+            left: matrix.mouse().left,
+            right: matrix.mouse().right,
+        });
+        if mouse != self.mouse {
+            if let Some(ref command) = mouse {
+                self.send(command)?;
+            }
+            self.mouse = mouse;
+        }
+        if !self.is_connected() {
+            return Ok(ControlFlow::Stop);
+        }
+        Ok(flow)
+    }
+}
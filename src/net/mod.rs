@@ -0,0 +1,9 @@
+/// `StateServer`の盤面を表示するクライアント
+#[cfg(feature = "gl")]
+pub mod client;
+/// WebSocketで送る盤面と観測量の形式
+pub mod protocol;
+/// 盤面を複数のクライアントに配信するサーバー
+pub mod server;
+/// WebSocketの接続
+pub mod websocket;
@@ -0,0 +1,300 @@
+use failure;
+use ndarray::Array2;
+use serde_json::{self, Map, Value};
+use visualizer::Matrix;
+
+/// 盤面のバイナリメッセージの先頭の1byte
+pub const FRAME_MESSAGE: u8 = 1;
+/// 盤面のバイナリメッセージのヘッダーの大きさ
+pub const FRAME_HEADER_SIZE: usize = 24;
+
+/// 盤面の値の送り方
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// 最小値から最大値を0〜255に量子化する(1セル1byte)
+    U8,
+    /// そのまま送る(1セル4byte)
+    F32,
+}
+
+/// WebSocketで送る盤面
+///
+/// バイナリメッセージの形式は次のとおり。数はすべてリトルエンディアンで、値はヘッダーの直後から行優先で並ぶ
+///
+/// | 位置 | 型 | 内容 |
+/// |---|---|---|
+/// | 0 | u8 | 種類(`FRAME_MESSAGE` = 1) |
+/// | 1 | u8 | 値の形式(0: u8, 1: f32) |
+/// | 2 | u16 | 行数 |
+/// | 4 | u16 | 列数 |
+/// | 6 | u16 | 縮小率(もとの盤面の何セルを1セルにまとめたか) |
+/// | 8 | u64 | ステップ数 |
+/// | 16 | f32 | 最小値 |
+/// | 20 | f32 | 最大値 |
+/// | 24 | u8かf32 | 値。u8のときは`min + v / 255 * (max - min)`に戻す |
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::arr2;
+/// use my_alife::net::protocol::{Encoding, StateFrame};
+///
+/// let frame = StateFrame::new(42, 1, arr2(&[[0.0, 0.5], [1.0, 2.0]]));
+/// assert_eq!(frame.range, (0.0, 2.0));
+/// let bytes = frame.encode(Encoding::U8);
+/// assert_eq!(bytes.len(), 24 + 4);
+/// let decoded = StateFrame::decode(&bytes).unwrap();
+/// assert_eq!(decoded.step, 42);
+/// assert!((decoded.cells[[0, 1]] - 0.5).abs() < 0.01);
+/// assert_eq!(StateFrame::decode(&frame.encode(Encoding::F32)).unwrap(), frame);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateFrame {
+    /// ステップ数
+    pub step: u64,
+    /// 縮小率
+    pub factor: usize,
+    /// (最小値, 最大値)
+    pub range: (f32, f32),
+    /// 縮小した盤面
+    pub cells: Matrix<f32>,
+}
+
+impl StateFrame {
+    /// 有限の値の範囲を`range`にする
+    pub fn new(step: u64, factor: usize, cells: Matrix<f32>) -> StateFrame {
+        let range = cells
+            .iter()
+            .filter(|x| x.is_finite())
+            .fold(None, |range: Option<(f32, f32)>, &x| {
+                Some(range.map_or((x, x), |(min, max)| (min.min(x), max.max(x))))
+            })
+            .unwrap_or((0.0, 0.0));
+        StateFrame {
+            step,
+            factor,
+            range,
+            cells,
+        }
+    }
+
+    /// バイナリメッセージにする
+    ///
+    /// # Panics
+    /// 行数・列数・縮小率が65535を超えるとき
+    pub fn encode(&self, encoding: Encoding) -> Vec<u8> {
+        let (rows, cols) = self.cells.dim();
+        assert!(
+            rows <= u16::MAX as usize && cols <= u16::MAX as usize && self.factor <= u16::MAX as usize,
+            "frame is too large"
+        );
+        let (min, max) = self.range;
+        let mut bytes = Vec::with_capacity(FRAME_HEADER_SIZE + self.cells.len() * 4);
+        bytes.push(FRAME_MESSAGE);
+        bytes.push(match encoding {
+            Encoding::U8 => 0,
+            Encoding::F32 => 1,
+        });
+        bytes.extend_from_slice(&(rows as u16).to_le_bytes());
+        bytes.extend_from_slice(&(cols as u16).to_le_bytes());
+        bytes.extend_from_slice(&(self.factor as u16).to_le_bytes());
+        bytes.extend_from_slice(&self.step.to_le_bytes());
+        bytes.extend_from_slice(&min.to_le_bytes());
+        bytes.extend_from_slice(&max.to_le_bytes());
+        match encoding {
+            Encoding::U8 => {
+                let scale = if max > min { 255.0 / (max - min) } else { 0.0 };
+                bytes.extend(self.cells.iter().map(|&x| {
+                    let x = if x.is_finite() { x } else { min };
+                    ((x - min) * scale).round().clamp(0.0, 255.0) as u8
+                }))
+            }
+            Encoding::F32 => {
+                for x in self.cells.iter() {
+                    bytes.extend_from_slice(&x.to_le_bytes());
+                }
+            }
+        }
+        bytes
+    }
+
+    /// `encode`の逆
+    pub fn decode(bytes: &[u8]) -> Result<StateFrame, failure::Error> {
+        if bytes.len() < FRAME_HEADER_SIZE || bytes[0] != FRAME_MESSAGE {
+            return Err(format_err!("not a frame message"));
+        }
+        let u16_at = |i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]) as usize;
+        let f32_at = |i: usize| f32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        let (rows, cols, factor) = (u16_at(2), u16_at(4), u16_at(6));
+        let mut step = [0; 8];
+        step.copy_from_slice(&bytes[8..16]);
+        let (min, max) = (f32_at(16), f32_at(20));
+        let size = match bytes[1] {
+            0 => 1,
+            1 => 4,
+            encoding => return Err(format_err!("unknown encoding {}", encoding)),
+        };
+        if bytes.len() != FRAME_HEADER_SIZE + rows * cols * size {
+            return Err(format_err!(
+                "frame has {} bytes but {}x{} cells",
+                bytes.len(),
+                rows,
+                cols
+            ));
+        }
+        let cells = Array2::from_shape_fn((rows, cols), |(row, col)| {
+            let i = FRAME_HEADER_SIZE + (row * cols + col) * size;
+            if size == 1 {
+                min + bytes[i] as f32 / 255.0 * (max - min)
+            } else {
+                f32_at(i)
+            }
+        });
+        Ok(StateFrame {
+            step: u64::from_le_bytes(step),
+            factor,
+            range: (min, max),
+            cells,
+        })
+    }
+}
+
+/// 観測量のテキストメッセージ`{"type": "observables", "step": n, "values": {"name": value, ...}}`
+///
+/// # Example
+/// ```
+/// use my_alife::net::protocol::{observables_message, parse_observables};
+///
+/// let message = observables_message(7, &[("population", 42.0)]);
+/// assert_eq!(parse_observables(&message).unwrap(), (7, vec![("population".to_string(), 42.0)]));
+/// ```
+pub fn observables_message(step: u64, values: &[(&str, f32)]) -> String {
+    let mut map = Map::new();
+    for &(name, value) in values {
+        map.insert(name.to_string(), Value::from(value as f64));
+    }
+    let mut message = Map::new();
+    message.insert("type".to_string(), Value::from("observables"));
+    message.insert("step".to_string(), Value::from(step));
+    message.insert("values".to_string(), Value::Object(map));
+    Value::Object(message).to_string()
+}
+
+/// `observables_message`の逆
+pub fn parse_observables(message: &str) -> Result<(u64, Vec<(String, f32)>), failure::Error> {
+    let value: Value = serde_json::from_str(message)?;
+    if value.get("type").and_then(Value::as_str) != Some("observables") {
+        return Err(format_err!("not an observables message"));
+    }
+    let step = value
+        .get("step")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| format_err!("missing step"))?;
+    let values = value
+        .get("values")
+        .and_then(Value::as_object)
+        .ok_or_else(|| format_err!("missing values"))?
+        .iter()
+        .filter_map(|(name, value)| value.as_f64().map(|value| (name.clone(), value as f32)))
+        .collect();
+    Ok((step, values))
+}
+
+/// クライアントからサーバーへの操作のテキストメッセージ
+///
+/// # Example
+/// ```
+/// use my_alife::net::protocol::ClientCommand;
+///
+/// let command = ClientCommand::Mouse { row: 10, col: 20, left: true, right: false };
+/// assert_eq!(ClientCommand::parse(&command.to_message()).unwrap(), command);
+/// let command = ClientCommand::parse(r#"{"type": "parameter", "name": "f", "value": 0.04}"#).unwrap();
+/// assert_eq!(command, ClientCommand::SetParameter("f".to_string(), 0.04));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClientCommand {
+    /// `{"type": "key", "key": "Space"}`: キーが押された。名前は`VirtualKeyCode`と同じ
+    Key(String),
+    /// `{"type": "mouse", "row": r, "col": c, "left": true, "right": false}`:
+    /// マウスがもとの(縮小する前の)盤面のセル(r, c)を指している
+    Mouse {
+        /// 行
+        row: usize,
+        /// 列
+        col: usize,
+        /// 左ボタンが押されているか
+        left: bool,
+        /// 右ボタンが押されているか
+        right: bool,
+    },
+    /// `{"type": "parameter", "name": "f", "value": 0.04}`: パラメータを変える
+    SetParameter(String, f32),
+}
+
+impl ClientCommand {
+    /// テキストメッセージにする
+    pub fn to_message(&self) -> String {
+        let mut message = Map::new();
+        let mut insert = |key: &str, value: Value| {
+            message.insert(key.to_string(), value);
+        };
+        match *self {
+            ClientCommand::Key(ref key) => {
+                insert("type", Value::from("key"));
+                insert("key", Value::from(key.clone()));
+            }
+            ClientCommand::Mouse { row, col, left, right } => {
+                insert("type", Value::from("mouse"));
+                insert("row", Value::from(row));
+                insert("col", Value::from(col));
+                insert("left", Value::from(left));
+                insert("right", Value::from(right));
+            }
+            ClientCommand::SetParameter(ref name, value) => {
+                insert("type", Value::from("parameter"));
+                insert("name", Value::from(name.clone()));
+                insert("value", Value::from(value as f64));
+            }
+        }
+        Value::Object(message).to_string()
+    }
+
+    /// `to_message`の逆
+    pub fn parse(message: &str) -> Result<ClientCommand, failure::Error> {
+        let value: Value = serde_json::from_str(message)?;
+        let text = |key: &str| {
+            value
+                .get(key)
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| format_err!("missing {}", key))
+        };
+        let index = |key: &str| {
+            value
+                .get(key)
+                .and_then(Value::as_u64)
+                .map(|x| x as usize)
+                .ok_or_else(|| format_err!("missing {}", key))
+        };
+        let flag = |key: &str| value.get(key).and_then(Value::as_bool).unwrap_or(false);
+        match text("type")?.as_str() {
+            "key" => Ok(ClientCommand::Key(text("key")?)),
+            "mouse" => Ok(ClientCommand::Mouse {
+                row: index("row")?,
+                col: index("col")?,
+                left: flag("left"),
+                right: flag("right"),
+            }),
+            "parameter" => Ok(ClientCommand::SetParameter(
+                text("name")?,
+                value
+                    .get("value")
+                    .and_then(Value::as_f64)
+                    .ok_or_else(|| format_err!("missing value"))? as f32,
+            )),
+            other => Err(format_err!("unknown command {}", other)),
+        }
+    }
+}
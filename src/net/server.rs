@@ -0,0 +1,209 @@
+use algorithm::coarse_grain::{factor_to_fit, Downsampling};
+use failure;
+use net::protocol::{observables_message, ClientCommand, Encoding, StateFrame};
+use net::websocket::{Message, WebSocket};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use visualizer::Matrix;
+
+// 送りきれていないメッセージの数の上限。遅いクライアントにはこれを超えた分を送らない
+const CLIENT_QUEUE_SIZE: usize = 4;
+
+// (接続した順の番号, 送るメッセージ)
+type Clients = Arc<Mutex<Vec<(usize, SyncSender<Arc<Message>>)>>>;
+type Commands = Arc<Mutex<Vec<ClientCommand>>>;
+
+/// 1つのワールドの状態を、WebSocketで複数のクライアントに配信するサーバー
+///
+/// 盤面は縦横`max_size`以下に縮小して`StateFrame`のバイナリメッセージで、
+/// 観測量は`observables_message`のテキストメッセージで送る。
+/// 送るのはクライアントごとのスレッドなので、`publish`は遅いクライアントを待たない。
+/// クライアントから届いた`ClientCommand`は`poll_commands`で取り出す。
+/// 落とすとすべてのクライアントにCloseを送って待ち受けをやめる
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::net::protocol::{parse_observables, StateFrame};
+/// use my_alife::net::server::StateServer;
+/// use my_alife::net::websocket::{Message, WebSocket};
+///
+/// let mut server = StateServer::bind("127.0.0.1:0").unwrap();
+/// server.set_max_size(64);
+/// let mut client = WebSocket::connect(server.local_addr(), "/").unwrap();
+/// while server.client_count() == 0 {}
+///
+/// server.publish(3, &Array2::<f32>::ones((256, 256)));
+/// server.publish_observables(3, &[("mean", 1.0)]);
+/// match client.receive().unwrap() {
+///     Message::Binary(bytes) => {
+///         let frame = StateFrame::decode(&bytes).unwrap();
+///         assert_eq!((frame.step, frame.factor, frame.cells.dim()), (3, 4, (64, 64)));
+///     }
+///     message => panic!("unexpected {:?}", message),
+/// }
+/// match client.receive().unwrap() {
+///     Message::Text(text) => assert_eq!(parse_observables(&text).unwrap().1[0].1, 1.0),
+///     message => panic!("unexpected {:?}", message),
+/// }
+///
+/// let mut other = WebSocket::connect(server.local_addr(), "/").unwrap();
+/// while server.client_count() < 2 {}
+/// client.close().unwrap();
+/// while server.client_count() > 1 {}
+/// drop(server);
+/// assert_eq!(other.receive().unwrap(), Message::Close);
+/// ```
+#[derive(Debug)]
+pub struct StateServer {
+    address: SocketAddr,
+    clients: Clients,
+    commands: Commands,
+    max_size: usize,
+    downsampling: Downsampling,
+    encoding: Encoding,
+    stopped: Arc<AtomicBool>,
+}
+
+impl StateServer {
+    /// `address`で待ち受ける。縦横256セル以下に平均で縮小し、u8で送る
+    pub fn bind<A: ToSocketAddrs>(address: A) -> Result<StateServer, failure::Error> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let clients: Clients = Arc::new(Mutex::new(Vec::new()));
+        let commands: Commands = Arc::new(Mutex::new(Vec::new()));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let (accepting, received, stopping) = (clients.clone(), commands.clone(), stopped.clone());
+        thread::spawn(move || {
+            for (id, stream) in listener.incoming().enumerate() {
+                if stopping.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    let (clients, commands) = (accepting.clone(), received.clone());
+                    thread::spawn(move || {
+                        let _ = serve(stream, id, clients, commands);
+                    });
+                }
+            }
+        });
+        Ok(StateServer {
+            address,
+            clients,
+            commands,
+            max_size: 256,
+            downsampling: Downsampling::BlockAverage,
+            encoding: Encoding::U8,
+            stopped,
+        })
+    }
+
+    /// 待ち受けているアドレス
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// 送る盤面の縦横の最大のセル数
+    pub fn set_max_size(&mut self, max_size: usize) {
+        self.max_size = max_size.max(1);
+    }
+
+    /// 盤面を縮小する方法
+    pub fn set_downsampling(&mut self, downsampling: Downsampling) {
+        self.downsampling = downsampling;
+    }
+
+    /// 値の送り方
+    pub fn set_encoding(&mut self, encoding: Encoding) {
+        self.encoding = encoding;
+    }
+
+    /// つながっているクライアントの数
+    pub fn client_count(&self) -> usize {
+        self.clients.lock().unwrap().len()
+    }
+
+    /// クライアントから届いた操作をすべて取り出す
+    pub fn poll_commands(&self) -> Vec<ClientCommand> {
+        self.commands.lock().unwrap().drain(..).collect()
+    }
+
+    /// `step`ステップ目の盤面を送る
+    pub fn publish<A>(&self, step: u64, matrix: &Matrix<A>)
+    where
+        A: Copy + Into<f32>,
+    {
+        if self.client_count() == 0 {
+            return;
+        }
+        let matrix = matrix.mapv(|x| x.into());
+        let factor = factor_to_fit(matrix.dim(), self.max_size);
+        let cells = if factor > 1 {
+            self.downsampling.apply(&matrix, factor)
+        } else {
+            matrix
+        };
+        let frame = StateFrame::new(step, factor, cells);
+        self.broadcast(Message::Binary(frame.encode(self.encoding)));
+    }
+
+    /// `step`ステップ目の観測量を送る
+    pub fn publish_observables(&self, step: u64, values: &[(&str, f32)]) {
+        self.broadcast(Message::Text(observables_message(step, values)));
+    }
+
+    /// メッセージをすべてのクライアントに送る
+    pub fn broadcast(&self, message: Message) {
+        let message = Arc::new(message);
+        self.clients
+            .lock()
+            .unwrap()
+            .retain(|(_, client)| match client.try_send(message.clone()) {
+                Ok(()) | Err(TrySendError::Full(_)) => true,
+                Err(TrySendError::Disconnected(_)) => false,
+            });
+    }
+}
+
+impl Drop for StateServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // 送る側のスレッドを止める。止まったスレッドがそれぞれの接続を閉じる
+        self.clients.lock().unwrap().clear();
+        // acceptで待っているスレッドを起こす
+        let _ = TcpStream::connect(self.address);
+    }
+}
+
+fn serve(stream: TcpStream, id: usize, clients: Clients, commands: Commands) -> Result<(), failure::Error> {
+    let (mut socket, _) = WebSocket::accept(stream)?;
+    let mut reader = socket.try_clone()?;
+    let (sender, receiver) = sync_channel::<Arc<Message>>(CLIENT_QUEUE_SIZE);
+    clients.lock().unwrap().push((id, sender));
+    // 相手からのメッセージを読む。切れたら一覧から外して送る側も止める
+    thread::spawn(move || {
+        while let Ok(message) = reader.receive() {
+            match message {
+                Message::Close => break,
+                Message::Text(text) => {
+                    if let Ok(command) = ClientCommand::parse(&text) {
+                        commands.lock().unwrap().push(command);
+                    }
+                }
+                _ => {}
+            }
+        }
+        clients.lock().unwrap().retain(|&(client, _)| client != id);
+        let _ = reader.close();
+    });
+    let sent = receiver.iter().try_for_each(|message| socket.send(&message));
+    // 送れなくなったら読む側も止める
+    let _ = socket.close();
+    sent
+}
@@ -0,0 +1,342 @@
+use failure;
+use rand::{self, Rng};
+use std::io::{Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpStream, ToSocketAddrs};
+use visualizer::image::base64;
+
+// RFC 6455でSec-WebSocket-Keyに付け足す決まった文字列
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+// ハンドシェイクのヘッダーとして読む最大の大きさ
+const MAX_HEADER_SIZE: usize = 8192;
+// 受け取るメッセージの最大の大きさ
+const MAX_MESSAGE_SIZE: u64 = 1 << 28;
+
+/// WebSocketのメッセージ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    /// UTF-8の文字列
+    Text(String),
+    /// バイト列
+    Binary(Vec<u8>),
+    /// 生存確認。受け取ったときは自動で同じ中身の`Pong`を返す
+    Ping(Vec<u8>),
+    /// `Ping`への返事
+    Pong(Vec<u8>),
+    /// 接続を閉じる
+    Close,
+}
+
+/// TCPの上のWebSocket(RFC 6455)の接続。拡張や分割されたメッセージの送信には対応していない
+///
+/// # Example
+/// ```
+/// use my_alife::net::websocket::{Message, WebSocket};
+/// use std::net::TcpListener;
+/// use std::thread;
+///
+/// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+/// let address = listener.local_addr().unwrap();
+/// let server = thread::spawn(move || {
+///     let (stream, _) = listener.accept().unwrap();
+///     let (mut socket, path) = WebSocket::accept(stream).unwrap();
+///     let message = socket.receive().unwrap();
+///     socket.send(&message).unwrap();
+///     path
+/// });
+///
+/// let mut client = WebSocket::connect(address, "/echo").unwrap();
+/// client.send(&Message::Text("hello".to_string())).unwrap();
+/// assert_eq!(client.receive().unwrap(), Message::Text("hello".to_string()));
+/// assert_eq!(server.join().unwrap(), "/echo");
+/// ```
+#[derive(Debug)]
+pub struct WebSocket {
+    stream: TcpStream,
+    // クライアントは送るフレームをマスクする
+    client: bool,
+}
+
+impl WebSocket {
+    /// サーバーとして`stream`のハンドシェイクに応じる。接続とリクエストのパスを返す
+    pub fn accept(mut stream: TcpStream) -> Result<(WebSocket, String), failure::Error> {
+        let request = read_header(&mut stream)?;
+        let path = request
+            .lines()
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .unwrap_or("/")
+            .to_string();
+        let key = header(&request, "sec-websocket-key").ok_or_else(|| format_err!("not a WebSocket request"))?;
+        write!(
+            stream,
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+            accept_key(&key)
+        )?;
+        Ok((WebSocket { stream, client: false }, path))
+    }
+
+    /// クライアントとして`address`の`path`に接続する
+    pub fn connect<A: ToSocketAddrs>(address: A, path: &str) -> Result<WebSocket, failure::Error> {
+        let mut stream = TcpStream::connect(address)?;
+        let host = stream.peer_addr()?;
+        let nonce: [u8; 16] = rand::thread_rng().gen();
+        let key = base64(&nonce);
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n\r\n",
+            path, host, key
+        )?;
+        let response = read_header(&mut stream)?;
+        if !response.starts_with("HTTP/1.1 101") {
+            return Err(format_err!(
+                "handshake failed: {}",
+                response.lines().next().unwrap_or("")
+            ));
+        }
+        if header(&response, "sec-websocket-accept") != Some(accept_key(&key)) {
+            return Err(format_err!("handshake failed: invalid Sec-WebSocket-Accept"));
+        }
+        Ok(WebSocket { stream, client: true })
+    }
+
+    /// 相手のアドレス
+    pub fn peer_addr(&self) -> Result<SocketAddr, failure::Error> {
+        Ok(self.stream.peer_addr()?)
+    }
+
+    /// 受け取るのと送るのを別のスレッドで行うための複製
+    pub fn try_clone(&self) -> Result<WebSocket, failure::Error> {
+        Ok(WebSocket {
+            stream: self.stream.try_clone()?,
+            client: self.client,
+        })
+    }
+
+    /// メッセージを1つのフレームで送る
+    pub fn send(&mut self, message: &Message) -> Result<(), failure::Error> {
+        let (opcode, payload): (u8, &[u8]) = match *message {
+            Message::Text(ref text) => (0x1, text.as_bytes()),
+            Message::Binary(ref data) => (0x2, data),
+            Message::Close => (0x8, &[]),
+            Message::Ping(ref data) => (0x9, data),
+            Message::Pong(ref data) => (0xa, data),
+        };
+        let mut frame = vec![0x80 | opcode];
+        let mask_bit = if self.client { 0x80 } else { 0 };
+        match payload.len() {
+            len if len < 126 => frame.push(mask_bit | len as u8),
+            len if len <= u16::MAX as usize => {
+                frame.push(mask_bit | 126);
+                frame.extend_from_slice(&(len as u16).to_be_bytes());
+            }
+            len => {
+                frame.push(mask_bit | 127);
+                frame.extend_from_slice(&(len as u64).to_be_bytes());
+            }
+        }
+        if self.client {
+            let mask: [u8; 4] = rand::thread_rng().gen();
+            frame.extend_from_slice(&mask);
+            frame.extend(payload.iter().enumerate().map(|(i, &b)| b ^ mask[i % 4]));
+        } else {
+            frame.extend_from_slice(payload);
+        }
+        self.stream.write_all(&frame)?;
+        Ok(())
+    }
+
+    /// メッセージを1つ受け取るまで待つ。Pingには自動でPongを返す
+    ///
+    /// 分割されたメッセージの途中に届いたPingとPongは返さずに、組み立てを続ける
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::net::websocket::{Message, WebSocket};
+    /// use std::io::{Read, Write};
+    /// use std::net::{TcpListener, TcpStream};
+    /// use std::thread;
+    ///
+    /// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    /// let address = listener.local_addr().unwrap();
+    /// let server = thread::spawn(move || {
+    ///     let (stream, _) = listener.accept().unwrap();
+    ///     let (mut socket, _) = WebSocket::accept(stream).unwrap();
+    ///     socket.receive().unwrap()
+    /// });
+    ///
+    /// let mut client = TcpStream::connect(address).unwrap();
+    /// client
+    ///     .write_all(b"GET / HTTP/1.1\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n")
+    ///     .unwrap();
+    /// let mut response = [0; 129];
+    /// client.read_exact(&mut response).unwrap();
+    /// // "hel"と"lo"の2つに分けたテキストの間にPingを挟む
+    /// client.write_all(&[0x01, 3, b'h', b'e', b'l', 0x89, 0, 0x80, 2, b'l', b'o']).unwrap();
+    /// let mut pong = [0; 2];
+    /// client.read_exact(&mut pong).unwrap();
+    /// assert_eq!(pong, [0x8a, 0]);
+    /// assert_eq!(server.join().unwrap(), Message::Text("hello".to_string()));
+    /// ```
+    pub fn receive(&mut self) -> Result<Message, failure::Error> {
+        let mut message = Vec::new();
+        let mut message_opcode = None;
+        loop {
+            let mut head = [0; 2];
+            self.stream.read_exact(&mut head)?;
+            let (fin, opcode) = (head[0] & 0x80 != 0, head[0] & 0x0f);
+            let masked = head[1] & 0x80 != 0;
+            let len = match head[1] & 0x7f {
+                126 => {
+                    let mut len = [0; 2];
+                    self.stream.read_exact(&mut len)?;
+                    u16::from_be_bytes(len) as u64
+                }
+                127 => {
+                    let mut len = [0; 8];
+                    self.stream.read_exact(&mut len)?;
+                    u64::from_be_bytes(len)
+                }
+                len => len as u64,
+            };
+            if len > MAX_MESSAGE_SIZE {
+                return Err(format_err!("message is too large ({} bytes)", len));
+            }
+            let mut mask = [0; 4];
+            if masked {
+                self.stream.read_exact(&mut mask)?;
+            }
+            let mut payload = vec![0; len as usize];
+            self.stream.read_exact(&mut payload)?;
+            if masked {
+                for (i, b) in payload.iter_mut().enumerate() {
+                    *b ^= mask[i % 4];
+                }
+            }
+            match opcode {
+                0x8 => return Ok(Message::Close),
+                0x9 => {
+                    self.send(&Message::Pong(payload.clone()))?;
+                    if message_opcode.is_none() {
+                        return Ok(Message::Ping(payload));
+                    }
+                }
+                0xa => {
+                    if message_opcode.is_none() {
+                        return Ok(Message::Pong(payload));
+                    }
+                }
+                0x0 if message_opcode.is_none() => {
+                    return Err(format_err!("continuation frame without a first frame"));
+                }
+                0x1 | 0x2 if message_opcode.is_some() => {
+                    return Err(format_err!("new message started before the previous one finished"));
+                }
+                0x0..=0x2 => {
+                    if opcode != 0 {
+                        message_opcode = Some(opcode);
+                    }
+                    message.extend_from_slice(&payload);
+                    if (message.len() as u64) > MAX_MESSAGE_SIZE {
+                        return Err(format_err!("message is too large"));
+                    }
+                    if fin {
+                        break;
+                    }
+                }
+                _ => return Err(format_err!("unknown opcode {}", opcode)),
+            }
+        }
+        if message_opcode == Some(0x1) {
+            Ok(Message::Text(String::from_utf8(message)?))
+        } else {
+            Ok(Message::Binary(message))
+        }
+    }
+
+    /// Closeを送って接続を閉じる。Closeを送れなくても接続は閉じる
+    pub fn close(&mut self) -> Result<(), failure::Error> {
+        let sent = self.send(&Message::Close);
+        self.stream.shutdown(Shutdown::Both)?;
+        sent
+    }
+}
+
+fn read_header(stream: &mut TcpStream) -> Result<String, failure::Error> {
+    // 本文を読みすぎないように1byteずつ読む
+    let mut header = Vec::new();
+    let mut byte = [0; 1];
+    while !header.ends_with(b"\r\n\r\n") {
+        if header.len() >= MAX_HEADER_SIZE {
+            return Err(format_err!("header is too large"));
+        }
+        if stream.read(&mut byte)? == 0 {
+            return Err(format_err!("connection closed during the handshake"));
+        }
+        header.push(byte[0]);
+    }
+    Ok(String::from_utf8_lossy(&header).into_owned())
+}
+
+fn header(request: &str, name: &str) -> Option<String> {
+    request.lines().skip(1).find_map(|line| {
+        let mut parts = line.splitn(2, ':');
+        let key = parts.next()?.trim();
+        if key.eq_ignore_ascii_case(name) {
+            parts.next().map(|value| value.trim().to_string())
+        } else {
+            None
+        }
+    })
+}
+
+fn accept_key(key: &str) -> String {
+    base64(&sha1(format!("{}{}", key, ACCEPT_GUID).as_bytes()))
+}
+
+// ハンドシェイクにだけ使うSHA-1
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476, 0xc3d2_e1f0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+    for block in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for i in 0..16 {
+            w[i] = u32::from_be_bytes([block[4 * i], block[4 * i + 1], block[4 * i + 2], block[4 * i + 3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let (mut a, mut b, mut c, mut d, mut e) = (h[0], h[1], h[2], h[3], h[4]);
+        for (i, &wi) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ed9_eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+                _ => (b ^ c ^ d, 0xca62_c1d6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(wi);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (h, x) in h.iter_mut().zip(&[a, b, c, d, e]) {
+            *h = h.wrapping_add(*x);
+        }
+    }
+    let mut digest = [0; 20];
+    for (i, word) in h.iter().enumerate() {
+        digest[4 * i..4 * i + 4].copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
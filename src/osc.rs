@@ -0,0 +1,426 @@
+use failure;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::str;
+
+// 1つのUDPパケットとして受け取る最大の大きさ
+const MAX_PACKET_SIZE: usize = 65536;
+
+/// OSCメッセージの引数
+#[derive(Debug, Clone, PartialEq)]
+pub enum OscArg {
+    /// 32bit整数(型タグ`i`)
+    Int(i32),
+    /// 32bit浮動小数点数(型タグ`f`)
+    Float(f32),
+    /// 文字列(型タグ`s`)
+    String(String),
+}
+
+/// OSC(Open Sound Control)のメッセージ
+///
+/// # Example
+/// ```
+/// use my_alife::osc::{OscArg, OscMessage};
+///
+/// let message = OscMessage::new("/alife/population").arg(OscArg::Int(42));
+/// let packet = message.encode();
+/// // アドレス・型タグ・引数はそれぞれ4byte単位に揃えられる
+/// assert_eq!(&packet[..20], b"/alife/population\0\0\0");
+/// assert_eq!(&packet[20..24], b",i\0\0");
+/// assert_eq!(&packet[24..], &[0, 0, 0, 42]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct OscMessage {
+    /// `/`で始まるアドレス
+    pub address: String,
+    /// 引数
+    pub args: Vec<OscArg>,
+}
+
+impl OscMessage {
+    /// 引数のないメッセージを生成する
+    pub fn new(address: &str) -> OscMessage {
+        OscMessage {
+            address: address.to_string(),
+            args: Vec::new(),
+        }
+    }
+
+    /// 引数を追加する
+    pub fn arg(mut self, arg: OscArg) -> OscMessage {
+        self.args.push(arg);
+        self
+    }
+
+    /// UDPで送るためのバイト列に変換する
+    pub fn encode(&self) -> Vec<u8> {
+        let mut packet = Vec::new();
+        write_string(&mut packet, &self.address);
+        let tags: String = Some(',')
+            .into_iter()
+            .chain(self.args.iter().map(|arg| match *arg {
+                OscArg::Int(_) => 'i',
+                OscArg::Float(_) => 'f',
+                OscArg::String(_) => 's',
+            }))
+            .collect();
+        write_string(&mut packet, &tags);
+        for arg in &self.args {
+            match *arg {
+                OscArg::Int(value) => packet.extend_from_slice(&value.to_be_bytes()),
+                OscArg::Float(value) => packet.extend_from_slice(&value.to_bits().to_be_bytes()),
+                OscArg::String(ref value) => write_string(&mut packet, value),
+            }
+        }
+        packet
+    }
+
+    /// 受け取ったパケットをメッセージに変換する。bundleの場合は中身のメッセージをすべて取り出す
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::osc::{OscArg, OscMessage};
+    ///
+    /// let message = OscMessage::new("/alife/inject")
+    ///     .arg(OscArg::Int(10))
+    ///     .arg(OscArg::Float(0.5))
+    ///     .arg(OscArg::String("v".to_string()));
+    /// assert_eq!(OscMessage::decode(&message.encode()).unwrap(), vec![message]);
+    /// assert!(OscMessage::decode(b"not osc").is_err());
+    ///
+    /// // 要素の大きさが負やパケットより大きいbundleはエラーになる
+    /// let mut bundle = b"#bundle\0\0\0\0\0\0\0\0\x01".to_vec();
+    /// bundle.extend_from_slice(&(-4i32).to_be_bytes());
+    /// assert!(OscMessage::decode(&bundle).is_err());
+    /// bundle.truncate(16);
+    /// bundle.extend_from_slice(&1000i32.to_be_bytes());
+    /// assert!(OscMessage::decode(&bundle).is_err());
+    /// ```
+    pub fn decode(packet: &[u8]) -> Result<Vec<OscMessage>, failure::Error> {
+        let mut messages = Vec::new();
+        decode_packet(packet, &mut messages)?;
+        Ok(messages)
+    }
+}
+
+fn decode_packet(packet: &[u8], messages: &mut Vec<OscMessage>) -> Result<(), failure::Error> {
+    let mut reader = Reader { packet, position: 0 };
+    if packet.starts_with(b"#bundle\0") {
+        // "#bundle"と時刻(8byte)の後に、大きさ付きの要素が並ぶ
+        reader.position = 16;
+        while reader.position < packet.len() {
+            let len = reader.int()?;
+            if len < 0 {
+                return Err(format_err!("invalid OSC bundle element size: {}", len));
+            }
+            let element = reader.bytes(len as usize)?;
+            decode_packet(element, messages)?;
+        }
+        return Ok(());
+    }
+    let address = reader.string()?;
+    if !address.starts_with('/') {
+        return Err(format_err!("invalid OSC address: {}", address));
+    }
+    let mut message = OscMessage::new(&address);
+    if reader.position < packet.len() {
+        let tags = reader.string()?;
+        if !tags.starts_with(',') {
+            return Err(format_err!("invalid OSC type tags: {}", tags));
+        }
+        for tag in tags.chars().skip(1) {
+            let arg = match tag {
+                'i' => OscArg::Int(reader.int()?),
+                'f' => OscArg::Float(f32::from_bits(reader.int()? as u32)),
+                's' => OscArg::String(reader.string()?),
+                _ => return Err(format_err!("unsupported OSC type tag: {}", tag)),
+            };
+            message.args.push(arg);
+        }
+    }
+    messages.push(message);
+    Ok(())
+}
+
+struct Reader<'a> {
+    packet: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], failure::Error> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|&end| end <= self.packet.len())
+            .ok_or_else(|| format_err!("truncated OSC packet"))?;
+        let bytes = &self.packet[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn int(&mut self) -> Result<i32, failure::Error> {
+        let bytes = self.bytes(4)?;
+        Ok(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn string(&mut self) -> Result<String, failure::Error> {
+        let rest = &self.packet[self.position.min(self.packet.len())..];
+        let len = rest
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or_else(|| format_err!("OSC string is not terminated"))?;
+        let value = str::from_utf8(&rest[..len])?.to_string();
+        self.bytes(len + 4 - len % 4)?;
+        Ok(value)
+    }
+}
+
+// OSCの文字列はnull終端して4byte単位に揃える
+fn write_string(packet: &mut Vec<u8>, value: &str) {
+    packet.extend_from_slice(value.as_bytes());
+    let padding = 4 - value.len() % 4;
+    packet.extend(std::iter::repeat_n(0, padding));
+}
+
+/// 外部に知らせるシミュレーションの出来事
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimulationEvent {
+    /// そのステップで生まれたセルの数
+    Births(u64),
+    /// そのステップで死んだセルの数
+    Deaths(u64),
+    /// 生きているセルの数
+    Population(u64),
+    /// 雪崩(連鎖的な変化)の大きさ
+    Avalanche(u64),
+    /// 名前をつけた任意の観測値
+    Observable(String, f32),
+}
+
+impl SimulationEvent {
+    // アドレスのprefixより後ろの部分
+    fn name(&self) -> &str {
+        match *self {
+            SimulationEvent::Births(_) => "births",
+            SimulationEvent::Deaths(_) => "deaths",
+            SimulationEvent::Population(_) => "population",
+            SimulationEvent::Avalanche(_) => "avalanche",
+            SimulationEvent::Observable(ref name, _) => name,
+        }
+    }
+
+    fn value(&self) -> OscArg {
+        match *self {
+            SimulationEvent::Births(n)
+            | SimulationEvent::Deaths(n)
+            | SimulationEvent::Population(n)
+            | SimulationEvent::Avalanche(n) => OscArg::Int(n.min(i32::MAX as u64) as i32),
+            SimulationEvent::Observable(_, value) => OscArg::Float(value),
+        }
+    }
+}
+
+/// シミュレーションの出来事をOSCメッセージとしてUDPで送る
+///
+/// SuperColliderやTouchDesigner、Max/MSPなどで受け取って、シミュレーションに反応させられる
+///
+/// # Example
+/// ```
+/// use std::net::UdpSocket;
+/// use my_alife::osc::{OscSender, SimulationEvent};
+///
+/// let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+/// let mut sender = OscSender::new(receiver.local_addr().unwrap()).unwrap();
+/// sender.set_prefix("/life");
+/// sender.send_event(&SimulationEvent::Population(42)).unwrap();
+///
+/// let mut buffer = [0; 64];
+/// let len = receiver.recv(&mut buffer).unwrap();
+/// assert!(buffer[..len].starts_with(b"/life/population\0"));
+/// ```
+#[derive(Debug)]
+pub struct OscSender {
+    socket: UdpSocket,
+    target: SocketAddr,
+    prefix: String,
+    muted: Vec<String>,
+}
+
+impl OscSender {
+    /// `target`にメッセージを送るOscSenderを生成する。アドレスのprefixは`/alife`
+    pub fn new<A: ToSocketAddrs>(target: A) -> Result<OscSender, failure::Error> {
+        let target = target
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| format_err!("no address to send OSC messages"))?;
+        let local = if target.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+        Ok(OscSender {
+            socket: UdpSocket::bind(local)?,
+            target,
+            prefix: "/alife".to_string(),
+            muted: Vec::new(),
+        })
+    }
+
+    /// 出来事を送るときのアドレスのprefixを変更する
+    pub fn set_prefix(&mut self, prefix: &str) {
+        self.prefix = prefix.trim_end_matches('/').to_string();
+    }
+
+    /// `name`(`births`や`population`、観測値の名前)の出来事を送るかどうかを変更する
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) {
+        self.muted.retain(|muted| muted != name);
+        if !enabled {
+            self.muted.push(name.to_string());
+        }
+    }
+
+    /// メッセージをそのまま送る
+    pub fn send(&self, message: &OscMessage) -> Result<(), failure::Error> {
+        self.socket.send_to(&message.encode(), self.target)?;
+        Ok(())
+    }
+
+    /// 出来事を`<prefix>/<name>`のアドレスで送る。送らない設定の出来事は無視する
+    pub fn send_event(&self, event: &SimulationEvent) -> Result<(), failure::Error> {
+        if self.muted.iter().any(|muted| muted == event.name()) {
+            return Ok(());
+        }
+        let address = format!("{}/{}", self.prefix, event.name());
+        self.send(&OscMessage::new(&address).arg(event.value()))
+    }
+}
+
+/// 外部から受け取ったシミュレーションへの操作
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlEvent {
+    /// `<prefix>/param/<name> value`: パラメータ`name`を`value`にする
+    SetParameter(String, f32),
+    /// `<prefix>/inject x y [value]`: セル(x, y)に`value`(省略すると1.0)を注入する
+    Inject {
+        /// 列
+        x: usize,
+        /// 行
+        y: usize,
+        /// 注入する値
+        value: f32,
+    },
+}
+
+impl ControlEvent {
+    /// メッセージを操作に変換する。知らないアドレスや引数の足りないメッセージは`None`
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::osc::{ControlEvent, OscArg, OscMessage};
+    ///
+    /// let message = OscMessage::new("/alife/param/f").arg(OscArg::Float(0.035));
+    /// assert_eq!(
+    ///     ControlEvent::from_message(&message, "/alife"),
+    ///     Some(ControlEvent::SetParameter("f".to_string(), 0.035))
+    /// );
+    /// let message = OscMessage::new("/alife/inject").arg(OscArg::Int(3)).arg(OscArg::Float(4.0));
+    /// assert_eq!(
+    ///     ControlEvent::from_message(&message, "/alife"),
+    ///     Some(ControlEvent::Inject { x: 3, y: 4, value: 1.0 })
+    /// );
+    /// ```
+    pub fn from_message(message: &OscMessage, prefix: &str) -> Option<ControlEvent> {
+        let path = message.address.strip_prefix(prefix.trim_end_matches('/'))?;
+        let numbers: Vec<f32> = message
+            .args
+            .iter()
+            .filter_map(|arg| match *arg {
+                OscArg::Int(value) => Some(value as f32),
+                OscArg::Float(value) => Some(value),
+                OscArg::String(_) => None,
+            })
+            .collect();
+        if let Some(name) = path.strip_prefix("/param/") {
+            return numbers.first().map(|&value| ControlEvent::SetParameter(name.to_string(), value));
+        }
+        match (path, numbers.as_slice()) {
+            ("/inject", &[x, y, ref rest @ ..]) if x >= 0.0 && y >= 0.0 => Some(ControlEvent::Inject {
+                x: x as usize,
+                y: y as usize,
+                value: rest.first().cloned().unwrap_or(1.0),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// UDPでOSCメッセージを受け取って、シミュレーションへの操作に変換する
+///
+/// ブロックしないので、描画ループの中で毎フレーム`poll_events`を呼べば良い
+///
+/// # Example
+/// ```
+/// use my_alife::osc::{ControlEvent, OscArg, OscMessage, OscReceiver, OscSender};
+///
+/// let receiver = OscReceiver::bind("127.0.0.1:0").unwrap();
+/// let sender = OscSender::new(receiver.local_addr().unwrap()).unwrap();
+/// sender.send(&OscMessage::new("/alife/param/k").arg(OscArg::Float(0.06))).unwrap();
+///
+/// // UDPなので届くまで待つ
+/// let mut events = Vec::new();
+/// while events.is_empty() {
+///     events = receiver.poll_events();
+/// }
+/// assert_eq!(events, vec![ControlEvent::SetParameter("k".to_string(), 0.06)]);
+/// ```
+#[derive(Debug)]
+pub struct OscReceiver {
+    socket: UdpSocket,
+    prefix: String,
+}
+
+impl OscReceiver {
+    /// `address`で待ち受けるOscReceiverを生成する。アドレスのprefixは`/alife`
+    pub fn bind<A: ToSocketAddrs>(address: A) -> Result<OscReceiver, failure::Error> {
+        let socket = UdpSocket::bind(address)?;
+        socket.set_nonblocking(true)?;
+        Ok(OscReceiver {
+            socket,
+            prefix: "/alife".to_string(),
+        })
+    }
+
+    /// 待ち受けているアドレス
+    pub fn local_addr(&self) -> Result<SocketAddr, failure::Error> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    /// 受け取るメッセージのアドレスのprefixを変更する
+    pub fn set_prefix(&mut self, prefix: &str) {
+        self.prefix = prefix.trim_end_matches('/').to_string();
+    }
+
+    /// 届いているメッセージをすべて取り出す。壊れたパケットは無視する
+    pub fn poll(&self) -> Vec<OscMessage> {
+        let mut buffer = vec![0; MAX_PACKET_SIZE];
+        let mut messages = Vec::new();
+        loop {
+            match self.socket.recv(&mut buffer) {
+                Ok(len) => {
+                    if let Ok(decoded) = OscMessage::decode(&buffer[..len]) {
+                        messages.extend(decoded);
+                    }
+                }
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(_) => break,
+            }
+        }
+        messages
+    }
+
+    /// 届いているメッセージのうち、操作として解釈できるものを取り出す
+    pub fn poll_events(&self) -> Vec<ControlEvent> {
+        self.poll()
+            .iter()
+            .filter_map(|message| ControlEvent::from_message(message, &self.prefix))
+            .collect()
+    }
+}
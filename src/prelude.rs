@@ -0,0 +1,41 @@
+//! よく使う型をまとめたもの。`use my_alife::prelude::*;`の1行で使えるようになる
+//!
+//! 盤面の型、描画、色、境界条件、盤面を進めるための`Stepper`と、よく使うモデルを含む
+//!
+//! # Example
+//! ```
+//! use my_alife::prelude::*;
+//!
+//! let mut stepper = CpuGrayScott::auto(&gray_scott::initial_matrix(), GrayScott::new(0.04, 0.06)).unwrap();
+//! let mut visualizer = MockVisualizer::new(3);
+//! visualizer.set_title("Gray Scott");
+//! loop {
+//!     stepper.step(8).unwrap();
+//!     let u: &Matrix<f32> = stepper.snapshot().unwrap();
+//!     if visualizer.render_frame(u).unwrap() == ControlFlow::Stop {
+//!         break;
+//!     }
+//! }
+//! assert_eq!(visualizer.frames(), 3);
+//! ```
+pub use algorithm::backend::{Backend, CpuGrayScott, CpuLife, Stepper};
+pub use algorithm::brusselator::Brusselator;
+pub use algorithm::game_of_life;
+pub use algorithm::gray_scott::{self, GrayScott};
+pub use algorithm::integrator::Integrator;
+pub use algorithm::larger_than_life::LtlRule;
+pub use algorithm::lenia::Lenia;
+pub use algorithm::life_like::LifeRule;
+pub use algorithm::neighborhood::Neighborhood;
+pub use algorithm::oregonator::Oregonator;
+pub use algorithm::reaction_diffusion::ReactionDiffusion;
+pub use algorithm::region::Boundary;
+pub use algorithm::stochastic_ca::StochasticCa;
+pub use visualizer::colormap::{Colormap, Transform};
+#[cfg(feature = "gl")]
+pub use visualizer::matrix_visualizer::MatrixVisualizer;
+pub use visualizer::mock::MockVisualizer;
+pub use visualizer::palette::Palette;
+#[cfg(feature = "gl")]
+pub use visualizer::VirtualKeyCode;
+pub use visualizer::{ControlFlow, Draw, Matrix};
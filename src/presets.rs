@@ -0,0 +1,337 @@
+use algorithm::larger_than_life::LtlRule;
+use algorithm::lenia::Animal;
+use algorithm::life_like::LifeRule;
+use rand::{Rng, SeedableRng, StdRng};
+use std::fmt;
+
+// 複雑なパターンを作ることが知られている1次元CAのルール(Wolframのクラス3, 4)
+const INTERESTING_RULES: [u8; 12] = [18, 22, 30, 45, 54, 73, 90, 105, 110, 124, 137, 150];
+
+/// モデルとそのパラメータ
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModelConfig {
+    /// Gray-Scottモデル
+    GrayScott {
+        /// 拡散するときの変数1
+        f: f32,
+        /// 拡散するときの変数2
+        k: f32,
+    },
+    /// ランダムな初期状態から始めるGame of Life
+    GameOfLife {
+        /// 生きているセルの割合
+        density: f32,
+    },
+    /// ランダムな初期状態から始める、B/S表記のルールのlife-like CA
+    LifeLike {
+        /// ルール
+        rule: LifeRule,
+        /// 生きているセルの割合
+        density: f32,
+    },
+    /// ランダムな初期状態から始める、半径の大きな近傍を使うLarger than Life
+    LargerThanLife {
+        /// ルール
+        rule: LtlRule,
+        /// 生きているセルの割合
+        density: f32,
+    },
+    /// 中央の1セルから始める1次元のセル・オートマトン
+    ElementaryCa {
+        /// ルール番号
+        rule: u8,
+        /// セルの数
+        width: usize,
+    },
+    /// 1匹の生き物を中央に置いたLenia
+    Lenia {
+        /// 生き物とその規則
+        animal: Animal,
+        /// 盤面の大きさ(行, 列)
+        dim: (usize, usize),
+    },
+}
+
+impl fmt::Display for ModelConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ModelConfig::GrayScott { f: feed, k } => write!(f, "Gray-Scott (f={}, k={})", feed, k),
+            ModelConfig::GameOfLife { density } => write!(f, "Game of Life (density={})", density),
+            ModelConfig::LifeLike { rule, density } => write!(f, "Life-like CA (rule={}, density={})", rule, density),
+            ModelConfig::LargerThanLife { rule, density } => {
+                write!(f, "Larger than Life (rule={}, density={})", rule, density)
+            }
+            ModelConfig::ElementaryCa { rule, width } => write!(f, "Elementary CA (rule={}, width={})", rule, width),
+            ModelConfig::Lenia { ref animal, dim } => {
+                write!(f, "Lenia (animal={}, size={}x{})", animal.name, dim.0, dim.1)
+            }
+        }
+    }
+}
+
+/// 名前をつけたモデルの設定
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preset {
+    /// 名前
+    pub name: String,
+    /// 説明
+    pub description: String,
+    /// モデルとそのパラメータ
+    pub config: ModelConfig,
+}
+
+impl Preset {
+    /// プリセットを生成する
+    pub fn new(name: &str, description: &str, config: ModelConfig) -> Preset {
+        Preset {
+            name: name.to_string(),
+            description: description.to_string(),
+            config,
+        }
+    }
+}
+
+/// 名前からモデルの設定を引くための一覧
+///
+/// `default()`は組み込みのプリセットを含む。実行時に独自のプリセットを追加することもできる
+///
+/// # Example
+/// ```
+/// use my_alife::presets::{ModelConfig, Preset, PresetRegistry};
+///
+/// let mut registry = PresetRegistry::default();
+/// assert_eq!(
+///     registry.get("coral").unwrap().config,
+///     ModelConfig::GrayScott { f: 0.0545, k: 0.062 }
+/// );
+/// assert!(registry.names().contains(&"orbium"));
+///
+/// registry.register(Preset::new("dense-life", "密なGame of Life", ModelConfig::GameOfLife { density: 0.5 }));
+/// assert!(registry.names().contains(&"dense-life"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PresetRegistry {
+    presets: Vec<Preset>,
+}
+
+impl Default for PresetRegistry {
+    fn default() -> PresetRegistry {
+        let mut registry = PresetRegistry::empty();
+        for preset in builtin() {
+            registry.register(preset);
+        }
+        registry
+    }
+}
+
+impl PresetRegistry {
+    /// プリセットを含まない一覧を生成する
+    pub fn empty() -> PresetRegistry {
+        PresetRegistry { presets: Vec::new() }
+    }
+
+    /// プリセットを追加する。同じ名前のものがあれば置き換えて、元のプリセットを返す
+    pub fn register(&mut self, preset: Preset) -> Option<Preset> {
+        match self
+            .presets
+            .iter_mut()
+            .find(|registered| registered.name == preset.name)
+        {
+            Some(registered) => Some(::std::mem::replace(registered, preset)),
+            None => {
+                self.presets.push(preset);
+                None
+            }
+        }
+    }
+
+    /// 名前`name`のプリセット
+    pub fn get(&self, name: &str) -> Option<&Preset> {
+        self.presets.iter().find(|preset| preset.name == name)
+    }
+
+    /// 登録されているプリセットの名前(登録順)
+    pub fn names(&self) -> Vec<&str> {
+        self.presets.iter().map(|preset| preset.name.as_str()).collect()
+    }
+
+    /// 登録されているプリセット(登録順)
+    pub fn iter(&self) -> ::std::slice::Iter<'_, Preset> {
+        self.presets.iter()
+    }
+}
+
+/// 組み込みのプリセット
+///
+/// Gray-Scottのパラメータは[Robert Munafo](http://mrob.com/pub/comp/xmorphia/)の分類を参考にしている
+pub fn builtin() -> Vec<Preset> {
+    vec![
+        Preset::new(
+            "amorphous",
+            "不定形なパターン",
+            ModelConfig::GrayScott { f: 0.04, k: 0.06 },
+        ),
+        Preset::new("spots", "斑点", ModelConfig::GrayScott { f: 0.035, k: 0.065 }),
+        Preset::new("stripes", "縞模様", ModelConfig::GrayScott { f: 0.022, k: 0.051 }),
+        Preset::new("waves", "波", ModelConfig::GrayScott { f: 0.025, k: 0.05 }),
+        Preset::new("bubbles", "泡", ModelConfig::GrayScott { f: 0.012, k: 0.05 }),
+        Preset::new(
+            "coral",
+            "珊瑚のように枝分かれして伸びる",
+            ModelConfig::GrayScott { f: 0.0545, k: 0.062 },
+        ),
+        Preset::new(
+            "mitosis",
+            "細胞分裂のように斑点が分かれて増える",
+            ModelConfig::GrayScott { f: 0.0367, k: 0.0649 },
+        ),
+        Preset::new(
+            "u-skate",
+            "U字型のパターンが滑るように動く",
+            ModelConfig::GrayScott { f: 0.062, k: 0.0609 },
+        ),
+        Preset::new(
+            "life",
+            "ランダムな初期状態から始めるGame of Life",
+            ModelConfig::GameOfLife { density: 0.2 },
+        ),
+        Preset::new(
+            "highlife",
+            "自己複製するパターンを持つB36/S23",
+            ModelConfig::LifeLike {
+                rule: LifeRule::highlife(),
+                density: 0.2,
+            },
+        ),
+        Preset::new(
+            "day-and-night",
+            "生と死を入れ替えても同じになるB3678/S34678",
+            ModelConfig::LifeLike {
+                rule: LifeRule::day_and_night(),
+                density: 0.5,
+            },
+        ),
+        Preset::new(
+            "seeds",
+            "爆発的に広がるB2/S",
+            ModelConfig::LifeLike {
+                rule: LifeRule::seeds(),
+                density: 0.01,
+            },
+        ),
+        Preset::new(
+            "brians-brain",
+            "3状態のGenerationsのルールB2/S/C3",
+            ModelConfig::LifeLike {
+                rule: LifeRule::brians_brain(),
+                density: 0.1,
+            },
+        ),
+        Preset::new(
+            "bosco",
+            "滑るように動くパターンが現れるLarger than Life",
+            ModelConfig::LargerThanLife {
+                rule: LtlRule::bosco(),
+                density: 0.5,
+            },
+        ),
+        Preset::new(
+            "majority",
+            "近傍の多数派に揃って模様が粗くなるLarger than Life",
+            ModelConfig::LargerThanLife {
+                rule: LtlRule::majority(),
+                density: 0.5,
+            },
+        ),
+        Preset::new(
+            "rule30",
+            "カオス的なパターンを作る1次元CA",
+            ModelConfig::ElementaryCa { rule: 30, width: 256 },
+        ),
+        Preset::new(
+            "rule90",
+            "Sierpinskiの三角形を描く1次元CA",
+            ModelConfig::ElementaryCa { rule: 90, width: 256 },
+        ),
+        Preset::new(
+            "rule110",
+            "計算万能性が知られている1次元CA",
+            ModelConfig::ElementaryCa { rule: 110, width: 256 },
+        ),
+        Preset::new(
+            "orbium",
+            "滑るように進むLeniaの生き物",
+            ModelConfig::Lenia {
+                animal: Animal::orbium(),
+                dim: (96, 96),
+            },
+        ),
+    ]
+}
+
+/// 乱数で選んだモデルの設定。同じ`seed`からは同じ設定と初期状態が得られる
+#[derive(Debug, Clone, PartialEq)]
+pub struct Experiment {
+    /// 設定と初期状態の生成に使ったseed
+    pub seed: u64,
+    /// モデルとそのパラメータ
+    pub config: ModelConfig,
+}
+
+impl fmt::Display for Experiment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "seed={} {}", self.seed, self.config)
+    }
+}
+
+impl Experiment {
+    /// 初期状態を作るための乱数生成器。設定を選ぶときに使った乱数の続きになっている
+    pub fn rng(&self) -> StdRng {
+        let mut rng = seeded_rng(self.seed);
+        random_config(&mut rng);
+        rng
+    }
+}
+
+/// モデルの種類とパラメータを面白いことが起きやすい範囲から乱数で選ぶ
+///
+/// 表示される`seed`を使えば、偶然見つけたパターンを後から再現できる
+///
+/// # Example
+/// ```
+/// use my_alife::presets::surprise;
+///
+/// let experiment = surprise(42);
+/// println!("{}", experiment);
+/// assert_eq!(surprise(42), experiment);
+/// ```
+pub fn surprise(seed: u64) -> Experiment {
+    Experiment {
+        seed,
+        config: random_config(&mut seeded_rng(seed)),
+    }
+}
+
+fn seeded_rng(seed: u64) -> StdRng {
+    let seed = [(seed & 0xffff_ffff) as usize, (seed >> 32) as usize];
+    SeedableRng::from_seed(&seed[..])
+}
+
+fn random_config<R: Rng>(rng: &mut R) -> ModelConfig {
+    match rng.gen_range(0, 3) {
+        0 => {
+            // 一様な状態が不安定になる境界 k = sqrt(f) / 2 - f のすぐ下にパターンが現れやすい
+            let f: f32 = rng.gen_range(0.01, 0.06);
+            let boundary = f.sqrt() / 2.0 - f;
+            let k = rng.gen_range(boundary - 0.01, boundary);
+            ModelConfig::GrayScott { f, k }
+        }
+        1 => ModelConfig::GameOfLife {
+            density: rng.gen_range(0.1, 0.5),
+        },
+        _ => ModelConfig::ElementaryCa {
+            rule: *rng.choose(&INTERESTING_RULES).unwrap(),
+            width: 256,
+        },
+    }
+}
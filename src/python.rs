@@ -0,0 +1,363 @@
+//! `python` featureを有効にしてビルドすると、Pythonの拡張モジュール`my_alife`になる
+//!
+//! ```python
+//! import my_alife
+//!
+//! model = my_alife.GrayScott(0.04, 0.06)
+//! model.step(100)
+//! v = model.v()  # numpy.ndarray
+//!
+//! lenia = my_alife.Lenia(96, 96)  # 中央にOrbiumを置く
+//! lenia.step(10)
+//!
+//! ga = my_alife.GeneticAlgorithm(30, 5, seed=1)
+//! ga.run(50, lambda genes: -((genes - 1.0) ** 2).sum())
+//! genes, fitness = ga.best()
+//! ```
+// #[pymethods]が生成するコードがPyResultをPyErrに変換し直すため
+#![allow(clippy::useless_conversion)]
+
+use algorithm::evolution::{random_genome, GaParams, GeneticAlgorithm};
+use algorithm::game_of_life;
+use algorithm::gray_scott::{initial_matrix, laplacian};
+use algorithm::lenia::Animal;
+use algorithm::region::paste;
+use failure;
+use ndarray::Array2;
+use numpy::{Element, PyArray1, PyArray2, PyArrayMethods, PyReadonlyArray2, PyUntypedArrayMethods};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rand::{self, SeedableRng, XorShiftRng};
+use visualizer::Matrix;
+
+fn value_error(err: failure::Error) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+// NumPyの配列はnumpy crateが使うndarrayの型なので、一度Vecを経由してコピーする
+fn to_numpy<'py, A: Element + Copy>(py: Python<'py>, matrix: &Matrix<A>) -> PyResult<Bound<'py, PyArray2<A>>> {
+    let data: Vec<A> = matrix.iter().cloned().collect();
+    PyArray1::from_vec_bound(py, data).reshape([matrix.rows(), matrix.cols()])
+}
+
+fn from_numpy<A: Element + Copy>(array: PyReadonlyArray2<A>) -> PyResult<Matrix<A>> {
+    let shape = (array.shape()[0], array.shape()[1]);
+    let data: Vec<A> = array.as_array().iter().cloned().collect();
+    Array2::from_shape_vec(shape, data).map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+/// Gray-Scottモデル。`u`と`v`はNumPyの配列として取り出せる
+#[pyclass(name = "GrayScott", unsendable)]
+pub struct PyGrayScott {
+    uv: (Matrix<f32>, Matrix<f32>),
+    /// 拡散するときの変数1
+    #[pyo3(get, set)]
+    f: f32,
+    /// 拡散するときの変数2
+    #[pyo3(get, set)]
+    k: f32,
+}
+
+#[pymethods]
+impl PyGrayScott {
+    #[new]
+    fn new(f: f32, k: f32) -> PyGrayScott {
+        PyGrayScott {
+            uv: initial_matrix(),
+            f,
+            k,
+        }
+    }
+
+    /// 描画1フレーム分の計算を`frames`回行う
+    #[pyo3(signature = (frames = 1))]
+    fn step(&mut self, frames: usize) {
+        for _ in 0..frames {
+            laplacian(&mut self.uv, self.f, self.k);
+        }
+    }
+
+    fn u<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<f32>>> {
+        to_numpy(py, &self.uv.0)
+    }
+
+    fn v<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<f32>>> {
+        to_numpy(py, &self.uv.1)
+    }
+
+    /// `u`と`v`を置き換える。2つの配列は同じ大きさでなければならない
+    fn set_uv(&mut self, u: PyReadonlyArray2<f32>, v: PyReadonlyArray2<f32>) -> PyResult<()> {
+        let (u, v) = (from_numpy(u)?, from_numpy(v)?);
+        if u.dim() != v.dim() {
+            return Err(PyValueError::new_err(format!(
+                "u and v must have the same shape, got {:?} and {:?}",
+                u.dim(),
+                v.dim()
+            )));
+        }
+        self.uv = (u, v);
+        Ok(())
+    }
+}
+
+/// Game of Life(周期境界条件)。セルはNumPyのuint8の配列として取り出せる
+#[pyclass(name = "GameOfLife", unsendable)]
+pub struct PyGameOfLife {
+    cells: Matrix<u8>,
+}
+
+#[pymethods]
+impl PyGameOfLife {
+    #[new]
+    #[pyo3(signature = (rows, cols, density = 0.2))]
+    fn new(rows: usize, cols: usize, density: f32) -> PyGameOfLife {
+        PyGameOfLife {
+            cells: game_of_life::random_cells((rows, cols), density),
+        }
+    }
+
+    #[pyo3(signature = (steps = 1))]
+    fn step(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.cells = game_of_life::step(&self.cells);
+        }
+    }
+
+    fn cells<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<u8>>> {
+        to_numpy(py, &self.cells)
+    }
+
+    fn set_cells(&mut self, cells: PyReadonlyArray2<u8>) -> PyResult<()> {
+        self.cells = from_numpy(cells)?;
+        Ok(())
+    }
+
+    fn population(&self) -> usize {
+        self.cells.iter().filter(|&&e| e == game_of_life::ALIVE).count()
+    }
+}
+
+/// Lenia(周期境界条件)。規則と初期配置はLeniaの形式のJSONの生き物から作り、セルはNumPyのfloat32の配列として取り出せる
+#[pyclass(name = "Lenia", unsendable)]
+pub struct PyLenia {
+    animal: Animal,
+    cells: Matrix<f32>,
+}
+
+impl PyLenia {
+    // 生き物を`rows`x`cols`の盤面の中央に置く
+    fn with_animal(animal: Animal, rows: usize, cols: usize) -> PyLenia {
+        let (height, width) = animal.cells.dim();
+        let mut cells = Array2::zeros((rows, cols));
+        paste(
+            &mut cells,
+            &animal.cells,
+            (rows as isize - height as isize) / 2,
+            (cols as isize - width as isize) / 2,
+            true,
+        );
+        PyLenia { animal, cells }
+    }
+}
+
+#[pymethods]
+impl PyLenia {
+    /// `animal`(JSONの文字列)を中央に置く。省略するとOrbiumを置く
+    #[new]
+    #[pyo3(signature = (rows = 96, cols = 96, animal = None))]
+    fn new(rows: usize, cols: usize, animal: Option<&str>) -> PyResult<PyLenia> {
+        let animal = match animal {
+            Some(json) => json.parse().map_err(value_error)?,
+            None => Animal::orbium(),
+        };
+        Ok(PyLenia::with_animal(animal, rows, cols))
+    }
+
+    /// Leniaの形式のJSONファイルから最初の生き物を読み込んで中央に置く
+    #[staticmethod]
+    #[pyo3(signature = (path, rows = 96, cols = 96))]
+    fn load(path: &str, rows: usize, cols: usize) -> PyResult<PyLenia> {
+        let animal = Animal::load_all(path)
+            .map_err(value_error)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| PyValueError::new_err(format!("{}: no animals in the file", path)))?;
+        Ok(PyLenia::with_animal(animal, rows, cols))
+    }
+
+    /// 1ステップ(1/T)の計算を`steps`回行う
+    #[pyo3(signature = (steps = 1))]
+    fn step(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.cells = self.animal.lenia.step(&self.cells);
+        }
+    }
+
+    fn cells<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<f32>>> {
+        to_numpy(py, &self.cells)
+    }
+
+    fn set_cells(&mut self, cells: PyReadonlyArray2<f32>) -> PyResult<()> {
+        self.cells = from_numpy(cells)?;
+        Ok(())
+    }
+
+    /// 生き物の名前
+    fn name(&self) -> &str {
+        &self.animal.name
+    }
+
+    /// 今の盤面を生き物としてLeniaの形式のJSONにする
+    fn to_json(&self) -> String {
+        Animal::new(
+            &self.animal.code,
+            &self.animal.name,
+            self.animal.lenia.clone(),
+            self.cells.clone(),
+        )
+        .to_string()
+    }
+
+    /// セルの値の合計
+    fn mass(&self) -> f32 {
+        self.cells.scalar_sum()
+    }
+}
+
+/// 実数の遺伝子型を進化させる遺伝的アルゴリズム。適応度はPythonの関数で計算する
+#[pyclass(name = "GeneticAlgorithm", unsendable)]
+pub struct PyGeneticAlgorithm {
+    ga: GeneticAlgorithm<Vec<f32>>,
+    rng: XorShiftRng,
+}
+
+impl PyGeneticAlgorithm {
+    fn evaluate(&mut self, fitness: &Bound<'_, PyAny>) -> PyResult<()> {
+        let py = fitness.py();
+        let values = self
+            .ga
+            .population()
+            .iter()
+            .map(|genes| fitness.call1((PyArray1::from_slice_bound(py, genes),))?.extract())
+            .collect::<PyResult<Vec<f32>>>()?;
+        self.ga.set_fitness(values);
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl PyGeneticAlgorithm {
+    /// 長さ`genome_length`の遺伝子型を[-scale, scale]の一様乱数で`population_size`個作る。
+    /// `seed`を省略すると毎回違う乱数を使う
+    #[new]
+    #[pyo3(signature = (
+        population_size,
+        genome_length,
+        scale = 1.0,
+        elites = 1,
+        tournament_size = 3,
+        crossover_rate = 0.7,
+        mutation_rate = 0.1,
+        seed = None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        population_size: usize,
+        genome_length: usize,
+        scale: f32,
+        elites: usize,
+        tournament_size: usize,
+        crossover_rate: f32,
+        mutation_rate: f32,
+        seed: Option<u64>,
+    ) -> PyResult<PyGeneticAlgorithm> {
+        if population_size == 0 {
+            return Err(PyValueError::new_err("population_size must be positive"));
+        }
+        let mut rng = match seed {
+            // XorShiftRngは種がすべて0だと使えないので、定数を混ぜる
+            Some(seed) => XorShiftRng::from_seed([seed as u32, (seed >> 32) as u32, 0x9e37_79b9, 0x7f4a_7c15]),
+            None => rand::weak_rng(),
+        };
+        let population = (0..population_size)
+            .map(|_| random_genome(genome_length, scale, &mut rng))
+            .collect();
+        let params = GaParams {
+            elites,
+            tournament_size,
+            crossover_rate,
+            mutation_rate,
+        };
+        Ok(PyGeneticAlgorithm {
+            ga: GeneticAlgorithm::new(population, params),
+            rng,
+        })
+    }
+
+    /// `fitness(genes)`で評価して次の世代を作ることを`generations`回繰り返し、最後の集団を評価して終わる
+    fn run(&mut self, generations: usize, fitness: &Bound<'_, PyAny>) -> PyResult<()> {
+        for _ in 0..generations {
+            self.evaluate(fitness)?;
+            self.ga.next_generation(&mut self.rng);
+        }
+        self.evaluate(fitness)
+    }
+
+    /// 集団を(個体数, 遺伝子の長さ)の配列にしたもの
+    fn population<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<f32>>> {
+        let population = self.ga.population();
+        let length = population[0].len();
+        let data = population.iter().flat_map(|genes| genes.iter().cloned()).collect();
+        let matrix = Array2::from_shape_vec((population.len(), length), data)
+            .map_err(|err| PyValueError::new_err(err.to_string()))?;
+        to_numpy(py, &matrix)
+    }
+
+    /// 集団全体を見て計算した適応度を設定する。数は集団の大きさと同じでなければならない
+    fn set_fitness(&mut self, fitness: Vec<f32>) -> PyResult<()> {
+        if fitness.len() != self.ga.population().len() {
+            return Err(PyValueError::new_err(format!(
+                "expected {} fitness values, got {}",
+                self.ga.population().len(),
+                fitness.len()
+            )));
+        }
+        self.ga.set_fitness(fitness);
+        Ok(())
+    }
+
+    /// `set_fitness`で評価した集団から次の世代を作る
+    fn next_generation(&mut self) -> PyResult<()> {
+        if self.ga.fitness().len() != self.ga.population().len() {
+            return Err(PyValueError::new_err("population is not evaluated"));
+        }
+        self.ga.next_generation(&mut self.rng);
+        Ok(())
+    }
+
+    /// 評価済みの集団で最も適応度の高い(遺伝子型, 適応度)。評価していなければNone
+    fn best<'py>(&self, py: Python<'py>) -> Option<(Bound<'py, PyArray1<f32>>, f32)> {
+        self.ga
+            .best()
+            .map(|(genes, fitness)| (PyArray1::from_slice_bound(py, genes), fitness))
+    }
+
+    /// 世代数
+    fn generation(&self) -> usize {
+        self.ga.generation()
+    }
+
+    /// 世代ごとの最良の適応度
+    fn history(&self) -> Vec<f32> {
+        self.ga.history().to_vec()
+    }
+}
+
+#[pymodule]
+fn my_alife(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyGrayScott>()?;
+    m.add_class::<PyGameOfLife>()?;
+    m.add_class::<PyLenia>()?;
+    m.add_class::<PyGeneticAlgorithm>()?;
+    Ok(())
+}
@@ -0,0 +1,186 @@
+use algorithm::game_of_life::alive_neighbors;
+use failure;
+use ndarray::Array2;
+use rhai::{Array, CallFnOptions, Dynamic, Engine, Map, Scope, AST};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use visualizer::Matrix;
+
+/// Rhaiで書いた更新規則・適応度関数・操作への反応
+///
+/// スクリプトには次の関数を(必要なものだけ)定義する
+/// * `rule(state, neighbors)` - セルの状態とMoore近傍で生きているセルの数から次の状態を返す
+/// * `fitness(genome)` - 遺伝子(小数の配列)の適応度を返す
+/// * `on_<event>(args...)` - 操作`<event>`への反応。`this`でパラメータを読み書きできる
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::arr2;
+/// use my_alife::scripting::Script;
+///
+/// let mut script = Script::new(
+///     r#"
+///     // B3/S23
+///     fn rule(state, neighbors) {
+///         if neighbors == 3 || (state == 1 && neighbors == 2) { 1 } else { 0 }
+///     }
+///     fn fitness(genome) {
+///         let sum = 0.0;
+///         for x in genome { sum -= x * x; }
+///         sum
+///     }
+///     fn on_key(key) {
+///         if key == "Up" { this.f += 0.5; }
+///     }
+///     "#,
+/// ).unwrap();
+///
+/// let blinker = arr2(&[[0, 0, 0, 0, 0], [0, 0, 1, 0, 0], [0, 0, 1, 0, 0], [0, 0, 1, 0, 0], [0, 0, 0, 0, 0]]);
+/// let next = script.step(&blinker).unwrap();
+/// assert_eq!(next.row(2).to_vec(), vec![0, 1, 1, 1, 0]);
+/// assert_eq!(script.fitness(&[1.0, 2.0]).unwrap(), -5.0);
+///
+/// script.set_param("f", 1.0);
+/// assert!(script.handle("key", vec!["Up".into()]).unwrap());
+/// assert!(!script.handle("click", vec![]).unwrap());
+/// assert_eq!(script.param("f"), Some(1.5));
+/// ```
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+    params: Dynamic,
+    // ruleは純粋な関数とみなして(状態, 近傍の数)ごとに結果を覚えておく
+    rules: HashMap<(u8, u8), u8>,
+}
+
+impl Script {
+    /// `source`をコンパイルする
+    pub fn new(source: &str) -> Result<Script, failure::Error> {
+        let engine = Engine::new();
+        let ast = engine.compile(source).map_err(|e| format_err!("{}", e))?;
+        Ok(Script {
+            engine,
+            ast,
+            path: None,
+            modified: None,
+            params: Dynamic::from_map(Map::new()),
+            rules: HashMap::new(),
+        })
+    }
+
+    /// `path`のスクリプトを読み込む。`reload_if_changed`で書き換えを反映できる
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Script, failure::Error> {
+        let path = path.as_ref();
+        let mut script = Script::new(&fs::read_to_string(path)?)?;
+        script.modified = Some(fs::metadata(path)?.modified()?);
+        script.path = Some(path.to_path_buf());
+        Ok(script)
+    }
+
+    /// ファイルが書き換わっていれば読み込み直してtrueを返す。パラメータはそのまま残る
+    ///
+    /// 読み込み直したスクリプトにエラーがあるときは、前のスクリプトを使い続けてエラーを返す
+    pub fn reload_if_changed(&mut self) -> Result<bool, failure::Error> {
+        let path = match self.path {
+            Some(ref path) => path.clone(),
+            None => return Ok(false),
+        };
+        let modified = fs::metadata(&path)?.modified()?;
+        if self.modified == Some(modified) {
+            return Ok(false);
+        }
+        self.modified = Some(modified);
+        let source = fs::read_to_string(&path)?;
+        self.ast = self
+            .engine
+            .compile(&source)
+            .map_err(|e| format_err!("{}: {}", path.display(), e))?;
+        self.rules.clear();
+        Ok(true)
+    }
+
+    /// `name`という関数が`arity`個の引数で定義されているかどうか
+    pub fn has_function(&self, name: &str, arity: usize) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|f| f.name == name && f.params.len() == arity)
+    }
+
+    fn call(&mut self, name: &str, args: Vec<Dynamic>) -> Result<Dynamic, failure::Error> {
+        let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(&mut self.params);
+        self.engine
+            .call_fn_with_options(options, &mut Scope::new(), &self.ast, name, args)
+            .map_err(|e| format_err!("{}: {}", name, e))
+    }
+
+    /// `rule`で1つのセルの次の状態を決める
+    pub fn next_state(&mut self, state: u8, neighbors: u8) -> Result<u8, failure::Error> {
+        if let Some(&next) = self.rules.get(&(state, neighbors)) {
+            return Ok(next);
+        }
+        let next = self
+            .call(
+                "rule",
+                vec![Dynamic::from(state as i64), Dynamic::from(neighbors as i64)],
+            )?
+            .as_int()
+            .map_err(|t| format_err!("rule must return an integer, not {}", t))?;
+        if next < 0 || next > u8::MAX as i64 {
+            return Err(format_err!("rule returned {} for ({}, {})", next, state, neighbors));
+        }
+        self.rules.insert((state, neighbors), next as u8);
+        Ok(next as u8)
+    }
+
+    /// `rule`で盤面全体を1ステップ進める(周期境界条件)
+    pub fn step(&mut self, cells: &Matrix<u8>) -> Result<Matrix<u8>, failure::Error> {
+        let mut next = Array2::zeros(cells.dim());
+        for ((row, col), e) in next.indexed_iter_mut() {
+            *e = self.next_state(cells[[row, col]], alive_neighbors(cells, row, col))?;
+        }
+        Ok(next)
+    }
+
+    /// `fitness`で遺伝子の適応度を計算する
+    pub fn fitness(&mut self, genome: &[f32]) -> Result<f32, failure::Error> {
+        let genome: Array = genome.iter().map(|&x| Dynamic::from(x as f64)).collect();
+        let fitness = self
+            .call("fitness", vec![Dynamic::from_array(genome)])?
+            .as_float()
+            .map_err(|t| format_err!("fitness must return a float, not {}", t))?;
+        Ok(fitness as f32)
+    }
+
+    /// 操作`event`を`on_<event>`に渡す。関数が定義されていなければfalseを返す
+    pub fn handle(&mut self, event: &str, args: Vec<Dynamic>) -> Result<bool, failure::Error> {
+        let name = format!("on_{}", event);
+        if !self.has_function(&name, args.len()) {
+            return Ok(false);
+        }
+        let _ = self.call(&name, args)?;
+        Ok(true)
+    }
+
+    /// パラメータ`name`を`value`にする。スクリプトからは`this.<name>`で読み書きできる
+    pub fn set_param(&mut self, name: &str, value: f32) {
+        if let Some(mut params) = self.params.write_lock::<Map>() {
+            params.insert(name.into(), Dynamic::from(value as f64));
+        }
+    }
+
+    /// パラメータ`name`の値。ないか小数でなければ`None`
+    pub fn param(&self, name: &str) -> Option<f32> {
+        let params = self.params.read_lock::<Map>()?;
+        params
+            .get(name)
+            .and_then(|value| value.as_float().ok())
+            .map(|value| value as f32)
+    }
+}
@@ -0,0 +1,257 @@
+use std::f32::consts::PI;
+use visualizer::colormap::normalize;
+
+#[cfg(feature = "audio")]
+pub use self::output::AudioOutput;
+
+// 行を音にするときの声部の最大数。これより長い行は平均して縮める
+const MAX_VOICES: usize = 32;
+// 1サンプルで目標の周波数・音量に近づく割合。急に変わるとノイズが出る
+const SMOOTHING: f32 = 0.002;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Voice {
+    frequency: f32,
+    amplitude: f32,
+    target_frequency: f32,
+    target_amplitude: f32,
+    phase: f32,
+}
+
+impl Voice {
+    fn new(frequency: f32) -> Voice {
+        Voice {
+            frequency,
+            amplitude: 0.0,
+            target_frequency: frequency,
+            target_amplitude: 0.0,
+            phase: 0.0,
+        }
+    }
+
+    fn next_sample(&mut self, sample_rate: f32) -> f32 {
+        self.frequency += (self.target_frequency - self.frequency) * SMOOTHING;
+        self.amplitude += (self.target_amplitude - self.amplitude) * SMOOTHING;
+        self.phase = (self.phase + self.frequency / sample_rate).fract();
+        (self.phase * 2.0 * PI).sin() * self.amplitude
+    }
+}
+
+/// シミュレーションで観測した値を正弦波の音に変換する
+///
+/// `set_observable`では1つの値を音の高さに、`set_row`では行列の1行を
+/// 左から低い音〜高い音の音量に対応させる
+///
+/// # Example
+/// ```
+/// use my_alife::sonification::Sonifier;
+///
+/// let mut sonifier = Sonifier::new(44100.0);
+/// sonifier.set_value_range(0.0, 1.0);
+/// sonifier.set_observable(0.5);
+///
+/// // ステレオなので2チャンネル分を交互に書き込む
+/// let mut buffer = vec![0.0; 2 * 4410];
+/// sonifier.fill(&mut buffer, 2);
+/// assert!(buffer.iter().any(|&sample| sample != 0.0));
+/// assert!(buffer.iter().all(|&sample| sample.abs() <= 1.0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sonifier {
+    sample_rate: f32,
+    pitch_range: (f32, f32),
+    value_range: (f32, f32),
+    volume: f32,
+    voices: Vec<Voice>,
+}
+
+impl Sonifier {
+    /// 無音のSonifierを生成する。音の高さは220Hz〜880Hz、値の範囲は0.0〜1.0
+    ///
+    /// # Arguments
+    /// * `sample_rate` - 出力する音のサンプリング周波数(Hz)
+    pub fn new(sample_rate: f32) -> Sonifier {
+        Sonifier {
+            sample_rate,
+            pitch_range: (220.0, 880.0),
+            value_range: (0.0, 1.0),
+            volume: 0.5,
+            voices: Vec::new(),
+        }
+    }
+
+    /// サンプリング周波数(Hz)
+    pub fn sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+
+    /// 使う音の高さの範囲(Hz)を変更する
+    pub fn set_pitch_range(&mut self, min: f32, max: f32) {
+        self.pitch_range = (min, max);
+    }
+
+    /// 観測する値の範囲を変更する。範囲外の値は端の値として扱う
+    pub fn set_value_range(&mut self, min: f32, max: f32) {
+        self.value_range = (min, max);
+    }
+
+    /// 全体の音量(0.0〜1.0)を変更する
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume.clamp(0.0, 1.0);
+    }
+
+    /// 1つの観測値を音の高さにする。音量は一定
+    pub fn set_observable(&mut self, value: f32) {
+        let frequency = self.pitch(normalize(value, self.value_range));
+        self.resize_voices(1);
+        let voice = &mut self.voices[0];
+        voice.target_frequency = frequency;
+        voice.target_amplitude = if value.is_finite() { 1.0 } else { 0.0 };
+    }
+
+    /// 行列の1行を音にする。左のセルほど低い音で、値が大きいほど音が大きい
+    ///
+    /// # Example
+    /// ```
+    /// extern crate ndarray;
+    /// extern crate my_alife;
+    ///
+    /// use ndarray::Array2;
+    /// use my_alife::sonification::Sonifier;
+    ///
+    /// let mut sonifier = Sonifier::new(44100.0);
+    /// let matrix = Array2::<f32>::zeros((256, 256));
+    /// sonifier.set_row(matrix.row(128).iter().cloned());
+    ///
+    /// // すべて0なので音は出ない
+    /// let mut buffer = vec![0.0; 1024];
+    /// sonifier.fill(&mut buffer, 1);
+    /// assert!(buffer.iter().all(|&sample| sample == 0.0));
+    /// ```
+    pub fn set_row<A, I>(&mut self, row: I)
+    where
+        A: Copy + Into<f32>,
+        I: IntoIterator<Item = A>,
+    {
+        let row: Vec<f32> = row.into_iter().map(Into::into).collect();
+        if row.is_empty() {
+            self.resize_voices(0);
+            return;
+        }
+        let voices = row.len().min(MAX_VOICES);
+        self.resize_voices(voices);
+        for i in 0..voices {
+            let bin = &row[i * row.len() / voices..(i + 1) * row.len() / voices];
+            let mean = bin.iter().filter(|e| e.is_finite()).sum::<f32>() / bin.len() as f32;
+            let position = if voices == 1 {
+                0.0
+            } else {
+                i as f32 / (voices - 1) as f32
+            };
+            let frequency = self.pitch(position);
+            let voice = &mut self.voices[i];
+            voice.target_frequency = frequency;
+            voice.target_amplitude = normalize(mean, self.value_range);
+        }
+    }
+
+    /// `buffer`に音の波形を書き込む
+    ///
+    /// # Arguments
+    /// * `buffer` - 書き込み先。チャンネルごとのサンプルが交互に並ぶ
+    /// * `channels` - チャンネル数。すべてのチャンネルに同じ音を書き込む
+    pub fn fill(&mut self, buffer: &mut [f32], channels: usize) {
+        let channels = channels.max(1);
+        let gain = self.volume / self.voices.len().max(1) as f32;
+        for frame in buffer.chunks_mut(channels) {
+            let sample_rate = self.sample_rate;
+            let sample = self
+                .voices
+                .iter_mut()
+                .map(|voice| voice.next_sample(sample_rate))
+                .sum::<f32>()
+                * gain;
+            for out in frame.iter_mut() {
+                *out = sample;
+            }
+        }
+    }
+
+    // 0.0〜1.0を音の高さに変換する。人は周波数の比で音の高さを感じるので指数的に対応させる
+    fn pitch(&self, position: f32) -> f32 {
+        let (min, max) = self.pitch_range;
+        min * (max / min).powf(position)
+    }
+
+    fn resize_voices(&mut self, len: usize) {
+        if self.voices.len() != len {
+            let low = self.pitch_range.0;
+            self.voices.resize(len, Voice::new(low));
+        }
+    }
+}
+
+#[cfg(feature = "audio")]
+mod output {
+    use cpal;
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+    use failure;
+    use sonification::Sonifier;
+    use std::sync::{Arc, Mutex, MutexGuard};
+
+    /// Sonifierの音をデフォルトの出力デバイスで鳴らす
+    ///
+    /// dropすると音が止まる
+    ///
+    /// # Example
+    /// ```no_run
+    /// use my_alife::sonification::AudioOutput;
+    ///
+    /// let audio = AudioOutput::new().unwrap();
+    /// audio.sonifier().set_observable(0.5);
+    /// ```
+    pub struct AudioOutput {
+        sonifier: Arc<Mutex<Sonifier>>,
+        _stream: cpal::Stream,
+    }
+
+    impl AudioOutput {
+        /// デフォルトの出力デバイスで再生を始める
+        pub fn new() -> Result<AudioOutput, failure::Error> {
+            let host = cpal::default_host();
+            let device = host
+                .default_output_device()
+                .ok_or_else(|| format_err!("no audio output device is available"))?;
+            let supported = device.default_output_config()?;
+            if supported.sample_format() != cpal::SampleFormat::F32 {
+                return Err(format_err!(
+                    "unsupported sample format: {:?}",
+                    supported.sample_format()
+                ));
+            }
+            let config: cpal::StreamConfig = supported.into();
+            let channels = config.channels as usize;
+            let sonifier = Arc::new(Mutex::new(Sonifier::new(config.sample_rate.0 as f32)));
+            let shared = Arc::clone(&sonifier);
+            let stream = device.build_output_stream(
+                &config,
+                move |buffer: &mut [f32], _: &cpal::OutputCallbackInfo| match shared.lock() {
+                    Ok(mut sonifier) => sonifier.fill(buffer, channels),
+                    Err(_) => buffer.iter_mut().for_each(|sample| *sample = 0.0),
+                },
+                |err| eprintln!("audio stream error: {}", err),
+                None,
+            )?;
+            stream.play()?;
+            Ok(AudioOutput {
+                sonifier,
+                _stream: stream,
+            })
+        }
+
+        /// 再生中のSonifier。ロックしている間は音の生成が止まるので、すぐに手放すこと
+        pub fn sonifier(&self) -> MutexGuard<'_, Sonifier> {
+            self.sonifier.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+        }
+    }
+}
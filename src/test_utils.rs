@@ -0,0 +1,230 @@
+//! 盤面を比べるassertや、基準となる状態(golden)を使った回帰テストのための関数
+//!
+//! 同梱のモデルでも自作のモデルでも、決まった乱数の種で短く動かした結果をファイルに保存しておけば、
+//! 実装を変えたときに結果が変わっていないかを確かめられる
+use algorithm::checkpoint::{join, parse_list, Checkpoint, CheckpointRng};
+use failure;
+use ndarray::Array2;
+use std::env;
+use std::fmt;
+use std::path::Path;
+use visualizer::Matrix;
+
+/// この環境変数が設定されているとき、`assert_golden`は比べずに基準のファイルを書き直す
+pub const UPDATE_GOLDEN_ENV: &str = "MY_ALIFE_UPDATE_GOLDEN";
+
+// 違いの報告に載せるセルの数
+const REPORTED_CELLS: usize = 8;
+
+/// 2つの盤面の違い
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatrixDiff {
+    /// 比べた盤面の大きさ
+    pub actual_dim: (usize, usize),
+    /// 期待した盤面の大きさ
+    pub expected_dim: (usize, usize),
+    /// 許容誤差を超えたセルの数。大きさが違うときは0
+    pub mismatches: usize,
+    /// 差の絶対値の最大値。NaNと数値の差は無限大とする
+    pub max_difference: f32,
+    /// 許容誤差を超えたセルのうち最初のいくつかの((行, 列), 値, 期待した値)
+    pub samples: Vec<((usize, usize), f32, f32)>,
+}
+
+impl fmt::Display for MatrixDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.actual_dim != self.expected_dim {
+            return write!(
+                f,
+                "matrix size differs: actual {:?}, expected {:?}",
+                self.actual_dim, self.expected_dim
+            );
+        }
+        let cells = self.actual_dim.0 * self.actual_dim.1;
+        write!(
+            f,
+            "{} of {} cells differ (max difference {})",
+            self.mismatches, cells, self.max_difference
+        )?;
+        for &((row, col), actual, expected) in &self.samples {
+            write!(f, "\n  [{}, {}]: actual {}, expected {}", row, col, actual, expected)?;
+        }
+        if self.mismatches > self.samples.len() {
+            write!(f, "\n  ... and {} more", self.mismatches - self.samples.len())?;
+        }
+        Ok(())
+    }
+}
+
+/// `actual`と`expected`のすべてのセルの差が`tolerance`以下かどうかを調べる。両方NaNのセルは同じとみなす
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::arr2;
+/// use my_alife::test_utils::compare;
+///
+/// let expected = arr2(&[[0.0, 0.5], [1.0, 0.25]]);
+/// assert!(compare(&arr2(&[[0.0, 0.5001], [1.0, 0.25]]), &expected, 1e-3).is_ok());
+///
+/// let diff = compare(&arr2(&[[0.0, 0.6], [1.0, 0.25]]), &expected, 1e-3).unwrap_err();
+/// assert_eq!(diff.mismatches, 1);
+/// assert_eq!(diff.samples[0].0, (0, 1));
+/// assert!(diff.to_string().starts_with("1 of 4 cells differ"));
+/// ```
+pub fn compare<A>(actual: &Matrix<A>, expected: &Matrix<A>, tolerance: f32) -> Result<(), MatrixDiff>
+where
+    A: Copy + Into<f32>,
+{
+    let mut diff = MatrixDiff {
+        actual_dim: actual.dim(),
+        expected_dim: expected.dim(),
+        mismatches: 0,
+        max_difference: 0.0,
+        samples: Vec::new(),
+    };
+    if actual.dim() != expected.dim() {
+        return Err(diff);
+    }
+    for ((cell, &a), &e) in actual.indexed_iter().zip(expected.iter()) {
+        let (a, e): (f32, f32) = (a.into(), e.into());
+        let difference = match (a.is_nan(), e.is_nan()) {
+            (true, true) => 0.0,
+            (false, false) if a == e => 0.0,
+            (false, false) => (a - e).abs(),
+            _ => f32::INFINITY,
+        };
+        diff.max_difference = diff.max_difference.max(difference);
+        if difference > tolerance {
+            diff.mismatches += 1;
+            if diff.samples.len() < REPORTED_CELLS {
+                diff.samples.push((cell, a, e));
+            }
+        }
+    }
+    match diff.mismatches {
+        0 => Ok(()),
+        _ => Err(diff),
+    }
+}
+
+/// `actual`と`expected`のすべてのセルの差が`tolerance`以下であることを確かめる
+///
+/// # Panics
+/// 大きさが違うか、差が`tolerance`を超えるセルがあるとき。違うセルの位置と値を表示する
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::gray_scott::{initial_matrix_using, laplacian};
+/// use my_alife::test_utils::{assert_matrix_close, run_seeded};
+///
+/// // 同じ種で動かせば同じ結果になる
+/// let run = || run_seeded(7, 20, |rng| initial_matrix_using(rng), |uv, _| { laplacian(uv, 0.04, 0.06); });
+/// let (a, b) = (run(), run());
+/// assert_matrix_close(&a.1, &b.1, 0.0);
+/// ```
+#[track_caller]
+pub fn assert_matrix_close<A>(actual: &Matrix<A>, expected: &Matrix<A>, tolerance: f32)
+where
+    A: Copy + Into<f32>,
+{
+    if let Err(diff) = compare(actual, expected, tolerance) {
+        panic!("matrices are not close (tolerance {}): {}", tolerance, diff);
+    }
+}
+
+/// 基準の状態として`matrix`を`path`に書き出す。値は読み戻すと同じになる形式で書く
+pub fn save_golden<P, A>(path: P, matrix: &Matrix<A>) -> Result<(), failure::Error>
+where
+    P: AsRef<Path>,
+    A: Copy + Into<f32>,
+{
+    let mut checkpoint = Checkpoint::new("golden");
+    checkpoint.push("dim", format!("{} {}", matrix.dim().0, matrix.dim().1));
+    for row in matrix.outer_iter() {
+        let values: Vec<f32> = row.iter().map(|&e| e.into()).collect();
+        checkpoint.push("row", join(&values));
+    }
+    checkpoint.save(path)
+}
+
+/// `save_golden`で書き出した基準の状態を読み込む
+pub fn load_golden<P: AsRef<Path>>(path: P) -> Result<Matrix<f32>, failure::Error> {
+    let checkpoint = Checkpoint::load(path)?;
+    checkpoint.expect_kind("golden")?;
+    let dim: Vec<usize> = parse_list(checkpoint.get("dim")?)?;
+    if dim.len() != 2 {
+        return Err(format_err!("invalid golden dim \"{}\"", checkpoint.get("dim")?));
+    }
+    let mut values = Vec::with_capacity(dim[0] * dim[1]);
+    for row in checkpoint.all("row") {
+        values.extend(parse_list::<f32>(row)?);
+    }
+    Array2::from_shape_vec((dim[0], dim[1]), values).map_err(|e| format_err!("invalid golden state: {}", e))
+}
+
+/// `actual`が`path`に保存した基準の状態と`tolerance`以内で一致することを確かめる
+///
+/// `path`がまだないか、環境変数`MY_ALIFE_UPDATE_GOLDEN`が設定されているときは、`actual`を新しい基準として書き出す
+///
+/// # Panics
+/// 基準の状態と違うとき、または読み書きできないとき
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::game_of_life::{random_cells_using, step};
+/// use my_alife::test_utils::{assert_golden, run_seeded};
+///
+/// let path = std::env::temp_dir().join("my_alife_life_golden.txt");
+/// let _ = std::fs::remove_file(&path);
+/// let run = || run_seeded(42, 10, |rng| random_cells_using((32, 32), 0.3, rng), |cells, _| *cells = step(cells));
+/// // 1回目は基準を書き出し、2回目は同じ結果になることを確かめる
+/// assert_golden(&path, &run(), 0.0);
+/// assert_golden(&path, &run(), 0.0);
+/// ```
+#[track_caller]
+pub fn assert_golden<P, A>(path: P, actual: &Matrix<A>, tolerance: f32)
+where
+    P: AsRef<Path>,
+    A: Copy + Into<f32>,
+{
+    let path = path.as_ref();
+    if !path.exists() || env::var_os(UPDATE_GOLDEN_ENV).is_some() {
+        if let Err(e) = save_golden(path, actual) {
+            panic!("failed to write golden state {}: {}", path.display(), e);
+        }
+        return;
+    }
+    let expected = match load_golden(path) {
+        Ok(expected) => expected,
+        Err(e) => panic!("failed to read golden state {}: {}", path.display(), e),
+    };
+    let actual = actual.mapv(Into::into);
+    if let Err(diff) = compare(&actual, &expected, tolerance) {
+        panic!(
+            "state differs from golden {} (tolerance {}): {}\nset {} to update it",
+            path.display(),
+            tolerance,
+            diff,
+            UPDATE_GOLDEN_ENV
+        );
+    }
+}
+
+/// 乱数の種`seed`で初期状態を`init`で作り、`step`で`steps`回進めた状態を返す
+///
+/// 同じ`seed`なら何度でも同じ結果になるので、短いシミュレーションの回帰テストに使う
+pub fn run_seeded<T, I, F>(seed: u64, steps: usize, init: I, mut step: F) -> T
+where
+    I: FnOnce(&mut CheckpointRng) -> T,
+    F: FnMut(&mut T, &mut CheckpointRng),
+{
+    let mut rng = CheckpointRng::new(seed);
+    let mut state = init(&mut rng);
+    for _ in 0..steps {
+        step(&mut state, &mut rng);
+    }
+    state
+}
@@ -0,0 +1,339 @@
+#[cfg(feature = "gl")]
+use failure;
+#[cfg(feature = "gl")]
+use glium::{glutin, Display, Surface};
+#[cfg(feature = "gl")]
+use visualizer::mouse::Mouse;
+use visualizer::overlay::Canvas;
+#[cfg(feature = "gl")]
+use visualizer::overlay::OverlayRenderer;
+use visualizer::texture::ValueMapping;
+use visualizer::Matrix;
+#[cfg(feature = "gl")]
+use visualizer::{ControlFlow, VirtualKeyCode};
+
+// 背景と向きを表す線の色
+const BACKGROUND: [u8; 4] = [0, 0, 0, 255];
+const HEADING_COLOR: [u8; 4] = [255, 255, 255, 255];
+
+/// 描画する1体のエージェント。座標は世界の座標(x: 右向き, y: 下向き)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AgentSprite {
+    /// 位置
+    pub position: (f32, f32),
+    /// 向き(ラジアン)。x軸の正の向きが0で、y軸の正の向きに回る
+    pub heading: f32,
+    /// 塗る色(RGB)
+    pub color: [u8; 3],
+}
+
+/// フレームごとの前の絵の扱い方
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenderMode {
+    /// 毎回消して、今のエージェントだけを描く
+    Clear,
+    /// 前のフレームのエージェントの色に値(0.0〜1.0)を掛けて残す。通った跡が尾を引いて薄れていく
+    Decay(f32),
+}
+
+/// エージェントを画像に描く。背景に場(光の強さや化学物質の濃度など)をヒートマップで描ける
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::visualizer::agent_visualizer::{AgentRenderer, AgentSprite, RenderMode};
+///
+/// let mut renderer = AgentRenderer::new((100.0, 100.0));
+/// renderer.set_mode(RenderMode::Decay(0.5));
+/// let mut agent = AgentSprite { position: (10.0, 50.0), heading: 0.0, color: [0, 255, 0] };
+/// renderer.render(None, &[agent], (100, 100));
+/// agent.position = (60.0, 50.0);
+/// let canvas = renderer.render(Some(&Array2::zeros((10, 10))), &[agent], (100, 100));
+/// // 前の位置には半分の明るさの跡が残る
+/// assert_eq!(canvas.pixel(9, 49), [0, 127, 0, 255]);
+/// assert_eq!(canvas.pixel(59, 49), [0, 255, 0, 255]);
+/// assert_eq!(canvas.pixel(30, 20), [0, 0, 0, 255]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AgentRenderer {
+    world: (f32, f32),
+    mode: RenderMode,
+    mapping: ValueMapping,
+    agent_radius: f32,
+    // 跡のRGB(画素ごと)と、その大きさ
+    trail: Vec<[f32; 3]>,
+    trail_size: (usize, usize),
+}
+
+impl AgentRenderer {
+    /// 幅と高さが`world`の世界を描く。初期値は`RenderMode::Clear`、エージェントの半径は3画素
+    pub fn new(world: (f32, f32)) -> AgentRenderer {
+        AgentRenderer {
+            world,
+            mode: RenderMode::Clear,
+            mapping: ValueMapping::default(),
+            agent_radius: 3.0,
+            trail: Vec::new(),
+            trail_size: (0, 0),
+        }
+    }
+
+    /// 前のフレームの扱い方を設定する
+    pub fn set_mode(&mut self, mode: RenderMode) {
+        self.mode = mode;
+        self.trail.clear();
+    }
+
+    /// 背景の場を色にする方法を設定する
+    pub fn set_mapping(&mut self, mapping: ValueMapping) {
+        self.mapping = mapping;
+    }
+
+    /// 背景の場を色にする方法
+    pub fn mapping(&self) -> &ValueMapping {
+        &self.mapping
+    }
+
+    /// エージェントの半径(画素)を設定する
+    pub fn set_agent_radius(&mut self, agent_radius: f32) {
+        self.agent_radius = agent_radius;
+    }
+
+    /// 世界の座標を画像の大きさ`size`の画素の座標にする
+    pub fn to_pixel(&self, (x, y): (f32, f32), size: (usize, usize)) -> (f32, f32) {
+        (x / self.world.0 * size.0 as f32, y / self.world.1 * size.1 as f32)
+    }
+
+    /// 画素の座標を世界の座標にする
+    pub fn to_world(&self, (x, y): (f32, f32), size: (usize, usize)) -> (f32, f32) {
+        (x / size.0 as f32 * self.world.0, y / size.1 as f32 * self.world.1)
+    }
+
+    /// 背景の場`field`(世界全体に引き伸ばす)と`agents`を、大きさ`size`(幅, 高さ)の画像に描く
+    pub fn render(&mut self, field: Option<&Matrix<f32>>, agents: &[AgentSprite], size: (usize, usize)) -> Canvas {
+        let (width, height) = size;
+        let mut canvas = Canvas::new(width, height);
+        canvas.fill_rect(0, 0, width, height, BACKGROUND);
+        if let Some(field) = field {
+            let (rows, cols) = field.dim();
+            if rows > 0 && cols > 0 {
+                for y in 0..height {
+                    let row = (y * rows / height.max(1)).min(rows - 1);
+                    for x in 0..width {
+                        let col = (x * cols / width.max(1)).min(cols - 1);
+                        canvas.set_pixel(x, y, self.mapping.rgba(field[[row, col]]));
+                    }
+                }
+            }
+        }
+        if let RenderMode::Decay(decay) = self.mode {
+            if self.trail_size != size || self.trail.len() != width * height {
+                self.trail = vec![[0.0; 3]; width * height];
+                self.trail_size = size;
+            }
+            for pixel in &mut self.trail {
+                for channel in pixel.iter_mut() {
+                    *channel *= decay;
+                }
+            }
+            for agent in agents {
+                let center = self.to_pixel(agent.position, size);
+                let color = [agent.color[0] as f32, agent.color[1] as f32, agent.color[2] as f32];
+                for_each_in_circle(center, self.agent_radius, size, |x, y| {
+                    self.trail[y * width + x] = color;
+                });
+            }
+            // 跡は背景より明るいところだけ上に重ねる
+            for (i, trail) in self.trail.iter().enumerate() {
+                let (x, y) = (i % width, i / width);
+                let mut pixel = canvas.pixel(x, y);
+                for c in 0..3 {
+                    pixel[c] = pixel[c].max(trail[c] as u8);
+                }
+                canvas.set_pixel(x, y, pixel);
+            }
+        }
+        for agent in agents {
+            let center = self.to_pixel(agent.position, size);
+            let [r, g, b] = agent.color;
+            for_each_in_circle(center, self.agent_radius, size, |x, y| {
+                canvas.set_pixel(x, y, [r, g, b, 255]);
+            });
+            if self.agent_radius >= 2.0 {
+                let length = 2.0 * self.agent_radius;
+                let tip = (
+                    center.0 + length * agent.heading.cos(),
+                    center.1 + length * agent.heading.sin(),
+                );
+                canvas.draw_line(center, tip, HEADING_COLOR);
+            }
+        }
+        canvas
+    }
+}
+
+fn for_each_in_circle<F: FnMut(usize, usize)>(center: (f32, f32), radius: f32, size: (usize, usize), mut f: F) {
+    let x0 = (center.0 - radius).floor().max(0.0) as usize;
+    let y0 = (center.1 - radius).floor().max(0.0) as usize;
+    let x1 = ((center.0 + radius).ceil().max(0.0) as usize).min(size.0);
+    let y1 = ((center.1 + radius).ceil().max(0.0) as usize).min(size.1);
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let (dx, dy) = (x as f32 + 0.5 - center.0, y as f32 + 0.5 - center.1);
+            if dx * dx + dy * dy <= radius * radius {
+                f(x, y);
+            }
+        }
+    }
+}
+
+/// 連続空間を動き回るエージェントをvisualizeする構造体
+///
+/// Braitenbergのビークルや群れのモデルを表示するために使う。`set_mode(RenderMode::Decay(..))`で通った跡を残せる
+///
+/// # Example
+/// ```no_run
+/// use my_alife::visualizer::agent_visualizer::{AgentSprite, AgentVisualizer, RenderMode};
+/// use my_alife::visualizer::ControlFlow;
+///
+/// let mut visualizer = AgentVisualizer::new("Agents", (200.0, 200.0)).unwrap();
+/// visualizer.set_mode(RenderMode::Decay(0.95));
+/// let mut agent = AgentSprite { position: (100.0, 100.0), heading: 0.0, color: [255, 200, 0] };
+/// loop {
+///     agent.heading += 0.05;
+///     agent.position.0 += agent.heading.cos();
+///     agent.position.1 += agent.heading.sin();
+///     if visualizer.draw(None, &[agent]).unwrap() == ControlFlow::Stop {
+///         break;
+///     }
+/// }
+/// ```
+#[cfg(feature = "gl")]
+pub struct AgentVisualizer {
+    events_loop: glutin::EventsLoop,
+    display: Display,
+    overlay: OverlayRenderer,
+    renderer: AgentRenderer,
+    mouse: Mouse,
+    // 直前の`poll_events`の間に押されたキー
+    keys: Vec<VirtualKeyCode>,
+}
+
+#[cfg(feature = "gl")]
+impl AgentVisualizer {
+    /// 600×600のウィンドウを開き、幅と高さが`world`の世界を描く
+    pub fn new(title: &str, world: (f32, f32)) -> Result<AgentVisualizer, failure::Error> {
+        let events_loop = glutin::EventsLoop::new();
+        let window = glutin::WindowBuilder::new()
+            .with_dimensions((600, 600).into())
+            .with_title(title);
+        let context = glutin::ContextBuilder::new();
+        let display = Display::new(window, context, &events_loop).map_err(|e| format_err!("{}", e))?;
+        let overlay = OverlayRenderer::new(&display)?;
+        Ok(AgentVisualizer {
+            events_loop,
+            display,
+            overlay,
+            renderer: AgentRenderer::new(world),
+            mouse: Mouse::default(),
+            keys: Vec::new(),
+        })
+    }
+
+    /// ウィンドウのタイトルを変更する
+    pub fn set_title(&self, title: &str) {
+        self.display.gl_window().set_title(title);
+    }
+
+    /// 前のフレームの扱い方を設定する
+    pub fn set_mode(&mut self, mode: RenderMode) {
+        self.renderer.set_mode(mode);
+    }
+
+    /// 背景の場を色にする方法を設定する
+    pub fn set_mapping(&mut self, mapping: ValueMapping) {
+        self.renderer.set_mapping(mapping);
+    }
+
+    /// エージェントの半径(画素)を設定する
+    pub fn set_agent_radius(&mut self, agent_radius: f32) {
+        self.renderer.set_agent_radius(agent_radius);
+    }
+
+    /// マウスの状態
+    pub fn mouse(&self) -> &Mouse {
+        &self.mouse
+    }
+
+    /// 直前の`poll_events`の間に押されたキー
+    pub fn pressed_keys(&self) -> &[VirtualKeyCode] {
+        &self.keys
+    }
+
+    /// マウスが指している世界の座標
+    pub fn mouse_position(&self) -> Option<(f32, f32)> {
+        let (x, y) = self.mouse.position?;
+        let (width, height) = self.window_size()?;
+        Some(
+            self.renderer
+                .to_world((x as f32, y as f32), (width as usize, height as usize)),
+        )
+    }
+
+    fn window_size(&self) -> Option<(f64, f64)> {
+        let size = self.display.gl_window().get_inner_size()?;
+        Some((size.width, size.height))
+    }
+
+    /// 背景の場`field`と`agents`を1フレームだけ描画し、溜まっているイベントを処理する
+    pub fn draw(&mut self, field: Option<&Matrix<f32>>, agents: &[AgentSprite]) -> Result<ControlFlow, failure::Error> {
+        let mut target = self.display.draw();
+        let (width, height) = target.get_dimensions();
+        let canvas = self.renderer.render(field, agents, (width as usize, height as usize));
+        target.clear_color(0.0, 0.0, 0.0, 1.0);
+        let drawn = self.overlay.draw(&self.display, &mut target, &canvas, (0, 0));
+        target.finish()?;
+        drawn?;
+        Ok(self.poll_events())
+    }
+
+    /// 溜まっているイベントを処理し、描画を止めるときは`ControlFlow::Stop`を返す
+    pub fn poll_events(&mut self) -> ControlFlow {
+        let mut flow = ControlFlow::Continue;
+        let mouse = &mut self.mouse;
+        let keys = &mut self.keys;
+        keys.clear();
+        self.events_loop.poll_events(|event| {
+            if let glutin::Event::WindowEvent { event, .. } = event {
+                match event {
+                    glutin::WindowEvent::CloseRequested => flow = ControlFlow::Stop,
+                    glutin::WindowEvent::CursorMoved {
+                        position, modifiers, ..
+                    } => {
+                        mouse.position = Some((position.x, position.y));
+                        mouse.shift = modifiers.shift;
+                    }
+                    glutin::WindowEvent::CursorLeft { .. } => mouse.position = None,
+                    glutin::WindowEvent::MouseInput { state, button, .. } => {
+                        let pressed = state == glutin::ElementState::Pressed;
+                        match button {
+                            glutin::MouseButton::Left => mouse.left = pressed,
+                            glutin::MouseButton::Right => mouse.right = pressed,
+                            _ => {}
+                        }
+                    }
+                    glutin::WindowEvent::KeyboardInput { input, .. } => {
+                        if let (Some(key), glutin::ElementState::Pressed) = (input.virtual_keycode, input.state) {
+                            keys.push(key);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        });
+        flow
+    }
+}
@@ -0,0 +1,273 @@
+use algorithm::geometry::{CellKind, Geometry};
+use rand::Rng;
+use visualizer::Matrix;
+#[cfg(feature = "gl")]
+use visualizer::VirtualKeyCode;
+
+/// ブラシの道具
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    /// 番号の物質(状態)を加える
+    Add(usize),
+    /// 初期状態に戻す
+    Erase,
+    /// ランダムに乱す
+    Noise,
+}
+
+/// ブラシで描ける盤面。モデルごとに、道具が状態をどう変えるかを決める
+pub trait Brush {
+    /// 盤面の大きさ
+    fn dim(&self) -> (usize, usize);
+
+    /// セル`cell`に強さ`weight`(0から1)で`tool`を使う
+    fn apply<R: Rng>(&mut self, tool: Tool, cell: (usize, usize), weight: f32, rng: &mut R);
+
+    /// `center`を中心とする`settings`の大きさの円に描く。強さは中心から離れるほど弱くなる
+    fn stroke<R: Rng>(&mut self, settings: &BrushSettings, center: (usize, usize), rng: &mut R) {
+        let (rows, cols) = self.dim();
+        let r = settings.radius() as isize;
+        for dr in -r..=r {
+            for dc in -r..=r {
+                let (row, col) = (center.0 as isize + dr, center.1 as isize + dc);
+                let distance = ((dr * dr + dc * dc) as f32).sqrt();
+                let inside = row >= 0 && col >= 0 && (row as usize) < rows && (col as usize) < cols;
+                if inside && distance <= r as f32 {
+                    let weight = settings.intensity() * (1.0 - distance / (r as f32 + 1.0));
+                    self.apply(settings.tool(), (row as usize, col as usize), weight, rng);
+                }
+            }
+        }
+    }
+}
+
+/// 選んでいる道具と、ブラシの半径・強さ
+///
+/// 数字キーで道具を選び、`[`/`]`で半径を、`,`/`.`で強さを変える
+///
+/// # Example
+/// ```
+/// use my_alife::visualizer::brush::{BrushSettings, Tool};
+///
+/// let mut brush = BrushSettings::new(vec![("add U", Tool::Add(0)), ("add V", Tool::Add(1)), ("erase", Tool::Erase)]);
+/// brush.select(1);
+/// brush.set_radius(6);
+/// assert_eq!(brush.tool(), Tool::Add(1));
+/// assert_eq!(brush.describe(), "add V / radius 6 / intensity 0.50");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrushSettings {
+    tools: Vec<(String, Tool)>,
+    selected: usize,
+    radius: usize,
+    intensity: f32,
+}
+
+impl BrushSettings {
+    /// (名前, 道具)の一覧から生成する。最初の道具を選び、半径は5、強さは0.5になる
+    ///
+    /// # Panics
+    /// 道具がないとき
+    pub fn new(tools: Vec<(&str, Tool)>) -> BrushSettings {
+        assert!(!tools.is_empty(), "brush needs at least one tool");
+        BrushSettings {
+            tools: tools.into_iter().map(|(name, tool)| (name.to_string(), tool)).collect(),
+            selected: 0,
+            radius: 5,
+            intensity: 0.5,
+        }
+    }
+
+    /// 選んでいる道具
+    pub fn tool(&self) -> Tool {
+        self.tools[self.selected].1
+    }
+
+    /// 選んでいる道具の名前
+    pub fn tool_name(&self) -> &str {
+        &self.tools[self.selected].0
+    }
+
+    /// 選んでいる道具の番号
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// `index`番目の道具を選ぶ。道具がなければ何もしない
+    pub fn select(&mut self, index: usize) {
+        if index < self.tools.len() {
+            self.selected = index;
+        }
+    }
+
+    /// 半径
+    pub fn radius(&self) -> usize {
+        self.radius
+    }
+
+    /// 半径を変更する
+    pub fn set_radius(&mut self, radius: usize) {
+        self.radius = radius;
+    }
+
+    /// 強さ
+    pub fn intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    /// 強さを変更する。0から1の範囲に収める
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity.clamp(0.0, 1.0);
+    }
+
+    /// キー入力で設定を変える。何か変わったかどうかを返す
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::visualizer::brush::{BrushSettings, Tool};
+    /// use my_alife::visualizer::VirtualKeyCode;
+    ///
+    /// let mut brush = BrushSettings::new(vec![("add U", Tool::Add(0)), ("add V", Tool::Add(1)), ("erase", Tool::Erase)]);
+    /// assert!(brush.handle_keys(&[VirtualKeyCode::Key2, VirtualKeyCode::RBracket]));
+    /// assert_eq!(brush.tool(), Tool::Add(1));
+    /// assert_eq!(brush.radius(), 6);
+    /// assert_eq!(brush.describe(), "add V / radius 6 / intensity 0.50");
+    /// // 道具のない番号は無視する
+    /// assert!(!brush.handle_keys(&[VirtualKeyCode::Key9]));
+    /// ```
+    #[cfg(feature = "gl")]
+    pub fn handle_keys(&mut self, keys: &[VirtualKeyCode]) -> bool {
+        let before = self.clone();
+        for &key in keys {
+            match key {
+                VirtualKeyCode::Key1 => self.select(0),
+                VirtualKeyCode::Key2 => self.select(1),
+                VirtualKeyCode::Key3 => self.select(2),
+                VirtualKeyCode::Key4 => self.select(3),
+                VirtualKeyCode::Key5 => self.select(4),
+                VirtualKeyCode::Key6 => self.select(5),
+                VirtualKeyCode::Key7 => self.select(6),
+                VirtualKeyCode::Key8 => self.select(7),
+                VirtualKeyCode::Key9 => self.select(8),
+                VirtualKeyCode::LBracket => self.radius = self.radius.saturating_sub(1),
+                VirtualKeyCode::RBracket => self.radius += 1,
+                VirtualKeyCode::Comma => {
+                    let intensity = self.intensity - 0.1;
+                    self.set_intensity(intensity)
+                }
+                VirtualKeyCode::Period => {
+                    let intensity = self.intensity + 0.1;
+                    self.set_intensity(intensity)
+                }
+                _ => {}
+            }
+        }
+        *self != before
+    }
+
+    /// ウィンドウのタイトルなどに表示する説明
+    pub fn describe(&self) -> String {
+        format!(
+            "{} / radius {} / intensity {:.2}",
+            self.tool_name(),
+            self.radius,
+            self.intensity
+        )
+    }
+}
+
+/// 反応拡散系の物質の濃度の場。濃度は0から1に収める
+///
+/// # Example
+/// ```
+/// extern crate my_alife;
+/// extern crate ndarray;
+/// extern crate rand;
+///
+/// use my_alife::visualizer::brush::{Brush, BrushSettings, Fields, Tool};
+/// use ndarray::Array2;
+///
+/// let mut uv = [Array2::<f32>::ones((32, 32)), Array2::<f32>::zeros((32, 32))];
+/// let mut brush = BrushSettings::new(vec![("add V", Tool::Add(1)), ("erase", Tool::Erase)]);
+/// let mut rng = rand::thread_rng();
+/// Fields::new(&mut uv, &[1.0, 0.0]).stroke(&brush, (16, 16), &mut rng);
+/// assert_eq!(uv[1][[16, 16]], 0.5);
+/// assert!(uv[1][[16, 19]] > 0.0 && uv[1][[16, 19]] < 0.5);
+/// assert_eq!(uv[1][[16, 22]], 0.0);
+/// // 消しゴムは初期状態に近づける
+/// brush.select(1);
+/// brush.set_intensity(1.0);
+/// Fields::new(&mut uv, &[1.0, 0.0]).stroke(&brush, (16, 16), &mut rng);
+/// assert_eq!(uv[1][[16, 16]], 0.0);
+/// ```
+pub struct Fields<'a> {
+    fields: &'a mut [Matrix<f32>],
+    rest: Vec<f32>,
+}
+
+impl<'a> Fields<'a> {
+    /// 物質ごとの場`fields`と、消しゴムで戻す値`rest`から生成する
+    pub fn new(fields: &'a mut [Matrix<f32>], rest: &[f32]) -> Fields<'a> {
+        assert_eq!(fields.len(), rest.len());
+        Fields {
+            fields,
+            rest: rest.to_vec(),
+        }
+    }
+}
+
+impl<'a> Brush for Fields<'a> {
+    fn dim(&self) -> (usize, usize) {
+        self.fields.first().map_or((0, 0), |field| field.dim())
+    }
+
+    fn apply<R: Rng>(&mut self, tool: Tool, cell: (usize, usize), weight: f32, rng: &mut R) {
+        for (i, field) in self.fields.iter_mut().enumerate() {
+            let value = field[cell];
+            let next = match tool {
+                Tool::Add(channel) if channel == i => value + weight,
+                Tool::Add(_) => value,
+                Tool::Erase => value + (self.rest[i] - value) * weight,
+                Tool::Noise => value + rng.gen_range(-weight, weight.max(1e-6)),
+            };
+            field[cell] = next.clamp(0.0, 1.0);
+        }
+    }
+}
+
+/// セル・オートマトンの状態。強さは状態を書き換える確率になる
+impl Brush for Matrix<u8> {
+    fn dim(&self) -> (usize, usize) {
+        Matrix::dim(self)
+    }
+
+    fn apply<R: Rng>(&mut self, tool: Tool, cell: (usize, usize), weight: f32, rng: &mut R) {
+        if rng.gen::<f32>() >= weight {
+            return;
+        }
+        self[cell] = match tool {
+            Tool::Add(state) => state as u8,
+            Tool::Erase => 0,
+            Tool::Noise => rng.gen_range(0, 2),
+        };
+    }
+}
+
+/// 壁などの配置。`Add`の番号は0が壁、1が湧き出し口、2が吸い込み口で、ノイズは壁をまばらに置く
+impl Brush for Geometry {
+    fn dim(&self) -> (usize, usize) {
+        Geometry::dim(self)
+    }
+
+    fn apply<R: Rng>(&mut self, tool: Tool, (row, col): (usize, usize), weight: f32, rng: &mut R) {
+        let kind = match tool {
+            Tool::Add(0) => CellKind::Wall,
+            Tool::Add(1) => CellKind::Source,
+            Tool::Add(_) => CellKind::Sink,
+            Tool::Erase => CellKind::Open,
+            Tool::Noise if rng.gen::<f32>() < weight => CellKind::Wall,
+            Tool::Noise => return,
+        };
+        self.set(row, col, kind);
+    }
+}
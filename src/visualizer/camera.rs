@@ -0,0 +1,138 @@
+/// ワールド座標(セル単位)の長方形の範囲
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellRect {
+    /// 左上のx座標(列)
+    pub x: i64,
+    /// 左上のy座標(行)
+    pub y: i64,
+    /// 幅(セル数)
+    pub width: usize,
+    /// 高さ(セル数)
+    pub height: usize,
+}
+
+/// 大きさに上限のないワールドのどこをどの倍率で表示するかを表す
+///
+/// ワールド座標はセル単位で、x軸は右向き、y軸は下向き。
+/// スクリーン座標は表示先の画素単位で、左上が原点
+///
+/// # Example
+/// ```
+/// use my_alife::visualizer::camera::Camera;
+///
+/// let mut camera = Camera::new(600.0, 600.0);
+/// camera.set_zoom(2.0);
+/// camera.set_center((100.0, 50.0));
+/// assert_eq!(camera.world_to_screen((100.0, 50.0)), (300.0, 300.0));
+/// assert_eq!(camera.screen_to_world((0.0, 0.0)), (-50.0, -100.0));
+///
+/// // 1セル2画素なので、300x300セルが見えている
+/// let visible = camera.visible_cells();
+/// assert_eq!((visible.x, visible.y, visible.width, visible.height), (-50, -100, 300, 300));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    center: (f64, f64),
+    zoom: f64,
+    viewport: (f64, f64),
+}
+
+impl Camera {
+    /// 原点を中心に、1セルを1画素で表示するカメラを生成する
+    ///
+    /// # Arguments
+    /// * `width` - 表示先の幅(画素)
+    /// * `height` - 表示先の高さ(画素)
+    pub fn new(width: f64, height: f64) -> Camera {
+        Camera {
+            center: (0.0, 0.0),
+            zoom: 1.0,
+            viewport: (width, height),
+        }
+    }
+
+    /// 画面の中心に表示しているワールド座標
+    pub fn center(&self) -> (f64, f64) {
+        self.center
+    }
+
+    /// 画面の中心に表示するワールド座標を変更する
+    pub fn set_center(&mut self, center: (f64, f64)) {
+        self.center = center;
+    }
+
+    /// 1セルあたりの画素数
+    pub fn zoom(&self) -> f64 {
+        self.zoom
+    }
+
+    /// 1セルあたりの画素数を変更する
+    pub fn set_zoom(&mut self, zoom: f64) {
+        self.zoom = zoom.max(1e-6);
+    }
+
+    /// 表示先の大きさ(画素)
+    pub fn viewport(&self) -> (f64, f64) {
+        self.viewport
+    }
+
+    /// 表示先の大きさを変更する。ウィンドウの大きさが変わったときに使う
+    pub fn set_viewport(&mut self, width: f64, height: f64) {
+        self.viewport = (width, height);
+    }
+
+    /// ワールド座標をスクリーン座標に変換する
+    pub fn world_to_screen(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        (
+            (x - self.center.0) * self.zoom + self.viewport.0 / 2.0,
+            (y - self.center.1) * self.zoom + self.viewport.1 / 2.0,
+        )
+    }
+
+    /// スクリーン座標をワールド座標に変換する
+    pub fn screen_to_world(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        (
+            (x - self.viewport.0 / 2.0) / self.zoom + self.center.0,
+            (y - self.viewport.1 / 2.0) / self.zoom + self.center.1,
+        )
+    }
+
+    /// スクリーン上で(dx, dy)画素だけ視点を動かす
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        self.center.0 += dx / self.zoom;
+        self.center.1 += dy / self.zoom;
+    }
+
+    /// スクリーン座標`at`にあるワールド座標を動かさずに`factor`倍に拡大する
+    pub fn zoom_at(&mut self, factor: f64, at: (f64, f64)) {
+        let before = self.screen_to_world(at);
+        self.set_zoom(self.zoom * factor);
+        let after = self.screen_to_world(at);
+        self.center.0 += before.0 - after.0;
+        self.center.1 += before.1 - after.1;
+    }
+
+    /// 画面の中心を`target`に近づける。毎フレーム呼ぶと`target`を追いかける
+    ///
+    /// # Arguments
+    /// * `target` - 追いかけるワールド座標
+    /// * `smoothing` - 1フレームで近づく割合。1.0ならすぐに`target`が中心になる
+    pub fn follow(&mut self, target: (f64, f64), smoothing: f64) {
+        let smoothing = smoothing.clamp(0.0, 1.0);
+        self.center.0 += (target.0 - self.center.0) * smoothing;
+        self.center.1 += (target.1 - self.center.1) * smoothing;
+    }
+
+    /// 画面に映っているセルの範囲
+    pub fn visible_cells(&self) -> CellRect {
+        let (left, top) = self.screen_to_world((0.0, 0.0));
+        let (right, bottom) = self.screen_to_world(self.viewport);
+        let (x, y) = (left.floor() as i64, top.floor() as i64);
+        CellRect {
+            x,
+            y,
+            width: (right.ceil() as i64 - x).max(1) as usize,
+            height: (bottom.ceil() as i64 - y).max(1) as usize,
+        }
+    }
+}
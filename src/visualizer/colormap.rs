@@ -0,0 +1,146 @@
+/// 値(0.0〜1.0)を色に変換する方法  
+/// CPU側でテクスチャを作るときも、シェーダー側で変換するときも同じ色になる
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colormap {
+    /// 黒から白へのグレースケール
+    Grayscale,
+    /// 黒、赤、黄、白と変化する
+    Heat,
+    /// matplotlibのviridisを多項式で近似したもの
+    Viridis,
+    /// 青、白、赤と変化する。0.5が白になるので、`Transform::Signed`と合わせて正負のある場に使う
+    Diverging,
+}
+
+// Divergingの両端の色
+const DIVERGING_LOW: [f32; 3] = [0.13, 0.4, 0.67];
+const DIVERGING_HIGH: [f32; 3] = [0.7, 0.09, 0.17];
+
+// viridisの多項式近似の係数(低次から)
+const VIRIDIS: [[f32; 3]; 7] = [
+    [0.277_727_33, 0.005_407_344_5, 0.334_099_8],
+    [0.105_093_04, 1.404_613_5, 1.384_590_2],
+    [-0.330_861_83, 0.214_847_56, 0.095_095_16],
+    [-4.634_230_5, -5.799_101, -19.332_441],
+    [6.228_27, 14.179_933, 56.690_55],
+    [4.776_385, -13.745_145, -65.353_035],
+    [-5.435_456, 4.645_852_6, 26.312_435],
+];
+
+impl Colormap {
+    /// `x`(0.0〜1.0に切り詰められる)をRGBに変換する
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::visualizer::colormap::Colormap;
+    ///
+    /// assert_eq!(Colormap::Grayscale.color(0.0), [0, 0, 0]);
+    /// assert_eq!(Colormap::Grayscale.color(2.0), [255, 255, 255]);
+    /// assert_eq!(Colormap::Heat.color(0.5), [255, 127, 0]);
+    /// assert_eq!(Colormap::Diverging.color(0.5), [255, 255, 255]);
+    /// ```
+    pub fn color(self, x: f32) -> [u8; 3] {
+        let x = x.clamp(0.0, 1.0);
+        let rgb = match self {
+            Colormap::Grayscale => [x, x, x],
+            Colormap::Heat => [3.0 * x, 3.0 * x - 1.0, 3.0 * x - 2.0],
+            Colormap::Viridis => {
+                let mut rgb = [0.0; 3];
+                for (i, c) in rgb.iter_mut().enumerate() {
+                    *c = VIRIDIS
+                        .iter()
+                        .rev()
+                        .fold(0.0, |acc, coefficient| acc * x + coefficient[i]);
+                }
+                rgb
+            }
+            Colormap::Diverging => {
+                let (end, t) = if x < 0.5 {
+                    (DIVERGING_LOW, 1.0 - 2.0 * x)
+                } else {
+                    (DIVERGING_HIGH, 2.0 * x - 1.0)
+                };
+                [
+                    1.0 + (end[0] - 1.0) * t,
+                    1.0 + (end[1] - 1.0) * t,
+                    1.0 + (end[2] - 1.0) * t,
+                ]
+            }
+        };
+        [to_u8(rgb[0]), to_u8(rgb[1]), to_u8(rgb[2])]
+    }
+
+    /// fragment shaderの`u_colormap`に渡す番号
+    #[cfg(feature = "gl")]
+    pub(crate) fn shader_index(self) -> i32 {
+        match self {
+            Colormap::Grayscale => 0,
+            Colormap::Heat => 1,
+            Colormap::Viridis => 2,
+            Colormap::Diverging => 3,
+        }
+    }
+}
+
+/// 値を0.0〜1.0にするときの変換
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Transform {
+    /// 範囲の最小値が0、最大値が1になるように線形に変換する
+    #[default]
+    Linear,
+    /// 対数で変換する。最小値が正なら`log(value / min) / log(max / min)`、
+    /// そうでなければ`log(1 + value - min) / log(1 + max - min)`になる。雪崩の大きさのように桁が大きく変わる場に使う
+    Log,
+    /// 0が0.5になるように、範囲の絶対値の大きい方を±1として変換する。渦度や摂動のように正負のある場に使う
+    Signed,
+}
+
+impl Transform {
+    /// `value`を`range`(最小値, 最大値)で0.0〜1.0に変換する
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::visualizer::colormap::Transform;
+    ///
+    /// let close = |a: f32, b: f32| (a - b).abs() < 1e-6;
+    /// assert!(close(Transform::Linear.apply(0.25, (0.0, 1.0)), 0.25));
+    /// assert!(close(Transform::Log.apply(10.0, (1.0, 100.0)), 0.5));
+    /// assert!(close(Transform::Log.apply(0.0, (0.0, 99.0)), 0.0));
+    /// assert!(close(Transform::Signed.apply(0.0, (-0.2, 0.5)), 0.5));
+    /// assert!(close(Transform::Signed.apply(-0.5, (-0.2, 0.5)), 0.0));
+    /// ```
+    pub fn apply(self, value: f32, range: (f32, f32)) -> f32 {
+        match self {
+            Transform::Linear => normalize(value, range),
+            Transform::Log if range.0 > 0.0 => {
+                ((value.max(range.0) / range.0).ln() / (range.1 / range.0).ln()).clamp(0.0, 1.0)
+            }
+            Transform::Log => {
+                ((value - range.0).max(0.0).ln_1p() / (range.1 - range.0).ln_1p()).clamp(0.0, 1.0)
+            }
+            Transform::Signed => {
+                let limit = range.0.abs().max(range.1.abs());
+                (0.5 + 0.5 * value / limit).clamp(0.0, 1.0)
+            }
+        }
+    }
+
+    /// fragment shaderの`u_transform`に渡す番号
+    #[cfg(feature = "gl")]
+    pub(crate) fn shader_index(self) -> i32 {
+        match self {
+            Transform::Linear => 0,
+            Transform::Log => 1,
+            Transform::Signed => 2,
+        }
+    }
+}
+
+/// `value`を`range`(最小値, 最大値)で0.0〜1.0に正規化する
+pub fn normalize(value: f32, range: (f32, f32)) -> f32 {
+    ((value - range.0) / (range.1 - range.0)).clamp(0.0, 1.0)
+}
+
+fn to_u8(x: f32) -> u8 {
+    (x.clamp(0.0, 1.0) * 255.0) as u8
+}
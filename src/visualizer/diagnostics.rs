@@ -0,0 +1,71 @@
+use std::error::Error;
+use std::fmt;
+use visualizer::Matrix;
+
+/// NaN/Infを見つけたセルを塗る色
+pub const WARNING_COLOR: [u8; 4] = [255, 0, 255, 255];
+
+/// 描画するMatrixにNaN/Infが含まれていないかをどう検査するか
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonFiniteCheck {
+    /// 検査しない
+    Off,
+    /// 該当するセルを`WARNING_COLOR`で塗って描画を続ける
+    Highlight,
+    /// 該当するセルを見つけたら描画せずに`NonFiniteError`を返す。`draw_loop`はそこで止まる
+    Abort,
+}
+
+/// NaN/Infが見つかったときのエラー
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NonFiniteError {
+    /// 見つかったフレーム(何回目の描画か)。シミュレーションのステップ数とは限らない
+    pub frame: usize,
+    /// 見つかった盤面のシミュレーションのステップ数。`MatrixVisualizer::set_step`で設定したときだけSome
+    pub step: Option<u64>,
+    /// 行
+    pub row: usize,
+    /// 列
+    pub col: usize,
+    /// 見つかった値
+    pub value: f32,
+}
+
+impl fmt::Display for NonFiniteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "non-finite value {} at (row: {}, col: {}) in frame {}",
+            self.value, self.row, self.col, self.frame
+        )?;
+        match self.step {
+            Some(step) => write!(f, " (step {})", step),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Error for NonFiniteError {}
+
+/// 最初に見つかったNaN/Infの座標(行, 列)と値を返す
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::visualizer::diagnostics::find_non_finite;
+///
+/// let mut matrix = Array2::<f32>::zeros((4, 4));
+/// assert_eq!(find_non_finite(&matrix), None);
+///
+/// matrix[[2, 1]] = ::std::f32::INFINITY;
+/// assert_eq!(find_non_finite(&matrix), Some(((2, 1), ::std::f32::INFINITY)));
+/// ```
+pub fn find_non_finite<A: Copy + Into<f32>>(matrix: &Matrix<A>) -> Option<((usize, usize), f32)> {
+    matrix
+        .indexed_iter()
+        .map(|(index, &e)| (index, e.into()))
+        .find(|&(_, e)| !e.is_finite())
+}
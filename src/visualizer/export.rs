@@ -0,0 +1,201 @@
+use failure;
+use serde_json;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{sync_channel, SyncSender};
+use std::thread::{self, JoinHandle};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use visualizer::image::render_png;
+use visualizer::Matrix;
+use visualizer::texture::ValueMapping;
+
+// 書き込みを待っている画像の数の上限。これを超えるとpushが書き込みを待つ
+const QUEUE_SIZE: usize = 32;
+
+/// 書き出すファイル名の形式。ffmpegの`-i`にそのまま渡せる
+pub const FRAME_PATTERN: &str = "frame_%06d.png";
+
+/// `index`番目(1から)の画像のファイル名
+pub fn frame_name(index: u64) -> String {
+    format!("frame_{:06}.png", index)
+}
+
+// 書き出した1枚の画像
+struct ExportedFrame {
+    index: u64,
+    step: u64,
+    time: f64,
+    parameters: Map<String, Value>,
+}
+
+/// 盤面を`frame_000001.png`から順に番号をつけたPNGとして書き出す
+///
+/// PNGへの変換と書き込みは別のスレッドで行うので、シミュレーションのループを止めない。
+/// `finish`で書き込みを待ち、パラメータと時刻を記録した`manifest.json`を書き出す
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::visualizer::export::FrameExporter;
+/// use my_alife::visualizer::texture::ValueMapping;
+/// use std::env;
+///
+/// let directory = env::temp_dir().join("my_alife_export_example");
+/// let mut exporter = FrameExporter::new(&directory, ValueMapping::default()).unwrap();
+/// exporter.set_parameter("f", 0.04);
+/// for step in 0..3 {
+///     let frame = exporter.push(&Array2::<f32>::eye(8), step * 10).unwrap();
+///     assert_eq!(frame, directory.join(format!("frame_00000{}.png", step + 1)));
+/// }
+/// let manifest = exporter.finish().unwrap();
+/// assert!(directory.join("frame_000003.png").exists());
+/// let manifest = std::fs::read_to_string(manifest).unwrap();
+/// assert!(manifest.contains("\"pattern\": \"frame_%06d.png\""));
+/// assert!(manifest.contains("\"step\": 20"));
+/// ```
+pub struct FrameExporter {
+    directory: PathBuf,
+    mapping: ValueMapping,
+    parameters: Map<String, Value>,
+    frames: Vec<ExportedFrame>,
+    started: Instant,
+    started_at: f64,
+    sender: Option<SyncSender<(PathBuf, Matrix<f32>)>>,
+    worker: Option<JoinHandle<Result<(), failure::Error>>>,
+}
+
+impl FrameExporter {
+    /// `directory`(なければ作る)に`mapping`の色で書き出す
+    pub fn new<P: AsRef<Path>>(directory: P, mapping: ValueMapping) -> Result<FrameExporter, failure::Error> {
+        let directory = directory.as_ref().to_path_buf();
+        fs::create_dir_all(&directory)?;
+        let (sender, receiver) = sync_channel::<(PathBuf, Matrix<f32>)>(QUEUE_SIZE);
+        let worker = thread::spawn(move || {
+            for (path, matrix) in receiver {
+                fs::write(&path, render_png(&matrix, &mapping)?)
+                    .map_err(|e| format_err!("{}: {}", path.display(), e))?;
+            }
+            Ok(())
+        });
+        Ok(FrameExporter {
+            directory,
+            mapping,
+            parameters: Map::new(),
+            frames: Vec::new(),
+            started: Instant::now(),
+            started_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs_f64())
+                .unwrap_or(0.0),
+            sender: Some(sender),
+            worker: Some(worker),
+        })
+    }
+
+    /// 書き出す先
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// 色の付け方
+    pub fn mapping(&self) -> &ValueMapping {
+        &self.mapping
+    }
+
+    /// 書き出した画像の数
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// まだ画像を書き出していないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// パラメータを記録する。以降に書き出す画像のマニフェストにこの値が入る
+    pub fn set_parameter(&mut self, name: &str, value: f32) {
+        self.parameters.insert(name.to_string(), Value::from(value as f64));
+    }
+
+    /// `step`ステップ目の盤面を次の番号で書き出すように頼み、そのファイルのパスを返す
+    ///
+    /// 書き込みが失敗していたときはエラーになる
+    pub fn push<A>(&mut self, matrix: &Matrix<A>, step: u64) -> Result<PathBuf, failure::Error>
+    where
+        A: Copy + Into<f32>,
+    {
+        let index = self.frames.len() as u64 + 1;
+        let path = self.directory.join(frame_name(index));
+        let sent = self
+            .sender
+            .as_ref()
+            .map(|sender| sender.send((path.clone(), matrix.mapv(|x| x.into()))).is_ok());
+        if sent != Some(true) {
+            // スレッドが止まっているので、その理由を返す
+            return Err(self
+                .join()
+                .err()
+                .unwrap_or_else(|| format_err!("the exporter is finished")));
+        }
+        self.frames.push(ExportedFrame {
+            index,
+            step,
+            time: self.started.elapsed().as_secs_f64(),
+            parameters: self.parameters.clone(),
+        });
+        Ok(path)
+    }
+
+    fn join(&mut self) -> Result<(), failure::Error> {
+        self.sender = None;
+        match self.worker.take() {
+            Some(worker) => worker.join().map_err(|_| format_err!("the export thread panicked"))?,
+            None => Ok(()),
+        }
+    }
+
+    /// 書き込みが終わるのを待ち、マニフェストを書き出してそのパスを返す
+    pub fn finish(mut self) -> Result<PathBuf, failure::Error> {
+        self.join()?;
+        let frames: Vec<Value> = self
+            .frames
+            .iter()
+            .map(|frame| {
+                let mut entry = Map::new();
+                entry.insert("file".to_string(), Value::from(frame_name(frame.index)));
+                entry.insert("step".to_string(), Value::from(frame.step));
+                entry.insert("time".to_string(), Value::from(frame.time));
+                entry.insert("parameters".to_string(), Value::Object(frame.parameters.clone()));
+                Value::Object(entry)
+            })
+            .collect();
+        let mut manifest = Map::new();
+        manifest.insert("pattern".to_string(), Value::from(FRAME_PATTERN));
+        manifest.insert("started_at".to_string(), Value::from(self.started_at));
+        manifest.insert("frames".to_string(), Value::Array(frames));
+        let path = self.directory.join("manifest.json");
+        fs::write(&path, serde_json::to_string_pretty(&Value::Object(manifest))?)?;
+        Ok(path)
+    }
+
+    /// 書き出した画像を`fps`で動画にするffmpegのコマンド
+    pub fn ffmpeg_command(&self, fps: u32, output: &str) -> String {
+        format!(
+            "ffmpeg -framerate {} -i {} -pix_fmt yuv420p {}",
+            fps,
+            self.directory.join(FRAME_PATTERN).display(),
+            output
+        )
+    }
+}
+
+impl Drop for FrameExporter {
+    fn drop(&mut self) {
+        // finishを呼ばなくても書き込みは最後まで行う
+        let _ = self.join();
+    }
+}
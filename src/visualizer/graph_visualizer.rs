@@ -0,0 +1,487 @@
+#[cfg(feature = "gl")]
+use failure;
+#[cfg(feature = "gl")]
+use glium::{glutin, Display, Surface};
+use rand::Rng;
+#[cfg(feature = "gl")]
+use visualizer::mouse::Mouse;
+use visualizer::overlay::Canvas;
+#[cfg(feature = "gl")]
+use visualizer::overlay::OverlayRenderer;
+#[cfg(feature = "gl")]
+use visualizer::palette::Palette;
+#[cfg(feature = "gl")]
+use visualizer::texture::ValueMapping;
+#[cfg(feature = "gl")]
+use visualizer::{ControlFlow, VirtualKeyCode};
+
+// 辺と背景の色
+const EDGE_COLOR: [u8; 4] = [110, 110, 110, 255];
+const BACKGROUND: [u8; 4] = [0, 0, 0, 255];
+// 画像の縁に空ける余白(画素)
+const MARGIN: f32 = 16.0;
+// 温度の初期値と、これより下げない値
+const INITIAL_TEMPERATURE: f32 = 0.1;
+const MIN_TEMPERATURE: f32 = 0.002;
+
+/// Fruchterman–Reingold法による力学モデルのグラフの配置
+///
+/// 全ての頂点の組が反発し、辺で結ばれた頂点が引き合うように少しずつ動かす。
+/// 1回の`step`で全ての組を調べるので、数百頂点くらいまでを想定している
+///
+/// # Example
+/// ```
+/// extern crate rand;
+/// extern crate my_alife;
+///
+/// use rand::{SeedableRng, XorShiftRng};
+/// use my_alife::visualizer::graph_visualizer::ForceLayout;
+///
+/// // 12頂点の輪
+/// let edges: Vec<_> = (0..12).map(|i| (i, (i + 1) % 12)).collect();
+/// let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+/// let mut layout = ForceLayout::new(12, edges, &mut rng);
+/// layout.run(300);
+///
+/// let distance = |a: usize, b: usize| {
+///     let (pa, pb) = (layout.positions()[a], layout.positions()[b]);
+///     (pa.0 - pb.0).hypot(pa.1 - pb.1)
+/// };
+/// // 隣の頂点は、反対側の頂点よりずっと近くに置かれる
+/// assert!(distance(0, 1) * 2.0 < distance(0, 6));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ForceLayout {
+    positions: Vec<(f32, f32)>,
+    edges: Vec<(usize, usize)>,
+    // 1回の`step`で頂点が動ける距離の上限。`cooling`を掛けて下げていく
+    temperature: f32,
+    cooling: f32,
+    // 辺の自然長の倍率
+    spring_length: f32,
+}
+
+impl ForceLayout {
+    /// `nodes`個の頂点を単位正方形にランダムに置く
+    ///
+    /// # Panics
+    /// `edges`に`nodes`以上の番号の頂点が含まれるとき
+    pub fn new<R: Rng>(nodes: usize, edges: Vec<(usize, usize)>, rng: &mut R) -> ForceLayout {
+        let mut layout = ForceLayout {
+            positions: Vec::with_capacity(nodes),
+            edges: Vec::new(),
+            temperature: INITIAL_TEMPERATURE,
+            cooling: 0.98,
+            spring_length: 1.0,
+        };
+        for _ in 0..nodes {
+            layout.add_node(rng);
+        }
+        layout.set_edges(edges);
+        layout
+    }
+
+    /// 頂点の数
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// 頂点がないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// 頂点の位置
+    pub fn positions(&self) -> &[(f32, f32)] {
+        &self.positions
+    }
+
+    /// 辺
+    pub fn edges(&self) -> &[(usize, usize)] {
+        &self.edges
+    }
+
+    /// 頂点を1つランダムな位置に加え、その番号を返す
+    pub fn add_node<R: Rng>(&mut self, rng: &mut R) -> usize {
+        self.positions.push((rng.gen(), rng.gen()));
+        self.positions.len() - 1
+    }
+
+    /// 辺を入れ替える。ネットワークが変わったときに使い、配置を動かしやすくするため温度を戻す
+    ///
+    /// # Panics
+    /// 頂点の数以上の番号の頂点が含まれるとき
+    pub fn set_edges(&mut self, edges: Vec<(usize, usize)>) {
+        let nodes = self.positions.len();
+        assert!(
+            edges.iter().all(|&(a, b)| a < nodes && b < nodes),
+            "edge refers to a node that does not exist"
+        );
+        self.edges = edges;
+        self.reheat();
+    }
+
+    /// 1回の`step`ごとに温度に掛ける値(0.0〜1.0)を設定する。初期値は0.98
+    pub fn set_cooling(&mut self, cooling: f32) {
+        self.cooling = cooling.clamp(0.0, 1.0);
+    }
+
+    /// 辺の自然長の倍率を設定する。初期値は1.0
+    pub fn set_spring_length(&mut self, spring_length: f32) {
+        self.spring_length = spring_length.max(f32::EPSILON);
+    }
+
+    /// 温度を初期値に戻し、大きく動けるようにする
+    pub fn reheat(&mut self) {
+        self.temperature = INITIAL_TEMPERATURE;
+    }
+
+    /// 1回動かし、動いた距離の最大値を返す
+    pub fn step(&mut self) -> f32 {
+        let n = self.positions.len();
+        if n == 0 {
+            return 0.0;
+        }
+        // 辺の自然長。全ての頂点が単位正方形に均等に並ぶときの間隔
+        let k = self.spring_length / (n as f32).sqrt();
+        let mut displacement = vec![(0.0f32, 0.0f32); n];
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let (dx, dy, d) = self.delta(i, j);
+                let force = k * k / d;
+                displacement[i].0 += dx / d * force;
+                displacement[i].1 += dy / d * force;
+                displacement[j].0 -= dx / d * force;
+                displacement[j].1 -= dy / d * force;
+            }
+        }
+        for &(a, b) in &self.edges {
+            if a == b {
+                continue;
+            }
+            let (dx, dy, d) = self.delta(a, b);
+            let force = d * d / k;
+            displacement[a].0 -= dx / d * force;
+            displacement[a].1 -= dy / d * force;
+            displacement[b].0 += dx / d * force;
+            displacement[b].1 += dy / d * force;
+        }
+        let mut moved = 0.0f32;
+        for (position, &(dx, dy)) in self.positions.iter_mut().zip(&displacement) {
+            let length = dx.hypot(dy);
+            if length > 0.0 {
+                let step = length.min(self.temperature);
+                position.0 += dx / length * step;
+                position.1 += dy / length * step;
+                moved = moved.max(step);
+            }
+        }
+        self.temperature = (self.temperature * self.cooling).max(MIN_TEMPERATURE);
+        moved
+    }
+
+    /// `iterations`回動かす
+    pub fn run(&mut self, iterations: usize) {
+        for _ in 0..iterations {
+            self.step();
+        }
+    }
+
+    // 頂点`j`から`i`への(x, y, 距離)。重なっているときは少しずらす
+    fn delta(&self, i: usize, j: usize) -> (f32, f32, f32) {
+        let (dx, dy) = (
+            self.positions[i].0 - self.positions[j].0,
+            self.positions[i].1 - self.positions[j].1,
+        );
+        let d = dx.hypot(dy);
+        if d > 1e-6 {
+            (dx, dy, d)
+        } else {
+            (1e-6 * (i as f32 - j as f32).signum(), 0.0, 1e-6)
+        }
+    }
+
+    // 頂点の位置の(最小のx, 最小のy, 最大のx, 最大のy)
+    fn bounds(&self) -> (f32, f32, f32, f32) {
+        self.positions.iter().fold(
+            (f32::INFINITY, f32::INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+            |(x0, y0, x1, y1), &(x, y)| (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+        )
+    }
+
+    /// 全体が`size`(幅, 高さ)の画像に収まるように拡大したときの、頂点`node`の画素の位置
+    pub fn to_pixel(&self, node: usize, size: (usize, usize)) -> (f32, f32) {
+        let (x0, y0, x1, y1) = self.bounds();
+        let (width, height) = (size.0 as f32 - 2.0 * MARGIN, size.1 as f32 - 2.0 * MARGIN);
+        let scale = (width / (x1 - x0).max(f32::EPSILON)).min(height / (y1 - y0).max(f32::EPSILON));
+        // 縦横の比を保ったまま中央に寄せる
+        let offset = (
+            (size.0 as f32 - (x1 - x0) * scale) / 2.0,
+            (size.1 as f32 - (y1 - y0) * scale) / 2.0,
+        );
+        let (x, y) = self.positions[node];
+        (offset.0 + (x - x0) * scale, offset.1 + (y - y0) * scale)
+    }
+
+    /// `size`の画像の画素の位置`point`から`radius`画素以内にある一番近い頂点
+    pub fn node_at(&self, point: (f32, f32), size: (usize, usize), radius: f32) -> Option<usize> {
+        (0..self.len())
+            .map(|node| {
+                let (x, y) = self.to_pixel(node, size);
+                (node, (x - point.0).hypot(y - point.1))
+            })
+            .filter(|&(_, d)| d <= radius)
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .map(|(node, _)| node)
+    }
+}
+
+/// `layout`の辺と、`colors`で塗った半径`node_radius`画素の頂点を`size`(幅, 高さ)の画像に描く
+///
+/// `colors`が頂点より少ないときは、足りない頂点を白で塗る
+///
+/// # Example
+/// ```
+/// extern crate rand;
+/// extern crate my_alife;
+///
+/// use rand::{SeedableRng, XorShiftRng};
+/// use my_alife::visualizer::graph_visualizer::{render_graph, ForceLayout};
+///
+/// let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+/// let layout = ForceLayout::new(2, vec![(0, 1)], &mut rng);
+/// let canvas = render_graph(&layout, &[[255, 0, 0], [0, 0, 255]], (200, 100), 4.0);
+/// assert_eq!((canvas.width(), canvas.height()), (200, 100));
+///
+/// let (x, y) = layout.to_pixel(1, (200, 100));
+/// assert_eq!(canvas.pixel(x as usize, y as usize), [0, 0, 255, 255]);
+/// assert_eq!(layout.node_at((x + 1.0, y), (200, 100), 4.0), Some(1));
+/// ```
+pub fn render_graph(layout: &ForceLayout, colors: &[[u8; 3]], size: (usize, usize), node_radius: f32) -> Canvas {
+    let (width, height) = size;
+    let mut canvas = Canvas::new(width, height);
+    canvas.fill_rect(0, 0, width, height, BACKGROUND);
+    if layout.is_empty() {
+        return canvas;
+    }
+    let pixels: Vec<_> = (0..layout.len()).map(|node| layout.to_pixel(node, size)).collect();
+    for &(a, b) in layout.edges() {
+        canvas.draw_line(pixels[a], pixels[b], EDGE_COLOR);
+    }
+    for (node, &(x, y)) in pixels.iter().enumerate() {
+        let [r, g, b] = colors.get(node).cloned().unwrap_or([255, 255, 255]);
+        fill_circle(&mut canvas, (x, y), node_radius, [r, g, b, 255]);
+    }
+    canvas
+}
+
+fn fill_circle(canvas: &mut Canvas, center: (f32, f32), radius: f32, color: [u8; 4]) {
+    let (x0, x1) = (
+        (center.0 - radius).floor().max(0.0),
+        (center.0 + radius).ceil().max(0.0),
+    );
+    let (y0, y1) = (
+        (center.1 - radius).floor().max(0.0),
+        (center.1 + radius).ceil().max(0.0),
+    );
+    for y in y0 as usize..=y1 as usize {
+        for x in x0 as usize..=x1 as usize {
+            let (dx, dy) = (x as f32 + 0.5 - center.0, y as f32 + 0.5 - center.1);
+            if dx * dx + dy * dy <= radius * radius {
+                canvas.set_pixel(x, y, color);
+            }
+        }
+    }
+}
+
+/// 力学モデルで配置したグラフを、頂点ごとの状態で色を付けてvisualizeする構造体
+///
+/// ランダムブール・ネットワークや系統樹、ネットワーク上の感染症のモデルを表示するために使う。
+/// 描画するたびに配置を`set_layout_steps`回だけ動かすので、動いていく様子も見える
+///
+/// # Example
+/// ```no_run
+/// extern crate rand;
+/// extern crate my_alife;
+///
+/// use my_alife::visualizer::graph_visualizer::GraphVisualizer;
+/// use my_alife::visualizer::palette::Palette;
+/// use my_alife::visualizer::ControlFlow;
+///
+/// let edges: Vec<_> = (0..30).map(|i| (i, (i * 7 + 3) % 30)).collect();
+/// let mut graph = GraphVisualizer::new("Network", 30, edges, &mut rand::thread_rng()).unwrap();
+/// let palette = Palette::new().with("Off", [40, 40, 120]).with("On", [250, 220, 60]);
+/// let mut states = vec![0u8; 30];
+/// loop {
+///     states.rotate_left(1);
+///     states[0] ^= 1;
+///     if graph.draw_states(&states, &palette).unwrap() == ControlFlow::Stop {
+///         break;
+///     }
+/// }
+/// ```
+#[cfg(feature = "gl")]
+pub struct GraphVisualizer {
+    events_loop: glutin::EventsLoop,
+    display: Display,
+    overlay: OverlayRenderer,
+    layout: ForceLayout,
+    layout_steps: usize,
+    node_radius: f32,
+    mouse: Mouse,
+    // 直前の`poll_events`の間に押されたキー
+    keys: Vec<VirtualKeyCode>,
+}
+
+#[cfg(feature = "gl")]
+impl GraphVisualizer {
+    /// 600×600のウィンドウを開き、`nodes`個の頂点と`edges`の辺のグラフを描く
+    pub fn new<R: Rng>(
+        title: &str,
+        nodes: usize,
+        edges: Vec<(usize, usize)>,
+        rng: &mut R,
+    ) -> Result<GraphVisualizer, failure::Error> {
+        let events_loop = glutin::EventsLoop::new();
+        let window = glutin::WindowBuilder::new()
+            .with_dimensions((600, 600).into())
+            .with_title(title);
+        let context = glutin::ContextBuilder::new();
+        let display = Display::new(window, context, &events_loop).map_err(|e| format_err!("{}", e))?;
+        let overlay = OverlayRenderer::new(&display)?;
+        Ok(GraphVisualizer {
+            events_loop,
+            display,
+            overlay,
+            layout: ForceLayout::new(nodes, edges, rng),
+            layout_steps: 5,
+            node_radius: 5.0,
+            mouse: Mouse::default(),
+            keys: Vec::new(),
+        })
+    }
+
+    /// ウィンドウのタイトルを変更する
+    pub fn set_title(&self, title: &str) {
+        self.display.gl_window().set_title(title);
+    }
+
+    /// 描画するたびに配置を動かす回数を設定する。0なら配置を止める。初期値は5
+    pub fn set_layout_steps(&mut self, layout_steps: usize) {
+        self.layout_steps = layout_steps;
+    }
+
+    /// 頂点の半径(画素)を設定する。初期値は5.0
+    pub fn set_node_radius(&mut self, node_radius: f32) {
+        self.node_radius = node_radius;
+    }
+
+    /// 配置
+    pub fn layout(&self) -> &ForceLayout {
+        &self.layout
+    }
+
+    /// 配置。頂点や辺を変えるときに使う
+    pub fn layout_mut(&mut self) -> &mut ForceLayout {
+        &mut self.layout
+    }
+
+    /// マウスの状態
+    pub fn mouse(&self) -> &Mouse {
+        &self.mouse
+    }
+
+    /// 直前の`poll_events`の間に押されたキー
+    pub fn pressed_keys(&self) -> &[VirtualKeyCode] {
+        &self.keys
+    }
+
+    /// マウスが指している頂点
+    pub fn mouse_node(&self) -> Option<usize> {
+        let (x, y) = self.mouse.position?;
+        let (width, height) = self.window_size()?;
+        self.layout.node_at(
+            (x as f32, y as f32),
+            (width as usize, height as usize),
+            self.node_radius,
+        )
+    }
+
+    fn window_size(&self) -> Option<(f64, f64)> {
+        let size = self.display.gl_window().get_inner_size()?;
+        Some((size.width, size.height))
+    }
+
+    /// 頂点ごとの色で1フレームだけ描画し、溜まっているイベントを処理する
+    pub fn draw_colors(&mut self, colors: &[[u8; 3]]) -> Result<ControlFlow, failure::Error> {
+        self.layout.run(self.layout_steps);
+        let mut target = self.display.draw();
+        let (width, height) = target.get_dimensions();
+        let canvas = render_graph(
+            &self.layout,
+            colors,
+            (width as usize, height as usize),
+            self.node_radius,
+        );
+        target.clear_color(0.0, 0.0, 0.0, 1.0);
+        let drawn = self.overlay.draw(&self.display, &mut target, &canvas, (0, 0));
+        target.finish()?;
+        drawn?;
+        Ok(self.poll_events())
+    }
+
+    /// 頂点ごとの値を`mapping`で色にして描画する
+    pub fn draw_values(&mut self, values: &[f32], mapping: &ValueMapping) -> Result<ControlFlow, failure::Error> {
+        let colors: Vec<_> = values
+            .iter()
+            .map(|&value| {
+                let [r, g, b, _] = mapping.rgba(value);
+                [r, g, b]
+            })
+            .collect();
+        self.draw_colors(&colors)
+    }
+
+    /// 頂点ごとの状態を`palette`の色で描画する
+    pub fn draw_states(&mut self, states: &[u8], palette: &Palette) -> Result<ControlFlow, failure::Error> {
+        let colors: Vec<_> = states.iter().map(|&state| palette.color(state)).collect();
+        self.draw_colors(&colors)
+    }
+
+    /// 溜まっているイベントを処理し、描画を止めるときは`ControlFlow::Stop`を返す
+    pub fn poll_events(&mut self) -> ControlFlow {
+        let mut flow = ControlFlow::Continue;
+        let mouse = &mut self.mouse;
+        let keys = &mut self.keys;
+        keys.clear();
+        self.events_loop.poll_events(|event| {
+            if let glutin::Event::WindowEvent { event, .. } = event {
+                match event {
+                    glutin::WindowEvent::CloseRequested => flow = ControlFlow::Stop,
+                    glutin::WindowEvent::CursorMoved {
+                        position, modifiers, ..
+                    } => {
+                        mouse.position = Some((position.x, position.y));
+                        mouse.shift = modifiers.shift;
+                    }
+                    glutin::WindowEvent::CursorLeft { .. } => mouse.position = None,
+                    glutin::WindowEvent::MouseInput { state, button, .. } => {
+                        let pressed = state == glutin::ElementState::Pressed;
+                        match button {
+                            glutin::MouseButton::Left => mouse.left = pressed,
+                            glutin::MouseButton::Right => mouse.right = pressed,
+                            _ => {}
+                        }
+                    }
+                    glutin::WindowEvent::KeyboardInput { input, .. } => {
+                        if let (Some(key), glutin::ElementState::Pressed) = (input.virtual_keycode, input.state) {
+                            keys.push(key);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        });
+        flow
+    }
+}
@@ -0,0 +1,157 @@
+use failure;
+use ndarray::Array2;
+use png;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use visualizer::Matrix;
+use visualizer::texture::ValueMapping;
+
+const BASE64_TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// `matrix`をウィンドウで表示するときと同じ色のRGBA(1セル4byte、行優先)に変換する
+pub fn to_rgba<A>(matrix: &Matrix<A>, mapping: &ValueMapping) -> Vec<u8>
+where
+    A: Copy + Into<f32>,
+{
+    let mut rgba = Vec::with_capacity(matrix.len() * 4);
+    for e in matrix.iter() {
+        rgba.extend_from_slice(&mapping.rgba((*e).into()));
+    }
+    rgba
+}
+
+/// `matrix`をウィンドウを開かずにPNGに変換する。1セルが1画素になる
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::visualizer::image::render_png;
+/// use my_alife::visualizer::texture::ValueMapping;
+///
+/// let matrix = Array2::<f32>::zeros((16, 32));
+/// let png = render_png(&matrix, &ValueMapping::default()).unwrap();
+/// assert_eq!(&png[1..4], b"PNG");
+/// ```
+pub fn render_png<A>(matrix: &Matrix<A>, mapping: &ValueMapping) -> Result<Vec<u8>, failure::Error>
+where
+    A: Copy + Into<f32>,
+{
+    let (rows, cols) = matrix.dim();
+    let mut png = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut png, cols as u32, rows as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&to_rgba(matrix, mapping))?;
+    }
+    Ok(png)
+}
+
+/// PNGを読み込んで、各画素の明るさ(0.0〜1.0)のMatrixにする。1画素が1セルになる
+///
+/// 障害物の配置などを画像で描いて読み込むために使う
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::visualizer::image::{decode_luminance, render_png};
+/// use my_alife::visualizer::texture::ValueMapping;
+///
+/// let png = render_png(&Array2::<u8>::eye(4), &ValueMapping::default()).unwrap();
+/// assert_eq!(decode_luminance(&png).unwrap(), Array2::<f32>::eye(4));
+/// ```
+pub fn decode_luminance(bytes: &[u8]) -> Result<Matrix<f32>, failure::Error> {
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+    let channels = info.color_type.samples();
+    let luminance = |pixel: &[u8]| match channels {
+        1 | 2 => f32::from(pixel[0]) / 255.0,
+        _ => (0.299 * f32::from(pixel[0]) + 0.587 * f32::from(pixel[1]) + 0.114 * f32::from(pixel[2])) / 255.0,
+    };
+    let (rows, cols) = (info.height as usize, info.width as usize);
+    Ok(Array2::from_shape_fn((rows, cols), |(row, col)| {
+        let start = row * info.line_size + col * channels;
+        luminance(&buffer[start..start + channels])
+    }))
+}
+
+/// `path`のPNGファイルを`decode_luminance`で読み込む
+pub fn load_luminance<P: AsRef<Path>>(path: P) -> Result<Matrix<f32>, failure::Error> {
+    let mut bytes = Vec::new();
+    File::open(path)?.read_to_end(&mut bytes)?;
+    decode_luminance(&bytes)
+}
+
+/// メモリ上のPNG画像
+///
+/// [evcxr](https://github.com/evcxr/evcxr)のJupyter kernelでは、セルの最後の式にすると画像として表示される
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::visualizer::image::PngImage;
+/// use my_alife::visualizer::texture::ValueMapping;
+///
+/// let image = PngImage::render(&Array2::<u8>::eye(8), &ValueMapping::default()).unwrap();
+/// assert!(image.to_base64().starts_with("iVBORw0KGgo"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PngImage {
+    /// PNGのバイト列
+    pub bytes: Vec<u8>,
+}
+
+impl PngImage {
+    /// `matrix`をPNGに変換する
+    pub fn render<A>(matrix: &Matrix<A>, mapping: &ValueMapping) -> Result<PngImage, failure::Error>
+    where
+        A: Copy + Into<f32>,
+    {
+        Ok(PngImage {
+            bytes: render_png(matrix, mapping)?,
+        })
+    }
+
+    /// base64で表したPNG
+    pub fn to_base64(&self) -> String {
+        base64(&self.bytes)
+    }
+
+    /// evcxrが画像を表示するときに呼ぶ
+    pub fn evcxr_display(&self) {
+        println!("EVCXR_BEGIN_CONTENT image/png\n{}\nEVCXR_END_CONTENT", self.to_base64());
+    }
+}
+
+/// `bytes`をbase64で表す
+pub(crate) fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64_TABLE[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
@@ -0,0 +1,160 @@
+#[cfg(feature = "gl")]
+use failure;
+#[cfg(feature = "gl")]
+use visualizer::matrix_visualizer::MatrixVisualizer;
+#[cfg(feature = "gl")]
+use visualizer::ControlFlow;
+use visualizer::Matrix;
+
+/// 遺伝子型の空間の2次元の断面で評価した適応度地形と、その上を動く集団の軌跡
+///
+/// 列が`axes.0`番目の遺伝子、行が`axes.1`番目の遺伝子に対応し、それ以外の遺伝子は`base`の値に固定する
+///
+/// # Example
+/// ```
+/// use my_alife::visualizer::landscape::FitnessLandscape;
+///
+/// let fitness = |genes: &[f32]| -(genes[0] * genes[0] + genes[2] * genes[2]);
+/// let mut landscape = FitnessLandscape::evaluate(&[0.0; 3], (0, 2), (-1.0, 1.0), (-1.0, 1.0), (20, 20), fitness);
+/// assert_eq!(landscape.values().dim(), (20, 20));
+/// // 原点は中央のセルに入る
+/// assert_eq!(landscape.cell(&[0.0, 5.0, 0.0]), Some((10, 10)));
+/// assert_eq!(landscape.cell(&[2.0, 0.0, 0.0]), None);
+///
+/// let population = vec![vec![0.5, 0.0, 0.5], vec![-0.5, 0.0, -0.5]];
+/// landscape.record(&population);
+/// let (min, max) = landscape.range();
+/// let overlay = landscape.overlay(&population);
+/// // 個体は最大値、集団の重心の軌跡は最小値で描かれる
+/// assert_eq!(overlay[landscape.cell(&population[0]).unwrap()], max);
+/// assert_eq!(overlay[[10, 10]], min);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FitnessLandscape {
+    base: Vec<f32>,
+    axes: (usize, usize),
+    x_range: (f32, f32),
+    y_range: (f32, f32),
+    values: Matrix<f32>,
+    trail: Vec<(usize, usize)>,
+}
+
+impl FitnessLandscape {
+    /// 断面の各セルの中心で`fitness`を評価する
+    ///
+    /// # Arguments
+    /// * `base` - 断面に含まれない遺伝子の値
+    /// * `axes` - 列(x)と行(y)にとる遺伝子の番号
+    /// * `x_range` - 列にとる遺伝子の範囲
+    /// * `y_range` - 行にとる遺伝子の範囲
+    /// * `resolution` - (行数, 列数)
+    /// * `fitness` - 適応度関数
+    pub fn evaluate<F: Fn(&[f32]) -> f32>(
+        base: &[f32],
+        axes: (usize, usize),
+        x_range: (f32, f32),
+        y_range: (f32, f32),
+        resolution: (usize, usize),
+        fitness: F,
+    ) -> FitnessLandscape {
+        assert!(axes.0 < base.len() && axes.1 < base.len() && axes.0 != axes.1);
+        let (rows, cols) = resolution;
+        let mut genes = base.to_vec();
+        let values = Matrix::from_shape_fn(resolution, |(row, col)| {
+            genes[axes.0] = x_range.0 + (col as f32 + 0.5) / cols as f32 * (x_range.1 - x_range.0);
+            genes[axes.1] = y_range.0 + (row as f32 + 0.5) / rows as f32 * (y_range.1 - y_range.0);
+            fitness(&genes)
+        });
+        FitnessLandscape {
+            base: base.to_vec(),
+            axes,
+            x_range,
+            y_range,
+            values,
+            trail: Vec::new(),
+        }
+    }
+
+    /// 断面に含まれない遺伝子の値
+    pub fn base(&self) -> &[f32] {
+        &self.base
+    }
+
+    /// 評価した適応度
+    pub fn values(&self) -> &Matrix<f32> {
+        &self.values
+    }
+
+    /// 有限な適応度の(最小値, 最大値)
+    pub fn range(&self) -> (f32, f32) {
+        self.values
+            .iter()
+            .filter(|v| v.is_finite())
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &v| {
+                (min.min(v), max.max(v))
+            })
+    }
+
+    /// 遺伝子型`genome`を断面に射影したセル。範囲の外なら`None`
+    pub fn cell(&self, genome: &[f32]) -> Option<(usize, usize)> {
+        let (rows, cols) = self.values.dim();
+        let index = |value: f32, (min, max): (f32, f32), size: usize| {
+            let t = (value - min) / (max - min);
+            if (0.0..1.0).contains(&t) {
+                Some((t * size as f32) as usize)
+            } else {
+                None
+            }
+        };
+        let col = index(genome[self.axes.0], self.x_range, cols)?;
+        let row = index(genome[self.axes.1], self.y_range, rows)?;
+        Some((row, col))
+    }
+
+    /// 集団の重心を軌跡に加える
+    pub fn record(&mut self, population: &[Vec<f32>]) {
+        if population.is_empty() {
+            return;
+        }
+        let mut centroid = self.base.clone();
+        for &axis in &[self.axes.0, self.axes.1] {
+            centroid[axis] = population.iter().map(|genes| genes[axis]).sum::<f32>() / population.len() as f32;
+        }
+        if let Some(cell) = self.cell(&centroid) {
+            if self.trail.last() != Some(&cell) {
+                self.trail.push(cell);
+            }
+        }
+    }
+
+    /// これまでの重心の軌跡
+    pub fn trail(&self) -> &[(usize, usize)] {
+        &self.trail
+    }
+
+    /// 地形に、重心の軌跡(最小値)と`population`の個体(最大値)を重ねた行列
+    pub fn overlay(&self, population: &[Vec<f32>]) -> Matrix<f32> {
+        let (min, max) = self.range();
+        let mut overlay = self.values.clone();
+        for &cell in &self.trail {
+            overlay[cell] = min;
+        }
+        for cell in population.iter().filter_map(|genes| self.cell(genes)) {
+            overlay[cell] = max;
+        }
+        overlay
+    }
+
+    /// 重心を軌跡に加え、地形と集団を`matrix`に描く
+    #[cfg(feature = "gl")]
+    pub fn render(
+        &mut self,
+        matrix: &mut MatrixVisualizer,
+        population: &[Vec<f32>],
+    ) -> Result<ControlFlow, failure::Error> {
+        self.record(population);
+        let (min, max) = self.range();
+        matrix.set_value_range(min, max);
+        matrix.render_frame(&self.overlay(population))
+    }
+}
@@ -1,20 +1,191 @@
+use algorithm::coarse_grain::{factor_to_fit, reduced_dim, Downsampling};
+use algorithm::cycle::{state_hash, Cycle, CycleDetector};
+use algorithm::dirty_tiles::DirtyTiles;
+use algorithm::pool::BufferPool;
 use failure;
-use glium::{glutin, index, texture, Display, Program, Surface, VertexBuffer};
-use ndarray::{ArrayBase, Dim, OwnedRepr};
+use glium::program::ProgramCreationInput;
+use glium::uniforms::{MagnifySamplerFilter, MinifySamplerFilter, Uniforms};
+use glium::{glutin, index, Display, Program, Surface, VertexBuffer};
+use std::cell::RefCell;
 use std::fs::File;
 use std::io;
 use std::io::prelude::*;
-use visualizer::WindowStatus;
+use visualizer::diagnostics::{self, NonFiniteCheck, NonFiniteError};
+use visualizer::camera::Camera;
+use visualizer::minimap::Minimap;
+use visualizer::mouse::Mouse;
+use visualizer::overlay::{Annotations, Canvas, OverlayRenderer};
+use visualizer::palette::Palette;
+use visualizer::colormap::{Colormap, Transform};
+use visualizer::profiler::{Phase, Profiler, Stopwatch};
+use visualizer::texture::{Adjustment, ColorSpace, GpuTexture, TextureFormat, TextureUploader, ValueMapping};
+/// `visualizer::Matrix`と同じもの
+pub use visualizer::Matrix;
+use visualizer::{ControlFlow, Draw, FrameInfo, WindowHandle, WindowStatus};
+
+/// `MatrixVisualizer::builder`で使うバーテックスシェーダー
+pub const DEFAULT_VERTEX_SHADER: &str = "res/shaders/matrix_visualizer_vertex.glsl";
+/// `MatrixVisualizer::builder`で使うフラグメントシェーダー
+pub const DEFAULT_FRAGMENT_SHADER: &str = "res/shaders/matrix_visualizer_fragment.glsl";
+
+/// `MatrixVisualizer`の設定
+///
+/// 色はシェーダーの中でsRGBにしてから書き込むので、環境によってフレームバッファが
+/// sRGBに対応していてもいなくても同じ明るさで表示される
+#[derive(Debug, Clone)]
+pub struct MatrixVisualizerBuilder {
+    title: String,
+    vertex_glsl_path: String,
+    fragment_glsl_path: String,
+    size: (u32, u32),
+    texture_format: TextureFormat,
+    color_space: ColorSpace,
+}
+
+impl MatrixVisualizerBuilder {
+    /// シェーダーのファイルを変える。初期値は`DEFAULT_VERTEX_SHADER`と`DEFAULT_FRAGMENT_SHADER`
+    pub fn shaders(mut self, vertex_glsl_path: &str, fragment_glsl_path: &str) -> MatrixVisualizerBuilder {
+        self.vertex_glsl_path = vertex_glsl_path.to_string();
+        self.fragment_glsl_path = fragment_glsl_path.to_string();
+        self
+    }
+
+    /// ウィンドウの大きさ。初期値は600×600
+    pub fn size(mut self, width: u32, height: u32) -> MatrixVisualizerBuilder {
+        self.size = (width, height);
+        self
+    }
+
+    /// テクスチャへの転送形式。初期値は`TextureFormat::Rgba8`  
+    /// 精度が要らなければ`TextureFormat::R8`で転送量を1/4にでき、細かな濃度差を見たいときは`TextureFormat::R32F`を使う
+    pub fn texture_format(mut self, format: TextureFormat) -> MatrixVisualizerBuilder {
+        self.texture_format = format;
+        self
+    }
+
+    /// 正規化した値の色空間。初期値は`ColorSpace::Srgb`
+    pub fn color_space(mut self, color_space: ColorSpace) -> MatrixVisualizerBuilder {
+        self.color_space = color_space;
+        self
+    }
+
+    /// ウィンドウを開いてMatrixVisualizerを作る
+    pub fn build(self) -> Result<MatrixVisualizer, failure::Error> {
+        let events_loop = glutin::EventsLoop::new();
+        let window = glutin::WindowBuilder::new()
+            .with_dimensions(self.size.into())
+            .with_title(self.title.as_str());
+        let context = glutin::ContextBuilder::new();
+        let display = Display::new(window, context, &events_loop).unwrap();
+        let vertex_shader = MatrixVisualizer::glsl(&self.vertex_glsl_path)?;
+        let fragment_shader = MatrixVisualizer::glsl(&self.fragment_glsl_path)?;
+        // シェーダーがsRGBの値を出すので、gliumが暗黙にGL_FRAMEBUFFER_SRGBを有効にしないようにする
+        let program = Program::new(
+            &display,
+            ProgramCreationInput::SourceCode {
+                vertex_shader: &vertex_shader,
+                tessellation_control_shader: None,
+                tessellation_evaluation_shader: None,
+                geometry_shader: None,
+                fragment_shader: &fragment_shader,
+                transform_feedback_varyings: None,
+                outputs_srgb: true,
+                uses_point_size: false,
+            },
+        )?;
+
+        let vertex_buffer = VertexBuffer::new(&display, &MatrixVisualizer::shape()).unwrap();
+        let overlay = OverlayRenderer::new(&display)?;
+        Ok(MatrixVisualizer {
+            program,
+            overlay,
+            legend: None,
+            annotations: Annotations::new(),
+            minimap: None,
+            footer: None,
+            events_loop,
+            vertex_buffer,
+            indices: index::NoIndices(index::PrimitiveType::TrianglesList),
+            display,
+            frame_hooks: Vec::new(),
+            non_finite_check: NonFiniteCheck::Off,
+            uploader: TextureUploader::new(self.texture_format),
+            mapping: ValueMapping {
+                color_space: self.color_space,
+                ..ValueMapping::default()
+            },
+            camera: None,
+            cycle_detector: None,
+            cycle_hooks: Vec::new(),
+            downsampling: None,
+            pool: BufferPool::new(),
+            mouse: Mouse::default(),
+            drawn_dim: None,
+            drawn_frames: 0,
+            step: None,
+            keys: Vec::new(),
+            profiler: if cfg!(feature = "profiling") {
+                Some(Profiler::new())
+            } else {
+                None
+            },
+            title: RefCell::new(self.title),
+        })
+    }
+}
 
 /// 直交座標系(XY座標系)を用いてvisualizeする構造体
 pub struct MatrixVisualizer {
     program: Program,
+    overlay: OverlayRenderer,
+    // 左上に重ねて描く凡例
+    legend: Option<Canvas>,
+    annotations: Annotations,
+    // カメラがあるときに右下に重ねて描く地図
+    minimap: Option<Minimap>,
+    // 下端に重ねて描く画像
+    footer: Option<Canvas>,
     events_loop: glutin::EventsLoop,
     vertex_buffer: VertexBuffer<Vertex>,
     indices: index::NoIndices,
     display: Display,
+    frame_hooks: Vec<FrameHook>,
+    non_finite_check: NonFiniteCheck,
+    uploader: TextureUploader,
+    mapping: ValueMapping,
+    camera: Option<Camera>,
+    cycle_detector: Option<CycleDetector>,
+    cycle_hooks: Vec<CycleHook>,
+    downsampling: Option<(Downsampling, usize)>,
+    // 縮小した盤面のバッファ
+    pool: BufferPool<f32>,
+    mouse: Mouse,
+    // 最後に描画したMatrixの大きさ
+    drawn_dim: Option<(usize, usize)>,
+    // `draw`と`draw_dirty`で描画したフレームの数
+    drawn_frames: usize,
+    // `set_step`で設定した、描画する盤面のシミュレーションのステップ数
+    step: Option<u64>,
+    // 直前の`poll_events`の間に押されたキー
+    keys: Vec<glutin::VirtualKeyCode>,
+    // `profiling`フィーチャーが有効なときだけSome
+    profiler: Option<Profiler>,
+    // `set_title`で最後に設定したタイトル
+    title: RefCell<String>,
 }
 
+// 凡例とウィンドウの端との間隔(画素)
+const LEGEND_MARGIN: usize = 8;
+
+// 地図とウィンドウの端との間隔(画素)
+const MINIMAP_MARGIN: usize = 8;
+
+// `profiling`フィーチャーでタイトルの時間を書き換えるフレームの間隔
+const PROFILER_TITLE_INTERVAL: usize = 15;
+
+type FrameHook = Box<dyn FnMut(&WindowHandle, &FrameInfo)>;
+type CycleHook = Box<dyn FnMut(&WindowHandle, &Cycle)>;
+
 impl MatrixVisualizer {
     /// MatrixVisualizerインスタンスを生成する
     ///
@@ -24,41 +195,505 @@ impl MatrixVisualizer {
     /// * `grafic_glsl_path` - グラフィックシェーダーのファイルを格納しているpath
     ///
     /// # Example
-    /// ``````
+    /// ```no_run
     /// use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
     /// let matrix_visualize = MatrixVisualizer::new(
     ///   "Gray Scott",
     ///   "res/shaders/matrix_visualizer_vertex.glsl",
     ///   "res/shaders/matrix_visualizer_fragment.glsl",
     /// ).unwrap();
-    ///
-    ///
+    /// ```
     pub fn new(
         title: &str,
         vertex_glsl_path: &str,
         faragment_glsl_path: &str,
     ) -> Result<MatrixVisualizer, failure::Error> {
-        let events_loop = glutin::EventsLoop::new();
-        let window = glutin::WindowBuilder::new()
-            .with_dimensions((600, 600).into())
-            .with_title(title);
-        let context = glutin::ContextBuilder::new();
-        let display = Display::new(window, context, &events_loop).unwrap();
-        let program = Program::from_source(
-            &display,
-            &Self::glsl(vertex_glsl_path)?,
-            &Self::glsl(faragment_glsl_path)?,
-            None,
-        )?;
+        MatrixVisualizer::builder(title)
+            .shaders(vertex_glsl_path, faragment_glsl_path)
+            .build()
+    }
 
-        let vertex_buffer = VertexBuffer::new(&display, &Self::shape()).unwrap();
-        Ok(MatrixVisualizer {
-            program: program,
-            events_loop: events_loop,
-            vertex_buffer: vertex_buffer,
-            indices: index::NoIndices(index::PrimitiveType::TrianglesList),
-            display: display,
-        })
+    /// ウィンドウの大きさやテクスチャの形式を決めてから作るためのbuilderを返す
+    ///
+    /// # Example
+    /// ```no_run
+    /// use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+    /// use my_alife::visualizer::texture::{ColorSpace, TextureFormat};
+    ///
+    /// let matrix = MatrixVisualizer::builder("Diffusion")
+    ///     .size(800, 800)
+    ///     .texture_format(TextureFormat::R32F)
+    ///     .color_space(ColorSpace::Linear)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn builder(title: &str) -> MatrixVisualizerBuilder {
+        MatrixVisualizerBuilder {
+            title: title.to_string(),
+            vertex_glsl_path: DEFAULT_VERTEX_SHADER.to_string(),
+            fragment_glsl_path: DEFAULT_FRAGMENT_SHADER.to_string(),
+            size: (600, 600),
+            texture_format: TextureFormat::Rgba8,
+            color_space: ColorSpace::Srgb,
+        }
+    }
+
+    /// ウィンドウのタイトルを変更する
+    pub fn set_title(&self, title: &str) {
+        *self.title.borrow_mut() = title.to_string();
+        self.window().set_title(title);
+    }
+
+    /// ウィンドウのアイコンを設定する
+    ///
+    /// # Arguments
+    /// * `rgba` - 32bitRGBAの画素データ
+    /// * `width` - アイコンの幅
+    /// * `height` - アイコンの高さ
+    pub fn set_icon(&self, rgba: Vec<u8>, width: u32, height: u32) -> Result<(), failure::Error> {
+        self.window().set_icon(rgba, width, height)
+    }
+
+    /// ウィンドウを操作するためのハンドルを返す
+    pub fn window(&self) -> WindowHandle<'_> {
+        WindowHandle::new(&self.display, self.title_suffix())
+    }
+
+    /// 各段階にかかった時間の集計。`profiling`フィーチャーが有効なときだけSomeになる
+    ///
+    /// `draw_loop`などを使うときは、盤面の更新・変換・転送・描画の時間が自動で記録される。
+    /// `render_frame`を使う自前のメインループでは、更新の時間は`profiler_mut`で記録する
+    ///
+    /// # Example
+    /// ```no_run
+    /// extern crate my_alife;
+    ///
+    /// use my_alife::algorithm::gray_scott::{initial_matrix, laplacian};
+    /// use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+    /// use my_alife::visualizer::profiler::Phase;
+    /// use my_alife::visualizer::ControlFlow;
+    ///
+    /// let mut matrix = MatrixVisualizer::new(
+    ///     "Gray Scott",
+    ///     "res/shaders/matrix_visualizer_vertex.glsl",
+    ///     "res/shaders/matrix_visualizer_fragment.glsl",
+    /// ).unwrap();
+    /// let mut uv = initial_matrix();
+    /// loop {
+    ///     let u = match matrix.profiler_mut() {
+    ///         Some(profiler) => profiler.time(Phase::Update, || laplacian(&mut uv, 0.04, 0.06).clone()),
+    ///         None => laplacian(&mut uv, 0.04, 0.06).clone(),
+    ///     };
+    ///     if matrix.render_frame(&u).unwrap() == ControlFlow::Stop {
+    ///         break;
+    ///     }
+    /// }
+    /// if let Some(profiler) = matrix.profiler() {
+    ///     println!("{}", profiler.report());
+    /// }
+    /// ```
+    pub fn profiler(&self) -> Option<&Profiler> {
+        self.profiler.as_ref()
+    }
+
+    /// 各段階にかかった時間の集計を変更するための参照
+    pub fn profiler_mut(&mut self) -> Option<&mut Profiler> {
+        self.profiler.as_mut()
+    }
+
+    fn title_suffix(&self) -> String {
+        match self.profiler {
+            Some(ref profiler) if profiler.frames() > 0 => format!(" | {}", profiler.hud()),
+            _ => String::new(),
+        }
+    }
+
+    /// OpenGLのコンテキストを返す。`GpuStepper`などGPUで計算するときに使う
+    pub fn display(&self) -> &Display {
+        &self.display
+    }
+
+    /// 1フレーム描画するごとに呼ばれるhookを登録する  
+    /// タイトルにフレーム数やパラメータを表示する、といった用途に使う
+    ///
+    /// # Example
+    /// ```no_run
+    /// use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+    ///
+    /// let mut matrix = MatrixVisualizer::new(
+    ///     "Gray Scott",
+    ///     "res/shaders/matrix_visualizer_vertex.glsl",
+    ///     "res/shaders/matrix_visualizer_fragment.glsl",
+    /// ).unwrap();
+    /// matrix.on_frame(|window, info| {
+    ///     window.set_title(&format!("Gray Scott frame: {} (f={}, k={})", info.frame, info.f, info.k));
+    /// });
+    /// ```
+    pub fn on_frame<H>(&mut self, hook: H)
+    where
+        H: FnMut(&WindowHandle, &FrameInfo) + 'static,
+    {
+        self.frame_hooks.push(Box::new(hook));
+    }
+
+    /// `draw_loop`などで描画する状態が以前と同じになったら、周期軌道に入ったとしてhookを呼んでループを終える  
+    /// 決定的なシミュレーションが同じ状態を繰り返すだけになったときに、止めずに動かし続けないようにする。
+    /// 覚えておくのは直近の`cycle::DEFAULT_WINDOW`フレームだけなので、それより長い周期は見つけない
+    ///
+    /// # Example
+    /// ```no_run
+    /// use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+    ///
+    /// let mut matrix = MatrixVisualizer::new(
+    ///     "Game of Life",
+    ///     "res/shaders/matrix_visualizer_vertex.glsl",
+    ///     "res/shaders/matrix_visualizer_fragment.glsl",
+    /// ).unwrap();
+    /// matrix.on_cycle(|_, cycle| {
+    ///     println!("period {} after {} frames", cycle.period, cycle.transient);
+    /// });
+    /// ```
+    pub fn on_cycle<H>(&mut self, hook: H)
+    where
+        H: FnMut(&WindowHandle, &Cycle) + 'static,
+    {
+        self.cycle_detector.get_or_insert_with(CycleDetector::new);
+        self.cycle_hooks.push(Box::new(hook));
+    }
+
+    /// 描画するMatrixにNaN/Infが含まれていないかを毎フレーム検査する  
+    /// 数値計算が発散していないかを調べたいときに使う。初期値は`NonFiniteCheck::Off`
+    pub fn set_non_finite_check(&mut self, check: NonFiniteCheck) {
+        self.non_finite_check = check;
+    }
+
+    /// 次に描画する盤面がシミュレーションの何ステップ目かを設定する。NaN/Infを見つけたときの`NonFiniteError::step`になる
+    ///
+    /// # Example
+    /// ```no_run
+    /// use my_alife::algorithm::gray_scott::{initial_matrix, laplacian};
+    /// use my_alife::visualizer::diagnostics::{NonFiniteCheck, NonFiniteError};
+    /// use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+    ///
+    /// let mut matrix = MatrixVisualizer::builder("Gray Scott").build().unwrap();
+    /// matrix.set_non_finite_check(NonFiniteCheck::Abort);
+    /// let mut uv = initial_matrix();
+    /// for step in 0.. {
+    ///     let u = laplacian(&mut uv, 0.04, 0.06);
+    ///     matrix.set_step(step);
+    ///     if let Err(e) = matrix.draw(u) {
+    ///         if let Some(error) = e.downcast_ref::<NonFiniteError>() {
+    ///             println!("diverged at step {:?}", error.step);
+    ///         }
+    ///         break;
+    ///     }
+    /// }
+    /// ```
+    pub fn set_step(&mut self, step: u64) {
+        self.step = Some(step);
+    }
+
+    /// テクスチャへの転送形式を変更する。初期値は`TextureFormat::Rgba8`  
+    /// 大きなグリッドでは`TextureFormat::R8`にすると転送量が1/4になる
+    pub fn set_texture_format(&mut self, format: TextureFormat) {
+        self.uploader.set_format(format);
+    }
+
+    /// 0.0〜1.0に正規化するときの値の範囲を変更する。初期値は(0.0, 1.0)
+    pub fn set_value_range(&mut self, min: f32, max: f32) {
+        self.mapping.range = (min, max);
+    }
+
+    /// 正規化した値の色空間を変更する。初期値は`ColorSpace::Srgb`  
+    /// 値が光の強さのような線形な量のときに`ColorSpace::Linear`にすると、中間の値が暗くなりすぎない
+    pub fn set_color_space(&mut self, color_space: ColorSpace) {
+        self.mapping.color_space = color_space;
+    }
+
+    /// 表示するときの露出・コントラスト・ガンマを変更する。初期値は`Adjustment::default()`で補正なし  
+    /// ウィンドウではF1/F2で露出、F3/F4でコントラスト、F5/F6でガンマを下げる/上げることができ、F8で元に戻す
+    ///
+    /// # Example
+    /// ```no_run
+    /// use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+    /// use my_alife::visualizer::texture::Adjustment;
+    ///
+    /// let mut matrix = MatrixVisualizer::builder("Diffusion").build().unwrap();
+    /// // 0.5付近に集まった値の差を4倍にして見る
+    /// matrix.set_adjustment(Adjustment { contrast: 4.0, ..Adjustment::default() });
+    /// ```
+    pub fn set_adjustment(&mut self, adjustment: Adjustment) {
+        self.mapping.adjustment = adjustment;
+    }
+
+    /// いまの表示の補正
+    pub fn adjustment(&self) -> Adjustment {
+        self.mapping.adjustment
+    }
+
+    /// 0.0〜1.0に正規化するときの変換を変更する。初期値は`Transform::Linear`
+    ///
+    /// # Example
+    /// ```no_run
+    /// use my_alife::visualizer::colormap::{Colormap, Transform};
+    /// use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+    ///
+    /// let mut matrix = MatrixVisualizer::builder("Vorticity").build().unwrap();
+    /// // -0.1〜0.1の渦度を、0を白として負を青、正を赤で表示する
+    /// matrix.set_value_range(-0.1, 0.1);
+    /// matrix.set_transform(Transform::Signed);
+    /// matrix.set_colormap(Colormap::Diverging);
+    /// ```
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.mapping.transform = transform;
+    }
+
+    /// 値を色に変換する方法を変更する。初期値は`Colormap::Grayscale`
+    pub fn set_colormap(&mut self, colormap: Colormap) {
+        self.mapping.colormap = colormap;
+    }
+
+    /// ウィンドウの左上に`palette`の凡例を重ねて描く。`None`にすると消す
+    pub fn set_legend(&mut self, palette: Option<&Palette>) {
+        self.legend = palette.map(Palette::legend);
+    }
+
+    /// 盤面の上に重ねて描く注釈
+    pub fn annotations(&self) -> &Annotations {
+        &self.annotations
+    }
+
+    /// 盤面の上に重ねて描く注釈を変更するための参照。注釈は消すまで毎フレーム描かれる
+    ///
+    /// # Example
+    /// ```no_run
+    /// use my_alife::algorithm::game_of_life::{random_cells, step};
+    /// use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+    /// use my_alife::visualizer::ControlFlow;
+    ///
+    /// let mut matrix = MatrixVisualizer::builder("Game of Life").build().unwrap();
+    /// let mut cells = random_cells((64, 64), 0.3);
+    /// for generation in 0.. {
+    ///     cells = step(&cells);
+    ///     let population = cells.iter().filter(|&&e| e == 1).count();
+    ///     let annotations = matrix.annotations_mut();
+    ///     annotations.clear();
+    ///     annotations
+    ///         .region((16, 16), 32, 32, [0, 255, 255, 255])
+    ///         .label((16.0, 16.0), &format!("gen {} pop {}", generation, population), [255, 255, 0, 255]);
+    ///     matrix.draw_states(&cells, 2).unwrap();
+    ///     if matrix.poll_events() == ControlFlow::Stop {
+    ///         break;
+    ///     }
+    /// }
+    /// ```
+    pub fn annotations_mut(&mut self) -> &mut Annotations {
+        &mut self.annotations
+    }
+
+    /// ウィンドウの下端に重ねて描く画像を変更する。`None`にすると消す  
+    /// `Timeline::render`で作ったバーを表示するのに使う
+    ///
+    /// # Example
+    /// ```no_run
+    /// extern crate my_alife;
+    ///
+    /// use my_alife::algorithm::gray_scott::{initial_matrix, laplacian};
+    /// use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+    /// use my_alife::visualizer::timeline::{Timeline, TimelineAction};
+    /// use my_alife::visualizer::ControlFlow;
+    ///
+    /// let mut matrix = MatrixVisualizer::builder("Gray Scott").build().unwrap();
+    /// let mut timeline = Timeline::new(256 << 20);
+    /// let mut uv = initial_matrix();
+    /// let mut step = 0;
+    /// loop {
+    ///     let window = matrix.window_size().unwrap_or((600.0, 600.0));
+    ///     if timeline.handle_input(matrix.mouse(), matrix.pressed_keys(), window) == TimelineAction::Branch {
+    ///         let (branched, state) = timeline.branch().unwrap();
+    ///         step = branched;
+    ///         uv = state;
+    ///         // ここで`uv`に摂動を加えて続きを動かす
+    ///     }
+    ///     if !timeline.is_scrubbing() {
+    ///         laplacian(&mut uv, 0.04, 0.06);
+    ///         step += 1;
+    ///         timeline.record(step, &uv);
+    ///     }
+    ///     matrix.set_footer(Some(timeline.render(window.0 as usize)));
+    ///     let shown = timeline.current().map_or(&uv.0, |(_, state)| &state.0).clone();
+    ///     if matrix.render_frame(&shown).unwrap() == ControlFlow::Stop {
+    ///         break;
+    ///     }
+    /// }
+    /// ```
+    pub fn set_footer(&mut self, footer: Option<Canvas>) {
+        self.footer = footer;
+    }
+
+    /// ウィンドウの中の(幅, 高さ)。マウスの位置と同じ単位
+    pub fn window_size(&self) -> Option<(f64, f64)> {
+        let size = self.display.gl_window().get_inner_size()?;
+        Some((size.width, size.height))
+    }
+
+    /// 縦横どちらかが`max_size`を超えるMatrixを、`downsampling`で縮小してから描画する  
+    /// 4096×4096のような大きな盤面を、小さなウィンドウでもエイリアシングなしに表示するために使う。初期値は`None`
+    pub fn set_downsampling(&mut self, downsampling: Option<(Downsampling, usize)>) {
+        self.downsampling = downsampling;
+    }
+
+    /// マウスの状態。`poll_events`を呼ぶたびに更新される
+    pub fn mouse(&self) -> &Mouse {
+        &self.mouse
+    }
+
+    /// 直前の`poll_events`の間に押されたキー。押された順に並ぶ
+    pub fn pressed_keys(&self) -> &[glutin::VirtualKeyCode] {
+        &self.keys
+    }
+
+    /// マウスのカーソルが指している、最後に描画したMatrixのセルの(行, 列)
+    ///
+    /// # Example
+    /// ```no_run
+    /// extern crate ndarray;
+    /// extern crate my_alife;
+    ///
+    /// use ndarray::Array2;
+    /// use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+    /// use my_alife::visualizer::ControlFlow;
+    ///
+    /// let mut matrix = MatrixVisualizer::new(
+    ///     "Paint",
+    ///     "res/shaders/matrix_visualizer_vertex.glsl",
+    ///     "res/shaders/matrix_visualizer_fragment.glsl",
+    /// ).unwrap();
+    /// let mut canvas = Array2::<f32>::zeros((64, 64));
+    /// while matrix.render_frame(&canvas).unwrap() == ControlFlow::Continue {
+    ///     if let (Some(cell), true) = (matrix.mouse_cell(), matrix.mouse().left) {
+    ///         canvas[cell] = 1.0;
+    ///     }
+    /// }
+    /// ```
+    pub fn mouse_cell(&self) -> Option<(usize, usize)> {
+        let dim = self.drawn_dim?;
+        self.mouse.cell(self.window_size()?, dim)
+    }
+
+    /// カメラを設定する。設定すると矢印キーで視点の移動、`+`/`-`キーで拡大縮小ができる
+    /// 描画する範囲は`camera().visible_cells()`で取得して、描画する側で切り出す
+    ///
+    /// # Example
+    /// ```no_run
+    /// use my_alife::algorithm::hashlife::HashLife;
+    /// use my_alife::visualizer::camera::Camera;
+    /// use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+    /// use my_alife::visualizer::ControlFlow;
+    ///
+    /// let mut matrix = MatrixVisualizer::new(
+    ///     "HashLife",
+    ///     "res/shaders/matrix_visualizer_vertex.glsl",
+    ///     "res/shaders/matrix_visualizer_fragment.glsl",
+    /// ).unwrap();
+    /// matrix.set_camera(Some(Camera::new(600.0, 600.0)));
+    /// let mut life = HashLife::new();
+    /// loop {
+    ///     life.step(1).unwrap();
+    ///     let rect = matrix.camera().unwrap().visible_cells();
+    ///     let view = life.viewport(rect.x, rect.y, rect.width, rect.height);
+    ///     if matrix.render_frame(&view).unwrap() == ControlFlow::Stop {
+    ///         break;
+    ///     }
+    /// }
+    /// ```
+    pub fn set_camera(&mut self, camera: Option<Camera>) {
+        self.camera = camera;
+    }
+
+    /// ウィンドウの右下に、ワールド全体とカメラに映っている範囲を示す地図を重ねて描く。`None`にすると消す  
+    /// カメラが設定されているときだけ描かれ、地図を左クリックするとその場所が画面の中心になる
+    ///
+    /// # Example
+    /// ```no_run
+    /// #[macro_use(s)]
+    /// extern crate ndarray;
+    /// extern crate my_alife;
+    ///
+    /// use my_alife::algorithm::game_of_life::{random_cells, step};
+    /// use my_alife::visualizer::camera::{Camera, CellRect};
+    /// use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+    /// use my_alife::visualizer::minimap::Minimap;
+    /// use my_alife::visualizer::ControlFlow;
+    ///
+    /// let mut matrix = MatrixVisualizer::builder("Game of Life").build().unwrap();
+    /// let mut camera = Camera::new(600.0, 600.0);
+    /// camera.set_zoom(4.0);
+    /// camera.set_center((1024.0, 1024.0));
+    /// matrix.set_camera(Some(camera));
+    /// matrix.set_minimap(Some(Minimap::new(CellRect { x: 0, y: 0, width: 2048, height: 2048 }, 160)));
+    /// let mut cells = random_cells((2048, 2048), 0.3);
+    /// for generation in 0.. {
+    ///     cells = step(&cells);
+    ///     if generation % 30 == 0 {
+    ///         let mapping = Default::default();
+    ///         matrix.minimap_mut().unwrap().set_image(&cells, &mapping);
+    ///     }
+    ///     let rect = matrix.camera().unwrap().visible_cells();
+    ///     let (x, y) = (rect.x.max(0) as usize, rect.y.max(0) as usize);
+    ///     let view = cells.slice(s![y..(y + rect.height).min(2048), x..(x + rect.width).min(2048)]);
+    ///     if matrix.render_frame(&view.to_owned()).unwrap() == ControlFlow::Stop {
+    ///         break;
+    ///     }
+    /// }
+    /// ```
+    pub fn set_minimap(&mut self, minimap: Option<Minimap>) {
+        self.minimap = minimap;
+    }
+
+    /// 設定されている地図
+    pub fn minimap(&self) -> Option<&Minimap> {
+        self.minimap.as_ref()
+    }
+
+    /// 設定されている地図を変更するための参照
+    pub fn minimap_mut(&mut self) -> Option<&mut Minimap> {
+        self.minimap.as_mut()
+    }
+
+    // 地図の左上の画素。`window`はウィンドウの(幅, 高さ)
+    fn minimap_position(minimap: &Minimap, window: (usize, usize)) -> (usize, usize) {
+        let (width, height) = minimap.size();
+        (
+            window.0.saturating_sub(width + MINIMAP_MARGIN),
+            window.1.saturating_sub(height + MINIMAP_MARGIN),
+        )
+    }
+
+    // 地図が左クリックされていたら、その場所を画面の中心にする
+    fn jump_by_minimap(&mut self) {
+        let (minimap, camera) = match (self.minimap.as_ref(), self.camera.as_mut()) {
+            (Some(minimap), Some(camera)) => (minimap, camera),
+            _ => return,
+        };
+        let (position, size) = match (self.mouse.position, self.display.gl_window().get_inner_size()) {
+            (Some(position), Some(size)) if self.mouse.left => (position, size),
+            _ => return,
+        };
+        let (left, top) = MatrixVisualizer::minimap_position(minimap, (size.width as usize, size.height as usize));
+        if let Some(world) = minimap.world_at((position.0 - left as f64, position.1 - top as f64)) {
+            camera.set_center(world);
+        }
+    }
+
+    /// 設定されているカメラ
+    pub fn camera(&self) -> Option<&Camera> {
+        self.camera.as_ref()
+    }
+
+    /// 設定されているカメラを変更するための参照
+    pub fn camera_mut(&mut self) -> Option<&mut Camera> {
+        self.camera.as_mut()
     }
 
     fn glsl(path: &str) -> Result<String, io::Error> {
@@ -123,25 +758,130 @@ impl MatrixVisualizer {
     ///
     ///
     /// ```
-    pub fn draw_loop<T, F>(
-        mut self,
-        mut initial_state: T,
+    pub fn draw_loop<T, F>(self, initial_state: T, f: f32, k: f32, mut update_fn: F) -> Result<(), failure::Error>
+    where
+        F: FnMut(&mut T, f32, f32) -> &Matrix<f32>,
+    {
+        self.draw_loop_until(initial_state, f, k, |state, f, k| {
+            (update_fn(state, f, k), ControlFlow::Continue)
+        })
+    }
+
+    /// `update_fn`から描画ループを止められるメインループ  
+    /// `update_fn`が`ControlFlow::Stop`を返すと、そのフレームを描画した後にループを抜けて`Ok(())`を返す
+    ///
+    /// # Arguments
+    /// * `initail_state` - 初期状態
+    /// * `unpdate_fn` - 描画する状態をどのように変更するかの関数。ループを続けるかどうかも返す
+    ///
+    /// # Example
+    /// ```no_run
+    /// extern crate my_alife;
+    ///
+    /// use my_alife::algorithm::gray_scott::{initial_matrix, laplacian};
+    /// use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+    /// use my_alife::visualizer::ControlFlow;
+    ///
+    /// let matrix = MatrixVisualizer::new(
+    ///     "Gray Scott",
+    ///     "res/shaders/matrix_visualizer_vertex.glsl",
+    ///     "res/shaders/matrix_visualizer_fragment.glsl",
+    /// ).unwrap();
+    /// // 1000フレーム描画したら終了する
+    /// let mut frame = 0;
+    /// matrix.draw_loop_until(initial_matrix(), 0.04, 0.06, |uv, f, k| {
+    ///     frame += 1;
+    ///     let flow = if frame < 1000 { ControlFlow::Continue } else { ControlFlow::Stop };
+    ///     (laplacian(uv, f, k), flow)
+    /// }).unwrap();
+    /// ```
+    pub fn draw_loop_until<T, F>(
+        self,
+        initial_state: T,
         f: f32,
         k: f32,
         mut update_fn: F,
     ) -> Result<(), failure::Error>
     where
-        F: FnMut(&mut T, f32, f32) -> &Matrix<f32>,
+        F: FnMut(&mut T, f32, f32) -> (&Matrix<f32>, ControlFlow),
     {
-        let mut window_status = WindowStatus::Open;
+        self.run_loop(initial_state, f, k, |state, f, k| Ok(update_fn(state, f, k)))
+    }
+
+    /// 失敗するかもしれない`update_fn`を使うメインループ  
+    /// `update_fn`がエラーを返すとウィンドウを閉じ、そのエラーを返す
+    ///
+    /// # Arguments
+    /// * `initail_state` - 初期状態
+    /// * `unpdate_fn` - 描画する状態をどのように変更するかの関数
+    ///
+    /// # Example
+    /// ```no_run
+    /// #[macro_use]
+    /// extern crate failure;
+    /// extern crate my_alife;
+    ///
+    /// use my_alife::algorithm::gray_scott::{initial_matrix, laplacian};
+    /// use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+    ///
+    /// # fn main() {
+    /// let matrix = MatrixVisualizer::new(
+    ///     "Gray Scott",
+    ///     "res/shaders/matrix_visualizer_vertex.glsl",
+    ///     "res/shaders/matrix_visualizer_fragment.glsl",
+    /// ).unwrap();
+    /// let result = matrix.try_draw_loop(initial_matrix(), 0.04, 0.06, |uv, f, k| {
+    ///     let u = laplacian(uv, f, k);
+    ///     if u.iter().any(|e| e.is_nan()) {
+    ///         return Err(format_err!("state became NaN"));
+    ///     }
+    ///     Ok(u)
+    /// });
+    /// # }
+    /// ```
+    pub fn try_draw_loop<T, F, E>(self, initial_state: T, f: f32, k: f32, mut update_fn: F) -> Result<(), failure::Error>
+    where
+        F: FnMut(&mut T, f32, f32) -> Result<&Matrix<f32>, E>,
+        E: Into<failure::Error>,
+    {
+        self.run_loop(initial_state, f, k, |state, f, k| match update_fn(state, f, k) {
+            Ok(u) => Ok((u, ControlFlow::Continue)),
+            Err(e) => Err(e.into()),
+        })
+    }
+
+    fn run_loop<T, F>(mut self, mut state: T, f: f32, k: f32, mut update_fn: F) -> Result<(), failure::Error>
+    where
+        F: FnMut(&mut T, f32, f32) -> Result<(&Matrix<f32>, ControlFlow), failure::Error>,
+    {
+        let mut frame = 0;
         loop {
-            if window_status == WindowStatus::Close {
-                break;
+            let _frame = trace_span!("frame", frame = frame + 1);
+            let stopwatch = Stopwatch::start();
+            let (u, flow) = {
+                let _update = trace_span!("update");
+                update_fn(&mut state, f, k)?
+            };
+            if let Some(ref mut profiler) = self.profiler {
+                profiler.record(Phase::Update, stopwatch.elapsed());
             }
-            let u = update_fn(&mut initial_state, f, k);
+            frame += 1;
             self.draw(u)?;
+            self.run_frame_hooks(&FrameInfo { frame, f, k });
+            let cycle = match self.cycle_detector {
+                Some(ref mut detector) => detector.observe(state_hash(u)),
+                None => None,
+            };
+            if let Some(cycle) = cycle {
+                #[cfg(feature = "tracing")]
+                ::tracing::info!(period = cycle.period, transient = cycle.transient, "cycle detected");
+                self.run_cycle_hooks(&cycle);
+                break;
+            }
 
-            window_status = self.hadling_event();
+            if self.poll_events() == ControlFlow::Stop || flow == ControlFlow::Stop {
+                break;
+            }
         }
         Ok(())
     }
@@ -151,25 +891,347 @@ impl MatrixVisualizer {
     /// # Arguments
     /// * `matrix` - 描画される内容
     ///
-    pub fn draw(&self, matrix: &Matrix<f32>) -> Result<(), failure::Error> {
-        let image = make_texture_image(matrix);
-        let texture = texture::Texture2d::new(&self.display, image);
+    pub fn draw<A>(&mut self, matrix: &Matrix<A>) -> Result<(), failure::Error>
+    where
+        A: Copy + Into<f32>,
+    {
+        self.check_non_finite(matrix)?;
+        self.drawn_dim = Some(matrix.dim());
+        {
+            let _upload = trace_span!("upload", rows = matrix.dim().0, cols = matrix.dim().1);
+            match self.downsampling {
+                Some((downsampling, max_size)) if factor_to_fit(matrix.dim(), max_size) > 1 => {
+                    let factor = factor_to_fit(matrix.dim(), max_size);
+                    let stopwatch = Stopwatch::start();
+                    let mut reduced = self.pool.take_matrix(reduced_dim(matrix.dim(), factor), 0.0);
+                    downsampling.apply_into(matrix, factor, &mut reduced);
+                    if let Some(ref mut profiler) = self.profiler {
+                        profiler.record(Phase::Convert, stopwatch.elapsed());
+                    }
+                    let uploaded = self.uploader.upload(&self.display, &reduced, &self.mapping);
+                    self.pool.recycle(reduced);
+                    uploaded?;
+                }
+                _ => self.uploader.upload(&self.display, matrix, &self.mapping)?,
+            }
+        }
+        self.draw_texture()
+    }
+
+    /// 複数の物質の濃度を重ねて描画する。1つ目が赤、2つ目が緑、3つ目が青になる
+    ///
+    /// # Arguments
+    /// * `channels` - 描画される内容(最大3つ)
+    /// * `ranges` - 各チャンネルを正規化するときの(最小値, 最大値)。足りない分は(0.0, 1.0)
+    pub fn draw_channels(&mut self, channels: &[&Matrix<f32>], ranges: &[(f32, f32)]) -> Result<(), failure::Error> {
+        self.drawn_dim = channels.first().map(|channel| channel.dim());
+        {
+            let _upload = trace_span!("upload", channels = channels.len());
+            self.uploader.upload_channels(&self.display, channels, ranges)?;
+        }
+        self.draw_texture()
+    }
+
+    /// `draw_channels`で1フレームだけ描画し、溜まっているイベントを処理する
+    ///
+    /// # Example
+    /// ```no_run
+    /// extern crate my_alife;
+    /// extern crate rand;
+    ///
+    /// use my_alife::algorithm::integrator::Integrator;
+    /// use my_alife::algorithm::oregonator::Oregonator;
+    /// use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+    /// use my_alife::visualizer::ControlFlow;
+    ///
+    /// let mut matrix = MatrixVisualizer::new(
+    ///     "Oregonator",
+    ///     "res/shaders/matrix_visualizer_vertex.glsl",
+    ///     "res/shaders/matrix_visualizer_fragment.glsl",
+    /// ).unwrap();
+    /// let model = Oregonator::default();
+    /// let mut fields = model.initial_fields((128, 128), &mut rand::thread_rng());
+    /// loop {
+    ///     for _ in 0..20 {
+    ///         Integrator::Euler.step(&model, &mut fields, 5e-4);
+    ///     }
+    ///     let channels = [&fields[0], &fields[1], &fields[2]];
+    ///     if matrix.render_channels(&channels, &model.ranges()).unwrap() == ControlFlow::Stop {
+    ///         break;
+    ///     }
+    /// }
+    /// ```
+    pub fn render_channels(
+        &mut self,
+        channels: &[&Matrix<f32>],
+        ranges: &[(f32, f32)],
+    ) -> Result<ControlFlow, failure::Error> {
+        self.draw_channels(channels, ranges)?;
+        Ok(self.poll_events())
+    }
+
+    /// 0〜`states - 1`の状態を持つセル・オートマトンの盤面を描画する  
+    /// 1セル1byteのままR8のテクスチャに転送し、色付けはシェーダー側で行う。f32に変換する`draw`より転送量もメモリも1/4になる
+    ///
+    /// # Example
+    /// ```no_run
+    /// use my_alife::algorithm::game_of_life::{random_cells, step};
+    /// use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+    /// use my_alife::visualizer::ControlFlow;
+    ///
+    /// let mut matrix = MatrixVisualizer::new(
+    ///     "Game of Life",
+    ///     "res/shaders/matrix_visualizer_vertex.glsl",
+    ///     "res/shaders/matrix_visualizer_fragment.glsl",
+    /// ).unwrap();
+    /// let mut cells = random_cells((1024, 1024), 0.3);
+    /// loop {
+    ///     cells = step(&cells);
+    ///     matrix.draw_states(&cells, 2).unwrap();
+    ///     if matrix.poll_events() == ControlFlow::Stop {
+    ///         break;
+    ///     }
+    /// }
+    /// ```
+    pub fn draw_states(&mut self, cells: &Matrix<u8>, states: u8) -> Result<(), failure::Error> {
+        self.drawn_dim = Some(cells.dim());
+        {
+            let _upload = trace_span!("upload", rows = cells.dim().0, cols = cells.dim().1);
+            self.uploader.upload_states(&self.display, cells, states)?;
+        }
+        self.draw_texture()
+    }
+
+    /// 各セルの状態を`palette`で決めた色にして描画する  
+    /// 山火事の空き地・木・燃えている木のように、大小に意味のない状態を区別して表示するために使う
+    ///
+    /// # Example
+    /// ```no_run
+    /// extern crate my_alife;
+    /// extern crate rand;
+    ///
+    /// use my_alife::algorithm::daisyworld::LatticeDaisyworld;
+    /// use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+    /// use my_alife::visualizer::palette::Palette;
+    /// use my_alife::visualizer::ControlFlow;
+    ///
+    /// let mut matrix = MatrixVisualizer::builder("Daisyworld").build().unwrap();
+    /// let palette = Palette::new()
+    ///     .with("Bare", [120, 90, 60])
+    ///     .with("White", [240, 240, 240])
+    ///     .with("Black", [20, 20, 20]);
+    /// matrix.set_legend(Some(&palette));
+    /// let mut rng = rand::thread_rng();
+    /// let mut world = LatticeDaisyworld::random((128, 128), 1.0, &mut rng);
+    /// loop {
+    ///     world.step(&mut rng);
+    ///     matrix.draw_palette(world.cells(), &palette).unwrap();
+    ///     if matrix.poll_events() == ControlFlow::Stop {
+    ///         break;
+    ///     }
+    /// }
+    /// ```
+    pub fn draw_palette(&mut self, cells: &Matrix<u8>, palette: &Palette) -> Result<(), failure::Error> {
+        self.drawn_dim = Some(cells.dim());
+        {
+            let _upload = trace_span!("upload", rows = cells.dim().0, cols = cells.dim().1);
+            self.uploader.upload_palette(&self.display, cells, palette)?;
+        }
+        self.draw_texture()
+    }
+
+    /// `dirty`に記録されたタイルだけをテクスチャに転送し直して描画する  
+    /// 盤面のほとんどが変化しないモデルで転送量を減らすために使う
+    ///
+    /// # Arguments
+    /// * `matrix` - 描画される内容
+    /// * `dirty` - 前回の描画から変化したタイル
+    pub fn draw_dirty<A>(&mut self, matrix: &Matrix<A>, dirty: &DirtyTiles) -> Result<(), failure::Error>
+    where
+        A: Copy + Into<f32>,
+    {
+        self.check_non_finite(matrix)?;
+        self.drawn_dim = Some(matrix.dim());
+        {
+            let _upload = trace_span!("upload", tiles = dirty.rects().count());
+            self.uploader
+                .upload_regions(&self.display, matrix, &self.mapping, dirty.rects())?;
+        }
+        self.draw_texture()
+    }
+
+    // `NonFiniteCheck::Abort`のとき、NaN/Infを含むMatrixを描画せずにエラーにする
+    fn check_non_finite<A>(&mut self, matrix: &Matrix<A>) -> Result<(), failure::Error>
+    where
+        A: Copy + Into<f32>,
+    {
+        self.drawn_frames += 1;
+        self.mapping.highlight_non_finite = self.non_finite_check != NonFiniteCheck::Off;
+        if self.non_finite_check == NonFiniteCheck::Abort {
+            if let Some(((row, col), value)) = diagnostics::find_non_finite(matrix) {
+                let (frame, step) = (self.drawn_frames, self.step);
+                #[cfg(feature = "tracing")]
+                ::tracing::error!(frame, step = ?step, row, col, value, "non-finite value");
+                return Err(NonFiniteError {
+                    frame,
+                    step,
+                    row,
+                    col,
+                    value,
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    fn draw_texture(&mut self) -> Result<(), failure::Error> {
+        let (convert, upload) = self.uploader.take_timings();
+        let stopwatch = Stopwatch::start();
+        self.render_texture()?;
+        let draw = stopwatch.elapsed();
+        if let Some(ref mut profiler) = self.profiler {
+            profiler.record(Phase::Convert, convert);
+            profiler.record(Phase::Upload, upload);
+            profiler.record(Phase::Draw, draw);
+            profiler.end_frame();
+            if profiler.frames() % PROFILER_TITLE_INTERVAL == 1 {
+                let title = self.title.borrow().clone();
+                self.window().set_title(&title);
+            }
+        }
+        Ok(())
+    }
+
+    fn render_texture(&self) -> Result<(), failure::Error> {
+        let (texture, format) = match self.uploader.texture() {
+            Some(texture) => texture,
+            None => return Ok(()),
+        };
+        // 8bitの形式ではCPU側で正規化済み。ただし状態をそのまま書き込んだときは、最大の状態が1になるようにする
+        let (value_range, transform) = match self.uploader.states() {
+            Some(states) => ((0.0, (states - 1) as f32 / 255.0), Transform::Linear),
+            None if format == TextureFormat::R32F => (self.mapping.range, self.mapping.transform),
+            None => ((0.0, 1.0), Transform::Linear),
+        };
+        // sRGBのテクスチャはgliumでは別の型なので、samplerを作るところだけ分ける
+        macro_rules! draw_sampled {
+            ($texture:expr) => {
+                self.draw_quad(uniform! {
+                    u_texture: $texture
+                        .sampled()
+                        .minify_filter(MinifySamplerFilter::Linear)
+                        .magnify_filter(MagnifySamplerFilter::Linear),
+                    u_single_channel: format.is_single_channel(),
+                    u_value_range: value_range,
+                    u_transform: transform.shader_index(),
+                    u_colormap: self.mapping.colormap.shader_index(),
+                    u_highlight_non_finite: self.mapping.highlight_non_finite,
+                    u_srgb_texture: format.is_srgb(),
+                    u_linear_values: self.mapping.color_space == ColorSpace::Linear,
+                    u_exposure: self.mapping.adjustment.exposure,
+                    u_contrast: self.mapping.adjustment.contrast,
+                    u_gamma: self.mapping.adjustment.gamma,
+                })
+            };
+        }
+        match *texture {
+            GpuTexture::Linear(ref texture) => draw_sampled!(texture),
+            GpuTexture::Srgb(ref texture) => draw_sampled!(texture),
+        }
+    }
+
+    fn draw_quad<U: Uniforms>(&self, uniforms: U) -> Result<(), failure::Error> {
         let mut target = self.display.draw();
         target.clear_color(1.0, 0.0, 0.0, 1.0);
-        target.draw(
-            &self.vertex_buffer,
-            &self.indices,
-            &self.program,
-            &uniform! {u_texture: texture?.sampled()},
-            &Default::default(),
-        )?;
+        target.draw(&self.vertex_buffer, self.indices, &self.program, &uniforms, &Default::default())?;
+        if let Some(ref legend) = self.legend {
+            self.overlay
+                .draw(&self.display, &mut target, legend, (LEGEND_MARGIN, LEGEND_MARGIN))?;
+        }
+        if let (Some(minimap), Some(camera)) = (self.minimap.as_ref(), self.camera.as_ref()) {
+            let (width, height) = target.get_dimensions();
+            let position = MatrixVisualizer::minimap_position(minimap, (width as usize, height as usize));
+            self.overlay
+                .draw(&self.display, &mut target, &minimap.render(camera), position)?;
+        }
+        if let Some(ref footer) = self.footer {
+            let (_, height) = target.get_dimensions();
+            let position = (0, (height as usize).saturating_sub(footer.height()));
+            self.overlay.draw(&self.display, &mut target, footer, position)?;
+        }
+        if let (false, Some(dim)) = (self.annotations.is_empty(), self.drawn_dim) {
+            let (width, height) = target.get_dimensions();
+            let canvas = self.annotations.render(dim, (width as usize, height as usize));
+            self.overlay.draw(&self.display, &mut target, &canvas, (0, 0))?;
+        }
+        let _swap = trace_span!("swap");
         target.finish()?;
         Ok(())
     }
 
+    /// 1フレームだけ描画し、溜まっているイベントを処理する  
+    /// `draw_loop`を使わずに、自前のメインループに組み込むときに使う
+    ///
+    /// # Arguments
+    /// * `matrix` - 描画される内容
+    ///
+    /// # Example
+    /// ```no_run
+    /// extern crate my_alife;
+    ///
+    /// use my_alife::algorithm::gray_scott::{initial_matrix, laplacian};
+    /// use my_alife::visualizer::matrix_visualizer::MatrixVisualizer;
+    /// use my_alife::visualizer::ControlFlow;
+    ///
+    /// let mut matrix = MatrixVisualizer::new(
+    ///     "Gray Scott",
+    ///     "res/shaders/matrix_visualizer_vertex.glsl",
+    ///     "res/shaders/matrix_visualizer_fragment.glsl",
+    /// ).unwrap();
+    /// let mut uv = initial_matrix();
+    /// loop {
+    ///     let u = laplacian(&mut uv, 0.04, 0.06);
+    ///     if matrix.render_frame(u).unwrap() == ControlFlow::Stop {
+    ///         break;
+    ///     }
+    /// }
+    /// ```
+    pub fn render_frame<A>(&mut self, matrix: &Matrix<A>) -> Result<ControlFlow, failure::Error>
+    where
+        A: Copy + Into<f32>,
+    {
+        self.draw(matrix)?;
+        Ok(self.poll_events())
+    }
+
+    /// 溜まっているイベントを処理し、ウィンドウが閉じられたら`ControlFlow::Stop`を返す
+    pub fn poll_events(&mut self) -> ControlFlow {
+        self.hadling_event().into()
+    }
+
+    fn run_frame_hooks(&mut self, info: &FrameInfo) {
+        let window = WindowHandle::new(&self.display, self.title_suffix());
+        for hook in self.frame_hooks.iter_mut() {
+            hook(&window, info);
+        }
+    }
+
+    fn run_cycle_hooks(&mut self, cycle: &Cycle) {
+        let window = WindowHandle::new(&self.display, self.title_suffix());
+        for hook in self.cycle_hooks.iter_mut() {
+            hook(&window, cycle);
+        }
+    }
+
     /// event handler
     pub fn hadling_event(&mut self) -> WindowStatus {
         let mut status = WindowStatus::Open;
+        // closureは変数全体を借用するので、使うfieldは事前に借用しておく
+        let camera = &mut self.camera;
+        let mouse = &mut self.mouse;
+        let keys = &mut self.keys;
+        keys.clear();
         self.events_loop.poll_events(|event| {
             // matchさせたいパターンが1つしかない場合、if let 形式で書ける
             // matchでやると
@@ -181,15 +1243,43 @@ impl MatrixVisualizer {
             if let glutin::Event::WindowEvent { event, .. } = event {
                 match event {
                     glutin::WindowEvent::CloseRequested => status = WindowStatus::Close,
+                    glutin::WindowEvent::CursorMoved { position, modifiers, .. } => {
+                        mouse.position = Some((position.x, position.y));
+                        mouse.shift = modifiers.shift;
+                    }
+                    glutin::WindowEvent::CursorLeft { .. } => mouse.position = None,
+                    glutin::WindowEvent::MouseInput { state, button, modifiers, .. } => {
+                        let pressed = state == glutin::ElementState::Pressed;
+                        mouse.shift = modifiers.shift;
+                        match button {
+                            glutin::MouseButton::Left => mouse.left = pressed,
+                            glutin::MouseButton::Right => mouse.right = pressed,
+                            _ => {}
+                        }
+                    }
+                    glutin::WindowEvent::Resized(size) => {
+                        if let Some(camera) = camera.as_mut() {
+                            camera.set_viewport(size.width, size.height);
+                        }
+                    }
                     glutin::WindowEvent::KeyboardInput {
                         device_id: _,
                         input: keyboard_input,
-                    } => match keyboard_input {
-                        glutin::KeyboardInput { // 構造体の各fieldをdestructuringできる
+                    } => {
+                        let glutin::KeyboardInput { // 構造体の各fieldをdestructuringできる
                             virtual_keycode, // virtual_keycode: virtual_keycode を省略形
                             modifiers, // modifiers: my_modifiers の様に省略しないで別名をつけても良い
-                            .. // 使わないfieldのscancode: _, state: _, を省略できる
-                        } => match (virtual_keycode, modifiers) { // 複数のパターンマッチにはタプルを使う
+                            state,
+                            .. // 使わないfieldのscancode: _, を省略できる
+                        } = keyboard_input;
+                        mouse.shift = modifiers.shift;
+                        if let (Some(key), glutin::ElementState::Pressed) = (virtual_keycode, state) {
+                            keys.push(key);
+                            if let Some(camera) = camera.as_mut() {
+                                control_camera(camera, key);
+                            }
+                        }
+                        match (virtual_keycode, modifiers) { // 複数のパターンマッチにはタプルを使う
                             #[cfg(target_os = "linux")] // conditional compile https://doc.rust-lang.org/reference/attributes.html#conditional-compilation
                             (Some(glutin::VirtualKeyCode::W), glutin::ModifiersState { ctrl, .. }) => {
                               if ctrl { status = WindowStatus::Close }
@@ -199,44 +1289,87 @@ impl MatrixVisualizer {
                               if logo { status = WindowStatus::Close }
                             },
                             (_, _) => {}
-                        },
+                        }
                     },
                     _ => {}
                 }
             };
         });
-        return status;
+        for &key in &self.keys {
+            control_adjustment(&mut self.mapping.adjustment, key);
+        }
+        self.jump_by_minimap();
+        status
+    }
+}
+
+impl Draw for MatrixVisualizer {
+    fn set_title(&self, title: &str) {
+        MatrixVisualizer::set_title(self, title)
+    }
+
+    fn draw<A: Copy + Into<f32>>(&mut self, matrix: &Matrix<A>) -> Result<(), failure::Error> {
+        MatrixVisualizer::draw(self, matrix)
+    }
+
+    fn draw_states(&mut self, cells: &Matrix<u8>, states: u8) -> Result<(), failure::Error> {
+        MatrixVisualizer::draw_states(self, cells, states)
+    }
+
+    fn poll_events(&mut self) -> ControlFlow {
+        MatrixVisualizer::poll_events(self)
+    }
+}
+
+// `profiling`フィーチャーが有効なときは、閉じるときに各段階の時間の集計を書き出す
+impl Drop for MatrixVisualizer {
+    fn drop(&mut self) {
+        if let Some(ref profiler) = self.profiler {
+            if profiler.frames() > 0 {
+                eprintln!("{}", profiler);
+            }
+        }
+    }
+}
+
+// 矢印キーで視点の移動、+/-キーで拡大縮小をする
+fn control_camera(camera: &mut Camera, key: glutin::VirtualKeyCode) {
+    let (width, height) = camera.viewport();
+    let center = (width / 2.0, height / 2.0);
+    match key {
+        glutin::VirtualKeyCode::Left => camera.pan(-width / 10.0, 0.0),
+        glutin::VirtualKeyCode::Right => camera.pan(width / 10.0, 0.0),
+        glutin::VirtualKeyCode::Up => camera.pan(0.0, -height / 10.0),
+        glutin::VirtualKeyCode::Down => camera.pan(0.0, height / 10.0),
+        glutin::VirtualKeyCode::Add | glutin::VirtualKeyCode::Equals => camera.zoom_at(1.25, center),
+        glutin::VirtualKeyCode::Subtract | glutin::VirtualKeyCode::Minus => camera.zoom_at(0.8, center),
+        _ => {}
+    }
+}
+
+// F1〜F6で表示の補正を変え、F8で元に戻す
+fn control_adjustment(adjustment: &mut Adjustment, key: glutin::VirtualKeyCode) {
+    match key {
+        glutin::VirtualKeyCode::F1 => adjustment.exposure -= 0.25,
+        glutin::VirtualKeyCode::F2 => adjustment.exposure += 0.25,
+        glutin::VirtualKeyCode::F3 => adjustment.contrast /= 1.25,
+        glutin::VirtualKeyCode::F4 => adjustment.contrast *= 1.25,
+        glutin::VirtualKeyCode::F5 => adjustment.gamma /= 1.25,
+        glutin::VirtualKeyCode::F6 => adjustment.gamma *= 1.25,
+        glutin::VirtualKeyCode::F8 => *adjustment = Adjustment::default(),
+        _ => {}
     }
 }
 
-/// 直交座標系(XY座標系)においてどの座標にどんな色(グレースケール)を表示するかを表現する。  
-/// 実体は2次元配列
-pub type Matrix<T> = ArrayBase<OwnedRepr<T>, Dim<[usize; 2]>>;
 
 #[derive(Copy, Clone)]
 struct Vertex {
     a_position: [f32; 2],
     a_texcoord: [f32; 2],
 }
-implement_vertex!(Vertex, a_position, a_texcoord);
-
-fn make_texture_image<'a>(u: &Matrix<f32>) -> texture::RawImage2d<'a, u8> {
-    let mut texture_data = Vec::new();
-    for row in u.outer_iter() {
-        for e in row.iter() {
-            let v = (if *e < 0.0 {
-                0.0
-            } else if *e > 1.0 {
-                1.0
-            } else {
-                *e
-            } * 255.0) as u8;
-
-            texture_data.push(v);
-            texture_data.push(v);
-            texture_data.push(v);
-            texture_data.push(v);
-        }
-    }
-    texture::RawImage2d::from_raw_rgba(texture_data, (u.shape()[0] as u32, u.shape()[1] as u32))
+// glium 0.22のマクロ内部で非推奨のmem::uninitializedが使われている
+#[allow(deprecated)]
+mod vertex_impl {
+    use super::Vertex;
+    implement_vertex!(Vertex, a_position, a_texcoord);
 }
@@ -0,0 +1,130 @@
+use visualizer::camera::{Camera, CellRect};
+use visualizer::Matrix;
+use visualizer::overlay::Canvas;
+use visualizer::texture::ValueMapping;
+
+// 背景・枠・見えている範囲の色
+const BACKGROUND: [u8; 4] = [0, 0, 0, 160];
+const BORDER: [u8; 4] = [255, 255, 255, 200];
+const VIEWPORT: [u8; 4] = [255, 220, 0, 255];
+
+/// ワールド全体を小さく表示し、いまカメラに映っている範囲を枠で示す地図
+///
+/// `MatrixVisualizer::set_minimap`で設定するとウィンドウの右下に重ねて描かれ、地図をクリックするとその場所に視点が移る
+///
+/// # Example
+/// ```
+/// use my_alife::visualizer::camera::{Camera, CellRect};
+/// use my_alife::visualizer::minimap::Minimap;
+///
+/// // 1024×512セルのワールドを長辺128画素の地図にする
+/// let minimap = Minimap::new(CellRect { x: 0, y: 0, width: 1024, height: 512 }, 128);
+/// assert_eq!(minimap.size(), (128, 64));
+/// assert_eq!(minimap.world_at((64.0, 32.0)), Some((512.0, 256.0)));
+/// assert_eq!(minimap.world_at((200.0, 32.0)), None);
+///
+/// // 画面の中心がワールドの中心で、1セル4画素なら150×150セルが見えている
+/// let mut camera = Camera::new(600.0, 600.0);
+/// camera.set_zoom(4.0);
+/// camera.set_center((512.0, 256.0));
+/// let canvas = minimap.render(&camera);
+/// assert_eq!((canvas.width(), canvas.height()), (128, 64));
+/// // 見えている範囲の左端
+/// assert_eq!(canvas.pixel(54, 32), [255, 220, 0, 255]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Minimap {
+    bounds: CellRect,
+    size: (usize, usize),
+    image: Option<Canvas>,
+}
+
+impl Minimap {
+    /// ワールドの`bounds`の範囲を、長辺が`max_size`画素になるように縮めて表示する地図
+    pub fn new(bounds: CellRect, max_size: usize) -> Minimap {
+        let longest = bounds.width.max(bounds.height).max(1) as f64;
+        let scale = max_size as f64 / longest;
+        let size = (
+            ((bounds.width as f64 * scale).round() as usize).max(1),
+            ((bounds.height as f64 * scale).round() as usize).max(1),
+        );
+        Minimap {
+            bounds,
+            size,
+            image: None,
+        }
+    }
+
+    /// 地図が表すワールドの範囲
+    pub fn bounds(&self) -> CellRect {
+        self.bounds
+    }
+
+    /// 地図の(幅, 高さ)(画素)
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    /// 地図に描くワールドの様子を更新する。`overview`は`bounds`全体を表す盤面で、大きさは`bounds`と違ってもよい
+    /// 毎フレーム呼ぶ必要はなく、変化が遅ければ数十フレームに1回で足りる
+    pub fn set_image<A>(&mut self, overview: &Matrix<A>, mapping: &ValueMapping)
+    where
+        A: Copy + Into<f32>,
+    {
+        let (rows, cols) = overview.dim();
+        let (width, height) = self.size;
+        let mut image = Canvas::new(width, height);
+        if rows > 0 && cols > 0 {
+            for y in 0..height {
+                for x in 0..width {
+                    let value = overview[[y * rows / height, x * cols / width]].into();
+                    image.set_pixel(x, y, mapping.rgba(value));
+                }
+            }
+        }
+        self.image = Some(image);
+    }
+
+    /// 地図の画素`pixel`(左上が原点)が表すワールド座標。地図の外なら`None`
+    pub fn world_at(&self, pixel: (f64, f64)) -> Option<(f64, f64)> {
+        let (width, height) = (self.size.0 as f64, self.size.1 as f64);
+        if pixel.0 < 0.0 || pixel.1 < 0.0 || pixel.0 >= width || pixel.1 >= height {
+            return None;
+        }
+        Some((
+            self.bounds.x as f64 + pixel.0 / width * self.bounds.width as f64,
+            self.bounds.y as f64 + pixel.1 / height * self.bounds.height as f64,
+        ))
+    }
+
+    // ワールド座標を地図の画素にする
+    fn to_pixel(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        (
+            (x - self.bounds.x as f64) / self.bounds.width.max(1) as f64 * self.size.0 as f64,
+            (y - self.bounds.y as f64) / self.bounds.height.max(1) as f64 * self.size.1 as f64,
+        )
+    }
+
+    /// `camera`に映っている範囲を枠で示した地図の画像
+    pub fn render(&self, camera: &Camera) -> Canvas {
+        let (width, height) = self.size;
+        let mut canvas = match self.image {
+            Some(ref image) => image.clone(),
+            None => {
+                let mut canvas = Canvas::new(width, height);
+                canvas.fill_rect(0, 0, width, height, BACKGROUND);
+                canvas
+            }
+        };
+        canvas.stroke_rect(0, 0, width, height, BORDER);
+        let (left, top) = self.to_pixel(camera.screen_to_world((0.0, 0.0)));
+        let (right, bottom) = self.to_pixel(camera.screen_to_world(camera.viewport()));
+        // 地図からはみ出す部分は地図の端に寄せて、見えている範囲が必ず残るようにする
+        let clamp_x = |x: f64| x.max(0.0).min(width as f64 - 1.0) as usize;
+        let clamp_y = |y: f64| y.max(0.0).min(height as f64 - 1.0) as usize;
+        let (left, right) = (clamp_x(left), clamp_x(right));
+        let (top, bottom) = (clamp_y(top), clamp_y(bottom));
+        canvas.stroke_rect(left, top, right - left + 1, bottom - top + 1, VIEWPORT);
+        canvas
+    }
+}
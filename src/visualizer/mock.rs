@@ -0,0 +1,156 @@
+use failure;
+use std::cell::RefCell;
+use visualizer::Matrix;
+use visualizer::{ControlFlow, Draw};
+
+/// ウィンドウもGLのコンテキストも使わずに、`MatrixVisualizer`と同じ描画ループを決まったフレーム数だけ動かす
+///
+/// 描画した盤面は`f32`にして最後の1枚だけ覚えておくので、doctestやCIで描画ループの結果を確かめられる
+///
+/// # Example
+/// ```
+/// use my_alife::algorithm::game_of_life::{random_cells, step};
+/// use my_alife::visualizer::mock::MockVisualizer;
+/// use my_alife::visualizer::{ControlFlow, Draw};
+///
+/// // `MatrixVisualizer`でも`MockVisualizer`でも動く描画ループ
+/// fn run<V: Draw>(visualizer: &mut V) -> usize {
+///     let mut cells = random_cells((32, 32), 0.3);
+///     let mut generation = 0;
+///     loop {
+///         cells = step(&cells);
+///         generation += 1;
+///         visualizer.set_title(&format!("Game of Life {}", generation));
+///         if visualizer.render_frame(&cells).unwrap() == ControlFlow::Stop {
+///             return generation;
+///         }
+///     }
+/// }
+///
+/// let mut mock = MockVisualizer::new(10);
+/// assert_eq!(run(&mut mock), 10);
+/// assert_eq!(mock.frames(), 10);
+/// assert_eq!(mock.title(), "Game of Life 10");
+/// assert_eq!(mock.last_frame().unwrap().dim(), (32, 32));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockVisualizer {
+    max_frames: usize,
+    frames: usize,
+    title: RefCell<String>,
+    last_frame: Option<Matrix<f32>>,
+}
+
+impl MockVisualizer {
+    /// `max_frames`フレーム描画したら`poll_events`が`ControlFlow::Stop`を返す
+    pub fn new(max_frames: usize) -> MockVisualizer {
+        MockVisualizer {
+            max_frames,
+            frames: 0,
+            title: RefCell::new(String::new()),
+            last_frame: None,
+        }
+    }
+
+    /// 描画したフレーム数
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// 最後に設定されたタイトル
+    pub fn title(&self) -> String {
+        self.title.borrow().clone()
+    }
+
+    /// 最後に描画した盤面
+    pub fn last_frame(&self) -> Option<&Matrix<f32>> {
+        self.last_frame.as_ref()
+    }
+
+    /// `MatrixVisualizer::draw_loop`と同じように`update_fn`で状態を変えながら描画し、最後の状態を返す
+    pub fn draw_loop<T, F>(self, initial_state: T, f: f32, k: f32, mut update_fn: F) -> Result<T, failure::Error>
+    where
+        F: FnMut(&mut T, f32, f32) -> &Matrix<f32>,
+    {
+        self.draw_loop_until(initial_state, f, k, |state, f, k| {
+            (update_fn(state, f, k), ControlFlow::Continue)
+        })
+    }
+
+    /// `MatrixVisualizer::draw_loop_until`と同じように、`update_fn`が`ControlFlow::Stop`を返すまで描画し、最後の状態を返す
+    pub fn draw_loop_until<T, F>(self, initial_state: T, f: f32, k: f32, mut update_fn: F) -> Result<T, failure::Error>
+    where
+        F: FnMut(&mut T, f32, f32) -> (&Matrix<f32>, ControlFlow),
+    {
+        self.try_draw_loop_until(initial_state, f, k, |state, f, k| Ok(update_fn(state, f, k)))
+    }
+
+    /// `MatrixVisualizer::try_draw_loop`と同じように、`update_fn`がエラーを返したらそのエラーを返す
+    ///
+    /// # Example
+    /// ```
+    /// extern crate failure;
+    /// extern crate my_alife;
+    ///
+    /// use my_alife::algorithm::gray_scott::{initial_matrix, laplacian};
+    /// use my_alife::visualizer::mock::MockVisualizer;
+    ///
+    /// let uv = MockVisualizer::new(5)
+    ///     .try_draw_loop(initial_matrix(), 0.04, 0.06, |uv, f, k| -> Result<_, failure::Error> {
+    ///         Ok(laplacian(uv, f, k))
+    ///     })
+    ///     .unwrap();
+    /// assert!(uv.0.iter().all(|e| e.is_finite()));
+    /// ```
+    pub fn try_draw_loop<T, F, E>(self, initial_state: T, f: f32, k: f32, mut update_fn: F) -> Result<T, failure::Error>
+    where
+        F: FnMut(&mut T, f32, f32) -> Result<&Matrix<f32>, E>,
+        E: Into<failure::Error>,
+    {
+        self.try_draw_loop_until(initial_state, f, k, |state, f, k| match update_fn(state, f, k) {
+            Ok(u) => Ok((u, ControlFlow::Continue)),
+            Err(e) => Err(e.into()),
+        })
+    }
+
+    fn try_draw_loop_until<T, F>(mut self, mut state: T, f: f32, k: f32, mut update_fn: F) -> Result<T, failure::Error>
+    where
+        F: FnMut(&mut T, f32, f32) -> Result<(&Matrix<f32>, ControlFlow), failure::Error>,
+    {
+        loop {
+            let flow = {
+                let (u, flow) = update_fn(&mut state, f, k)?;
+                self.draw(u)?;
+                flow
+            };
+            if self.poll_events() == ControlFlow::Stop || flow == ControlFlow::Stop {
+                break;
+            }
+        }
+        Ok(state)
+    }
+}
+
+impl Draw for MockVisualizer {
+    fn set_title(&self, title: &str) {
+        *self.title.borrow_mut() = title.to_string();
+    }
+
+    fn draw<A: Copy + Into<f32>>(&mut self, matrix: &Matrix<A>) -> Result<(), failure::Error> {
+        self.frames += 1;
+        self.last_frame = Some(matrix.mapv(Into::into));
+        Ok(())
+    }
+
+    fn draw_states(&mut self, cells: &Matrix<u8>, _states: u8) -> Result<(), failure::Error> {
+        self.draw(cells)
+    }
+
+    fn poll_events(&mut self) -> ControlFlow {
+        if self.frames >= self.max_frames {
+            ControlFlow::Stop
+        } else {
+            ControlFlow::Continue
+        }
+    }
+}
@@ -1,11 +1,155 @@
+use failure;
+#[cfg(feature = "gl")]
+use glium::glutin::Icon;
+#[cfg(feature = "gl")]
+use glium::Display;
+use ndarray::{ArrayBase, Dim, OwnedRepr};
+
+/// キーボードのキー。`MatrixVisualizer::pressed_keys`で使う
+#[cfg(feature = "gl")]
+pub use glium::glutin::VirtualKeyCode;
+
+/// 連続空間を動くエージェントの描画
+pub mod agent_visualizer;
+/// マウスで盤面に描くブラシ
+pub mod brush;
+/// 大きさに上限のないワールドを表示するためのカメラ
+pub mod camera;
+/// 値を色に変換するためのモジュール
+pub mod colormap;
+/// 数値の発散(NaN/Inf)を調べるためのモジュール
+pub mod diagnostics;
+/// 盤面を連番の画像として書き出すためのモジュール
+pub mod export;
+/// 頂点と辺のグラフをvisualizeするためのモジュール
+pub mod graph_visualizer;
+/// ウィンドウを開かずに画像にするためのモジュール
+pub mod image;
+/// 適応度地形と集団の軌跡を表示するためのモジュール
+pub mod landscape;
 /// 直交座標系(XY座標系)を用いてvisualizeするためのモジュール
+#[cfg(feature = "gl")]
 pub mod matrix_visualizer;
+/// ワールド全体と見えている範囲を小さく表示する地図のモジュール
+pub mod minimap;
+/// マウスでセルを指すためのモジュール
+pub mod mouse;
+/// ウィンドウを開かずに描画ループを動かすためのモジュール
+pub mod mock;
+/// 盤面の上に文字や図形を重ねて描くためのモジュール
+pub mod overlay;
+/// 状態ごとに決めた色で描くためのモジュール
+pub mod palette;
+/// 極座標系を用いてvisualizeするためのモジュール
+#[cfg(feature = "gl")]
+pub mod polar_visualizer;
+/// 描画の段階ごとにかかった時間を計るためのモジュール
+pub mod profiler;
+/// 盤面の一部を選んでコピー・貼り付けするためのモジュール
+pub mod selection;
+/// 操作を記録して同じように再生するためのモジュール
+pub mod session;
+/// 球面に貼ってvisualizeするためのモジュール
+#[cfg(feature = "gl")]
+pub mod sphere_visualizer;
+/// Matrixをテクスチャに転送するためのモジュール
+pub mod texture;
+/// 記録した過去の状態をたどって分岐させるためのモジュール
+pub mod timeline;
+/// 操作を取り消す・やり直すためのモジュール
+pub mod undo;
+
+/// 直交座標系(XY座標系)においてどの座標にどんな色(グレースケール)を表示するかを表現する。  
+/// 実体は2次元配列
+pub type Matrix<T> = ArrayBase<OwnedRepr<T>, Dim<[usize; 2]>>;
 
 /// windowの状態
-#[derive(PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum WindowStatus {
     /// 開いている
     Open,
     /// 閉じている
     Close,
 }
+
+/// 描画ループを続けるかどうか
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlFlow {
+    /// 続ける
+    Continue,
+    /// 止める
+    Stop,
+}
+
+impl From<WindowStatus> for ControlFlow {
+    fn from(status: WindowStatus) -> ControlFlow {
+        match status {
+            WindowStatus::Open => ControlFlow::Continue,
+            WindowStatus::Close => ControlFlow::Stop,
+        }
+    }
+}
+
+/// 盤面を描画するものに共通する操作
+///
+/// `MatrixVisualizer`と、ウィンドウを開かない`MockVisualizer`のどちらでも動く描画ループを書くために使う
+pub trait Draw {
+    /// ウィンドウのタイトルを変更する
+    fn set_title(&self, title: &str);
+
+    /// 盤面を描画する
+    fn draw<A: Copy + Into<f32>>(&mut self, matrix: &Matrix<A>) -> Result<(), failure::Error>;
+
+    /// 0〜`states - 1`の状態を持つセル・オートマトンの盤面を描画する
+    fn draw_states(&mut self, cells: &Matrix<u8>, states: u8) -> Result<(), failure::Error>;
+
+    /// 溜まっているイベントを処理し、描画を止めるときは`ControlFlow::Stop`を返す
+    fn poll_events(&mut self) -> ControlFlow;
+
+    /// `draw`で1フレームだけ描画し、溜まっているイベントを処理する
+    fn render_frame<A: Copy + Into<f32>>(&mut self, matrix: &Matrix<A>) -> Result<ControlFlow, failure::Error> {
+        self.draw(matrix)?;
+        Ok(self.poll_events())
+    }
+}
+
+/// 描画中のウィンドウを操作するためのハンドル
+#[cfg(feature = "gl")]
+pub struct WindowHandle<'a> {
+    display: &'a Display,
+    // タイトルの後ろに付け足す文字列
+    suffix: String,
+}
+
+#[cfg(feature = "gl")]
+impl<'a> WindowHandle<'a> {
+    pub(crate) fn new(display: &'a Display, suffix: String) -> WindowHandle<'a> {
+        WindowHandle { display, suffix }
+    }
+
+    /// ウィンドウのタイトルを変更する。`profiling`フィーチャーが有効なときは後ろに各段階の時間が付く
+    pub fn set_title(&self, title: &str) {
+        self.display.gl_window().set_title(&format!("{}{}", title, self.suffix));
+    }
+
+    /// ウィンドウのアイコンを設定する。画素データは32bitRGBAで`width * height`個必要
+    pub fn set_icon(&self, rgba: Vec<u8>, width: u32, height: u32) -> Result<(), failure::Error> {
+        let icon = Icon::from_rgba(rgba, width, height).map_err(|e| format_err!("{}", e))?;
+        self.display.gl_window().set_window_icon(Some(icon));
+        Ok(())
+    }
+}
+
+/// frame hookに渡される描画中の情報
+///
+/// `f`と`k`は`draw_loop`に渡したパラメータで、`update_fn`にもそのまま渡される。
+/// `draw_loop`自体がGray-Scottモデルの2つのパラメータを受け取る形なので、ほかのモデルでは使わなくてよい
+#[derive(Debug, Clone, Copy)]
+pub struct FrameInfo {
+    /// 描画したフレーム数
+    pub frame: usize,
+    /// `draw_loop`に渡した1つ目のパラメータ(Gray-Scottモデルの供給率f)
+    pub f: f32,
+    /// `draw_loop`に渡した2つ目のパラメータ(Gray-Scottモデルの除去率k)
+    pub k: f32,
+}
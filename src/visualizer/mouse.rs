@@ -0,0 +1,35 @@
+/// ウィンドウ上のマウスの状態
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Mouse {
+    /// ウィンドウの左上を原点とするカーソルの位置。ウィンドウの外にあるときは`None`
+    pub position: Option<(f64, f64)>,
+    /// 左ボタンが押されているかどうか
+    pub left: bool,
+    /// 右ボタンが押されているかどうか
+    pub right: bool,
+    /// Shiftキーが押されているかどうか
+    pub shift: bool,
+}
+
+impl Mouse {
+    /// ウィンドウ全体に`dim`の大きさのMatrixを描画したときに、カーソルが指しているセルの(行, 列)
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::visualizer::mouse::Mouse;
+    ///
+    /// let mouse = Mouse { position: Some((150.0, 599.0)), ..Mouse::default() };
+    /// assert_eq!(mouse.cell((600.0, 600.0), (64, 32)), Some((63, 8)));
+    /// assert_eq!(Mouse::default().cell((600.0, 600.0), (64, 32)), None);
+    /// ```
+    pub fn cell(&self, window: (f64, f64), dim: (usize, usize)) -> Option<(usize, usize)> {
+        let (x, y) = self.position?;
+        let (width, height) = window;
+        if x < 0.0 || y < 0.0 || x >= width || y >= height {
+            return None;
+        }
+        let row = (y / height * dim.0 as f64) as usize;
+        let col = (x / width * dim.1 as f64) as usize;
+        Some((row.min(dim.0.saturating_sub(1)), col.min(dim.1.saturating_sub(1))))
+    }
+}
@@ -0,0 +1,580 @@
+#[cfg(feature = "gl")]
+use failure;
+#[cfg(feature = "gl")]
+use glium::backend::Facade;
+#[cfg(feature = "gl")]
+use glium::texture::{RawImage2d, Texture2d};
+#[cfg(feature = "gl")]
+use glium::uniforms::{MagnifySamplerFilter, MinifySamplerFilter};
+#[cfg(feature = "gl")]
+use glium::{index, Blend, DrawParameters, Program, Surface, VertexBuffer};
+
+// 1文字の幅と高さ(画素)。文字の間は1画素空ける
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_HEIGHT: usize = 8;
+
+// ASCIIの' '〜'~'の5×8のフォント。1byteが1列で、下位bitが上の画素
+const GLYPHS: [[u8; GLYPH_WIDTH]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00],
+    [0x00, 0x00, 0x5F, 0x00, 0x00],
+    [0x00, 0x07, 0x00, 0x07, 0x00],
+    [0x14, 0x7F, 0x14, 0x7F, 0x14],
+    [0x24, 0x2A, 0x7F, 0x2A, 0x12],
+    [0x23, 0x13, 0x08, 0x64, 0x62],
+    [0x36, 0x49, 0x56, 0x20, 0x50],
+    [0x00, 0x08, 0x07, 0x03, 0x00],
+    [0x00, 0x1C, 0x22, 0x41, 0x00],
+    [0x00, 0x41, 0x22, 0x1C, 0x00],
+    [0x2A, 0x1C, 0x7F, 0x1C, 0x2A],
+    [0x08, 0x08, 0x3E, 0x08, 0x08],
+    [0x00, 0x80, 0x70, 0x30, 0x00],
+    [0x08, 0x08, 0x08, 0x08, 0x08],
+    [0x00, 0x00, 0x60, 0x60, 0x00],
+    [0x20, 0x10, 0x08, 0x04, 0x02],
+    [0x3E, 0x51, 0x49, 0x45, 0x3E],
+    [0x00, 0x42, 0x7F, 0x40, 0x00],
+    [0x72, 0x49, 0x49, 0x49, 0x46],
+    [0x21, 0x41, 0x49, 0x4D, 0x33],
+    [0x18, 0x14, 0x12, 0x7F, 0x10],
+    [0x27, 0x45, 0x45, 0x45, 0x39],
+    [0x3C, 0x4A, 0x49, 0x49, 0x31],
+    [0x41, 0x21, 0x11, 0x09, 0x07],
+    [0x36, 0x49, 0x49, 0x49, 0x36],
+    [0x46, 0x49, 0x49, 0x29, 0x1E],
+    [0x00, 0x00, 0x14, 0x00, 0x00],
+    [0x00, 0x40, 0x34, 0x00, 0x00],
+    [0x00, 0x08, 0x14, 0x22, 0x41],
+    [0x14, 0x14, 0x14, 0x14, 0x14],
+    [0x00, 0x41, 0x22, 0x14, 0x08],
+    [0x02, 0x01, 0x59, 0x09, 0x06],
+    [0x3E, 0x41, 0x5D, 0x59, 0x4E],
+    [0x7C, 0x12, 0x11, 0x12, 0x7C],
+    [0x7F, 0x49, 0x49, 0x49, 0x36],
+    [0x3E, 0x41, 0x41, 0x41, 0x22],
+    [0x7F, 0x41, 0x41, 0x41, 0x3E],
+    [0x7F, 0x49, 0x49, 0x49, 0x41],
+    [0x7F, 0x09, 0x09, 0x09, 0x01],
+    [0x3E, 0x41, 0x41, 0x51, 0x73],
+    [0x7F, 0x08, 0x08, 0x08, 0x7F],
+    [0x00, 0x41, 0x7F, 0x41, 0x00],
+    [0x20, 0x40, 0x41, 0x3F, 0x01],
+    [0x7F, 0x08, 0x14, 0x22, 0x41],
+    [0x7F, 0x40, 0x40, 0x40, 0x40],
+    [0x7F, 0x02, 0x1C, 0x02, 0x7F],
+    [0x7F, 0x04, 0x08, 0x10, 0x7F],
+    [0x3E, 0x41, 0x41, 0x41, 0x3E],
+    [0x7F, 0x09, 0x09, 0x09, 0x06],
+    [0x3E, 0x41, 0x51, 0x21, 0x5E],
+    [0x7F, 0x09, 0x19, 0x29, 0x46],
+    [0x26, 0x49, 0x49, 0x49, 0x32],
+    [0x03, 0x01, 0x7F, 0x01, 0x03],
+    [0x3F, 0x40, 0x40, 0x40, 0x3F],
+    [0x1F, 0x20, 0x40, 0x20, 0x1F],
+    [0x3F, 0x40, 0x38, 0x40, 0x3F],
+    [0x63, 0x14, 0x08, 0x14, 0x63],
+    [0x03, 0x04, 0x78, 0x04, 0x03],
+    [0x61, 0x59, 0x49, 0x4D, 0x43],
+    [0x00, 0x7F, 0x41, 0x41, 0x41],
+    [0x02, 0x04, 0x08, 0x10, 0x20],
+    [0x00, 0x41, 0x41, 0x41, 0x7F],
+    [0x04, 0x02, 0x01, 0x02, 0x04],
+    [0x40, 0x40, 0x40, 0x40, 0x40],
+    [0x00, 0x03, 0x07, 0x08, 0x00],
+    [0x20, 0x54, 0x54, 0x78, 0x40],
+    [0x7F, 0x28, 0x44, 0x44, 0x38],
+    [0x38, 0x44, 0x44, 0x44, 0x28],
+    [0x38, 0x44, 0x44, 0x28, 0x7F],
+    [0x38, 0x54, 0x54, 0x54, 0x18],
+    [0x00, 0x08, 0x7E, 0x09, 0x02],
+    [0x18, 0xA4, 0xA4, 0x9C, 0x78],
+    [0x7F, 0x08, 0x04, 0x04, 0x78],
+    [0x00, 0x44, 0x7D, 0x40, 0x00],
+    [0x20, 0x40, 0x40, 0x3D, 0x00],
+    [0x7F, 0x10, 0x28, 0x44, 0x00],
+    [0x00, 0x41, 0x7F, 0x40, 0x00],
+    [0x7C, 0x04, 0x78, 0x04, 0x78],
+    [0x7C, 0x08, 0x04, 0x04, 0x78],
+    [0x38, 0x44, 0x44, 0x44, 0x38],
+    [0xFC, 0x18, 0x24, 0x24, 0x18],
+    [0x18, 0x24, 0x24, 0x18, 0xFC],
+    [0x7C, 0x08, 0x04, 0x04, 0x08],
+    [0x48, 0x54, 0x54, 0x54, 0x24],
+    [0x04, 0x04, 0x3F, 0x44, 0x24],
+    [0x3C, 0x40, 0x40, 0x20, 0x7C],
+    [0x1C, 0x20, 0x40, 0x20, 0x1C],
+    [0x3C, 0x40, 0x30, 0x40, 0x3C],
+    [0x44, 0x28, 0x10, 0x28, 0x44],
+    [0x4C, 0x90, 0x90, 0x90, 0x7C],
+    [0x44, 0x64, 0x54, 0x4C, 0x44],
+    [0x00, 0x08, 0x36, 0x41, 0x00],
+    [0x00, 0x00, 0x77, 0x00, 0x00],
+    [0x00, 0x41, 0x36, 0x08, 0x00],
+    [0x02, 0x01, 0x02, 0x04, 0x02],
+];
+
+/// `text`を`scale`倍の大きさで描いたときの(幅, 高さ)(画素)。ASCII以外の文字は`?`として数える
+///
+/// # Example
+/// ```
+/// use my_alife::visualizer::overlay::text_size;
+///
+/// assert_eq!(text_size("Tree", 1), (23, 8));
+/// assert_eq!(text_size("Tree", 2), (46, 16));
+/// assert_eq!(text_size("", 2), (0, 16));
+/// ```
+pub fn text_size(text: &str, scale: usize) -> (usize, usize) {
+    let chars = text.chars().count();
+    let width = match chars {
+        0 => 0,
+        n => n * (GLYPH_WIDTH + 1) - 1,
+    };
+    (width * scale, GLYPH_HEIGHT * scale)
+}
+
+fn glyph(c: char) -> &'static [u8; GLYPH_WIDTH] {
+    match c {
+        ' '..='~' => &GLYPHS[c as usize - ' ' as usize],
+        _ => &GLYPHS['?' as usize - ' ' as usize],
+    }
+}
+
+/// 盤面の上に重ねて描くRGBA(1画素4byte、左上から行優先)の画像
+///
+/// 透明度が0の画素は下の盤面がそのまま見える
+///
+/// # Example
+/// ```
+/// use my_alife::visualizer::overlay::Canvas;
+///
+/// let mut canvas = Canvas::new(40, 12);
+/// canvas.fill_rect(0, 0, 40, 12, [0, 0, 0, 128]);
+/// canvas.draw_text(2, 2, "Hi!", [255, 255, 255, 255], 1);
+/// assert_eq!(canvas.pixel(0, 0), [0, 0, 0, 128]);
+/// // 'H'の左の縦棒
+/// assert_eq!(canvas.pixel(2, 2), [255, 255, 255, 255]);
+/// assert_eq!(canvas.pixels().len(), 40 * 12 * 4);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    /// すべて透明な`width`×`height`の画像
+    pub fn new(width: usize, height: usize) -> Canvas {
+        Canvas {
+            width,
+            height,
+            pixels: vec![0; width * height * 4],
+        }
+    }
+
+    /// 幅(画素)
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// 高さ(画素)
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// RGBAの画素データ
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// 画素(x, y)の色
+    ///
+    /// # Panics
+    /// 画像の外を指定したとき
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        assert!(x < self.width && y < self.height, "pixel is out of bounds");
+        let i = (y * self.width + x) * 4;
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+
+    /// 画素(x, y)を`color`にする。画像の外なら何もしない
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: [u8; 4]) {
+        if x < self.width && y < self.height {
+            let i = (y * self.width + x) * 4;
+            self.pixels[i..i + 4].copy_from_slice(&color);
+        }
+    }
+
+    /// 左上が(x, y)で`width`×`height`の四角を塗る。画像からはみ出した部分は描かない
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: [u8; 4]) {
+        for row in y..(y + height).min(self.height) {
+            for col in x..(x + width).min(self.width) {
+                self.set_pixel(col, row, color);
+            }
+        }
+    }
+
+    /// 左上が(x, y)で`width`×`height`の四角の枠を描く
+    pub fn stroke_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: [u8; 4]) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        self.fill_rect(x, y, width, 1, color);
+        self.fill_rect(x, y + height - 1, width, 1, color);
+        self.fill_rect(x, y, 1, height, color);
+        self.fill_rect(x + width - 1, y, 1, height, color);
+    }
+
+    /// `from`から`to`まで太さ1画素の線を引く。画像の外の点を指定してもよい
+    pub fn draw_line(&mut self, from: (f32, f32), to: (f32, f32), color: [u8; 4]) {
+        let steps = (to.0 - from.0).abs().max((to.1 - from.1).abs()).ceil().max(1.0) as usize;
+        for i in 0..=steps {
+            let t = i as f32 / steps as f32;
+            self.plot(from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t, color);
+        }
+    }
+
+    /// 中心が`center`で半径`radius`の円を描く
+    pub fn draw_circle(&mut self, center: (f32, f32), radius: f32, color: [u8; 4]) {
+        // 隙間ができないように、円周1画素あたり1点以上打つ
+        let steps = (radius * ::std::f32::consts::PI * 2.0).ceil().max(8.0) as usize;
+        for i in 0..steps {
+            let angle = i as f32 / steps as f32 * ::std::f32::consts::PI * 2.0;
+            self.plot(center.0 + radius * angle.cos(), center.1 + radius * angle.sin(), color);
+        }
+    }
+
+    /// `from`から`to`に向かう矢印を描く
+    pub fn draw_arrow(&mut self, from: (f32, f32), to: (f32, f32), color: [u8; 4]) {
+        self.draw_line(from, to, color);
+        let (dx, dy) = (from.0 - to.0, from.1 - to.1);
+        let length = (dx * dx + dy * dy).sqrt();
+        if length == 0.0 {
+            return;
+        }
+        let head = ARROW_HEAD.min(length / 2.0);
+        for &angle in &[ARROW_ANGLE, -ARROW_ANGLE] {
+            let (sin, cos) = angle.sin_cos();
+            let x = (dx * cos - dy * sin) / length * head;
+            let y = (dx * sin + dy * cos) / length * head;
+            self.draw_line(to, (to.0 + x, to.1 + y), color);
+        }
+    }
+
+    fn plot(&mut self, x: f32, y: f32, color: [u8; 4]) {
+        if x >= 0.0 && y >= 0.0 {
+            self.set_pixel(x as usize, y as usize, color);
+        }
+    }
+
+    /// 左上が(x, y)になるように`text`を`scale`倍の大きさで描く。改行はしない
+    pub fn draw_text(&mut self, x: usize, y: usize, text: &str, color: [u8; 4], scale: usize) {
+        for (i, c) in text.chars().enumerate() {
+            let left = x + i * (GLYPH_WIDTH + 1) * scale;
+            for (col, bits) in glyph(c).iter().enumerate() {
+                for row in 0..GLYPH_HEIGHT {
+                    if bits >> row & 1 == 1 {
+                        self.fill_rect(left + col * scale, y + row * scale, scale, scale, color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// 矢印の先の長さ(画素)と開き具合(rad)
+const ARROW_HEAD: f32 = 10.0;
+const ARROW_ANGLE: f32 = 0.45;
+// ラベルの文字の倍率
+const LABEL_SCALE: usize = 2;
+
+/// 盤面の上に重ねて描く注釈。位置はセルの(行, 列)で、小数にするとセルの間も指せる
+#[derive(Debug, Clone, PartialEq)]
+pub enum Annotation {
+    /// `cell`の右上に文字を描く
+    Label {
+        /// 指すセル
+        cell: (f32, f32),
+        /// 文字列
+        text: String,
+        /// 色
+        color: [u8; 4],
+    },
+    /// `cell`を囲む円を描く。半径は画素
+    Marker {
+        /// 指すセル
+        cell: (f32, f32),
+        /// 半径(画素)
+        radius: f32,
+        /// 色
+        color: [u8; 4],
+    },
+    /// `from`から`to`に向かう矢印を描く
+    Arrow {
+        /// 矢印の根本のセル
+        from: (f32, f32),
+        /// 矢印の先のセル
+        to: (f32, f32),
+        /// 色
+        color: [u8; 4],
+    },
+    /// (行, 列)から`rows`×`cols`セルの範囲を枠で囲む
+    Region {
+        /// 左上のセル
+        cell: (usize, usize),
+        /// 行数
+        rows: usize,
+        /// 列数
+        cols: usize,
+        /// 色
+        color: [u8; 4],
+    },
+}
+
+/// 毎フレーム盤面の上に重ねて描く注釈の一覧
+///
+/// `MatrixVisualizer::annotations_mut`で取得して、描画する前に追加・削除する。消すまで毎フレーム描かれる
+///
+/// # Example
+/// ```
+/// use my_alife::visualizer::overlay::Annotations;
+///
+/// let mut annotations = Annotations::new();
+/// annotations
+///     .label((10.0, 10.0), "oldest", [255, 255, 0, 255])
+///     .marker((10.0, 10.0), 8.0, [255, 255, 0, 255])
+///     .region((0, 0), 32, 32, [0, 255, 255, 255]);
+/// assert_eq!(annotations.len(), 3);
+///
+/// // 64×64の盤面を256×256画素のウィンドウに描くと、1セルが4画素になる
+/// let canvas = annotations.render((64, 64), (256, 256));
+/// assert_eq!(canvas.pixel(0, 0), [0, 255, 255, 255]);
+/// assert_eq!(canvas.pixel(127, 64), [0, 255, 255, 255]);
+/// assert_eq!(canvas.pixel(200, 200), [0, 0, 0, 0]);
+/// annotations.clear();
+/// assert!(annotations.is_empty());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Annotations {
+    items: Vec<Annotation>,
+}
+
+impl Annotations {
+    /// 注釈のない一覧
+    pub fn new() -> Annotations {
+        Annotations::default()
+    }
+
+    /// 注釈を加える
+    pub fn push(&mut self, annotation: Annotation) -> &mut Annotations {
+        self.items.push(annotation);
+        self
+    }
+
+    /// `cell`の右上に`text`を描く
+    pub fn label(&mut self, cell: (f32, f32), text: &str, color: [u8; 4]) -> &mut Annotations {
+        self.push(Annotation::Label {
+            cell,
+            text: text.to_string(),
+            color,
+        })
+    }
+
+    /// `cell`を半径`radius`画素の円で囲む
+    pub fn marker(&mut self, cell: (f32, f32), radius: f32, color: [u8; 4]) -> &mut Annotations {
+        self.push(Annotation::Marker { cell, radius, color })
+    }
+
+    /// `from`から`to`に向かう矢印を描く
+    pub fn arrow(&mut self, from: (f32, f32), to: (f32, f32), color: [u8; 4]) -> &mut Annotations {
+        self.push(Annotation::Arrow { from, to, color })
+    }
+
+    /// (行, 列)から`rows`×`cols`セルの範囲を枠で囲む。測定する範囲を示すのに使う
+    pub fn region(&mut self, cell: (usize, usize), rows: usize, cols: usize, color: [u8; 4]) -> &mut Annotations {
+        self.push(Annotation::Region {
+            cell,
+            rows,
+            cols,
+            color,
+        })
+    }
+
+    /// 注釈の数
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// 注釈がないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// 注釈を加えた順に返す
+    pub fn iter(&self) -> impl Iterator<Item = &Annotation> + '_ {
+        self.items.iter()
+    }
+
+    /// すべての注釈を消す
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// `dim`の大きさの盤面を`window`(幅, 高さ)画素に描いたときの位置に、注釈を描いた画像
+    pub fn render(&self, dim: (usize, usize), window: (usize, usize)) -> Canvas {
+        let mut canvas = Canvas::new(window.0, window.1);
+        if dim.0 == 0 || dim.1 == 0 {
+            return canvas;
+        }
+        let (cell_width, cell_height) = (window.0 as f32 / dim.1 as f32, window.1 as f32 / dim.0 as f32);
+        // セルの中心の画素
+        let center = |(row, col): (f32, f32)| ((col + 0.5) * cell_width, (row + 0.5) * cell_height);
+        for item in &self.items {
+            match *item {
+                Annotation::Label { cell, ref text, color } => {
+                    let (x, y) = center(cell);
+                    let (width, height) = text_size(text, LABEL_SCALE);
+                    let left = (x + cell_width / 2.0 + 2.0).max(0.0) as usize;
+                    let top = (y - cell_height / 2.0 - height as f32 - 2.0).max(0.0) as usize;
+                    // 盤面の色によらず読めるように、半透明の黒を敷く
+                    canvas.fill_rect(left, top, width + 4, height + 4, [0, 0, 0, 160]);
+                    canvas.draw_text(left + 2, top + 2, text, color, LABEL_SCALE);
+                }
+                Annotation::Marker { cell, radius, color } => canvas.draw_circle(center(cell), radius, color),
+                Annotation::Arrow { from, to, color } => canvas.draw_arrow(center(from), center(to), color),
+                Annotation::Region {
+                    cell: (row, col),
+                    rows,
+                    cols,
+                    color,
+                } => {
+                    let left = (col as f32 * cell_width) as usize;
+                    let top = (row as f32 * cell_height) as usize;
+                    let right = ((col + cols) as f32 * cell_width) as usize;
+                    let bottom = ((row + rows) as f32 * cell_height) as usize;
+                    canvas.stroke_rect(left, top, right - left, bottom - top, color);
+                }
+            }
+        }
+        canvas
+    }
+}
+
+#[cfg(feature = "gl")]
+const OVERLAY_VERTEX_SHADER: &str = r#"
+#version 140
+
+in vec2 a_position;
+in vec2 a_texcoord;
+out vec2 v_texcoord;
+void main()
+{
+    gl_Position = vec4(a_position, 0.0, 1.0);
+    v_texcoord = a_texcoord;
+}
+"#;
+
+#[cfg(feature = "gl")]
+const OVERLAY_FRAGMENT_SHADER: &str = r#"
+#version 140
+
+uniform sampler2D u_texture;
+in vec2 v_texcoord;
+out vec4 flagColor;
+void main()
+{
+    flagColor = texture(u_texture, v_texcoord);
+}
+"#;
+
+#[cfg(feature = "gl")]
+#[derive(Copy, Clone)]
+struct OverlayVertex {
+    a_position: [f32; 2],
+    a_texcoord: [f32; 2],
+}
+// glium 0.22のマクロ内部で非推奨のmem::uninitializedが使われている
+#[cfg(feature = "gl")]
+#[allow(deprecated)]
+mod vertex_impl {
+    use super::OverlayVertex;
+    implement_vertex!(OverlayVertex, a_position, a_texcoord);
+}
+
+/// `Canvas`を画面の決まった位置に、透明度を使って重ねて描く
+#[cfg(feature = "gl")]
+pub(crate) struct OverlayRenderer {
+    program: Program,
+}
+
+#[cfg(feature = "gl")]
+impl OverlayRenderer {
+    pub(crate) fn new<F: Facade>(facade: &F) -> Result<OverlayRenderer, failure::Error> {
+        // 色はsRGBのまま書き込むので、gliumがGL_FRAMEBUFFER_SRGBを有効にしないようにする
+        let program = program!(facade, 140 => {
+            vertex: OVERLAY_VERTEX_SHADER,
+            fragment: OVERLAY_FRAGMENT_SHADER,
+            outputs_srgb: true
+        })?;
+        Ok(OverlayRenderer { program })
+    }
+
+    /// 画面の左上から`position`(画素)の位置に`canvas`を1画素1画素で描く
+    pub(crate) fn draw<F: Facade, S: Surface>(
+        &self,
+        facade: &F,
+        target: &mut S,
+        canvas: &Canvas,
+        position: (usize, usize),
+    ) -> Result<(), failure::Error> {
+        if canvas.width() == 0 || canvas.height() == 0 {
+            return Ok(());
+        }
+        let (screen_width, screen_height) = target.get_dimensions();
+        let to_x = |x: usize| x as f32 / screen_width as f32 * 2.0 - 1.0;
+        let to_y = |y: usize| 1.0 - y as f32 / screen_height as f32 * 2.0;
+        let (left, top) = (to_x(position.0), to_y(position.1));
+        let (right, bottom) = (to_x(position.0 + canvas.width()), to_y(position.1 + canvas.height()));
+        // Canvasは上の行から並んでいるので、テクスチャの1行目が上に来るようにする
+        let vertex = |x: f32, y: f32, s: f32, t: f32| OverlayVertex {
+            a_position: [x, y],
+            a_texcoord: [s, t],
+        };
+        let shape = [
+            vertex(left, bottom, 0.0, 1.0),
+            vertex(right, bottom, 1.0, 1.0),
+            vertex(right, top, 1.0, 0.0),
+            vertex(left, bottom, 0.0, 1.0),
+            vertex(left, top, 0.0, 0.0),
+            vertex(right, top, 1.0, 0.0),
+        ];
+        let vertex_buffer = VertexBuffer::new(facade, &shape)?;
+        let image = RawImage2d::from_raw_rgba(
+            canvas.pixels().to_vec(),
+            (canvas.width() as u32, canvas.height() as u32),
+        );
+        let texture = Texture2d::new(facade, image)?;
+        let uniforms = uniform! {
+            u_texture: texture
+                .sampled()
+                .minify_filter(MinifySamplerFilter::Nearest)
+                .magnify_filter(MagnifySamplerFilter::Nearest),
+        };
+        let parameters = DrawParameters {
+            blend: Blend::alpha_blending(),
+            ..Default::default()
+        };
+        target.draw(
+            &vertex_buffer,
+            index::NoIndices(index::PrimitiveType::TrianglesList),
+            &self.program,
+            &uniforms,
+            &parameters,
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,189 @@
+use visualizer::Matrix;
+use visualizer::overlay::{text_size, Canvas};
+
+/// 登録されていない状態の色。非有限値のハイライトと同じマゼンタ
+pub const UNKNOWN_COLOR: [u8; 3] = [255, 0, 255];
+
+// 凡例の文字の倍率と、色見本・余白の大きさ(画素)
+const LEGEND_SCALE: usize = 2;
+const LEGEND_SWATCH: usize = 16;
+const LEGEND_PADDING: usize = 6;
+// 色相を黄金角ずつずらすための、黄金比の逆数
+const GOLDEN_RATIO_CONJUGATE: f32 = 0.618_034;
+
+/// 0, 1, 2, ...の状態ごとに名前と色を決めたもの
+///
+/// 空き地・木・燃えている木やS/I/Rのように、大小に意味のない状態をグレースケールの濃淡ではなく決まった色で描くために使う
+///
+/// # Example
+/// ```
+/// use my_alife::visualizer::palette::{Palette, UNKNOWN_COLOR};
+///
+/// let palette = Palette::new()
+///     .with("Empty", [0, 0, 0])
+///     .with("Tree", [34, 139, 34])
+///     .with("Burning", [255, 80, 0]);
+/// assert_eq!(palette.len(), 3);
+/// assert_eq!(palette.color(1), [34, 139, 34]);
+/// assert_eq!(palette.name(2), Some("Burning"));
+/// assert_eq!(palette.color(3), UNKNOWN_COLOR);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Palette {
+    entries: Vec<(String, [u8; 3])>,
+}
+
+impl Palette {
+    /// 状態のない空のパレット
+    pub fn new() -> Palette {
+        Palette::default()
+    }
+
+    /// 隣り合う状態どうしの色相が黄金角ずつずれた、名前のない`count`個の状態のパレット
+    ///
+    /// 系統や細胞のように、番号だけで区別する多数の状態を描くために使う。`count`は256で打ち切る
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::visualizer::palette::Palette;
+    ///
+    /// let palette = Palette::categorical(10);
+    /// assert_eq!(palette.len(), 10);
+    /// assert_ne!(palette.color(0), palette.color(1));
+    /// assert_eq!(palette.name(3), Some(""));
+    /// // 名前がないので凡例には載らない
+    /// assert_eq!(palette.legend().height(), 6);
+    /// ```
+    pub fn categorical(count: usize) -> Palette {
+        let entries = (0..count.min(256))
+            .map(|i| (String::new(), hsv_to_rgb((i as f32 * GOLDEN_RATIO_CONJUGATE).fract(), 0.65, 0.95)))
+            .collect();
+        Palette { entries }
+    }
+
+    /// 次の状態の名前と色を加える。最初に加えたものが状態0になる
+    pub fn with(mut self, name: &str, color: [u8; 3]) -> Palette {
+        self.entries.push((name.to_string(), color));
+        self
+    }
+
+    /// 状態`state`の色を変える。まだない状態なら、間を`UNKNOWN_COLOR`の名前のない状態で埋める
+    pub fn set(&mut self, state: u8, name: &str, color: [u8; 3]) {
+        let state = usize::from(state);
+        if self.entries.len() <= state {
+            self.entries.resize(state + 1, (String::new(), UNKNOWN_COLOR));
+        }
+        self.entries[state] = (name.to_string(), color);
+    }
+
+    /// 状態の数
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 状態がないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 状態`state`の色。登録されていなければ`UNKNOWN_COLOR`
+    pub fn color(&self, state: u8) -> [u8; 3] {
+        self.entries
+            .get(usize::from(state))
+            .map_or(UNKNOWN_COLOR, |&(_, color)| color)
+    }
+
+    /// 状態`state`の名前
+    pub fn name(&self, state: u8) -> Option<&str> {
+        self.entries.get(usize::from(state)).map(|(name, _)| name.as_str())
+    }
+
+    /// (名前, 色)を状態の順に返す
+    pub fn entries(&self) -> &[(String, [u8; 3])] {
+        &self.entries
+    }
+
+    /// `cells`をウィンドウで表示するときと同じ色のRGBA(1セル4byte、行優先)に変換する
+    ///
+    /// # Example
+    /// ```
+    /// extern crate ndarray;
+    /// extern crate my_alife;
+    ///
+    /// use ndarray::arr2;
+    /// use my_alife::visualizer::palette::Palette;
+    ///
+    /// let palette = Palette::new().with("S", [0, 0, 255]).with("I", [255, 0, 0]);
+    /// let rgba = palette.to_rgba(&arr2(&[[0, 1]]));
+    /// assert_eq!(rgba, vec![0, 0, 255, 255, 255, 0, 0, 255]);
+    /// ```
+    pub fn to_rgba(&self, cells: &Matrix<u8>) -> Vec<u8> {
+        let mut rgba = Vec::with_capacity(cells.len() * 4);
+        self.write_rgba(cells, &mut rgba);
+        rgba
+    }
+
+    pub(crate) fn write_rgba(&self, cells: &Matrix<u8>, rgba: &mut Vec<u8>) {
+        rgba.clear();
+        for &state in cells.iter() {
+            let [r, g, b] = self.color(state);
+            rgba.extend_from_slice(&[r, g, b, 255]);
+        }
+    }
+
+    /// 色見本と名前を縦に並べた凡例。名前のない状態は載せない
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::visualizer::palette::Palette;
+    ///
+    /// let palette = Palette::new().with("Susceptible", [0, 0, 255]).with("Infected", [255, 0, 0]);
+    /// let legend = palette.legend();
+    /// assert!(legend.width() > legend.height());
+    /// // 1つ目の色見本
+    /// assert_eq!(legend.pixel(10, 10), [0, 0, 255, 255]);
+    /// ```
+    pub fn legend(&self) -> Canvas {
+        let named: Vec<_> = self.entries.iter().filter(|(name, _)| !name.is_empty()).collect();
+        let (_, text_height) = text_size("", LEGEND_SCALE);
+        let line = LEGEND_SWATCH.max(text_height) + LEGEND_PADDING;
+        let text_width = named
+            .iter()
+            .map(|(name, _)| text_size(name, LEGEND_SCALE).0)
+            .max()
+            .unwrap_or(0);
+        let width = LEGEND_PADDING * 3 + LEGEND_SWATCH + text_width;
+        let height = LEGEND_PADDING + line * named.len();
+        let mut canvas = Canvas::new(width, height);
+        canvas.fill_rect(0, 0, width, height, [0, 0, 0, 160]);
+        for (i, (name, [r, g, b])) in named.iter().enumerate() {
+            let top = LEGEND_PADDING + line * i;
+            canvas.fill_rect(LEGEND_PADDING, top, LEGEND_SWATCH, LEGEND_SWATCH, [*r, *g, *b, 255]);
+            let text_top = top + LEGEND_SWATCH.saturating_sub(text_height) / 2;
+            canvas.draw_text(
+                LEGEND_PADDING * 2 + LEGEND_SWATCH,
+                text_top,
+                name,
+                [255, 255, 255, 255],
+                LEGEND_SCALE,
+            );
+        }
+        canvas
+    }
+}
+
+// 色相`h`、彩度`s`、明度`v`(いずれも0.0〜1.0)をRGBにする
+pub(crate) fn hsv_to_rgb(h: f32, s: f32, v: f32) -> [u8; 3] {
+    let sector = h * 6.0;
+    let f = sector.fract();
+    let (p, q, t) = (v * (1.0 - s), v * (1.0 - s * f), v * (1.0 - s * (1.0 - f)));
+    let (r, g, b) = match sector as usize % 6 {
+        0 => (v, t, p),
+        1 => (q, v, p),
+        2 => (p, v, t),
+        3 => (p, q, v),
+        4 => (t, p, v),
+        _ => (v, p, q),
+    };
+    [(r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8]
+}
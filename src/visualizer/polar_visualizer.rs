@@ -0,0 +1,350 @@
+use failure;
+use glium::uniforms::{MagnifySamplerFilter, MinifySamplerFilter, Sampler, SamplerBehavior, SamplerWrapFunction};
+use glium::{glutin, index, Display, Program, Surface, VertexBuffer};
+use std::f32::consts::PI;
+use visualizer::colormap::{Colormap, Transform};
+use visualizer::mouse::Mouse;
+use visualizer::texture::{GpuTexture, TextureFormat, TextureUploader, ValueMapping};
+use visualizer::{ControlFlow, Draw, Matrix, VirtualKeyCode};
+
+const POLAR_VERTEX_SHADER: &str = r#"
+#version 140
+
+in vec2 a_position;
+in vec2 a_texcoord;
+out vec2 v_texcoord;
+void main()
+{
+    gl_Position = vec4(a_position, 0.0, 1.0);
+    v_texcoord = a_texcoord;
+}
+"#;
+
+// PolarMapping::polarと同じ計算で、画面の位置からテクスチャの(角度, 半径)を求める
+const POLAR_FRAGMENT_SHADER: &str = r#"
+#version 140
+
+uniform sampler2D u_texture;
+uniform float u_inner_radius;
+uniform float u_rotation;
+// 円が短い辺に収まるようにするための(幅, 高さ) / 短い辺
+uniform vec2 u_scale;
+in vec2 v_texcoord;
+out vec4 flagColor;
+const float TAU = 6.28318530718;
+void main()
+{
+    vec2 p = (vec2(v_texcoord.x, 1.0 - v_texcoord.y) * 2.0 - 1.0) * u_scale;
+    float r = length(p);
+    if (r > 1.0 || r < u_inner_radius) {
+        flagColor = vec4(0.0, 0.0, 0.0, 1.0);
+        return;
+    }
+    float radial = (r - u_inner_radius) / (1.0 - u_inner_radius);
+    float angle = mod(atan(p.y, p.x) - u_rotation, TAU) / TAU;
+    flagColor = vec4(texture(u_texture, vec2(angle, radial)).rgb, 1.0);
+}
+"#;
+
+/// Matrixの行を半径、列を角度にして円盤(円環)に描くときの対応
+///
+/// 0行目が内側の円、最後の行が外側の円になる。列は`rotation`の向き(0なら右)から反時計回りに1周する
+///
+/// # Example
+/// ```
+/// use my_alife::visualizer::polar_visualizer::PolarMapping;
+///
+/// let polar = PolarMapping { inner_radius: 0.5, rotation: 0.0 };
+/// // 右端は外側の円の角度0
+/// assert_eq!(polar.cell_at((0.99, 0.0), (10, 36)), Some((9, 0)));
+/// // 真上は1/4周
+/// assert_eq!(polar.cell_at((0.0, 0.51), (10, 36)), Some((0, 9)));
+/// // 円環の内側と外側にはセルがない
+/// assert_eq!(polar.cell_at((0.1, 0.1), (10, 36)), None);
+/// assert_eq!(polar.cell_at((1.0, 1.0), (10, 36)), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PolarMapping {
+    /// 内側の円の半径(外側の円を1とする)。0なら円盤になる
+    pub inner_radius: f32,
+    /// 0列目の向き(rad)。右から反時計回りに測る。回転座標系のモデルで一緒に回すときに使う
+    pub rotation: f32,
+}
+
+impl Default for PolarMapping {
+    fn default() -> PolarMapping {
+        PolarMapping {
+            inner_radius: 0.0,
+            rotation: 0.0,
+        }
+    }
+}
+
+impl PolarMapping {
+    /// 中心を原点、外側の円の半径を1とした座標(上が+y)の位置の(半径, 角度)を、それぞれ0〜1にしたもの。円環の外ならば`None`
+    pub fn polar(&self, point: (f32, f32)) -> Option<(f32, f32)> {
+        let r = point.0.hypot(point.1);
+        if r > 1.0 || r < self.inner_radius {
+            return None;
+        }
+        let radial = (r - self.inner_radius) / (1.0 - self.inner_radius).max(f32::EPSILON);
+        let angle = (point.1.atan2(point.0) - self.rotation).rem_euclid(2.0 * PI) / (2.0 * PI);
+        Some((radial, angle))
+    }
+
+    /// `point`に描かれる`dim`の大きさのMatrixのセル
+    pub fn cell_at(&self, point: (f32, f32), dim: (usize, usize)) -> Option<(usize, usize)> {
+        let (rows, cols) = dim;
+        if rows == 0 || cols == 0 {
+            return None;
+        }
+        let (radial, angle) = self.polar(point)?;
+        let row = ((radial * rows as f32) as usize).min(rows - 1);
+        let col = ((angle * cols as f32) as usize).min(cols - 1);
+        Some((row, col))
+    }
+}
+
+#[derive(Copy, Clone)]
+struct PolarVertex {
+    a_position: [f32; 2],
+    a_texcoord: [f32; 2],
+}
+// glium 0.22のマクロ内部で非推奨のmem::uninitializedが使われている
+#[allow(deprecated)]
+mod vertex_impl {
+    use super::PolarVertex;
+    implement_vertex!(PolarVertex, a_position, a_texcoord);
+}
+
+/// 極座標系を用いてvisualizeする構造体。Matrixの行を半径、列を角度にして円盤(円環)に描く
+///
+/// リング状の生息地や、回転座標系で書いたモデルを表示するために使う
+///
+/// # Example
+/// ```no_run
+/// use my_alife::algorithm::gray_scott::{initial_matrix, laplacian};
+/// use my_alife::visualizer::polar_visualizer::PolarVisualizer;
+/// use my_alife::visualizer::{ControlFlow, Draw};
+///
+/// let mut polar = PolarVisualizer::new("Gray Scott on a ring").unwrap();
+/// polar.set_inner_radius(0.3);
+/// let mut uv = initial_matrix();
+/// let mut frame = 0;
+/// loop {
+///     frame += 1;
+///     // 少しずつ回す
+///     polar.set_rotation(frame as f32 * 0.01);
+///     if polar.render_frame(laplacian(&mut uv, 0.04, 0.06)).unwrap() == ControlFlow::Stop {
+///         break;
+///     }
+/// }
+/// ```
+pub struct PolarVisualizer {
+    events_loop: glutin::EventsLoop,
+    display: Display,
+    program: Program,
+    vertex_buffer: VertexBuffer<PolarVertex>,
+    uploader: TextureUploader,
+    mapping: ValueMapping,
+    polar: PolarMapping,
+    mouse: Mouse,
+    // 直前の`poll_events`の間に押されたキー
+    keys: Vec<VirtualKeyCode>,
+    // 最後に描画したMatrixの大きさ
+    drawn_dim: Option<(usize, usize)>,
+}
+
+impl PolarVisualizer {
+    /// 600×600のウィンドウを開く
+    pub fn new(title: &str) -> Result<PolarVisualizer, failure::Error> {
+        let events_loop = glutin::EventsLoop::new();
+        let window = glutin::WindowBuilder::new()
+            .with_dimensions((600, 600).into())
+            .with_title(title);
+        let context = glutin::ContextBuilder::new();
+        let display = Display::new(window, context, &events_loop).map_err(|e| format_err!("{}", e))?;
+        // 色はsRGBのまま書き込むので、gliumがGL_FRAMEBUFFER_SRGBを有効にしないようにする
+        let program = program!(&display, 140 => {
+            vertex: POLAR_VERTEX_SHADER,
+            fragment: POLAR_FRAGMENT_SHADER,
+            outputs_srgb: true
+        })?;
+        let vertex = |x: f32, y: f32| PolarVertex {
+            a_position: [x, y],
+            a_texcoord: [(x + 1.0) / 2.0, (1.0 - y) / 2.0],
+        };
+        let shape = [
+            vertex(-1.0, -1.0),
+            vertex(1.0, -1.0),
+            vertex(1.0, 1.0),
+            vertex(-1.0, -1.0),
+            vertex(-1.0, 1.0),
+            vertex(1.0, 1.0),
+        ];
+        let vertex_buffer = VertexBuffer::new(&display, &shape)?;
+        Ok(PolarVisualizer {
+            events_loop,
+            display,
+            program,
+            vertex_buffer,
+            uploader: TextureUploader::new(TextureFormat::Rgba8),
+            mapping: ValueMapping::default(),
+            polar: PolarMapping::default(),
+            mouse: Mouse::default(),
+            keys: Vec::new(),
+            drawn_dim: None,
+        })
+    }
+
+    /// 正規化するときの(最小値, 最大値)を設定する。初期値は(0.0, 1.0)
+    pub fn set_value_range(&mut self, min: f32, max: f32) {
+        self.mapping.range = (min, max);
+    }
+
+    /// 正規化するときの変換を設定する
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.mapping.transform = transform;
+    }
+
+    /// 正規化した値を色に変換する方法を設定する
+    pub fn set_colormap(&mut self, colormap: Colormap) {
+        self.mapping.colormap = colormap;
+    }
+
+    /// 内側の円の半径(外側の円を1とする)。0なら円盤に描く
+    pub fn set_inner_radius(&mut self, inner_radius: f32) {
+        self.polar.inner_radius = inner_radius.clamp(0.0, 0.99);
+    }
+
+    /// 0列目の向き(rad)を設定する
+    pub fn set_rotation(&mut self, rotation: f32) {
+        self.polar.rotation = rotation;
+    }
+
+    /// 行・列と半径・角度の対応
+    pub fn polar(&self) -> PolarMapping {
+        self.polar
+    }
+
+    /// マウスの状態
+    pub fn mouse(&self) -> &Mouse {
+        &self.mouse
+    }
+
+    /// 直前の`poll_events`の間に押されたキー
+    pub fn pressed_keys(&self) -> &[VirtualKeyCode] {
+        &self.keys
+    }
+
+    /// マウスが指しているセル。円環の外や、まだ描画していないときは`None`
+    pub fn mouse_cell(&self) -> Option<(usize, usize)> {
+        let (x, y) = self.mouse.position?;
+        let (width, height) = self.window_size()?;
+        let side = width.min(height);
+        let point = ((2.0 * x - width) / side, (height - 2.0 * y) / side);
+        self.polar.cell_at((point.0 as f32, point.1 as f32), self.drawn_dim?)
+    }
+
+    fn window_size(&self) -> Option<(f64, f64)> {
+        let size = self.display.gl_window().get_inner_size()?;
+        Some((size.width, size.height))
+    }
+
+    fn render(&self) -> Result<(), failure::Error> {
+        let texture = match self.uploader.texture() {
+            Some((GpuTexture::Linear(texture), _)) => texture,
+            _ => return Ok(()),
+        };
+        let mut target = self.display.draw();
+        let (width, height) = target.get_dimensions();
+        let side = width.min(height).max(1) as f32;
+        // 角度の方向は1周でつながり、半径の方向はつながらない
+        let behavior = SamplerBehavior {
+            wrap_function: (
+                SamplerWrapFunction::Repeat,
+                SamplerWrapFunction::Clamp,
+                SamplerWrapFunction::Clamp,
+            ),
+            minify_filter: MinifySamplerFilter::Linear,
+            magnify_filter: MagnifySamplerFilter::Linear,
+            ..Default::default()
+        };
+        let uniforms = uniform! {
+            u_texture: Sampler(texture, behavior),
+            u_inner_radius: self.polar.inner_radius,
+            u_rotation: self.polar.rotation,
+            u_scale: [width as f32 / side, height as f32 / side],
+        };
+        target.clear_color(0.0, 0.0, 0.0, 1.0);
+        let indices = index::NoIndices(index::PrimitiveType::TrianglesList);
+        let drawn = target.draw(
+            &self.vertex_buffer,
+            indices,
+            &self.program,
+            &uniforms,
+            &Default::default(),
+        );
+        target.finish()?;
+        Ok(drawn?)
+    }
+}
+
+impl Draw for PolarVisualizer {
+    fn set_title(&self, title: &str) {
+        self.display.gl_window().set_title(title);
+    }
+
+    fn draw<A: Copy + Into<f32>>(&mut self, matrix: &Matrix<A>) -> Result<(), failure::Error> {
+        self.drawn_dim = Some(matrix.dim());
+        self.uploader.upload(&self.display, matrix, &self.mapping)?;
+        self.render()
+    }
+
+    fn draw_states(&mut self, cells: &Matrix<u8>, states: u8) -> Result<(), failure::Error> {
+        let mapping = ValueMapping {
+            range: (0.0, f32::from(states.max(2) - 1)),
+            transform: Transform::Linear,
+            ..self.mapping
+        };
+        self.drawn_dim = Some(cells.dim());
+        self.uploader.upload(&self.display, cells, &mapping)?;
+        self.render()
+    }
+
+    fn poll_events(&mut self) -> ControlFlow {
+        let mut flow = ControlFlow::Continue;
+        let mouse = &mut self.mouse;
+        let keys = &mut self.keys;
+        keys.clear();
+        self.events_loop.poll_events(|event| {
+            if let glutin::Event::WindowEvent { event, .. } = event {
+                match event {
+                    glutin::WindowEvent::CloseRequested => flow = ControlFlow::Stop,
+                    glutin::WindowEvent::CursorMoved {
+                        position, modifiers, ..
+                    } => {
+                        mouse.position = Some((position.x, position.y));
+                        mouse.shift = modifiers.shift;
+                    }
+                    glutin::WindowEvent::CursorLeft { .. } => mouse.position = None,
+                    glutin::WindowEvent::MouseInput { state, button, .. } => {
+                        let pressed = state == glutin::ElementState::Pressed;
+                        match button {
+                            glutin::MouseButton::Left => mouse.left = pressed,
+                            glutin::MouseButton::Right => mouse.right = pressed,
+                            _ => {}
+                        }
+                    }
+                    glutin::WindowEvent::KeyboardInput { input, .. } => {
+                        if let (Some(key), glutin::ElementState::Pressed) = (input.virtual_keycode, input.state) {
+                            keys.push(key);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        });
+        flow
+    }
+}
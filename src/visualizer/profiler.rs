@@ -0,0 +1,202 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// 1フレームの中の段階
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// 盤面の更新(`draw_loop`などに渡した関数)
+    Update,
+    /// 盤面をテクスチャに書き込む形式に変換する
+    Convert,
+    /// テクスチャに転送する
+    Upload,
+    /// 描画して画面を入れ替える
+    Draw,
+}
+
+impl Phase {
+    /// すべての段階。1フレームの中で行う順に並んでいる
+    pub const ALL: [Phase; 4] = [Phase::Update, Phase::Convert, Phase::Upload, Phase::Draw];
+
+    fn index(self) -> usize {
+        match self {
+            Phase::Update => 0,
+            Phase::Convert => 1,
+            Phase::Upload => 2,
+            Phase::Draw => 3,
+        }
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            Phase::Update => "update",
+            Phase::Convert => "convert",
+            Phase::Upload => "upload",
+            Phase::Draw => "draw",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// ある段階にかかった時間の集計
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PhaseStats {
+    /// すべてのフレームの合計
+    pub total: Duration,
+    /// 一番時間がかかったフレームの時間
+    pub max: Duration,
+    /// 最後のフレームの時間
+    pub last: Duration,
+}
+
+/// 各段階にかかった時間をフレームごとに集計する
+///
+/// `profiling`フィーチャーを有効にすると`MatrixVisualizer`が自動で計測し、
+/// ウィンドウのタイトルに直前のフレームの内訳を表示して、終了時に集計を標準エラー出力に書き出す
+///
+/// # Example
+/// ```
+/// use my_alife::visualizer::profiler::{Phase, Profiler};
+/// use std::time::Duration;
+///
+/// let mut profiler = Profiler::new();
+/// for i in 1..=4 {
+///     profiler.record(Phase::Update, Duration::from_millis(i));
+///     profiler.record(Phase::Draw, Duration::from_millis(2));
+///     profiler.end_frame();
+/// }
+/// assert_eq!(profiler.frames(), 4);
+/// assert_eq!(profiler.mean(Phase::Update), Duration::from_micros(2500));
+/// assert_eq!(profiler.stats(Phase::Update).max, Duration::from_millis(4));
+/// assert_eq!(profiler.hud(), "update 4.00ms | convert 0.00ms | upload 0.00ms | draw 2.00ms");
+/// assert!(profiler.report().contains("frames: 4"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Profiler {
+    stats: [PhaseStats; 4],
+    // 終わっていないフレームで計った時間
+    current: [Duration; 4],
+    frames: usize,
+}
+
+impl Profiler {
+    /// 何も計っていない状態
+    pub fn new() -> Profiler {
+        Profiler::default()
+    }
+
+    /// いまのフレームで`phase`に`elapsed`かかったことを記録する。同じフレームで何度記録してもよい
+    pub fn record(&mut self, phase: Phase, elapsed: Duration) {
+        self.current[phase.index()] += elapsed;
+    }
+
+    /// `f`にかかった時間を`phase`として記録する
+    pub fn time<R, F: FnOnce() -> R>(&mut self, phase: Phase, f: F) -> R {
+        let start = Instant::now();
+        let result = f();
+        self.record(phase, start.elapsed());
+        result
+    }
+
+    /// いまのフレームを終えて集計に加える
+    pub fn end_frame(&mut self) {
+        for (stats, current) in self.stats.iter_mut().zip(self.current.iter_mut()) {
+            stats.total += *current;
+            stats.max = stats.max.max(*current);
+            stats.last = *current;
+            *current = Duration::from_secs(0);
+        }
+        self.frames += 1;
+    }
+
+    /// 集計したフレームの数
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// `phase`の集計
+    pub fn stats(&self, phase: Phase) -> &PhaseStats {
+        &self.stats[phase.index()]
+    }
+
+    /// `phase`の1フレームあたりの平均
+    pub fn mean(&self, phase: Phase) -> Duration {
+        match self.frames {
+            0 => Duration::from_secs(0),
+            frames => self.stats(phase).total / frames as u32,
+        }
+    }
+
+    /// 直前のフレームの内訳を1行にしたもの。ウィンドウのタイトルに表示する
+    pub fn hud(&self) -> String {
+        Phase::ALL
+            .iter()
+            .map(|&phase| format!("{} {:.2}ms", phase, millis(self.stats(phase).last)))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+
+    /// 段階ごとの平均・最大と、1フレームに占める割合をまとめた表
+    pub fn report(&self) -> String {
+        let whole: Duration = self.stats.iter().map(|stats| stats.total).sum();
+        let mut report = format!(
+            "frames: {}\n{:<8} {:>10} {:>10} {:>7}",
+            self.frames, "phase", "mean", "max", "share"
+        );
+        for &phase in &Phase::ALL {
+            let share = if whole > Duration::from_secs(0) {
+                self.stats(phase).total.as_secs_f64() / whole.as_secs_f64() * 100.0
+            } else {
+                0.0
+            };
+            report += &format!(
+                "\n{:<8} {:>8.2}ms {:>8.2}ms {:>6.1}%",
+                phase.to_string(),
+                millis(self.mean(phase)),
+                millis(self.stats(phase).max),
+                share
+            );
+        }
+        report
+    }
+}
+
+impl fmt::Display for Profiler {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.report())
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// 経過時間を計る。`profiling`フィーチャーが無効なときは何もせず、常に0を返す
+#[cfg(feature = "gl")]
+pub(crate) struct Stopwatch {
+    #[cfg(feature = "profiling")]
+    start: Instant,
+}
+
+#[cfg(feature = "gl")]
+impl Stopwatch {
+    pub(crate) fn start() -> Stopwatch {
+        Stopwatch {
+            #[cfg(feature = "profiling")]
+            start: Instant::now(),
+        }
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        #[cfg(feature = "profiling")]
+        {
+            self.start.elapsed()
+        }
+        #[cfg(not(feature = "profiling"))]
+        {
+            Duration::from_secs(0)
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use algorithm::patterns::{transform, Rotation};
+use algorithm::region::{crop, paste, Boundary, Region};
+use visualizer::Matrix;
+use visualizer::mouse::Mouse;
+#[cfg(feature = "gl")]
+use visualizer::VirtualKeyCode;
+
+/// Shiftを押しながら左ドラッグで選ぶ長方形の領域
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Selection {
+    anchor: Option<(usize, usize)>,
+    region: Option<Region>,
+}
+
+impl Selection {
+    /// 何も選択していない状態を生成する
+    pub fn new() -> Selection {
+        Selection::default()
+    }
+
+    /// マウスの状態から選択範囲を更新する。ドラッグ中ならばtrueを返すので、その間はブラシなどを止めるとよい
+    ///
+    /// # Arguments
+    /// * `mouse` - マウスの状態
+    /// * `cell` - カーソルが指しているセル
+    ///
+    /// # Example
+    /// ```
+    /// use my_alife::algorithm::region::Region;
+    /// use my_alife::visualizer::mouse::Mouse;
+    /// use my_alife::visualizer::selection::Selection;
+    ///
+    /// let drag = Mouse { left: true, shift: true, ..Mouse::default() };
+    /// let mut selection = Selection::new();
+    /// assert!(selection.update(&drag, Some((5, 8))));
+    /// assert!(selection.update(&drag, Some((2, 10))));
+    /// assert!(!selection.update(&Mouse::default(), Some((0, 0))));
+    /// assert_eq!(selection.region(), Some(Region { row: 2, col: 8, rows: 4, cols: 3 }));
+    /// ```
+    pub fn update(&mut self, mouse: &Mouse, cell: Option<(usize, usize)>) -> bool {
+        if !mouse.left {
+            self.anchor = None;
+            return false;
+        }
+        match (self.anchor, cell) {
+            (Some(anchor), Some(cell)) => self.region = Some(span(anchor, cell)),
+            (None, Some(cell)) if mouse.shift => {
+                self.anchor = Some(cell);
+                self.region = Some(span(cell, cell));
+            }
+            _ => {}
+        }
+        self.anchor.is_some()
+    }
+
+    /// 選択範囲。何も選んでいなければ`None`
+    pub fn region(&self) -> Option<Region> {
+        self.region
+    }
+
+    /// ドラッグ中かどうか
+    pub fn is_dragging(&self) -> bool {
+        self.anchor.is_some()
+    }
+
+    /// 選択を解除する
+    pub fn clear(&mut self) {
+        self.anchor = None;
+        self.region = None;
+    }
+}
+
+// 2つの角を含む最小の長方形
+fn span(a: (usize, usize), b: (usize, usize)) -> Region {
+    Region {
+        row: a.0.min(b.0),
+        col: a.1.min(b.1),
+        rows: a.0.max(b.0) - a.0.min(b.0) + 1,
+        cols: a.1.max(b.1) - a.1.min(b.1) + 1,
+    }
+}
+
+/// 盤面の一部を切り取って貼り付けるためのクリップボード
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Clipboard<A> {
+    contents: Option<Matrix<A>>,
+}
+
+impl<A: Copy> Clipboard<A> {
+    /// 空のクリップボードを生成する
+    pub fn new() -> Clipboard<A> {
+        Clipboard { contents: None }
+    }
+
+    /// 保持している内容
+    pub fn contents(&self) -> Option<&Matrix<A>> {
+        self.contents.as_ref()
+    }
+
+    /// `state`の`region`を写し取る。盤面からはみ出した部分は周期境界条件で反対側から取る
+    pub fn copy(&mut self, state: &Matrix<A>, region: Region) {
+        self.contents = Some(crop(
+            state,
+            region.row as isize,
+            region.col as isize,
+            region.rows,
+            region.cols,
+            Boundary::Periodic,
+        ));
+    }
+
+    /// `state`の`region`を写し取り、元の場所を`fill`で埋める
+    ///
+    /// # Example
+    /// ```
+    /// extern crate ndarray;
+    /// extern crate my_alife;
+    ///
+    /// use ndarray::arr2;
+    /// use my_alife::algorithm::region::Region;
+    /// use my_alife::visualizer::selection::Clipboard;
+    ///
+    /// let mut state = arr2(&[[1, 2, 3], [4, 5, 6]]);
+    /// let mut clipboard = Clipboard::new();
+    /// clipboard.cut(&mut state, Region { row: 0, col: 1, rows: 2, cols: 2 }, 0);
+    /// assert_eq!(state, arr2(&[[1, 0, 0], [4, 0, 0]]));
+    /// assert_eq!(clipboard.contents(), Some(&arr2(&[[2, 3], [5, 6]])));
+    /// ```
+    pub fn cut(&mut self, state: &mut Matrix<A>, region: Region, fill: A) {
+        self.copy(state, region);
+        let (rows, cols) = state.dim();
+        let bottom = (region.row + region.rows).min(rows);
+        let right = (region.col + region.cols).min(cols);
+        if region.row < bottom && region.col < right {
+            state.slice_mut(s![region.row..bottom, region.col..right]).fill(fill);
+        }
+    }
+
+    /// 保持している内容を`at`を左上として`state`に貼り付ける。空ならばfalseを返す
+    ///
+    /// # Arguments
+    /// * `state` - 貼り付けられる盤面
+    /// * `at` - 左上の(行, 列)
+    /// * `periodic` - trueならばはみ出した部分を反対側に貼り付け、falseならば捨てる
+    pub fn paste(&self, state: &mut Matrix<A>, at: (usize, usize), periodic: bool) -> bool {
+        match self.contents {
+            Some(ref contents) => {
+                paste(state, contents, at.0 as isize, at.1 as isize, periodic);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 保持している内容を時計回りに90度回転する
+    ///
+    /// # Example
+    /// ```
+    /// extern crate ndarray;
+    /// extern crate my_alife;
+    ///
+    /// use ndarray::arr2;
+    /// use my_alife::algorithm::region::Region;
+    /// use my_alife::visualizer::selection::Clipboard;
+    ///
+    /// let mut clipboard = Clipboard::new();
+    /// clipboard.copy(&arr2(&[[1, 2, 3], [4, 5, 6]]), Region { row: 0, col: 0, rows: 2, cols: 3 });
+    /// clipboard.rotate();
+    /// assert_eq!(clipboard.contents(), Some(&arr2(&[[4, 1], [5, 2], [6, 3]])));
+    /// clipboard.mirror();
+    /// assert_eq!(clipboard.contents(), Some(&arr2(&[[1, 4], [2, 5], [3, 6]])));
+    /// ```
+    pub fn rotate(&mut self) {
+        self.contents = self
+            .contents
+            .as_ref()
+            .map(|contents| transform(contents, Rotation::Deg90, false));
+    }
+
+    /// 保持している内容を左右反転する
+    pub fn mirror(&mut self) {
+        self.contents = self
+            .contents
+            .as_ref()
+            .map(|contents| transform(contents, Rotation::Deg0, true));
+    }
+
+    /// キー操作を反映する。C: コピー, X: 切り取り, V: カーソル位置に貼り付け, R: 回転, M: 左右反転。
+    /// `state`を書き換えたときにtrueを返す
+    ///
+    /// # Arguments
+    /// * `keys` - このフレームで押されたキー
+    /// * `state` - 操作する盤面
+    /// * `selection` - 選択範囲
+    /// * `cursor` - カーソルが指しているセル
+    /// * `fill` - 切り取った跡を埋める値
+    ///
+    /// # Example
+    /// ```
+    /// extern crate ndarray;
+    /// extern crate my_alife;
+    ///
+    /// use ndarray::Array2;
+    /// use my_alife::algorithm::region::Region;
+    /// use my_alife::visualizer::selection::Clipboard;
+    /// use my_alife::visualizer::VirtualKeyCode;
+    ///
+    /// let mut state = Array2::<u8>::zeros((8, 8));
+    /// state[[1, 1]] = 1;
+    /// let region = Some(Region { row: 0, col: 0, rows: 3, cols: 3 });
+    /// let mut clipboard = Clipboard::new();
+    /// assert!(!clipboard.handle_keys(&[VirtualKeyCode::C], &mut state, region, None, 0));
+    /// assert!(clipboard.handle_keys(&[VirtualKeyCode::V], &mut state, region, Some((4, 4)), 0));
+    /// assert_eq!(state[[5, 5]], 1);
+    /// ```
+    #[cfg(feature = "gl")]
+    pub fn handle_keys(
+        &mut self,
+        keys: &[VirtualKeyCode],
+        state: &mut Matrix<A>,
+        selection: Option<Region>,
+        cursor: Option<(usize, usize)>,
+        fill: A,
+    ) -> bool {
+        let mut changed = false;
+        for &key in keys {
+            match (key, selection, cursor) {
+                (VirtualKeyCode::C, Some(region), _) => self.copy(state, region),
+                (VirtualKeyCode::X, Some(region), _) => {
+                    self.cut(state, region, fill);
+                    changed = true;
+                }
+                (VirtualKeyCode::V, _, Some(at)) => changed |= self.paste(state, at, true),
+                (VirtualKeyCode::R, _, _) => self.rotate(),
+                (VirtualKeyCode::M, _, _) => self.mirror(),
+                _ => {}
+            }
+        }
+        changed
+    }
+}
+
+/// `region`の枠を`value`で描いた`view`の複製。表示用で、盤面そのものは変えない
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::algorithm::region::Region;
+/// use my_alife::visualizer::selection::outline;
+///
+/// let view = outline(&Array2::<f32>::zeros((8, 8)), Region { row: 2, col: 2, rows: 3, cols: 4 }, 1.0);
+/// assert_eq!(view.iter().filter(|&&e| e == 1.0).count(), 10);
+/// assert_eq!(view[[3, 3]], 0.0);
+/// ```
+pub fn outline<A: Copy>(view: &Matrix<A>, region: Region, value: A) -> Matrix<A> {
+    let mut view = view.clone();
+    let (rows, cols) = view.dim();
+    let bottom = region.row + region.rows - 1;
+    let right = region.col + region.cols - 1;
+    for r in region.row..=bottom {
+        for c in region.col..=right {
+            let edge = r == region.row || r == bottom || c == region.col || c == right;
+            if edge && r < rows && c < cols {
+                view[[r, c]] = value;
+            }
+        }
+    }
+    view
+}
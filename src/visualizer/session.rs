@@ -0,0 +1,214 @@
+use algorithm::checkpoint::{join, parse_list, CheckpointRng};
+use failure;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// ユーザーの1回の操作
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interaction {
+    /// 操作したときのステップ数
+    pub step: u64,
+    /// 操作の種類。空白を含まない
+    pub name: String,
+    /// 操作の引数
+    pub args: Vec<f32>,
+}
+
+/// 乱数の種と、操作をステップ数とともに並べたもの。同じ種で操作をやり直すと同じ結果になる
+///
+/// ファイルは1行目が`session <seed>`で、2行目以降が`<step> <name> <args...>`になる
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate rand;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use rand::Rng;
+/// use my_alife::visualizer::session::{Interaction, Player, Session};
+///
+/// // 毎ステップ乱数で選んだセルを反転し、操作で指定したセルを1にする
+/// fn simulate<F: FnMut(u64) -> Vec<Interaction>>(session: &Session, mut input: F) -> Array2<u8> {
+///     let mut rng = session.rng();
+///     let mut cells = Array2::zeros((8, 8));
+///     for step in 0..20 {
+///         for event in input(step) {
+///             cells[[event.args[0] as usize, event.args[1] as usize]] = 1;
+///         }
+///         let (r, c) = (rng.gen_range(0, 8), rng.gen_range(0, 8));
+///         cells[[r, c]] ^= 1;
+///     }
+///     cells
+/// }
+///
+/// let mut session = Session::new(42);
+/// let recorded = simulate(&Session::new(42), |step| {
+///     if step % 7 == 3 {
+///         session.record(step, "set", &[(step % 8) as f32, 2.0]);
+///     }
+///     session.at(step).to_vec()
+/// });
+/// assert_eq!(session.interactions().len(), 3);
+///
+/// let loaded: Session = session.to_string().parse().unwrap();
+/// let mut player = Player::new(loaded.clone());
+/// assert_eq!(simulate(&loaded, |step| player.poll(step)), recorded);
+/// assert!(player.is_finished());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session {
+    seed: u64,
+    interactions: Vec<Interaction>,
+}
+
+impl Session {
+    /// 乱数の種`seed`で始まる、操作のないセッションを生成する
+    pub fn new(seed: u64) -> Session {
+        Session {
+            seed,
+            interactions: Vec::new(),
+        }
+    }
+
+    /// 乱数の種
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// 種から作った乱数生成器。記録するときも再生するときもこれを使う
+    pub fn rng(&self) -> CheckpointRng {
+        CheckpointRng::new(self.seed)
+    }
+
+    /// 記録した操作
+    pub fn interactions(&self) -> &[Interaction] {
+        &self.interactions
+    }
+
+    /// `step`ステップ目の操作を記録する
+    ///
+    /// # Panics
+    /// `step`が最後に記録した操作より前のとき、または`name`が空か空白を含むとき
+    pub fn record(&mut self, step: u64, name: &str, args: &[f32]) {
+        assert!(
+            self.interactions.last().is_none_or(|last| last.step <= step),
+            "interactions must be recorded in step order"
+        );
+        assert!(
+            !name.is_empty() && !name.contains(char::is_whitespace),
+            "invalid interaction name {:?}",
+            name
+        );
+        self.interactions.push(Interaction {
+            step,
+            name: name.to_string(),
+            args: args.to_vec(),
+        });
+    }
+
+    /// `step`ステップ目に記録した操作
+    pub fn at(&self, step: u64) -> &[Interaction] {
+        let start = self.interactions.partition_point(|interaction| interaction.step < step);
+        let end = self
+            .interactions
+            .partition_point(|interaction| interaction.step <= step);
+        &self.interactions[start..end]
+    }
+
+    /// 最後の操作のステップ数
+    pub fn last_step(&self) -> Option<u64> {
+        self.interactions.last().map(|interaction| interaction.step)
+    }
+
+    /// ファイルに書き出す
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), failure::Error> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    /// `save`で書き出したファイルから読み込む
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Session, failure::Error> {
+        fs::read_to_string(path)?.parse()
+    }
+}
+
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "session {}", self.seed)?;
+        for interaction in &self.interactions {
+            writeln!(
+                f,
+                "{} {} {}",
+                interaction.step,
+                interaction.name,
+                join(&interaction.args)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Session {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Session, failure::Error> {
+        let mut lines = s.lines();
+        let seed = match lines.next().map(|line| line.split_whitespace().collect::<Vec<_>>()) {
+            Some(ref header) if header.len() == 2 && header[0] == "session" => header[1].parse()?,
+            _ => return Err(format_err!("not a session")),
+        };
+        let mut session = Session::new(seed);
+        for line in lines.filter(|line| !line.trim().is_empty()) {
+            let mut parts = line.splitn(3, ' ');
+            let step: u64 = parts.next().unwrap_or("").parse()?;
+            let name = parts
+                .next()
+                .filter(|name| !name.is_empty())
+                .ok_or_else(|| format_err!("missing interaction name: {}", line))?;
+            let args = parse_list(parts.next().unwrap_or(""))?;
+            if session.last_step().is_some_and(|last| last > step) {
+                return Err(format_err!("interactions are not in step order: {}", line));
+            }
+            session.record(step, name, &args);
+        }
+        Ok(session)
+    }
+}
+
+/// 記録した操作をステップ数に合わせて取り出す
+#[derive(Debug, Clone)]
+pub struct Player {
+    session: Session,
+    cursor: usize,
+}
+
+impl Player {
+    /// `session`を最初から再生する
+    pub fn new(session: Session) -> Player {
+        Player { session, cursor: 0 }
+    }
+
+    /// 再生しているセッション
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    /// `step`ステップ目までのまだ取り出していない操作を取り出す。ステップ数は増えていく順に渡す
+    pub fn poll(&mut self, step: u64) -> Vec<Interaction> {
+        let interactions = &self.session.interactions[self.cursor..];
+        let count = interactions
+            .iter()
+            .take_while(|interaction| interaction.step <= step)
+            .count();
+        self.cursor += count;
+        interactions[..count].to_vec()
+    }
+
+    /// すべての操作を取り出したかどうか
+    pub fn is_finished(&self) -> bool {
+        self.cursor == self.session.interactions.len()
+    }
+}
@@ -0,0 +1,392 @@
+use failure;
+use glium::uniforms::{MagnifySamplerFilter, MinifySamplerFilter, Sampler, SamplerBehavior, SamplerWrapFunction};
+use glium::{glutin, index, Display, Program, Surface, VertexBuffer};
+use std::f32::consts::{FRAC_PI_2, PI};
+use visualizer::colormap::{Colormap, Transform};
+use visualizer::mouse::Mouse;
+use visualizer::texture::{GpuTexture, TextureFormat, TextureUploader, ValueMapping};
+use visualizer::{ControlFlow, Draw, Matrix, VirtualKeyCode};
+
+const SPHERE_VERTEX_SHADER: &str = r#"
+#version 140
+
+in vec2 a_position;
+out vec2 v_position;
+void main()
+{
+    gl_Position = vec4(a_position, 0.0, 1.0);
+    v_position = a_position;
+}
+"#;
+
+// SphereMapping::lat_longと同じ計算で、画面の位置から球面上の(経度, 緯度)を求める
+const SPHERE_FRAGMENT_SHADER: &str = r#"
+#version 140
+
+uniform sampler2D u_texture;
+uniform float u_yaw;
+uniform float u_pitch;
+// 球が短い辺に収まるようにするための(幅, 高さ) / 短い辺
+uniform vec2 u_scale;
+in vec2 v_position;
+out vec4 flagColor;
+const float PI = 3.14159265359;
+void main()
+{
+    vec2 p = v_position * u_scale;
+    float d = dot(p, p);
+    if (d > 1.0) {
+        flagColor = vec4(0.0, 0.0, 0.0, 1.0);
+        return;
+    }
+    float z = sqrt(1.0 - d);
+    vec3 w = vec3(p.x, cos(u_pitch) * p.y + sin(u_pitch) * z, cos(u_pitch) * z - sin(u_pitch) * p.y);
+    float longitude = mod(atan(w.x, w.z) + u_yaw, 2.0 * PI) / (2.0 * PI);
+    float latitude = 0.5 - asin(clamp(w.y, -1.0, 1.0)) / PI;
+    // 縁に近いほど暗くして、球らしく見せる
+    float shade = 0.35 + 0.65 * z;
+    flagColor = vec4(texture(u_texture, vec2(longitude, latitude)).rgb * shade, 1.0);
+}
+"#;
+
+// ドラッグしたときに、ウィンドウの短い辺の長さあたりに回す角度(rad)
+const DRAG_SENSITIVITY: f64 = ::std::f64::consts::PI;
+
+/// Matrixを緯度経度の地図として球面に貼るときの対応
+///
+/// 0行目が北極、最後の行が南極になる。列は経度で、0列目から東向きに1周する
+///
+/// # Example
+/// ```
+/// use std::f32::consts::FRAC_PI_2;
+/// use my_alife::visualizer::sphere_visualizer::SphereMapping;
+///
+/// let sphere = SphereMapping::default();
+/// // 正面は赤道の経度0
+/// assert_eq!(sphere.cell_at((0.0, 0.0), (10, 36)), Some((5, 0)));
+/// // 上の縁は北極の近く
+/// assert_eq!(sphere.cell_at((0.0, 0.99), (10, 36)), Some((0, 0)));
+/// // 球の外にはセルがない
+/// assert_eq!(sphere.cell_at((1.0, 1.0), (10, 36)), None);
+///
+/// // 手前に90°傾けると、正面に北極が来る
+/// let tilted = SphereMapping { yaw: 0.0, pitch: FRAC_PI_2 };
+/// assert_eq!(tilted.cell_at((0.0, 0.0), (10, 36)).unwrap().0, 0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SphereMapping {
+    /// 正面に来る経度(rad)。増やすと球が西から東へ回って見える
+    pub yaw: f32,
+    /// 北極を手前に傾ける角度(rad)。-π/2〜π/2
+    pub pitch: f32,
+}
+
+impl Default for SphereMapping {
+    fn default() -> SphereMapping {
+        SphereMapping { yaw: 0.0, pitch: 0.0 }
+    }
+}
+
+impl SphereMapping {
+    /// 球の中心を原点、半径を1とした画面上の座標(上が+y)の位置の(緯度, 経度)を、それぞれ0〜1にしたもの
+    ///
+    /// 緯度は北極が0、南極が1になる。球の外ならば`None`
+    pub fn lat_long(&self, point: (f32, f32)) -> Option<(f32, f32)> {
+        let (x, y) = point;
+        let d = x * x + y * y;
+        if d > 1.0 {
+            return None;
+        }
+        let z = (1.0 - d).sqrt();
+        let (sin, cos) = self.pitch.sin_cos();
+        let world = (x, cos * y + sin * z, cos * z - sin * y);
+        let longitude = (world.0.atan2(world.2) + self.yaw).rem_euclid(2.0 * PI) / (2.0 * PI);
+        let latitude = 0.5 - world.1.clamp(-1.0, 1.0).asin() / PI;
+        Some((latitude, longitude))
+    }
+
+    /// `point`に見えている`dim`の大きさのMatrixのセル
+    pub fn cell_at(&self, point: (f32, f32), dim: (usize, usize)) -> Option<(usize, usize)> {
+        let (rows, cols) = dim;
+        if rows == 0 || cols == 0 {
+            return None;
+        }
+        let (latitude, longitude) = self.lat_long(point)?;
+        let row = ((latitude * rows as f32) as usize).min(rows - 1);
+        let col = ((longitude * cols as f32) as usize).min(cols - 1);
+        Some((row, col))
+    }
+}
+
+#[derive(Copy, Clone)]
+struct SphereVertex {
+    a_position: [f32; 2],
+}
+// glium 0.22のマクロ内部で非推奨のmem::uninitializedが使われている
+#[allow(deprecated)]
+mod vertex_impl {
+    use super::SphereVertex;
+    implement_vertex!(SphereVertex, a_position);
+}
+
+/// Matrixを緯度経度の地図として、回る球面に貼ってvisualizeする構造体
+///
+/// Daisyworldや全球の感染症、気候のおもちゃのモデルのように、惑星の表面で動くモデルを表示するために使う。
+/// 左ボタンでドラッグすると球を回せる
+///
+/// # Example
+/// ```no_run
+/// use my_alife::algorithm::gray_scott::{initial_matrix, laplacian};
+/// use my_alife::visualizer::sphere_visualizer::SphereVisualizer;
+/// use my_alife::visualizer::{ControlFlow, Draw};
+///
+/// let mut sphere = SphereVisualizer::new("Gray Scott on a sphere").unwrap();
+/// // 1フレームに0.01radずつ自転させ、北半球が少し見えるように傾ける
+/// sphere.set_spin(0.01);
+/// sphere.set_pitch(0.4);
+/// let mut uv = initial_matrix();
+/// loop {
+///     if sphere.render_frame(laplacian(&mut uv, 0.04, 0.06)).unwrap() == ControlFlow::Stop {
+///         break;
+///     }
+/// }
+/// ```
+pub struct SphereVisualizer {
+    events_loop: glutin::EventsLoop,
+    display: Display,
+    program: Program,
+    vertex_buffer: VertexBuffer<SphereVertex>,
+    uploader: TextureUploader,
+    mapping: ValueMapping,
+    sphere: SphereMapping,
+    // 1フレームごとに自転させる角度(rad)
+    spin: f32,
+    mouse: Mouse,
+    // ドラッグ中に最後にカーソルがあった位置
+    drag_from: Option<(f64, f64)>,
+    // 直前の`poll_events`の間に押されたキー
+    keys: Vec<VirtualKeyCode>,
+    // 最後に描画したMatrixの大きさ
+    drawn_dim: Option<(usize, usize)>,
+}
+
+impl SphereVisualizer {
+    /// 600×600のウィンドウを開く
+    pub fn new(title: &str) -> Result<SphereVisualizer, failure::Error> {
+        let events_loop = glutin::EventsLoop::new();
+        let window = glutin::WindowBuilder::new()
+            .with_dimensions((600, 600).into())
+            .with_title(title);
+        let context = glutin::ContextBuilder::new();
+        let display = Display::new(window, context, &events_loop).map_err(|e| format_err!("{}", e))?;
+        // 色はsRGBのまま書き込むので、gliumがGL_FRAMEBUFFER_SRGBを有効にしないようにする
+        let program = program!(&display, 140 => {
+            vertex: SPHERE_VERTEX_SHADER,
+            fragment: SPHERE_FRAGMENT_SHADER,
+            outputs_srgb: true
+        })?;
+        let vertex = |x: f32, y: f32| SphereVertex { a_position: [x, y] };
+        let shape = [
+            vertex(-1.0, -1.0),
+            vertex(1.0, -1.0),
+            vertex(1.0, 1.0),
+            vertex(-1.0, -1.0),
+            vertex(-1.0, 1.0),
+            vertex(1.0, 1.0),
+        ];
+        let vertex_buffer = VertexBuffer::new(&display, &shape)?;
+        Ok(SphereVisualizer {
+            events_loop,
+            display,
+            program,
+            vertex_buffer,
+            uploader: TextureUploader::new(TextureFormat::Rgba8),
+            mapping: ValueMapping::default(),
+            sphere: SphereMapping::default(),
+            spin: 0.0,
+            mouse: Mouse::default(),
+            drag_from: None,
+            keys: Vec::new(),
+            drawn_dim: None,
+        })
+    }
+
+    /// 正規化するときの(最小値, 最大値)を設定する。初期値は(0.0, 1.0)
+    pub fn set_value_range(&mut self, min: f32, max: f32) {
+        self.mapping.range = (min, max);
+    }
+
+    /// 正規化するときの変換を設定する
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.mapping.transform = transform;
+    }
+
+    /// 正規化した値を色に変換する方法を設定する
+    pub fn set_colormap(&mut self, colormap: Colormap) {
+        self.mapping.colormap = colormap;
+    }
+
+    /// 正面に来る経度(rad)を設定する
+    pub fn set_yaw(&mut self, yaw: f32) {
+        self.sphere.yaw = yaw;
+    }
+
+    /// 北極を手前に傾ける角度(rad)を設定する。-π/2〜π/2に収める
+    pub fn set_pitch(&mut self, pitch: f32) {
+        self.sphere.pitch = pitch.clamp(-FRAC_PI_2, FRAC_PI_2);
+    }
+
+    /// 1フレームごとに自転させる角度(rad)を設定する。0なら止まる。ドラッグしている間は自転しない
+    pub fn set_spin(&mut self, spin: f32) {
+        self.spin = spin;
+    }
+
+    /// 球の向き
+    pub fn sphere(&self) -> SphereMapping {
+        self.sphere
+    }
+
+    /// マウスの状態
+    pub fn mouse(&self) -> &Mouse {
+        &self.mouse
+    }
+
+    /// 直前の`poll_events`の間に押されたキー
+    pub fn pressed_keys(&self) -> &[VirtualKeyCode] {
+        &self.keys
+    }
+
+    /// マウスが指しているセル。球の外や、まだ描画していないときは`None`
+    pub fn mouse_cell(&self) -> Option<(usize, usize)> {
+        let (x, y) = self.mouse.position?;
+        let (width, height) = self.window_size()?;
+        let side = width.min(height);
+        let point = ((2.0 * x - width) / side, (height - 2.0 * y) / side);
+        self.sphere.cell_at((point.0 as f32, point.1 as f32), self.drawn_dim?)
+    }
+
+    fn window_size(&self) -> Option<(f64, f64)> {
+        let size = self.display.gl_window().get_inner_size()?;
+        Some((size.width, size.height))
+    }
+
+    // 左ボタンでのドラッグに合わせて球を回す。ドラッグしていなければ自転させる
+    fn rotate(&mut self) {
+        match (self.mouse.position, self.mouse.left) {
+            (Some(position), true) => {
+                if let (Some(from), Some((width, height))) = (self.drag_from, self.window_size()) {
+                    let side = width.min(height).max(1.0);
+                    let yaw = self.sphere.yaw - ((position.0 - from.0) / side * DRAG_SENSITIVITY) as f32;
+                    let pitch = self.sphere.pitch + ((position.1 - from.1) / side * DRAG_SENSITIVITY) as f32;
+                    self.set_yaw(yaw);
+                    self.set_pitch(pitch);
+                }
+                self.drag_from = Some(position);
+            }
+            _ => {
+                self.drag_from = None;
+                self.sphere.yaw += self.spin;
+            }
+        }
+    }
+
+    fn render(&self) -> Result<(), failure::Error> {
+        let texture = match self.uploader.texture() {
+            Some((GpuTexture::Linear(texture), _)) => texture,
+            _ => return Ok(()),
+        };
+        let mut target = self.display.draw();
+        let (width, height) = target.get_dimensions();
+        let side = width.min(height).max(1) as f32;
+        // 経度の方向は1周でつながり、緯度の方向は極で止まる
+        let behavior = SamplerBehavior {
+            wrap_function: (
+                SamplerWrapFunction::Repeat,
+                SamplerWrapFunction::Clamp,
+                SamplerWrapFunction::Clamp,
+            ),
+            minify_filter: MinifySamplerFilter::Linear,
+            magnify_filter: MagnifySamplerFilter::Linear,
+            ..Default::default()
+        };
+        let uniforms = uniform! {
+            u_texture: Sampler(texture, behavior),
+            u_yaw: self.sphere.yaw,
+            u_pitch: self.sphere.pitch,
+            u_scale: [width as f32 / side, height as f32 / side],
+        };
+        target.clear_color(0.0, 0.0, 0.0, 1.0);
+        let indices = index::NoIndices(index::PrimitiveType::TrianglesList);
+        let drawn = target.draw(
+            &self.vertex_buffer,
+            indices,
+            &self.program,
+            &uniforms,
+            &Default::default(),
+        );
+        target.finish()?;
+        Ok(drawn?)
+    }
+}
+
+impl Draw for SphereVisualizer {
+    fn set_title(&self, title: &str) {
+        self.display.gl_window().set_title(title);
+    }
+
+    fn draw<A: Copy + Into<f32>>(&mut self, matrix: &Matrix<A>) -> Result<(), failure::Error> {
+        self.drawn_dim = Some(matrix.dim());
+        self.uploader.upload(&self.display, matrix, &self.mapping)?;
+        self.render()
+    }
+
+    fn draw_states(&mut self, cells: &Matrix<u8>, states: u8) -> Result<(), failure::Error> {
+        let mapping = ValueMapping {
+            range: (0.0, f32::from(states.max(2) - 1)),
+            transform: Transform::Linear,
+            ..self.mapping
+        };
+        self.drawn_dim = Some(cells.dim());
+        self.uploader.upload(&self.display, cells, &mapping)?;
+        self.render()
+    }
+
+    fn poll_events(&mut self) -> ControlFlow {
+        let mut flow = ControlFlow::Continue;
+        {
+            let mouse = &mut self.mouse;
+            let keys = &mut self.keys;
+            keys.clear();
+            self.events_loop.poll_events(|event| {
+                if let glutin::Event::WindowEvent { event, .. } = event {
+                    match event {
+                        glutin::WindowEvent::CloseRequested => flow = ControlFlow::Stop,
+                        glutin::WindowEvent::CursorMoved {
+                            position, modifiers, ..
+                        } => {
+                            mouse.position = Some((position.x, position.y));
+                            mouse.shift = modifiers.shift;
+                        }
+                        glutin::WindowEvent::CursorLeft { .. } => mouse.position = None,
+                        glutin::WindowEvent::MouseInput { state, button, .. } => {
+                            let pressed = state == glutin::ElementState::Pressed;
+                            match button {
+                                glutin::MouseButton::Left => mouse.left = pressed,
+                                glutin::MouseButton::Right => mouse.right = pressed,
+                                _ => {}
+                            }
+                        }
+                        glutin::WindowEvent::KeyboardInput { input, .. } => {
+                            if let (Some(key), glutin::ElementState::Pressed) = (input.virtual_keycode, input.state) {
+                                keys.push(key);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            });
+        }
+        self.rotate();
+        flow
+    }
+}
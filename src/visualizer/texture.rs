@@ -0,0 +1,578 @@
+#[cfg(feature = "gl")]
+use algorithm::dirty_tiles::TileRect;
+#[cfg(feature = "gl")]
+use failure;
+#[cfg(feature = "gl")]
+use glium::backend::Facade;
+#[cfg(feature = "gl")]
+use glium::texture::{
+    ClientFormat, MipmapsOption, RawImage2d, SrgbFormat, SrgbTexture2d, Texture2d, Texture2dDataSource,
+    UncompressedFloatFormat,
+};
+#[cfg(feature = "gl")]
+use glium::Rect;
+#[cfg(feature = "gl")]
+use ndarray::ArrayView2;
+#[cfg(feature = "gl")]
+use std::borrow::Cow;
+#[cfg(feature = "gl")]
+use std::time::Duration;
+#[cfg(feature = "gl")]
+use visualizer::colormap;
+use visualizer::colormap::{Colormap, Transform};
+use visualizer::diagnostics;
+#[cfg(feature = "gl")]
+use visualizer::palette::Palette;
+#[cfg(feature = "gl")]
+use visualizer::profiler::Stopwatch;
+#[cfg(feature = "gl")]
+use visualizer::Matrix;
+
+/// Matrixをテクスチャに転送するときの形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFormat {
+    /// 1セルあたり4byte(RGBA)。NaN/Infのハイライトのように色を付けられる
+    Rgba8,
+    /// 1セルあたり1byte。転送量はRgba8の1/4で、色はシェーダー側で付ける
+    R8,
+    /// 1セルあたり4byte(float)。u8への量子化をせず、正規化や色付けをすべてシェーダー側で行う
+    R32F,
+    /// 1セルあたり4byte(RGBA)。Rgba8と同じ色をsRGBのテクスチャとして持ち、拡大・縮小の補間を線形な明るさで行う
+    Srgba8,
+}
+
+impl TextureFormat {
+    #[cfg(feature = "gl")]
+    fn bytes_per_cell(self) -> usize {
+        match self {
+            TextureFormat::Rgba8 | TextureFormat::Srgba8 => 4,
+            TextureFormat::R8 => 1,
+            TextureFormat::R32F => 4,
+        }
+    }
+
+    #[cfg(feature = "gl")]
+    fn client_format(self) -> ClientFormat {
+        match self {
+            TextureFormat::Rgba8 | TextureFormat::Srgba8 => ClientFormat::U8U8U8U8,
+            TextureFormat::R8 => ClientFormat::U8,
+            TextureFormat::R32F => ClientFormat::F32,
+        }
+    }
+
+    /// rチャンネルしか持たない形式かどうか
+    pub fn is_single_channel(self) -> bool {
+        self == TextureFormat::R8 || self == TextureFormat::R32F
+    }
+
+    /// sRGBのテクスチャかどうか。サンプリングするとsRGBから線形な値に戻される
+    pub fn is_srgb(self) -> bool {
+        self == TextureFormat::Srgba8
+    }
+}
+
+/// 正規化した値が何を表しているか
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSpace {
+    /// 画面に出す値そのもの(sRGB)。0.5は見た目で中間の灰色になる
+    #[default]
+    Srgb,
+    /// 線形な明るさ。sRGBに変換してから色を付けるので、0.5は白の半分の明るさになる
+    Linear,
+}
+
+/// 線形な明るさ(0.0〜1.0)をsRGBの値にする
+///
+/// # Example
+/// ```
+/// use my_alife::visualizer::texture::linear_to_srgb;
+///
+/// assert_eq!(linear_to_srgb(0.0), 0.0);
+/// assert!((linear_to_srgb(0.5) - 0.735).abs() < 1e-3);
+/// assert!((linear_to_srgb(1.0) - 1.0).abs() < 1e-6);
+/// ```
+pub fn linear_to_srgb(x: f32) -> f32 {
+    if x <= 0.003_130_8 {
+        x * 12.92
+    } else {
+        1.055 * x.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// 表示するときだけ正規化した値にかける補正。シミュレーションの値は変えない
+///
+/// 露出、コントラスト、ガンマの順にかける。拡散のゆるやかな勾配のように、
+/// 値の差が小さくて見えにくい場を見やすくするために使う
+///
+/// # Example
+/// ```
+/// use my_alife::visualizer::texture::Adjustment;
+///
+/// let close = |a: f32, b: f32| (a - b).abs() < 1e-6;
+/// assert!(close(Adjustment::default().apply(0.3), 0.3));
+/// // 1段明るくすると2倍になる
+/// let brighter = Adjustment { exposure: 1.0, ..Adjustment::default() };
+/// assert!(close(brighter.apply(0.3), 0.6));
+/// // コントラストを上げると0.5から離れる
+/// let contrast = Adjustment { contrast: 2.0, ..Adjustment::default() };
+/// assert!(close(contrast.apply(0.6), 0.7));
+/// let gamma = Adjustment { gamma: 2.0, ..Adjustment::default() };
+/// assert!(close(gamma.apply(0.25), 0.5));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Adjustment {
+    /// 露出(段)。1増やすと2倍明るくなる
+    pub exposure: f32,
+    /// コントラスト。0.5を中心に値の差を何倍にするか
+    pub contrast: f32,
+    /// ガンマ。1より大きいと暗い部分が明るくなる
+    pub gamma: f32,
+}
+
+impl Default for Adjustment {
+    fn default() -> Adjustment {
+        Adjustment {
+            exposure: 0.0,
+            contrast: 1.0,
+            gamma: 1.0,
+        }
+    }
+}
+
+impl Adjustment {
+    /// 0.0〜1.0に正規化した値を補正する
+    pub fn apply(&self, x: f32) -> f32 {
+        let x = x * self.exposure.exp2();
+        let x = ((x - 0.5) * self.contrast + 0.5).clamp(0.0, 1.0);
+        x.powf(1.0 / self.gamma)
+    }
+}
+
+/// 値を色に変換するときの設定
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValueMapping {
+    /// 0.0〜1.0に正規化するときの(最小値, 最大値)
+    pub range: (f32, f32),
+    /// 0.0〜1.0に正規化するときの変換
+    pub transform: Transform,
+    /// 正規化した値を色に変換する方法
+    pub colormap: Colormap,
+    /// NaN/Infを`diagnostics::WARNING_COLOR`で表示するかどうか
+    pub highlight_non_finite: bool,
+    /// 正規化した値の色空間
+    pub color_space: ColorSpace,
+    /// 表示するときの補正
+    pub adjustment: Adjustment,
+}
+
+impl Default for ValueMapping {
+    fn default() -> ValueMapping {
+        ValueMapping {
+            range: (0.0, 1.0),
+            transform: Transform::Linear,
+            colormap: Colormap::Grayscale,
+            highlight_non_finite: false,
+            color_space: ColorSpace::Srgb,
+            adjustment: Adjustment::default(),
+        }
+    }
+}
+
+impl ValueMapping {
+    /// 値を`transform`で0.0〜1.0に変換する
+    pub(crate) fn normalize(&self, value: f32) -> f32 {
+        self.transform.apply(value, self.range)
+    }
+
+    /// 値をRGBAに変換する
+    pub(crate) fn rgba(&self, value: f32) -> [u8; 4] {
+        if self.highlight_non_finite && !value.is_finite() {
+            return diagnostics::WARNING_COLOR;
+        }
+        let x = self.adjustment.apply(self.normalize(value));
+        let x = match self.color_space {
+            ColorSpace::Srgb => x,
+            ColorSpace::Linear => linear_to_srgb(x),
+        };
+        let [r, g, b] = self.colormap.color(x);
+        [r, g, b, 255]
+    }
+}
+
+/// 転送先のテクスチャ。sRGBのテクスチャはgliumでは別の型になる
+#[cfg(feature = "gl")]
+pub(crate) enum GpuTexture {
+    Linear(Texture2d),
+    Srgb(SrgbTexture2d),
+}
+
+#[cfg(feature = "gl")]
+impl GpuTexture {
+    fn dimensions(&self) -> (u32, u32) {
+        match *self {
+            GpuTexture::Linear(ref texture) => texture.dimensions(),
+            GpuTexture::Srgb(ref texture) => texture.dimensions(),
+        }
+    }
+
+    fn write<'a, T: Texture2dDataSource<'a>>(&self, rect: Rect, data: T) {
+        match *self {
+            GpuTexture::Linear(ref texture) => texture.write(rect, data),
+            GpuTexture::Srgb(ref texture) => texture.write(rect, data),
+        }
+    }
+}
+
+/// 毎フレームの転送で使うバッファとテクスチャを使い回し、フレームごとのメモリ確保をなくす
+#[cfg(feature = "gl")]
+pub(crate) struct TextureUploader {
+    format: TextureFormat,
+    buffer: Vec<u8>,
+    float_buffer: Vec<f32>,
+    texture: Option<(GpuTexture, TextureFormat)>,
+    // `upload_states`で書き込んだときの状態の数
+    states: Option<u8>,
+    // `take_timings`を呼んでから変換・転送にかかった時間
+    timings: (Duration, Duration),
+}
+
+#[cfg(feature = "gl")]
+impl TextureUploader {
+    pub(crate) fn new(format: TextureFormat) -> TextureUploader {
+        TextureUploader {
+            format,
+            buffer: Vec::new(),
+            float_buffer: Vec::new(),
+            texture: None,
+            states: None,
+            timings: (Duration::from_secs(0), Duration::from_secs(0)),
+        }
+    }
+
+    pub(crate) fn set_format(&mut self, format: TextureFormat) {
+        self.format = format;
+    }
+
+    /// `matrix`をテクスチャに書き込む  
+    /// 8bitの形式では正規化をCPU側で行い、Rgba8では色付けもCPU側で行う。
+    /// R8ではNaN/Infを表す値がないので、ハイライトするときはRgba8を使う
+    pub(crate) fn upload<F, A>(
+        &mut self,
+        facade: &F,
+        matrix: &Matrix<A>,
+        mapping: &ValueMapping,
+    ) -> Result<(), failure::Error>
+    where
+        F: Facade,
+        A: Copy + Into<f32>,
+    {
+        let format = self.actual_format(mapping);
+        let (height, width) = matrix.dim();
+        self.ensure_texture(facade, format, width as u32, height as u32)?;
+        self.write_region(matrix.view(), format, mapping, 0, 0);
+        Ok(())
+    }
+
+    /// `regions`の範囲だけをテクスチャに書き込む  
+    /// テクスチャがまだないか、大きさや形式が変わったときは全体を書き込む
+    pub(crate) fn upload_regions<F, A, I>(
+        &mut self,
+        facade: &F,
+        matrix: &Matrix<A>,
+        mapping: &ValueMapping,
+        regions: I,
+    ) -> Result<(), failure::Error>
+    where
+        F: Facade,
+        A: Copy + Into<f32>,
+        I: IntoIterator<Item = TileRect>,
+    {
+        let format = self.actual_format(mapping);
+        let (height, width) = matrix.dim();
+        if !self.is_reusable(format, width as u32, height as u32) {
+            return self.upload(facade, matrix, mapping);
+        }
+        for rect in regions {
+            let region = matrix.slice(s![rect.row..rect.row + rect.rows, rect.col..rect.col + rect.cols]);
+            self.write_region(region, format, mapping, rect.row, rect.col);
+        }
+        Ok(())
+    }
+
+    /// 0〜`states - 1`の状態を持つ1セル1byteの盤面を、変換せずにそのままR8のテクスチャに書き込む  
+    /// 正規化はシェーダー側で行う。`cells`のメモリが連続していればコピーもしない
+    pub(crate) fn upload_states<F: Facade>(
+        &mut self,
+        facade: &F,
+        cells: &Matrix<u8>,
+        states: u8,
+    ) -> Result<(), failure::Error> {
+        let (height, width) = cells.dim();
+        let format = TextureFormat::R8;
+        self.ensure_texture(facade, format, width as u32, height as u32)?;
+        let data = match cells.as_slice() {
+            Some(data) => Cow::Borrowed(data),
+            None => {
+                self.buffer.clear();
+                self.buffer.extend(cells.iter().cloned());
+                Cow::Borrowed(&self.buffer[..])
+            }
+        };
+        let stopwatch = Stopwatch::start();
+        let texture = &self.texture.as_ref().unwrap().0;
+        texture.write(
+            Rect {
+                left: 0,
+                bottom: 0,
+                width: width as u32,
+                height: height as u32,
+            },
+            RawImage2d {
+                data,
+                width: width as u32,
+                height: height as u32,
+                format: format.client_format(),
+            },
+        );
+        self.timings.1 += stopwatch.elapsed();
+        self.states = Some(states.max(2));
+        Ok(())
+    }
+
+    /// 各セルの状態を`palette`の色にして、Rgba8のテクスチャに書き込む
+    pub(crate) fn upload_palette<F: Facade>(
+        &mut self,
+        facade: &F,
+        cells: &Matrix<u8>,
+        palette: &Palette,
+    ) -> Result<(), failure::Error> {
+        let (height, width) = cells.dim();
+        let format = TextureFormat::Rgba8;
+        self.ensure_texture(facade, format, width as u32, height as u32)?;
+        self.states = None;
+        let stopwatch = Stopwatch::start();
+        palette.write_rgba(cells, &mut self.buffer);
+        self.timings.0 += stopwatch.elapsed();
+        let stopwatch = Stopwatch::start();
+        let texture = &self.texture.as_ref().unwrap().0;
+        texture.write(
+            Rect {
+                left: 0,
+                bottom: 0,
+                width: width as u32,
+                height: height as u32,
+            },
+            RawImage2d {
+                data: Cow::Borrowed(&self.buffer[..]),
+                width: width as u32,
+                height: height as u32,
+                format: format.client_format(),
+            },
+        );
+        self.timings.1 += stopwatch.elapsed();
+        Ok(())
+    }
+
+    /// `upload_states`で最後に書き込んだときの状態の数
+    pub(crate) fn states(&self) -> Option<u8> {
+        self.states
+    }
+
+    /// 前に呼んでから(変換, 転送)にかかった時間。`profiling`フィーチャーが無効なときは0
+    pub(crate) fn take_timings(&mut self) -> (Duration, Duration) {
+        let zero = Duration::from_secs(0);
+        ::std::mem::replace(&mut self.timings, (zero, zero))
+    }
+
+    /// 最大3つの`channels`をそれぞれ`ranges`で正規化して、赤・緑・青としてRgba8のテクスチャに書き込む  
+    /// 足りないチャンネルは0になる。すべてのチャンネルは同じ大きさであること
+    pub(crate) fn upload_channels<F: Facade>(
+        &mut self,
+        facade: &F,
+        channels: &[&Matrix<f32>],
+        ranges: &[(f32, f32)],
+    ) -> Result<(), failure::Error> {
+        let (height, width) = match channels.first() {
+            Some(channel) => channel.dim(),
+            None => return Ok(()),
+        };
+        if let Some(channel) = channels.iter().find(|channel| channel.dim() != (height, width)) {
+            return Err(format_err!(
+                "channel size mismatch: {:?} and {:?}",
+                (height, width),
+                channel.dim()
+            ));
+        }
+        let format = TextureFormat::Rgba8;
+        self.ensure_texture(facade, format, width as u32, height as u32)?;
+        self.states = None;
+        let stopwatch = Stopwatch::start();
+        self.buffer.clear();
+        self.buffer.resize(width * height * format.bytes_per_cell(), 0);
+        for (i, channel) in channels.iter().take(3).enumerate() {
+            let range = ranges.get(i).cloned().unwrap_or((0.0, 1.0));
+            for (rgba, &e) in self.buffer.chunks_mut(4).zip(channel.iter()) {
+                rgba[i] = (colormap::normalize(e, range) * 255.0) as u8;
+            }
+        }
+        for rgba in self.buffer.chunks_mut(4) {
+            rgba[3] = 255;
+        }
+        self.timings.0 += stopwatch.elapsed();
+        let stopwatch = Stopwatch::start();
+        let texture = &self.texture.as_ref().unwrap().0;
+        let rect = Rect {
+            left: 0,
+            bottom: 0,
+            width: width as u32,
+            height: height as u32,
+        };
+        texture.write(
+            rect,
+            RawImage2d {
+                data: Cow::Borrowed(&self.buffer[..]),
+                width: width as u32,
+                height: height as u32,
+                format: format.client_format(),
+            },
+        );
+        self.timings.1 += stopwatch.elapsed();
+        Ok(())
+    }
+
+    /// 最後に書き込んだテクスチャとその形式
+    pub(crate) fn texture(&self) -> Option<(&GpuTexture, TextureFormat)> {
+        self.texture.as_ref().map(|(texture, format)| (texture, *format))
+    }
+
+    fn actual_format(&self, mapping: &ValueMapping) -> TextureFormat {
+        if mapping.highlight_non_finite && self.format == TextureFormat::R8 {
+            TextureFormat::Rgba8
+        } else {
+            self.format
+        }
+    }
+
+    fn is_reusable(&self, format: TextureFormat, width: u32, height: u32) -> bool {
+        match self.texture {
+            Some((ref texture, f)) => f == format && texture.dimensions() == (width, height),
+            None => false,
+        }
+    }
+
+    fn ensure_texture<F: Facade>(
+        &mut self,
+        facade: &F,
+        format: TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> Result<(), failure::Error> {
+        if !self.is_reusable(format, width, height) {
+            let mipmaps = MipmapsOption::NoMipmap;
+            let texture = match format {
+                TextureFormat::Rgba8 => GpuTexture::Linear(Texture2d::empty_with_format(
+                    facade,
+                    UncompressedFloatFormat::U8U8U8U8,
+                    mipmaps,
+                    width,
+                    height,
+                )?),
+                TextureFormat::R8 => GpuTexture::Linear(Texture2d::empty_with_format(
+                    facade,
+                    UncompressedFloatFormat::U8,
+                    mipmaps,
+                    width,
+                    height,
+                )?),
+                TextureFormat::R32F => GpuTexture::Linear(Texture2d::empty_with_format(
+                    facade,
+                    UncompressedFloatFormat::F32,
+                    mipmaps,
+                    width,
+                    height,
+                )?),
+                TextureFormat::Srgba8 => GpuTexture::Srgb(SrgbTexture2d::empty_with_format(
+                    facade,
+                    SrgbFormat::U8U8U8U8,
+                    mipmaps,
+                    width,
+                    height,
+                )?),
+            };
+            self.texture = Some((texture, format));
+        }
+        Ok(())
+    }
+
+    // `region`を変換して、テクスチャの(row, col)を左下とする範囲に書き込む
+    fn write_region<A>(
+        &mut self,
+        region: ArrayView2<A>,
+        format: TextureFormat,
+        mapping: &ValueMapping,
+        row: usize,
+        col: usize,
+    ) where
+        A: Copy + Into<f32>,
+    {
+        self.states = None;
+        let (height, width) = region.dim();
+        let cells = width * height;
+        let stopwatch = Stopwatch::start();
+        match format {
+            TextureFormat::Rgba8 | TextureFormat::Srgba8 => {
+                self.buffer.clear();
+                self.buffer.reserve(cells * format.bytes_per_cell());
+                for e in region.iter() {
+                    self.buffer.extend_from_slice(&mapping.rgba((*e).into()));
+                }
+            }
+            TextureFormat::R8 => {
+                self.buffer.clear();
+                self.buffer.reserve(cells);
+                self.buffer.extend(
+                    region
+                        .iter()
+                        .map(|e| (mapping.normalize((*e).into()) * 255.0) as u8),
+                );
+            }
+            TextureFormat::R32F => {
+                self.float_buffer.clear();
+                self.float_buffer.extend(region.iter().map(|e| (*e).into()));
+            }
+        }
+        self.timings.0 += stopwatch.elapsed();
+
+        let stopwatch = Stopwatch::start();
+        let texture = &self.texture.as_ref().unwrap().0;
+        let (width, height) = (width as u32, height as u32);
+        let rect = Rect {
+            left: col as u32,
+            bottom: row as u32,
+            width,
+            height,
+        };
+        if format == TextureFormat::R32F {
+            texture.write(
+                rect,
+                RawImage2d {
+                    data: Cow::Borrowed(&self.float_buffer[..]),
+                    width,
+                    height,
+                    format: format.client_format(),
+                },
+            );
+        } else {
+            texture.write(
+                rect,
+                RawImage2d {
+                    data: Cow::Borrowed(&self.buffer[..]),
+                    width,
+                    height,
+                    format: format.client_format(),
+                },
+            );
+        }
+        self.timings.1 += stopwatch.elapsed();
+    }
+}
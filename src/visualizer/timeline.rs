@@ -0,0 +1,220 @@
+use algorithm::memory::{History, MemoryUsage};
+#[cfg(feature = "gl")]
+use visualizer::mouse::Mouse;
+use visualizer::overlay::{text_size, Canvas};
+#[cfg(feature = "gl")]
+use visualizer::VirtualKeyCode;
+
+/// タイムラインのバーの高さ(画素)
+pub const TIMELINE_HEIGHT: usize = 28;
+
+// バーの色
+const BACKGROUND: [u8; 4] = [0, 0, 0, 170];
+const RECORDED: [u8; 4] = [90, 90, 90, 255];
+const HANDLE: [u8; 4] = [255, 220, 0, 255];
+const TEXT: [u8; 4] = [255, 255, 255, 255];
+// 左右の余白(画素)
+const PADDING: usize = 8;
+
+/// `Timeline::handle_input`で起きたこと
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelineAction {
+    /// 何もしていない
+    Idle,
+    /// 過去の状態を表示する位置を変えた
+    Scrub,
+    /// 表示している過去の状態から新しく始めるように指示された
+    Branch,
+    /// 最新の状態に戻って続きを動かすように指示された
+    Resume,
+}
+
+/// 記録した過去の状態をたどって表示し、好きな時点から分岐して動かし直すためのタイムライン
+///
+/// 状態は`memory::History`に覚えるので、使用量が上限を超えると古いものから捨てる。
+/// ウィンドウでは下端のバーをドラッグするか`,`/`.`キーで1つずつたどり、`Enter`キーで分岐、`End`キーで最新の状態に戻る
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::visualizer::timeline::Timeline;
+///
+/// let mut timeline = Timeline::new(1 << 20);
+/// let mut state = Array2::<f32>::zeros((16, 16));
+/// for step in 0..10 {
+///     state += 1.0;
+///     timeline.record(step, &state);
+/// }
+/// assert_eq!(timeline.len(), 10);
+/// assert!(!timeline.is_scrubbing());
+///
+/// // 4ステップ目に戻って、そこから分岐する
+/// timeline.scrub_to(4);
+/// assert_eq!(timeline.current().unwrap().0, 4);
+/// let (step, mut state) = timeline.branch().unwrap();
+/// assert_eq!((step, state[[0, 0]]), (4, 5.0));
+/// assert_eq!(timeline.len(), 5);
+///
+/// // 分岐した後は、続きとして記録される
+/// state[[8, 8]] = -1.0;
+/// timeline.record(step + 1, &state);
+/// assert_eq!(timeline.latest().unwrap().0, 5);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Timeline<T> {
+    history: History<(u64, T)>,
+    // 表示している過去の状態の位置。最新の状態を動かしているときは`None`
+    cursor: Option<usize>,
+}
+
+impl<T: MemoryUsage + Clone> Timeline<T> {
+    /// 使用量の上限`cap`(byte)まで状態を覚えるタイムライン
+    pub fn new(cap: usize) -> Timeline<T> {
+        Timeline {
+            history: History::new(cap),
+            cursor: None,
+        }
+    }
+
+    /// `step`の状態を記録する。過去の状態を表示している間は記録しない
+    pub fn record(&mut self, step: u64, state: &T) {
+        if self.cursor.is_none() {
+            self.history.push((step, state.clone()));
+        }
+    }
+
+    /// 覚えている状態の数
+    pub fn len(&self) -> usize {
+        self.history.len()
+    }
+
+    /// 何も覚えていないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.history.is_empty()
+    }
+
+    /// 過去の状態を表示しているかどうか。このときはシミュレーションを止めておく
+    pub fn is_scrubbing(&self) -> bool {
+        self.cursor.is_some()
+    }
+
+    /// 表示している過去の状態の位置(古い方から何番目か)
+    pub fn cursor(&self) -> Option<usize> {
+        self.cursor
+    }
+
+    /// 古い方から`index`番目の状態を表示する。範囲の外なら一番近い状態になる
+    pub fn scrub_to(&mut self, index: usize) {
+        if !self.history.is_empty() {
+            self.cursor = Some(index.min(self.history.len() - 1));
+        }
+    }
+
+    /// 表示している位置を`delta`だけずらす。最新の状態を動かしているときは最新の状態から数える
+    pub fn scrub_by(&mut self, delta: isize) {
+        let last = self.history.len() as isize - 1;
+        if last < 0 {
+            return;
+        }
+        let current = self.cursor.map_or(last, |cursor| cursor as isize);
+        self.cursor = Some((current + delta).clamp(0, last) as usize);
+    }
+
+    /// 最新の状態に戻る
+    pub fn resume(&mut self) {
+        self.cursor = None;
+    }
+
+    /// 表示している(ステップ, 状態)。最新の状態を動かしているときは`None`
+    pub fn current(&self) -> Option<&(u64, T)> {
+        self.history.get(self.cursor?)
+    }
+
+    /// 最後に記録した(ステップ, 状態)
+    pub fn latest(&self) -> Option<&(u64, T)> {
+        self.history.latest()
+    }
+
+    /// 表示している状態より新しい記録を捨てて最新の状態に戻り、表示していた(ステップ, 状態)を返す
+    /// 返した状態に手を加えて動かし直せば、「ここで摂動を加えたら」を試せる
+    pub fn branch(&mut self) -> Option<(u64, T)> {
+        let cursor = self.cursor.take()?;
+        self.history.truncate(cursor + 1);
+        self.history.latest().cloned()
+    }
+
+    /// ウィンドウの下端のバーのドラッグと、キーの入力でたどる位置を変える
+    ///
+    /// # Arguments
+    /// * `mouse` - `MatrixVisualizer::mouse`
+    /// * `keys` - `MatrixVisualizer::pressed_keys`
+    /// * `window` - ウィンドウの(幅, 高さ)
+    #[cfg(feature = "gl")]
+    pub fn handle_input(&mut self, mouse: &Mouse, keys: &[VirtualKeyCode], window: (f64, f64)) -> TimelineAction {
+        let mut action = TimelineAction::Idle;
+        for key in keys {
+            match *key {
+                VirtualKeyCode::Comma => {
+                    self.scrub_by(-1);
+                    action = TimelineAction::Scrub;
+                }
+                VirtualKeyCode::Period => {
+                    self.scrub_by(1);
+                    action = TimelineAction::Scrub;
+                }
+                VirtualKeyCode::End => {
+                    self.resume();
+                    action = TimelineAction::Resume;
+                }
+                VirtualKeyCode::Return if self.is_scrubbing() => return TimelineAction::Branch,
+                _ => {}
+            }
+        }
+        if let (Some((x, y)), true) = (mouse.position, mouse.left) {
+            if y >= window.1 - TIMELINE_HEIGHT as f64 && y < window.1 {
+                if let Some(index) = self.index_at(x, window.0) {
+                    self.scrub_to(index);
+                    action = TimelineAction::Scrub;
+                }
+            }
+        }
+        action
+    }
+
+    // 幅`width`のバーでx座標`x`が指す状態の位置
+    #[cfg(feature = "gl")]
+    fn index_at(&self, x: f64, width: f64) -> Option<usize> {
+        if self.history.is_empty() {
+            return None;
+        }
+        let track = (width - 2.0 * PADDING as f64).max(1.0);
+        let t = ((x - PADDING as f64) / track).clamp(0.0, 1.0);
+        Some((t * (self.history.len() - 1) as f64).round() as usize)
+    }
+
+    /// 幅`width`画素のバーの画像。`MatrixVisualizer::set_footer`でウィンドウの下端に重ねて描く
+    pub fn render(&self, width: usize) -> Canvas {
+        let mut canvas = Canvas::new(width, TIMELINE_HEIGHT);
+        canvas.fill_rect(0, 0, width, TIMELINE_HEIGHT, BACKGROUND);
+        let track = width.saturating_sub(2 * PADDING);
+        canvas.fill_rect(PADDING, TIMELINE_HEIGHT - 8, track, 4, RECORDED);
+        let len = self.history.len();
+        if len == 0 {
+            return canvas;
+        }
+        let index = self.cursor.unwrap_or(len - 1);
+        let x = PADDING + track.saturating_sub(1) * index / (len - 1).max(1);
+        canvas.fill_rect(x.saturating_sub(2), TIMELINE_HEIGHT - 12, 5, 12, HANDLE);
+        let step = self.history.get(index).map_or(0, |&(step, _)| step);
+        let label = match self.cursor {
+            Some(_) => format!("step {} ({}/{})", step, index + 1, len),
+            None => format!("step {} live", step),
+        };
+        let (_, height) = text_size(&label, 1);
+        canvas.draw_text(PADDING, (TIMELINE_HEIGHT - 12 - height) / 2, &label, TEXT, 1);
+        canvas
+    }
+}
@@ -0,0 +1,268 @@
+use algorithm::memory::MemoryUsage;
+use std::collections::VecDeque;
+use std::mem;
+use visualizer::Matrix;
+
+// 1つのセルの変化
+#[derive(Debug, Clone, PartialEq)]
+struct CellChange<A> {
+    field: usize,
+    index: (usize, usize),
+    before: A,
+    after: A,
+}
+
+// 1回の操作で変わったセルとパラメータ
+#[derive(Debug, Clone, PartialEq)]
+struct Edit<A> {
+    label: String,
+    cells: Vec<CellChange<A>>,
+    parameters: Vec<(String, f32, f32)>,
+}
+
+impl<A> Edit<A> {
+    fn cost(&self) -> usize {
+        self.cells.len() + self.parameters.len()
+    }
+}
+
+/// ブラシやスタンプ、パラメータの変更を取り消す・やり直すための履歴
+///
+/// 盤面全体ではなく変化したセルだけを覚える。覚えている変化の数が`budget`を超えると古い操作から忘れる
+///
+/// # Example
+/// ```
+/// extern crate ndarray;
+/// extern crate my_alife;
+///
+/// use ndarray::Array2;
+/// use my_alife::visualizer::undo::UndoStack;
+///
+/// let mut fields = vec![Array2::<u8>::zeros((8, 8))];
+/// let mut history = UndoStack::new(1000);
+/// // ドラッグ中の2フレーム分の変化をまとめる
+/// history.begin();
+/// for col in 3..5 {
+///     let before = fields.clone();
+///     fields[0][[3, col]] = 1;
+///     history.record("draw", &before, &fields);
+/// }
+/// assert!(history.end("draw"));
+/// history.record_parameter("f", 0.04, 0.05);
+///
+/// assert_eq!(history.undo(&mut fields), Some(vec![("f".to_string(), 0.04)]));
+/// assert_eq!(history.undo(&mut fields), Some(vec![]));
+/// assert_eq!(fields[0].iter().filter(|&&e| e == 1).count(), 0);
+/// assert_eq!(history.undo(&mut fields), None);
+/// history.redo(&mut fields);
+/// assert_eq!(fields[0][[3, 4]], 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct UndoStack<A> {
+    undo: VecDeque<Edit<A>>,
+    redo: Vec<Edit<A>>,
+    budget: usize,
+    group: Option<Edit<A>>,
+}
+
+impl<A: Copy + PartialEq> UndoStack<A> {
+    /// # Arguments
+    /// * `budget` - 覚えておくセルとパラメータの変化の数の上限
+    pub fn new(budget: usize) -> UndoStack<A> {
+        UndoStack {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            budget,
+            group: None,
+        }
+    }
+
+    /// 操作のまとまりを始める。`end`までに`record`した変化は1つの操作として取り消される。
+    /// まとまりの途中で呼んでも何もしないので、ドラッグ中は毎フレーム呼んでよい
+    pub fn begin(&mut self) {
+        if self.group.is_none() {
+            self.group = Some(Edit {
+                label: String::new(),
+                cells: Vec::new(),
+                parameters: Vec::new(),
+            });
+        }
+    }
+
+    /// `begin`から`end`までの途中かどうか
+    pub fn is_grouping(&self) -> bool {
+        self.group.is_some()
+    }
+
+    /// 操作のまとまりを`label`という名前で閉じる。まとまりの途中でないか何も変わっていなければfalseを返す
+    pub fn end(&mut self, label: &str) -> bool {
+        match self.group.take() {
+            Some(mut edit) if edit.cost() > 0 => {
+                edit.label = label.to_string();
+                self.push(edit);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// `before`から`after`への変化を記録する。何も変わっていなければfalseを返す
+    ///
+    /// # Panics
+    /// `before`と`after`の数や大きさが異なるとき
+    pub fn record(&mut self, label: &str, before: &[Matrix<A>], after: &[Matrix<A>]) -> bool {
+        assert_eq!(before.len(), after.len(), "field count mismatch");
+        let mut cells = Vec::new();
+        for (field, (before, after)) in before.iter().zip(after).enumerate() {
+            assert_eq!(before.dim(), after.dim(), "field {} changed its shape", field);
+            for ((index, &b), &a) in before.indexed_iter().zip(after.iter()) {
+                if a != b {
+                    cells.push(CellChange {
+                        field,
+                        index,
+                        before: b,
+                        after: a,
+                    });
+                }
+            }
+        }
+        if cells.is_empty() {
+            return false;
+        }
+        self.push(Edit {
+            label: label.to_string(),
+            cells,
+            parameters: Vec::new(),
+        });
+        true
+    }
+
+    /// パラメータ`name`を`before`から`after`に変えたことを記録する
+    pub fn record_parameter(&mut self, name: &str, before: f32, after: f32) {
+        if before == after {
+            return;
+        }
+        self.push(Edit {
+            label: name.to_string(),
+            cells: Vec::new(),
+            parameters: vec![(name.to_string(), before, after)],
+        });
+    }
+
+    fn push(&mut self, mut edit: Edit<A>) {
+        if let Some(ref mut group) = self.group {
+            group.cells.append(&mut edit.cells);
+            group.parameters.append(&mut edit.parameters);
+            return;
+        }
+        self.redo.clear();
+        if edit.cost() > self.budget {
+            // 取り消せない操作より前には戻れない
+            self.undo.clear();
+            return;
+        }
+        self.undo.push_back(edit);
+        while self.used() > self.budget {
+            self.undo.pop_front();
+        }
+    }
+
+    /// 最後の操作を取り消す。戻すべきパラメータの(名前, 値)を返し、取り消す操作がなければ`None`
+    pub fn undo(&mut self, fields: &mut [Matrix<A>]) -> Option<Vec<(String, f32)>> {
+        self.group = None;
+        let edit = self.undo.pop_back()?;
+        for change in edit.cells.iter().rev() {
+            fields[change.field][change.index] = change.before;
+        }
+        let parameters = edit
+            .parameters
+            .iter()
+            .map(|&(ref name, before, _)| (name.clone(), before))
+            .collect();
+        self.redo.push(edit);
+        Some(parameters)
+    }
+
+    /// 最後に取り消した操作をやり直す。設定すべきパラメータの(名前, 値)を返し、やり直す操作がなければ`None`
+    pub fn redo(&mut self, fields: &mut [Matrix<A>]) -> Option<Vec<(String, f32)>> {
+        self.group = None;
+        let edit = self.redo.pop()?;
+        for change in &edit.cells {
+            fields[change.field][change.index] = change.after;
+        }
+        let parameters = edit
+            .parameters
+            .iter()
+            .map(|&(ref name, _, after)| (name.clone(), after))
+            .collect();
+        self.undo.push_back(edit);
+        Some(parameters)
+    }
+
+    /// 次に取り消される操作の名前
+    pub fn undo_label(&self) -> Option<&str> {
+        self.undo.back().map(|edit| edit.label.as_str())
+    }
+
+    /// 次にやり直される操作の名前
+    pub fn redo_label(&self) -> Option<&str> {
+        self.redo.last().map(|edit| edit.label.as_str())
+    }
+
+    /// 取り消せる操作の数
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// やり直せる操作の数
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    /// 取り消し用に覚えている変化の数。`budget`を超えることはない
+    ///
+    /// # Example
+    /// ```
+    /// extern crate ndarray;
+    /// extern crate my_alife;
+    ///
+    /// use ndarray::Array2;
+    /// use my_alife::visualizer::undo::UndoStack;
+    ///
+    /// let blank = vec![Array2::<u8>::zeros((4, 4))];
+    /// let full = vec![Array2::<u8>::ones((4, 4))];
+    /// let mut history = UndoStack::new(20);
+    /// history.record_parameter("k", 0.06, 0.065);
+    /// history.record("fill", &blank, &full);
+    /// history.record("clear", &full, &blank);
+    /// // 16セルの操作を2つは覚えられないので古いものから忘れる
+    /// assert_eq!(history.used(), 16);
+    /// assert_eq!(history.undo_label(), Some("clear"));
+    /// ```
+    pub fn used(&self) -> usize {
+        self.undo.iter().map(Edit::cost).sum()
+    }
+
+    /// 履歴をすべて捨てる
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.group = None;
+    }
+}
+
+impl<A> MemoryUsage for UndoStack<A> {
+    fn memory_usage(&self) -> usize {
+        self.undo
+            .iter()
+            .chain(self.redo.iter())
+            .chain(self.group.iter())
+            .map(|edit| {
+                edit.cells.len() * mem::size_of::<CellChange<A>>()
+                    + edit.parameters.iter().map(|(name, _, _)| name.len()).sum::<usize>()
+                    + edit.parameters.len() * mem::size_of::<(String, f32, f32)>()
+                    + edit.label.len()
+            })
+            .sum()
+    }
+}